@@ -22,7 +22,7 @@ bitflags! {
     pub struct AnimationPackFlags: u32 {
         const Pause   = 1 << 0;
         const Flag1   = 1 << 1;
-        const Flag2   = 1 << 2;
+        const HasNeck = 1 << 2;
         const Flag3   = 1 << 3;
         const Flag4   = 1 << 4;
         const Flag5   = 1 << 5;
@@ -69,7 +69,7 @@ where A: Allocator + Clone
     base: Option<NonNull<ItemArray<NonNull<Animation<A>>>>>,
     add: Option<NonNull<ItemArray<NonNull<Animation<A>>>>>,
     add2: Option<NonNull<ItemArray<NonNull<Animation<A>>>>>,
-    neck: Option<NonNull<AnimationNeck>>,
+    neck: Option<NonNull<AnimationNeck<A>>>,
     biped_ik: *mut BipedIK,
     bounding_box: BoundingBox,
     field_2d8: usize,
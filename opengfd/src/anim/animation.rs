@@ -1,27 +1,56 @@
 use allocator_api2::alloc::Allocator;
+use allocator_api2::boxed::Box as ABox;
+use allocator_api2::vec::Vec as AVec;
 use bitflags::bitflags;
 use crate::{
-    anim::key::{ 
-        AnimKey, KeyNode, KeyMaterial,
-        KeyShape, KeyCamera, KeyLight,
-        KeyList
+    anim::{
+        anim_controller::AnimationPackFlags,
+        key::{
+            AnimKey, KeyNode, KeyMaterial,
+            KeyShape, KeyCamera, KeyLight,
+            KeyList
+        }
+    },
+    kernel::{
+        allocator::GfdAllocator,
+        version::GfdVersion
     },
-    kernel::allocator::GfdAllocator,
     object::epl::EPL,
     utility::{
         item_array::ItemArray,
         misc::BoundingBox,
-        name::Name,
+        name::{ Name, NameSerializationContext, NameSerializationHashGAP, NameSerializationTechnique },
         property::Property,
-        reference::Reference
+        reference::Reference,
+        stream::{
+            DeserializationHeap, DeserializationStack, DeserializationStrategy,
+            ChunkType, GfdSerializationUserData, GfdSerialize, SerializationSingleAllocator,
+            Stream, StreamIODevice
+        }
     }
 };
 use std::{
     error::Error,
-    fmt::Display,
+    fmt::{ Debug, Display },
+    io::{ self, Read, Seek, Write },
     ptr::NonNull
 };
 
+#[derive(Debug)]
+pub enum AnimationError {
+    InvalidJointType(u16),
+    UnsupportedEffect
+}
+impl Error for AnimationError {}
+impl Display for AnimationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidJointType(v) => write!(f, "Invalid animation joint type {}", v),
+            Self::UnsupportedEffect => write!(f, "Reading animation effects is not supported yet")
+        }
+    }
+}
+
 bitflags! {
     pub struct AnimationFlags: u32 {
         const Flag0           = 1 << 0;
@@ -66,13 +95,14 @@ where A: Allocator + Clone
     flags: AnimationFlags,
     duration: f32,
     num_joints: u32,
-    joints: Option<NonNull<AnimationJoint>>,
+    joints: Option<NonNull<AnimationJoint<A>>>,
     effect: Option<NonNull<ItemArray<NonNull<EPL<A>>, A>>>,
-    neck: Option<NonNull<AnimationNeck>>,
+    neck: Option<NonNull<AnimationNeck<A>>>,
     bounding_box: Option<NonNull<BoundingBox>>,
-    translation: Option<NonNull<AnimationTranslation>>,
+    translation: Option<NonNull<AnimationTranslation<A>>>,
     properties: Option<NonNull<Property<A>>>,
     frequency: f32,
+    #[cfg(feature = "cfb_gap")]
     cfb_data: AnimationCFB,
     ref_: Reference,
     _allocator: A
@@ -80,6 +110,40 @@ where A: Allocator + Clone
 
 impl<A> Animation<A>
 where A: Allocator + Clone {
+    /// Create an animation from a set of joints. Other data (bounding box, speed, properties) is
+    /// added using the setters, which also update the animation flags.
+    pub fn new_in(duration: f32, joints: AVec<AnimationJoint<A>, A>, allocator: A) -> NonNull<Self> {
+        let mut this = unsafe { ABox::new_zeroed_in(allocator.clone()).assume_init() };
+        let this_mut: &mut Self = this.as_mut();
+        this_mut.ref_ = Reference::new();
+        this_mut.duration = duration;
+        this_mut.num_joints = joints.len() as u32;
+        this_mut.joints = NonNull::new(ABox::into_raw(joints.into_boxed_slice()) as *mut AnimationJoint<A>);
+        unsafe { std::ptr::write(&raw mut this_mut._allocator, allocator) };
+        unsafe { NonNull::new_unchecked(ABox::into_raw(this)) }
+    }
+    pub fn get_flags(&self) -> u32 {
+        self.flags.bits()
+    }
+    pub fn set_bounding_box(&mut self, bounding_box: BoundingBox) {
+        if let Some(old) = self.bounding_box.take() {
+            drop(unsafe { ABox::from_raw_in(old.as_ptr(), self._allocator.clone()) });
+        }
+        self.bounding_box = Some(unsafe { NonNull::new_unchecked(ABox::into_raw(ABox::new_in(bounding_box, self._allocator.clone()))) });
+        self.flags |= AnimationFlags::HasBoundingBox;
+    }
+    pub fn set_frequency(&mut self, frequency: f32) {
+        self.frequency = frequency;
+        self.flags |= AnimationFlags::HasSpeed;
+    }
+    /// Set the animation's properties. The animation takes ownership of them, and frees the
+    /// properties that they replace.
+    pub fn set_properties(&mut self, properties: NonNull<Property<A>>) {
+        if let Some(old) = self.properties.replace(properties).filter(|v| *v != properties) {
+            drop(unsafe { ABox::from_raw_in(old.as_ptr(), self._allocator.clone()) });
+        }
+        self.flags |= AnimationFlags::HasProperties;
+    }
     pub fn has_flags(&self, flags: AnimationFlags) -> bool {
         self.flags.contains(flags)
     }
@@ -92,11 +156,150 @@ where A: Allocator + Clone {
     pub fn get_frequency(&self) -> f32 {
         self.frequency
     }
-    pub fn get_translation_distance(&self, start: f32, end: f32) {
+    pub fn get_joints(&self) -> &[AnimationJoint<A>] {
+        match self.joints {
+            Some(v) => unsafe { std::slice::from_raw_parts(v.as_ptr(), self.num_joints as usize) },
+            None => &[]
+        }
+    }
+    pub fn get_neck(&self) -> Option<&AnimationNeck<A>> {
+        self.neck.map(|v| unsafe { v.as_ref() })
+    }
+    pub fn get_bounding_box(&self) -> Option<&BoundingBox> {
+        self.bounding_box.map(|v| unsafe { v.as_ref() })
+    }
+    pub fn get_translation(&self) -> Option<&AnimationTranslation<A>> {
+        self.translation.map(|v| unsafe { v.as_ref() })
+    }
+    pub fn get_properties(&self) -> Option<&Property<A>> {
+        self.properties.map(|v| unsafe { v.as_ref() })
+    }
+}
 
+impl<A> Drop for Animation<A>
+where A: Allocator + Clone {
+    fn drop(&mut self) {
+        let allocator = self._allocator.clone();
+        if let Some(joints) = self.joints.take() {
+            let joints = std::ptr::slice_from_raw_parts_mut(joints.as_ptr(), self.num_joints as usize);
+            drop(unsafe { ABox::from_raw_in(joints, allocator.clone()) });
+        }
+        if let Some(neck) = self.neck.take() {
+            drop(unsafe { ABox::from_raw_in(neck.as_ptr(), allocator.clone()) });
+        }
+        if let Some(bounding_box) = self.bounding_box.take() {
+            drop(unsafe { ABox::from_raw_in(bounding_box.as_ptr(), allocator.clone()) });
+        }
+        if let Some(translation) = self.translation.take() {
+            drop(unsafe { ABox::from_raw_in(translation.as_ptr(), allocator.clone()) });
+        }
+        if let Some(properties) = self.properties.take() {
+            drop(unsafe { ABox::from_raw_in(properties.as_ptr(), allocator) });
+        }
     }
 }
 
+#[cfg(feature = "serialize")]
+impl<AStream, AObject, T> GfdSerialize<AStream, T, AObject, DeserializationHeap<Self, AObject>, SerializationSingleAllocator<AObject>> for Animation<AObject>
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      AStream: Allocator + Clone + Debug,
+      AObject: Allocator + Clone
+{
+    fn stream_read(stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<DeserializationHeap<Self, AObject>, Box<dyn Error>> {
+        let mut this = DeserializationHeap::<Self, AObject>::zeroed(param);
+        this.ref_ = Reference::new();
+        unsafe { std::ptr::write(&raw mut this._allocator, param.get_heap_allocator().unwrap()) };
+        this.stream_read_inner(stream, param)?;
+        Ok(this)
+    }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream, param)
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl<AObject> Animation<AObject>
+where AObject: Allocator + Clone
+{
+    fn stream_read_inner<AStream, T>(&mut self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        let allocator = param.get_heap_allocator().unwrap();
+        if stream.has_feature(GfdVersion::AnimationHasFlags).is_some() {
+            self.flags = AnimationFlags::from_bits_retain(stream.read_u32()?);
+        }
+        self.duration = stream.read_f32()?;
+        self.num_joints = stream.read_u32()?;
+        if self.num_joints as u64 > stream.get_remaining()? / ANIMATION_JOINT_MIN_SIZE {
+            return Err(Box::new(io::Error::from(io::ErrorKind::UnexpectedEof)));
+        }
+        let mut joints = AVec::with_capacity_in(self.num_joints as usize, allocator.clone());
+        for _ in 0..self.num_joints {
+            joints.push(AnimationJoint::<AObject>::stream_read(stream, param)?.into_raw());
+        }
+        self.joints = NonNull::new(ABox::into_raw(joints.into_boxed_slice()) as *mut AnimationJoint<AObject>);
+        if self.flags.contains(AnimationFlags::HasEffect) {
+            // Effect animations contain EPLs, which can't be fully read yet. MeshFile keeps the
+            // animation pack as a raw chunk when this happens.
+            return Err(Box::new(AnimationError::UnsupportedEffect));
+        }
+        if self.flags.contains(AnimationFlags::HasNeck) {
+            self.neck = Some(AnimationNeck::<AObject>::stream_read(stream, param)?.into_raw());
+        }
+        if self.flags.contains(AnimationFlags::HasBoundingBox) {
+            let bounding_box = BoundingBox::stream_read(stream, &mut ())?.into_raw();
+            self.bounding_box = Some(unsafe { NonNull::new_unchecked(ABox::into_raw(ABox::new_in(bounding_box, allocator.clone()))) });
+        }
+        if self.flags.contains(AnimationFlags::HasTranslation) {
+            self.translation = Some(AnimationTranslation::<AObject>::stream_read(stream, param)?.into_raw());
+        }
+        if self.flags.contains(AnimationFlags::HasProperties) {
+            self.properties = Some(Property::<AObject>::stream_read(stream, param)?.into_raw());
+        }
+        if self.flags.contains(AnimationFlags::HasSpeed) {
+            self.frequency = stream.read_f32()?;
+        }
+        Ok(())
+    }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        if stream.has_feature(GfdVersion::AnimationHasFlags).is_some() {
+            stream.write_u32(self.flags.bits())?;
+        }
+        stream.write_f32(self.duration)?;
+        stream.write_u32(self.num_joints)?;
+        for joint in self.get_joints() {
+            joint.stream_write(stream, param)?;
+        }
+        if self.flags.contains(AnimationFlags::HasEffect) {
+            return Err(Box::new(AnimationError::UnsupportedEffect));
+        }
+        if let Some(neck) = self.get_neck().filter(|_| self.flags.contains(AnimationFlags::HasNeck)) {
+            neck.stream_write(stream, param)?;
+        }
+        if let Some(bounding_box) = self.get_bounding_box().filter(|_| self.flags.contains(AnimationFlags::HasBoundingBox)) {
+            bounding_box.stream_write(stream, &mut ())?;
+        }
+        if let Some(translation) = self.get_translation().filter(|_| self.flags.contains(AnimationFlags::HasTranslation)) {
+            translation.stream_write(stream, param)?;
+        }
+        if let Some(properties) = self.get_properties().filter(|_| self.flags.contains(AnimationFlags::HasProperties)) {
+            properties.stream_write(stream, param)?;
+        }
+        if self.flags.contains(AnimationFlags::HasSpeed) {
+            stream.write_f32(self.frequency)?;
+        }
+        Ok(())
+    }
+}
+
+/// Runtime state used by CFB's animation loader. This isn't stored in GAP files, so it's zeroed
+/// for animations read from a stream. The part of CFB's GAP format that does differ is the extra
+/// byte in joint names, which NameSerializationHashGAP reads based on the stream's game profile.
+/// This is part of the game's Animation layout, so it's still chosen by the cfb_gap feature.
 #[cfg(feature = "cfb_gap")]
 #[repr(C)]
 pub struct AnimationCFB {
@@ -108,12 +311,56 @@ pub struct AnimationCFB {
 }
 
 #[repr(C)]
-pub struct AnimationNeck {
-    anim: [Animation; 4],
+pub struct AnimationNeck<A = GfdAllocator>
+where A: Allocator + Clone
+{
+    anim: [Animation<A>; 4],
     angle: [f32; 4]
 }
 
+impl<A> AnimationNeck<A>
+where A: Allocator + Clone
+{
+    pub fn get_anim(&self, index: usize) -> Option<&Animation<A>> {
+        self.anim.get(index)
+    }
+    pub fn get_angle(&self, index: usize) -> Option<f32> {
+        self.angle.get(index).copied()
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl<AStream, AObject, T> GfdSerialize<AStream, T, AObject, DeserializationHeap<Self, AObject>, SerializationSingleAllocator<AObject>> for AnimationNeck<AObject>
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      AStream: Allocator + Clone + Debug,
+      AObject: Allocator + Clone
+{
+    fn stream_read(stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<DeserializationHeap<Self, AObject>, Box<dyn Error>> {
+        let mut this = DeserializationHeap::<Self, AObject>::zeroed(param);
+        // Every animation is dropped with the neck, so they all need an allocator before any of
+        // them are read
+        for anim in this.anim.iter_mut() {
+            anim.ref_ = Reference::new();
+            unsafe { std::ptr::write(&raw mut anim._allocator, param.get_heap_allocator().unwrap()) };
+        }
+        for i in 0..this.anim.len() {
+            this.anim[i].stream_read_inner(stream, param)?;
+            this.angle[i] = stream.read_f32()?;
+        }
+        Ok(this)
+    }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        for i in 0..self.anim.len() {
+            self.anim[i].stream_write_inner(stream, param)?;
+            stream.write_f32(self.angle[i])?;
+        }
+        Ok(())
+    }
+}
+
 #[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AnimJointType {
     Invalid = 0,
     Node = 1,
@@ -122,23 +369,311 @@ pub enum AnimJointType {
     Morph = 4
 }
 
+impl TryFrom<u16> for AnimJointType {
+    type Error = AnimationError;
+    fn try_from(value: u16) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Invalid),
+            1 => Ok(Self::Node),
+            2 => Ok(Self::Material),
+            3 => Ok(Self::Camera),
+            4 => Ok(Self::Morph),
+            v => Err(AnimationError::InvalidJointType(v))
+        }
+    }
+}
+
 #[repr(C)]
-pub struct AnimationJoint {
+pub struct AnimationJoint<A = GfdAllocator>
+where A: Allocator + Clone
+{
     type_: AnimJointType,
     field_04: u32,
     target_id: u32,
-    name: Name,
+    name: Name<A>,
     num_keys: u32,
     keys: *mut KeyList
 }
 
+impl<A> AnimationJoint<A>
+where A: Allocator + Clone
+{
+    pub fn get_type(&self) -> AnimJointType {
+        self.type_
+    }
+    pub fn get_target_id(&self) -> u32 {
+        self.target_id
+    }
+    pub fn get_name(&self) -> &Name<A> {
+        &self.name
+    }
+    /// Create a joint from a set of key lists. The key lists are freed with the name's allocator,
+    /// so they should be allocated by the same allocator as the name.
+    pub fn new_in(type_: AnimJointType, target_id: u32, name: Name<A>, keys: AVec<KeyList, A>) -> Self {
+        let num_keys = keys.len() as u32;
+        let keys = ABox::into_raw(keys.into_boxed_slice()) as *mut KeyList;
+        Self { type_, field_04: 0, target_id, name, num_keys, keys }
+    }
+    pub fn get_key_lists(&self) -> &[KeyList] {
+        match self.keys.is_null() {
+            true => &[],
+            false => unsafe { std::slice::from_raw_parts(self.keys, self.num_keys as usize) }
+        }
+    }
+}
+
+// Key lists don't store their allocator, so they're freed using the allocator for the name
+impl<A> Drop for AnimationJoint<A>
+where A: Allocator + Clone
+{
+    fn drop(&mut self) {
+        if self.keys.is_null() {
+            return;
+        }
+        let allocator = self.name._allocator.clone();
+        let mut keys = unsafe { ABox::from_raw_in(std::ptr::slice_from_raw_parts_mut(self.keys, self.num_keys as usize), allocator.clone()) };
+        for key in keys.iter_mut() {
+            unsafe { key.free_in(&allocator) };
+        }
+        self.keys = std::ptr::null_mut();
+    }
+}
+
+// Type, target, name length and key list count of a joint with an empty name and no keys
+#[cfg(feature = "serialize")]
+const ANIMATION_JOINT_MIN_SIZE: u64 = 12;
+// Type and key count of a key list with no keys
+#[cfg(feature = "serialize")]
+const KEY_LIST_MIN_SIZE: u64 = 8;
+
+#[cfg(feature = "serialize")]
+impl<AStream, AObject, T> GfdSerialize<AStream, T, AObject, DeserializationStack<Self>, SerializationSingleAllocator<AObject>> for AnimationJoint<AObject>
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      AStream: Allocator + Clone + Debug,
+      AObject: Allocator + Clone
+{
+    fn stream_read(stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<DeserializationStack<Self>, Box<dyn Error>> {
+        let allocator = param.get_heap_allocator().unwrap();
+        let type_ = stream.read_u16()?.try_into()?;
+        let target_id = stream.read_u32()?;
+        let name = Name::<AObject>::stream_read(stream, &mut NameSerializationContext::new(allocator.clone(), NameSerializationHashGAP))?.into_raw();
+        let num_keys = stream.read_u32()?;
+        if num_keys as u64 > stream.get_remaining()? / KEY_LIST_MIN_SIZE {
+            return Err(Box::new(io::Error::from(io::ErrorKind::UnexpectedEof)));
+        }
+        let mut keys = AVec::with_capacity_in(num_keys as usize, allocator.clone());
+        for _ in 0..num_keys {
+            keys.push(KeyList::stream_read(stream, param)?.into_raw());
+        }
+        let keys = ABox::into_raw(keys.into_boxed_slice()) as *mut KeyList;
+        Ok(Self { type_, field_04: 0, target_id, name, num_keys, keys }.into())
+    }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        stream.write_u16(self.type_ as u16)?;
+        stream.write_u32(self.target_id)?;
+        NameSerializationHashGAP.stream_write_inner(stream, &self.name)?;
+        stream.write_u32(self.num_keys)?;
+        for keys in self.get_key_lists() {
+            keys.stream_write(stream, param)?;
+        }
+        Ok(())
+    }
+}
+
 #[repr(C)]
-pub struct AnimationTranslation {
+pub struct AnimationTranslation<A = GfdAllocator>
+where A: Allocator + Clone
+{
     field_0: u64,
-    name: Name,
+    name: Name<A>,
     keys: KeyList
 }
 
+impl<A> AnimationTranslation<A>
+where A: Allocator + Clone
+{
+    pub fn get_name(&self) -> &Name<A> {
+        &self.name
+    }
+    pub fn get_key_list(&self) -> &KeyList {
+        &self.keys
+    }
+}
+
+impl<A> Drop for AnimationTranslation<A>
+where A: Allocator + Clone
+{
+    fn drop(&mut self) {
+        let allocator = self.name._allocator.clone();
+        unsafe { self.keys.free_in(&allocator) };
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl<AStream, AObject, T> GfdSerialize<AStream, T, AObject, DeserializationHeap<Self, AObject>, SerializationSingleAllocator<AObject>> for AnimationTranslation<AObject>
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      AStream: Allocator + Clone + Debug,
+      AObject: Allocator + Clone
+{
+    fn stream_read(stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<DeserializationHeap<Self, AObject>, Box<dyn Error>> {
+        let allocator = param.get_heap_allocator().unwrap();
+        let field_0 = stream.read_u32()? as u64;
+        let name = Name::<AObject>::stream_read(stream, &mut NameSerializationContext::new(allocator.clone(), NameSerializationHashGAP))?.into_raw();
+        let keys = KeyList::stream_read(stream, param)?.into_raw();
+        Ok(DeserializationHeap::new(Self { field_0, name, keys }, allocator))
+    }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        stream.write_u32(self.field_0 as u32)?;
+        NameSerializationHashGAP.stream_write_inner(stream, &self.name)?;
+        self.keys.stream_write(stream, param)
+    }
+}
+
+/// Set of animations loaded from an AnimationPack chunk (the contents of a GAP file)
+pub struct AnimationPack<A = GfdAllocator>
+where A: Allocator + Clone
+{
+    flags: AnimationPackFlags,
+    animations: ItemArray<NonNull<Animation<A>>, A>,
+    blend_animations: ItemArray<NonNull<Animation<A>>, A>,
+    neck: Option<NonNull<AnimationNeck<A>>>,
+    _allocator: A
+}
+
+impl<A> AnimationPack<A>
+where A: Allocator + Clone
+{
+    pub fn new(allocator: A) -> Self {
+        Self {
+            flags: AnimationPackFlags::empty(),
+            animations: ItemArray::new(allocator.clone()),
+            blend_animations: ItemArray::new(allocator.clone()),
+            neck: None,
+            _allocator: allocator
+        }
+    }
+    pub fn get_flags(&self) -> u32 {
+        self.flags.bits()
+    }
+    /// Add a base animation. The pack takes ownership of the animation.
+    pub fn push_animation(&mut self, animation: NonNull<Animation<A>>) -> Result<(), Box<dyn Error>> {
+        Ok(self.animations.push(animation)?)
+    }
+    /// Add a blend animation. The pack takes ownership of the animation.
+    pub fn push_blend_animation(&mut self, animation: NonNull<Animation<A>>) -> Result<(), Box<dyn Error>> {
+        Ok(self.blend_animations.push(animation)?)
+    }
+    /// Set the neck animations. The pack takes ownership of the new neck animations, and frees
+    /// the ones that they replace.
+    pub fn set_neck(&mut self, neck: Option<NonNull<AnimationNeck<A>>>) {
+        if let Some(old) = std::mem::replace(&mut self.neck, neck).filter(|v| Some(*v) != neck) {
+            drop(unsafe { ABox::from_raw_in(old.as_ptr(), self._allocator.clone()) });
+        }
+        match neck.is_some() {
+            true => self.flags |= AnimationPackFlags::HasNeck,
+            false => self.flags &= !AnimationPackFlags::HasNeck
+        };
+    }
+    pub fn get_animations(&self) -> &[NonNull<Animation<A>>] {
+        self.animations.as_slice()
+    }
+    pub fn get_blend_animations(&self) -> &[NonNull<Animation<A>>] {
+        self.blend_animations.as_slice()
+    }
+    pub fn get_animation(&self, index: usize) -> Option<&Animation<A>> {
+        self.animations.as_slice().get(index).map(|v| unsafe { v.as_ref() })
+    }
+    pub fn get_blend_animation(&self, index: usize) -> Option<&Animation<A>> {
+        self.blend_animations.as_slice().get(index).map(|v| unsafe { v.as_ref() })
+    }
+    pub fn get_neck(&self) -> Option<&AnimationNeck<A>> {
+        self.neck.map(|v| unsafe { v.as_ref() })
+    }
+    /// Create a playback sequence for the base animation at the given index
+    pub fn create_sequence(&self, index: usize) -> Option<AnimationSequence<A>> {
+        self.animations.as_slice().get(index).map(|v| AnimationSequence::new(*v))
+    }
+}
+
+impl<A> Drop for AnimationPack<A>
+where A: Allocator + Clone
+{
+    fn drop(&mut self) {
+        let animations = self.animations.as_slice().iter().chain(self.blend_animations.as_slice());
+        for animation in animations {
+            drop(unsafe { ABox::from_raw_in(animation.as_ptr(), self._allocator.clone()) });
+        }
+        if let Some(neck) = self.neck.take() {
+            drop(unsafe { ABox::from_raw_in(neck.as_ptr(), self._allocator.clone()) });
+        }
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl<AStream, AObject, T> GfdSerialize<AStream, T, AObject, DeserializationHeap<Self, AObject>, SerializationSingleAllocator<AObject>> for AnimationPack<AObject>
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      AStream: Allocator + Clone + Debug,
+      AObject: Allocator + Clone
+{
+    fn stream_read(stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<DeserializationHeap<Self, AObject>, Box<dyn Error>> {
+        let allocator = param.get_heap_allocator().unwrap();
+        let mut this = Self::new(allocator.clone());
+        if stream.has_feature(GfdVersion::AnmationPackHasFlags).is_some() {
+            this.flags = AnimationPackFlags::from_bits_retain(stream.read_u32()?);
+        }
+        // Animations are added to the pack as they're read so that they're freed on error
+        Self::stream_read_animations(stream, param, &mut this.animations)?;
+        Self::stream_read_animations(stream, param, &mut this.blend_animations)?;
+        if this.flags.contains(AnimationPackFlags::HasNeck) {
+            this.neck = Some(AnimationNeck::<AObject>::stream_read(stream, param)?.into_raw());
+        }
+        Ok(DeserializationHeap::new(this, allocator))
+    }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        if stream.has_feature(GfdVersion::AnmationPackHasFlags).is_some() {
+            stream.write_u32(self.flags.bits())?;
+        }
+        for animations in [&self.animations, &self.blend_animations] {
+            stream.write_u32(animations.len() as u32)?;
+            for animation in animations.as_slice() {
+                unsafe { animation.as_ref() }.stream_write(stream, param)?;
+            }
+        }
+        if let Some(neck) = self.get_neck().filter(|_| self.flags.contains(AnimationPackFlags::HasNeck)) {
+            neck.stream_write(stream, param)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl<AObject> AnimationPack<AObject>
+where AObject: Allocator + Clone
+{
+    /// Write the animation pack as a standalone GAP file. The stream should only contain the file
+    /// header, which determines the version that the animations are written as.
+    pub fn stream_write_gap<AStream, T>(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        let version = stream.get_header_version();
+        stream.write_chunk(ChunkType::AnimationPack, version, |stream| self.stream_write(stream, param))?;
+        stream.write_chunk(ChunkType::EOF, version, |_| Ok(()))
+    }
+
+    fn stream_read_animations<AStream, T>(stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>, animations: &mut ItemArray<NonNull<Animation<AObject>>, AObject>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        let count = stream.read_u32()?;
+        for _ in 0..count {
+            animations.push(Animation::<AObject>::stream_read(stream, param)?.into_raw())?;
+        }
+        Ok(())
+    }
+}
+
 bitflags! {
     pub struct SequenceFlags: u32 {
         const Playing  = 1 << 0;
@@ -177,21 +712,37 @@ bitflags! {
 }
 
 #[repr(C)]
-pub struct AnimationSequence {
+pub struct AnimationSequence<A = GfdAllocator>
+where A: Allocator + Clone
+{
     flags: SequenceFlags,
     time: f32,
     current_time: f32,
-    animation: Option<NonNull<Animation>>,
+    animation: Option<NonNull<Animation<A>>>,
     type_: u32,
     weight: f32,
     frequency: f32
 }
 
-impl AnimationSequence {
-    pub fn get_anim(&self) -> Option<&Animation> {
+impl<A> AnimationSequence<A>
+where A: Allocator + Clone
+{
+    /// Create a sequence which plays the animation from the start at full weight
+    pub fn new(animation: NonNull<Animation<A>>) -> Self {
+        Self {
+            flags: SequenceFlags::Playing,
+            time: 0.,
+            current_time: 0.,
+            animation: Some(animation),
+            type_: 0,
+            weight: 1.,
+            frequency: 1.
+        }
+    }
+    pub fn get_anim(&self) -> Option<&Animation<A>> {
         self.animation.map(|v| unsafe { v.as_ref() })
     }
-    pub fn get_anim_mut(&mut self) -> Option<&mut Animation> {
+    pub fn get_anim_mut(&mut self) -> Option<&mut Animation<A>> {
         self.animation.map(|mut v| unsafe { v.as_mut() })
     }
 }
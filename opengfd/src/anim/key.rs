@@ -1,11 +1,54 @@
 #![allow(dead_code)]
 //! NOTE: Float arrays are used instead of glam's vectors to keep alignemnt value as alignof(f32)
 
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+use std::io::{self, Read, Seek, Write};
 use std::ops::Deref;
+use allocator_api2::alloc::{Allocator, Layout};
+use allocator_api2::boxed::Box as ABox;
+use allocator_api2::vec::Vec as AVec;
 use bitflags::bitflags;
 use glam::{FloatExt, Quat, Vec2, Vec3, Vec4 };
 use std::ptr::NonNull;
 use half::f16;
+use crate::utility::stream::{DeserializationStack, GfdSerializationUserData, GfdSerialize, SerializationSingleAllocator, Stream, StreamIODevice};
+
+#[derive(Debug)]
+pub enum KeyError {
+    InvalidKeyType(u32),
+    MismatchedKeyFormat(KeyType),
+    InvalidTransformType(u8),
+    KeyCountMismatch(usize, usize)
+}
+impl Error for KeyError {}
+impl Display for KeyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidKeyType(v) => write!(f, "Invalid key type {}", v),
+            Self::MismatchedKeyFormat(v) => write!(f, "Keys don't use the key format for {:?}", v),
+            Self::InvalidTransformType(v) => write!(f, "Invalid transform type {}", v),
+            Self::KeyCountMismatch(t, k) => write!(f, "Key list has {} times but {} keys", t, k)
+        }
+    }
+}
+
+#[cfg(feature = "serialize")]
+fn read_f16_array<AStream, T, const N: usize>(stream: &mut Stream<AStream, T>) -> Result<[f16; N], Box<dyn Error>>
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      AStream: Allocator + Clone + Debug {
+    let mut bits = [0u16; N];
+    stream.read_u16_slice(bits.as_mut_slice())?;
+    Ok(bits.map(f16::from_bits))
+}
+
+#[cfg(feature = "serialize")]
+fn write_f16_array<AStream, T, const N: usize>(stream: &mut Stream<AStream, T>, value: &[f16; N]) -> Result<(), Box<dyn Error>>
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      AStream: Allocator + Clone + Debug {
+    stream.write_u16_slice(value.map(|v| v.to_bits()).as_slice())?;
+    Ok(())
+}
 
 pub trait AnimKey {
     fn blend(&self, other: &Self, rate: f32) -> Self;
@@ -22,6 +65,9 @@ impl AnimKey for f32 {
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
 pub struct Float(f32);
+impl Float {
+    pub fn new(value: f32) -> Self { Self(value) }
+}
 impl Deref for Float {
     type Target = f32;
     fn deref(&self) -> &Self::Target {
@@ -40,6 +86,21 @@ impl Default for Float {
     }
 }
 
+#[cfg(feature = "serialize")]
+impl<A, T> GfdSerialize<A, T> for Float
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      A: Allocator + Clone + Debug
+{
+    fn stream_read(stream: &mut Stream<A, T>, _: &mut ()) -> Result<DeserializationStack<Self>, Box<dyn Error>> {
+        Ok(Self(stream.read_f32()?).into())
+    }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        stream.write_f32(self.0)?;
+        Ok(())
+    }
+}
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
 pub struct FloatStep(f32);
@@ -61,6 +122,21 @@ impl Default for FloatStep {
     }
 }
 
+#[cfg(feature = "serialize")]
+impl<A, T> GfdSerialize<A, T> for FloatStep
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      A: Allocator + Clone + Debug
+{
+    fn stream_read(stream: &mut Stream<A, T>, _: &mut ()) -> Result<DeserializationStack<Self>, Box<dyn Error>> {
+        Ok(Self(stream.read_f32()?).into())
+    }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        stream.write_f32(self.0)?;
+        Ok(())
+    }
+}
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
 pub struct Alpha {
@@ -95,6 +171,24 @@ impl Default for Alpha {
     }
 }
 
+#[cfg(feature = "serialize")]
+impl<A, T> GfdSerialize<A, T> for Alpha
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      A: Allocator + Clone + Debug
+{
+    fn stream_read(stream: &mut Stream<A, T>, _: &mut ()) -> Result<DeserializationStack<Self>, Box<dyn Error>> {
+        let interp = stream.read_u8()?.try_into()?;
+        let data = stream.read_f32()?;
+        Ok(Self { interp, data }.into())
+    }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        stream.write_u8(self.interp as u8)?;
+        stream.write_f32(self.data)?;
+        Ok(())
+    }
+}
+
 // Wrapper type for a 4-byte aligned Quaternion
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
@@ -112,6 +206,23 @@ impl Default for Quaternion {
     }
 }
 
+#[cfg(feature = "serialize")]
+impl<A, T> GfdSerialize<A, T> for Quaternion
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      A: Allocator + Clone + Debug
+{
+    fn stream_read(stream: &mut Stream<A, T>, _: &mut ()) -> Result<DeserializationStack<Self>, Box<dyn Error>> {
+        let mut new = Self::default();
+        stream.read_f32_slice(new.0.as_mut_slice())?;
+        Ok(new.into())
+    }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        stream.write_f32_slice(self.0.as_slice())?;
+        Ok(())
+    }
+}
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
 pub struct KeyTR {
@@ -119,6 +230,14 @@ pub struct KeyTR {
     r: Quaternion
 }
 
+impl KeyTR {
+    pub fn new(t: Vec3, r: Quat) -> Self {
+        Self { t, r: Quaternion(r.to_array()) }
+    }
+    pub fn get_translate(&self) -> Vec3 { self.t }
+    pub fn get_rotate(&self) -> Quat { Quat::from_array(self.r.0) }
+}
+
 impl AnimKey for KeyTR {
     fn blend(&self, other: &Self, rate: f32) -> Self {
         Self {
@@ -137,6 +256,23 @@ impl Default for KeyTR {
     }
 }
 
+#[cfg(feature = "serialize")]
+impl<A, T> GfdSerialize<A, T> for KeyTR
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      A: Allocator + Clone + Debug
+{
+    fn stream_read(stream: &mut Stream<A, T>, _: &mut ()) -> Result<DeserializationStack<Self>, Box<dyn Error>> {
+        let t = Vec3::stream_read(stream, &mut ())?.into_raw();
+        let r = Quaternion::stream_read(stream, &mut ())?.into_raw();
+        Ok(Self { t, r }.into())
+    }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.t.stream_write(stream, &mut ())?;
+        self.r.stream_write(stream, &mut ())
+    }
+}
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy, Default)]
 pub struct KeyCompressedTR {
@@ -170,6 +306,170 @@ impl AnimKey for KeyCompressedTR {
     }
 }
 
+#[cfg(feature = "serialize")]
+impl<A, T> GfdSerialize<A, T> for KeyCompressedTR
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      A: Allocator + Clone + Debug
+{
+    fn stream_read(stream: &mut Stream<A, T>, _: &mut ()) -> Result<DeserializationStack<Self>, Box<dyn Error>> {
+        let t = read_f16_array(stream)?;
+        let r = read_f16_array(stream)?;
+        Ok(Self { t, r }.into())
+    }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        write_f16_array(stream, &self.t)?;
+        write_f16_array(stream, &self.r)
+    }
+}
+
+/// Half-precision rotation key (NodeRHalf)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeyCompressedR([f16; 4]);
+
+impl AnimKey for KeyCompressedR {
+    fn blend(&self, other: &Self, rate: f32) -> Self {
+        let self_f32 = Quaternion(self.0.map(|v| v.to_f32()));
+        let other_f32 = Quaternion(other.0.map(|v| v.to_f32()));
+        Self(self_f32.blend(&other_f32, rate).0.map(f16::from_f32))
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl<A, T> GfdSerialize<A, T> for KeyCompressedR
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      A: Allocator + Clone + Debug
+{
+    fn stream_read(stream: &mut Stream<A, T>, _: &mut ()) -> Result<DeserializationStack<Self>, Box<dyn Error>> {
+        Ok(Self(read_f16_array(stream)?).into())
+    }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        write_f16_array(stream, &self.0)
+    }
+}
+
+/// Half-precision scale key (NodeSHalf)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeyCompressedS([f16; 3]);
+
+impl AnimKey for KeyCompressedS {
+    fn blend(&self, other: &Self, rate: f32) -> Self {
+        let self_f32 = Vec3::from_array(self.0.map(|v| v.to_f32()));
+        let other_f32 = Vec3::from_array(other.0.map(|v| v.to_f32()));
+        Self(self_f32.lerp(other_f32, rate).to_array().map(f16::from_f32))
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl<A, T> GfdSerialize<A, T> for KeyCompressedS
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      A: Allocator + Clone + Debug
+{
+    fn stream_read(stream: &mut Stream<A, T>, _: &mut ()) -> Result<DeserializationStack<Self>, Box<dyn Error>> {
+        Ok(Self(read_f16_array(stream)?).into())
+    }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        write_f16_array(stream, &self.0)
+    }
+}
+
+/// Half-precision rotation and scale key (P5R_34)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeyCompressedRS {
+    r: [f16; 4],
+    s: [f16; 3]
+}
+
+impl AnimKey for KeyCompressedRS {
+    fn blend(&self, other: &Self, rate: f32) -> Self {
+        Self {
+            r: KeyCompressedR(self.r).blend(&KeyCompressedR(other.r), rate).0,
+            s: KeyCompressedS(self.s).blend(&KeyCompressedS(other.s), rate).0
+        }
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl<A, T> GfdSerialize<A, T> for KeyCompressedRS
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      A: Allocator + Clone + Debug
+{
+    fn stream_read(stream: &mut Stream<A, T>, _: &mut ()) -> Result<DeserializationStack<Self>, Box<dyn Error>> {
+        let r = read_f16_array(stream)?;
+        let s = read_f16_array(stream)?;
+        Ok(Self { r, s }.into())
+    }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        write_f16_array(stream, &self.r)?;
+        write_f16_array(stream, &self.s)
+    }
+}
+
+/// Half-precision translation key (P5R_35)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeyCompressedT([f16; 3]);
+
+impl AnimKey for KeyCompressedT {
+    fn blend(&self, other: &Self, rate: f32) -> Self {
+        Self(KeyCompressedS(self.0).blend(&KeyCompressedS(other.0), rate).0)
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl<A, T> GfdSerialize<A, T> for KeyCompressedT
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      A: Allocator + Clone + Debug
+{
+    fn stream_read(stream: &mut Stream<A, T>, _: &mut ()) -> Result<DeserializationStack<Self>, Box<dyn Error>> {
+        Ok(Self(read_f16_array(stream)?).into())
+    }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        write_f16_array(stream, &self.0)
+    }
+}
+
+/// Half-precision translation and scale key (P5R_36)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeyCompressedTS {
+    t: [f16; 3],
+    s: [f16; 3]
+}
+
+impl AnimKey for KeyCompressedTS {
+    fn blend(&self, other: &Self, rate: f32) -> Self {
+        Self {
+            t: KeyCompressedS(self.t).blend(&KeyCompressedS(other.t), rate).0,
+            s: KeyCompressedS(self.s).blend(&KeyCompressedS(other.s), rate).0
+        }
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl<A, T> GfdSerialize<A, T> for KeyCompressedTS
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      A: Allocator + Clone + Debug
+{
+    fn stream_read(stream: &mut Stream<A, T>, _: &mut ()) -> Result<DeserializationStack<Self>, Box<dyn Error>> {
+        let t = read_f16_array(stream)?;
+        let s = read_f16_array(stream)?;
+        Ok(Self { t, s }.into())
+    }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        write_f16_array(stream, &self.t)?;
+        write_f16_array(stream, &self.s)
+    }
+}
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy, Default)]
 pub struct KeyCompressedTRS {
@@ -207,6 +507,25 @@ impl AnimKey for KeyCompressedTRS {
     }
 }
 
+#[cfg(feature = "serialize")]
+impl<A, T> GfdSerialize<A, T> for KeyCompressedTRS
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      A: Allocator + Clone + Debug
+{
+    fn stream_read(stream: &mut Stream<A, T>, _: &mut ()) -> Result<DeserializationStack<Self>, Box<dyn Error>> {
+        let t = read_f16_array(stream)?;
+        let r = read_f16_array(stream)?;
+        let s = read_f16_array(stream)?;
+        Ok(Self { t, r, s }.into())
+    }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        write_f16_array(stream, &self.t)?;
+        write_f16_array(stream, &self.r)?;
+        write_f16_array(stream, &self.s)
+    }
+}
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
 pub struct KeyTRS {
@@ -215,6 +534,15 @@ pub struct KeyTRS {
     s: Vec3,
 }
 
+impl KeyTRS {
+    pub fn new(t: Vec3, r: Quat, s: Vec3) -> Self {
+        Self { t, r: Quaternion(r.to_array()), s }
+    }
+    pub fn get_translate(&self) -> Vec3 { self.t }
+    pub fn get_rotate(&self) -> Quat { Quat::from_array(self.r.0) }
+    pub fn get_scale(&self) -> Vec3 { self.s }
+}
+
 impl AnimKey for KeyTRS {
     fn blend(&self, other: &Self, rate: f32) -> Self {
         Self {
@@ -235,6 +563,25 @@ impl Default for KeyTRS {
     }
 }
 
+#[cfg(feature = "serialize")]
+impl<A, T> GfdSerialize<A, T> for KeyTRS
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      A: Allocator + Clone + Debug
+{
+    fn stream_read(stream: &mut Stream<A, T>, _: &mut ()) -> Result<DeserializationStack<Self>, Box<dyn Error>> {
+        let t = Vec3::stream_read(stream, &mut ())?.into_raw();
+        let r = Quaternion::stream_read(stream, &mut ())?.into_raw();
+        let s = Vec3::stream_read(stream, &mut ())?.into_raw();
+        Ok(Self { t, r, s }.into())
+    }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.t.stream_write(stream, &mut ())?;
+        self.r.stream_write(stream, &mut ())?;
+        self.s.stream_write(stream, &mut ())
+    }
+}
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
 pub struct KeyRGB(Vec3);
@@ -251,6 +598,71 @@ impl Default for KeyRGB {
     }
 }
 
+#[cfg(feature = "serialize")]
+impl<A, T> GfdSerialize<A, T> for KeyRGB
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      A: Allocator + Clone + Debug
+{
+    fn stream_read(stream: &mut Stream<A, T>, _: &mut ()) -> Result<DeserializationStack<Self>, Box<dyn Error>> {
+        Ok(Self(Vec3::stream_read(stream, &mut ())?.into_raw()).into())
+    }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.0.stream_write(stream, &mut ())
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct KeyColor {
+    interp: TransformType,
+    data: KeyRGB
+}
+
+impl AnimKey for KeyColor {
+    fn blend(&self, other: &Self, rate: f32) -> Self {
+        let interp = self.interp;
+        match interp {
+            TransformType::Step => Self { interp, data: self.data },
+            TransformType::Blend => Self { interp, data: self.data.blend(&other.data, rate) },
+            TransformType::Cosine => {
+                let rate_adj = 1. - (rate * std::f32::consts::FRAC_PI_2).cos();
+                Self { interp, data: self.data.blend(&other.data, rate_adj) }
+            },
+            TransformType::Sine => {
+                let rate_adj = (rate * std::f32::consts::FRAC_PI_2).sin();
+                Self { interp, data: self.data.blend(&other.data, rate_adj) }
+            }
+        }
+    }
+}
+
+impl Default for KeyColor {
+    fn default() -> Self {
+        Self {
+            interp: TransformType::Step,
+            data: KeyRGB::default()
+        }
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl<A, T> GfdSerialize<A, T> for KeyColor
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      A: Allocator + Clone + Debug
+{
+    fn stream_read(stream: &mut Stream<A, T>, _: &mut ()) -> Result<DeserializationStack<Self>, Box<dyn Error>> {
+        let interp = stream.read_u8()?.try_into()?;
+        let data = KeyRGB::stream_read(stream, &mut ())?.into_raw();
+        Ok(Self { interp, data }.into())
+    }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        stream.write_u8(self.interp as u8)?;
+        self.data.stream_write(stream, &mut ())
+    }
+}
+
 
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
@@ -268,6 +680,23 @@ impl Default for KeyRGBA {
     }
 }
 
+#[cfg(feature = "serialize")]
+impl<A, T> GfdSerialize<A, T> for KeyRGBA
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      A: Allocator + Clone + Debug
+{
+    fn stream_read(stream: &mut Stream<A, T>, _: &mut ()) -> Result<DeserializationStack<Self>, Box<dyn Error>> {
+        let mut new = Self::default();
+        stream.read_f32_slice(new.0.as_mut_slice())?;
+        Ok(new.into())
+    }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        stream.write_f32_slice(self.0.as_slice())?;
+        Ok(())
+    }
+}
+
 
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
@@ -297,6 +726,26 @@ impl Default for KeyUV {
     }
 }
 
+#[cfg(feature = "serialize")]
+impl<A, T> GfdSerialize<A, T> for KeyUV
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      A: Allocator + Clone + Debug
+{
+    fn stream_read(stream: &mut Stream<A, T>, _: &mut ()) -> Result<DeserializationStack<Self>, Box<dyn Error>> {
+        let offset = Vec2::stream_read(stream, &mut ())?.into_raw();
+        let tile = Vec2::stream_read(stream, &mut ())?.into_raw();
+        let angle = stream.read_f32()?;
+        Ok(Self { offset, tile, angle }.into())
+    }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.offset.stream_write(stream, &mut ())?;
+        self.tile.stream_write(stream, &mut ())?;
+        stream.write_f32(self.angle)?;
+        Ok(())
+    }
+}
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
 pub struct KeyUVStep {
@@ -321,6 +770,26 @@ impl Default for KeyUVStep {
     }
 }
 
+#[cfg(feature = "serialize")]
+impl<A, T> GfdSerialize<A, T> for KeyUVStep
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      A: Allocator + Clone + Debug
+{
+    fn stream_read(stream: &mut Stream<A, T>, _: &mut ()) -> Result<DeserializationStack<Self>, Box<dyn Error>> {
+        let offset = Vec2::stream_read(stream, &mut ())?.into_raw();
+        let tile = Vec2::stream_read(stream, &mut ())?.into_raw();
+        let angle = stream.read_f32()?;
+        Ok(Self { offset, tile, angle }.into())
+    }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.offset.stream_write(stream, &mut ())?;
+        self.tile.stream_write(stream, &mut ())?;
+        stream.write_f32(self.angle)?;
+        Ok(())
+    }
+}
+
 #[repr(u32)]
 #[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq)]
 pub enum TransformType {
@@ -330,6 +799,19 @@ pub enum TransformType {
     Sine = 3
 }
 
+impl TryFrom<u8> for TransformType {
+    type Error = KeyError;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Step),
+            1 => Ok(Self::Blend),
+            2 => Ok(Self::Cosine),
+            3 => Ok(Self::Sine),
+            v => Err(KeyError::InvalidTransformType(v))
+        }
+    }
+}
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
 pub struct KeyTransform {
@@ -337,6 +819,23 @@ pub struct KeyTransform {
     data: KeyTRS
 }
 
+#[cfg(feature = "serialize")]
+impl<A, T> GfdSerialize<A, T> for KeyTransform
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      A: Allocator + Clone + Debug
+{
+    fn stream_read(stream: &mut Stream<A, T>, _: &mut ()) -> Result<DeserializationStack<Self>, Box<dyn Error>> {
+        let interp = stream.read_u8()?.try_into()?;
+        let data = KeyTRS::stream_read(stream, &mut ())?.into_raw();
+        Ok(Self { interp, data }.into())
+    }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        stream.write_u8(self.interp as u8)?;
+        self.data.stream_write(stream, &mut ())
+    }
+}
+
 impl AnimKey for KeyTransform {
     fn blend(&self, other: &Self, rate: f32) -> Self {
         let interp = self.interp;
@@ -364,6 +863,27 @@ pub struct KeyMotion {
     next: KeyController,
 }
 
+#[cfg(feature = "serialize")]
+impl<A, T> GfdSerialize<A, T> for KeyMotion
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      A: Allocator + Clone + Debug
+{
+    fn stream_read(stream: &mut Stream<A, T>, _: &mut ()) -> Result<DeserializationStack<Self>, Box<dyn Error>> {
+        let interp = stream.read_u8()?.try_into()?;
+        let motion = stream.read_u32()?;
+        let data = KeyController::stream_read(stream, &mut ())?.into_raw();
+        let next = KeyController::stream_read(stream, &mut ())?.into_raw();
+        Ok(Self { interp, motion, data, next }.into())
+    }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        stream.write_u8(self.interp as u8)?;
+        stream.write_u32(self.motion)?;
+        self.data.stream_write(stream, &mut ())?;
+        self.next.stream_write(stream, &mut ())
+    }
+}
+
 impl AnimKey for KeyMotion {
     fn blend(&self, other: &Self, rate: f32) -> Self {
         self.clone()
@@ -380,6 +900,30 @@ pub struct KeyController {
     speed: f32,
 }
 
+#[cfg(feature = "serialize")]
+impl<A, T> GfdSerialize<A, T> for KeyController
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      A: Allocator + Clone + Debug
+{
+    fn stream_read(stream: &mut Stream<A, T>, _: &mut ()) -> Result<DeserializationStack<Self>, Box<dyn Error>> {
+        let flags = stream.read_u32()?;
+        let anim = stream.read_u32()?;
+        let blend = stream.read_f32()?;
+        let weight = stream.read_f32()?;
+        let speed = stream.read_f32()?;
+        Ok(Self { flags, anim, blend, weight, speed }.into())
+    }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        stream.write_u32(self.flags)?;
+        stream.write_u32(self.anim)?;
+        stream.write_f32(self.blend)?;
+        stream.write_f32(self.weight)?;
+        stream.write_f32(self.speed)?;
+        Ok(())
+    }
+}
+
 impl AnimKey for KeyController {
     fn blend(&self, other: &Self, rate: f32) -> Self {
         self.clone()
@@ -566,7 +1110,7 @@ impl AnimKey for KeyLight {
 #[allow(non_camel_case_types)]
 #[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq)]
 pub enum KeyType {
-    NULL = 0,
+    NULL = 0, // Times only
     NodePR = 1, // TR
     NodePRS = 2, // TRS
     Vector3 = 3, // RGB
@@ -597,12 +1141,69 @@ pub enum KeyType {
     NodePRHalf_2 = 28, // CompressTR
     MaterialSingle_9 = 29, // FLOAT
     SingleAlt_3 = 30, // FLOATStep
-    Type31 = 31,
-    NodeRHalf = 32,
-    NodeSHalf = 33,
-	P5R_34 = 34,
-	P5R_35 = 35,
-    P5R_36 = 36,
+    Type31 = 31, // Times only
+    NodeRHalf = 32, // CompressR
+    NodeSHalf = 33, // CompressS
+    P5R_34 = 34, // CompressRS
+    P5R_35 = 35, // CompressT
+    P5R_36 = 36, // CompressTS
+}
+
+impl TryFrom<u32> for KeyType {
+    type Error = KeyError;
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::NULL),
+            1 => Ok(Self::NodePR),
+            2 => Ok(Self::NodePRS),
+            3 => Ok(Self::Vector3),
+            4 => Ok(Self::Quaternion),
+            5 => Ok(Self::Single),
+            6 => Ok(Self::Vector3_2),
+            7 => Ok(Self::Vector3_3),
+            8 => Ok(Self::Vector3_4),
+            9 => Ok(Self::Single_2),
+            10 => Ok(Self::Quaternion_2),
+            11 => Ok(Self::Single_3),
+            12 => Ok(Self::MaterialSingle_4),
+            13 => Ok(Self::Single5),
+            14 => Ok(Self::MaterialVector3_5),
+            15 => Ok(Self::Single_5),
+            16 => Ok(Self::Single_6),
+            17 => Ok(Self::PRSByte),
+            18 => Ok(Self::Single3Byte),
+            19 => Ok(Self::SingleByte),
+            20 => Ok(Self::Single5_2),
+            21 => Ok(Self::Single5Alt),
+            22 => Ok(Self::Type22),
+            23 => Ok(Self::CameraFieldOfView),
+            24 => Ok(Self::Single_8),
+            25 => Ok(Self::SingleAlt_2),
+            26 => Ok(Self::NodePRHalf),
+            27 => Ok(Self::NodePRSHalf),
+            28 => Ok(Self::NodePRHalf_2),
+            29 => Ok(Self::MaterialSingle_9),
+            30 => Ok(Self::SingleAlt_3),
+            31 => Ok(Self::Type31),
+            32 => Ok(Self::NodeRHalf),
+            33 => Ok(Self::NodeSHalf),
+            34 => Ok(Self::P5R_34),
+            35 => Ok(Self::P5R_35),
+            36 => Ok(Self::P5R_36),
+            v => Err(KeyError::InvalidKeyType(v))
+        }
+    }
+}
+
+impl KeyType {
+    /// Half-precision key types store their translation relative to a per-list scale
+    pub fn has_translation_scale(&self) -> bool {
+        matches!(self, Self::NodePRHalf | Self::NodePRSHalf | Self::NodePRHalf_2 | Self::P5R_35 | Self::P5R_36)
+    }
+    /// Half-precision key types which store scale relative to a per-list scale
+    pub fn has_scale_scale(&self) -> bool {
+        matches!(self, Self::NodePRSHalf | Self::NodeSHalf | Self::P5R_34 | Self::P5R_36)
+    }
 }
 
 #[repr(C)]
@@ -611,6 +1212,17 @@ pub struct KeyMaxDiff {
     s: Vec3
 }
 
+impl Default for KeyMaxDiff {
+    fn default() -> Self {
+        Self { t: Vec3::ONE, s: Vec3::ONE }
+    }
+}
+
+impl KeyMaxDiff {
+    pub fn get_translation(&self) -> Vec3 { self.t }
+    pub fn get_scale(&self) -> Vec3 { self.s }
+}
+
 #[repr(C)]
 pub struct KeyList {
     key_type: KeyType,
@@ -620,14 +1232,397 @@ pub struct KeyList {
     diff: KeyMaxDiff
 }
 
+/// Typed view over the keys stored in a KeyList. Each variant covers every KeyType which uses
+/// that key format. Key types that only store key times use Empty.
+#[derive(Debug)]
+pub enum KeyListData<'a> {
+    Empty,
+    Float(&'a [Float]),
+    FloatStep(&'a [FloatStep]),
+    TR(&'a [KeyTR]),
+    TRS(&'a [KeyTRS]),
+    RGB(&'a [KeyRGB]),
+    RGBA(&'a [KeyRGBA]),
+    UV(&'a [KeyUV]),
+    UVStep(&'a [KeyUVStep]),
+    Transform(&'a [KeyTransform]),
+    Color(&'a [KeyColor]),
+    Alpha(&'a [Alpha]),
+    Motion(&'a [KeyMotion]),
+    CompressedTR(&'a [KeyCompressedTR]),
+    CompressedTRS(&'a [KeyCompressedTRS]),
+    CompressedR(&'a [KeyCompressedR]),
+    CompressedS(&'a [KeyCompressedS]),
+    CompressedRS(&'a [KeyCompressedRS]),
+    CompressedT(&'a [KeyCompressedT]),
+    CompressedTS(&'a [KeyCompressedTS]),
+}
+
+impl<'a> KeyListData<'a> {
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Empty => 0,
+            Self::Float(v) => v.len(),
+            Self::FloatStep(v) => v.len(),
+            Self::TR(v) => v.len(),
+            Self::TRS(v) => v.len(),
+            Self::RGB(v) => v.len(),
+            Self::RGBA(v) => v.len(),
+            Self::UV(v) => v.len(),
+            Self::UVStep(v) => v.len(),
+            Self::Transform(v) => v.len(),
+            Self::Color(v) => v.len(),
+            Self::Alpha(v) => v.len(),
+            Self::Motion(v) => v.len(),
+            Self::CompressedTR(v) => v.len(),
+            Self::CompressedTRS(v) => v.len(),
+            Self::CompressedR(v) => v.len(),
+            Self::CompressedS(v) => v.len(),
+            Self::CompressedRS(v) => v.len(),
+            Self::CompressedT(v) => v.len(),
+            Self::CompressedTS(v) => v.len(),
+        }
+    }
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    // Layout of the buffer that the keys were copied into
+    fn get_layout(&self) -> Layout {
+        match self {
+            Self::Empty => Layout::new::<()>(),
+            Self::Float(v) => Layout::for_value(*v),
+            Self::FloatStep(v) => Layout::for_value(*v),
+            Self::TR(v) => Layout::for_value(*v),
+            Self::TRS(v) => Layout::for_value(*v),
+            Self::RGB(v) => Layout::for_value(*v),
+            Self::RGBA(v) => Layout::for_value(*v),
+            Self::UV(v) => Layout::for_value(*v),
+            Self::UVStep(v) => Layout::for_value(*v),
+            Self::Transform(v) => Layout::for_value(*v),
+            Self::Color(v) => Layout::for_value(*v),
+            Self::Alpha(v) => Layout::for_value(*v),
+            Self::Motion(v) => Layout::for_value(*v),
+            Self::CompressedTR(v) => Layout::for_value(*v),
+            Self::CompressedTRS(v) => Layout::for_value(*v),
+            Self::CompressedR(v) => Layout::for_value(*v),
+            Self::CompressedS(v) => Layout::for_value(*v),
+            Self::CompressedRS(v) => Layout::for_value(*v),
+            Self::CompressedT(v) => Layout::for_value(*v),
+            Self::CompressedTS(v) => Layout::for_value(*v),
+        }
+    }
+}
+
 impl KeyList {
+    /// Create a key list from a set of key times and key values. The key type must use the same
+    /// key format as the provided keys. The buffers are freed by the AnimationJoint or
+    /// AnimationTranslation that the key list is added to.
+    pub fn new_in<A>(key_type: KeyType, times: &[f32], keys: KeyListData<'_>, allocator: A) -> Result<Self, KeyError>
+    where A: Allocator + Clone {
+        let mut this = Self { key_type, key_count: 0, keys: None, times: None, diff: KeyMaxDiff::default() };
+        if std::mem::discriminant(&this.get_keys()) != std::mem::discriminant(&keys) {
+            return Err(KeyError::MismatchedKeyFormat(key_type));
+        }
+        if !matches!(keys, KeyListData::Empty) && times.len() != keys.len() {
+            return Err(KeyError::KeyCountMismatch(times.len(), keys.len()));
+        }
+        this.key_count = times.len() as u32;
+        this.times = Self::copy_slice(times, allocator.clone()).map(|v| v.cast());
+        this.keys = match keys {
+            KeyListData::Empty => None,
+            KeyListData::Float(v) => Self::copy_slice(v, allocator),
+            KeyListData::FloatStep(v) => Self::copy_slice(v, allocator),
+            KeyListData::TR(v) => Self::copy_slice(v, allocator),
+            KeyListData::TRS(v) => Self::copy_slice(v, allocator),
+            KeyListData::RGB(v) => Self::copy_slice(v, allocator),
+            KeyListData::RGBA(v) => Self::copy_slice(v, allocator),
+            KeyListData::UV(v) => Self::copy_slice(v, allocator),
+            KeyListData::UVStep(v) => Self::copy_slice(v, allocator),
+            KeyListData::Transform(v) => Self::copy_slice(v, allocator),
+            KeyListData::Color(v) => Self::copy_slice(v, allocator),
+            KeyListData::Alpha(v) => Self::copy_slice(v, allocator),
+            KeyListData::Motion(v) => Self::copy_slice(v, allocator),
+            KeyListData::CompressedTR(v) => Self::copy_slice(v, allocator),
+            KeyListData::CompressedTRS(v) => Self::copy_slice(v, allocator),
+            KeyListData::CompressedR(v) => Self::copy_slice(v, allocator),
+            KeyListData::CompressedS(v) => Self::copy_slice(v, allocator),
+            KeyListData::CompressedRS(v) => Self::copy_slice(v, allocator),
+            KeyListData::CompressedT(v) => Self::copy_slice(v, allocator),
+            KeyListData::CompressedTS(v) => Self::copy_slice(v, allocator),
+        };
+        Ok(this)
+    }
+
+    /// Free the key and time buffers. They must have been allocated by `allocator`, which is the
+    /// case for key lists made by new_in or read from a stream.
+    pub(crate) unsafe fn free_in<A>(&mut self, allocator: &A)
+    where A: Allocator {
+        let layout = self.get_keys().get_layout();
+        if let Some(keys) = self.keys.take().filter(|_| layout.size() > 0) {
+            allocator.deallocate(keys, layout);
+        }
+        let layout = Layout::for_value(self.get_times());
+        if let Some(times) = self.times.take().filter(|_| layout.size() > 0) {
+            allocator.deallocate(times.cast(), layout);
+        }
+    }
+
+    fn copy_slice<K, A>(values: &[K], allocator: A) -> Option<NonNull<u8>>
+    where K: Copy,
+          A: Allocator + Clone {
+        if values.is_empty() {
+            return None;
+        }
+        let mut copy = AVec::with_capacity_in(values.len(), allocator);
+        copy.extend_from_slice(values);
+        NonNull::new(ABox::into_raw(copy.into_boxed_slice()) as *mut u8)
+    }
+
+    /// Set the translation and scale ranges used by half-precision key types
+    pub fn set_max_diff(&mut self, translation: Vec3, scale: Vec3) {
+        self.diff = KeyMaxDiff { t: translation, s: scale };
+    }
+
     pub fn get_key_type(&self) -> KeyType {
         self.key_type
     }
+    pub fn get_key_count(&self) -> usize {
+        self.key_count as usize
+    }
     pub fn get_times(&self) -> &[f32] {
         match self.times {
             Some(s) => unsafe { std::slice::from_raw_parts(s.as_ptr(), self.key_count as usize) },
             None => &[]
         }
     }
-}
\ No newline at end of file
+    pub fn get_max_diff(&self) -> &KeyMaxDiff {
+        &self.diff
+    }
+
+    fn get_keys_as<K>(&self) -> &[K] {
+        match self.keys {
+            Some(s) => unsafe { std::slice::from_raw_parts(s.as_ptr() as *const K, self.key_count as usize) },
+            None => &[]
+        }
+    }
+
+    /// Get the keys stored in this list, typed by the list's key type.
+    pub fn get_keys(&self) -> KeyListData<'_> {
+        match self.key_type {
+            KeyType::NULL | KeyType::Type31 => KeyListData::Empty,
+            KeyType::Single | KeyType::Single_2 | KeyType::Single_3 | KeyType::MaterialSingle_4 |
+            KeyType::Single_5 | KeyType::Single_6 | KeyType::CameraFieldOfView | KeyType::Single_8 |
+            KeyType::MaterialSingle_9 => KeyListData::Float(self.get_keys_as()),
+            KeyType::SingleAlt_2 | KeyType::SingleAlt_3 => KeyListData::FloatStep(self.get_keys_as()),
+            KeyType::NodePR => KeyListData::TR(self.get_keys_as()),
+            KeyType::NodePRS => KeyListData::TRS(self.get_keys_as()),
+            KeyType::Vector3 | KeyType::Vector3_2 | KeyType::Vector3_3 | KeyType::Vector3_4 |
+            KeyType::MaterialVector3_5 => KeyListData::RGB(self.get_keys_as()),
+            KeyType::Quaternion | KeyType::Quaternion_2 => KeyListData::RGBA(self.get_keys_as()),
+            KeyType::Single5 | KeyType::Single5_2 => KeyListData::UV(self.get_keys_as()),
+            KeyType::Single5Alt => KeyListData::UVStep(self.get_keys_as()),
+            KeyType::PRSByte => KeyListData::Transform(self.get_keys_as()),
+            KeyType::Single3Byte => KeyListData::Color(self.get_keys_as()),
+            KeyType::SingleByte => KeyListData::Alpha(self.get_keys_as()),
+            KeyType::Type22 => KeyListData::Motion(self.get_keys_as()),
+            KeyType::NodePRHalf | KeyType::NodePRHalf_2 => KeyListData::CompressedTR(self.get_keys_as()),
+            KeyType::NodePRSHalf => KeyListData::CompressedTRS(self.get_keys_as()),
+            KeyType::NodeRHalf => KeyListData::CompressedR(self.get_keys_as()),
+            KeyType::NodeSHalf => KeyListData::CompressedS(self.get_keys_as()),
+            KeyType::P5R_34 => KeyListData::CompressedRS(self.get_keys_as()),
+            KeyType::P5R_35 => KeyListData::CompressedT(self.get_keys_as()),
+            KeyType::P5R_36 => KeyListData::CompressedTS(self.get_keys_as()),
+        }
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl<AStream, AObject, T> GfdSerialize<AStream, T, AObject, DeserializationStack<Self>, SerializationSingleAllocator<AObject>> for KeyList
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      AStream: Allocator + Clone + Debug,
+      AObject: Allocator + Clone
+{
+    fn stream_read(stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<DeserializationStack<Self>, Box<dyn Error>> {
+        let allocator = param.get_heap_allocator().unwrap();
+        let key_type: KeyType = stream.read_u32()?.try_into()?;
+        let key_count = stream.read_u32()?;
+        // Each key has at least a time
+        if key_count as u64 > stream.get_remaining()? / size_of::<f32>() as u64 {
+            return Err(Box::new(io::Error::from(io::ErrorKind::UnexpectedEof)));
+        }
+        let mut times = AVec::with_capacity_in(key_count as usize, allocator.clone());
+        times.resize(key_count as usize, 0f32);
+        stream.read_f32_slice(times.as_mut_slice())?;
+        let times = NonNull::new(ABox::into_raw(times.into_boxed_slice()) as *mut f32);
+        let count = key_count as usize;
+        let keys = match key_type {
+            KeyType::NULL | KeyType::Type31 => None,
+            KeyType::Single | KeyType::Single_2 | KeyType::Single_3 | KeyType::MaterialSingle_4 |
+            KeyType::Single_5 | KeyType::Single_6 | KeyType::CameraFieldOfView | KeyType::Single_8 |
+            KeyType::MaterialSingle_9 => Self::read_keys::<Float, _, _, _>(stream, count, allocator)?,
+            KeyType::SingleAlt_2 | KeyType::SingleAlt_3 => Self::read_keys::<FloatStep, _, _, _>(stream, count, allocator)?,
+            KeyType::NodePR => Self::read_keys::<KeyTR, _, _, _>(stream, count, allocator)?,
+            KeyType::NodePRS => Self::read_keys::<KeyTRS, _, _, _>(stream, count, allocator)?,
+            KeyType::Vector3 | KeyType::Vector3_2 | KeyType::Vector3_3 | KeyType::Vector3_4 |
+            KeyType::MaterialVector3_5 => Self::read_keys::<KeyRGB, _, _, _>(stream, count, allocator)?,
+            KeyType::Quaternion | KeyType::Quaternion_2 => Self::read_keys::<KeyRGBA, _, _, _>(stream, count, allocator)?,
+            KeyType::Single5 | KeyType::Single5_2 => Self::read_keys::<KeyUV, _, _, _>(stream, count, allocator)?,
+            KeyType::Single5Alt => Self::read_keys::<KeyUVStep, _, _, _>(stream, count, allocator)?,
+            KeyType::PRSByte => Self::read_keys::<KeyTransform, _, _, _>(stream, count, allocator)?,
+            KeyType::Single3Byte => Self::read_keys::<KeyColor, _, _, _>(stream, count, allocator)?,
+            KeyType::SingleByte => Self::read_keys::<Alpha, _, _, _>(stream, count, allocator)?,
+            KeyType::Type22 => Self::read_keys::<KeyMotion, _, _, _>(stream, count, allocator)?,
+            KeyType::NodePRHalf | KeyType::NodePRHalf_2 => Self::read_keys::<KeyCompressedTR, _, _, _>(stream, count, allocator)?,
+            KeyType::NodePRSHalf => Self::read_keys::<KeyCompressedTRS, _, _, _>(stream, count, allocator)?,
+            KeyType::NodeRHalf => Self::read_keys::<KeyCompressedR, _, _, _>(stream, count, allocator)?,
+            KeyType::NodeSHalf => Self::read_keys::<KeyCompressedS, _, _, _>(stream, count, allocator)?,
+            KeyType::P5R_34 => Self::read_keys::<KeyCompressedRS, _, _, _>(stream, count, allocator)?,
+            KeyType::P5R_35 => Self::read_keys::<KeyCompressedT, _, _, _>(stream, count, allocator)?,
+            KeyType::P5R_36 => Self::read_keys::<KeyCompressedTS, _, _, _>(stream, count, allocator)?,
+        };
+        let mut diff = KeyMaxDiff::default();
+        if key_type.has_translation_scale() {
+            diff.t = Vec3::stream_read(stream, &mut ())?.into_raw();
+        }
+        if key_type.has_scale_scale() {
+            diff.s = Vec3::stream_read(stream, &mut ())?.into_raw();
+        }
+        Ok(Self { key_type, key_count, keys, times, diff }.into())
+    }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        stream.write_u32(self.key_type as u32)?;
+        stream.write_u32(self.key_count)?;
+        stream.write_f32_slice(self.get_times())?;
+        match self.get_keys() {
+            KeyListData::Empty => (),
+            KeyListData::Float(v) => Self::write_keys(stream, v)?,
+            KeyListData::FloatStep(v) => Self::write_keys(stream, v)?,
+            KeyListData::TR(v) => Self::write_keys(stream, v)?,
+            KeyListData::TRS(v) => Self::write_keys(stream, v)?,
+            KeyListData::RGB(v) => Self::write_keys(stream, v)?,
+            KeyListData::RGBA(v) => Self::write_keys(stream, v)?,
+            KeyListData::UV(v) => Self::write_keys(stream, v)?,
+            KeyListData::UVStep(v) => Self::write_keys(stream, v)?,
+            KeyListData::Transform(v) => Self::write_keys(stream, v)?,
+            KeyListData::Color(v) => Self::write_keys(stream, v)?,
+            KeyListData::Alpha(v) => Self::write_keys(stream, v)?,
+            KeyListData::Motion(v) => Self::write_keys(stream, v)?,
+            KeyListData::CompressedTR(v) => Self::write_keys(stream, v)?,
+            KeyListData::CompressedTRS(v) => Self::write_keys(stream, v)?,
+            KeyListData::CompressedR(v) => Self::write_keys(stream, v)?,
+            KeyListData::CompressedS(v) => Self::write_keys(stream, v)?,
+            KeyListData::CompressedRS(v) => Self::write_keys(stream, v)?,
+            KeyListData::CompressedT(v) => Self::write_keys(stream, v)?,
+            KeyListData::CompressedTS(v) => Self::write_keys(stream, v)?,
+        };
+        if self.key_type.has_translation_scale() {
+            self.diff.t.stream_write(stream, &mut ())?;
+        }
+        if self.key_type.has_scale_scale() {
+            self.diff.s.stream_write(stream, &mut ())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl KeyList {
+    fn read_keys<K, AStream, AObject, T>(stream: &mut Stream<AStream, T>, count: usize, allocator: AObject) -> Result<Option<NonNull<u8>>, Box<dyn Error>>
+    where K: GfdSerialize<AStream, T>,
+          T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug,
+          AObject: Allocator + Clone {
+        if count == 0 {
+            return Ok(None);
+        }
+        let mut keys = AVec::with_capacity_in(count, allocator);
+        for _ in 0..count {
+            keys.push(K::stream_read(stream, &mut ())?.into_raw());
+        }
+        Ok(NonNull::new(ABox::into_raw(keys.into_boxed_slice()) as *mut u8))
+    }
+
+    fn write_keys<K, AStream, T>(stream: &mut Stream<AStream, T>, keys: &[K]) -> Result<(), Box<dyn Error>>
+    where K: GfdSerialize<AStream, T>,
+          T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        for key in keys {
+            key.stream_write(stream, &mut ())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "serialize"))]
+pub mod tests {
+    use allocator_api2::alloc::Global;
+    use half::f16;
+    use std::error::Error;
+    use crate::utility::stream::{ DevicePlatform, GfdSerialize, SerializationSingleAllocator, StreamFactory, StreamHeader };
+    use super::{ KeyError, KeyList, KeyListData, KeyType };
+
+    type TestReturn = Result<(), Box<dyn Error>>;
+
+    #[test]
+    fn half_key_list_round_trip() -> TestReturn {
+        let factory = StreamFactory::new(Global);
+        let mut param = SerializationSingleAllocator::new(Global);
+        let mut stream = factory.write_to_memory(StreamHeader::new(0x1105100, DevicePlatform::P5R_DX11))?;
+        stream.write_u32(KeyType::NodePRSHalf as u32)?;
+        stream.write_u32(1)?;
+        stream.write_f32(0.5)?;
+        stream.write_u16_slice([0.25f32, 0.5, 1., 0., 0., 0., 1., 1., 1., 1.].map(|v| f16::from_f32(v).to_bits()).as_slice())?;
+        stream.write_f32_slice(&[2., 2., 2.])?;
+        stream.write_f32_slice(&[1., 1., 1.])?;
+        let mut stream = factory.read_from_memory(stream.as_slice())?;
+        let keys = KeyList::stream_read(&mut stream, &mut param)?.into_raw();
+        assert_eq!(keys.get_key_type(), KeyType::NodePRSHalf);
+        assert_eq!(keys.get_times(), &[0.5]);
+        assert_eq!(keys.get_max_diff().get_translation().x, 2.);
+        match keys.get_keys() {
+            KeyListData::CompressedTRS(v) => assert_eq!(v[0].t[1].to_f32(), 0.5),
+            _ => return Err(Box::new(KeyError::MismatchedKeyFormat(keys.get_key_type())))
+        };
+        let mut out = factory.write_to_memory(StreamHeader::new(0x1105100, DevicePlatform::P5R_DX11))?;
+        keys.stream_write(&mut out, &mut param)?;
+        let mut out = factory.read_from_memory(out.as_slice())?;
+        let keys = KeyList::stream_read(&mut out, &mut param)?.into_raw();
+        assert_eq!(keys.get_key_count(), 1);
+        assert_eq!(keys.get_times(), &[0.5]);
+        Ok(())
+    }
+
+    #[test]
+    fn time_only_and_partial_half_key_lists() -> TestReturn {
+        let factory = StreamFactory::new(Global);
+        let mut param = SerializationSingleAllocator::new(Global);
+        let mut stream = factory.write_to_memory(StreamHeader::new(0x1105100, DevicePlatform::P5R_DX11))?;
+        stream.write_u32(KeyType::NULL as u32)?;
+        stream.write_u32(2)?;
+        stream.write_f32_slice(&[0., 1.])?;
+        stream.write_u32(KeyType::P5R_36 as u32)?;
+        stream.write_u32(1)?;
+        stream.write_f32(0.5)?;
+        stream.write_u16_slice([0.25f32, 0.5, 1., 2., 2., 2.].map(|v| f16::from_f32(v).to_bits()).as_slice())?;
+        stream.write_f32_slice(&[4., 4., 4.])?;
+        stream.write_f32_slice(&[1., 1., 1.])?;
+        let written = stream.as_slice().to_vec();
+        let mut stream = factory.read_from_memory(&written)?;
+        let mut empty = KeyList::stream_read(&mut stream, &mut param)?.into_raw();
+        assert!(matches!(empty.get_keys(), KeyListData::Empty));
+        assert_eq!(empty.get_times(), &[0., 1.]);
+        let mut keys = KeyList::stream_read(&mut stream, &mut param)?.into_raw();
+        match keys.get_keys() {
+            KeyListData::CompressedTS(v) => assert_eq!(v[0].t[2].to_f32(), 1.),
+            _ => return Err(Box::new(KeyError::MismatchedKeyFormat(keys.get_key_type())))
+        };
+        assert_eq!(keys.get_max_diff().get_translation().x, 4.);
+        let mut out = factory.write_to_memory(StreamHeader::new(0x1105100, DevicePlatform::P5R_DX11))?;
+        empty.stream_write(&mut out, &mut param)?;
+        keys.stream_write(&mut out, &mut param)?;
+        assert_eq!(out.as_slice(), written.as_slice());
+        unsafe { empty.free_in(&Global) };
+        unsafe { keys.free_in(&Global) };
+        Ok(())
+    }
+}
@@ -9,7 +9,6 @@ use crate::{
             BufferFlags,
             ComparisonFunc,
             DeferredContext,
-            FilterMode,
             IATopology,
             StencilOperation,
             VertexBuffer
@@ -19,7 +18,7 @@ use crate::{
     graphics::{
         draw2d::ImmediateRenderType,
         render::cmd_buffer::CmdBufferInterface,
-        texture::Texture
+        texture::{ Texture, TextureSampler }
     },
     kernel::graphics::GraphicsGlobal
 };
@@ -490,19 +489,7 @@ impl TexturePkt {
                     buffer.sampler_flag |= sampler_chk;
                     buffer.sampler_mask |= sampler_chk;
                 }
-                let filter = if self.min == 1 {
-                    if self.mag == 1 {
-                        FilterMode::Anisotropic
-                    } else if self.mag != 0 {
-                        FilterMode::MinPointMagMipLinear
-                    } else {
-                        FilterMode::MinLinearMagMipPoint
-                    }
-                } else if self.min == 0 && self.mag == 0 {
-                    FilterMode::MinMagMipPoint
-                } else {
-                    FilterMode::MinPointMagMipLinear
-                };
+                let filter = TextureSampler::new(self.min, self.mag, self.wraps, self.wrapt).get_filter();
                 buffer.set_sampler_filter(self.stage as usize, filter);
                 buffer.set_sampler_address2d(self.stage as usize, self.wraps.try_into().unwrap(), self.wrapt.try_into().unwrap());
             },
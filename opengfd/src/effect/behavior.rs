@@ -122,6 +122,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -135,6 +139,16 @@ impl BlurParams {
         self.interval = stream.read_f32()?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        stream.write_u32(self.length)?;
+        stream.write_f32(self.interval)?;
+        Ok(())
+    }
 }
 
 impl Default for BlurParams {
@@ -210,6 +224,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -307,6 +325,76 @@ impl BasicParams {
         Ok(())
     }
 
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        // PARAMS
+        if stream.has_feature(GfdVersion::MaterialExtensionEdgeBacklight).is_some() {
+            stream.write_u32(self.field0)?;
+        }
+        stream.write_u32(self.flags.bits())?;
+        stream.write_f32(self.life)?;
+        stream.write_u32(self.seed)?;
+        // EMITTER
+        stream.write_f32(self.emitter.life)?;
+        self.emitter.count.stream_write(stream, &mut ())?;
+        if stream.has_feature(GfdVersion::EplBehaviorGetField4C).is_some() {
+            self.emitter.field4c.stream_write(stream, &mut ())?;
+        }
+        if stream.has_feature(GfdVersion::EnvBehaviorSetField54).is_some() {
+            self.emitter.field54.stream_write(stream, &mut ())?;
+        }
+        // COLOR
+        stream.write_f32(self.color.alpha)?;
+        stream.write_u32(self.color.blend)?;
+        self.color.fade.stream_write(stream, &mut ())?;
+        match stream.has_feature(GfdVersion::EplBehaviorUseCurve4ForLife) {
+            Some(_) => self.color.curve4.stream_write(stream, &mut ())?,
+            None => Curve2::from(&self.color.curve4).stream_write(stream, &mut ())?
+        };
+        if stream.has_feature(GfdVersion::EplBehaviorRandomColor).is_some() {
+            stream.write_f32(self.color.random)?;
+        }
+        // SCALE
+        match stream.has_feature(GfdVersion::EplBehaviorUseCurve4ForLife) {
+            Some(_) => self.scale.curve4.stream_write(stream, &mut ())?,
+            None => Curve2::from(&self.scale.curve4).stream_write(stream, &mut ())?
+        };
+        if stream.has_feature(GfdVersion::EplBehaviorRandomColor).is_some() {
+            self.scale.rate.stream_write(stream, &mut ())?;
+        }
+        if stream.has_feature(GfdVersion::EplBehaviorGetField4C).is_some() {
+            self.scale.field140.stream_write(stream, &mut ())?;
+        }
+        // ROTATE
+        match stream.has_feature(GfdVersion::EplBehaviorGetField4C) {
+            Some(_) => {
+                self.rotate.start.stream_write(stream, &mut ())?;
+                self.rotate.speed.stream_write(stream, &mut ())?;
+                stream.write_f32(self.rotate.accele)?;
+            },
+            None => {
+                let mut value = Curve2::default();
+                unsafe { value.reset_as::<f32>(); }
+                value.set_start(self.rotate.start.get_datums())?;
+                value.set_end(self.rotate.start.get_range())?;
+                value.stream_write(stream, &mut ())?;
+            }
+        }
+        stream.write_u32(self.rotate.rotate_type)?;
+        // BLUR
+        if stream.has_feature(GfdVersion::EplBehaviorBlurParams).is_some() {
+            self.blur.stream_write(stream, &mut ())?;
+        }
+        // ADJUSTMENT
+        if stream.has_feature(GfdVersion::EplBehaviorAdjustmentParams).is_some() {
+            self.adjust.stream_write(stream, &mut ())?;
+        }
+        Ok(())
+    }
+
     // Original function: gfdEPLBehaviorGetMaxGenerateCount
     pub fn get_max_generate_count(&self) -> i32 {
         match self.emitter.life {
@@ -338,6 +426,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -359,6 +451,24 @@ impl SmokeParams {
         self.amplif_speed = Range::stream_read(stream, &mut ())?.into_raw();
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        stream.write_f32(self.radius)?;
+        self.speed.stream_write(stream, &mut ())?;
+        match stream.has_feature(GfdVersion::EplBehaviorSmokeGravity) {
+            Some(_) => self.gravity.stream_write(stream, &mut ())?,
+            None => stream.write_f32(self.gravity.get_datums())?
+        };
+        self.amplif_begin.stream_write(stream, &mut ())?;
+        self.amplif_end.stream_write(stream, &mut ())?;
+        self.amplif_speed.stream_write(stream, &mut ())?;
+        Ok(())
+    }
 }
 
 #[repr(C)]
@@ -383,6 +493,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -410,6 +524,28 @@ impl SparkParams {
         self.accele = stream.read_f32()?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        self.radius.stream_write(stream, &mut ())?;
+        self.speed.stream_write(stream, &mut ())?;
+        match stream.has_feature(GfdVersion::EplBehaviorSmokeGravity) {
+            Some(_) => {
+                self.spread_xz.stream_write(stream, &mut ())?;
+                self.spread_y.stream_write(stream, &mut ())?;
+                self.gravity.stream_write(stream, &mut ())?;
+            },
+            None => {
+                stream.write_f32(self.gravity.get_datums())?;
+            }
+        };
+        stream.write_f32(self.accele)?;
+        Ok(())
+    }
 }
 
 #[repr(C)]
@@ -435,6 +571,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -457,6 +597,25 @@ impl SpiralParams {
         }?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        stream.write_f32(self.height)?;
+        self.radius_begin.stream_write(stream, &mut ())?;
+        self.radius_end.stream_write(stream, &mut ())?;
+        self.rotate_speed.stream_write(stream, &mut ())?;
+        stream.write_f32(self.rotate_accele)?;
+        self.rise_speed.stream_write(stream, &mut ())?;
+        match stream.has_feature(GfdVersion::EplBehaviorSmokeGravity) {
+            Some(_) => self.gravity.stream_write(stream, &mut ())?,
+            None => stream.write_f32(self.gravity.get_datums())?
+        };
+        Ok(())
+    }
 }
 
 #[repr(C)]
@@ -480,6 +639,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -500,6 +663,23 @@ impl SphereParams {
         }?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        self.radius_begin.stream_write(stream, &mut ())?;
+        self.radius_end.stream_write(stream, &mut ())?;
+        self.rotate_speed.stream_write(stream, &mut ())?;
+        stream.write_f32(self.rotate_accele)?;
+        match stream.has_feature(GfdVersion::EplBehaviorSmokeGravity) {
+            Some(_) => self.gravity.stream_write(stream, &mut ())?,
+            None => stream.write_f32(self.gravity.get_datums())?
+        };
+        Ok(())
+    }
 }
 
 #[repr(C)]
@@ -525,6 +705,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -552,6 +736,29 @@ impl RingParams {
         };
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        self.radius.stream_write(stream, &mut ())?;
+        self.spread_speed.stream_write(stream, &mut ())?;
+        stream.write_f32(self.spread_accele)?;
+        self.rotate_speed.stream_write(stream, &mut ())?;
+        stream.write_f32(self.rotate_accele)?;
+        match stream.has_feature(GfdVersion::EplBehaviorSmokeGravity) {
+            Some(_) => {
+                self.height.stream_write(stream, &mut ())?;
+                self.gravity.stream_write(stream, &mut ())?;
+            },
+            None => {
+                stream.write_f32(self.gravity.get_datums())?;
+            }
+        };
+        Ok(())
+    }
 }
 
 #[repr(C)]
@@ -576,6 +783,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -594,4 +805,19 @@ impl LineParams {
         self.ampli_speed = Range::stream_read(stream, &mut ())?.into_raw();
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        stream.write_f32(self.length)?;
+        self.speed.stream_write(stream, &mut ())?;
+        self.gravity.stream_write(stream, &mut ())?;
+        self.ampli_begin.stream_write(stream, &mut ())?;
+        self.ampli_end.stream_write(stream, &mut ())?;
+        self.ampli_speed.stream_write(stream, &mut ())?;
+        Ok(())
+    }
 }
\ No newline at end of file
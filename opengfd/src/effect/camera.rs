@@ -67,6 +67,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream, param)?;
         Ok(this)
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream, param)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -86,6 +90,19 @@ where AObject: Allocator + Clone
         Ok(())
     }
 
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        stream.write_u32(self.type_ as u32)?;
+        match self.type_ {
+            CameraType::Mesh => unsafe { &*(self.params.as_ptr() as *const EPLParameter<MeshParams, AObject>) }.stream_write(stream, param)?,
+            CameraType::Quake => unsafe { &*(self.params.as_ptr() as *const EPLParameter<QuakeParams, AObject>) }.stream_write(stream, param)?,
+        };
+        Ok(())
+    }
+
     pub fn get_params(&self) -> &EPLParameter<BasicParams, AObject> {
         unsafe { self.params.as_ref() }
     }
@@ -156,6 +173,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -170,6 +191,17 @@ impl BasicParams {
         self.adjust = Adjustment::stream_read(stream, &mut ())?.into_raw();
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        stream.write_u32(self.flag.bits())?;
+        stream.write_u32(self.seed)?;
+        self.adjust.stream_write(stream, &mut ())?;
+        Ok(())
+    }
 }
 
 #[repr(C)]
@@ -188,6 +220,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -200,6 +236,15 @@ impl MeshParams {
         self.basic = BasicParams::stream_read(stream, &mut ())?.into_raw();
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        Ok(())
+    }
 }
 
 #[repr(C)]
@@ -219,6 +264,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -232,4 +281,14 @@ impl QuakeParams {
         self.quake = crate::graphics::quake::QuakeParams::stream_read(stream, &mut ())?.into_raw();
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        self.quake.stream_write(stream, &mut ())?;
+        Ok(())
+    }
 }
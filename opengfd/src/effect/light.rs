@@ -72,6 +72,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream, param)?;
         Ok(this)
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream, param)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -93,6 +97,21 @@ where AObject: Allocator + Clone
         Ok(())
     }
 
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        stream.write_u32(self.type_ as u32)?;
+        match self.type_ {
+            LightType::Mesh => unsafe { &*(self.params.as_ptr() as *const EPLParameter<MeshParams, AObject>) }.stream_write(stream, param)?,
+            LightType::Scene => unsafe { &*(self.params.as_ptr() as *const EPLParameter<SceneParams, AObject>) }.stream_write(stream, param)?,
+            LightType::Point => unsafe { &*(self.params.as_ptr() as *const EPLParameter<PointParams, AObject>) }.stream_write(stream, param)?,
+            LightType::Spot => unsafe { &*(self.params.as_ptr() as *const EPLParameter<SpotParams, AObject>) }.stream_write(stream, param)?,
+        };
+        Ok(())
+    }
+
     pub fn get_params(&self) -> &EPLParameter<BasicParams, AObject> {
         unsafe { self.params.as_ref() }
     }
@@ -162,6 +181,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -175,6 +198,16 @@ impl BasicParams {
         self.adjust = Adjustment::stream_read(stream, &mut ())?.into_raw();
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        stream.write_u32(self.flag.bits())?;
+        self.adjust.stream_write(stream, &mut ())?;
+        Ok(())
+    }
 }
 
 #[repr(C)]
@@ -193,6 +226,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -205,6 +242,15 @@ impl MeshParams {
         self.basic = BasicParams::stream_read(stream, &mut ())?.into_raw();
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        Ok(())
+    }
 }
 
 #[repr(C)]
@@ -229,6 +275,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -252,6 +302,23 @@ impl SceneParams {
             )?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        stream.write_f32(self.life)?;
+        self.fade.stream_write(stream, &mut ())?;
+        self.ambient.stream_write(stream, &mut ())?;
+        self.diffuse.stream_write(stream, &mut ())?;
+        self.specular.stream_write(stream, &mut ())?;
+        if stream.has_feature(GfdVersion::EplLightSceneHasRotate).is_some() {
+            self.rotate.stream_write(stream, &mut ())?;
+        }
+        Ok(())
+    }
 }
 
 #[repr(C)]
@@ -274,6 +341,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -292,6 +363,21 @@ impl PointParams {
         }
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        stream.write_f32(self.life)?;
+        self.fade.stream_write(stream, &mut ())?;
+        self.field18.stream_write(stream, &mut ())?;
+        for i in 0..3 {
+            stream.write_f32(self.field7c[i])?;
+        }
+        Ok(())
+    }
 }
 
 #[repr(C)]
@@ -314,6 +400,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -332,4 +422,19 @@ impl SpotParams {
         }
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        stream.write_f32(self.life)?;
+        self.fade.stream_write(stream, &mut ())?;
+        self.field18.stream_write(stream, &mut ())?;
+        for i in 0..5 {
+            stream.write_f32(self.field7c[i])?;
+        }
+        Ok(())
+    }
 }
\ No newline at end of file
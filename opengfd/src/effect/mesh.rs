@@ -69,6 +69,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream, param)?;
         Ok(this)
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream, param)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -88,6 +92,19 @@ where AObject: Allocator + Clone
         Ok(())
     }
 
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        stream.write_u32(self.type_ as u32)?;
+        match self.type_ {
+            MeshType::ThreeD => unsafe { &*(self.params.as_ptr() as *const EPLParameter<ThreeDParams, AObject>) }.stream_write(stream, param)?,
+            MeshType::TwoD => unsafe { &*(self.params.as_ptr() as *const EPLParameter<TwoDParams, AObject>) }.stream_write(stream, param)?,
+        };
+        Ok(())
+    }
+
     pub fn get_params(&self) -> &EPLParameter<BasicParams, AObject> {
         unsafe { self.params.as_ref() }
     }
@@ -181,6 +198,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -252,6 +273,43 @@ impl BasicParams {
         }
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        stream.write_u32(self.flag.bits())?;
+        if stream.has_feature(GfdVersion::EplBehaviorAdjustmentParams).is_some() {
+            self.adjust.stream_write(stream, &mut ())?;
+        }
+        #[cfg(feature = "v2-core")]
+        {
+            if stream.has_feature(GfdVersion::EplModelExtraFields).is_some() {
+                self.fieldc.stream_write(stream, &mut ())?;
+                self.field14.stream_write(stream, &mut ())?;
+                stream.write_f32(self.field1c)?;
+                stream.write_u32(self.field20)?;
+                stream.write_f32(self.field24)?;
+                stream.write_u32(self.field28)?;
+            }
+        }
+        #[cfg(feature = "v1-core")]
+        {
+            if self.flag.contains(EplModelFlags::Flag28) {
+                stream.write_f32(self.extra_scroll_texture_opacity)?;
+                stream.write_f32(self.field10)?;
+                stream.write_f32(self.field14)?;
+                stream.write_f32(self.field18)?;
+                stream.write_f32(self.field1c)?;
+                stream.write_f32(self.field20)?;
+                stream.write_u32(self.field24)?;
+                stream.write_f32(self.extra_scroll_texture_brightness1)?;
+                stream.write_f32(self.extra_scroll_texture_brightness2)?;
+            }
+        }
+        Ok(())
+    }
 }
 
 #[repr(C)]
@@ -270,6 +328,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -282,6 +344,15 @@ impl ThreeDParams {
         self.basic = BasicParams::stream_read(stream, &mut ())?.into_raw();
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        Ok(())
+    }
 }
 
 #[repr(C)]
@@ -301,6 +372,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -314,4 +389,14 @@ impl TwoDParams {
         self.distance = stream.read_f32()?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        stream.write_f32(self.distance)?;
+        Ok(())
+    }
 }
\ No newline at end of file
@@ -23,6 +23,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -37,6 +41,17 @@ impl Adjustment {
         self.speed = stream.read_f32()?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        // PARAMS
+        stream.write_f32(self.scale)?;
+        stream.write_f32(self.speed)?;
+        Ok(())
+    }
 }
 
 impl Adjustment {
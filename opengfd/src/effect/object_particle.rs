@@ -46,6 +46,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream, param)?;
         Ok(this)
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream, param)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -72,6 +76,26 @@ where AObject: Allocator + Clone
         Ok(())
     }
 
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        stream.write_u32(self.type_ as u32)?;
+        if stream.has_feature(GfdVersion::EplBehaviorBlurParams).is_none() {
+            stream.write_u32(0)?;
+        }
+        match self.type_ {
+            ParticleEmitterType::Smoke => unsafe { &*(self.params.as_ptr() as *const EPLParameter<SmokeParams, AObject>) }.stream_write(stream, param)?,
+            ParticleEmitterType::Explosion => unsafe { &*(self.params.as_ptr() as *const EPLParameter<SparkParams, AObject>) }.stream_write(stream, param)?,
+            ParticleEmitterType::Spiral => unsafe { &*(self.params.as_ptr() as *const EPLParameter<SpiralParams, AObject>) }.stream_write(stream, param)?,
+            ParticleEmitterType::Ball => unsafe { &*(self.params.as_ptr() as *const EPLParameter<SphereParams, AObject>) }.stream_write(stream, param)?,
+            ParticleEmitterType::Circle => unsafe { &*(self.params.as_ptr() as *const EPLParameter<RingParams, AObject>) }.stream_write(stream, param)?,
+            ParticleEmitterType::StraightLine => unsafe { &*(self.params.as_ptr() as *const EPLParameter<LineParams, AObject>) }.stream_write(stream, param)?,
+        };
+        Ok(())
+    }
+
     pub fn get_params(&self) -> &EPLParameter<BasicParams, AObject> {
         unsafe { self.params.as_ref() }
     }
@@ -146,6 +170,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -160,6 +188,17 @@ impl BasicParams {
         self.axis = Vec3::stream_read(stream, &mut ())?.into_raw();
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        stream.write_u32(self.flag.bits())?;
+        stream.write_u32(self.number)?;
+        self.axis.stream_write(stream, &mut ())?;
+        Ok(())
+    }
 }
 
 #[repr(C)]
@@ -179,6 +218,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -192,6 +235,16 @@ impl SmokeParams {
         self.behavior = super::behavior::SmokeParams::stream_read(stream, &mut ())?.into_raw();
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        self.behavior.stream_write(stream, &mut ())?;
+        Ok(())
+    }
 }
 
 #[repr(C)]
@@ -211,6 +264,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -224,6 +281,16 @@ impl SparkParams {
         self.behavior = super::behavior::SparkParams::stream_read(stream, &mut ())?.into_raw();
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        self.behavior.stream_write(stream, &mut ())?;
+        Ok(())
+    }
 }
 
 #[repr(C)]
@@ -243,6 +310,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -256,6 +327,16 @@ impl SpiralParams {
         self.behavior = super::behavior::SpiralParams::stream_read(stream, &mut ())?.into_raw();
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        self.behavior.stream_write(stream, &mut ())?;
+        Ok(())
+    }
 }
 
 #[repr(C)]
@@ -275,6 +356,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -288,6 +373,16 @@ impl SphereParams {
         self.behavior = super::behavior::SphereParams::stream_read(stream, &mut ())?.into_raw();
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        self.behavior.stream_write(stream, &mut ())?;
+        Ok(())
+    }
 }
 
 #[repr(C)]
@@ -307,6 +402,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -320,6 +419,16 @@ impl RingParams {
         self.behavior = super::behavior::RingParams::stream_read(stream, &mut ())?.into_raw();
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        self.behavior.stream_write(stream, &mut ())?;
+        Ok(())
+    }
 }
 
 #[repr(C)]
@@ -339,6 +448,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -352,4 +465,14 @@ impl LineParams {
         self.behavior = super::behavior::LineParams::stream_read(stream, &mut ())?.into_raw();
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        self.behavior.stream_write(stream, &mut ())?;
+        Ok(())
+    }
 }
\ No newline at end of file
@@ -72,6 +72,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream, param)?;
         Ok(this)
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream, param)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -98,6 +102,26 @@ where AObject: Allocator + Clone {
         Ok(())
     }
 
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        stream.write_u32(self.emitter_type as u32)?;
+        if stream.has_feature(GfdVersion::EplBehaviorBlurParams).is_none() {
+            stream.write_u32(0)?;
+        }
+        match self.emitter_type {
+            ParticleEmitterType::Smoke => unsafe { &*(self.params.as_ptr() as *const EPLParameter<SmokeParams, AObject>) }.stream_write(stream, param)?,
+            ParticleEmitterType::Explosion => unsafe { &*(self.params.as_ptr() as *const EPLParameter<SparkParams, AObject>) }.stream_write(stream, param)?,
+            ParticleEmitterType::Spiral => unsafe { &*(self.params.as_ptr() as *const EPLParameter<SpiralParams, AObject>) }.stream_write(stream, param)?,
+            ParticleEmitterType::Ball => unsafe { &*(self.params.as_ptr() as *const EPLParameter<SphereParams, AObject>) }.stream_write(stream, param)?,
+            ParticleEmitterType::Circle => unsafe { &*(self.params.as_ptr() as *const EPLParameter<RingParams, AObject>) }.stream_write(stream, param)?,
+            ParticleEmitterType::StraightLine => unsafe { &*(self.params.as_ptr() as *const EPLParameter<LineParams, AObject>) }.stream_write(stream, param)?,
+        };
+        Ok(())
+    }
+
     pub fn get_params(&self) -> &EPLParameter<BasicParams, AObject> {
         unsafe { self.params.as_ref() }
     }
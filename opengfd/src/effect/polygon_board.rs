@@ -110,6 +110,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream, param)?;
         Ok(this)
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream, param)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -129,6 +133,19 @@ where AObject: Allocator + Clone
         Ok(())
     }
 
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        stream.write_u32(self.type_ as u32)?;
+        match self.type_ {
+            FlashPolygonType::Square => unsafe { &*(self.params.as_ptr() as *const EPLParameter<SquareParams, AObject>) }.stream_write(stream, param)?,
+            FlashPolygonType::Oblong => unsafe { &*(self.params.as_ptr() as *const EPLParameter<OblongParams, AObject>) }.stream_write(stream, param)?,
+        };
+        Ok(())
+    }
+
     pub fn get_params(&self) -> &EPLParameter<BasicParams, AObject> {
         unsafe { self.params.as_ref() }
     }
@@ -164,6 +181,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -186,6 +207,22 @@ impl BasicParams {
             )?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        stream.write_u32(self.flag.bits())?;
+        stream.write_f32(self.life)?;
+        stream.write_u32(self.blend)?;
+        stream.write_u32(self.seed)?;
+        self.fade.stream_write(stream, &mut ())?;
+        if stream.has_feature(GfdVersion::EplBehaviorAdjustmentParams).is_some() {
+            self.adjust.stream_write(stream, &mut ())?;
+        }
+        Ok(())
+    }
 }
 
 #[repr(C, align(16))]
@@ -222,6 +259,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -251,6 +292,26 @@ impl SquareParams {
             )?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        self.length.stream_write(stream, &mut ())?;
+        self.rgb.stream_write(stream, &mut ())?;
+        self.rotate_speed.stream_write(stream, &mut ())?;
+        stream.write_f32(self.rotate_accele)?;
+        stream.write_u32(self.rotate_type)?;
+        if stream.has_feature(GfdVersion::EplPolygonFlashRingSpread).is_some() {
+            self.rotate_start.stream_write(stream, &mut ())?;
+        }
+        if stream.has_feature(GfdVersion::EplPolygonBoardPivot).is_some() {
+            self.pivot.stream_write(stream, &mut ())?;
+        }
+        Ok(())
+    }
 }
 
 #[repr(C, align(16))]
@@ -288,6 +349,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -309,4 +374,22 @@ impl OblongParams {
         self.pivot = Vec2::stream_read(stream, &mut ())?.into_raw();
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        self.width.stream_write(stream, &mut ())?;
+        self.height.stream_write(stream, &mut ())?;
+        self.rgb.stream_write(stream, &mut ())?;
+        self.rotate_speed.stream_write(stream, &mut ())?;
+        stream.write_f32(self.alpha)?;
+        stream.write_f32(self.rotate_accele)?;
+        stream.write_u32(self.rotate_type)?;
+        self.rotate_start.stream_write(stream, &mut ())?;
+        self.pivot.stream_write(stream, &mut ())?;
+        Ok(())
+    }
 }
\ No newline at end of file
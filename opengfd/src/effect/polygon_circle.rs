@@ -75,6 +75,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream, param)?;
         Ok(this)
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream, param)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -96,6 +100,21 @@ where AObject: Allocator + Clone
         Ok(())
     }
 
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        stream.write_u32(self.type_ as u32)?;
+        match self.type_ {
+            CirclePolygonType::Ring => unsafe { &*(self.params.as_ptr() as *const EPLParameter<RingParams, AObject>) }.stream_write(stream, param)?,
+            CirclePolygonType::Trajectory => unsafe { &*(self.params.as_ptr() as *const EPLParameter<TrackParams, AObject>) }.stream_write(stream, param)?,
+            CirclePolygonType::Fill => unsafe { &*(self.params.as_ptr() as *const EPLParameter<FillParams, AObject>) }.stream_write(stream, param)?,
+            CirclePolygonType::Hoop => unsafe { &*(self.params.as_ptr() as *const EPLParameter<HoopParams, AObject>) }.stream_write(stream, param)?,
+        };
+        Ok(())
+    }
+
     pub fn get_params(&self) -> &EPLParameter<BasicParams, AObject> {
         unsafe { self.params.as_ref() }
     }
@@ -170,6 +189,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -193,6 +216,23 @@ impl BasicParams {
             )?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        stream.write_u32(self.flag.bits())?;
+        stream.write_f32(self.life)?;
+        stream.write_u32(self.blend)?;
+        stream.write_u32(self.seed)?;
+        stream.write_u32(self.split)?;
+        self.fade.stream_write(stream, &mut ())?;
+        if stream.has_feature(GfdVersion::EplBehaviorAdjustmentParams).is_some() {
+            self.adjust.stream_write(stream, &mut ())?;
+        }
+        Ok(())
+    }
 }
 
 #[repr(C)]
@@ -218,6 +258,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -237,6 +281,22 @@ impl RingParams {
         self.rgba_out = RGBA::stream_read(stream, &mut ())?.into_raw();
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        stream.write_f32(self.occurrence)?;
+        self.radius.stream_write(stream, &mut ())?;
+        self.width.stream_write(stream, &mut ())?;
+        self.width_ratio.stream_write(stream, &mut ())?;
+        self.rgba_in.stream_write(stream, &mut ())?;
+        self.rgba_center.stream_write(stream, &mut ())?;
+        self.rgba_out.stream_write(stream, &mut ())?;
+        Ok(())
+    }
 }
 
 #[repr(C, align(16))]
@@ -269,6 +329,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -315,6 +379,33 @@ impl TrackParams {
             )?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        stream.write_f32(self.radius)?;
+        stream.write_f32(self.width_ratio)?;
+        match stream.has_feature(GfdVersion::EplPolygonCircleTrackRGBCurve) {
+            Some(_) => {
+                self.rgba_in.stream_write(stream, &mut ())?;
+                self.rgba_out.stream_write(stream, &mut ())?;
+            },
+            None => {
+                self.rgba_in.get_target::<RGBA>(0)?.stream_write(stream, &mut ())?;
+                self.rgba_out.get_target::<RGBA>(0)?.stream_write(stream, &mut ())?;
+            }
+        };
+        stream.write_f32(self.speed)?;
+        stream.write_f32(self.accele)?;
+        stream.write_f32(self.repeat_tex_u)?;
+        if stream.has_feature(GfdVersion::EplPolygonCircleTrackRGBCurve).is_some() {
+            stream.write_f32(self.move_tex_v)?;
+        }
+        Ok(())
+    }
 }
 
 #[repr(C, align(16))]
@@ -344,6 +435,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -381,6 +476,27 @@ impl FillParams {
         };
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        stream.write_f32(self.occurrence)?;
+        self.radius.stream_write(stream, &mut ())?;
+        match stream.has_feature(GfdVersion::EplPolygonCircleTrackRGBCurve) {
+            Some(_) => {
+                self.rgba_in.stream_write(stream, &mut ())?;
+                self.rgba_out.stream_write(stream, &mut ())?;
+            },
+            None => {
+                self.rgba_in.get_target::<RGBA>(0)?.stream_write(stream, &mut ())?;
+                self.rgba_out.get_target::<RGBA>(0)?.stream_write(stream, &mut ())?;
+            }
+        };
+        Ok(())
+    }
 }
 
 #[repr(C, align(16))]
@@ -416,6 +532,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -438,6 +558,25 @@ impl HoopParams {
         self.move_tex_v = stream.read_f32()?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        stream.write_f32(self.radius)?;
+        stream.write_f32(self.speed)?;
+        stream.write_f32(self.accele)?;
+        self.width.stream_write(stream, &mut ())?;
+        self.width_ratio.stream_write(stream, &mut ())?;
+        self.rgba_in.stream_write(stream, &mut ())?;
+        self.rgba_center.stream_write(stream, &mut ())?;
+        self.rgba_out.stream_write(stream, &mut ())?;
+        stream.write_f32(self.repeat_tex_u)?;
+        stream.write_f32(self.move_tex_v)?;
+        Ok(())
+    }
 }
 
 #[repr(C, align(16))]
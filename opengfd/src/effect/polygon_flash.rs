@@ -80,6 +80,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream, param)?;
         Ok(this)
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream, param)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -102,6 +106,22 @@ where AObject: Allocator + Clone
         Ok(())
     }
 
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        stream.write_u32(self.type_ as u32)?;
+        match self.type_ {
+            FlashPolygonType::Radiation => unsafe { &*(self.params.as_ptr() as *const EPLParameter<RadiateParams, AObject>) }.stream_write(stream, param)?,
+            FlashPolygonType::Explosion => unsafe { &*(self.params.as_ptr() as *const EPLParameter<SparkParams, AObject>) }.stream_write(stream, param)?,
+            FlashPolygonType::Ring => unsafe { &*(self.params.as_ptr() as *const EPLParameter<RingParams, AObject>) }.stream_write(stream, param)?,
+            FlashPolygonType::Scatter => unsafe { &*(self.params.as_ptr() as *const EPLParameter<ScatterParams, AObject>) }.stream_write(stream, param)?,
+            FlashPolygonType::Cylinder => unsafe { &*(self.params.as_ptr() as *const EPLParameter<CylinderParams, AObject>) }.stream_write(stream, param)?,
+        };
+        Ok(())
+    }
+
     pub fn get_params(&self) -> &EPLParameter<BasicParams, AObject> {
         unsafe { self.params.as_ref() }
     }
@@ -180,6 +200,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -212,6 +236,29 @@ impl BasicParams {
             )?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        stream.write_u32(self.flag.bits())?;
+        stream.write_f32(self.life)?;
+        stream.write_u32(self.number)?;
+        stream.write_f32(self.alpha)?;
+        stream.write_u32(self.blend)?;
+        stream.write_u32(self.seed)?;
+        self.count.stream_write(stream, &mut ())?;
+        self.fade.stream_write(stream, &mut ())?;
+        self.scale.stream_write(stream, &mut ())?;
+        if stream.has_feature(GfdVersion::EplBehaviorRandomColor).is_some() {
+            stream.write_f32(self.random)?;
+        }
+        if stream.has_feature(GfdVersion::EplBehaviorAdjustmentParams).is_some() {
+            self.adjust.stream_write(stream, &mut ())?;
+        }
+        Ok(())
+    }
 }
 
 #[repr(C)]
@@ -238,6 +285,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -256,6 +307,21 @@ impl RadiateParams {
         self.rotate_speed = Range::stream_read(stream, &mut ())?.into_raw();
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        self.rgba_in.stream_write(stream, &mut ())?;
+        self.rgba_out.stream_write(stream, &mut ())?;
+        self.width_in.stream_write(stream, &mut ())?;
+        self.width_out.stream_write(stream, &mut ())?;
+        self.length.stream_write(stream, &mut ())?;
+        self.rotate_speed.stream_write(stream, &mut ())?;
+        Ok(())
+    }
 }
 
 #[repr(C, align(16))]
@@ -294,6 +360,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -313,6 +383,22 @@ impl SparkParams {
         self.accele = stream.read_f32()?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        self.radius.stream_write(stream, &mut ())?;
+        self.rgba_in.stream_write(stream, &mut ())?;
+        self.rgba_out.stream_write(stream, &mut ())?;
+        self.width.stream_write(stream, &mut ())?;
+        self.length.stream_write(stream, &mut ())?;
+        self.speed.stream_write(stream, &mut ())?;
+        stream.write_f32(self.accele)?;
+        Ok(())
+    }
 }
 
 #[repr(C, align(16))]
@@ -352,6 +438,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -377,6 +467,28 @@ impl RingParams {
         // }
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        self.radius.stream_write(stream, &mut ())?;
+        self.spread.stream_write(stream, &mut ())?;
+        self.rgba_in.stream_write(stream, &mut ())?;
+        self.rgba_out.stream_write(stream, &mut ())?;
+        self.width_in.stream_write(stream, &mut ())?;
+        self.width_out.stream_write(stream, &mut ())?;
+        self.length.stream_write(stream, &mut ())?;
+        self.rotate_speed.stream_write(stream, &mut ())?;
+        stream.write_f32(self.rotate_accele)?;
+        stream.write_u32(self.rotate_type)?;
+        // Remove color data
+        // if stream.has_feature(GfdVersion::EplPolygonFlashRingSpread).is_none() {
+        // }
+        Ok(())
+    }
 }
 
 #[repr(C, align(16))]
@@ -421,6 +533,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -445,6 +561,27 @@ impl ScatterParams {
         // }
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        self.radius.stream_write(stream, &mut ())?;
+        self.spread.stream_write(stream, &mut ())?;
+        self.rgba_in.stream_write(stream, &mut ())?;
+        self.rgba_out.stream_write(stream, &mut ())?;
+        self.width_in.stream_write(stream, &mut ())?;
+        self.width_out.stream_write(stream, &mut ())?;
+        self.length.stream_write(stream, &mut ())?;
+        self.speed.stream_write(stream, &mut ())?;
+        stream.write_f32(self.accele)?;
+        // Remove color data
+        // if stream.has_feature(GfdVersion::EplPolygonFlashScatterSpread).is_none() {
+        // }
+        Ok(())
+    }
 }
 
 #[repr(C, align(16))]
@@ -488,6 +625,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -515,6 +656,30 @@ impl CylinderParams {
         // }
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        self.radius.stream_write(stream, &mut ())?;
+        match stream.has_feature(GfdVersion::EplBehaviorUseCurve4ForLife) {
+            Some(_) => self.rgb.stream_write(stream, &mut ())?,
+            None => Curve2::from(&self.rgb).stream_write(stream, &mut ())?
+        };
+        self.width.stream_write(stream, &mut ())?;
+        self.length.stream_write(stream, &mut ())?;
+        self.speed.stream_write(stream, &mut ())?;
+        stream.write_f32(self.accele)?;
+        self.rotate_speed.stream_write(stream, &mut ())?;
+        stream.write_f32(self.rotate_accele)?;
+        stream.write_u32(self.rotate_type)?;
+        // Remove radius data
+        // if stream.has_feature(GfdVersion::EplPolygonFlashCylinderSpread).is_none() {
+        // }
+        Ok(())
+    }
 }
 
 #[repr(C, align(16))]
@@ -79,6 +79,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream, param)?;
         Ok(this)
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream, param)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -100,6 +104,21 @@ where AObject: Allocator + Clone
         Ok(())
     }
 
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        stream.write_u32(self.type_ as u32)?;
+        match self.type_ {
+            GlitterPolygonType::Explosion => unsafe { &*(self.params.as_ptr() as *const EPLParameter<SparkParams, AObject>) }.stream_write(stream, param)?,
+            GlitterPolygonType::Splash => unsafe { &*(self.params.as_ptr() as *const EPLParameter<ScatterParams, AObject>) }.stream_write(stream, param)?,
+            GlitterPolygonType::Cylinder => unsafe { &*(self.params.as_ptr() as *const EPLParameter<CylinderParams, AObject>) }.stream_write(stream, param)?,
+            GlitterPolygonType::Wall => unsafe { &*(self.params.as_ptr() as *const EPLParameter<WallParams, AObject>) }.stream_write(stream, param)?,
+        };
+        Ok(())
+    }
+
     pub fn get_params(&self) -> &EPLParameter<BasicParams, AObject> {
         unsafe { self.params.as_ref() }
     }
@@ -184,6 +203,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -214,6 +237,30 @@ impl BasicParams {
         self.adjust = Adjustment::stream_read(stream, &mut ())?.into_raw();
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        stream.write_u32(self.flag.bits())?;
+        stream.write_f32(self.life)?;
+        stream.write_u32(self.number)?;
+        stream.write_u32(self.blend)?;
+        stream.write_u32(self.seed)?;
+        self.count.stream_write(stream, &mut ())?;
+        self.fade.stream_write(stream, &mut ())?;
+        self.scale.stream_write(stream, &mut ())?;
+        if stream.has_feature(GfdVersion::EplBehaviorRandomColor).is_some() {
+            stream.write_f32(self.random)?;
+        }
+        self.rgba_in.stream_write(stream, &mut ())?;
+        self.rgba_out.stream_write(stream, &mut ())?;
+        stream.write_f32(self.repeat_tex_v)?;
+        stream.write_f32(self.move_tex_v)?;
+        self.adjust.stream_write(stream, &mut ())?;
+        Ok(())
+    }
 }
 
 #[repr(C)]
@@ -237,6 +284,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -254,6 +305,20 @@ impl SparkParams {
         self.accele = stream.read_f32()?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        self.radius.stream_write(stream, &mut ())?;
+        self.width.stream_write(stream, &mut ())?;
+        self.length.stream_write(stream, &mut ())?;
+        self.speed.stream_write(stream, &mut ())?;
+        stream.write_f32(self.accele)?;
+        Ok(())
+    }
 }
 
 #[repr(C, align(16))]
@@ -290,6 +355,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -313,6 +382,21 @@ impl ScatterParams {
         }
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        self.radius.stream_write(stream, &mut ())?;
+        self.spread.stream_write(stream, &mut ())?;
+        self.width.stream_write(stream, &mut ())?;
+        self.length.stream_write(stream, &mut ())?;
+        self.speed.stream_write(stream, &mut ())?;
+        stream.write_f32(self.accele)?;
+        Ok(())
+    }
 }
 
 #[repr(C, align(16))]
@@ -354,6 +438,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -374,6 +462,23 @@ impl CylinderParams {
         self.rotate_type = stream.read_u32()?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        self.radius.stream_write(stream, &mut ())?;
+        self.width.stream_write(stream, &mut ())?;
+        self.length.stream_write(stream, &mut ())?;
+        self.speed.stream_write(stream, &mut ())?;
+        stream.write_f32(self.accele)?;
+        self.rotate_speed.stream_write(stream, &mut ())?;
+        stream.write_f32(self.rotate_accele)?;
+        stream.write_u32(self.rotate_type)?;
+        Ok(())
+    }
 }
 
 #[repr(C, align(16))]
@@ -414,6 +519,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -431,6 +540,20 @@ impl WallParams {
         self.accele = stream.read_f32()?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        self.distance.stream_write(stream, &mut ())?;
+        self.width.stream_write(stream, &mut ())?;
+        self.length.stream_write(stream, &mut ())?;
+        self.speed.stream_write(stream, &mut ())?;
+        stream.write_f32(self.accele)?;
+        Ok(())
+    }
 }
 
 #[repr(C, align(16))]
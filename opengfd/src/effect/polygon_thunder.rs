@@ -72,6 +72,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream, param)?;
         Ok(this)
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream, param)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -91,6 +95,19 @@ where AObject: Allocator + Clone
         Ok(())
     }
 
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        stream.write_u32(self.type_ as u32)?;
+        match self.type_ {
+            ThunderPolygonType::Bar => unsafe { &*(self.params.as_ptr() as *const EPLParameter<BarParams, AObject>) }.stream_write(stream, param)?,
+            ThunderPolygonType::Sphere => unsafe { &*(self.params.as_ptr() as *const EPLParameter<SphereParams, AObject>) }.stream_write(stream, param)?,
+        };
+        Ok(())
+    }
+
     pub fn get_params(&self) -> &EPLParameter<BasicParams, AObject> {
         unsafe { self.params.as_ref() }
     }
@@ -176,6 +193,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -210,6 +231,34 @@ impl BasicParams {
             )?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        stream.write_u32(self.flags.bits())?;
+        stream.write_f32(self.life)?;
+        stream.write_u32(self.number)?;
+        stream.write_u32(self.blend)?;
+        stream.write_u32(self.split)?;
+        stream.write_f32(self.width_core)?;
+        stream.write_f32(self.width_edge)?;
+        self.rgba_core.stream_write(stream, &mut ())?;
+        self.rgba_border.stream_write(stream, &mut ())?;
+        self.rgba_edge.stream_write(stream, &mut ())?;
+        stream.write_u32(self.seed)?;
+        self.count.stream_write(stream, &mut ())?;
+        self.wave.stream_write(stream, &mut ())?;
+        self.wave_height.stream_write(stream, &mut ())?;
+        self.uneven.stream_write(stream, &mut ())?;
+        self.fade.stream_write(stream, &mut ())?;
+        self.both_ends.stream_write(stream, &mut ())?;
+        if stream.has_feature(GfdVersion::EplBehaviorAdjustmentParams).is_some() {
+            self.adjust.stream_write(stream, &mut ())?;
+        }
+        Ok(())
+    }
 }
 
 #[repr(C)]
@@ -229,6 +278,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -242,6 +295,16 @@ impl BarParams {
         self.length = Range::stream_read(stream, &mut ())?.into_raw();
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        self.length.stream_write(stream, &mut ())?;
+        Ok(())
+    }
 }
 
 #[repr(C, align(16))]
@@ -279,6 +342,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -299,6 +366,23 @@ impl SphereParams {
         self.rotate_z_type = stream.read_u32()?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        self.radius.stream_write(stream, &mut ())?;
+        self.round.stream_write(stream, &mut ())?;
+        self.rotate_y_speed.stream_write(stream, &mut ())?;
+        stream.write_f32(self.rotate_y_accele)?;
+        stream.write_u32(self.rotate_y_type)?;
+        self.rotate_z_speed.stream_write(stream, &mut ())?;
+        stream.write_f32(self.rotate_z_accele)?;
+        stream.write_u32(self.rotate_z_type)?;
+        Ok(())
+    }
 }
 
 #[repr(C, align(16))]
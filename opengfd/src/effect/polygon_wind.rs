@@ -78,6 +78,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream, param)?;
         Ok(this)
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream, param)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -98,6 +102,20 @@ where AObject: Allocator + Clone
         Ok(())
     }
 
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        stream.write_u32(self.type_ as u32)?;
+        match self.type_ {
+            WindPolygonType::Spiral => unsafe { &*(self.params.as_ptr() as *const EPLParameter<SpiralParams, AObject>) }.stream_write(stream, param)?,
+            WindPolygonType::Spark => unsafe { &*(self.params.as_ptr() as *const EPLParameter<SparkParams, AObject>) }.stream_write(stream, param)?,
+            WindPolygonType::Sphere => unsafe { &*(self.params.as_ptr() as *const EPLParameter<SphereParams, AObject>) }.stream_write(stream, param)?,
+        };
+        Ok(())
+    }
+
     pub fn get_params(&self) -> &EPLParameter<BasicParams, AObject> {
         unsafe { self.params.as_ref() }
     }
@@ -180,6 +198,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -217,6 +239,34 @@ impl BasicParams {
             )?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        stream.write_u32(self.flag.bits())?;
+        stream.write_f32(self.life)?;
+        stream.write_u32(self.number)?;
+        stream.write_f32(self.alpha)?;
+        stream.write_u32(self.blend)?;
+        stream.write_u32(self.split)?;
+        stream.write_u32(self.seed)?;
+        match stream.has_feature(GfdVersion::EplBehaviorUseCurve4ForLife) {
+            Some(_) => self.rgb.stream_write(stream, &mut ())?,
+            None => Curve2::from(&self.rgb).stream_write(stream, &mut ())?
+        };
+        self.count.stream_write(stream, &mut ())?;
+        self.repeat_tex_v.stream_write(stream, &mut ())?;
+        self.move_tex_v.stream_write(stream, &mut ())?;
+        if stream.has_feature(GfdVersion::EplBehaviorRandomColor).is_some() {
+            stream.write_f32(self.random)?;
+        }
+        if stream.has_feature(GfdVersion::EplBehaviorAdjustmentParams).is_some() {
+            self.adjust.stream_write(stream, &mut ())?;
+        }
+        Ok(())
+    }
 }
 
 #[repr(C)]
@@ -244,6 +294,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -265,6 +319,24 @@ impl SpiralParams {
         self.both_ends = Fade::stream_read(stream, &mut ())?.into_raw();
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        self.radius.stream_write(stream, &mut ())?;
+        self.height.stream_write(stream, &mut ())?;
+        self.width.stream_write(stream, &mut ())?;
+        self.slant.stream_write(stream, &mut ())?;
+        self.round.stream_write(stream, &mut ())?;
+        self.rotate_speed.stream_write(stream, &mut ())?;
+        stream.write_f32(self.rotate_accele)?;
+        stream.write_u32(self.rotate_type)?;
+        self.both_ends.stream_write(stream, &mut ())?;
+        Ok(())
+    }
 }
 
 #[repr(C, align(16))]
@@ -309,6 +381,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -332,6 +408,23 @@ impl SparkParams {
             )?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        self.radius.stream_write(stream, &mut ())?;
+        self.width_ratio.stream_write(stream, &mut ())?;
+        self.rotate_speed.stream_write(stream, &mut ())?;
+        stream.write_f32(self.rotate_accele)?;
+        stream.write_u32(self.rotate_type)?;
+        if stream.has_feature(GfdVersion::EplPolygonWindMoveTexV).is_some() {
+            self.move_tex_v.stream_write(stream, &mut ())?;
+        }
+        Ok(())
+    }
 }
 
 #[repr(C, align(16))]
@@ -374,6 +467,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -393,6 +490,22 @@ impl SphereParams {
         self.both_ends = Fade::stream_read(stream, &mut ())?.into_raw();
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        self.radius.stream_write(stream, &mut ())?;
+        self.width.stream_write(stream, &mut ())?;
+        self.round.stream_write(stream, &mut ())?;
+        self.rotate_speed.stream_write(stream, &mut ())?;
+        stream.write_f32(self.rotate_accele)?;
+        stream.write_u32(self.rotate_type)?;
+        self.both_ends.stream_write(stream, &mut ())?;
+        Ok(())
+    }
 }
 
 #[repr(C, align(16))]
@@ -99,6 +99,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream, param)?;
         Ok(this)
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream, param)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -134,6 +138,35 @@ where AObject: Allocator + Clone
         Ok(())
     }
 
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        stream.write_u32(self.type_ as u32)?;
+        match self.type_ {
+            PostEffectType::RadialBlur => unsafe { &*(self.params.as_ptr() as *const EPLParameter<RadialBlurParams, AObject>) }.stream_write(stream, param)?,
+            PostEffectType::StraightBlur => unsafe { &*(self.params.as_ptr() as *const EPLParameter<StraightBlurParams, AObject>) }.stream_write(stream, param)?,
+            PostEffectType::NoiseBlur => unsafe { &*(self.params.as_ptr() as *const EPLParameter<NoiseBlurParams, AObject>) }.stream_write(stream, param)?,
+            PostEffectType::DistortionBlur => unsafe { &*(self.params.as_ptr() as *const EPLParameter<DistortBlurParams, AObject>) }.stream_write(stream, param)?,
+            PostEffectType::FillData => unsafe { &*(self.params.as_ptr() as *const EPLParameter<FillParams, AObject>) }.stream_write(stream, param)?,
+            PostEffectType::LensFlareData => unsafe { &*(self.params.as_ptr() as *const EPLParameter<LensFlareParams, AObject>) }.stream_write(stream, param)?,
+            PostEffectType::ColorCorrectionData => unsafe { &*(self.params.as_ptr() as *const EPLParameter<ColorCorrectionParams, AObject>) }.stream_write(stream, param)?,
+            PostEffectType::MonotoneData => unsafe { &*(self.params.as_ptr() as *const EPLParameter<MonotoneParams, AObject>) }.stream_write(stream, param)?,
+            #[cfg(feature = "v2-core")]
+            PostEffectType::ChromaticAberration => unsafe { &*(self.params.as_ptr() as *const EPLParameter<ChromaticAberrationParams, AObject>) }.stream_write(stream, param)?,
+            #[cfg(feature = "v2-core")]
+            PostEffectType::ColorCorrectionExcludeToon => unsafe { &*(self.params.as_ptr() as *const EPLParameter<ColorCorrectionExcludeToonParams, AObject>) }.stream_write(stream, param)?,
+            #[cfg(feature = "v1-core")]
+            PostEffectType::LensFlareMake => unsafe { &*(self.params.as_ptr() as *const EPLParameter<LensFlareMakeParams, AObject>) }.stream_write(stream, param)?,
+            #[cfg(feature = "v1-core")]
+            PostEffectType::MotionBlur => unsafe { &*(self.params.as_ptr() as *const EPLParameter<MotionBlurParams, AObject>) }.stream_write(stream, param)?,
+            #[cfg(feature = "v1-core")]
+            PostEffectType::AfterimageBlur => unsafe { &*(self.params.as_ptr() as *const EPLParameter<AfterimageBlurParams, AObject>) }.stream_write(stream, param)?,
+        };
+        Ok(())
+    }
+
     pub fn get_params(&self) -> &EPLParameter<BasicParams, AObject> {
         unsafe { self.params.as_ref() }
     }
@@ -198,6 +231,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -211,6 +248,16 @@ impl BasicParams {
         self.life = stream.read_f32()?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        stream.write_u32(self.flag.bits())?;
+        stream.write_f32(self.life)?;
+        Ok(())
+    }
 }
 
 #[repr(C, align(16))]
@@ -236,6 +283,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -260,6 +311,24 @@ impl RadialBlurParams {
             )?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        self.rgba.stream_write(stream, &mut ())?;
+        stream.write_u32(self.blend)?;
+        self.power.stream_write(stream, &mut ())?;
+        stream.write_f32(self.falloff)?;
+        stream.write_f32(self.center_x)?;
+        stream.write_f32(self.center_y)?;
+        if stream.has_feature(GfdVersion::EplRadialBlurSSAOMask).is_some() {
+            stream.write_u8(self.ssao_mask as u8)?;
+        }
+        Ok(())
+    }
 }
 
 #[repr(C, align(16))]
@@ -282,6 +351,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -298,6 +371,19 @@ impl StraightBlurParams {
         self.direction = stream.read_f32()?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        self.rgba.stream_write(stream, &mut ())?;
+        stream.write_u32(self.blend)?;
+        self.power.stream_write(stream, &mut ())?;
+        stream.write_f32(self.direction)?;
+        Ok(())
+    }
 }
 
 #[repr(C, align(16))]
@@ -321,6 +407,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -343,6 +433,22 @@ impl NoiseBlurParams {
             )?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        self.rgba.stream_write(stream, &mut ())?;
+        stream.write_u32(self.blend)?;
+        self.power.stream_write(stream, &mut ())?;
+        self.scale.stream_write(stream, &mut ())?;
+        if stream.has_feature(GfdVersion::EplNoiseBlurSSAOMask).is_some() {
+            stream.write_u8(self.ssao_mask as u8)?;
+        }
+        Ok(())
+    }
 }
 
 #[repr(C, align(16))]
@@ -365,6 +471,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -385,6 +495,23 @@ impl DistortBlurParams {
         }
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        self.rgba.stream_write(stream, &mut ())?;
+        stream.write_u32(self.blend)?;
+        for i in 0..2 {
+            self.power[i].stream_write(stream, &mut ())?;
+        }
+        for i in 0..2 {
+            self.uv[i].stream_write(stream, &mut ())?;
+        }
+        Ok(())
+    }
 }
 
 #[repr(C, align(16))]
@@ -406,6 +533,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -423,6 +554,20 @@ impl FillParams {
         self.power = Curve4::stream_read(stream, &mut ())?.into_raw();
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        for i in 0..4 {
+            self.rgba[i].stream_write(stream, &mut ())?;
+        }
+        stream.write_u32(self.blend)?;
+        self.power.stream_write(stream, &mut ())?;
+        Ok(())
+    }
 }
 
 bitflags! {
@@ -484,6 +629,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -501,6 +650,20 @@ impl LensFlareParams {
         self.brightness = stream.read_f32()?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        stream.write_u32(self.flags.bits())?;
+        stream.write_u32(self.templ)?;
+        stream.write_u32(self.filter)?;
+        self.rgba.stream_write(stream, &mut ())?;
+        stream.write_f32(self.brightness)?;
+        Ok(())
+    }
 }
 
 #[repr(C, align(16))]
@@ -526,6 +689,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -545,6 +712,22 @@ impl ColorCorrectionParams {
         self.fade = Fade::stream_read(stream, &mut ())?.into_raw();
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        stream.write_f32(self.cyan)?;
+        stream.write_f32(self.magenta)?;
+        stream.write_f32(self.yellow)?;
+        stream.write_f32(self.dodge)?;
+        stream.write_f32(self.burn)?;
+        stream.write_f32(self.alpha)?;
+        self.fade.stream_write(stream, &mut ())?;
+        Ok(())
+    }
 }
 
 #[repr()]
@@ -565,6 +748,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -579,6 +766,17 @@ impl MonotoneParams {
         self.fade = Fade::stream_read(stream, &mut ())?.into_raw();
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        stream.write_f32(self.alpha)?;
+        self.fade.stream_write(stream, &mut ())?;
+        Ok(())
+    }
 }
 
 #[cfg(feature = "v1-core")]
@@ -604,6 +802,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(all(feature = "serialize", feature = "v1-core"))]
@@ -620,6 +822,19 @@ impl LensFlareMakeParams {
         self.field90 = Curve4::stream_read(stream, &mut ())?.into_raw();
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        stream.write_u32(self.field10)?;
+        stream.write_u32(self.field8)?;
+        self.field50.stream_write(stream, &mut ())?;
+        self.field90.stream_write(stream, &mut ())?;
+        Ok(())
+    }
 }
 
 #[cfg(feature = "v2-core")]
@@ -644,6 +859,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -661,6 +880,20 @@ impl ChromaticAberrationParams {
         self.field24 = Fade::stream_read(stream, &mut ())?.into_raw();
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        stream.write_f32(self.field10)?;
+        stream.write_f32(self.field14)?;
+        stream.write_f32(self.field18)?;
+        self.field1c.stream_write(stream, &mut ())?;
+        self.field24.stream_write(stream, &mut ())?;
+        Ok(())
+    }
 }
 
 #[cfg(feature = "v1-core")]
@@ -685,6 +918,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(all(feature = "serialize", feature = "v1-core"))]
@@ -702,6 +939,20 @@ impl MotionBlurParams {
         self.fieldd8 = stream.read_f32()?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        self.field8.stream_write(stream, &mut ())?;
+        stream.write_u32(self.field6c)?;
+        self.field70.stream_write(stream, &mut ())?;
+        stream.write_f32(self.fieldd4)?;
+        stream.write_f32(self.fieldd8)?;
+        Ok(())
+    }
 }
 
 #[cfg(feature = "v2-core")]
@@ -731,6 +982,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -753,6 +1008,25 @@ impl ColorCorrectionExcludeToonParams {
         self.field34 = Fade::stream_read(stream, &mut ())?.into_raw();
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        stream.write_f32(self.field10)?;
+        stream.write_f32(self.field14)?;
+        stream.write_f32(self.field18)?;
+        stream.write_f32(self.field1c)?;
+        stream.write_f32(self.field20)?;
+        stream.write_f32(self.field24)?;
+        stream.write_f32(self.field28)?;
+        stream.write_f32(self.field2c)?;
+        stream.write_f32(self.field30)?;
+        self.field34.stream_write(stream, &mut ())?;
+        Ok(())
+    }
 }
 
 #[cfg(feature = "v1-core")]
@@ -776,6 +1050,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(all(feature = "serialize", feature = "v1-core"))]
@@ -792,4 +1070,17 @@ impl AfterimageBlurParams {
         self.fieldd4 = stream.read_f32()?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        self.basic.stream_write(stream, &mut ())?;
+        self.field8.stream_write(stream, &mut ())?;
+        stream.write_u32(self.field6c)?;
+        self.field70.stream_write(stream, &mut ())?;
+        stream.write_f32(self.fieldd4)?;
+        Ok(())
+    }
 }
\ No newline at end of file
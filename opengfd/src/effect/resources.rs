@@ -67,6 +67,10 @@ where TStream: Debug + Read + Write + Seek + StreamIODevice,
         this.data = TObject::stream_read(stream, &mut ())?.into_raw();
         Ok(this)
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, TStream>, _: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        self.data.stream_write(stream, &mut ())
+    }
 }
 
 impl<T, A> Deref for EPLParameter<T, A>
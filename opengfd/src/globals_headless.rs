@@ -0,0 +1,9 @@
+#![allow(dead_code)]
+//! Program globals for headless builds. Instead of linking to opengfd-globals, each global is
+//! owned by OpenGFD itself, so nothing needs to be hooked or loaded for OpenGFD to run. Globals
+//! start out unset, so code that requires a running game (rendering, input, jobs) will see None.
+//!
+//! The list of globals is included from opengfd-globals, which is also used to generate the
+//! links in globals_xrd759.rs, so both builds always have the same set of globals.
+
+include!("../../opengfd-globals/src/globals_xrd759.rs");
@@ -53,6 +53,21 @@ impl Clone for CurveTarget {
     }
 }
 
+#[cfg(feature = "serialize")]
+impl CurveTarget {
+    fn stream_write<A, T>(&self, stream: &mut Stream<A, T>, curve_type: CurveType) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          A: Allocator + Clone + Debug {
+        match curve_type {
+            CurveType::Integer => stream.write_u32(unsafe { self.integer } as u32)?,
+            CurveType::Float => stream.write_f32(unsafe { self.float })?,
+            CurveType::Color => unsafe { self.color }.stream_write(stream, &mut ())?,
+            CurveType::Vec2 => unsafe { self.range }.stream_write(stream, &mut ())?,
+        };
+        Ok(())
+    }
+}
+
 /// Two-point bezier curve, with a start and end point. Mostly used for particle systems.
 #[repr(C)]
 #[derive(Default, Clone)]
@@ -96,6 +111,16 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         stream.read_u16_slice(this.table.as_mut_slice())?;
         Ok(this.into())
     }
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        stream.write_u16(self.curve_type as u16)?;
+        self.start.stream_write(stream, self.curve_type)?;
+        self.end.stream_write(stream, self.curve_type)?;
+        for point in &self.point {
+            point.stream_write(stream, &mut ())?;
+        }
+        stream.write_u16_slice(self.table.as_slice())?;
+        Ok(())
+    }
 }
 
 impl Debug for Curve2 {
@@ -172,6 +197,18 @@ impl Curve2 {
         self.start = std::mem::zeroed::<CurveTarget>();
         self.end = std::mem::zeroed::<CurveTarget>();
     }
+    pub fn set_start<T>(&mut self, value: T) -> Result<(), EplError> where T: GetCurveValue {
+        match self.curve_type == T::CURVE_TYPE {
+            true => Ok(T::set_value(&mut self.start, value)),
+            false => Err(EplError::IncorrectCurveType((T::CURVE_TYPE, self.curve_type)))
+        }
+    }
+    pub fn set_end<T>(&mut self, value: T) -> Result<(), EplError> where T: GetCurveValue {
+        match self.curve_type == T::CURVE_TYPE {
+            true => Ok(T::set_value(&mut self.end, value)),
+            false => Err(EplError::IncorrectCurveType((T::CURVE_TYPE, self.curve_type)))
+        }
+    }
     pub fn set_start_point(&mut self, value: Vec2) {
         self.point[0] = value;
     }
@@ -238,6 +275,20 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
             )?;
         Ok(this.into())
     }
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        stream.write_u16(self.curve_type as u16)?;
+        for target in &self.target {
+            target.stream_write(stream, self.curve_type)?;
+        }
+        for point in &self.point {
+            point.stream_write(stream, &mut ())?;
+        }
+        stream.write_u16_slice(self.table.as_slice())?;
+        if stream.has_feature(GfdVersion::EplCurve4Field62).is_some() {
+            stream.write_u16(self.flags.bits())?;
+        }
+        Ok(())
+    }
 }
 
 impl From<Curve2> for Curve4 {
@@ -308,6 +359,20 @@ impl From<Curve2> for Curve4 {
     }
 }
 
+impl From<&Curve4> for Curve2 {
+    // Used when writing to versions before Curve4 was introduced. Only the outer targets are
+    // kept, so any shaping from the inner two targets is lost.
+    fn from(value: &Curve4) -> Self {
+        Self {
+            curve_type: value.curve_type,
+            start: value.target[0].clone(),
+            end: value.target[3].clone(),
+            point: value.point,
+            table: value.table
+        }
+    }
+}
+
 impl Curve4 {
     pub fn get_target<T>(&self, index: usize) -> Result<T, EplError> where T: GetCurveValue {
         match self.curve_type == T::CURVE_TYPE && index < 4 {
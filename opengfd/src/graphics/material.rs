@@ -1,6 +1,7 @@
 use std::error::Error;
 use std::fmt::{Debug, Display, Formatter};
 use std::io::{Read, Seek, Write};
+use std::marker::PhantomData;
 use std::mem::{ManuallyDrop, MaybeUninit};
 use std::ptr::NonNull;
 use allocator_api2::alloc::Allocator;
@@ -15,7 +16,7 @@ use crate::{
             },
             shader::{ ShaderID, ShaderFlags }
         },
-        texture::Texture
+        texture::{ Texture, TextureSampler }
     },
     kernel::{
         allocator::GfdAllocator,
@@ -27,7 +28,7 @@ use crate::{
     object::geometry::VertexAttributeFlags,
     utility::{
         name::Name,
-        reference::{ GfdRcType, Reference }
+        reference::{ GfdRc, GfdRcType, Reference }
     }
 };
 use glam::Mat4;
@@ -36,7 +37,7 @@ use opengfd_proc::GfdRcAuto;
 use crate::device::ngr::renderer::shader::{PixelShaderPlatform, VertexShaderPlatform};
 use crate::kernel::version::GfdVersion;
 use crate::object::mesh::Mesh;
-use crate::utility::name::{NameSerializationContext, NameSerializationHash};
+use crate::utility::name::{NameSerializationContext, NameSerializationHash, NameSerializationTechnique};
 use crate::utility::stream::{DeserializationHeap, DeserializationStack, DeserializationStrategy, GfdSerializationUserData, GfdSerialize, SerializationSingleAllocator, Stream, StreamIODevice};
 
 #[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq)]
@@ -46,7 +47,21 @@ pub enum MaterialError {
     UnknownMultiplyType(u8),
     UnknownAlphaTestFunc(u16),
     UnknownCullMode(u16),
-    UnknownMaterialExtension(u32)
+    UnknownMaterialExtension(u32),
+    /// The texture flag for this slot is set, but there's no texture in it
+    MissingTexture(usize),
+    /// Materials only have texture slots 0 to 9
+    InvalidTextureSlot(usize),
+    /// The texture in this slot has a UV transform, but UVTransform isn't set on the material
+    UVTransformDisabled(usize),
+    /// Flags that can't be used by this material
+    InvalidFlags(MaterialFlags),
+    /// Flags that the material's parameters depend on that aren't set
+    ParameterRequiresFlags(MaterialFlags),
+    /// This build of OpenGFD has nowhere to store parameters of this type
+    ParametersUnavailable(MaterialId),
+    /// The parameters don't fit in the space that materials have for them
+    ParametersTooLarge(MaterialId)
 }
 impl Error for MaterialError {}
 impl Display for MaterialError {
@@ -56,7 +71,8 @@ impl Display for MaterialError {
 }
 
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Blending {
     ty: BlendType,
     src_color: u8,
@@ -77,6 +93,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -96,6 +116,21 @@ impl Blending {
         Ok(())
     }
 
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug
+    {
+        stream.write_u8(self.ty as u8)?;
+        stream.write_u8(self.src_color)?;
+        stream.write_u8(self.dst_color)?;
+        stream.write_u8(self.src_alpha)?;
+        stream.write_u8(self.dst_alpha)?;
+        stream.write_u8(self.multiple as u8)?;
+        // self.control = stream.read_u8()?;
+        Ok(())
+    }
+
     pub fn get_type(&self) -> BlendType {
         self.ty
     }
@@ -119,8 +154,44 @@ impl Blending {
     }
 }
 
+impl Default for Blending {
+    // Original function: gfdMaterialInitialize
+    fn default() -> Self {
+        Self::new(BlendType::Opaque)
+    }
+}
+
+impl Blending {
+    pub fn new(ty: BlendType) -> Self {
+        Self {
+            ty,
+            src_color: 1,
+            dst_color: 0,
+            src_alpha: 1,
+            dst_alpha: 0,
+            multiple: MultiplyType::Semi,
+            control: 0
+        }
+    }
+    pub fn set_type(&mut self, ty: BlendType) {
+        self.ty = ty;
+    }
+    pub fn set_color_factors(&mut self, src: u8, dst: u8) {
+        self.src_color = src;
+        self.dst_color = dst;
+    }
+    pub fn set_alpha_factors(&mut self, src: u8, dst: u8) {
+        self.src_alpha = src;
+        self.dst_alpha = dst;
+    }
+    pub fn set_multiply_type(&mut self, multiple: MultiplyType) {
+        self.multiple = multiple;
+    }
+}
+
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BlendType {
     Opaque = 0,
     Semitrans = 1,
@@ -149,6 +220,7 @@ impl TryFrom<u8> for BlendType {
 
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MultiplyType {
     None = 0,
     Semi = 1,
@@ -175,6 +247,7 @@ impl TryFrom<u8> for MultiplyType {
 
 bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    #[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct MaterialFlags : u32 {
         const Ambient             = 1 << 0; 
         const Diffuse             = 1 << 1;   
@@ -213,6 +286,7 @@ bitflags! {
 
 bitflags! { 
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    #[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct MaterialFlags2 : u16 {
         const EnableBloom             = 1 << 0;
         const LightMapModulateMode    = 1 << 1;
@@ -231,7 +305,10 @@ bitflags! {
 
 
 pub mod extensions {
-    use crate::graphics::material::MaterialError;
+    use crate::{
+        graphics::material::MaterialError,
+        kernel::version::GameProfile
+    };
 
     #[repr(u32)]
     #[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq)]
@@ -248,12 +325,21 @@ pub mod extensions {
         Type7 = 0x10007,
         AlphaCrunch = 0x10008,
         // Metaphor (unused, switched to v2 format early in development)
-        #[cfg(not(feature = "v1-core"))]
         Type9 = 0x10009,
-        #[cfg(not(feature = "v1-core"))]
         Type10 = 0x1000a,
     }
 
+    impl ExtensionId {
+        /// Check if the extension exists in the given game
+        pub fn is_available(&self, profile: GameProfile) -> bool {
+            match profile {
+                GameProfile::Persona4Dancing => *self <= Self::ShadowEdge,
+                GameProfile::Persona5Royal | GameProfile::CatherineFullBody => *self <= Self::AlphaCrunch,
+                GameProfile::MetaphorRefantazio | GameProfile::MetaphorRefantazioDemo => true
+            }
+        }
+    }
+
     impl TryFrom<u32> for ExtensionId {
         type Error = MaterialError;
         fn try_from(value: u32) -> Result<Self, Self::Error> {
@@ -267,9 +353,7 @@ pub mod extensions {
                 0x10006 => Ok(Self::Type6),
                 0x10007 => Ok(Self::Type7),
                 0x10008 => Ok(Self::AlphaCrunch),
-                #[cfg(not(feature = "v1-core"))]
                 0x10009 => Ok(Self::Type9),
-                #[cfg(not(feature = "v1-core"))]
                 0x1000a => Ok(Self::Type10),
                 v => Err(MaterialError::UnknownMaterialExtension(v))
             }
@@ -277,6 +361,55 @@ pub mod extensions {
     }
 }
 
+#[repr(u16)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MaterialId {
+    Field = 0,
+    Lambert = 1,
+    CharacterToon = 2,
+    Type3 = 3,
+    CharacterDistort = 4,
+    Water = 5,
+    DualLayer = 6,
+    Type7 = 7,
+    Type8 = 8,
+    Type9 = 9,
+    Sky = 10,
+    Type11 = 11,
+    CharacterMetal = 12,
+    Type13 = 13,
+    Type14 = 14,
+    Type15 = 15,
+    Shadow = 16
+}
+
+impl TryFrom<u16> for MaterialId {
+    type Error = MaterialError;
+    fn try_from(value: u16) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Field),
+            1 => Ok(Self::Lambert),
+            2 => Ok(Self::CharacterToon),
+            3 => Ok(Self::Type3),
+            4 => Ok(Self::CharacterDistort),
+            5 => Ok(Self::Water),
+            6 => Ok(Self::DualLayer),
+            7 => Ok(Self::Type7),
+            8 => Ok(Self::Type8),
+            9 => Ok(Self::Type9),
+            10 => Ok(Self::Sky),
+            11 => Ok(Self::Type11),
+            12 => Ok(Self::CharacterMetal),
+            13 => Ok(Self::Type13),
+            14 => Ok(Self::Type14),
+            15 => Ok(Self::Type15),
+            16 => Ok(Self::Shadow),
+            v => Err(MaterialError::UnknownMaterialType(v))
+        }
+    }
+}
+
 #[cfg(feature = "v2-core")]
 pub mod params {
     use allocator_api2::alloc::Allocator;
@@ -307,56 +440,13 @@ pub mod params {
         fmt::Display,
         mem::ManuallyDrop
     };
-    use crate::graphics::material::MaterialError;
+    use allocator_api2::boxed::Box as ABox;
+    use crate::graphics::material::{ MaterialError, MaterialFlags, MaterialTextureFlags };
     use crate::kernel::allocator::GfdAllocator;
+    use crate::utility::name::Name;
+    use std::ptr::NonNull;
 
-    #[repr(u16)]
-    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-    pub enum MaterialId {
-        Field = 0,
-        Lambert = 1,
-        CharacterToon = 2,
-        Type3 = 3,
-        CharacterDistort = 4,
-        Water = 5,
-        DualLayer = 6,
-        Type7 = 7,
-        Type8 = 8,
-        Type9 = 9,
-        Sky = 10,
-        Type11 = 11,
-        CharacterMetal = 12,
-        Type13 = 13,
-        Type14 = 14,
-        Type15 = 15,
-        Shadow = 16
-    }
-
-    impl TryFrom<u16> for MaterialId {
-        type Error = MaterialError;
-        fn try_from(value: u16) -> Result<Self, Self::Error> {
-            match value {
-                0 => Ok(Self::Field),
-                1 => Ok(Self::Lambert),
-                2 => Ok(Self::CharacterToon),
-                3 => Ok(Self::Type3),
-                4 => Ok(Self::CharacterDistort),
-                5 => Ok(Self::Water),
-                6 => Ok(Self::DualLayer),
-                7 => Ok(Self::Type7),
-                8 => Ok(Self::Type8),
-                9 => Ok(Self::Type9),
-                10 => Ok(Self::Sky),
-                11 => Ok(Self::Type11),
-                12 => Ok(Self::CharacterMetal),
-                13 => Ok(Self::Type13),
-                14 => Ok(Self::Type14),
-                15 => Ok(Self::Type15),
-                16 => Ok(Self::Shadow),
-                v => Err(MaterialError::UnknownMaterialType(v))
-            }
-        }
-    }
+    pub use super::MaterialId;
 
     #[allow(dead_code)]
     #[repr(C, packed(4))]
@@ -397,6 +487,11 @@ pub mod params {
             self.mat_type
         }
 
+        /// Get what the material type uses the texture slot for, e.g "Normal Texture"
+        pub fn get_texture_slot_name(&self, slot: usize) -> &'static str {
+            self.get_data().get_tex_name(slot)
+        }
+
         pub fn get_data(&self) -> Box<&dyn MaterialType> {
             Box::new(match self.mat_type {
                 MaterialId::Field => unsafe { &*(&raw const self.data as *const Field<A>) },
@@ -454,11 +549,123 @@ pub mod params {
                 false => Err(MaterialIdMismatch(MaterialId::Lambert, self.mat_type))
             }
         }
+
+        pub fn try_get_field(&self) -> Result<&Field<A>, MaterialIdMismatch> {
+            match self.mat_type == MaterialId::Field {
+                true => Ok(unsafe { &*(&raw const self.data as *const Field<A>) }),
+                false => Err(MaterialIdMismatch(MaterialId::Field, self.mat_type))
+            }
+        }
+
+        /// Replace this material's parameters with Lambert parameters. GFD v1 files only store
+        /// Lambert style parameters, so this is used when converting a material to v1.
+        pub fn set_lambert(&mut self, value: Lambert<A>) {
+            self.data.lambert = ManuallyDrop::new(value);
+            self.mat_type = MaterialId::Lambert;
+        }
+
+        /// Copy out this material's parameters. The caller must check that T matches the
+        /// material type.
+        #[cfg(feature = "io_serde")]
+        pub(crate) unsafe fn read_data<T>(&self) -> T
+        where T: MaterialType {
+            unsafe { std::ptr::read_unaligned(&raw const self.data as *const T) }
+        }
+
+        /// Replace this material's parameters, changing the material type to match
+        pub fn set_data<T>(&mut self, value: T) -> Result<(), MaterialError>
+        where T: MaterialType {
+            if size_of::<T>() > size_of::<MaterialData<A>>() {
+                return Err(MaterialError::ParametersTooLarge(value.get_material_id()));
+            }
+            self.mat_type = value.get_material_id();
+            unsafe { std::ptr::write_unaligned(&raw mut self.data as *mut T, value) };
+            Ok(())
+        }
+
+        /// Create a material using the defaults from gfdMaterialInitialize and the given
+        /// parameters
+        pub fn new_in<T>(name: &str, data: T, allocator: A) -> Result<NonNull<Self>, MaterialError>
+        where T: MaterialType {
+            Ok(unsafe { NonNull::new_unchecked(ABox::into_raw(Self::new_boxed_in(name, data, allocator)?)) })
+        }
+
+        pub(crate) fn new_boxed_in<T>(name: &str, data: T, allocator: A) -> Result<ABox<Self, A>, MaterialError>
+        where T: MaterialType {
+            let mut this = Self::new_boxed_empty_in(name, allocator);
+            this.set_data(data)?;
+            Ok(this)
+        }
+
+        fn new_boxed_empty_in(name: &str, allocator: A) -> ABox<Self, A> {
+            let mut this: ABox<Self, A> = unsafe { ABox::new_zeroed_in(allocator.clone()).assume_init() };
+            this.initialize();
+            unsafe { std::ptr::write(&raw mut this._allocator, allocator.clone()) };
+            this.name = Name::new_in(name, allocator);
+            this
+        }
+
+        /// Create a material with the default parameters for the given material type
+        pub fn new_with_type_in(name: &str, mat_type: MaterialId, allocator: A) -> NonNull<Self> {
+            unsafe { NonNull::new_unchecked(ABox::into_raw(Self::new_boxed_with_type_in(name, mat_type, allocator))) }
+        }
+
+        pub(crate) fn new_boxed_with_type_in(name: &str, mat_type: MaterialId, allocator: A) -> ABox<Self, A> {
+            let mut this = Self::new_boxed_empty_in(name, allocator);
+            match mat_type {
+                MaterialId::Field => this.data.field = ManuallyDrop::new(Field::<A>::default()),
+                MaterialId::Lambert => this.data.lambert = ManuallyDrop::new(Lambert::<A>::default()),
+                MaterialId::CharacterToon => this.data.chara_toon = ManuallyDrop::new(CharacterToon::<A>::default()),
+                MaterialId::Type3 => this.data.type3 = ManuallyDrop::new(Type3::<A>::default()),
+                MaterialId::CharacterDistort => this.data.chara_distort = ManuallyDrop::new(CharacterDistortion::<A>::default()),
+                MaterialId::Water => this.data.water = ManuallyDrop::new(Water::<A>::default()),
+                MaterialId::DualLayer => this.data.dual_layer = ManuallyDrop::new(TwoLayer::<A>::default()),
+                MaterialId::Type7 => this.data.type7 = ManuallyDrop::new(FourLayer::<A>::default()),
+                MaterialId::Type8 => this.data.type8 = ManuallyDrop::new(Type8::<A>::default()),
+                MaterialId::Type9 => this.data.type9 = ManuallyDrop::new(Type9::<A>::default()),
+                MaterialId::Sky => this.data.sky = ManuallyDrop::new(Sky::<A>::default()),
+                MaterialId::Type11 => this.data.type11 = ManuallyDrop::new(Type11::<A>::default()),
+                MaterialId::CharacterMetal => this.data.metal = ManuallyDrop::new(Metal::<A>::default()),
+                MaterialId::Type13 => this.data.type13 = ManuallyDrop::new(Type13::<A>::default()),
+                MaterialId::Type14 => this.data.type14 = ManuallyDrop::new(Type14::<A>::default()),
+                MaterialId::Type15 => this.data.type15 = ManuallyDrop::new(Type15::<A>::default()),
+                MaterialId::Shadow => this.data.shadow = ManuallyDrop::new(Shadow::<A>::default()),
+            }
+            this.mat_type = mat_type;
+            this
+        }
+
+        /// Check that the material's flags agree with its textures and parameters.
+        pub fn validate(&self) -> Result<(), MaterialError> {
+            // Materials from v1 files keep their extension list, which is only written out if the
+            // flag is set
+            if self.extension.is_some() && !self.flags.contains(MaterialFlags::Extension) {
+                return Err(MaterialError::ParameterRequiresFlags(MaterialFlags::Extension));
+            }
+            for (i, texture) in self.textures.iter().enumerate() {
+                if !self.flags.contains(MaterialFlags::from_bits_retain(1 << (20 + i))) {
+                    continue;
+                }
+                if texture.texture.is_none() {
+                    return Err(MaterialError::MissingTexture(i));
+                }
+                if texture.flags.contains(MaterialTextureFlags::HasUVTransform)
+                && !self.flags.contains(MaterialFlags::UVTransform) {
+                    return Err(MaterialError::UVTransformDisabled(i));
+                }
+            }
+            let required = self.get_data().get_required_flags();
+            if !self.flags.contains(required) {
+                return Err(MaterialError::ParameterRequiresFlags(required.difference(self.flags)));
+            }
+            Ok(())
+        }
     }
 }
 
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AlphaTest {
     value: i16,
     func: AlphaTestFunc
@@ -474,6 +681,22 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
+}
+
+impl AlphaTest {
+    pub fn new(func: AlphaTestFunc, value: i16) -> Self {
+        Self { value, func }
+    }
+    pub fn get_func(&self) -> AlphaTestFunc {
+        self.func
+    }
+    pub fn get_value(&self) -> i16 {
+        self.value
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -487,6 +710,16 @@ impl AlphaTest {
         self.func = stream.read_u16()?.try_into()?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug
+    {
+        stream.write_u16(self.value as u16)?;
+        stream.write_u16(self.func as u16)?;
+        Ok(())
+    }
 }
 
 #[repr(C)]
@@ -523,7 +756,7 @@ where A: Allocator + Clone
     #[cfg(feature = "v2-core")]
     data: params::MaterialData<A>,
     #[cfg(feature = "v2-core")]
-    mat_type: params::MaterialId,
+    mat_type: MaterialId,
     field23_0x2de: u16,
     ref_: Reference,
     field25_0x2e4: [u16; 3usize],
@@ -541,6 +774,120 @@ where A: Allocator + Clone {
     _allocator: A
 }
 
+impl<A> Extension<A>
+where A: Allocator + Clone {
+    /// Iterate through extension objects in the order they're stored in the file
+    pub fn iter(&self) -> ExtensionIterator<'_, A> {
+        ExtensionIterator { current: self.tail, _lifetime: PhantomData }
+    }
+
+    fn find(&self, id: extensions::ExtensionId) -> Option<NonNull<ExtensionObject<A>>> {
+        let mut current = self.tail;
+        while let Some(object) = current {
+            if unsafe { object.as_ref() }.id == id {
+                return Some(object);
+            }
+            current = unsafe { object.as_ref() }.prev;
+        }
+        None
+    }
+
+    pub fn get_toon(&self) -> Option<&crate::graphics::shader::attribute::toon_v1::Toon<A>> {
+        self.find(extensions::ExtensionId::Toon).map(|v| unsafe { v.cast().as_ref() })
+    }
+    pub fn get_toon_mut(&mut self) -> Option<&mut crate::graphics::shader::attribute::toon_v1::Toon<A>> {
+        self.find(extensions::ExtensionId::Toon).map(|v| unsafe { v.cast().as_mut() })
+    }
+
+    pub fn get_edge(&self) -> Option<&crate::graphics::shader::attribute::edge_v1::Edge<A>> {
+        self.find(extensions::ExtensionId::Edge).map(|v| unsafe { v.cast().as_ref() })
+    }
+    pub fn get_edge_mut(&mut self) -> Option<&mut crate::graphics::shader::attribute::edge_v1::Edge<A>> {
+        self.find(extensions::ExtensionId::Edge).map(|v| unsafe { v.cast().as_mut() })
+    }
+
+    pub fn get_outline(&self) -> Option<&crate::graphics::shader::attribute::outline_v1::Outline<A>> {
+        self.find(extensions::ExtensionId::Outline).map(|v| unsafe { v.cast().as_ref() })
+    }
+    pub fn get_outline_mut(&mut self) -> Option<&mut crate::graphics::shader::attribute::outline_v1::Outline<A>> {
+        self.find(extensions::ExtensionId::Outline).map(|v| unsafe { v.cast().as_mut() })
+    }
+
+    pub fn get_water(&self) -> Option<&crate::graphics::shader::attribute::water_v1::Water<A>> {
+        self.find(extensions::ExtensionId::Water).map(|v| unsafe { v.cast().as_ref() })
+    }
+    pub fn get_water_mut(&mut self) -> Option<&mut crate::graphics::shader::attribute::water_v1::Water<A>> {
+        self.find(extensions::ExtensionId::Water).map(|v| unsafe { v.cast().as_mut() })
+    }
+
+    pub fn get_shadow_edge(&self) -> Option<&crate::graphics::shader::attribute::shadow_edge_v1::ShadowEdge<A>> {
+        self.find(extensions::ExtensionId::ShadowEdge).map(|v| unsafe { v.cast().as_ref() })
+    }
+    pub fn get_shadow_edge_mut(&mut self) -> Option<&mut crate::graphics::shader::attribute::shadow_edge_v1::ShadowEdge<A>> {
+        self.find(extensions::ExtensionId::ShadowEdge).map(|v| unsafe { v.cast().as_mut() })
+    }
+
+    pub fn get_type5(&self) -> Option<&crate::graphics::shader::attribute::type5_v1::Type5<A>> {
+        self.find(extensions::ExtensionId::Type5).map(|v| unsafe { v.cast().as_ref() })
+    }
+    pub fn get_type5_mut(&mut self) -> Option<&mut crate::graphics::shader::attribute::type5_v1::Type5<A>> {
+        self.find(extensions::ExtensionId::Type5).map(|v| unsafe { v.cast().as_mut() })
+    }
+
+    pub fn get_type6(&self) -> Option<&crate::graphics::shader::attribute::type6_v1::Type6<A>> {
+        self.find(extensions::ExtensionId::Type6).map(|v| unsafe { v.cast().as_ref() })
+    }
+    pub fn get_type6_mut(&mut self) -> Option<&mut crate::graphics::shader::attribute::type6_v1::Type6<A>> {
+        self.find(extensions::ExtensionId::Type6).map(|v| unsafe { v.cast().as_mut() })
+    }
+
+    pub fn get_type7(&self) -> Option<&crate::graphics::shader::attribute::type7_v1::Type7<A>> {
+        self.find(extensions::ExtensionId::Type7).map(|v| unsafe { v.cast().as_ref() })
+    }
+    pub fn get_type7_mut(&mut self) -> Option<&mut crate::graphics::shader::attribute::type7_v1::Type7<A>> {
+        self.find(extensions::ExtensionId::Type7).map(|v| unsafe { v.cast().as_mut() })
+    }
+
+    pub fn get_alpha_crunch(&self) -> Option<&crate::graphics::shader::attribute::alpha_v1::AlphaCrunch<A>> {
+        self.find(extensions::ExtensionId::AlphaCrunch).map(|v| unsafe { v.cast().as_ref() })
+    }
+    pub fn get_alpha_crunch_mut(&mut self) -> Option<&mut crate::graphics::shader::attribute::alpha_v1::AlphaCrunch<A>> {
+        self.find(extensions::ExtensionId::AlphaCrunch).map(|v| unsafe { v.cast().as_mut() })
+    }
+
+    pub fn get_type9(&self) -> Option<&crate::graphics::shader::attribute::type9_v1::Type9<A>> {
+        self.find(extensions::ExtensionId::Type9).map(|v| unsafe { v.cast().as_ref() })
+    }
+    pub fn get_type9_mut(&mut self) -> Option<&mut crate::graphics::shader::attribute::type9_v1::Type9<A>> {
+        self.find(extensions::ExtensionId::Type9).map(|v| unsafe { v.cast().as_mut() })
+    }
+
+    pub fn get_type10(&self) -> Option<&crate::graphics::shader::attribute::type10_v1::Type10<A>> {
+        self.find(extensions::ExtensionId::Type10).map(|v| unsafe { v.cast().as_ref() })
+    }
+    pub fn get_type10_mut(&mut self) -> Option<&mut crate::graphics::shader::attribute::type10_v1::Type10<A>> {
+        self.find(extensions::ExtensionId::Type10).map(|v| unsafe { v.cast().as_mut() })
+    }
+}
+
+pub struct ExtensionIterator<'a, A>
+where A: Allocator + Clone {
+    current: Option<NonNull<ExtensionObject<A>>>,
+    _lifetime: PhantomData<&'a ExtensionObject<A>>
+}
+
+impl<'a, A> Iterator for ExtensionIterator<'a, A>
+where A: Allocator + Clone {
+    type Item = &'a ExtensionObject<A>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.current.map(|v| {
+            let object = unsafe { v.as_ref() };
+            self.current = object.prev;
+            object
+        })
+    }
+}
+
 #[cfg(feature = "serialize")]
 impl<AStream, AObject, T> GfdSerialize<AStream, T, AObject, DeserializationHeap<Self, AObject>, SerializationSingleAllocator<AObject>> for Extension<AObject>
 where T: Debug + Read + Write + Seek + StreamIODevice,
@@ -552,6 +899,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream, param)?;
         Ok(this)
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream, param)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -562,9 +913,13 @@ where AObject: Allocator + Clone {
     where T: Debug + Read + Write + Seek + StreamIODevice,
           AStream: Allocator + Clone + Debug
     {
+        self.ref_ = Reference::new();
         let count = stream.read_u32()?;
         for i in 0..count {
             let id: extensions::ExtensionId = stream.read_u32()?.try_into()?;
+            if !id.is_available(stream.get_game_profile()) {
+                return Err(Box::new(MaterialError::UnknownMaterialExtension(id as u32)));
+            }
             let mut context = ExtensionObjectContext::new(id, param.get_heap_allocator().unwrap());
             let mut object = unsafe { NonNull::new_unchecked(match id {
                 extensions::ExtensionId::Toon => crate::graphics::shader::attribute::toon_v1::Toon::<AObject>::stream_read(stream, &mut context)?.into_raw().as_ptr() as *mut ExtensionObject<AObject>,
@@ -576,9 +931,7 @@ where AObject: Allocator + Clone {
                 extensions::ExtensionId::Type6 => crate::graphics::shader::attribute::type6_v1::Type6::<AObject>::stream_read(stream, &mut context)?.into_raw().as_ptr() as *mut ExtensionObject<AObject>,
                 extensions::ExtensionId::Type7 => crate::graphics::shader::attribute::type7_v1::Type7::<AObject>::stream_read(stream, &mut context)?.into_raw().as_ptr() as *mut ExtensionObject<AObject>,
                 extensions::ExtensionId::AlphaCrunch => crate::graphics::shader::attribute::alpha_v1::AlphaCrunch::<AObject>::stream_read(stream, &mut context)?.into_raw().as_ptr() as *mut ExtensionObject<AObject>,
-                #[cfg(not(feature = "v1-core"))]
                 extensions::ExtensionId::Type9 => crate::graphics::shader::attribute::type9_v1::Type9::<AObject>::stream_read(stream, &mut context)?.into_raw().as_ptr() as *mut ExtensionObject<AObject>,
-                #[cfg(not(feature = "v1-core"))]
                 extensions::ExtensionId::Type10 => crate::graphics::shader::attribute::type10_v1::Type10::<AObject>::stream_read(stream, &mut context)?.into_raw().as_ptr() as *mut ExtensionObject<AObject>,
             })};
             match self.head {
@@ -597,6 +950,42 @@ where AObject: Allocator + Clone {
         }
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug
+    {
+        let mut count = 0;
+        let mut current = self.tail;
+        while let Some(object) = current {
+            count += 1;
+            current = unsafe { object.as_ref() }.prev;
+        }
+        stream.write_u32(count)?;
+        // objects are prepended on read, so walk backwards to keep the original order
+        let mut current = self.tail;
+        while let Some(object) = current {
+            let object = unsafe { object.as_ref() };
+            let mut context = ExtensionObjectContext::new(object.id, param.get_heap_allocator().unwrap());
+            stream.write_u32(object.id as u32)?;
+            let ptr = object as *const ExtensionObject<AObject>;
+            match object.id {
+                extensions::ExtensionId::Toon => unsafe { &*(ptr as *const crate::graphics::shader::attribute::toon_v1::Toon<AObject>) }.stream_write(stream, &mut context)?,
+                extensions::ExtensionId::Edge => unsafe { &*(ptr as *const crate::graphics::shader::attribute::edge_v1::Edge<AObject>) }.stream_write(stream, &mut context)?,
+                extensions::ExtensionId::Outline => unsafe { &*(ptr as *const crate::graphics::shader::attribute::outline_v1::Outline<AObject>) }.stream_write(stream, &mut context)?,
+                extensions::ExtensionId::Water => unsafe { &*(ptr as *const crate::graphics::shader::attribute::water_v1::Water<AObject>) }.stream_write(stream, &mut context)?,
+                extensions::ExtensionId::ShadowEdge => unsafe { &*(ptr as *const crate::graphics::shader::attribute::shadow_edge_v1::ShadowEdge<AObject>) }.stream_write(stream, &mut context)?,
+                extensions::ExtensionId::Type5 => unsafe { &*(ptr as *const crate::graphics::shader::attribute::type5_v1::Type5<AObject>) }.stream_write(stream, &mut context)?,
+                extensions::ExtensionId::Type6 => unsafe { &*(ptr as *const crate::graphics::shader::attribute::type6_v1::Type6<AObject>) }.stream_write(stream, &mut context)?,
+                extensions::ExtensionId::Type7 => unsafe { &*(ptr as *const crate::graphics::shader::attribute::type7_v1::Type7<AObject>) }.stream_write(stream, &mut context)?,
+                extensions::ExtensionId::AlphaCrunch => unsafe { &*(ptr as *const crate::graphics::shader::attribute::alpha_v1::AlphaCrunch<AObject>) }.stream_write(stream, &mut context)?,
+                extensions::ExtensionId::Type9 => unsafe { &*(ptr as *const crate::graphics::shader::attribute::type9_v1::Type9<AObject>) }.stream_write(stream, &mut context)?,
+                extensions::ExtensionId::Type10 => unsafe { &*(ptr as *const crate::graphics::shader::attribute::type10_v1::Type10<AObject>) }.stream_write(stream, &mut context)?,
+            };
+            current = object.prev;
+        }
+        Ok(())
+    }
 }
 
 #[repr(C)]
@@ -618,6 +1007,10 @@ where A: Allocator + Clone {
             _allocator: alloc
         }
     }
+
+    pub fn get_id(&self) -> extensions::ExtensionId {
+        self.id
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -631,6 +1024,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream, param)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -643,6 +1040,14 @@ where AObject: Allocator + Clone {
         self.id = stream.read_u32()?.try_into()?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug
+    {
+        stream.write_u32(self.id as u32)?;
+        Ok(())
+    }
 }
 
 pub struct ExtensionObjectContext<A> where A: Allocator + Clone {
@@ -679,6 +1084,7 @@ pub struct PixelBuffer {
 
 #[repr(u16)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AlphaTestFunc {
     Never = 0,
     LessOrEqual0 = 1,
@@ -707,6 +1113,7 @@ impl TryFrom<u16> for AlphaTestFunc {
 
 #[repr(u16)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Culling {
     Backface = 0,
     None = 1
@@ -747,6 +1154,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream, param)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream, param)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -758,18 +1169,104 @@ where AObject: Allocator + Clone {
         AStream: Allocator + Clone + Debug
     {
         let name = Name::<AObject>::stream_read(stream, &mut NameSerializationContext::new(param.get_heap_allocator().unwrap().clone(), NameSerializationHash))?.into_raw();
+        let texture = Texture::new_unloaded(name, param.get_heap_allocator().unwrap());
+        self.texture = Some(unsafe { NonNull::new_unchecked(GfdRc::into_raw(texture) as *mut Texture<AObject>) });
         self.flags = MaterialTextureFlags::from_bits_truncate(stream.read_u32()?);
         self.min = stream.read_u8()?;
         self.mag = stream.read_u8()?;
         self.wraps = stream.read_u8()?;
         self.wrapt = stream.read_u8()?;
+        self.update_texture_sampler();
         self.tm = Mat4::stream_read(stream, &mut ())?.into_raw();
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug
+    {
+        match self.texture {
+            Some(v) => NameSerializationHash.stream_write_inner(stream, unsafe { v.as_ref() }.get_name())?,
+            None => NameSerializationHash.stream_write_inner(stream, &Name::empty_in(param.get_heap_allocator().unwrap()))?
+        };
+        stream.write_u32(self.flags.bits())?;
+        stream.write_u8(self.min)?;
+        stream.write_u8(self.mag)?;
+        stream.write_u8(self.wraps)?;
+        stream.write_u8(self.wrapt)?;
+        self.tm.stream_write(stream, &mut ())?;
+        Ok(())
+    }
+}
+
+impl<A> MaterialTexture<A>
+where A: Allocator + Clone {
+    pub fn get_texture(&self) -> Option<&Texture<A>> {
+        self.texture.map(|v| unsafe { v.as_ref() })
+    }
+    /// Get the name of the texture in the mesh's texture dictionary
+    pub fn get_name(&self) -> Option<&str> {
+        self.get_texture().and_then(|v| v.get_name().get_string())
+    }
+    pub fn get_flags(&self) -> MaterialTextureFlags { self.flags }
+    /// UV transform matrix, used when HasUVTransform is set
+    pub fn get_transform(&self) -> Mat4 { self.tm }
+    pub fn get_min_filter(&self) -> u8 { self.min }
+    pub fn get_mag_filter(&self) -> u8 { self.mag }
+    pub fn get_wrap_s(&self) -> u8 { self.wraps }
+    pub fn get_wrap_t(&self) -> u8 { self.wrapt }
+    pub fn get_sampler(&self) -> TextureSampler { TextureSampler::new(self.min, self.mag, self.wraps, self.wrapt) }
+
+    /// Set the UV transform, turning on HasUVTransform
+    pub fn set_transform(&mut self, tm: Mat4) {
+        self.tm = tm;
+        self.flags |= MaterialTextureFlags::HasUVTransform;
+    }
+    pub fn set_flags(&mut self, flags: MaterialTextureFlags) { self.flags = flags }
+    pub fn set_filter(&mut self, min: u8, mag: u8) {
+        self.min = min;
+        self.mag = mag;
+        self.update_texture_sampler();
+    }
+    pub fn set_wrap(&mut self, wraps: u8, wrapt: u8) {
+        self.wraps = wraps;
+        self.wrapt = wrapt;
+        self.update_texture_sampler();
+    }
+    pub fn set_sampler(&mut self, sampler: TextureSampler) {
+        (self.min, self.mag, self.wraps, self.wrapt) = (sampler.min, sampler.mag, sampler.wraps, sampler.wrapt);
+        self.update_texture_sampler();
+    }
+    /// The renderer reads sampler settings from the texture rather than the material, so keep
+    /// them in sync
+    fn update_texture_sampler(&mut self) {
+        let sampler = self.get_sampler();
+        if let Some(mut texture) = self.texture {
+            unsafe { texture.as_mut() }.set_sampler(sampler);
+        }
+    }
+
+    /// Create a texture slot that refers to a texture in the mesh's texture dictionary by name.
+    /// Sampler settings match the defaults for a new texture.
+    pub fn new_in(name: &str, allocator: A) -> Self {
+        let texture = Texture::new_unloaded(Name::new_in(name, allocator.clone()), allocator.clone());
+        Self {
+            tm: Mat4::IDENTITY,
+            texture: Some(unsafe { NonNull::new_unchecked(GfdRc::into_raw(texture) as *mut Texture<A>) }),
+            flags: MaterialTextureFlags::empty(),
+            min: 1,
+            mag: 1,
+            wraps: 0,
+            wrapt: 0,
+            _allocator: allocator
+        }
+    }
 }
 
 bitflags! { 
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    #[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct MaterialTextureFlags : u32 {
         const HasUVTransform  = 1 << 0;
         const Flag1  = 1 << 1;
@@ -819,10 +1316,26 @@ where A: Allocator + Clone
     }
     /// Original function: gfdMaterialCheckShadowCaster
     pub fn check_shadow_caster(&self) -> bool {
-        let glb = GraphicsGlobal::get_gfd_graphics_global();
+        self.check_shadow_caster_in(&ShaderFlagContext::from_global())
+    }
+    /// Same as [`Material::check_shadow_caster`], using the given graphics state instead of the
+    /// game's
+    pub fn check_shadow_caster_in(&self, ctx: &ShaderFlagContext) -> bool {
         self.flags.contains(MaterialFlags::ShadowCaster)
-        && glb.has_flags(GraphicsFlags::ShadowCaster)
+        && ctx.has_flags(GraphicsFlags::ShadowCaster)
+    }
 
+    // Original function: gfdMaterialInitialize (0x14106bf90, Steam Prologue Demo 1.01)
+    fn initialize(&mut self) {
+        self.flags = MaterialFlags::Ambient | MaterialFlags::Diffuse;
+        self.alpha_test.func = AlphaTestFunc::GreaterOrEqual0;
+        self.blend.src_color = 1;
+        self.texture = unsafe { NonNull::new_unchecked((&mut self.textures).as_mut_ptr()) };
+        self.blend.src_alpha = 1;
+        self.blend.multiple = MultiplyType::Semi;
+        self.constant = -1;
+        self.field16_0x6c = 1.;
+        self.ref_ = Reference::new();
     }
 
     pub fn has_flags(&self, flag: MaterialFlags) -> bool {
@@ -850,9 +1363,24 @@ where A: Allocator + Clone
     pub fn get_flag2(&self) -> MaterialFlags2 {
         self.flags2
     }
+    #[cfg(feature = "v2-core")]
     pub fn get_mat_data_offset() -> usize {
         std::mem::offset_of!(Self, data)
     }
+    #[cfg(all(feature = "v1-core", not(feature = "v2-core")))]
+    pub fn get_mat_data_offset() -> usize {
+        std::mem::offset_of!(Self, lambert)
+    }
+    /// GFD v1 materials always have Lambert parameters, which are stored at the start of the
+    /// material
+    #[cfg(all(feature = "v1-core", not(feature = "v2-core")))]
+    pub fn get_lambert(&self) -> &crate::graphics::shader::attribute::lambert_v2::Lambert<A> {
+        &self.lambert
+    }
+    #[cfg(all(feature = "v1-core", not(feature = "v2-core")))]
+    pub fn get_lambert_mut(&mut self) -> &mut crate::graphics::shader::attribute::lambert_v2::Lambert<A> {
+        &mut self.lambert
+    }
     /// Original function: gfdMaterialSetFlagBit
     pub fn set_flag(&mut self, flag: MaterialFlags, value: bool) {
         self.flags.set(flag, value)
@@ -887,6 +1415,32 @@ where A: Allocator + Clone
     pub fn get_blend(&self) -> &Blending {
         &self.blend
     }
+    pub fn set_blend(&mut self, blend: Blending) {
+        self.blend = blend;
+    }
+    pub fn get_alpha_test(&self) -> &AlphaTest {
+        &self.alpha_test
+    }
+    /// Set the alpha test function, turning on AlphaTest
+    pub fn set_alpha_test(&mut self, alpha_test: AlphaTest) {
+        self.alpha_test = alpha_test;
+        self.flag_on(MaterialFlags::AlphaTest);
+    }
+    pub fn get_culling(&self) -> Culling {
+        self.culling
+    }
+    pub fn set_culling(&mut self, culling: Culling) {
+        self.culling = culling;
+    }
+    pub fn get_sort_priority(&self) -> i16 {
+        self.sort_priority
+    }
+    pub fn set_sort_priority(&mut self, priority: i16) {
+        self.sort_priority = priority.min(16);
+    }
+    pub fn set_constant(&mut self, constant: i32) {
+        self.constant = constant;
+    }
 }
 pub trait MaterialType {
     fn check_billboard_shadow_map(&self) -> bool;
@@ -916,6 +1470,21 @@ pub trait MaterialType {
     fn get_tex8_name(&self) -> &'static str { "Texture 8" } // register(t7)
     fn get_tex9_name(&self) -> &'static str { "Texture 9" } // register(t8)
     fn get_tex10_name(&self) -> &'static str { "Texture 10" } // register(t9)
+    fn get_tex_name(&self, slot: usize) -> &'static str {
+        match slot {
+            0 => self.get_tex1_name(),
+            1 => self.get_tex2_name(),
+            2 => self.get_tex3_name(),
+            3 => self.get_tex4_name(),
+            4 => self.get_tex5_name(),
+            5 => self.get_tex6_name(),
+            6 => self.get_tex7_name(),
+            7 => self.get_tex8_name(),
+            8 => self.get_tex9_name(),
+            9 => self.get_tex10_name(),
+            _ => ""
+        }
+    }
 
     // See gfdMaterialGetShaderFlags
     fn set_shader_flags(&self, vtx: VertexAttributeFlags, flags: &mut ShaderFlags);
@@ -926,10 +1495,41 @@ pub trait MaterialType {
     fn update(&mut self);
     // Material->MapType also called from 
     // - inside gfdThJobGeometryUpdate
-    fn get_material_id(&self) -> params::MaterialId;
+    fn get_material_id(&self) -> MaterialId;
     fn get_shader_id(&self) -> u32;
-    // TODO: StreamWrite, create material
+    /// Material flags that need to be set for the parameters to work, such as a texture slot
+    /// that a parameter flag reads from
+    fn get_required_flags(&self) -> MaterialFlags { MaterialFlags::empty() }
+}
 
+/// The parts of the graphics state that change which shader permutation a material needs. The
+/// game reads these from the graphics global while building shader flags. HDR and deferred are
+/// set by the render pass rather than the material, so they're off when read from the graphics
+/// global.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShaderFlagContext {
+    pub flags: GraphicsFlags,
+    pub hdr: bool,
+    pub deferred: bool
+}
+
+impl Default for ShaderFlagContext {
+    fn default() -> Self {
+        Self::new(GraphicsFlags::empty())
+    }
+}
+
+impl ShaderFlagContext {
+    pub fn new(flags: GraphicsFlags) -> Self {
+        Self { flags, hdr: false, deferred: false }
+    }
+    /// Use the game's current graphics state
+    pub fn from_global() -> Self {
+        Self::new(GraphicsGlobal::get_gfd_graphics_global().get_flags())
+    }
+    pub fn has_flags(&self, flag: GraphicsFlags) -> bool {
+        self.flags.contains(flag)
+    }
 }
 
 pub(crate) const TEX_BIT_SIZE: usize = 3;
@@ -968,9 +1568,14 @@ where A: Allocator + Clone
 
     // Original function: 0x141071d70 (Metaphor Prologue Demo)
     pub fn get_shader_flags(&self, vtx: VertexAttributeFlags) -> ShaderFlags {
+        self.get_shader_flags_in(vtx, &ShaderFlagContext::from_global())
+    }
+
+    /// Get the shader flags for this material using the given graphics state instead of the
+    /// game's, so that the shader permutation a material needs can be found outside of the game.
+    pub fn get_shader_flags_in(&self, vtx: VertexAttributeFlags, ctx: &ShaderFlagContext) -> ShaderFlags {
         let param = self.get_data();
         let map_id = param.get_shader_id();
-        let glb = GraphicsGlobal::get_gfd_graphics_global();
         let mut flags = ShaderFlags::default();
         flags.reset_flag0(ShaderFlag0::FLAG0_ALWAYS_ENABLED | ShaderFlag0::FLAG0_CONSTANTCOLOR);
         flags.reset_flag1(ShaderFlag1::FLAG1_MATERIAL_AMBDIFF);
@@ -1010,10 +1615,10 @@ where A: Allocator + Clone
             flags |= ShaderFlag1::FLAG1_MATERIAL_VERTEXCOLOR;
         }
         if self.flags.contains(MaterialFlags::Fog) {
-            if glb.has_flags(GraphicsFlags::Fog) {
+            if ctx.has_flags(GraphicsFlags::Fog) {
                 flags |= ShaderFlag1::FLAG1_MATERIAL_FOG;
             }
-            if glb.has_flags(GraphicsFlags::HeightFog) {
+            if ctx.has_flags(GraphicsFlags::HeightFog) {
                 flags |= ShaderFlag1::FLAG1_MATERIAL_HEIGHTFOG;
             }
             if self.blend.ty == BlendType::AddTrans
@@ -1028,7 +1633,7 @@ where A: Allocator + Clone
             flags |= ShaderFlag1::FLAG1_MATERIAL_EMISSIVE;
         }
         if self.flags.contains(MaterialFlags::ShadowReceiver) 
-        && glb.has_flags(GraphicsFlags::ShadowCaster) {
+        && ctx.has_flags(GraphicsFlags::ShadowCaster) {
             flags |= ShaderFlag1::FLAG1_MATERIAL_SHADOW;
         }
         if self.flags.contains(MaterialFlags::Texture5) {
@@ -1059,6 +1664,12 @@ where A: Allocator + Clone
         if self.flags2.contains(MaterialFlags2::Grayscale) {
             flags |= ShaderFlag0::FLAG0_GRAYSCALE;
         }
+        if ctx.hdr {
+            flags |= ShaderFlag0::FLAG0_HDR;
+        }
+        if ctx.deferred {
+            flags |= ShaderFlag0::FLAG0_DEFERRED;
+        }
         flags
     }
 
@@ -1066,12 +1677,57 @@ where A: Allocator + Clone
         self.name.get_string()
     }
 
+    pub fn get_extension(&self) -> Option<&Extension<A>> {
+        self.extension.map(|v| unsafe { v.as_ref() })
+    }
+
+    /// Get the texture in the given slot (0 for Texture1 up to 9 for Texture10), if the
+    /// material uses it
+    pub fn get_texture(&self, slot: usize) -> Option<&MaterialTexture<A>> {
+        match slot < self.textures.len() && self.flags.contains(MaterialFlags::from_bits_retain(1 << (20 + slot))) {
+            true => Some(&self.textures[slot]),
+            false => None
+        }
+    }
+
+    /// Iterate through the texture slots that the material uses, along with their slot index
+    pub fn iter_textures(&self) -> impl Iterator<Item = (usize, &MaterialTexture<A>)> {
+        (0..self.textures.len()).filter_map(|i| self.get_texture(i).map(|t| (i, t)))
+    }
+
+    /// Put a texture in the given slot, or clear the slot if `texture` is None. The slot's
+    /// texture flag is updated to match.
+    pub fn set_texture(&mut self, slot: usize, texture: Option<MaterialTexture<A>>) -> Result<(), MaterialError> {
+        if slot >= self.textures.len() {
+            return Err(MaterialError::InvalidTextureSlot(slot));
+        }
+        let flag = MaterialFlags::from_bits_retain(1 << (20 + slot));
+        self.set_flag(flag, texture.is_some());
+        if let Some(texture) = texture {
+            self.textures[slot] = texture;
+        }
+        Ok(())
+    }
+
+    pub fn set_name(&mut self, name: &str) {
+        self.name = Name::new_in(name, self._allocator.clone());
+    }
+
     pub fn get_shader_data(&self) -> &ShaderID {
         &self.shader
     }
+    pub fn set_shader_data(&mut self, shader: ShaderID) {
+        self.shader = shader;
+    }
     pub fn get_constant(&self) -> i32 {
         self.constant
     }
+    pub fn get_field6c(&self) -> f32 {
+        self.field16_0x6c
+    }
+    pub fn set_field6c(&mut self, value: f32) {
+        self.field16_0x6c = value;
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -1082,19 +1738,14 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
 {
     fn stream_read(stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<DeserializationHeap<Self, AObject>, Box<dyn Error>> {
         let mut this = DeserializationHeap::<Self, AObject>::zeroed(param);
-        // Original function: gfdMaterialInitialize (0x14106bf90, Steam Prologue Demo 1.01)
-        this.flags = MaterialFlags::Ambient | MaterialFlags::Diffuse;
-        this.alpha_test.func = AlphaTestFunc::GreaterOrEqual0;
-        this.blend.src_color = 1;
-        this.texture = unsafe { NonNull::new_unchecked((&mut this.textures).as_mut_ptr()) };
-        this.blend.src_alpha = 1;
-        this.blend.multiple = MultiplyType::Semi;
-        this.constant = -1;
-        this.field16_0x6c = 1.;
-        this.ref_ = Reference::new();
+        this.initialize();
         this.stream_read_inner(stream, param.get_heap_allocator().unwrap())?;
         Ok(this)
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream, param.get_heap_allocator().unwrap())
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -1104,12 +1755,11 @@ where AObject: Allocator + Clone {
     fn stream_read_inner<AStream, T>(&mut self, stream: &mut Stream<AStream, T>, alloc: AObject) -> Result<(), Box<dyn Error>>
     where T: Debug + Read + Write + Seek + StreamIODevice,
           AStream: Allocator + Clone + Debug {
-        self.mat_type = stream
-            .has_feature(GfdVersion::GFDV2)
-            .map_or::<Result<params::MaterialId, Box<dyn Error>>, _>(
-                Ok(params::MaterialId::Lambert),
-                |_| Ok(stream.read_u16()?.try_into()?)
-            )?;
+        // Only GFD v2 games store the material type, older games always use Lambert parameters
+        let mat_type = match stream.get_game_profile().is_gfd_v2() {
+            true => stream.read_u16()?.try_into()?,
+            false => MaterialId::Lambert
+        };
         self.name = Name::<AObject>::stream_read(stream, &mut NameSerializationContext::new(alloc.clone(), NameSerializationHash))?.into_raw();
         self.flags = MaterialFlags::from_bits_truncate(stream.read_u32()?);
         if stream.has_feature(GfdVersion::MaterialDiffusivitySSAONotRequired).is_none() {
@@ -1118,32 +1768,7 @@ where AObject: Allocator + Clone {
         if stream.has_feature(GfdVersion::MaterialAllowUVTransform).is_none() {
             self.flags.remove(MaterialFlags::UVTransform);
         }
-        #[cfg(feature = "v2-core")]
-        {
-            match self.mat_type {
-                params::MaterialId::Field => self.data.field = ManuallyDrop::new(crate::graphics::shader::attribute::field_v2::Field::<AObject>::stream_read(stream, &mut ())?.into_raw()),
-                params::MaterialId::Lambert => self.data.lambert = ManuallyDrop::new(crate::graphics::shader::attribute::lambert_v2::Lambert::<AObject>::stream_read(stream, &mut ())?.into_raw()),
-                params::MaterialId::CharacterToon => self.data.chara_toon = ManuallyDrop::new(crate::graphics::shader::attribute::toon_v2::CharacterToon::<AObject>::stream_read(stream, &mut ())?.into_raw()),
-                params::MaterialId::Type3 => self.data.type3 = ManuallyDrop::new(crate::graphics::shader::attribute::type3_v2::Type3::<AObject>::stream_read(stream, &mut ())?.into_raw()),
-                params::MaterialId::CharacterDistort => self.data.chara_distort = ManuallyDrop::new(crate::graphics::shader::attribute::distortion_v2::CharacterDistortion::<AObject>::stream_read(stream, &mut ())?.into_raw()),
-                params::MaterialId::Water => self.data.water = ManuallyDrop::new(crate::graphics::shader::attribute::water_v2::Water::<AObject>::stream_read(stream, &mut ())?.into_raw()),
-                params::MaterialId::DualLayer => self.data.dual_layer = ManuallyDrop::new(crate::graphics::shader::attribute::layered_v2::TwoLayer::<AObject>::stream_read(stream, &mut ())?.into_raw()),
-                params::MaterialId::Type7 => self.data.type7 = ManuallyDrop::new(crate::graphics::shader::attribute::type7_v2::FourLayer::<AObject>::stream_read(stream, &mut ())?.into_raw()),
-                params::MaterialId::Type8 => self.data.type8 = ManuallyDrop::new(crate::graphics::shader::attribute::type8_v2::Type8::<AObject>::stream_read(stream, &mut ())?.into_raw()),
-                params::MaterialId::Type9 => self.data.type9 = ManuallyDrop::new(crate::graphics::shader::attribute::type9_v2::Type9::<AObject>::stream_read(stream, &mut ())?.into_raw()),
-                params::MaterialId::Sky => self.data.sky = ManuallyDrop::new(crate::graphics::shader::attribute::sky_v2::Sky::<AObject>::stream_read(stream, &mut ())?.into_raw()),
-                params::MaterialId::Type11 => self.data.type11 = ManuallyDrop::new(crate::graphics::shader::attribute::type11_v2::Type11::<AObject>::stream_read(stream, &mut ())?.into_raw()),
-                params::MaterialId::CharacterMetal => self.data.metal = ManuallyDrop::new(crate::graphics::shader::attribute::metal_v2::Metal::<AObject>::stream_read(stream, &mut ())?.into_raw()),
-                params::MaterialId::Type13 => self.data.type13 = ManuallyDrop::new(crate::graphics::shader::attribute::type13_v2::Type13::<AObject>::stream_read(stream, &mut ())?.into_raw()),
-                params::MaterialId::Type14 => self.data.type14 = ManuallyDrop::new(crate::graphics::shader::attribute::type14_v2::Type14::<AObject>::stream_read(stream, &mut ())?.into_raw()),
-                params::MaterialId::Type15 => self.data.type15 = ManuallyDrop::new(crate::graphics::shader::attribute::type15_v2::Type15::<AObject>::stream_read(stream, &mut ())?.into_raw()),
-                params::MaterialId::Shadow => self.data.shadow = ManuallyDrop::new(crate::graphics::shader::attribute::shadow_v2::Shadow::<AObject>::stream_read(stream, &mut ())?.into_raw()),
-            }
-        }
-        #[cfg(feature = "v1-core")]
-        {
-            self.lambert = crate::graphics::shader::attribute::field_v2::Field::<AObject>::stream_read(stream, &mut ())?.into_raw();
-        }
+        self.stream_read_params(stream, mat_type)?;
         self.blend = Blending::stream_read(stream, &mut ())?.into_raw();
         self.alpha_test = AlphaTest::stream_read(stream, &mut ())?.into_raw();
         self.flags2 = stream
@@ -1172,19 +1797,307 @@ where AObject: Allocator + Clone {
             )?;
         for i in 0..10 {
             if self.flags.contains(MaterialFlags::from_bits_truncate(1 << (20 + i))) {
-                let tex_info: MaterialTexture<AObject> = MaterialTexture::stream_read(stream, &mut SerializationSingleAllocator::new(alloc.clone()))?.into_raw();
+                self.textures[i] = MaterialTexture::stream_read(stream, &mut SerializationSingleAllocator::new(alloc.clone()))?.into_raw();
             }
         }
-        println!("{}: {:?}, {:?}", self.name, self.mat_type, self.flags);
         // println!("ShaderFlags(PositionXYZ) = {:?}", self.get_shader_flags(VertexAttributeFlags::PositionXYZ));
         // GFD extensions (v1 only)
-        #[cfg(feature = "v1-core")]
-        {
-
+        if self.flags.contains(MaterialFlags::Extension) && !stream.get_game_profile().is_gfd_v2() {
+            self.extension = Some(Extension::<AObject>::stream_read(stream, &mut SerializationSingleAllocator::new(alloc.clone()))?.into_raw());
         }
         // println!("{:?}", unsafe { &self.data.chara_toon });
         // println!("{:?}", self.blend);
         // println!("{:?}, {:?}, {:?}", self.alpha_test, self.flags2, self.shader);
         Ok(())
     }
-}
\ No newline at end of file
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>, alloc: AObject) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        if stream.get_game_profile().is_gfd_v2() {
+            stream.write_u16(self.get_params_type() as u16)?;
+        }
+        NameSerializationHash.stream_write_inner(stream, &self.name)?;
+        stream.write_u32(self.flags.bits())?;
+        self.stream_write_params(stream)?;
+        self.blend.stream_write(stream, &mut ())?;
+        self.alpha_test.stream_write(stream, &mut ())?;
+        match stream.has_feature(GfdVersion::MaterialAddSecondFlags) {
+            Some(_) => {
+                stream.write_u16(self.flags2.bits())?;
+                stream.write_u16(self.sort_priority as u16)?;
+            },
+            None => stream.write_u32(self.sort_priority as u32)?
+        };
+        self.shader.stream_write(stream, &mut ())?;
+        stream.write_u16(self.culling as u16)?;
+        if stream.has_feature(GfdVersion::MaterialFlagsAllowConstantColor).is_some() {
+            stream.write_u32(self.constant as u32)?;
+        }
+        if stream.has_feature(GfdVersion::MaterialAddField6C).is_some() {
+            stream.write_f32(self.field16_0x6c)?;
+        }
+        for i in 0..10 {
+            if self.flags.contains(MaterialFlags::from_bits_truncate(1 << (20 + i))) {
+                self.textures[i].stream_write(stream, &mut SerializationSingleAllocator::new(alloc.clone()))?;
+            }
+        }
+        if self.flags.contains(MaterialFlags::Extension) && !stream.get_game_profile().is_gfd_v2() {
+            match self.extension {
+                Some(ext) => unsafe { ext.as_ref() }.stream_write(stream, &mut SerializationSingleAllocator::new(alloc.clone()))?,
+                None => stream.write_u32(0)?
+            };
+        }
+        Ok(())
+    }
+
+    // The parameter layout in the file is chosen by the game profile, but where it's stored
+    // depends on the core that OpenGFD was built for
+    #[cfg(feature = "v2-core")]
+    fn get_params_type(&self) -> MaterialId {
+        self.mat_type
+    }
+
+    #[cfg(not(feature = "v2-core"))]
+    fn get_params_type(&self) -> MaterialId {
+        MaterialId::Lambert
+    }
+
+    #[cfg(feature = "v2-core")]
+    fn stream_read_params<AStream, T>(&mut self, stream: &mut Stream<AStream, T>, mat_type: MaterialId) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        self.mat_type = mat_type;
+        match mat_type {
+            MaterialId::Field => self.data.field = ManuallyDrop::new(crate::graphics::shader::attribute::field_v2::Field::<AObject>::stream_read(stream, &mut ())?.into_raw()),
+            MaterialId::Lambert => self.data.lambert = ManuallyDrop::new(crate::graphics::shader::attribute::lambert_v2::Lambert::<AObject>::stream_read(stream, &mut ())?.into_raw()),
+            MaterialId::CharacterToon => self.data.chara_toon = ManuallyDrop::new(crate::graphics::shader::attribute::toon_v2::CharacterToon::<AObject>::stream_read(stream, &mut ())?.into_raw()),
+            MaterialId::Type3 => self.data.type3 = ManuallyDrop::new(crate::graphics::shader::attribute::type3_v2::Type3::<AObject>::stream_read(stream, &mut ())?.into_raw()),
+            MaterialId::CharacterDistort => self.data.chara_distort = ManuallyDrop::new(crate::graphics::shader::attribute::distortion_v2::CharacterDistortion::<AObject>::stream_read(stream, &mut ())?.into_raw()),
+            MaterialId::Water => self.data.water = ManuallyDrop::new(crate::graphics::shader::attribute::water_v2::Water::<AObject>::stream_read(stream, &mut ())?.into_raw()),
+            MaterialId::DualLayer => self.data.dual_layer = ManuallyDrop::new(crate::graphics::shader::attribute::layered_v2::TwoLayer::<AObject>::stream_read(stream, &mut ())?.into_raw()),
+            MaterialId::Type7 => self.data.type7 = ManuallyDrop::new(crate::graphics::shader::attribute::type7_v2::FourLayer::<AObject>::stream_read(stream, &mut ())?.into_raw()),
+            MaterialId::Type8 => self.data.type8 = ManuallyDrop::new(crate::graphics::shader::attribute::type8_v2::Type8::<AObject>::stream_read(stream, &mut ())?.into_raw()),
+            MaterialId::Type9 => self.data.type9 = ManuallyDrop::new(crate::graphics::shader::attribute::type9_v2::Type9::<AObject>::stream_read(stream, &mut ())?.into_raw()),
+            MaterialId::Sky => self.data.sky = ManuallyDrop::new(crate::graphics::shader::attribute::sky_v2::Sky::<AObject>::stream_read(stream, &mut ())?.into_raw()),
+            MaterialId::Type11 => self.data.type11 = ManuallyDrop::new(crate::graphics::shader::attribute::type11_v2::Type11::<AObject>::stream_read(stream, &mut ())?.into_raw()),
+            MaterialId::CharacterMetal => self.data.metal = ManuallyDrop::new(crate::graphics::shader::attribute::metal_v2::Metal::<AObject>::stream_read(stream, &mut ())?.into_raw()),
+            MaterialId::Type13 => self.data.type13 = ManuallyDrop::new(crate::graphics::shader::attribute::type13_v2::Type13::<AObject>::stream_read(stream, &mut ())?.into_raw()),
+            MaterialId::Type14 => self.data.type14 = ManuallyDrop::new(crate::graphics::shader::attribute::type14_v2::Type14::<AObject>::stream_read(stream, &mut ())?.into_raw()),
+            MaterialId::Type15 => self.data.type15 = ManuallyDrop::new(crate::graphics::shader::attribute::type15_v2::Type15::<AObject>::stream_read(stream, &mut ())?.into_raw()),
+            MaterialId::Shadow => self.data.shadow = ManuallyDrop::new(crate::graphics::shader::attribute::shadow_v2::Shadow::<AObject>::stream_read(stream, &mut ())?.into_raw()),
+        }
+        Ok(())
+    }
+
+    #[cfg(all(feature = "v1-core", not(feature = "v2-core")))]
+    fn stream_read_params<AStream, T>(&mut self, stream: &mut Stream<AStream, T>, mat_type: MaterialId) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        // v1 materials only have space for Lambert parameters
+        match mat_type {
+            MaterialId::Lambert => self.lambert = crate::graphics::shader::attribute::lambert_v2::Lambert::<AObject>::stream_read(stream, &mut ())?.into_raw(),
+            v => return Err(Box::new(MaterialError::ParametersUnavailable(v)))
+        };
+        Ok(())
+    }
+
+    #[cfg(not(any(feature = "v1-core", feature = "v2-core")))]
+    fn stream_read_params<AStream, T>(&mut self, _: &mut Stream<AStream, T>, mat_type: MaterialId) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        Err(Box::new(MaterialError::ParametersUnavailable(mat_type)))
+    }
+
+    #[cfg(feature = "v2-core")]
+    fn stream_write_params<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        match self.mat_type {
+            MaterialId::Field => unsafe { &self.data.field }.stream_write(stream, &mut ())?,
+            MaterialId::Lambert => unsafe { &self.data.lambert }.stream_write(stream, &mut ())?,
+            MaterialId::CharacterToon => unsafe { &self.data.chara_toon }.stream_write(stream, &mut ())?,
+            MaterialId::Type3 => unsafe { &self.data.type3 }.stream_write(stream, &mut ())?,
+            MaterialId::CharacterDistort => unsafe { &self.data.chara_distort }.stream_write(stream, &mut ())?,
+            MaterialId::Water => unsafe { &self.data.water }.stream_write(stream, &mut ())?,
+            MaterialId::DualLayer => unsafe { &self.data.dual_layer }.stream_write(stream, &mut ())?,
+            MaterialId::Type7 => unsafe { &self.data.type7 }.stream_write(stream, &mut ())?,
+            MaterialId::Type8 => unsafe { &self.data.type8 }.stream_write(stream, &mut ())?,
+            MaterialId::Type9 => unsafe { &self.data.type9 }.stream_write(stream, &mut ())?,
+            MaterialId::Sky => unsafe { &self.data.sky }.stream_write(stream, &mut ())?,
+            MaterialId::Type11 => unsafe { &self.data.type11 }.stream_write(stream, &mut ())?,
+            MaterialId::CharacterMetal => unsafe { &self.data.metal }.stream_write(stream, &mut ())?,
+            MaterialId::Type13 => unsafe { &self.data.type13 }.stream_write(stream, &mut ())?,
+            MaterialId::Type14 => unsafe { &self.data.type14 }.stream_write(stream, &mut ())?,
+            MaterialId::Type15 => unsafe { &self.data.type15 }.stream_write(stream, &mut ())?,
+            MaterialId::Shadow => unsafe { &self.data.shadow }.stream_write(stream, &mut ())?,
+        }
+        Ok(())
+    }
+
+    #[cfg(all(feature = "v1-core", not(feature = "v2-core")))]
+    fn stream_write_params<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        self.lambert.stream_write(stream, &mut ())
+    }
+
+    #[cfg(not(any(feature = "v1-core", feature = "v2-core")))]
+    fn stream_write_params<AStream, T>(&self, _: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        Err(Box::new(MaterialError::ParametersUnavailable(self.get_params_type())))
+    }
+}
+#[cfg(all(test, feature = "serialize"))]
+pub mod tests {
+    use allocator_api2::alloc::Global;
+    use crate::{
+        graphics::shader::attribute::outline_v1::OutlineFlags,
+        utility::stream::{ DevicePlatform, GfdSerialize, SerializationSingleAllocator, StreamFactory, StreamHeader }
+    };
+    use std::error::Error;
+    use glam::Mat4;
+    use super::{
+        extensions::ExtensionId,
+        Extension,
+        MaterialFlags,
+        MaterialTexture,
+        MaterialTextureFlags
+    };
+
+    type TestReturn = Result<(), Box<dyn Error>>;
+
+    #[test]
+    fn extension_list_round_trip() -> TestReturn {
+        let factory = StreamFactory::new(Global);
+        let header = StreamHeader::new(0x1105100, DevicePlatform::P5R_DX11);
+        let mut stream = factory.write_to_memory(header.clone())?;
+        // Outline (flags, palette), then Type6 (3 unknown u32s)
+        stream.write_u32_slice(&[2, 0x10002, 1, 3, 0x10006, 4, 5, 6])?;
+        let written = stream.as_slice().to_vec();
+        let mut stream = factory.read_from_memory(&written)?;
+        let mut param = SerializationSingleAllocator::new(Global);
+        let extension = Extension::<Global>::stream_read(&mut stream, &mut param)?.into_raw();
+        let extension = unsafe { extension.as_ref() };
+        assert_eq!(extension.iter().map(|e| e.get_id()).collect::<Vec<_>>(), vec![ExtensionId::Outline, ExtensionId::Type6]);
+        let outline = extension.get_outline().unwrap();
+        assert_eq!(outline.get_flags(), OutlineFlags::Normal);
+        assert_eq!(outline.get_palette(), 3);
+        assert!(extension.get_toon().is_none());
+        let mut out = factory.write_to_memory(header)?;
+        extension.stream_write(&mut out, &mut param)?;
+        assert_eq!(out.as_slice(), written.as_slice());
+        // Metaphor's unused extensions aren't in P5R
+        let mut stream = factory.write_to_memory(StreamHeader::new(0x1105100, DevicePlatform::P5R_DX11))?;
+        stream.write_u32_slice(&[1, 0x10009])?;
+        let written = stream.as_slice().to_vec();
+        let mut stream = factory.read_from_memory(&written)?;
+        assert!(Extension::<Global>::stream_read(&mut stream, &mut param).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn read_material_texture() -> TestReturn {
+        let factory = StreamFactory::new(Global);
+        let mut stream = factory.write_to_memory(StreamHeader::new(0x1105100, DevicePlatform::P5R_DX11))?;
+        stream.write_u16(5)?;
+        stream.write_u8_slice(b"a.dds")?;
+        stream.write_u32(0)?;
+        stream.write_u32(MaterialTextureFlags::HasUVTransform.bits())?;
+        stream.write_u8_slice(&[1, 0, 2, 1])?;
+        Mat4::from_scale(glam::Vec3::splat(2.)).stream_write(&mut stream, &mut ())?;
+        let written = stream.as_slice().to_vec();
+        let mut stream = factory.read_from_memory(&written)?;
+        let texture = MaterialTexture::<Global>::stream_read(&mut stream, &mut SerializationSingleAllocator::new(Global))?.into_raw();
+        assert_eq!(texture.get_name(), Some("a.dds"));
+        assert_eq!(texture.get_flags(), MaterialTextureFlags::HasUVTransform);
+        assert_eq!((texture.get_min_filter(), texture.get_mag_filter()), (1, 0));
+        assert_eq!((texture.get_wrap_s(), texture.get_wrap_t()), (2, 1));
+        assert_eq!(texture.get_transform(), Mat4::from_scale(glam::Vec3::splat(2.)));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "v2-core")]
+    fn shader_flags_from_context() -> TestReturn {
+        use crate::{
+            graphics::material_builder::MaterialBuilder,
+            kernel::graphics::GraphicsFlags,
+            object::geometry::VertexAttributeFlags
+        };
+        use super::{ MaterialId, ShaderFlag0, ShaderFlag1, ShaderFlagContext };
+        let material = MaterialBuilder::with_type("field", MaterialId::Field, Global)
+            .flag(MaterialFlags::Fog, true)
+            .flag(MaterialFlags::ShadowReceiver, true)
+            .flag(MaterialFlags::ShadowCaster, true)
+            .build()?;
+        let material = unsafe { material.as_ref() };
+        let vtx = VertexAttributeFlags::PositionXYZ | VertexAttributeFlags::Normal;
+        let flags = material.get_shader_flags_in(vtx, &ShaderFlagContext::default());
+        assert!(flags.get_flag0().contains(ShaderFlag0::FLAG0_ALWAYS_ENABLED));
+        assert!(!flags.get_flag1().intersects(ShaderFlag1::FLAG1_MATERIAL_FOG | ShaderFlag1::FLAG1_MATERIAL_SHADOW));
+        assert!(!material.check_shadow_caster_in(&ShaderFlagContext::default()));
+        let mut ctx = ShaderFlagContext::new(GraphicsFlags::Fog | GraphicsFlags::ShadowCaster);
+        ctx.hdr = true;
+        let flags = material.get_shader_flags_in(vtx, &ctx);
+        assert!(flags.get_flag1().contains(ShaderFlag1::FLAG1_MATERIAL_FOG | ShaderFlag1::FLAG1_MATERIAL_SHADOW));
+        assert!(!flags.get_flag1().contains(ShaderFlag1::FLAG1_MATERIAL_HEIGHTFOG));
+        assert!(flags.get_flag0().contains(ShaderFlag0::FLAG0_HDR));
+        assert!(!flags.get_flag0().contains(ShaderFlag0::FLAG0_DEFERRED));
+        assert!(material.check_shadow_caster_in(&ctx));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "v2-core")]
+    fn set_texture_slots() -> TestReturn {
+        use crate::graphics::material_builder::MaterialBuilder;
+        use super::{ MaterialError, MaterialId };
+        let mut material = MaterialBuilder::with_type("field", MaterialId::Field, Global).build()?;
+        let material = unsafe { material.as_mut() };
+        material.set_texture(9, Some(MaterialTexture::new_in("last.dds", Global)))?;
+        assert!(material.has_flags(MaterialFlags::Texture10));
+        assert_eq!(material.get_texture(9).and_then(|t| t.get_name()), Some("last.dds"));
+        assert_eq!(material.set_texture(10, Some(MaterialTexture::new_in("a.dds", Global))), Err(MaterialError::InvalidTextureSlot(10)));
+        material.set_texture(9, None)?;
+        assert!(!material.has_flags(MaterialFlags::Texture10));
+        assert!(material.get_texture(9).is_none());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(all(feature = "v1-core", not(feature = "v2-core")))]
+    fn v1_material_round_trip() -> TestReturn {
+        use crate::utility::{ misc::RGBAFloat, name::Name };
+        use super::Material;
+        let factory = StreamFactory::new(Global);
+        let header = StreamHeader::new(0x1105100, DevicePlatform::P5R_DX11);
+        let mut stream = factory.write_to_memory(header.clone())?;
+        stream.write_u16(7)?;
+        stream.write_u8_slice(b"lambert")?;
+        stream.write_u32(Name::new_in("lambert", Global).get_hash())?;
+        stream.write_u32((MaterialFlags::Ambient | MaterialFlags::Diffuse | MaterialFlags::Diffusivity | MaterialFlags::SSAO).bits())?;
+        // Ambient, diffuse, specular and emissive colors, then reflectivity and lerp blend rate
+        stream.write_f32_slice(&[0.25, 0.25, 0.25, 1., 0.5, 0.5, 0.5, 1., 0., 0., 0., 1., 0., 0., 0., 0., 0.75, 0.])?;
+        // Blending, alpha test, second flags and sort priority
+        stream.write_u8_slice(&[0; 6])?;
+        stream.write_u16_slice(&[0, 0, 0, 0])?;
+        // Shader, culling and constant color
+        stream.write_u16(0)?;
+        stream.write_u32_slice(&[0, 0])?;
+        stream.write_u16(0)?;
+        stream.write_u32(0)?;
+        let written = stream.as_slice().to_vec();
+        let mut stream = factory.read_from_memory(&written)?;
+        let mut param = SerializationSingleAllocator::new(Global);
+        let material = Material::<Global>::stream_read(&mut stream, &mut param)?.into_raw();
+        let material = unsafe { material.as_ref() };
+        assert_eq!(material.get_lambert().get_diffuse_color(), RGBAFloat::from_rgba_array_f32([0.5, 0.5, 0.5, 1.]));
+        assert_eq!(material.get_lambert().get_reflectivity(), 0.75);
+        let mut out = factory.write_to_memory(header)?;
+        material.stream_write(&mut out, &mut param)?;
+        assert_eq!(out.as_slice(), written.as_slice());
+        Ok(())
+    }
+}
@@ -0,0 +1,165 @@
+//! Create materials without loading them from a file.
+
+use allocator_api2::{
+    alloc::Allocator,
+    boxed::Box as ABox
+};
+use crate::graphics::material::{
+    params::MaterialId,
+    AlphaTest,
+    Blending,
+    Culling,
+    Material,
+    MaterialError,
+    MaterialFlags,
+    MaterialFlags2,
+    MaterialTexture,
+    MaterialType
+};
+use std::ptr::NonNull;
+
+/// Builds a material, starting from the defaults set by gfdMaterialInitialize. Flags that
+/// correspond to a setting (textures, alpha test) are updated along with that setting, and the
+/// finished material is checked with Material::validate. The first setting that couldn't be
+/// applied is returned as an error from build.
+pub struct MaterialBuilder<A>
+where A: Allocator + Clone {
+    material: ABox<Material<A>, A>,
+    error: Option<MaterialError>
+}
+
+impl<A> MaterialBuilder<A>
+where A: Allocator + Clone {
+    /// Start a material with the given parameters
+    pub fn new<T>(name: &str, data: T, allocator: A) -> Self
+    where T: MaterialType {
+        Self::with_type(name, data.get_material_id(), allocator).data(data)
+    }
+
+    /// Start a material with the default parameters for the material type
+    pub fn with_type(name: &str, mat_type: MaterialId, allocator: A) -> Self {
+        Self { material: Material::new_boxed_with_type_in(name, mat_type, allocator), error: None }
+    }
+
+    /// Replace the material's parameters, changing the material type to match
+    pub fn data<T>(mut self, data: T) -> Self
+    where T: MaterialType {
+        if let Err(e) = self.material.set_data(data) {
+            self.error.get_or_insert(e);
+        }
+        self
+    }
+
+    pub fn flag(mut self, flag: MaterialFlags, value: bool) -> Self {
+        self.material.set_flag(flag, value);
+        self
+    }
+
+    pub fn flag2(mut self, flag: MaterialFlags2, value: bool) -> Self {
+        self.material.set_flag2(flag, value);
+        self
+    }
+
+    pub fn blend(mut self, blend: Blending) -> Self {
+        self.material.set_blend(blend);
+        self
+    }
+
+    pub fn alpha_test(mut self, alpha_test: AlphaTest) -> Self {
+        self.material.set_alpha_test(alpha_test);
+        self
+    }
+
+    pub fn culling(mut self, culling: Culling) -> Self {
+        self.material.set_culling(culling);
+        self
+    }
+
+    pub fn sort_priority(mut self, priority: i16) -> Self {
+        self.material.set_sort_priority(priority);
+        self
+    }
+
+    pub fn constant(mut self, constant: i32) -> Self {
+        self.material.set_constant(constant);
+        self
+    }
+
+    /// Put a texture in the given slot (0 for Texture1 up to 9 for Texture10)
+    pub fn texture(mut self, slot: usize, texture: MaterialTexture<A>) -> Self {
+        if let Err(e) = self.material.set_texture(slot, Some(texture)) {
+            self.error.get_or_insert(e);
+        }
+        self
+    }
+
+    /// Access the material directly for settings that the builder doesn't cover
+    pub fn get_material_mut(&mut self) -> &mut Material<A> {
+        &mut self.material
+    }
+
+    /// Check the material and return it if its flags and parameters agree
+    pub fn build(self) -> Result<NonNull<Material<A>>, MaterialError> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        self.material.validate()?;
+        Ok(unsafe { NonNull::new_unchecked(ABox::into_raw(self.material)) })
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use allocator_api2::alloc::Global;
+    use crate::graphics::{
+        material::{
+            params::MaterialId,
+            AlphaTest,
+            AlphaTestFunc,
+            MaterialError,
+            MaterialFlags,
+            MaterialTexture
+        },
+        shader::attribute::{ field_v2::Field, lambert_v2::Lambert }
+    };
+    use glam::Mat4;
+    use std::error::Error;
+    use super::MaterialBuilder;
+
+    type TestReturn = Result<(), Box<dyn Error>>;
+
+    #[test]
+    fn build_materials() -> TestReturn {
+        let material = MaterialBuilder::with_type("metal", MaterialId::CharacterMetal, Global)
+            .alpha_test(AlphaTest::new(AlphaTestFunc::GreaterOrEqual0, 0x80))
+            .texture(0, MaterialTexture::new_in("base.dds", Global))
+            .build()?;
+        let material = unsafe { material.as_ref() };
+        assert_eq!(material.get_name(), Some("metal"));
+        assert_eq!(material.get_data_type(), MaterialId::CharacterMetal);
+        assert!(material.has_flags(MaterialFlags::Ambient | MaterialFlags::Diffuse | MaterialFlags::AlphaTest | MaterialFlags::Texture1));
+        assert_eq!(material.get_texture(0).and_then(|t| t.get_name()), Some("base.dds"));
+        assert!(material.get_texture(1).is_none());
+        // UV transforms are ignored unless the material allows them
+        let mut texture = MaterialTexture::new_in("base.dds", Global);
+        texture.set_transform(Mat4::from_scale(glam::Vec3::splat(2.)));
+        let result = MaterialBuilder::new("field", Field::<Global>::default(), Global)
+            .texture(0, texture)
+            .build();
+        assert!(matches!(result, Err(MaterialError::UVTransformDisabled(0))));
+        let result = MaterialBuilder::with_type("field", MaterialId::Field, Global)
+            .flag(MaterialFlags::Texture2, true)
+            .build();
+        assert!(matches!(result, Err(MaterialError::MissingTexture(1))));
+        let result = MaterialBuilder::with_type("field", MaterialId::Field, Global)
+            .texture(10, MaterialTexture::new_in("base.dds", Global))
+            .build();
+        assert!(matches!(result, Err(MaterialError::InvalidTextureSlot(10))));
+        // Extension lists are kept from v1 files, so the flag on its own is fine
+        let material = MaterialBuilder::new("lambert", Lambert::<Global>::default(), Global)
+            .flag(MaterialFlags::Extension, true)
+            .build()?;
+        assert_eq!(unsafe { material.as_ref() }.get_data_type(), MaterialId::Lambert);
+        Ok(())
+    }
+}
@@ -0,0 +1,382 @@
+//! Export materials to JSON or TOML so that they can be edited by hand, then
+//! import them back into a material.
+//!
+//! A material document stores everything that's written to a GFD file for a v2 material. Flags
+//! are written as a list of flag names, colors as arrays of floats and the parameter block as a
+//! table named after the material type. Texture filter and wrap modes are stored as the same
+//! numbers used in the file.
+//!
+//! ```toml
+//! name = "body"
+//! flags = "Ambient | Diffuse | Texture1 | Diffusivity | SSAO"
+//! flags2 = "EnableBloom"
+//! culling = "Backface"
+//! sort_priority = 0
+//! constant = 0
+//! field6c = 1.0
+//!
+//! [blend]
+//! ty = "Opaque"
+//! src_color = 0
+//! dst_color = 0
+//! src_alpha = 0
+//! dst_alpha = 0
+//! multiple = "None"
+//! control = 0
+//!
+//! [alpha_test]
+//! value = 0
+//! func = "Never"
+//!
+//! [shader]
+//! id = 0
+//! texcoord0 = 0
+//! texcoord1 = 0
+//!
+//! [[textures]]
+//! slot = 0
+//! name = "body_base.dds"
+//! flags = ""
+//! min_filter = 1
+//! mag_filter = 1
+//! wrap_s = 0
+//! wrap_t = 0
+//!
+//! [params.CharacterToon]
+//! base_color = [1.0, 1.0, 1.0, 1.0]
+//! # ...remaining CharacterToon fields
+//! ```
+//!
+//! Textures are listed by slot (0 for Texture1 up to 9 for Texture10), and importing a texture
+//! turns on its flag in the material. `transform` is an optional column major 4x4 matrix that's
+//! only stored when the texture has a UV transform. v1 material extensions aren't included.
+
+use allocator_api2::{
+    alloc::Allocator,
+    boxed::Box as ABox
+};
+use crate::graphics::{
+    material::{
+        params::MaterialId,
+        AlphaTest,
+        Blending,
+        Culling,
+        Material,
+        MaterialError,
+        MaterialFlags,
+        MaterialFlags2,
+        MaterialTexture,
+        MaterialTextureFlags
+    },
+    shader::{
+        attribute::{
+            distortion_v2::CharacterDistortion,
+            field_v2::Field,
+            lambert_v2::Lambert,
+            layered_v2::TwoLayer,
+            metal_v2::Metal,
+            shadow_v2::Shadow,
+            sky_v2::Sky,
+            toon_v2::CharacterToon,
+            type3_v2::Type3,
+            type7_v2::FourLayer,
+            type8_v2::Type8,
+            type9_v2::Type9,
+            type11_v2::Type11,
+            type13_v2::Type13,
+            type14_v2::Type14,
+            type15_v2::Type15,
+            water_v2::Water
+        },
+        shader::ShaderID
+    }
+};
+use glam::Mat4;
+use serde::{ Deserialize, Serialize };
+use std::{
+    error::Error,
+    ptr::NonNull
+};
+
+/// Parameter block for each material type
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub enum MaterialParams<A>
+where A: Allocator + Clone {
+    Field(Field<A>),
+    Lambert(Lambert<A>),
+    CharacterToon(CharacterToon<A>),
+    Type3(Type3<A>),
+    CharacterDistort(CharacterDistortion<A>),
+    Water(Water<A>),
+    DualLayer(TwoLayer<A>),
+    Type7(FourLayer<A>),
+    Type8(Type8<A>),
+    Type9(Type9<A>),
+    Sky(Sky<A>),
+    Type11(Type11<A>),
+    CharacterMetal(Metal<A>),
+    Type13(Type13<A>),
+    Type14(Type14<A>),
+    Type15(Type15<A>),
+    Shadow(Shadow<A>)
+}
+
+impl<A> MaterialParams<A>
+where A: Allocator + Clone {
+    pub fn from_material(material: &Material<A>) -> Self {
+        unsafe {
+            match material.get_data_type() {
+                MaterialId::Field => Self::Field(material.read_data()),
+                MaterialId::Lambert => Self::Lambert(material.read_data()),
+                MaterialId::CharacterToon => Self::CharacterToon(material.read_data()),
+                MaterialId::Type3 => Self::Type3(material.read_data()),
+                MaterialId::CharacterDistort => Self::CharacterDistort(material.read_data()),
+                MaterialId::Water => Self::Water(material.read_data()),
+                MaterialId::DualLayer => Self::DualLayer(material.read_data()),
+                MaterialId::Type7 => Self::Type7(material.read_data()),
+                MaterialId::Type8 => Self::Type8(material.read_data()),
+                MaterialId::Type9 => Self::Type9(material.read_data()),
+                MaterialId::Sky => Self::Sky(material.read_data()),
+                MaterialId::Type11 => Self::Type11(material.read_data()),
+                MaterialId::CharacterMetal => Self::CharacterMetal(material.read_data()),
+                MaterialId::Type13 => Self::Type13(material.read_data()),
+                MaterialId::Type14 => Self::Type14(material.read_data()),
+                MaterialId::Type15 => Self::Type15(material.read_data()),
+                MaterialId::Shadow => Self::Shadow(material.read_data()),
+            }
+        }
+    }
+
+    fn into_material(self, name: &str, allocator: A) -> Result<ABox<Material<A>, A>, MaterialError> {
+        match self {
+            Self::Field(v) => Material::new_boxed_in(name, v, allocator),
+            Self::Lambert(v) => Material::new_boxed_in(name, v, allocator),
+            Self::CharacterToon(v) => Material::new_boxed_in(name, v, allocator),
+            Self::Type3(v) => Material::new_boxed_in(name, v, allocator),
+            Self::CharacterDistort(v) => Material::new_boxed_in(name, v, allocator),
+            Self::Water(v) => Material::new_boxed_in(name, v, allocator),
+            Self::DualLayer(v) => Material::new_boxed_in(name, v, allocator),
+            Self::Type7(v) => Material::new_boxed_in(name, v, allocator),
+            Self::Type8(v) => Material::new_boxed_in(name, v, allocator),
+            Self::Type9(v) => Material::new_boxed_in(name, v, allocator),
+            Self::Sky(v) => Material::new_boxed_in(name, v, allocator),
+            Self::Type11(v) => Material::new_boxed_in(name, v, allocator),
+            Self::CharacterMetal(v) => Material::new_boxed_in(name, v, allocator),
+            Self::Type13(v) => Material::new_boxed_in(name, v, allocator),
+            Self::Type14(v) => Material::new_boxed_in(name, v, allocator),
+            Self::Type15(v) => Material::new_boxed_in(name, v, allocator),
+            Self::Shadow(v) => Material::new_boxed_in(name, v, allocator),
+        }
+    }
+}
+
+/// The parts of ShaderID that are stored in the file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShaderDocument {
+    pub id: i16,
+    pub texcoord0: u32,
+    pub texcoord1: u32
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextureDocument {
+    pub slot: usize,
+    pub name: String,
+    pub flags: MaterialTextureFlags,
+    pub min_filter: u8,
+    pub mag_filter: u8,
+    pub wrap_s: u8,
+    pub wrap_t: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transform: Option<[f32; 16]>
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MaterialDocument<A>
+where A: Allocator + Clone {
+    pub name: String,
+    pub flags: MaterialFlags,
+    pub flags2: MaterialFlags2,
+    pub blend: Blending,
+    pub alpha_test: AlphaTest,
+    pub culling: Culling,
+    pub sort_priority: i16,
+    pub constant: i32,
+    /// Only stored in files that have MaterialAddField6C
+    #[serde(default = "default_field6c")]
+    pub field6c: f32,
+    pub shader: ShaderDocument,
+    #[serde(default)]
+    pub textures: Vec<TextureDocument>,
+    pub params: MaterialParams<A>
+}
+
+// gfdMaterialInitialize sets this to 1
+fn default_field6c() -> f32 { 1. }
+
+/// Number of texture slots in a material (Texture1 to Texture10)
+const MATERIAL_TEXTURE_SLOTS: usize = 10;
+
+impl<A> MaterialDocument<A>
+where A: Allocator + Clone {
+    pub fn from_material(material: &Material<A>) -> Self {
+        let shader = material.get_shader_data();
+        let textures = material.iter_textures()
+            .map(|(slot, texture)| {
+                let flags = texture.get_flags();
+                TextureDocument {
+                    slot,
+                    name: texture.get_name().unwrap_or_default().to_owned(),
+                    flags,
+                    min_filter: texture.get_min_filter(),
+                    mag_filter: texture.get_mag_filter(),
+                    wrap_s: texture.get_wrap_s(),
+                    wrap_t: texture.get_wrap_t(),
+                    transform: flags.contains(MaterialTextureFlags::HasUVTransform)
+                        .then(|| texture.get_transform().to_cols_array())
+                }
+            }).collect();
+        Self {
+            name: material.get_name().unwrap_or_default().to_owned(),
+            flags: material.get_flag(),
+            flags2: material.get_flag2(),
+            blend: material.get_blend().clone(),
+            alpha_test: material.get_alpha_test().clone(),
+            culling: material.get_culling(),
+            sort_priority: material.get_sort_priority(),
+            constant: material.get_constant(),
+            field6c: material.get_field6c(),
+            shader: ShaderDocument {
+                id: shader.get_id(),
+                texcoord0: *shader.get_texcoord_in(),
+                texcoord1: *shader.get_texcoord_out()
+            },
+            textures,
+            params: MaterialParams::from_material(material)
+        }
+    }
+
+    /// Create a material from this document. The material is checked with Material::validate
+    /// before it's returned.
+    pub fn into_material(self, allocator: A) -> Result<NonNull<Material<A>>, Box<dyn Error>> {
+        if let Some(doc) = self.textures.iter().find(|t| t.slot >= MATERIAL_TEXTURE_SLOTS) {
+            return Err(Box::new(MaterialError::InvalidTextureSlot(doc.slot)));
+        }
+        let mut material = self.params.into_material(&self.name, allocator.clone())?;
+        material.set_all_flags(self.flags);
+        material.set_all_flags2(self.flags2);
+        material.set_blend(self.blend);
+        // set_alpha_test turns on the alpha test flag, so restore the flags from the document
+        material.set_alpha_test(self.alpha_test);
+        material.set_flag(MaterialFlags::AlphaTest, self.flags.contains(MaterialFlags::AlphaTest));
+        material.set_culling(self.culling);
+        material.set_sort_priority(self.sort_priority);
+        material.set_constant(self.constant);
+        material.set_field6c(self.field6c);
+        material.set_shader_data(ShaderID::new(
+            self.shader.id,
+            self.shader.texcoord0.try_into()?,
+            self.shader.texcoord1.try_into()?
+        ));
+        for doc in self.textures {
+            let mut texture = MaterialTexture::new_in(&doc.name, allocator.clone());
+            texture.set_flags(doc.flags);
+            texture.set_filter(doc.min_filter, doc.mag_filter);
+            texture.set_wrap(doc.wrap_s, doc.wrap_t);
+            if let Some(tm) = doc.transform {
+                texture.set_transform(Mat4::from_cols_array(&tm));
+            }
+            material.set_texture(doc.slot, Some(texture))?;
+        }
+        material.validate()?;
+        Ok(unsafe { NonNull::new_unchecked(ABox::into_raw(material)) })
+    }
+
+    pub fn to_json(&self) -> Result<String, Box<dyn Error>> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(serde_json::from_str(text)?)
+    }
+
+    pub fn to_toml(&self) -> Result<String, Box<dyn Error>> {
+        Ok(toml::to_string_pretty(self)?)
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(toml::from_str(text)?)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use allocator_api2::alloc::Global;
+    use crate::graphics::{
+        material::{
+            params::MaterialId,
+            AlphaTest,
+            AlphaTestFunc,
+            MaterialError,
+            MaterialFlags,
+            MaterialTexture,
+            MaterialTextureFlags
+        },
+        material_builder::MaterialBuilder
+    };
+    use glam::{ Mat4, Vec3 };
+    use std::error::Error;
+    use super::{ MaterialDocument, TextureDocument };
+
+    type TestReturn = Result<(), Box<dyn Error>>;
+
+    #[test]
+    fn material_document_round_trip() -> TestReturn {
+        let mut texture = MaterialTexture::new_in("body_base.dds", Global);
+        texture.set_transform(Mat4::from_scale(Vec3::new(2., 1., 1.)));
+        let material = MaterialBuilder::with_type("body", MaterialId::CharacterToon, Global)
+            .flag(MaterialFlags::UVTransform, true)
+            .alpha_test(AlphaTest::new(AlphaTestFunc::GreaterOrEqual0, 0x80))
+            .texture(0, texture)
+            .texture(2, MaterialTexture::new_in("body_normal.dds", Global))
+            .build()?;
+        let document = MaterialDocument::from_material(unsafe { material.as_ref() });
+        let toml = document.to_toml()?;
+        let json = document.to_json()?;
+        let imported = MaterialDocument::<Global>::from_toml(&toml)?.into_material(Global)?;
+        let imported = unsafe { imported.as_ref() };
+        assert_eq!(imported.get_data_type(), MaterialId::CharacterToon);
+        assert!(imported.has_flags(MaterialFlags::AlphaTest | MaterialFlags::Texture1 | MaterialFlags::Texture3));
+        assert_eq!(imported.get_texture(2).and_then(|t| t.get_name()), Some("body_normal.dds"));
+        assert_eq!(imported.get_texture(0).map(|t| t.get_transform()), Some(Mat4::from_scale(Vec3::new(2., 1., 1.))));
+        assert_eq!(MaterialDocument::from_material(imported).to_toml()?, toml);
+        let imported = MaterialDocument::<Global>::from_json(&json)?.into_material(Global)?;
+        assert_eq!(MaterialDocument::from_material(unsafe { imported.as_ref() }).to_json()?, json);
+        Ok(())
+    }
+
+    #[test]
+    fn material_document_fields() -> TestReturn {
+        let mut material = MaterialBuilder::with_type("field", MaterialId::Field, Global).build()?;
+        unsafe { material.as_mut() }.set_field6c(0.5);
+        let mut document = MaterialDocument::from_material(unsafe { material.as_ref() });
+        let imported = MaterialDocument::<Global>::from_toml(&document.to_toml()?)?.into_material(Global)?;
+        assert_eq!(unsafe { imported.as_ref() }.get_field6c(), 0.5);
+        // Texture slots past Texture10 don't exist
+        document.textures.push(TextureDocument {
+            slot: 10,
+            name: "a.dds".to_owned(),
+            flags: MaterialTextureFlags::empty(),
+            min_filter: 1,
+            mag_filter: 1,
+            wrap_s: 0,
+            wrap_t: 0,
+            transform: None
+        });
+        let result = document.into_material(Global);
+        assert!(matches!(result.as_ref().map_err(|e| e.downcast_ref::<MaterialError>()), Err(Some(MaterialError::InvalidTextureSlot(10)))));
+        Ok(())
+    }
+}
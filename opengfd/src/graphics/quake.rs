@@ -47,6 +47,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -63,6 +67,19 @@ impl QuakeParams {
         self.fade_out_time = stream.read_f32()?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        stream.write_f32(self.power)?;
+        stream.write_f32(self.pitch_weight)?;
+        stream.write_f32(self.total_time)?;
+        stream.write_f32(self.fade_in_time)?;
+        stream.write_f32(self.fade_out_time)?;
+        Ok(())
+    }
 }
 
 bitflags! {
@@ -46,6 +46,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream, param)?;
         Ok(this)
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ExtensionObjectContext<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(all(feature = "serialize", not(feature = "cfb_gap")))]
@@ -60,6 +64,15 @@ where AObject: Allocator + Clone {
         self.field1c = stream.read_u32()?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug
+    {
+        stream.write_f32(self.field18)?;
+        stream.write_u32(self.field1c)?;
+        Ok(())
+    }
 }
 
 #[cfg(all(feature = "serialize", feature = "cfb_gap"))]
@@ -82,4 +95,21 @@ where AObject: Allocator + Clone {
         self.field54 = stream.read_u32()?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug
+    {
+        self.field18.stream_write(stream, &mut ())?;
+        stream.write_f32(self.field28)?;
+        stream.write_f32(self.field2c)?;
+        stream.write_f32(self.field30)?;
+        self.field34.stream_write(stream, &mut ())?;
+        stream.write_f32(self.field44)?;
+        stream.write_f32(self.field48)?;
+        stream.write_f32(self.field4c)?;
+        stream.write_f32(self.field50)?;
+        stream.write_u32(self.field54)?;
+        Ok(())
+    }
 }
\ No newline at end of file
@@ -30,6 +30,7 @@ use crate::utility::stream::{DeserializationStack, GfdSerialize, Stream, StreamI
 
 bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    #[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct DistortionFlags: u32 {
         const FlowMapMultiAsMask = 0x00000001;
         const HDRStar = 0x00000002;
@@ -55,6 +56,8 @@ bitflags! {
 /// Shader File: 21.HLSL
 #[repr(C)]
 #[derive(Debug)]
+#[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "io_serde", serde(bound = ""))]
 pub struct CharacterDistortion<A = GfdAllocator> 
 where A: Allocator + Clone
 {
@@ -68,6 +71,7 @@ where A: Allocator + Clone
     multi_fitting_tile: f32,
     fieldc8: f32,
     flags: DistortionFlags,
+    #[cfg_attr(feature = "io_serde", serde(skip))]
     _allocator: std::marker::PhantomData<A>
 }
 
@@ -215,6 +219,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -236,4 +244,39 @@ where AObject: Allocator + Clone {
         self.fieldc8 = 1.;
         Ok(())
     }
-}
\ No newline at end of file
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug
+    {
+        self.base_color.stream_write(stream, &mut ())?;
+        self.emissive_color.stream_write(stream, &mut ())?;
+        stream.write_f32(self.distortion_power)?;
+        stream.write_f32(self.distortion_threshold)?;
+        stream.write_f32(self.p4_4)?;
+        stream.write_u32(self.flags.bits())?;
+        if stream.has_feature(GfdVersion::MaterialParameter4AddBloomIntensity).is_some() {
+            stream.write_f32(self.bloom_strength)?;
+        }
+        if stream.has_feature(GfdVersion::MaterialParameterDistortAddMultiFittingTile).is_some() {
+            stream.write_f32(self.fitting_tile)?;
+        }
+        if stream.has_feature(GfdVersion::MaterialParameterDistortAddP8).is_some() {
+            stream.write_f32(self.multi_fitting_tile)?;
+        }
+        Ok(())
+    }
+}
+
+impl<A> Default for CharacterDistortion<A>
+where A: Allocator + Clone
+{
+    fn default() -> Self {
+        let mut this: Self = unsafe { MaybeUninit::zeroed().assume_init() };
+        this.base_color = RGBAFloat::from_single_f32(1.);
+        this.bloom_strength = 0.5;
+        this.fitting_tile = 1.;
+        this.fieldc8 = 1.;
+        this
+    }
+}
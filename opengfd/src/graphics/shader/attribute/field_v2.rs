@@ -27,6 +27,7 @@ use crate::utility::stream::{DeserializationStack, GfdSerialize, Stream, StreamI
 
 bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    #[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct FieldFlags: u32 {
         const Flag0 = 0x00000001;
         const Flag1 = 0x00000002;
@@ -48,6 +49,8 @@ bitflags! {
 /// Shader File: 7.HLSL or 9.HLSL
 #[repr(C)]
 #[derive(Debug)]
+#[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "io_serde", serde(bound = ""))]
 pub struct Field<A = GfdAllocator> 
 where A: Allocator + Clone
 {
@@ -58,6 +61,7 @@ where A: Allocator + Clone
     multi_alpha: f32,
     bloom_intensity: f32,
     flags: FieldFlags,
+    #[cfg_attr(feature = "io_serde", serde(skip))]
     _allocator: std::marker::PhantomData<A>
 }
 
@@ -68,6 +72,8 @@ where A: Allocator + Clone
         let ofs = Material::<A>::get_mat_data_offset();
         unsafe { &*((&raw const *self as *const u8).sub(ofs) as *const Material<A>) }
     }
+
+    pub fn get_base_color(&self) -> RGBAFloat { self.base_color }
 }
 
 impl<A> MaterialType for Field<A> 
@@ -119,6 +125,12 @@ where A: Allocator + Clone
     fn get_tex5_name(&self) -> &'static str { "Multiply Texture" }
     // R: roughness, G: metallic, B: emissive, A: intensity
     fn get_tex8_name(&self) -> &'static str { "PBR Params Texture" }
+    fn get_required_flags(&self) -> MaterialFlags {
+        match self.flags.contains(FieldFlags::MultiTextureMask) {
+            true => MaterialFlags::Texture5,
+            false => MaterialFlags::empty()
+        }
+    }
 
     fn set_shader_flags(&self, _vtx: VertexAttributeFlags, flags: &mut ShaderFlags) {
         let mat = self.get_material();
@@ -185,6 +197,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -206,4 +222,39 @@ where AObject: Allocator + Clone {
         }
         Ok(())
     }
-}
\ No newline at end of file
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug
+    {
+        self.base_color.stream_write(stream, &mut ())?;
+        stream.write_f32(self.emissive_strength)?;
+        stream.write_f32(self.roughness)?;
+        stream.write_f32(self.metallic)?;
+        if stream.has_feature(GfdVersion::MaterialParameter0AddMultiAlpha).is_some() {
+            stream.write_f32(self.multi_alpha)?;
+        }
+        if stream.has_feature(GfdVersion::MaterialParameterAddBloomIntensity).is_some() {
+            stream.write_f32(self.bloom_intensity)?;
+        }
+        if stream.has_feature(GfdVersion::MaterialFieldAddFlags).is_some() {
+            stream.write_u32(self.flags.bits())?;
+        }
+        if stream.get_header_version() == GfdVersion::MaterialFieldAddExtraFloat as u32 {
+            stream.write_f32(0.)?;
+        }
+        Ok(())
+    }
+}
+
+impl<A> Default for Field<A>
+where A: Allocator + Clone
+{
+    fn default() -> Self {
+        let mut this: Self = unsafe { MaybeUninit::zeroed().assume_init() };
+        this.base_color = RGBAFloat::from_single_f32(1.);
+        this.multi_alpha = 1.;
+        this.bloom_intensity = 0.5;
+        this
+    }
+}
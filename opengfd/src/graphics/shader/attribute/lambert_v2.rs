@@ -23,7 +23,7 @@ use crate::{
 };
 use glam::Vec4;
 use crate::graphics::material::{MaterialFlags, MaterialFlags2};
-use crate::graphics::material::params::MaterialId;
+use crate::graphics::material::MaterialId;
 use crate::kernel::version::GfdVersion;
 use crate::utility::misc::RGBAFloat;
 use crate::utility::stream::{DeserializationStack, GfdSerialize, Stream, StreamIODevice};
@@ -32,6 +32,8 @@ use crate::utility::stream::{DeserializationStack, GfdSerialize, Stream, StreamI
 /// Shader file: 3.HLSL
 #[repr(C)]
 #[derive(Debug)]
+#[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "io_serde", serde(bound = ""))]
 pub struct Lambert<A = GfdAllocator> 
 where A: Allocator + Clone 
 {
@@ -41,12 +43,27 @@ where A: Allocator + Clone
     emissive_color: RGBAFloat,
     reflectivity: f32,
     lerp_blend_rate: f32,
+    #[cfg_attr(feature = "io_serde", serde(skip))]
     _allocator: std::marker::PhantomData<A>
 }
 
 impl<A> Lambert<A> 
 where A: Allocator + Clone
 {
+    /// Create Lambert parameters with the given diffuse color. Ambient uses the same color, while
+    /// specular and emissive are left black.
+    pub fn new(diffuse_color: RGBAFloat) -> Self {
+        Self {
+            ambient_color: diffuse_color,
+            diffuse_color,
+            specular_color: RGBAFloat::from_rgba_array_f32([0., 0., 0., 1.]),
+            emissive_color: RGBAFloat::from_rgba_array_f32([0., 0., 0., 0.]),
+            reflectivity: 0.,
+            lerp_blend_rate: 0.,
+            _allocator: std::marker::PhantomData
+        }
+    }
+
     pub fn get_material(&self) -> &Material<A> {
         let ofs = Material::<A>::get_mat_data_offset();
         unsafe { &*((&raw const *self as *const u8).sub(ofs) as *const Material<A>) }
@@ -162,6 +179,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -179,4 +200,25 @@ where AObject: Allocator + Clone {
         self.lerp_blend_rate = stream.read_f32()?;
         Ok(())
     }
-}
\ No newline at end of file
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug
+    {
+        self.ambient_color.stream_write(stream, &mut ())?;
+        self.diffuse_color.stream_write(stream, &mut ())?;
+        self.specular_color.stream_write(stream, &mut ())?;
+        self.emissive_color.stream_write(stream, &mut ())?;
+        stream.write_f32(self.reflectivity)?;
+        stream.write_f32(self.lerp_blend_rate)?;
+        Ok(())
+    }
+}
+
+impl<A> Default for Lambert<A>
+where A: Allocator + Clone
+{
+    fn default() -> Self {
+        Self::new(RGBAFloat::from_single_f32(1.))
+    }
+}
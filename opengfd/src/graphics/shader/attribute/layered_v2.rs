@@ -27,6 +27,7 @@ use crate::utility::stream::{DeserializationStack, GfdSerialize, Stream, StreamI
 
 bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    #[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct TwoLayerFlags: u32 {
             const Automatic = 0x00000001;
             const UseSecondColorSet = 0x00000002;
@@ -44,6 +45,7 @@ bitflags! {
 
 #[repr(C)]
 #[derive(Debug)]
+#[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LayerData {
     base_color: RGBAFloat,
     emissive: f32,
@@ -62,6 +64,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -77,10 +83,24 @@ impl LayerData {
         self.f4 = stream.read_f32()?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug
+    {
+        self.base_color.stream_write(stream, &mut ())?;
+        stream.write_f32(self.emissive)?;
+        stream.write_f32(self.roughness)?;
+        stream.write_f32(self.bloom_intensity)?;
+        stream.write_f32(self.f4)?;
+        Ok(())
+    }
 }
 
 #[repr(C)]
 #[derive(Debug)]
+#[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "io_serde", serde(bound = ""))]
 pub struct TwoLayer<A = GfdAllocator> 
 where A: Allocator + Clone
 {
@@ -90,6 +110,7 @@ where A: Allocator + Clone
     p6_3: f32,
     p6_4: f32,
     flags: TwoLayerFlags,
+    #[cfg_attr(feature = "io_serde", serde(skip))]
     _allocator: std::marker::PhantomData<A>
 }
 
@@ -197,6 +218,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -222,4 +247,36 @@ where AObject: Allocator + Clone {
         self.flags = TwoLayerFlags::from_bits_truncate(stream.read_u32()?);
         Ok(())
     }
-}
\ No newline at end of file
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug
+    {
+        for i in 0..2 {
+            self.layers[i].stream_write(stream, &mut ())?;
+        }
+        if stream.get_header_version() < GfdVersion::MaterialParameterLayerExtraFields as u32 {
+            stream.write_f32(self.p6_1)?;
+        } else {
+            stream.write_f32(self.p6_1)?;
+            stream.write_f32(self.p6_2)?;
+            stream.write_f32(self.p6_3)?;
+        }
+        stream.write_f32(self.p6_4)?;
+        stream.write_u32(self.flags.bits())?;
+        Ok(())
+    }
+}
+
+impl<A> Default for TwoLayer<A>
+where A: Allocator + Clone
+{
+    fn default() -> Self {
+        let mut this: Self = unsafe { MaybeUninit::zeroed().assume_init() };
+        for layer in &mut this.layers {
+            layer.base_color = RGBAFloat::from_single_f32(1.);
+            layer.bloom_intensity = 0.5;
+        }
+        this
+    }
+}
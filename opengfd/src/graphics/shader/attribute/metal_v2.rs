@@ -32,6 +32,7 @@ use crate::utility::stream::{DeserializationStack, GfdSerialize, Stream, StreamI
 
 bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    #[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct MetalFlags: u32 {
         const ToonReferenceNormalMap = 0x00000001;
         const FLAG1 = 0x00000002;
@@ -66,6 +67,8 @@ bitflags! {
 /// Shader File: 45.HLSL
 #[repr(C)]
 #[derive(Debug)]
+#[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "io_serde", serde(bound = ""))]
 pub struct Metal<A = GfdAllocator> 
 where A: Allocator + Clone
 {
@@ -93,6 +96,7 @@ where A: Allocator + Clone
     p12_13: f32,
     field128: f32,
     flags: MetalFlags,
+    #[cfg_attr(feature = "io_serde", serde(skip))]
     _allocator: std::marker::PhantomData<A>
 }
 
@@ -233,6 +237,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -268,4 +276,73 @@ where AObject: Allocator + Clone {
         self.field128 = 1.;
         Ok(())
     }
-}
\ No newline at end of file
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug
+    {
+        self.base_color.stream_write(stream, &mut ())?;
+        self.edge_color.stream_write(stream, &mut ())?;
+        self.emissive_color.stream_write(stream, &mut ())?;
+        stream.write_f32(self.metallic)?;
+        stream.write_f32(self.edge_threshold)?;
+        stream.write_f32(self.edge_factor)?;
+        stream.write_u32(self.flags.bits())?;
+        stream.write_f32(self.p12_7)?;
+        self.p12_8.stream_write(stream, &mut ())?;
+        stream.write_f32(self.mat_bloom_intensity)?;
+        stream.write_f32(self.edge_remove_y_axis_factor)?;
+        if stream.has_feature(GfdVersion::MaterialParameterToonAddP17).is_some() {
+            stream.write_f32(self.p12_11)?;
+        }
+        if stream.has_feature(GfdVersion::MaterialParameterToonAddP17).is_some() {
+            stream.write_f32(self.p12_12)?;
+        }
+        if stream.has_feature(GfdVersion::MaterialParameterToonAddP17).is_some() {
+            stream.write_f32(self.p12_13)?;
+        }
+        if stream.has_feature(GfdVersion::MaterialParameterToonAddP20).is_some() {
+            stream.write_f32(self.mat_bloom_intensity2)?;
+        }
+        if stream.has_feature(GfdVersion::MaterialParameterMetalAddSpecular).is_some() {
+            self.specular_color.stream_write(stream, &mut ())?;
+        }
+        if stream.has_feature(GfdVersion::MaterialParameterMetalAddSpecular).is_some() {
+            stream.write_f32(self.specular_threshold)?;
+        }
+        if stream.has_feature(GfdVersion::MaterialParameterMetalAddSpecular).is_some() {
+            stream.write_f32(self.specular_power)?;
+        }
+        if stream.has_feature(GfdVersion::MaterialParameterMetalAddSpecular).is_some() {
+            stream.write_f32(self.roughness)?;
+        }
+        if stream.has_feature(GfdVersion::MaterialParameterMetalAddSpecular).is_some() {
+            stream.write_f32(self.p12_19)?;
+        }
+        if stream.has_feature(GfdVersion::MaterialParameterMetalAddShadow).is_some() {
+            self.shadow_color.stream_write(stream, &mut ())?;
+        }
+        if stream.has_feature(GfdVersion::MaterialParameterMetalAddShadow).is_some() {
+            stream.write_f32(self.shadow_threshold)?;
+        }
+        if stream.has_feature(GfdVersion::MaterialParameterMetalAddShadow).is_some() {
+            stream.write_f32(self.shadow_factor)?;
+        }
+        Ok(())
+    }
+}
+
+impl<A> Default for Metal<A>
+where A: Allocator + Clone
+{
+    fn default() -> Self {
+        let mut this: Self = unsafe { MaybeUninit::zeroed().assume_init() };
+        this.base_color = RGBAFloat::from_single_f32(1.);
+        this.specular_threshold = 1.;
+        this.roughness = 1.;
+        this.p12_11 = 1.;
+        this.mat_bloom_intensity2 = 0.1;
+        this.field128 = 1.;
+        this
+    }
+}
@@ -35,6 +35,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream, param)?;
         Ok(this)
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ExtensionObjectContext<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -49,4 +53,22 @@ where AObject: Allocator + Clone {
         self.palette = stream.read_u32()?;
         Ok(())
     }
-}
\ No newline at end of file
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug
+    {
+        stream.write_u32(self.flags.bits())?;
+        stream.write_u32(self.palette)?;
+        Ok(())
+    }
+}
+
+impl<A> Outline<A>
+where A: Allocator + Clone {
+    pub fn get_flags(&self) -> OutlineFlags { self.flags }
+    pub fn get_palette(&self) -> u32 { self.palette }
+
+    pub fn set_flags(&mut self, value: OutlineFlags) { self.flags = value }
+    pub fn set_palette(&mut self, value: u32) { self.palette = value }
+}
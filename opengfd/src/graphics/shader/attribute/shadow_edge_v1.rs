@@ -57,6 +57,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream, param)?;
         Ok(this)
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ExtensionObjectContext<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -85,4 +89,63 @@ where AObject: Allocator + Clone {
         self.alpha = 1.;
         Ok(())
     }
-}
\ No newline at end of file
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug
+    {
+        self.light_color.stream_write(stream, &mut ())?;
+        stream.write_f32(self.light_threshold)?;
+        stream.write_f32(self.light_factor)?;
+        self.shadow_color.stream_write(stream, &mut ())?;
+        stream.write_f32(self.shadow_threshold)?;
+        stream.write_f32(self.shadow_factor)?;
+        stream.write_f32(self.dark_map_speed)?;
+        stream.write_f32(self.dark_map_power)?;
+        stream.write_f32(self.dark_map_scale)?;
+        stream.write_f32(self.dark_map_height)?;
+        stream.write_f32(self.dark_map_alpha)?;
+        stream.write_f32(self.dark_map_direction)?;
+        stream.write_f32(self.dark_grad_height)?;
+        stream.write_f32(self.dark_grad_alpha)?;
+        stream.write_u32(self.flags.bits())?;
+        Ok(())
+    }
+}
+
+impl<A> ShadowEdge<A>
+where A: Allocator + Clone {
+    pub fn get_light_color(&self) -> RGBAFloat { self.light_color }
+    pub fn get_light_threshold(&self) -> f32 { self.light_threshold }
+    pub fn get_light_factor(&self) -> f32 { self.light_factor }
+    pub fn get_shadow_color(&self) -> RGBAFloat { self.shadow_color }
+    pub fn get_shadow_threshold(&self) -> f32 { self.shadow_threshold }
+    pub fn get_shadow_factor(&self) -> f32 { self.shadow_factor }
+    pub fn get_dark_map_speed(&self) -> f32 { self.dark_map_speed }
+    pub fn get_dark_map_power(&self) -> f32 { self.dark_map_power }
+    pub fn get_dark_map_scale(&self) -> f32 { self.dark_map_scale }
+    pub fn get_dark_map_height(&self) -> f32 { self.dark_map_height }
+    pub fn get_dark_map_alpha(&self) -> f32 { self.dark_map_alpha }
+    pub fn get_dark_map_direction(&self) -> f32 { self.dark_map_direction }
+    pub fn get_dark_grad_height(&self) -> f32 { self.dark_grad_height }
+    pub fn get_dark_grad_alpha(&self) -> f32 { self.dark_grad_alpha }
+    pub fn get_flags(&self) -> ShadowEdgeFlags { self.flags }
+    pub fn get_alpha(&self) -> f32 { self.alpha }
+
+    pub fn set_light_color(&mut self, value: RGBAFloat) { self.light_color = value }
+    pub fn set_light_threshold(&mut self, value: f32) { self.light_threshold = value }
+    pub fn set_light_factor(&mut self, value: f32) { self.light_factor = value }
+    pub fn set_shadow_color(&mut self, value: RGBAFloat) { self.shadow_color = value }
+    pub fn set_shadow_threshold(&mut self, value: f32) { self.shadow_threshold = value }
+    pub fn set_shadow_factor(&mut self, value: f32) { self.shadow_factor = value }
+    pub fn set_dark_map_speed(&mut self, value: f32) { self.dark_map_speed = value }
+    pub fn set_dark_map_power(&mut self, value: f32) { self.dark_map_power = value }
+    pub fn set_dark_map_scale(&mut self, value: f32) { self.dark_map_scale = value }
+    pub fn set_dark_map_height(&mut self, value: f32) { self.dark_map_height = value }
+    pub fn set_dark_map_alpha(&mut self, value: f32) { self.dark_map_alpha = value }
+    pub fn set_dark_map_direction(&mut self, value: f32) { self.dark_map_direction = value }
+    pub fn set_dark_grad_height(&mut self, value: f32) { self.dark_grad_height = value }
+    pub fn set_dark_grad_alpha(&mut self, value: f32) { self.dark_grad_alpha = value }
+    pub fn set_flags(&mut self, value: ShadowEdgeFlags) { self.flags = value }
+    pub fn set_alpha(&mut self, value: f32) { self.alpha = value }
+}
@@ -25,6 +25,7 @@ use crate::utility::stream::{DeserializationStack, GfdSerialize, Stream, StreamI
 
 bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    #[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct SkyFlags: u32 {
         const BlendClearColor = 0x00000001;
     }
@@ -35,6 +36,8 @@ bitflags! {
 /// Shader File: 37.HLSL
 #[repr(C)]
 #[derive(Debug)]
+#[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "io_serde", serde(bound = ""))]
 pub struct Sky<A = GfdAllocator> 
 where A: Allocator + Clone
 {
@@ -42,6 +45,7 @@ where A: Allocator + Clone
     p10_2: f32,
     p10_3: f32,
     flags: SkyFlags,
+    #[cfg_attr(feature = "io_serde", serde(skip))]
     _allocator: std::marker::PhantomData<A>
 }
 
@@ -125,6 +129,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -140,4 +148,30 @@ where AObject: Allocator + Clone {
         self.flags = stream.has_feature(GfdVersion::MaterialParameterSkyAddFlags).map_or::<Result<SkyFlags, Box<dyn Error>>, _>(Ok(SkyFlags::empty()), |_| Ok(SkyFlags::from_bits_retain(stream.read_u32()?)))?;
         Ok(())
     }
-}
\ No newline at end of file
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug
+    {
+        self.base_color.stream_write(stream, &mut ())?;
+        if stream.has_feature(GfdVersion::MaterialParameterSkyAddP2).is_some() {
+            stream.write_f32(self.p10_2)?;
+        }
+        stream.write_f32(self.p10_3)?;
+        if stream.has_feature(GfdVersion::MaterialParameterSkyAddFlags).is_some() {
+            stream.write_u32(self.flags.bits())?;
+        }
+        Ok(())
+    }
+}
+
+impl<A> Default for Sky<A>
+where A: Allocator + Clone
+{
+    fn default() -> Self {
+        let mut this: Self = unsafe { MaybeUninit::zeroed().assume_init() };
+        this.base_color = RGBAFloat::from_single_f32(1.);
+        this.p10_2 = 1.;
+        this
+    }
+}
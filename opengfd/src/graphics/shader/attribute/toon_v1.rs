@@ -47,6 +47,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream, param)?;
         Ok(this)
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ExtensionObjectContext<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -95,4 +99,58 @@ where AObject: Allocator + Clone {
         }
         Ok(())
     }
-}
\ No newline at end of file
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug
+    {
+        if stream.get_header_version() >= GfdVersion::MaterialExtensionToonV3 as u32 {
+            self.light_color.stream_write(stream, &mut ())?;
+            stream.write_f32(self.light_threshold)?;
+            stream.write_f32(self.light_factor)?;
+            stream.write_f32(self.shadow_brightness)?;
+            stream.write_f32(self.shadow_threshold)?;
+            stream.write_f32(self.shadow_factor)?;
+            stream.write_u32(self.flags.bits())?;
+        } else if stream.get_header_version() >= GfdVersion::MaterialExtensionToonV2 as u32 {
+            self.light_color.stream_write(stream, &mut ())?;
+            stream.write_f32(self.light_threshold)?;
+            stream.write_f32(self.light_factor)?;
+            stream.write_f32(self.shadow_brightness)?;
+            stream.write_f32(self.shadow_threshold)?;
+            stream.write_f32(self.shadow_factor)?;
+            stream.write_u8(self.flags.contains(ToonFlags::LightNormalMap) as u8)?;
+            stream.write_u8(self.flags.contains(ToonFlags::LightAdd) as u8)?;
+            stream.write_u8(self.flags.contains(ToonFlags::ShadowNormalMap) as u8)?;
+            if stream.get_header_version() >= GfdVersion::MaterialExtensionToonV2LockYAxis as u32 {
+                stream.write_u8(self.flags.contains(ToonFlags::LockYAxis) as u8)?;
+            }
+        } else {
+            self.light_color.stream_write(stream, &mut ())?;
+            stream.write_f32(self.light_threshold)?;
+            stream.write_f32(self.light_factor)?;
+            stream.write_f32(self.shadow_threshold)?;
+            stream.write_f32(self.shadow_factor)?;
+        }
+        Ok(())
+    }
+}
+
+impl<A> Toon<A>
+where A: Allocator + Clone {
+    pub fn get_light_color(&self) -> RGBAFloat { self.light_color }
+    pub fn get_light_threshold(&self) -> f32 { self.light_threshold }
+    pub fn get_light_factor(&self) -> f32 { self.light_factor }
+    pub fn get_shadow_brightness(&self) -> f32 { self.shadow_brightness }
+    pub fn get_shadow_threshold(&self) -> f32 { self.shadow_threshold }
+    pub fn get_shadow_factor(&self) -> f32 { self.shadow_factor }
+    pub fn get_flags(&self) -> ToonFlags { self.flags }
+
+    pub fn set_light_color(&mut self, value: RGBAFloat) { self.light_color = value }
+    pub fn set_light_threshold(&mut self, value: f32) { self.light_threshold = value }
+    pub fn set_light_factor(&mut self, value: f32) { self.light_factor = value }
+    pub fn set_shadow_brightness(&mut self, value: f32) { self.shadow_brightness = value }
+    pub fn set_shadow_threshold(&mut self, value: f32) { self.shadow_threshold = value }
+    pub fn set_shadow_factor(&mut self, value: f32) { self.shadow_factor = value }
+    pub fn set_flags(&mut self, value: ToonFlags) { self.flags = value }
+}
@@ -34,6 +34,7 @@ use crate::utility::stream::{DeserializationStrategy, DeserializationStack, GfdS
 
 bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord )]
+    #[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct ToonBaseFlags : u32 {
         const Flag0 = 0x00000001;
         const Flag1 = 0x00000002;
@@ -65,6 +66,7 @@ bitflags! {
 
 #[repr(C)]
 #[derive(Debug)]
+#[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Toon {
     pub(super) base_color: RGBAFloat,
     pub(super) shadow_color: RGBAFloat,
@@ -95,6 +97,7 @@ pub struct Toon {
 
 bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord )]
+    #[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct CharaToonFlags : u32 {
         const ToonRefNormalMap = 0x00000001;
         const ToonRemoveLightYAxis = 0x00000002;
@@ -128,10 +131,14 @@ bitflags! {
 /// Source File: 11.HLSL
 #[repr(C)]
 #[derive(Debug)]
+#[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "io_serde", serde(bound = ""))]
 pub struct CharacterToon<A = GfdAllocator>
 where A: Allocator + Clone
 {
+    #[cfg_attr(feature = "io_serde", serde(flatten))]
     _impl: Toon,
+    #[cfg_attr(feature = "io_serde", serde(skip))]
     _alloc: std::marker::PhantomData<A>
 }
 
@@ -313,6 +320,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -348,6 +359,61 @@ impl Toon {
         self.multi_fitting_tile = stream.has_feature(GfdVersion::MaterialParameterToonAddMultiFittingTile).map_or::<Result<f32, Box<dyn Error>>, _>(Ok(0.), |_| Ok(stream.read_f32()?))?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug
+    {
+        self.base_color.stream_write(stream, &mut ())?;
+        self.shadow_color.stream_write(stream, &mut ())?;
+        self.edge_color.stream_write(stream, &mut ())?;
+        self.emissive_color.stream_write(stream, &mut ())?;
+        self.specular_color.stream_write(stream, &mut ())?;
+        stream.write_f32(self.specular_power)?;
+        stream.write_f32(self.metallic)?;
+        stream.write_f32(self.edge_threshold)?;
+        stream.write_f32(self.edge_factor)?;
+        stream.write_f32(self.shadow_threshold)?;
+        stream.write_f32(self.shadow_factor)?;
+        stream.write_u32(self.flags.bits())?;
+        if stream.has_feature(GfdVersion::MaterialParameterToonSetP12).is_some() {
+            stream.write_f32(self.field74)?;
+        }
+        if stream.has_feature(GfdVersion::MaterialParameterToonSetP12).is_some() {
+            self.field7c.stream_write(stream, &mut ())?;
+        }
+        if stream.has_feature(GfdVersion::MaterialParameterAddBloomIntensity).is_some() {
+            stream.write_f32(self.bloom_strength)?;
+        }
+        if stream.has_feature(GfdVersion::MaterialParameterToonAddSpecularThreshold).is_some() {
+            stream.write_f32(self.specular_threshold)?;
+        }
+        if stream.has_feature(GfdVersion::MaterialParameterToonAddEdgeRemoveYAxisFactor).is_some() {
+            stream.write_f32(self.edge_remove_y_axis_factor)?;
+        }
+        if stream.has_feature(GfdVersion::MaterialParameterToonAddP17).is_some() {
+            stream.write_f32(self.field88)?;
+        }
+        if stream.has_feature(GfdVersion::MaterialParameterToonAddP17).is_some() {
+            stream.write_f32(self.field8c)?;
+        }
+        if stream.has_feature(GfdVersion::MaterialParameterToonAddP17).is_some() {
+            stream.write_f32(self.field90)?;
+        }
+        if stream.has_feature(GfdVersion::MaterialParameterToonAddP20).is_some() {
+            stream.write_f32(self.field78)?;
+        }
+        if stream.has_feature(GfdVersion::MaterialParameterToonAddMatRoughness).is_some() {
+            stream.write_f32(self.roughness)?;
+        }
+        if stream.has_feature(GfdVersion::MaterialParameterToonAddFittingTile).is_some() {
+            stream.write_f32(self.fitting_tile)?;
+        }
+        if stream.has_feature(GfdVersion::MaterialParameterToonAddMultiFittingTile).is_some() {
+            stream.write_f32(self.multi_fitting_tile)?;
+        }
+        Ok(())
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -360,4 +426,30 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         let _impl = Toon::stream_read(stream, &mut ())?.into_raw();
         Ok(CharacterToon { _impl, _alloc: PhantomData::<AObject> }.into())
     }
-}
\ No newline at end of file
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self._impl.stream_write(stream, &mut ())
+    }
+}
+
+impl Default for Toon {
+    fn default() -> Self {
+        let mut this: Self = unsafe { MaybeUninit::zeroed().assume_init() };
+        this.base_color = RGBAFloat::from_single_f32(1.);
+        this.bloom_strength = 0.5;
+        this.specular_threshold = 1.;
+        this.edge_remove_y_axis_factor = 3.;
+        this.field78 = 0.1;
+        this.field88 = 1.;
+        this.field94 = 1.;
+        this
+    }
+}
+
+impl<A> Default for CharacterToon<A>
+where A: Allocator + Clone
+{
+    fn default() -> Self {
+        Self { _impl: Toon::default(), _alloc: std::marker::PhantomData }
+    }
+}
@@ -36,6 +36,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream, param)?;
         Ok(this)
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ExtensionObjectContext<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -61,4 +65,23 @@ where AObject: Allocator + Clone
         self.field40 = stream.read_u32()?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug
+    {
+        stream.write_f32(self.field18)?;
+        stream.write_f32(self.field1c)?;
+        stream.write_f32(self.field20)?;
+        stream.write_f32(self.field24)?;
+        stream.write_u8(self.field28)?;
+        stream.write_f32(self.field2c)?;
+        stream.write_f32(self.field30)?;
+        stream.write_f32(self.field34)?;
+        stream.write_f32(self.field38)?;
+        stream.write_u8(self.field3c)?;
+        stream.write_u32(self.field40)?;
+        Ok(())
+    }
 }
\ No newline at end of file
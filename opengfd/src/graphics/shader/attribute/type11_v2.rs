@@ -24,11 +24,14 @@ use crate::utility::stream::{DeserializationStack, GfdSerialize, Stream, StreamI
 /// Shader File: 39.HLSL or 41.HLSL
 #[repr(C)]
 #[derive(Debug)]
+#[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "io_serde", serde(bound = ""))]
 pub struct Type11<A = GfdAllocator> 
 where A: Allocator + Clone
 {
     base_color: RGBAFloat,
     field10: f32,
+    #[cfg_attr(feature = "io_serde", serde(skip))]
     _allocator: std::marker::PhantomData<A>
 }
 
@@ -107,6 +110,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -120,4 +127,26 @@ where AObject: Allocator + Clone {
         self.field10 = stream.has_feature(GfdVersion::MaterialParameterType11AddField).map_or::<Result<f32, Box<dyn Error>>, _>(Ok(0.5), |_| Ok(stream.read_f32()?))?;
         Ok(())
     }
-}
\ No newline at end of file
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug
+    {
+        self.base_color.stream_write(stream, &mut ())?;
+        if stream.has_feature(GfdVersion::MaterialParameterType11AddField).is_some() {
+            stream.write_f32(self.field10)?;
+        }
+        Ok(())
+    }
+}
+
+impl<A> Default for Type11<A>
+where A: Allocator + Clone
+{
+    fn default() -> Self {
+        let mut this: Self = unsafe { MaybeUninit::zeroed().assume_init() };
+        this.base_color = RGBAFloat::from_single_f32(1.);
+        this.field10 = 0.5;
+        this
+    }
+}
@@ -24,6 +24,7 @@ use crate::graphics::shader::attribute::toon_v2::ToonBaseFlags;
 
 bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord )]
+    #[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct Type13Flags : u32 {
         const ToonRefNormalMap = 0x00000001;
         const ToonRemoveLightYAxis = 0x00000002;
@@ -53,10 +54,14 @@ bitflags! {
     }
 }
 
+#[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "io_serde", serde(bound = ""))]
 pub struct Type13<A = GfdAllocator>
 where A: Allocator + Clone
 {
+    #[cfg_attr(feature = "io_serde", serde(flatten))]
     _impl: Toon,
+    #[cfg_attr(feature = "io_serde", serde(skip))]
     _alloc: std::marker::PhantomData<A>
 }
 
@@ -139,4 +144,16 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         let _impl = Toon::stream_read(stream, &mut ())?.into_raw();
         Ok(Type13 { _impl, _alloc: PhantomData::<AObject> }.into())
     }
-}
\ No newline at end of file
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self._impl.stream_write(stream, &mut ())
+    }
+}
+
+impl<A> Default for Type13<A>
+where A: Allocator + Clone
+{
+    fn default() -> Self {
+        Self { _impl: Toon::default(), _alloc: std::marker::PhantomData }
+    }
+}
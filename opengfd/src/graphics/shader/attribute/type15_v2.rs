@@ -25,6 +25,7 @@ use crate::utility::stream::{DeserializationStack, GfdSerialize, Stream, StreamI
 
 #[repr(C)]
 #[derive(Debug)]
+#[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Layer {
     tile_size: f32,
     field1: f32,
@@ -45,6 +46,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -62,10 +67,25 @@ impl Layer {
         self.color = Vec3::stream_read(stream, &mut ())?.into_raw();
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug
+    {
+        stream.write_f32(self.tile_size)?;
+        stream.write_f32(self.field1)?;
+        stream.write_f32(self.tile_offset)?;
+        stream.write_f32(self.field3)?;
+        stream.write_f32(self.roughness)?;
+        stream.write_f32(self.metallic)?;
+        self.color.stream_write(stream, &mut ())?;
+        Ok(())
+    }
 }
 
 bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    #[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct Type15Flags : u32 {
         const TriplanarMapping = 0x00000001;
         const GBufferSkyFlag = 0x00000002;
@@ -74,6 +94,8 @@ bitflags! {
 
 #[repr(C)]
 #[derive(Debug)]
+#[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "io_serde", serde(bound = ""))]
 pub struct Type15<A = GfdAllocator> 
 where A: Allocator + Clone 
 {
@@ -81,6 +103,7 @@ where A: Allocator + Clone
     layer_count: u32,
     triplanar_scale: f32,
     flags: Type15Flags,
+    #[cfg_attr(feature = "io_serde", serde(skip))]
     _allocator: std::marker::PhantomData<A>
 }
 
@@ -166,6 +189,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -183,4 +210,25 @@ where AObject: Allocator + Clone {
         self.flags = Type15Flags::from_bits_truncate(stream.read_u32()?);
         Ok(())
     }
-}
\ No newline at end of file
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug
+    {
+        for i in 0..16 {
+            self.layers[i].stream_write(stream, &mut ())?;
+        }
+        stream.write_u32(self.layer_count)?;
+        stream.write_f32(self.triplanar_scale)?;
+        stream.write_u32(self.flags.bits())?;
+        Ok(())
+    }
+}
+
+impl<A> Default for Type15<A>
+where A: Allocator + Clone
+{
+    fn default() -> Self {
+        unsafe { MaybeUninit::zeroed().assume_init() }
+    }
+}
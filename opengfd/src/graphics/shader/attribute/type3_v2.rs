@@ -21,15 +21,20 @@ use crate::{
 use crate::graphics::material::params::MaterialId;
 use crate::utility::stream::{DeserializationStack, GfdSerialize, Stream, StreamIODevice};
 
+#[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "io_serde", serde(bound = ""))]
 pub struct Type3<A = GfdAllocator>
 where A: Allocator + Clone
 {
+    #[cfg_attr(feature = "io_serde", serde(flatten))]
     _impl: Toon,
+    #[cfg_attr(feature = "io_serde", serde(skip))]
     _alloc: std::marker::PhantomData<A>
 }
 
 bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord )]
+    #[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct Type3Flags : u32 {
         const ToonRefNormalMap = 0x00000001;
         const ToonRemoveLightYAxis = 0x00000002;
@@ -148,4 +153,16 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         let _impl = Toon::stream_read(stream, &mut ())?.into_raw();
         Ok(Type3 { _impl, _alloc: PhantomData::<AObject> }.into())
     }
-}
\ No newline at end of file
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self._impl.stream_write(stream, &mut ())
+    }
+}
+
+impl<A> Default for Type3<A>
+where A: Allocator + Clone
+{
+    fn default() -> Self {
+        Self { _impl: Toon::default(), _alloc: std::marker::PhantomData }
+    }
+}
@@ -38,6 +38,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream, param)?;
         Ok(this)
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ExtensionObjectContext<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -63,4 +67,24 @@ where AObject: Allocator + Clone {
         self.field54 = RGBAFloat::stream_read(stream, &mut ())?.into_raw();
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug
+    {
+        stream.write_u32(self.field18)?;
+        stream.write_u32(self.field1c)?;
+        stream.write_f32(self.field20)?;
+        stream.write_f32(self.field24)?;
+        self.field28.stream_write(stream, &mut ())?;
+        stream.write_f32(self.field38)?;
+        stream.write_f32(self.field3c)?;
+        stream.write_f32(self.field40)?;
+        stream.write_f32(self.field44)?;
+        stream.write_f32(self.field48)?;
+        stream.write_f32(self.field4c)?;
+        stream.write_f32(self.field50)?;
+        self.field54.stream_write(stream, &mut ())?;
+        Ok(())
+    }
 }
\ No newline at end of file
@@ -27,6 +27,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream, param)?;
         Ok(this)
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ExtensionObjectContext<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -44,4 +48,15 @@ where AObject: Allocator + Clone
         self.field20 = stream.read_u32()?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug
+    {
+        stream.write_u32(self.field18)?;
+        stream.write_u32(self.field1c)?;
+        stream.write_u32(self.field20)?;
+        Ok(())
+    }
 }
\ No newline at end of file
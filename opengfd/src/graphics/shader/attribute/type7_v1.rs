@@ -25,6 +25,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream, param)?;
         Ok(this)
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ExtensionObjectContext<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -39,4 +43,12 @@ where AObject: Allocator + Clone
         self._super = ExtensionObject::<AObject>::new(param.get_id(), param.get_heap_allocator().unwrap());
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, _: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug
+    {
+        Ok(())
+    }
 }
\ No newline at end of file
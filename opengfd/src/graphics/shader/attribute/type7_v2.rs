@@ -25,17 +25,21 @@ use crate::utility::stream::{DeserializationStack, GfdSerialize, Stream, StreamI
 /// Shader File: 29.HLSL
 #[repr(C)]
 #[derive(Debug)]
+#[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "io_serde", serde(bound = ""))]
 pub struct FourLayer<A = GfdAllocator> 
 where A: Allocator + Clone
 {
     layers: [Layer; 4],
     p7_1: f32,
     flags: Type7Flags,
+    #[cfg_attr(feature = "io_serde", serde(skip))]
     _allocator: std::marker::PhantomData<A>
 }
 
 #[repr(C)]
 #[derive(Debug)]
+#[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Layer {
     field00: f32,
     field04: f32,
@@ -56,6 +60,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -72,10 +80,24 @@ impl Layer {
         self.field14 = stream.read_f32()?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug
+    {
+        stream.write_f32(self.field00)?;
+        stream.write_f32(self.field04)?;
+        stream.write_f32(self.field08)?;
+        stream.write_f32(self.field0c)?;
+        stream.write_f32(self.field10)?;
+        stream.write_f32(self.field14)?;
+        Ok(())
+    }
 }
 
 bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    #[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct Type7Flags : u32 {
         const TriplanarMapping = 1 << 0;
         const Sky = 1 << 1;
@@ -166,6 +188,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -182,4 +208,24 @@ where AObject: Allocator + Clone {
         self.flags = Type7Flags::from_bits_truncate(stream.read_u32()?);
         Ok(())
     }
-}
\ No newline at end of file
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug
+    {
+        for i in 0..4 {
+            self.layers[i].stream_write(stream, &mut ())?;
+        }
+        stream.write_f32(self.p7_1)?;
+        stream.write_u32(self.flags.bits())?;
+        Ok(())
+    }
+}
+
+impl<A> Default for FourLayer<A>
+where A: Allocator + Clone
+{
+    fn default() -> Self {
+        unsafe { MaybeUninit::zeroed().assume_init() }
+    }
+}
@@ -23,6 +23,8 @@ use crate::utility::stream::{DeserializationStack, GfdSerialize, Stream, StreamI
 /// Shader File: 39.HLSL or 41.HLSL
 #[repr(C)]
 #[derive(Debug)]
+#[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "io_serde", serde(bound = ""))]
 pub struct Type8<A = GfdAllocator> 
 where A: Allocator + Clone
 {
@@ -35,6 +37,7 @@ where A: Allocator + Clone
     field24: f32,
     field28: f32,
     field2c: f32,
+    #[cfg_attr(feature = "io_serde", serde(skip))]
     _allocator: std::marker::PhantomData<A>
 }
 
@@ -112,6 +115,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -132,4 +139,30 @@ where AObject: Allocator + Clone {
         self.field2c = stream.read_f32()?;
         Ok(())
     }
-}
\ No newline at end of file
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug
+    {
+        stream.write_f32(self.field0)?;
+        stream.write_f32(self.field4)?;
+        stream.write_f32(self.field8)?;
+        stream.write_f32(self.fieldc)?;
+        self.base_color.stream_write(stream, &mut ())?;
+        stream.write_f32(self.field20)?;
+        stream.write_f32(self.field24)?;
+        stream.write_f32(self.field28)?;
+        stream.write_f32(self.field2c)?;
+        Ok(())
+    }
+}
+
+impl<A> Default for Type8<A>
+where A: Allocator + Clone
+{
+    fn default() -> Self {
+        let mut this: Self = unsafe { MaybeUninit::zeroed().assume_init() };
+        this.base_color = RGBAFloat::from_single_f32(1.);
+        this
+    }
+}
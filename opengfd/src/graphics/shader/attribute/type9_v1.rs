@@ -43,6 +43,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream, param)?;
         Ok(this)
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ExtensionObjectContext<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -74,4 +78,29 @@ where AObject: Allocator + Clone
         self.field64 = stream.read_u32()?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug
+    {
+        self.field18.stream_write(stream, &mut ())?;
+        stream.write_f32(self.field28)?;
+        stream.write_f32(self.field2c)?;
+        stream.write_f32(self.field30)?;
+        stream.write_f32(self.field34)?;
+        stream.write_f32(self.field38)?;
+        stream.write_f32(self.field3c)?;
+        stream.write_f32(self.field40)?;
+        stream.write_f32(self.field44)?;
+        stream.write_f32(self.field48)?;
+        stream.write_u8(self.field4c)?;
+        stream.write_f32(self.field50)?;
+        stream.write_f32(self.field54)?;
+        stream.write_f32(self.field58)?;
+        stream.write_f32(self.field5c)?;
+        stream.write_u8(self.field60)?;
+        stream.write_u32(self.field64)?;
+        Ok(())
+    }
 }
\ No newline at end of file
@@ -29,6 +29,7 @@ use crate::graphics::material::params::MaterialId;
 
 bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    #[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct Type9Flags: u32 {
         const ToonRefNormalMap = 1 << 0;
         const ToonRemoveLightYAxis = 1 << 1;
@@ -52,6 +53,8 @@ bitflags! {
 /// Shader File: 39.HLSL or 41.HLSL
 #[repr(C)]
 #[derive(Debug)]
+#[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "io_serde", serde(bound = ""))]
 pub struct Type9<A = GfdAllocator> 
 where A: Allocator + Clone
 {
@@ -73,6 +76,7 @@ where A: Allocator + Clone
     field74: f32,
     field78: f32,
     flags: Type9Flags,
+    #[cfg_attr(feature = "io_serde", serde(skip))]
     _allocator: std::marker::PhantomData<A>
 }
 
@@ -173,6 +177,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -202,4 +210,40 @@ where AObject: Allocator + Clone {
         self.flags = Type9Flags::from_bits_retain(stream.read_u32()?);
         Ok(())
     }
-}
\ No newline at end of file
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug
+    {
+        stream.write_f32(self.field0)?;
+        stream.write_f32(self.field4)?;
+        stream.write_f32(self.field8)?;
+        stream.write_f32(self.fieldc)?;
+        self.base_color.stream_write(stream, &mut ())?;
+        self.shadow_color.stream_write(stream, &mut ())?;
+        self.edge_color.stream_write(stream, &mut ())?;
+        self.emissive_color.stream_write(stream, &mut ())?;
+        self.specular_color.stream_write(stream, &mut ())?;
+        stream.write_f32(self.specular_threshold)?;
+        stream.write_f32(self.specular_power)?;
+        stream.write_f32(self.metallic)?;
+        stream.write_f32(self.roughness)?;
+        stream.write_f32(self.edge_threshold)?;
+        stream.write_f32(self.field70)?;
+        stream.write_f32(self.field74)?;
+        stream.write_f32(self.field78)?;
+        stream.write_u32(self.flags.bits())?;
+        Ok(())
+    }
+}
+
+impl<A> Default for Type9<A>
+where A: Allocator + Clone
+{
+    fn default() -> Self {
+        let mut this: Self = unsafe { MaybeUninit::zeroed().assume_init() };
+        this.base_color = RGBAFloat::from_single_f32(1.);
+        this.specular_threshold = 1.;
+        this
+    }
+}
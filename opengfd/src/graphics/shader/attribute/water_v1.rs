@@ -44,6 +44,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream, param)?;
         Ok(this)
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ExtensionObjectContext<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -63,4 +67,37 @@ where AObject: Allocator + Clone {
         self.flags = WaterFlags::from_bits_truncate(stream.read_u32()?);
         Ok(())
     }
-}
\ No newline at end of file
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug
+    {
+        self.wave_length.stream_write(stream, &mut ())?;
+        self.amplitude.stream_write(stream, &mut ())?;
+        self.speed.stream_write(stream, &mut ())?;
+        stream.write_f32(self.reflect_alpha)?;
+        stream.write_f32(self.refract_alpha)?;
+        stream.write_f32(self.refract_ratio)?;
+        stream.write_u32(self.flags.bits())?;
+        Ok(())
+    }
+}
+
+impl<A> Water<A>
+where A: Allocator + Clone {
+    pub fn get_wave_length(&self) -> Vec3 { self.wave_length }
+    pub fn get_amplitude(&self) -> Vec3 { self.amplitude }
+    pub fn get_speed(&self) -> Vec3 { self.speed }
+    pub fn get_reflect_alpha(&self) -> f32 { self.reflect_alpha }
+    pub fn get_refract_alpha(&self) -> f32 { self.refract_alpha }
+    pub fn get_refract_ratio(&self) -> f32 { self.refract_ratio }
+    pub fn get_flags(&self) -> WaterFlags { self.flags }
+
+    pub fn set_wave_length(&mut self, value: Vec3) { self.wave_length = value }
+    pub fn set_amplitude(&mut self, value: Vec3) { self.amplitude = value }
+    pub fn set_speed(&mut self, value: Vec3) { self.speed = value }
+    pub fn set_reflect_alpha(&mut self, value: f32) { self.reflect_alpha = value }
+    pub fn set_refract_alpha(&mut self, value: f32) { self.refract_alpha = value }
+    pub fn set_refract_ratio(&mut self, value: f32) { self.refract_ratio = value }
+    pub fn set_flags(&mut self, value: WaterFlags) { self.flags = value }
+}
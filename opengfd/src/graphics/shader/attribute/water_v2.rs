@@ -25,6 +25,7 @@ use crate::utility::stream::{DeserializationStack, GfdSerialize, Stream, StreamI
 
 bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    #[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct WaterFlags: u32 {
         const InfluencedBySky = 0x00000001;
         const HasWaterReflection = 0x00000002;
@@ -39,6 +40,8 @@ bitflags! {
 
 #[repr(C)]
 #[derive(Debug)]
+#[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "io_serde", serde(bound = ""))]
 pub struct Water<A = GfdAllocator> 
 where A: Allocator + Clone
 {
@@ -57,7 +60,8 @@ where A: Allocator + Clone
     texture_animation_speed: f32,
     p5_13: f32,
     flags: WaterFlags,
-    _allocator: A
+    #[cfg_attr(feature = "io_serde", serde(skip))]
+    _allocator: std::marker::PhantomData<A>
 }
 
 impl<A> Water<A> 
@@ -146,6 +150,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -172,4 +180,44 @@ where AObject: Allocator + Clone {
         self.flags = stream.has_feature(GfdVersion::MaterialParameterWaterAddFlags).map_or::<Result<WaterFlags, Box<dyn Error>>, _>(Ok(WaterFlags::empty()), |_| Ok(WaterFlags::from_bits_truncate(stream.read_u32()?)))?;
         Ok(())
     }
-}
\ No newline at end of file
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug
+    {
+        stream.write_f32(self.p5_0)?;
+        stream.write_f32(self.p5_1)?;
+        stream.write_f32(self.tc_scale)?;
+        stream.write_f32(self.p5_3)?;
+        stream.write_f32(self.ocean_depth_scale)?;
+        stream.write_f32(self.disturbance_camera_scale)?;
+        stream.write_f32(self.disturbance_depth_scale)?;
+        stream.write_f32(self.scattering_camera_scale)?;
+        stream.write_f32(self.disturbance_tolerance)?;
+        stream.write_f32(self.foam_distance)?;
+        stream.write_f32(self.caustics_tolerance)?;
+        if stream.has_feature(GfdVersion::MaterialParameterWaterAddTextureSpeed).is_some() {
+            stream.write_f32(self.p5_11)?;
+        }
+        if stream.has_feature(GfdVersion::MaterialParameterWaterAddTextureSpeed).is_some() {
+            stream.write_f32(self.texture_animation_speed)?;
+        }
+        if stream.has_feature(GfdVersion::EnvAddInfiniteOcean_LUTRecolorParams).is_some() {
+            stream.write_f32(self.p5_13)?;
+        }
+        if stream.has_feature(GfdVersion::MaterialParameterWaterAddFlags).is_some() {
+            stream.write_u32(self.flags.bits())?;
+        }
+        Ok(())
+    }
+}
+
+impl<A> Default for Water<A>
+where A: Allocator + Clone
+{
+    fn default() -> Self {
+        let mut this: Self = unsafe { MaybeUninit::zeroed().assume_init() };
+        this.p5_11 = 1.;
+        this
+    }
+}
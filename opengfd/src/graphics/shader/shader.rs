@@ -101,6 +101,9 @@ pub struct ShaderID {
 }
 
 impl ShaderID {
+    pub fn new(id: i16, texcoord0: TexcoordID, texcoord1: TexcoordID) -> Self {
+        Self { id, flags: ShaderFlags::default(), texcoord0, texcoord1, vertex_attrib: 0 }
+    }
     pub fn get_texcoord_in(&self) -> TexcoordID {
         self.texcoord0
     }
@@ -128,6 +131,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(all(feature = "serialize", feature = "v2-core"))]
@@ -142,6 +149,17 @@ impl ShaderID {
         self.texcoord1 = stream.read_u32()?.try_into()?;
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug
+    {
+        stream.write_u16(self.id as u16)?;
+        stream.write_u32(*self.texcoord0)?;
+        stream.write_u32(*self.texcoord1)?;
+        Ok(())
+    }
 }
 
 #[cfg(feature = "v2-core")]
@@ -1,4 +1,16 @@
-use crate::utility::item_array::ItemArray;
+use allocator_api2::alloc::Allocator;
+use crate::{
+    object::{
+        geometry::VertexWeight,
+        mesh::MeshFile
+    },
+    utility::item_array::ItemArray
+};
+use glam::{ Mat3, Mat4, Vec3 };
+use std::{
+    error::Error,
+    fmt::Display
+};
 
 #[repr(C)]
 #[derive(Debug)]
@@ -8,3 +20,128 @@ pub struct SkinBoneObject {
     pub field2_0x10: u8,
     pub ref_: i32,
 }
+
+#[derive(Debug)]
+pub enum SkinError {
+    /// The bone refers to a node that isn't in the mesh's node list
+    InvalidNodeIndex { bone: usize, node: usize },
+    /// The bone doesn't have an inverse bind matrix
+    MissingInverseBindMatrix { bone: usize },
+    /// The vertex is weighted to a bone that isn't in the skin palette
+    InvalidBoneIndex { vertex: usize, bone: usize },
+    /// The geometry has no vertex data to skin
+    MissingVertexData
+}
+impl Error for SkinError {}
+impl Display for SkinError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SkinError: {:?}", self)
+    }
+}
+
+/// The matrix for each bone in a skin, which moves a vertex from the bind pose into the pose set
+/// by the node transforms. This is the bone's node world transform multiplied by the bone's
+/// inverse bind matrix.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkinPalette(Vec<Mat4>);
+
+impl SkinPalette {
+    /// Build the palette from a world transform for each node in the mesh's node list, and the
+    /// inverse bind matrix and node index for each bone
+    pub fn new(world_matrices: &[Mat4], inverse_bind_matrices: &[Mat4], bone_to_node: &[u16]) -> Result<Self, SkinError> {
+        bone_to_node.iter().enumerate().map(|(bone, node)| {
+            let world = world_matrices.get(*node as usize)
+                .ok_or(SkinError::InvalidNodeIndex { bone, node: *node as usize })?;
+            let inverse_bind = inverse_bind_matrices.get(bone)
+                .ok_or(SkinError::MissingInverseBindMatrix { bone })?;
+            Ok(*world * *inverse_bind)
+        }).collect::<Result<Vec<_>, _>>().map(Self)
+    }
+
+    /// Build the palette from the current world transforms of the mesh's nodes. Use
+    /// Mesh::evaluate_world_transforms first if any node transforms were changed.
+    pub fn from_mesh<A>(mesh: &MeshFile<A>) -> Result<Self, SkinError>
+    where A: Allocator + Clone {
+        let world_matrices: Vec<Mat4> = mesh.get_node_list().iter()
+            .map(|n| unsafe { n.as_ref() }.get_world_transform())
+            .collect();
+        Self::new(&world_matrices, mesh.get_inverse_bind_matrices(), mesh.get_bone_to_node_indices())
+    }
+
+    pub fn get_matrices(&self) -> &[Mat4] { self.0.as_slice() }
+    pub fn len(&self) -> usize { self.0.len() }
+    pub fn is_empty(&self) -> bool { self.0.is_empty() }
+
+    /// Blend the matrices of the bones that influence a vertex. Weights are normalized, and
+    /// vertices without any weights are left in their bind pose. Returns the index of the first
+    /// bone that isn't in the palette if there is one.
+    pub fn get_blend_matrix(&self, weight: &VertexWeight) -> Result<Mat4, usize> {
+        let total: f32 = weight.weights.iter().filter(|w| **w > 0.).sum();
+        if total <= 0. {
+            return Ok(Mat4::IDENTITY);
+        }
+        let mut out = Mat4::ZERO;
+        for (w, bone) in weight.weights.iter().zip(weight.indices.iter()).filter(|(w, _)| **w > 0.) {
+            let matrix = self.0.get(*bone as usize).ok_or(*bone as usize)?;
+            out += *matrix * (*w / total);
+        }
+        Ok(out)
+    }
+}
+
+/// Vertex positions and normals after moving them into a pose
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SkinnedVertices {
+    pub positions: Vec<Vec3>,
+    pub normals: Vec<Vec3>
+}
+
+impl SkinnedVertices {
+    /// Move positions and normals by a matrix. Normals use the inverse transpose so that they
+    /// stay perpendicular to the surface when the matrix has a non-uniform scale.
+    pub(crate) fn push(&mut self, matrix: &Mat4, position: Vec3, normal: Option<Vec3>) {
+        self.positions.push(matrix.transform_point3(position));
+        if let Some(normal) = normal {
+            let normal_matrix = Mat3::from_mat4(*matrix);
+            let normal_matrix = match normal_matrix.determinant() != 0. {
+                true => normal_matrix.inverse().transpose(),
+                false => normal_matrix
+            };
+            self.normals.push((normal_matrix * normal).normalize_or_zero());
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use allocator_api2::alloc::Global;
+    use crate::object::geometry::{ Geometry, GeometryVertexData, TriangleIndexFormat, VertexWeight };
+    use glam::{ Mat4, Quat, Vec3, Vec4 };
+    use std::error::Error;
+    use super::{ SkinError, SkinPalette };
+
+    type TestReturn = Result<(), Box<dyn Error>>;
+
+    #[test]
+    fn skin_geometry() -> TestReturn {
+        // The second bone's node is one unit above the first, rotated 90 degrees around Z
+        let world = [
+            Mat4::IDENTITY,
+            Mat4::from_rotation_translation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_2), Vec3::Y)
+        ];
+        let palette = SkinPalette::new(&world, &[Mat4::IDENTITY, Mat4::IDENTITY], &[0, 1])?;
+        assert!(matches!(SkinPalette::new(&world, &[Mat4::IDENTITY], &[2]), Err(SkinError::InvalidNodeIndex { bone: 0, node: 2 })));
+        assert!(matches!(SkinPalette::new(&world, &[Mat4::IDENTITY], &[0, 1]), Err(SkinError::MissingInverseBindMatrix { bone: 1 })));
+        let mut vertices = GeometryVertexData::new(Global);
+        vertices.get_positions_mut().extend([Vec4::new(1., 0., 0., 1.), Vec4::new(1., 0., 0., 1.)]);
+        vertices.get_normals_mut().extend([Vec3::X, Vec3::X]);
+        vertices.get_weights_mut().push(VertexWeight { weights: [1., 0., 0., 0., 0., 0., 0., 0.], indices: [0; 8] });
+        vertices.get_weights_mut().push(VertexWeight { weights: [0., 2., 0., 0., 0., 0., 0., 0.], indices: [0, 1, 0, 0, 0, 0, 0, 0] });
+        let geometry = Geometry::new_in(vertices, None, TriangleIndexFormat::ThreeIndicesPerTriangle, Global)?;
+        let skinned = unsafe { geometry.as_ref() }.get_skinned_vertices(&palette)?;
+        assert!(skinned.positions[0].abs_diff_eq(Vec3::X, 1e-5));
+        assert!(skinned.positions[1].abs_diff_eq(Vec3::new(0., 2., 0.), 1e-5));
+        assert!(skinned.normals[1].abs_diff_eq(Vec3::Y, 1e-5));
+        Ok(())
+    }
+}
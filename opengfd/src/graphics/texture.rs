@@ -1,14 +1,28 @@
 use allocator_api2::alloc::Allocator;
+use allocator_api2::boxed::Box as ABox;
 use bitflags::bitflags;
 use crate::{
     device::ngr::{
         allocator::AllocatorHook,
-        renderer::platform::d3d::TextureResource
+        renderer::{
+            platform::d3d::TextureResource,
+            state::{ FilterMode, TextureAddressMode }
+        }
+    },
+    graphics::texture_codec::{
+        dds::{ decode_dds, encode_dds, DdsPixelFormat },
+        gnf::decode_gnf,
+        gtf::decode_gtf,
+        gxt::decode_gxt,
+        pvr::decode_pvr,
+        surface::DecodedTexture,
+        tmx::decode_tmx
     },
     kernel::{
         allocator::GfdAllocator,
         file::FileFlags,
         graphics::GraphicsGlobal,
+        version::GameProfile
     },
     utility::{
         name::Name,
@@ -21,8 +35,10 @@ use std::fmt::Formatter;
 use std::io::{Read, Seek, Write};
 #[cfg(feature = "image_loader")]
 use image::ImageReader;
-use crate::utility::name::{NameSerializationContext, NameSerializationNoHash};
-use crate::utility::stream::{DeserializationHeap, DeserializationStrategy, GfdSerializationUserData, GfdSerialize, Stream, StreamIODevice};
+use allocator_api2::vec::Vec as AVec;
+use crate::utility::item_array::ItemArray;
+use crate::utility::name::{NameSerializationContext, NameSerializationNoHash, NameSerializationTechnique};
+use crate::utility::stream::{DeserializationHeap, GfdSerializationUserData, GfdSerialize, SerializationSingleAllocator, Stream, StreamIODevice};
 
 bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -62,8 +78,8 @@ bitflags! {
     }
 }
 
-#[cfg(feature = "v2-core")]
-static DUMMY_TEXTURE_DATA: [u8; 160] = [
+// 8x8 DXT1 texture used by GFD v2 games
+static DUMMY_TEXTURE_DATA_V2: [u8; 160] = [
     0x44, 0x44, 0x53, 0x20, 0x7C, 0x00, 0x00, 0x00, 0x07, 0x10, 0x0A, 0x00, 0x08, 0x00, 0x00, 0x00,
     0x08, 0x00, 0x00, 0x00, 0x20, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
@@ -76,8 +92,8 @@ static DUMMY_TEXTURE_DATA: [u8; 160] = [
     0x89, 0x25, 0x89, 0x25, 0x00, 0x00, 0x00, 0x00, 0x89, 0x25, 0x89, 0x25, 0x00, 0x00, 0x00, 0x00,
 ];
 
-#[cfg(feature = "v1-core")]
-static DUMMY_TEXTURE_DATA: [u8; 192] = [
+// 8x8 DXT5 texture used by GFD v1 games
+static DUMMY_TEXTURE_DATA_V1: [u8; 192] = [
     0x44, 0x44, 0x53, 0x20, 0x7C, 0x00, 0x00, 0x00, 0x07, 0x10, 0x00, 0x00,
     0x08, 0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
@@ -96,6 +112,14 @@ static DUMMY_TEXTURE_DATA: [u8; 192] = [
     0xFF, 0xFF, 0xFF, 0xFF, 0x78, 0xFD, 0x89, 0x25, 0x05, 0x05, 0x50, 0x50
 ];
 
+/// Get the DDS that the game uses in place of missing textures
+pub fn get_dummy_texture_data(profile: GameProfile) -> &'static [u8] {
+    match profile.is_gfd_v2() {
+        true => DUMMY_TEXTURE_DATA_V2.as_slice(),
+        false => DUMMY_TEXTURE_DATA_V1.as_slice()
+    }
+}
+
 #[repr(transparent)]
 #[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq)]
 pub struct UnsupportedTextureFormat(u32);
@@ -148,6 +172,29 @@ impl TryFrom<u16> for TextureFormat {
     }
 }
 
+impl TextureFormat {
+    /// Decode file data stored in this format into RGBA8 surfaces. Console formats have their
+    /// swizzling removed. TGA and BMP are decoded with the image crate.
+    pub fn decode(&self, bytes: &[u8]) -> Result<DecodedTexture, Box<dyn Error>> {
+        match self {
+            Self::DDS => Ok(decode_dds(bytes)?),
+            Self::TMX => Ok(decode_tmx(bytes)?),
+            Self::GTF => Ok(decode_gtf(bytes)?),
+            Self::GXT => Ok(decode_gxt(bytes)?),
+            Self::PVR => Ok(decode_pvr(bytes)?),
+            Self::GNF => Ok(decode_gnf(bytes)?),
+            #[cfg(feature = "image_loader")]
+            Self::TGA | Self::BMP => {
+                let img = ImageReader::new(Cursor::new(bytes)).with_guessed_format()?.decode()?.into_rgba8();
+                let (width, height) = img.dimensions();
+                let surface = crate::graphics::texture_codec::surface::Surface::new(0, 0, width, height, img.into_raw());
+                Ok(DecodedTexture::new(width, height, 1, 1, false, vec![surface]))
+            },
+            f => Err(Box::new(UnsupportedTextureFormat(*f as u32)))
+        }
+    }
+}
+
 pub type TexHandle = Option<NonNull<TextureResource<AllocatorHook>>>;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -162,6 +209,59 @@ impl Display for TextureLoadError {
     }
 }
 
+/// Sampler settings stored alongside a texture, in the same form as they're written in a texture
+/// dictionary or material. The renderer turns these into a [`FilterMode`] and a pair of
+/// [`TextureAddressMode`]s when the texture is bound.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextureSampler {
+    pub min: u8,
+    pub mag: u8,
+    pub wraps: u8,
+    pub wrapt: u8
+}
+
+impl Default for TextureSampler {
+    fn default() -> Self {
+        Self::new(1, 1, 0, 0)
+    }
+}
+
+impl TextureSampler {
+    pub fn new(min: u8, mag: u8, wraps: u8, wrapt: u8) -> Self {
+        Self { min, mag, wraps, wrapt }
+    }
+
+    // From TexturePkt::set_texture (0x141101150)
+    pub fn get_filter(&self) -> FilterMode {
+        match (self.min, self.mag) {
+            (1, 1) => FilterMode::Anisotropic,
+            (1, 0) => FilterMode::MinLinearMagMipPoint,
+            (0, 0) => FilterMode::MinMagMipPoint,
+            _ => FilterMode::MinPointMagMipLinear
+        }
+    }
+    /// Set the min and mag values to the pair that the renderer maps to the closest filter mode,
+    /// since only four filter modes can be represented.
+    pub fn set_filter(&mut self, filter: FilterMode) {
+        (self.min, self.mag) = match filter {
+            FilterMode::MinMagMipPoint => (0, 0),
+            FilterMode::MinLinearMagMipPoint => (1, 0),
+            FilterMode::MinMagLinearMipPoint
+            | FilterMode::MinMagMipLinear
+            | FilterMode::Anisotropic => (1, 1),
+            _ => (0, 1)
+        };
+    }
+    /// Returns None if the value isn't a valid address mode
+    pub fn get_address_u(&self) -> Option<TextureAddressMode> { self.wraps.try_into().ok() }
+    pub fn get_address_v(&self) -> Option<TextureAddressMode> { self.wrapt.try_into().ok() }
+    pub fn set_address(&mut self, u: TextureAddressMode, v: TextureAddressMode) {
+        (self.wraps, self.wrapt) = (u as u8, v as u8);
+    }
+}
+
 #[repr(C)]
 #[derive(GfdRcAuto)]
 pub struct Texture<A = GfdAllocator> 
@@ -210,11 +310,34 @@ where A: Allocator + Clone
 
     pub fn get_texture_flags(&self) -> TextureFlags { self.flags }
     pub fn get_file_flags(&self) -> FileFlags { self.file_flags }
+    pub fn get_sampler(&self) -> TextureSampler { TextureSampler::new(self.min, self.mag, self.wraps, self.wrapt) }
 
     pub fn set_texture_flags(&mut self, flag: TextureFlags) { self.flags = flag }
     pub fn set_file_flags(&mut self, flag: FileFlags) { self.file_flags = flag }
+    pub fn set_sampler(&mut self, sampler: TextureSampler) {
+        (self.min, self.mag, self.wraps, self.wrapt) = (sampler.min, sampler.mag, sampler.wraps, sampler.wrapt);
+    }
 
     pub fn is_ready(&self) -> bool { !self.file_flags.contains(FileFlags::NOT_READY) }
+
+    /// Create a texture reference that only carries a name. This is used by materials read from a
+    /// stream, which refer to entries in the mesh's texture dictionary by name.
+    pub fn new_unloaded(name: Name<A>, allocator: A) -> GfdRc<Self, A> {
+        GfdRc::new_in(Self {
+            flags: TextureFlags::NO_CREATING_RESOURCE | TextureFlags::NO_TEXTURE_LIST,
+            handle: None,
+            ref_: Reference::new(),
+            name,
+            min: 1,
+            mag: 1,
+            wraps: 0,
+            wrapt: 0,
+            prev: None,
+            next: None,
+            file_flags: FileFlags::empty(),
+            _allocator: allocator.clone()
+        }, allocator)
+    }
 }
 
 impl<A> Texture<A> 
@@ -281,74 +404,301 @@ where A: Allocator + Clone + Debug
     }
 }
 
-#[cfg(feature = "serialize")]
-pub(crate) struct TextureSerializationContext<AOuter, AInner>
-where AOuter: Allocator + Clone,
-      AInner: Allocator + Clone
+/// A single entry in a mesh's texture dictionary. Unlike [`Texture`], this keeps the texture's
+/// file data around so that it can be written back out.
+pub struct TextureEntry<A = GfdAllocator>
+where A: Allocator + Clone
 {
-    outer: AOuter,
-    inner: AInner
+    name: Name<A>,
+    format: TextureFormat,
+    data: AVec<u8, A>,
+    sampler: TextureSampler
+}
+
+impl<A> Debug for TextureEntry<A>
+where A: Allocator + Clone
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TextureEntry {{ name: {}, format: {:?}, size: 0x{:x}, sampler: {:?} }}",
+        self.name, self.format, self.data.len(), self.sampler)
+    }
+}
+
+impl<A> TextureEntry<A>
+where A: Allocator + Clone
+{
+    pub fn new(name: Name<A>, format: TextureFormat, data: AVec<u8, A>) -> Self {
+        Self { name, format, data, sampler: TextureSampler::default() }
+    }
+
+    pub fn get_name(&self) -> &Name<A> { &self.name }
+    pub fn get_format(&self) -> TextureFormat { self.format }
+    pub fn get_data(&self) -> &[u8] { self.data.as_slice() }
+    pub fn get_sampler(&self) -> TextureSampler { self.sampler }
+    pub fn get_min(&self) -> u8 { self.sampler.min }
+    pub fn get_mag(&self) -> u8 { self.sampler.mag }
+    pub fn get_wraps(&self) -> u8 { self.sampler.wraps }
+    pub fn get_wrapt(&self) -> u8 { self.sampler.wrapt }
+
+    /// Encode an RGBA8 image in row order into a block compressed DDS with a full mip chain
+    pub fn from_rgba(name: &str, width: u32, height: u32, pixels: &[u8], format: DdsPixelFormat, allocator: A) -> Result<Self, Box<dyn Error>> {
+        let dds = encode_dds(width, height, pixels, format, true)?;
+        let mut data = AVec::with_capacity_in(dds.len(), allocator.clone());
+        data.extend_from_slice(dds.as_slice());
+        Ok(Self::new(Name::new_in(name, allocator), TextureFormat::DDS, data))
+    }
+
+    /// Load an image in any format supported by the image crate (PNG, TGA, etc.) and encode it
+    /// into a block compressed DDS with a full mip chain
+    #[cfg(feature = "image_loader")]
+    pub fn from_image_stream(name: &str, bytes: &[u8], format: DdsPixelFormat, allocator: A) -> Result<Self, Box<dyn Error>> {
+        let img = ImageReader::new(Cursor::new(bytes)).with_guessed_format()?.decode()?.into_rgba8();
+        Self::from_rgba(name, img.width(), img.height(), img.as_raw(), format, allocator)
+    }
+
+    /// Decode the texture's file data into RGBA8 surfaces
+    pub fn decode(&self) -> Result<DecodedTexture, Box<dyn Error>> {
+        self.format.decode(self.data.as_slice())
+    }
+
+    pub fn set_data(&mut self, format: TextureFormat, data: AVec<u8, A>) {
+        self.format = format;
+        self.data = data;
+    }
+    /// Take the file data from another entry, keeping this entry's name and sampler settings
+    pub fn replace_data(&mut self, other: Self) {
+        self.set_data(other.format, other.data);
+    }
+    pub fn set_sampler(&mut self, sampler: TextureSampler) { self.sampler = sampler }
+    pub fn set_min(&mut self, value: u8) { self.sampler.min = value }
+    pub fn set_mag(&mut self, value: u8) { self.sampler.mag = value }
+    pub fn set_wraps(&mut self, value: u8) { self.sampler.wraps = value }
+    pub fn set_wrapt(&mut self, value: u8) { self.sampler.wrapt = value }
 }
 
 #[cfg(feature = "serialize")]
-impl<AOuter, AInner> GfdSerializationUserData<AOuter> for TextureSerializationContext<AOuter, AInner>
-where AOuter: Allocator + Clone,
-      AInner: Allocator + Clone
+impl<AStream, AObject, T> GfdSerialize<AStream, T, AObject, DeserializationHeap<Self, AObject>, SerializationSingleAllocator<AObject>> for TextureEntry<AObject>
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      AStream: Allocator + Clone + Debug,
+      AObject: Allocator + Clone
 {
-    fn get_heap_allocator(&self) -> Option<AOuter> {
-        Some(self.outer.clone())
+    // Original function: gfdTextureStreamRead (0x14105e380, Steam Prologue Demo 1.01)
+    fn stream_read(stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<DeserializationHeap<Self, AObject>, Box<dyn Error>> {
+        let allocator = param.get_heap_allocator().unwrap();
+        let name = Name::<AObject>::stream_read(stream, &mut NameSerializationContext::new(allocator.clone(), NameSerializationNoHash))?.into_raw();
+        let format: TextureFormat = stream.read_u16()?.try_into()?;
+        let len = stream.read_u32()? as usize;
+        if len as u64 > stream.get_remaining()? {
+            return Err(Box::new(io::Error::from(io::ErrorKind::UnexpectedEof)));
+        }
+        let mut data = AVec::with_capacity_in(len, allocator.clone());
+        data.resize(len, 0);
+        stream.read_u8_slice(data.as_mut_slice())?;
+        let mut this = Self::new(name, format, data);
+        this.sampler.min = stream.read_u8()?;
+        this.sampler.mag = stream.read_u8()?;
+        this.sampler.wraps = stream.read_u8()?;
+        this.sampler.wrapt = stream.read_u8()?;
+        Ok(DeserializationHeap::new(this, allocator))
+    }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        NameSerializationNoHash.stream_write_inner(stream, &self.name)?;
+        stream.write_u16(self.format as u16)?;
+        stream.write_u32(self.data.len() as u32)?;
+        stream.write_u8_slice(self.data.as_slice())?;
+        stream.write_u8(self.sampler.min)?;
+        stream.write_u8(self.sampler.mag)?;
+        stream.write_u8(self.sampler.wraps)?;
+        stream.write_u8(self.sampler.wrapt)?;
+        Ok(())
     }
 }
 
+// Format, data length and sampler of an entry with an empty name and no data
 #[cfg(feature = "serialize")]
-impl<AOuter, AInner> TextureSerializationContext<AOuter, AInner>
-where AOuter: Allocator + Clone,
-      AInner: Allocator + Clone
+const TEXTURE_ENTRY_MIN_SIZE: u64 = 10;
+
+/// The textures embedded in a model file, stored in the mesh's TextureDictionary chunk. Materials
+/// refer to these by name.
+pub struct TextureDictionary<A = GfdAllocator>
+where A: Allocator + Clone
+{
+    textures: ItemArray<NonNull<TextureEntry<A>>, A>,
+    _allocator: A
+}
+
+impl<A> Debug for TextureDictionary<A>
+where A: Allocator + Clone
 {
-    pub(crate) fn get_inner_allocator(&self) -> AInner {
-        self.inner.clone()
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
     }
-    pub(crate) fn new(outer: AOuter, inner: AInner) -> Self {
-        Self { outer, inner }
+}
+
+impl<A> TextureDictionary<A>
+where A: Allocator + Clone
+{
+    pub fn new(allocator: A) -> Self {
+        Self { textures: ItemArray::new(allocator.clone()), _allocator: allocator }
+    }
+
+    pub fn len(&self) -> usize { self.textures.len() as usize }
+    pub fn is_empty(&self) -> bool { self.textures.len() == 0 }
+    pub fn as_slice(&self) -> &[NonNull<TextureEntry<A>>] { self.textures.as_slice() }
+    pub fn iter(&self) -> impl Iterator<Item = &TextureEntry<A>> {
+        self.textures.as_slice().iter().map(|v| unsafe { v.as_ref() })
+    }
+
+    pub fn get(&self, name: &Name<A>) -> Option<&TextureEntry<A>> {
+        self.iter().find(|v| v.get_name() == name)
+    }
+    pub fn get_mut(&mut self, name: &Name<A>) -> Option<&mut TextureEntry<A>> {
+        self.textures.as_slice_mut().iter_mut().map(|v| unsafe { v.as_mut() }).find(|v| v.get_name() == name)
+    }
+    pub fn get_by_str(&self, name: &str) -> Option<&TextureEntry<A>> {
+        self.iter().find(|v| *v.get_name() == *name)
+    }
+
+    /// Add a texture to the dictionary. If a texture with the same name already exists, only its
+    /// data is replaced so that it keeps its sampler settings. Returns true if an existing texture
+    /// was replaced.
+    pub fn insert(&mut self, texture: TextureEntry<A>) -> Result<bool, Box<dyn Error>> {
+        match self.get_mut(texture.get_name()) {
+            Some(existing) => {
+                existing.replace_data(texture);
+                Ok(true)
+            },
+            None => {
+                let texture = ABox::into_raw(ABox::new_in(texture, self._allocator.clone()));
+                self.textures.push(unsafe { NonNull::new_unchecked(texture) })?;
+                Ok(false)
+            }
+        }
+    }
+
+    /// Remove a texture from the dictionary, returning it if it existed
+    pub fn remove(&mut self, name: &Name<A>) -> Result<Option<TextureEntry<A>>, Box<dyn Error>> {
+        let index = match self.iter().position(|v| v.get_name() == name) {
+            Some(i) => i,
+            None => return Ok(None)
+        };
+        let texture = *self.textures.get(index)?;
+        self.textures.remove(index)?;
+        Ok(Some(*unsafe { ABox::from_raw_in(texture.as_ptr(), self._allocator.clone()) }))
     }
 }
 
+impl<A> Drop for TextureDictionary<A>
+where A: Allocator + Clone
+{
+    fn drop(&mut self) {
+        for texture in self.textures.as_slice() {
+            // SAFETY: entries are only allocated by insert and stream_read, using this allocator
+            drop(unsafe { ABox::from_raw_in(texture.as_ptr(), self._allocator.clone()) });
+        }
+    }
+}
 
 #[cfg(feature = "serialize")]
-impl<AStream, AOuter, AInner, T> GfdSerialize<AStream, T, AOuter, DeserializationHeap<Self, AOuter>, TextureSerializationContext<AOuter, AInner>> for Texture<AOuter>
+impl<AStream, AObject, T> GfdSerialize<AStream, T, AObject, DeserializationHeap<Self, AObject>, SerializationSingleAllocator<AObject>> for TextureDictionary<AObject>
 where T: Debug + Read + Write + Seek + StreamIODevice,
       AStream: Allocator + Clone + Debug,
-      AOuter: Allocator + Clone,
-      AInner: Allocator + Clone
+      AObject: Allocator + Clone
 {
-    // Original function: gfdTextureStreamRead (0x14105e380, Steam Prologue Demo 1.01)
-    fn stream_read(stream: &mut Stream<AStream, T>, param: &mut TextureSerializationContext<AOuter, AInner>) -> Result<DeserializationHeap<Self, AOuter>, Box<dyn Error>> {
-        let mut this = DeserializationHeap::<Self, AOuter>::zeroed(param);
-        this.ref_ = Reference::new();
-        this.stream_read_inner(stream, param)?;
-        Ok(this)
+    fn stream_read(stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<DeserializationHeap<Self, AObject>, Box<dyn Error>> {
+        let allocator = param.get_heap_allocator().unwrap();
+        let count = stream.read_u32()?;
+        if count as u64 > stream.get_remaining()? / TEXTURE_ENTRY_MIN_SIZE {
+            return Err(Box::new(io::Error::from(io::ErrorKind::UnexpectedEof)));
+        }
+        let mut textures = ItemArray::with_capacity(count as usize, allocator.clone())?;
+        for _ in 0..count {
+            textures.push(TextureEntry::<AObject>::stream_read(stream, param)?.into_raw())?;
+        }
+        Ok(DeserializationHeap::new(Self { textures, _allocator: allocator.clone() }, allocator))
+    }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        stream.write_u32(self.len() as u32)?;
+        for texture in self.iter() {
+            texture.stream_write(stream, param)?;
+        }
+        Ok(())
     }
 }
 
-#[cfg(feature = "serialize")]
-impl<AOuter> Texture<AOuter>
-where AOuter: Allocator + Clone {
-    fn stream_read_inner<AStream, AInner, T>(&mut self, stream: &mut Stream<AStream, T>, param: &mut TextureSerializationContext<AOuter, AInner>) -> Result<(), Box<dyn Error>>
-    where T: Debug + Read + Write + Seek + StreamIODevice,
-          AStream: Allocator + Clone + Debug,
-          AInner: Allocator + Clone
-    {
-        let name = Name::<AOuter>::stream_read(stream, &mut NameSerializationContext::new(param.get_heap_allocator().unwrap(), NameSerializationNoHash))?.into_raw();
-        let tex_format: TextureFormat = stream.read_u16()?.try_into()?;
-        let tex_len = stream.read_u32()? as usize;
-        let tex_data = stream.read_u8_owned(tex_len)?;
-        let min = stream.read_u8()?;
-        let mag = stream.read_u8()?;
-        let wraps = stream.read_u8()?;
-        let wrapt = stream.read_u8()?;
-        // println!("{}, {:?} (size: 0x{:x}), {}, {}, {}, {}", name, tex_format, tex_data.len(), min, mag, wraps, wrapt);
-        // Is this texture already loaded in the cache?
-        // let global = unsafe { crate::globals::get_gfd_global_mut() };
+#[cfg(test)]
+pub mod tests {
+    use allocator_api2::{ alloc::Global, vec::Vec as AVec };
+    use crate::{
+        device::ngr::renderer::state::{ FilterMode, TextureAddressMode },
+        utility::name::Name
+    };
+    use std::error::Error;
+    use super::{ TextureDictionary, TextureEntry, TextureFormat, TextureSampler };
+
+    type TestReturn = Result<(), Box<dyn Error>>;
+
+    #[test]
+    fn sampler_filter_round_trip() -> TestReturn {
+        assert_eq!(TextureSampler::default().get_filter(), FilterMode::Anisotropic);
+        assert_eq!(TextureSampler::new(1, 2, 0, 0).get_filter(), FilterMode::MinPointMagMipLinear);
+        for filter in [FilterMode::MinMagMipPoint, FilterMode::MinPointMagMipLinear, FilterMode::MinLinearMagMipPoint, FilterMode::Anisotropic] {
+            let mut sampler = TextureSampler::default();
+            sampler.set_filter(filter);
+            assert_eq!(sampler.get_filter(), filter);
+        }
+        let mut sampler = TextureSampler::default();
+        sampler.set_address(TextureAddressMode::Clamp, TextureAddressMode::Mirror);
+        assert_eq!((sampler.wraps, sampler.wrapt), (2, 1));
+        assert_eq!(sampler.get_address_u(), Some(TextureAddressMode::Clamp));
+        assert_eq!(TextureSampler::new(1, 1, 7, 0).get_address_u(), None);
+        Ok(())
+    }
+
+    fn create_entry(name: &str, data: &[u8]) -> TextureEntry<Global> {
+        let mut bytes = AVec::new_in(Global);
+        bytes.extend_from_slice(data);
+        TextureEntry::new(Name::new_in(name, Global), TextureFormat::DDS, bytes)
+    }
+
+    #[test]
+    fn insert_keeps_sampler() -> TestReturn {
+        let mut dictionary = TextureDictionary::new(Global);
+        assert!(!dictionary.insert(create_entry("a.dds", &[1]))?);
+        let sampler = TextureSampler::new(1, 2, 2, 1);
+        dictionary.get_mut(&Name::new_in("a.dds", Global)).unwrap().set_sampler(sampler);
+        assert!(dictionary.insert(create_entry("a.dds", &[2, 3]))?);
+        assert_eq!(dictionary.len(), 1);
+        let texture = dictionary.get_by_str("a.dds").unwrap();
+        assert_eq!(texture.get_data(), &[2, 3]);
+        assert_eq!(texture.get_sampler(), sampler);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "serialize")]
+    fn reject_truncated_dictionary() -> TestReturn {
+        use crate::utility::stream::{ DevicePlatform, GfdSerialize, SerializationSingleAllocator, StreamFactory, StreamHeader };
+        let factory = StreamFactory::new(Global);
+        let mut param = SerializationSingleAllocator::new(Global);
+        let mut dictionary = TextureDictionary::new(Global);
+        dictionary.insert(create_entry("a.dds", &[1, 2, 3, 4]))?;
+        let mut stream = factory.write_to_memory(StreamHeader::new(0x1105100, DevicePlatform::P5R_DX11))?;
+        dictionary.stream_write(&mut stream, &mut param)?;
+        let written = stream.as_slice().to_vec();
+        let mut stream = factory.read_from_memory(&written)?;
+        let read = TextureDictionary::<Global>::stream_read(&mut stream, &mut param)?.into_box();
+        assert_eq!(read.get_by_str("a.dds").map(|t| t.get_data()), Some([1, 2, 3, 4].as_slice()));
+        // Data length past the end of the file
+        let mut stream = factory.read_from_memory(&written[..written.len() - 6])?;
+        assert!(TextureDictionary::<Global>::stream_read(&mut stream, &mut param).is_err());
+        // More entries than could fit in the file
+        let mut huge = written.clone();
+        huge[0x10..0x14].copy_from_slice(&u32::MAX.to_le_bytes());
+        let mut stream = factory.read_from_memory(&huge)?;
+        assert!(TextureDictionary::<Global>::stream_read(&mut stream, &mut param).is_err());
         Ok(())
     }
 }
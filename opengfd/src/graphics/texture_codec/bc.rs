@@ -0,0 +1,388 @@
+//! Block compressed texture decoders. Each function takes one compressed block and returns the
+//! 4x4 pixels that it covers as RGBA8, in row order.
+
+/// Decoded pixels for a single 4x4 block
+pub type Block = [[u8; 4]; 16];
+
+pub const BC1_BLOCK_SIZE: usize = 8;
+pub const BC2_BLOCK_SIZE: usize = 16;
+pub const BC3_BLOCK_SIZE: usize = 16;
+pub const BC4_BLOCK_SIZE: usize = 8;
+pub const BC5_BLOCK_SIZE: usize = 16;
+pub const BC7_BLOCK_SIZE: usize = 16;
+
+pub(crate) fn unpack_565(value: u16) -> [u8; 4] {
+    let r = (value >> 11 & 0x1f) as u8;
+    let g = (value >> 5 & 0x3f) as u8;
+    let b = (value & 0x1f) as u8;
+    [r << 3 | r >> 2, g << 2 | g >> 4, b << 3 | b >> 2, u8::MAX]
+}
+
+// BC1 blocks where color0 <= color1 use three colors and transparent black. BC2 and BC3 always
+// use four colors.
+fn decode_color_block(block: &[u8], allow_transparent: bool) -> Block {
+    let c0 = u16::from_le_bytes([block[0], block[1]]);
+    let c1 = u16::from_le_bytes([block[2], block[3]]);
+    let (p0, p1) = (unpack_565(c0), unpack_565(c1));
+    let mut palette = [p0, p1, [0, 0, 0, u8::MAX], [0; 4]];
+    if c0 > c1 || !allow_transparent {
+        palette[3][3] = u8::MAX;
+        for i in 0..3 {
+            palette[2][i] = ((2 * p0[i] as u16 + p1[i] as u16) / 3) as u8;
+            palette[3][i] = ((p0[i] as u16 + 2 * p1[i] as u16) / 3) as u8;
+        }
+    } else {
+        for i in 0..3 {
+            palette[2][i] = ((p0[i] as u16 + p1[i] as u16) / 2) as u8;
+        }
+    }
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+    std::array::from_fn(|i| palette[(indices >> (2 * i) & 3) as usize])
+}
+
+// Shared by BC3 alpha and BC4/BC5 channels. When the first endpoint is smaller, the last two
+// indices are fixed to the minimum and maximum values.
+fn decode_channel_block(e0: i32, e1: i32, indices: &[u8], min: i32, max: i32) -> [i32; 16] {
+    let mut palette = [e0, e1, 0, 0, 0, 0, min, max];
+    if e0 > e1 {
+        for i in 1..7 {
+            palette[i as usize + 1] = ((7 - i) * e0 + i * e1) / 7;
+        }
+    } else {
+        for i in 1..5 {
+            palette[i as usize + 1] = ((5 - i) * e0 + i * e1) / 5;
+        }
+    }
+    let bits = indices.iter().take(6).enumerate()
+        .fold(0u64, |bits, (i, b)| bits | (*b as u64) << (8 * i));
+    std::array::from_fn(|i| palette[(bits >> (3 * i) & 7) as usize])
+}
+
+fn decode_unorm_channel(block: &[u8]) -> [u8; 16] {
+    decode_channel_block(block[0] as i32, block[1] as i32, &block[2..8], 0, 255).map(|v| v as u8)
+}
+
+fn decode_snorm_channel(block: &[u8]) -> [u8; 16] {
+    // -128 and -127 both map to -1.0
+    let e0 = (block[0] as i8).max(-127) as i32;
+    let e1 = (block[1] as i8).max(-127) as i32;
+    decode_channel_block(e0, e1, &block[2..8], -127, 127).map(|v| ((v + 127) * 255 / 254) as u8)
+}
+
+pub fn decode_bc1(block: &[u8]) -> Block {
+    decode_color_block(block, true)
+}
+
+pub fn decode_bc2(block: &[u8]) -> Block {
+    let mut out = decode_color_block(&block[8..], false);
+    let alpha = u64::from_le_bytes(block[..8].try_into().unwrap());
+    for (i, pixel) in out.iter_mut().enumerate() {
+        pixel[3] = (alpha >> (4 * i) & 0xf) as u8 * 17;
+    }
+    out
+}
+
+pub fn decode_bc3(block: &[u8]) -> Block {
+    let mut out = decode_color_block(&block[8..], false);
+    for (pixel, alpha) in out.iter_mut().zip(decode_unorm_channel(&block[..8])) {
+        pixel[3] = alpha;
+    }
+    out
+}
+
+/// Single channel textures are returned as grayscale
+pub fn decode_bc4(block: &[u8], signed: bool) -> Block {
+    let red = match signed {
+        true => decode_snorm_channel(block),
+        false => decode_unorm_channel(block)
+    };
+    red.map(|r| [r, r, r, u8::MAX])
+}
+
+/// Red and green are stored as-is, blue is left at zero
+pub fn decode_bc5(block: &[u8], signed: bool) -> Block {
+    let (red, green) = match signed {
+        true => (decode_snorm_channel(&block[..8]), decode_snorm_channel(&block[8..])),
+        false => (decode_unorm_channel(&block[..8]), decode_unorm_channel(&block[8..]))
+    };
+    std::array::from_fn(|i| [red[i], green[i], 0, u8::MAX])
+}
+
+struct Bc7Mode {
+    subsets: usize,
+    partition_bits: u32,
+    rotation_bits: u32,
+    index_selection_bits: u32,
+    color_bits: u32,
+    alpha_bits: u32,
+    endpoint_pbits: bool,
+    shared_pbits: bool,
+    index_bits: u32,
+    index2_bits: u32
+}
+
+impl Bc7Mode {
+    /// Endpoint bits are (color, alpha), p-bits are (per endpoint, shared per subset) and index
+    /// bits are (primary, secondary)
+    const fn new(subsets: usize, partition_bits: u32, rotation_bits: u32, index_selection_bits: u32,
+        (color_bits, alpha_bits): (u32, u32), (endpoint_pbits, shared_pbits): (bool, bool),
+        (index_bits, index2_bits): (u32, u32)) -> Self {
+        Self { subsets, partition_bits, rotation_bits, index_selection_bits, color_bits,
+            alpha_bits, endpoint_pbits, shared_pbits, index_bits, index2_bits }
+    }
+}
+
+static BC7_MODES: [Bc7Mode; 8] = [
+    Bc7Mode::new(3, 4, 0, 0, (4, 0), (true, false), (3, 0)),
+    Bc7Mode::new(2, 6, 0, 0, (6, 0), (false, true), (3, 0)),
+    Bc7Mode::new(3, 6, 0, 0, (5, 0), (false, false), (2, 0)),
+    Bc7Mode::new(2, 6, 0, 0, (7, 0), (true, false), (2, 0)),
+    Bc7Mode::new(1, 0, 2, 1, (5, 6), (false, false), (2, 3)),
+    Bc7Mode::new(1, 0, 2, 0, (7, 8), (false, false), (2, 2)),
+    Bc7Mode::new(1, 0, 0, 0, (7, 7), (true, false), (4, 0)),
+    Bc7Mode::new(2, 6, 0, 0, (5, 5), (true, false), (2, 0)),
+];
+
+// Two subset partitions, with one bit per pixel for the pixel's subset
+static BC7_PARTITION2: [u16; 64] = [
+    0xcccc, 0x8888, 0xeeee, 0xecc8, 0xc880, 0xfeec, 0xfec8, 0xec80,
+    0xc800, 0xffec, 0xfe80, 0xe800, 0xffe8, 0xff00, 0xfff0, 0xf000,
+    0xf710, 0x008e, 0x7100, 0x08ce, 0x008c, 0x7310, 0x3100, 0x8cce,
+    0x088c, 0x3110, 0x6666, 0x366c, 0x17e8, 0x0ff0, 0x718e, 0x399c,
+    0xaaaa, 0xf0f0, 0x5a5a, 0x33cc, 0x3c3c, 0x55aa, 0x9696, 0xa55a,
+    0x73ce, 0x13c8, 0x324c, 0x3bdc, 0x6996, 0xc33c, 0x9966, 0x0660,
+    0x0272, 0x04e4, 0x4e40, 0x2720, 0xc936, 0x936c, 0x39c6, 0x639c,
+    0x9336, 0x9cc6, 0x817e, 0xe718, 0xccf0, 0x0fcc, 0x7744, 0xee22,
+];
+
+static BC7_PARTITION3: [[u8; 16]; 64] = [
+    [0, 0, 1, 1, 0, 0, 1, 1, 0, 2, 2, 1, 2, 2, 2, 2], [0, 0, 0, 1, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2, 2, 1],
+    [0, 0, 0, 0, 2, 0, 0, 1, 2, 2, 1, 1, 2, 2, 1, 1], [0, 2, 2, 2, 0, 0, 2, 2, 0, 0, 1, 1, 0, 1, 1, 1],
+    [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2], [0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 2, 2, 0, 0, 2, 2],
+    [0, 0, 2, 2, 0, 0, 2, 2, 1, 1, 1, 1, 1, 1, 1, 1], [0, 0, 1, 1, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2, 1, 1],
+    [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2], [0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 2],
+    [0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 2, 2, 2, 2], [0, 0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2],
+    [0, 1, 1, 2, 0, 1, 1, 2, 0, 1, 1, 2, 0, 1, 1, 2], [0, 1, 2, 2, 0, 1, 2, 2, 0, 1, 2, 2, 0, 1, 2, 2],
+    [0, 0, 1, 1, 0, 1, 1, 2, 1, 1, 2, 2, 1, 2, 2, 2], [0, 0, 1, 1, 2, 0, 0, 1, 2, 2, 0, 0, 2, 2, 2, 0],
+    [0, 0, 0, 1, 0, 0, 1, 1, 0, 1, 1, 2, 1, 1, 2, 2], [0, 1, 1, 1, 0, 0, 1, 1, 2, 0, 0, 1, 2, 2, 0, 0],
+    [0, 0, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2, 1, 1, 2, 2], [0, 0, 2, 2, 0, 0, 2, 2, 0, 0, 2, 2, 1, 1, 1, 1],
+    [0, 1, 1, 1, 0, 1, 1, 1, 0, 2, 2, 2, 0, 2, 2, 2], [0, 0, 0, 1, 0, 0, 0, 1, 2, 2, 2, 1, 2, 2, 2, 1],
+    [0, 0, 0, 0, 0, 0, 1, 1, 0, 1, 2, 2, 0, 1, 2, 2], [0, 0, 0, 0, 1, 1, 0, 0, 2, 2, 1, 0, 2, 2, 1, 0],
+    [0, 1, 2, 2, 0, 1, 2, 2, 0, 0, 1, 1, 0, 0, 0, 0], [0, 0, 1, 2, 0, 0, 1, 2, 1, 1, 2, 2, 2, 2, 2, 2],
+    [0, 1, 1, 0, 1, 2, 2, 1, 1, 2, 2, 1, 0, 1, 1, 0], [0, 0, 0, 0, 0, 1, 1, 0, 1, 2, 2, 1, 1, 2, 2, 1],
+    [0, 0, 2, 2, 1, 1, 0, 2, 1, 1, 0, 2, 0, 0, 2, 2], [0, 1, 1, 0, 0, 1, 1, 0, 2, 0, 0, 2, 2, 2, 2, 2],
+    [0, 0, 1, 1, 0, 1, 2, 2, 0, 1, 2, 2, 0, 0, 1, 1], [0, 0, 0, 0, 2, 0, 0, 0, 2, 2, 1, 1, 2, 2, 2, 1],
+    [0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2, 2, 1, 2, 2, 2], [0, 2, 2, 2, 0, 0, 2, 2, 0, 0, 1, 2, 0, 0, 1, 1],
+    [0, 0, 1, 1, 0, 0, 1, 2, 0, 0, 2, 2, 0, 2, 2, 2], [0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2, 0],
+    [0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 0, 0, 0, 0], [0, 1, 2, 0, 1, 2, 0, 1, 2, 0, 1, 2, 0, 1, 2, 0],
+    [0, 1, 2, 0, 2, 0, 1, 2, 1, 2, 0, 1, 0, 1, 2, 0], [0, 0, 1, 1, 2, 2, 0, 0, 1, 1, 2, 2, 0, 0, 1, 1],
+    [0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 0, 0, 0, 0, 1, 1], [0, 1, 0, 1, 0, 1, 0, 1, 2, 2, 2, 2, 2, 2, 2, 2],
+    [0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 2, 1, 2, 1, 2, 1], [0, 0, 2, 2, 1, 1, 2, 2, 0, 0, 2, 2, 1, 1, 2, 2],
+    [0, 0, 2, 2, 0, 0, 1, 1, 0, 0, 2, 2, 0, 0, 1, 1], [0, 2, 2, 0, 1, 2, 2, 1, 0, 2, 2, 0, 1, 2, 2, 1],
+    [0, 1, 0, 1, 2, 2, 2, 2, 2, 2, 2, 2, 0, 1, 0, 1], [0, 0, 0, 0, 2, 1, 2, 1, 2, 1, 2, 1, 2, 1, 2, 1],
+    [0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 2, 2, 2, 2], [0, 2, 2, 2, 0, 1, 1, 1, 0, 2, 2, 2, 0, 1, 1, 1],
+    [0, 0, 0, 2, 1, 1, 1, 2, 0, 0, 0, 2, 1, 1, 1, 2], [0, 0, 0, 0, 2, 1, 1, 2, 2, 1, 1, 2, 2, 1, 1, 2],
+    [0, 2, 2, 2, 0, 1, 1, 1, 0, 1, 1, 1, 0, 2, 2, 2], [0, 0, 0, 2, 1, 1, 1, 2, 1, 1, 1, 2, 0, 0, 0, 2],
+    [0, 1, 1, 0, 0, 1, 1, 0, 0, 1, 1, 0, 2, 2, 2, 2], [0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2, 2, 1, 1, 2],
+    [0, 1, 1, 0, 0, 1, 1, 0, 2, 2, 2, 2, 2, 2, 2, 2], [0, 0, 2, 2, 0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 2, 2],
+    [0, 0, 2, 2, 1, 1, 2, 2, 1, 1, 2, 2, 0, 0, 2, 2], [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2],
+    [0, 0, 0, 2, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 1], [0, 2, 2, 2, 1, 2, 2, 2, 0, 2, 2, 2, 1, 2, 2, 2],
+    [0, 1, 0, 1, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2], [0, 1, 1, 1, 2, 0, 1, 1, 2, 2, 0, 1, 2, 2, 2, 0],
+];
+
+// Anchor pixels, which store their index with one less bit. The first subset's anchor is always
+// pixel 0.
+static BC7_ANCHOR2: [u8; 64] = [
+    15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15,
+    15,  2,  8,  2,  2,  8,  8, 15,  2,  8,  2,  2,  8,  8,  2,  2,
+    15, 15,  6,  8,  2,  8, 15, 15,  2,  8,  2,  2,  2, 15, 15,  6,
+     6,  2,  6,  8, 15, 15,  2,  2, 15, 15, 15, 15, 15,  2,  2, 15,
+];
+
+static BC7_ANCHOR3_SECOND: [u8; 64] = [
+     3,  3, 15, 15,  8,  3, 15, 15,  8,  8,  6,  6,  6,  5,  3,  3,
+     3,  3,  8, 15,  3,  3,  6, 10,  5,  8,  8,  6,  8,  5, 15, 15,
+     8, 15,  3,  5,  6, 10,  8, 15, 15,  3, 15,  5, 15, 15, 15, 15,
+     3, 15,  5,  5,  5,  8,  5, 10,  5, 10,  8, 13, 15, 12,  3,  3,
+];
+
+static BC7_ANCHOR3_THIRD: [u8; 64] = [
+    15,  8,  8,  3, 15, 15,  3,  8, 15, 15, 15, 15, 15, 15, 15,  8,
+    15,  8, 15,  3, 15,  8, 15,  8,  3, 15,  6, 10, 15, 15, 10,  8,
+    15,  3, 15, 10, 10,  8,  9, 10,  6, 15,  8, 15,  3,  6,  6,  8,
+    15,  3, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15,  3, 15, 15,  8,
+];
+
+static BC7_WEIGHTS2: [u32; 4] = [0, 21, 43, 64];
+static BC7_WEIGHTS3: [u32; 8] = [0, 9, 18, 27, 37, 46, 55, 64];
+static BC7_WEIGHTS4: [u32; 16] = [0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64];
+
+struct BitReader {
+    bits: u128,
+    position: u32
+}
+
+impl BitReader {
+    fn read(&mut self, count: u32) -> u32 {
+        let value = (self.bits >> self.position) as u32 & ((1 << count) - 1);
+        self.position += count;
+        value
+    }
+}
+
+fn bc7_weight(bits: u32, index: u32) -> u32 {
+    match bits {
+        2 => BC7_WEIGHTS2[index as usize],
+        3 => BC7_WEIGHTS3[index as usize],
+        _ => BC7_WEIGHTS4[index as usize]
+    }
+}
+
+fn bc7_expand(value: u32, bits: u32) -> u32 {
+    (value << (8 - bits)) | (value >> (2 * bits - 8))
+}
+
+fn bc7_interpolate(e0: u32, e1: u32, weight: u32) -> u8 {
+    (((64 - weight) * e0 + weight * e1 + 32) >> 6) as u8
+}
+
+fn bc7_subset(mode: &Bc7Mode, partition: usize, pixel: usize) -> usize {
+    match mode.subsets {
+        2 => (BC7_PARTITION2[partition] >> pixel & 1) as usize,
+        3 => BC7_PARTITION3[partition][pixel] as usize,
+        _ => 0
+    }
+}
+
+fn bc7_is_anchor(mode: &Bc7Mode, partition: usize, pixel: usize) -> bool {
+    pixel == 0 || match mode.subsets {
+        2 => BC7_ANCHOR2[partition] as usize == pixel,
+        3 => BC7_ANCHOR3_SECOND[partition] as usize == pixel || BC7_ANCHOR3_THIRD[partition] as usize == pixel,
+        _ => false
+    }
+}
+
+/// Blocks with an invalid mode decode to transparent black
+pub fn decode_bc7(block: &[u8]) -> Block {
+    let bits = u128::from_le_bytes(block[..16].try_into().unwrap());
+    let mode_id = bits.trailing_zeros();
+    if mode_id >= 8 {
+        return [[0; 4]; 16];
+    }
+    let mode = &BC7_MODES[mode_id as usize];
+    let mut reader = BitReader { bits, position: mode_id + 1 };
+    let partition = reader.read(mode.partition_bits) as usize;
+    let rotation = reader.read(mode.rotation_bits);
+    let index_selection = reader.read(mode.index_selection_bits);
+    let count = mode.subsets * 2;
+    let mut endpoints = [[0u32; 4]; 6];
+    for channel in 0..3 {
+        for endpoint in endpoints.iter_mut().take(count) {
+            endpoint[channel] = reader.read(mode.color_bits);
+        }
+    }
+    for endpoint in endpoints.iter_mut().take(count) {
+        endpoint[3] = reader.read(mode.alpha_bits);
+    }
+    let (mut color_bits, mut alpha_bits) = (mode.color_bits, mode.alpha_bits);
+    if mode.endpoint_pbits || mode.shared_pbits {
+        let mut pbit = 0;
+        for (i, endpoint) in endpoints.iter_mut().enumerate().take(count) {
+            if mode.endpoint_pbits || i % 2 == 0 {
+                pbit = reader.read(1);
+            }
+            for channel in endpoint.iter_mut() {
+                *channel = *channel << 1 | pbit;
+            }
+        }
+        color_bits += 1;
+        if alpha_bits > 0 {
+            alpha_bits += 1;
+        }
+    }
+    for endpoint in endpoints.iter_mut().take(count) {
+        for channel in endpoint.iter_mut().take(3) {
+            *channel = bc7_expand(*channel, color_bits);
+        }
+        endpoint[3] = match alpha_bits {
+            0 => u8::MAX as u32,
+            n => bc7_expand(endpoint[3], n)
+        };
+    }
+    let mut indices = [0u32; 16];
+    for (i, index) in indices.iter_mut().enumerate() {
+        *index = reader.read(mode.index_bits - bc7_is_anchor(mode, partition, i) as u32);
+    }
+    let mut indices2 = [0u32; 16];
+    if mode.index2_bits > 0 {
+        for (i, index) in indices2.iter_mut().enumerate() {
+            *index = reader.read(mode.index2_bits - (i == 0) as u32);
+        }
+    }
+    std::array::from_fn(|i| {
+        let subset = bc7_subset(mode, partition, i);
+        let (e0, e1) = (endpoints[subset * 2], endpoints[subset * 2 + 1]);
+        let ((color_index, color_bits), (alpha_index, alpha_bits)) = match (mode.index2_bits, index_selection) {
+            (0, _) => ((indices[i], mode.index_bits), (indices[i], mode.index_bits)),
+            (_, 0) => ((indices[i], mode.index_bits), (indices2[i], mode.index2_bits)),
+            _ => ((indices2[i], mode.index2_bits), (indices[i], mode.index_bits))
+        };
+        let color_weight = bc7_weight(color_bits, color_index);
+        let alpha_weight = bc7_weight(alpha_bits, alpha_index);
+        let mut pixel = [
+            bc7_interpolate(e0[0], e1[0], color_weight),
+            bc7_interpolate(e0[1], e1[1], color_weight),
+            bc7_interpolate(e0[2], e1[2], color_weight),
+            bc7_interpolate(e0[3], e1[3], alpha_weight)
+        ];
+        if rotation > 0 {
+            pixel.swap(3, rotation as usize - 1);
+        }
+        pixel
+    })
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::error::Error;
+    use super::{ decode_bc1, decode_bc7 };
+
+    type TestReturn = Result<(), Box<dyn Error>>;
+
+    #[test]
+    fn decode_bc1_punchthrough() -> TestReturn {
+        // color0 <= color1, so index 3 is transparent
+        let block = decode_bc1(&[0x00, 0x00, 0xff, 0xff, 0b11100100, 0, 0, 0]);
+        assert_eq!(block[0], [0, 0, 0, 255]);
+        assert_eq!(block[1], [255, 255, 255, 255]);
+        assert_eq!(block[2], [127, 127, 127, 255]);
+        assert_eq!(block[3], [0, 0, 0, 0]);
+        Ok(())
+    }
+
+    #[test]
+    fn decode_bc7_mode6() -> TestReturn {
+        // Endpoint 0 is transparent black, endpoint 1 is opaque white (127 with a p-bit of 1)
+        let mut bits = 1u128 << 6;
+        let mut position = 7;
+        let mut write = |value: u128, count: u32| {
+            bits |= value << position;
+            position += count;
+        };
+        for _ in 0..4 {
+            write(0, 7);
+            write(127, 7);
+        }
+        write(0, 1);
+        write(1, 1);
+        write(0, 3);
+        write(15, 4);
+        write(8, 4);
+        let block = decode_bc7(&bits.to_le_bytes());
+        assert_eq!(block[0], [0, 0, 0, 0]);
+        assert_eq!(block[1], [255, 255, 255, 255]);
+        assert_eq!(block[2], [135, 135, 135, 135]);
+        assert_eq!(block[3], [0, 0, 0, 0]);
+        Ok(())
+    }
+}
@@ -0,0 +1,238 @@
+//! Block compressed texture encoders. Each function takes the 4x4 RGBA8 pixels
+//! covered by a block, in row order, and returns the compressed block.
+//!
+//! Endpoints are fitted along the principal axis of the block's colors, then each pixel picks
+//! the closest palette entry. This favours speed and predictability over the best possible
+//! quality, which is fine for building texture mods.
+
+use crate::graphics::texture_codec::bc::{
+    unpack_565, Block, BC1_BLOCK_SIZE, BC3_BLOCK_SIZE, BC4_BLOCK_SIZE, BC5_BLOCK_SIZE, BC7_BLOCK_SIZE
+};
+
+/// Pixels with alpha below this are transparent in BC1
+const BC1_ALPHA_THRESHOLD: u8 = 0x80;
+
+const BC7_WEIGHTS4: [u32; 16] = [0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64];
+
+/// Find the two ends of a line through the first `N` channels of each pixel that best fits them.
+/// The direction is found by power iteration on the covariance matrix.
+fn fit_endpoints<const N: usize>(pixels: &[[u8; 4]]) -> ([f32; N], [f32; N]) {
+    if pixels.is_empty() {
+        return ([0.; N], [0.; N]);
+    }
+    let count = pixels.len() as f32;
+    let mut mean = [0f32; N];
+    for pixel in pixels {
+        for c in 0..N {
+            mean[c] += pixel[c] as f32 / count;
+        }
+    }
+    let mut covariance = [[0f32; N]; N];
+    for pixel in pixels {
+        let d: [f32; N] = std::array::from_fn(|c| pixel[c] as f32 - mean[c]);
+        for i in 0..N {
+            for j in 0..N {
+                covariance[i][j] += d[i] * d[j];
+            }
+        }
+    }
+    let mut axis = [1f32; N];
+    for _ in 0..8 {
+        let next: [f32; N] = std::array::from_fn(|i| (0..N).map(|j| covariance[i][j] * axis[j]).sum());
+        let length = next.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if length < f32::EPSILON {
+            break;
+        }
+        axis = next.map(|v| v / length);
+    }
+    let (mut t_min, mut t_max) = (f32::MAX, f32::MIN);
+    for pixel in pixels {
+        let t: f32 = (0..N).map(|c| (pixel[c] as f32 - mean[c]) * axis[c]).sum();
+        t_min = t_min.min(t);
+        t_max = t_max.max(t);
+    }
+    (std::array::from_fn(|c| (mean[c] + axis[c] * t_min).clamp(0., 255.)),
+        std::array::from_fn(|c| (mean[c] + axis[c] * t_max).clamp(0., 255.)))
+}
+
+fn get_distance<const N: usize>(a: &[u8; 4], b: &[u8; 4]) -> u32 {
+    (0..N).map(|c| (a[c] as i32 - b[c] as i32).pow(2) as u32).sum()
+}
+
+fn get_closest<const N: usize>(pixel: &[u8; 4], palette: &[[u8; 4]]) -> u32 {
+    (0..palette.len()).min_by_key(|i| get_distance::<N>(pixel, &palette[*i])).unwrap() as u32
+}
+
+fn pack_565(color: &[f32; 3]) -> u16 {
+    let r = (color[0] * 31. / 255. + 0.5) as u16;
+    let g = (color[1] * 63. / 255. + 0.5) as u16;
+    let b = (color[2] * 31. / 255. + 0.5) as u16;
+    r << 11 | g << 5 | b
+}
+
+/// Encode the color part of a BC1, BC2 or BC3 block. BC1 blocks with transparent pixels use the
+/// three color mode, where the last index is transparent black.
+fn encode_color_block(pixels: &Block, allow_transparent: bool) -> [u8; 8] {
+    let transparent = allow_transparent && pixels.iter().any(|p| p[3] < BC1_ALPHA_THRESHOLD);
+    let opaque: Vec<[u8; 4]> = pixels.iter().filter(|p| !transparent || p[3] >= BC1_ALPHA_THRESHOLD).copied().collect();
+    let (e0, e1) = fit_endpoints::<3>(&opaque);
+    let (mut c0, mut c1) = (pack_565(&e1), pack_565(&e0));
+    // The mode is picked by the order of the endpoints, so swap them to select the one we want
+    if transparent == (c0 > c1) {
+        std::mem::swap(&mut c0, &mut c1);
+    }
+    let (p0, p1) = (unpack_565(c0), unpack_565(c1));
+    let palette: Vec<[u8; 4]> = match transparent || c0 == c1 {
+        false => vec![p0, p1,
+            std::array::from_fn(|i| if i == 3 { 255 } else { ((2 * p0[i] as u16 + p1[i] as u16) / 3) as u8 }),
+            std::array::from_fn(|i| if i == 3 { 255 } else { ((p0[i] as u16 + 2 * p1[i] as u16) / 3) as u8 })],
+        true => vec![p0, p1,
+            std::array::from_fn(|i| if i == 3 { 255 } else { ((p0[i] as u16 + p1[i] as u16) / 2) as u8 })]
+    };
+    let mut indices = 0u32;
+    for (i, pixel) in pixels.iter().enumerate() {
+        let index = match transparent && pixel[3] < BC1_ALPHA_THRESHOLD {
+            true => 3,
+            false => get_closest::<3>(pixel, &palette)
+        };
+        indices |= index << (2 * i);
+    }
+    let mut out = [0u8; BC1_BLOCK_SIZE];
+    out[0..2].copy_from_slice(&c0.to_le_bytes());
+    out[2..4].copy_from_slice(&c1.to_le_bytes());
+    out[4..8].copy_from_slice(&indices.to_le_bytes());
+    out
+}
+
+/// Encode a single channel using the eight value mode, used by BC3 alpha and BC4/BC5
+fn encode_channel_block(values: [u8; 16]) -> [u8; 8] {
+    let (min, max) = (*values.iter().min().unwrap(), *values.iter().max().unwrap());
+    let mut out = [0u8; BC4_BLOCK_SIZE];
+    out[0] = max;
+    out[1] = min;
+    if max == min {
+        return out;
+    }
+    let palette: [i32; 8] = std::array::from_fn(|i| match i {
+        0 => max as i32,
+        1 => min as i32,
+        i => ((8 - i as i32) * max as i32 + (i as i32 - 1) * min as i32) / 7
+    });
+    let mut bits = 0u64;
+    for (i, value) in values.iter().enumerate() {
+        let index = (0..8).min_by_key(|p| (palette[*p] - *value as i32).abs()).unwrap() as u64;
+        bits |= index << (3 * i);
+    }
+    out[2..8].copy_from_slice(&bits.to_le_bytes()[..6]);
+    out
+}
+
+pub fn encode_bc1(pixels: &Block) -> [u8; BC1_BLOCK_SIZE] {
+    encode_color_block(pixels, true)
+}
+
+pub fn encode_bc3(pixels: &Block) -> [u8; BC3_BLOCK_SIZE] {
+    let mut out = [0u8; BC3_BLOCK_SIZE];
+    out[..8].copy_from_slice(&encode_channel_block(pixels.map(|p| p[3])));
+    out[8..].copy_from_slice(&encode_color_block(pixels, false));
+    out
+}
+
+/// Encodes the red channel
+pub fn encode_bc4(pixels: &Block) -> [u8; BC4_BLOCK_SIZE] {
+    encode_channel_block(pixels.map(|p| p[0]))
+}
+
+/// Encodes the red and green channels, which is the usual layout for normal maps
+pub fn encode_bc5(pixels: &Block) -> [u8; BC5_BLOCK_SIZE] {
+    let mut out = [0u8; BC5_BLOCK_SIZE];
+    out[..8].copy_from_slice(&encode_channel_block(pixels.map(|p| p[0])));
+    out[8..].copy_from_slice(&encode_channel_block(pixels.map(|p| p[1])));
+    out
+}
+
+/// Quantize an endpoint to 7 bits per channel with a shared p-bit, picking the p-bit that gives
+/// the lowest error
+fn quantize_bc7_endpoint(endpoint: &[f32; 4]) -> ([u32; 4], u32) {
+    (0..2).map(|pbit| {
+        let q: [u32; 4] = endpoint.map(|c| ((c - pbit as f32) / 2. + 0.5).clamp(0., 127.) as u32);
+        let error: f32 = (0..4).map(|c| ((q[c] << 1 | pbit) as f32 - endpoint[c]).powi(2)).sum();
+        ((q, pbit), error)
+    }).min_by(|a, b| a.1.total_cmp(&b.1)).unwrap().0
+}
+
+/// Encodes using mode 6, which has a single subset with 7-bit RGBA endpoints, p-bits and 4-bit
+/// indices
+pub fn encode_bc7(pixels: &Block) -> [u8; BC7_BLOCK_SIZE] {
+    let (e0, e1) = fit_endpoints::<4>(pixels);
+    let (mut q0, mut p0) = quantize_bc7_endpoint(&e0);
+    let (mut q1, mut p1) = quantize_bc7_endpoint(&e1);
+    let get_palette = |q0: &[u32; 4], p0: u32, q1: &[u32; 4], p1: u32| -> [[u8; 4]; 16] {
+        std::array::from_fn(|i| std::array::from_fn(|c| {
+            let (a, b) = (q0[c] << 1 | p0, q1[c] << 1 | p1);
+            (((64 - BC7_WEIGHTS4[i]) * a + BC7_WEIGHTS4[i] * b + 32) >> 6) as u8
+        }))
+    };
+    let mut palette = get_palette(&q0, p0, &q1, p1);
+    let mut indices: [u32; 16] = std::array::from_fn(|i| get_closest::<4>(&pixels[i], &palette));
+    // The first pixel is the anchor and stores its index without the top bit
+    if indices[0] >= 8 {
+        std::mem::swap(&mut q0, &mut q1);
+        std::mem::swap(&mut p0, &mut p1);
+        palette = get_palette(&q0, p0, &q1, p1);
+        indices = std::array::from_fn(|i| get_closest::<4>(&pixels[i], &palette));
+        indices[0] = indices[0].min(7);
+    }
+    let mut bits = 1u128 << 6;
+    let mut offset = 7;
+    let mut write = |value: u32, count: u32| {
+        bits |= (value as u128) << offset;
+        offset += count;
+    };
+    for c in 0..4 {
+        write(q0[c], 7);
+        write(q1[c], 7);
+    }
+    write(p0, 1);
+    write(p1, 1);
+    for (i, index) in indices.iter().enumerate() {
+        write(*index, if i == 0 { 3 } else { 4 });
+    }
+    bits.to_le_bytes()
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::graphics::texture_codec::bc::{ decode_bc1, decode_bc3, decode_bc5, decode_bc7, Block };
+    use std::error::Error;
+    use super::{ encode_bc1, encode_bc3, encode_bc5, encode_bc7 };
+
+    type TestReturn = Result<(), Box<dyn Error>>;
+
+    fn get_max_error(a: &Block, b: &Block, channels: usize) -> i32 {
+        a.iter().zip(b.iter())
+            .flat_map(|(a, b)| (0..channels).map(|c| (a[c] as i32 - b[c] as i32).abs()))
+            .max().unwrap()
+    }
+
+    #[test]
+    fn encode_round_trip() -> TestReturn {
+        // A gradient between two colors, which only loses precision to endpoint quantization
+        let pixels: Block = std::array::from_fn(|i| {
+            let t = (i % 4) as u32;
+            [(255 * t / 3) as u8, (128 - 40 * t) as u8, 64, (255 - 85 * t) as u8]
+        });
+        let opaque = pixels.map(|p| [p[0], p[1], p[2], 255]);
+        assert!(get_max_error(&pixels, &decode_bc1(&encode_bc1(&opaque)), 3) <= 12);
+        assert!(get_max_error(&pixels, &decode_bc3(&encode_bc3(&pixels)), 4) <= 16);
+        assert!(get_max_error(&pixels, &decode_bc5(&encode_bc5(&pixels), false), 2) <= 16);
+        assert!(get_max_error(&pixels, &decode_bc7(&encode_bc7(&pixels)), 4) <= 6);
+        // Transparent pixels in BC1 use the three color mode
+        let mut cutout = opaque;
+        cutout[5][3] = 0;
+        let decoded = decode_bc1(&encode_bc1(&cutout));
+        assert_eq!(decoded[5], [0, 0, 0, 0]);
+        assert!(decoded.iter().enumerate().all(|(i, p)| i == 5 || p[3] == 255));
+        Ok(())
+    }
+}
@@ -0,0 +1,392 @@
+//! Decode DDS textures into RGBA8 surfaces without going through D3D, so that
+//! texture dictionaries can be previewed and exported on any platform.
+//!
+//! Supported pixel formats are BC1-5, BC7 and uncompressed formats that can be described with
+//! channel bit masks. DDS files can store these using either a FourCC, legacy bit masks or a
+//! DX10 header. Array textures and cubemaps are decoded, volume textures are not.
+//!
+//! RGBA8 images can also be encoded into BC1, BC3, BC4, BC5 or BC7 DDS files with a full mip
+//! chain.
+
+use crate::graphics::texture_codec::{
+    bc,
+    bc_encode,
+    surface::{
+        decode_blocks, encode_blocks, get_mip_size, get_surface_count, DecodedTexture, PixelMask,
+        Surface, TextureDecodeError, TextureEncodeError, MAX_MIP_COUNT
+    }
+};
+
+pub const DDS_MAGIC: u32 = 0x20534444;
+pub const DDS_HEADER_SIZE: usize = 0x80;
+pub const DDS_HEADER_DX10_SIZE: usize = 0x14;
+
+const DDPF_ALPHAPIXELS: u32 = 0x1;
+const DDPF_ALPHA: u32 = 0x2;
+const DDPF_FOURCC: u32 = 0x4;
+const DDPF_RGB: u32 = 0x40;
+const DDPF_LUMINANCE: u32 = 0x20000;
+const DDSCAPS2_CUBEMAP: u32 = 0x200;
+const DDSCAPS2_VOLUME: u32 = 0x200000;
+const DDS_RESOURCE_MISC_TEXTURECUBE: u32 = 0x4;
+const D3D10_RESOURCE_DIMENSION_TEXTURE2D: u32 = 3;
+const D3D10_RESOURCE_DIMENSION_TEXTURE3D: u32 = 4;
+const DDSD_CAPS: u32 = 0x1;
+const DDSD_HEIGHT: u32 = 0x2;
+const DDSD_WIDTH: u32 = 0x4;
+const DDSD_PIXELFORMAT: u32 = 0x1000;
+const DDSD_MIPMAPCOUNT: u32 = 0x20000;
+const DDSD_LINEARSIZE: u32 = 0x80000;
+const DDSCAPS_COMPLEX: u32 = 0x8;
+const DDSCAPS_TEXTURE: u32 = 0x1000;
+const DDSCAPS_MIPMAP: u32 = 0x400000;
+const DXGI_FORMAT_BC7_UNORM: u32 = 98;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DdsPixelFormat {
+    Bc1,
+    Bc2,
+    Bc3,
+    Bc4,
+    Bc4Signed,
+    Bc5,
+    Bc5Signed,
+    Bc7,
+    Uncompressed(PixelMask)
+}
+
+impl DdsPixelFormat {
+    fn from_fourcc(fourcc: &[u8]) -> Result<Self, TextureDecodeError> {
+        match fourcc {
+            b"DXT1" => Ok(Self::Bc1),
+            b"DXT2" | b"DXT3" => Ok(Self::Bc2),
+            b"DXT4" | b"DXT5" => Ok(Self::Bc3),
+            b"ATI1" | b"BC4U" => Ok(Self::Bc4),
+            b"BC4S" => Ok(Self::Bc4Signed),
+            b"ATI2" | b"BC5U" => Ok(Self::Bc5),
+            b"BC5S" => Ok(Self::Bc5Signed),
+            v => Err(TextureDecodeError::UnsupportedPixelFormat(format!("FourCC {}", String::from_utf8_lossy(v))))
+        }
+    }
+
+    fn from_dxgi(format: u32) -> Result<Self, TextureDecodeError> {
+        match format {
+            24 => Ok(Self::Uncompressed(PixelMask::new(32, 0x3ff, 0xffc00, 0x3ff00000, 0xc0000000))),
+            27..=29 => Ok(Self::Uncompressed(PixelMask::new(32, 0xff, 0xff00, 0xff0000, 0xff000000))),
+            48 | 49 => Ok(Self::Uncompressed(PixelMask::new(16, 0xff, 0xff00, 0, 0))),
+            60 | 61 => Ok(Self::Uncompressed(PixelMask::luminance(8, 0xff, 0))),
+            65 => Ok(Self::Uncompressed(PixelMask::new(8, 0, 0, 0, 0xff))),
+            70..=72 => Ok(Self::Bc1),
+            73..=75 => Ok(Self::Bc2),
+            76..=78 => Ok(Self::Bc3),
+            79 | 80 => Ok(Self::Bc4),
+            81 => Ok(Self::Bc4Signed),
+            82 | 83 => Ok(Self::Bc5),
+            84 => Ok(Self::Bc5Signed),
+            85 => Ok(Self::Uncompressed(PixelMask::new(16, 0xf800, 0x7e0, 0x1f, 0))),
+            86 => Ok(Self::Uncompressed(PixelMask::new(16, 0x7c00, 0x3e0, 0x1f, 0x8000))),
+            87 | 90 | 91 => Ok(Self::Uncompressed(PixelMask::new(32, 0xff0000, 0xff00, 0xff, 0xff000000))),
+            88 | 92 | 93 => Ok(Self::Uncompressed(PixelMask::new(32, 0xff0000, 0xff00, 0xff, 0))),
+            97..=99 => Ok(Self::Bc7),
+            115 => Ok(Self::Uncompressed(PixelMask::new(16, 0xf00, 0xf0, 0xf, 0xf000))),
+            v => Err(TextureDecodeError::UnsupportedPixelFormat(format!("DXGI format {}", v)))
+        }
+    }
+
+    /// Number of bytes in each 4x4 block, or None for uncompressed formats
+    pub fn get_block_size(&self) -> Option<usize> {
+        match self {
+            Self::Bc1 => Some(bc::BC1_BLOCK_SIZE),
+            Self::Bc2 => Some(bc::BC2_BLOCK_SIZE),
+            Self::Bc3 => Some(bc::BC3_BLOCK_SIZE),
+            Self::Bc4 | Self::Bc4Signed => Some(bc::BC4_BLOCK_SIZE),
+            Self::Bc5 | Self::Bc5Signed => Some(bc::BC5_BLOCK_SIZE),
+            Self::Bc7 => Some(bc::BC7_BLOCK_SIZE),
+            Self::Uncompressed(_) => None
+        }
+    }
+
+    /// Number of bytes used by a surface of the given size
+    pub fn get_surface_size(&self, width: u32, height: u32) -> usize {
+        match (self, self.get_block_size()) {
+            (_, Some(block)) => (width.div_ceil(4) as usize).saturating_mul(height.div_ceil(4) as usize).saturating_mul(block),
+            (Self::Uncompressed(mask), None) => (width as usize).saturating_mul(height as usize).saturating_mul(mask.bits as usize / 8),
+            _ => unreachable!()
+        }
+    }
+
+    /// Width and height in elements and the size of each element, where an element is a block for
+    /// compressed formats and a pixel otherwise. Used when removing console swizzling.
+    pub fn get_element_layout(&self, width: u32, height: u32) -> (u32, u32, usize) {
+        match (self, self.get_block_size()) {
+            (_, Some(block)) => (width.div_ceil(4), height.div_ceil(4), block),
+            (Self::Uncompressed(mask), None) => (width, height, mask.bits as usize / 8),
+            _ => unreachable!()
+        }
+    }
+
+    pub fn decode_surface(&self, data: &[u8], width: u32, height: u32) -> Result<Vec<u8>, TextureDecodeError> {
+        match self {
+            Self::Bc1 => decode_blocks(data, width, height, bc::BC1_BLOCK_SIZE, bc::decode_bc1),
+            Self::Bc2 => decode_blocks(data, width, height, bc::BC2_BLOCK_SIZE, bc::decode_bc2),
+            Self::Bc3 => decode_blocks(data, width, height, bc::BC3_BLOCK_SIZE, bc::decode_bc3),
+            Self::Bc4 => decode_blocks(data, width, height, bc::BC4_BLOCK_SIZE, |b| bc::decode_bc4(b, false)),
+            Self::Bc4Signed => decode_blocks(data, width, height, bc::BC4_BLOCK_SIZE, |b| bc::decode_bc4(b, true)),
+            Self::Bc5 => decode_blocks(data, width, height, bc::BC5_BLOCK_SIZE, |b| bc::decode_bc5(b, false)),
+            Self::Bc5Signed => decode_blocks(data, width, height, bc::BC5_BLOCK_SIZE, |b| bc::decode_bc5(b, true)),
+            Self::Bc7 => decode_blocks(data, width, height, bc::BC7_BLOCK_SIZE, bc::decode_bc7),
+            Self::Uncompressed(mask) => mask.decode(data, width, height)
+        }
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, TextureDecodeError> {
+    bytes.get(offset..offset + 4)
+        .map(|v| u32::from_le_bytes(v.try_into().unwrap()))
+        .ok_or(TextureDecodeError::UnexpectedEof)
+}
+
+/// The parts of a DDS header needed to find and decode each surface
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DdsHeader {
+    pub width: u32,
+    pub height: u32,
+    pub mip_count: u32,
+    /// Number of array layers, multiplied by six for cubemaps
+    pub layer_count: u32,
+    pub cubemap: bool,
+    pub format: DdsPixelFormat,
+    pub data_offset: usize
+}
+
+impl DdsHeader {
+    pub fn read(bytes: &[u8]) -> Result<Self, TextureDecodeError> {
+        let magic = read_u32(bytes, 0)?;
+        if magic != DDS_MAGIC {
+            return Err(TextureDecodeError::InvalidMagic(magic));
+        }
+        let height = read_u32(bytes, 0xc)?;
+        let width = read_u32(bytes, 0x10)?;
+        let mip_count = read_u32(bytes, 0x1c)?.clamp(1, MAX_MIP_COUNT);
+        let pf_flags = read_u32(bytes, 0x50)?;
+        let caps2 = read_u32(bytes, 0x70)?;
+        if pf_flags & DDPF_FOURCC != 0 && bytes[0x54..0x58] == *b"DX10" {
+            let format = DdsPixelFormat::from_dxgi(read_u32(bytes, 0x80)?)?;
+            if read_u32(bytes, 0x84)? == D3D10_RESOURCE_DIMENSION_TEXTURE3D {
+                return Err(TextureDecodeError::UnsupportedVolumeTexture);
+            }
+            let cubemap = read_u32(bytes, 0x88)? & DDS_RESOURCE_MISC_TEXTURECUBE != 0;
+            let array_size = read_u32(bytes, 0x8c)?.max(1);
+            let layer_count = array_size.checked_mul(if cubemap { 6 } else { 1 })
+                .ok_or(TextureDecodeError::InvalidSurfaceCount(array_size, mip_count))?;
+            return Ok(Self { width, height, mip_count, layer_count, cubemap, format,
+                data_offset: DDS_HEADER_SIZE + DDS_HEADER_DX10_SIZE });
+        }
+        if caps2 & DDSCAPS2_VOLUME != 0 {
+            return Err(TextureDecodeError::UnsupportedVolumeTexture);
+        }
+        let format = if pf_flags & DDPF_FOURCC != 0 {
+            DdsPixelFormat::from_fourcc(&bytes[0x54..0x58])?
+        } else if pf_flags & (DDPF_RGB | DDPF_LUMINANCE | DDPF_ALPHA) != 0 {
+            let alpha = match pf_flags & (DDPF_ALPHAPIXELS | DDPF_ALPHA) {
+                0 => 0,
+                _ => read_u32(bytes, 0x68)?
+            };
+            let mask = PixelMask {
+                bits: read_u32(bytes, 0x58)?,
+                r: read_u32(bytes, 0x5c)?,
+                g: read_u32(bytes, 0x60)?,
+                b: read_u32(bytes, 0x64)?,
+                a: alpha,
+                luminance: pf_flags & DDPF_LUMINANCE != 0
+            };
+            mask.get_stride()?;
+            DdsPixelFormat::Uncompressed(mask)
+        } else {
+            return Err(TextureDecodeError::UnsupportedPixelFormat(format!("pixel format flags 0x{:x}", pf_flags)));
+        };
+        let cubemap = caps2 & DDSCAPS2_CUBEMAP != 0;
+        Ok(Self { width, height, mip_count, layer_count: if cubemap { 6 } else { 1 },
+            cubemap, format, data_offset: DDS_HEADER_SIZE })
+    }
+}
+
+/// Decode every surface in a DDS file
+pub fn decode_dds(bytes: &[u8]) -> Result<DecodedTexture, TextureDecodeError> {
+    let header = DdsHeader::read(bytes)?;
+    let count = get_surface_count(header.layer_count, header.mip_count, bytes.len().saturating_sub(header.data_offset))?;
+    let mut surfaces = Vec::with_capacity(count);
+    let mut offset = header.data_offset;
+    for layer in 0..header.layer_count {
+        for mip in 0..header.mip_count {
+            let (width, height) = get_mip_size(header.width, header.height, mip);
+            let size = header.format.get_surface_size(width, height);
+            let data = offset.checked_add(size)
+                .and_then(|end| bytes.get(offset..end))
+                .ok_or(TextureDecodeError::UnexpectedEof)?;
+            surfaces.push(Surface::new(layer, mip, width, height, header.format.decode_surface(data, width, height)?));
+            offset += size;
+        }
+    }
+    Ok(DecodedTexture::new(header.width, header.height, header.mip_count, header.layer_count, header.cubemap, surfaces))
+}
+
+impl DdsPixelFormat {
+    fn encode_surface(&self, surface: &Surface) -> Result<Vec<u8>, TextureEncodeError> {
+        match self {
+            Self::Bc1 => Ok(encode_blocks(surface, bc_encode::encode_bc1)),
+            Self::Bc3 => Ok(encode_blocks(surface, bc_encode::encode_bc3)),
+            Self::Bc4 => Ok(encode_blocks(surface, bc_encode::encode_bc4)),
+            Self::Bc5 => Ok(encode_blocks(surface, bc_encode::encode_bc5)),
+            Self::Bc7 => Ok(encode_blocks(surface, bc_encode::encode_bc7)),
+            f => Err(TextureEncodeError::UnsupportedPixelFormat(format!("{:?}", f)))
+        }
+    }
+
+    /// The FourCC for the format, where BC7 is only available through the DX10 header
+    fn get_fourcc(&self) -> &'static [u8; 4] {
+        match self {
+            Self::Bc1 => b"DXT1",
+            Self::Bc3 => b"DXT5",
+            Self::Bc4 => b"ATI1",
+            Self::Bc5 => b"ATI2",
+            _ => b"DX10"
+        }
+    }
+}
+
+/// Encode an RGBA8 image in row order into a DDS file. When `mips` is set, the full mip chain down
+/// to 1x1 is generated with a box filter.
+pub fn encode_dds(width: u32, height: u32, data: &[u8], format: DdsPixelFormat, mips: bool) -> Result<Vec<u8>, TextureEncodeError> {
+    if width == 0 || height == 0 || data.len() < width as usize * height as usize * 4 {
+        return Err(TextureEncodeError::InvalidImageSize(width, height));
+    }
+    let mut surface = Surface::new(0, 0, width, height, data[..width as usize * height as usize * 4].to_vec());
+    let mip_count = match mips {
+        true => 32 - width.max(height).leading_zeros(),
+        false => 1
+    };
+    let mut levels = Vec::with_capacity(mip_count as usize);
+    for mip in 0..mip_count {
+        if mip > 0 {
+            surface = surface.downsample();
+        }
+        levels.push(format.encode_surface(&surface)?);
+    }
+    let fourcc = format.get_fourcc();
+    let mut out = Vec::with_capacity(DDS_HEADER_SIZE + DDS_HEADER_DX10_SIZE + levels.iter().map(|l| l.len()).sum::<usize>());
+    let mut flags = DDSD_CAPS | DDSD_HEIGHT | DDSD_WIDTH | DDSD_PIXELFORMAT | DDSD_LINEARSIZE;
+    let mut caps = DDSCAPS_TEXTURE;
+    if mip_count > 1 {
+        flags |= DDSD_MIPMAPCOUNT;
+        caps |= DDSCAPS_COMPLEX | DDSCAPS_MIPMAP;
+    }
+    for value in [DDS_MAGIC, 124, flags, height, width, levels[0].len() as u32, 0, mip_count] {
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+    out.resize(0x4c, 0);
+    for value in [32, DDPF_FOURCC] {
+        out.extend_from_slice(&u32::to_le_bytes(value));
+    }
+    out.extend_from_slice(fourcc);
+    out.resize(0x6c, 0);
+    out.extend_from_slice(&caps.to_le_bytes());
+    out.resize(DDS_HEADER_SIZE, 0);
+    if fourcc == b"DX10" {
+        for value in [DXGI_FORMAT_BC7_UNORM, D3D10_RESOURCE_DIMENSION_TEXTURE2D, 0, 1, 0] {
+            out.extend_from_slice(&u32::to_le_bytes(value));
+        }
+    }
+    levels.iter().for_each(|l| out.extend_from_slice(l));
+    Ok(out)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::{
+        graphics::texture::get_dummy_texture_data,
+        kernel::version::GameProfile
+    };
+    use std::error::Error;
+    use super::{ decode_dds, encode_dds, DdsHeader, DdsPixelFormat, DDS_MAGIC, MAX_MIP_COUNT, TextureDecodeError };
+
+    type TestReturn = Result<(), Box<dyn Error>>;
+
+    #[test]
+    fn decode_dummy_textures() -> TestReturn {
+        for profile in [GameProfile::MetaphorRefantazio, GameProfile::Persona5Royal] {
+            let texture = decode_dds(get_dummy_texture_data(profile))?;
+            assert_eq!((texture.get_width(), texture.get_height()), (8, 8));
+            let surface = texture.get_main_surface().unwrap();
+            assert_eq!(surface.get_pixel(0, 0), [33, 178, 74, 255]);
+            assert_eq!(surface.get_pixel(7, 7), [33, 178, 74, 255]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn decode_dx10_array_with_mips() -> TestReturn {
+        let mut bytes = vec![0u8; 0x94];
+        let mut set = |offset: usize, value: u32| bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+        set(0, DDS_MAGIC);
+        set(0xc, 2);
+        set(0x10, 2);
+        set(0x1c, 2);
+        set(0x50, 0x4);
+        set(0x54, u32::from_le_bytes(*b"DX10"));
+        set(0x80, 28); // R8G8B8A8_UNORM
+        set(0x84, 3);
+        set(0x8c, 2);
+        for layer in 0..2u8 {
+            // 2x2 mip then 1x1 mip
+            for _ in 0..5 {
+                bytes.extend_from_slice(&[layer, 0x80, 0xff, 0x40]);
+            }
+        }
+        let texture = decode_dds(&bytes)?;
+        assert_eq!(texture.get_surfaces().len(), 4);
+        assert_eq!(texture.get_layer_count(), 2);
+        let mip = texture.get_surface(1, 1).unwrap();
+        assert_eq!((mip.get_width(), mip.get_height()), (1, 1));
+        assert_eq!(mip.get_pixel(0, 0), [1, 0x80, 0xff, 0x40]);
+        assert!(decode_dds(&bytes[..bytes.len() - 1]).is_err());
+        // Array sizes that can't fit in the file are rejected before anything is allocated
+        bytes[0x8c..0x90].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(decode_dds(&bytes), Err(TextureDecodeError::InvalidSurfaceCount(_, _))));
+        Ok(())
+    }
+
+    #[test]
+    fn reject_invalid_pixel_masks() -> TestReturn {
+        let mut bytes = vec![0u8; 0x80];
+        let mut set = |offset: usize, value: u32| bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+        set(0, DDS_MAGIC);
+        set(0xc, 1);
+        set(0x10, 1);
+        set(0x1c, 100);
+        set(0x50, 0x40);
+        set(0x58, 0);
+        set(0x5c, 0xff);
+        assert!(matches!(DdsHeader::read(&bytes), Err(TextureDecodeError::UnsupportedPixelFormat(_))));
+        bytes[0x58] = 8;
+        let header = DdsHeader::read(&bytes)?;
+        assert_eq!(header.mip_count, MAX_MIP_COUNT);
+        Ok(())
+    }
+
+    #[test]
+    fn encode_dds_with_mips() -> TestReturn {
+        let pixels: Vec<u8> = (0..6 * 5).flat_map(|i| [i as u8 * 8, 0x80, 0x40, 0xff]).collect();
+        for format in [DdsPixelFormat::Bc1, DdsPixelFormat::Bc3, DdsPixelFormat::Bc5, DdsPixelFormat::Bc7] {
+            let texture = decode_dds(&encode_dds(6, 5, &pixels, format, true)?)?;
+            assert_eq!(texture.get_mip_count(), 3);
+            let last = texture.get_surface(0, 2).unwrap();
+            assert_eq!((last.get_width(), last.get_height()), (1, 1));
+            let main = texture.get_main_surface().unwrap();
+            assert!((main.get_pixel(5, 4)[0] as i32 - 232).abs() <= 12);
+            assert!((main.get_pixel(5, 4)[1] as i32 - 0x80).abs() <= 4);
+        }
+        assert!(encode_dds(6, 5, &pixels, DdsPixelFormat::Bc2, false).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,177 @@
+//! Decode GNF textures, which are used by PS4 games. A GNF file is a header
+//! followed by one or more GCN texture descriptors (T#) and the texture data.
+//!
+//! Linear textures and textures using 1D (8x8 thin micro) tiling are supported. 2D, 3D and PRT
+//! macro tiling depend on the GPU's bank and pipe configuration, so they're rejected. Channels are remapped using the
+//! descriptor's destination select. Only the base level of the first texture is decoded.
+
+use crate::graphics::texture_codec::{
+    dds::DdsPixelFormat,
+    surface::{ DecodedTexture, PixelMask, Surface, TextureDecodeError },
+    swizzle::untile_ps4
+};
+
+pub const GNF_MAGIC: u32 = 0x20464E47;
+pub const GNF_HEADER_SIZE: usize = 0x10;
+
+const GNF_TILE_MODE_DISPLAY_LINEAR_ALIGNED: u32 = 8;
+const GNF_TILE_MODE_DISPLAY_1D_THIN: u32 = 9;
+const GNF_TILE_MODE_THIN_1D_THIN: u32 = 13;
+const GNF_TILE_MODE_DISPLAY_LINEAR_GENERAL: u32 = 27;
+const GNF_NUM_FORMAT_SNORM: u32 = 1;
+
+/// Where each output channel is read from, as written in a T#
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GnfChannel {
+    Zero,
+    One,
+    X,
+    Y,
+    Z,
+    W
+}
+
+impl GnfChannel {
+    fn from_select(value: u32) -> Self {
+        match value {
+            1 => Self::One,
+            4 => Self::X,
+            5 => Self::Y,
+            6 => Self::Z,
+            7 => Self::W,
+            _ => Self::Zero
+        }
+    }
+
+    fn get(&self, pixel: &[u8]) -> u8 {
+        match self {
+            Self::Zero => 0,
+            Self::One => u8::MAX,
+            Self::X => pixel[0],
+            Self::Y => pixel[1],
+            Self::Z => pixel[2],
+            Self::W => pixel[3]
+        }
+    }
+}
+
+fn get_pixel_format(data_format: u32, num_format: u32) -> Result<DdsPixelFormat, TextureDecodeError> {
+    let signed = num_format == GNF_NUM_FORMAT_SNORM;
+    match data_format {
+        1 => Ok(DdsPixelFormat::Uncompressed(PixelMask::new(8, 0xff, 0, 0, 0))),
+        3 => Ok(DdsPixelFormat::Uncompressed(PixelMask::new(16, 0xff, 0xff00, 0, 0))),
+        10 => Ok(DdsPixelFormat::Uncompressed(PixelMask::new(32, 0xff, 0xff00, 0xff0000, 0xff000000))),
+        16 => Ok(DdsPixelFormat::Uncompressed(PixelMask::new(16, 0x1f, 0x7e0, 0xf800, 0))),
+        17 => Ok(DdsPixelFormat::Uncompressed(PixelMask::new(16, 0x1f, 0x3e0, 0x7c00, 0x8000))),
+        19 => Ok(DdsPixelFormat::Uncompressed(PixelMask::new(16, 0xf, 0xf0, 0xf00, 0xf000))),
+        35 => Ok(DdsPixelFormat::Bc1),
+        36 => Ok(DdsPixelFormat::Bc2),
+        37 => Ok(DdsPixelFormat::Bc3),
+        38 => Ok(if signed { DdsPixelFormat::Bc4Signed } else { DdsPixelFormat::Bc4 }),
+        39 => Ok(if signed { DdsPixelFormat::Bc5Signed } else { DdsPixelFormat::Bc5 }),
+        41 => Ok(DdsPixelFormat::Bc7),
+        v => Err(TextureDecodeError::UnsupportedPixelFormat(format!("GNF data format {}", v)))
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, TextureDecodeError> {
+    bytes.get(offset..offset + 4)
+        .map(|v| u32::from_le_bytes(v.try_into().unwrap()))
+        .ok_or(TextureDecodeError::UnexpectedEof)
+}
+
+/// The parts of the first T# needed to find and decode the base level
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GnfHeader {
+    pub texture_count: u32,
+    pub data_offset: usize,
+    pub format: DdsPixelFormat,
+    pub width: u32,
+    pub height: u32,
+    pub pitch: u32,
+    pub tile_mode: u32,
+    pub channels: [GnfChannel; 4]
+}
+
+impl GnfHeader {
+    pub fn read(bytes: &[u8]) -> Result<Self, TextureDecodeError> {
+        let magic = read_u32(bytes, 0)?;
+        if magic != GNF_MAGIC {
+            return Err(TextureDecodeError::InvalidMagic(magic));
+        }
+        let contents_size = read_u32(bytes, 4)? as usize;
+        let texture_count = *bytes.get(9).ok_or(TextureDecodeError::UnexpectedEof)? as u32;
+        let t = GNF_HEADER_SIZE;
+        let (dword0, dword1, dword2) = (read_u32(bytes, t)?, read_u32(bytes, t + 4)?, read_u32(bytes, t + 8)?);
+        let (dword3, dword4) = (read_u32(bytes, t + 0xc)?, read_u32(bytes, t + 0x10)?);
+        Ok(Self {
+            texture_count,
+            data_offset: 8 + contents_size + ((dword0 as usize) << 8),
+            format: get_pixel_format(dword1 >> 20 & 0x3f, dword1 >> 26 & 0xf)?,
+            width: (dword2 & 0x3fff) + 1,
+            height: (dword2 >> 14 & 0x3fff) + 1,
+            pitch: (dword4 >> 13 & 0x3fff) + 1,
+            tile_mode: dword3 >> 20 & 0x1f,
+            channels: std::array::from_fn(|i| GnfChannel::from_select(dword3 >> (3 * i) & 7))
+        })
+    }
+}
+
+/// Decode the base level of the first texture in a GNF file
+pub fn decode_gnf(bytes: &[u8]) -> Result<DecodedTexture, TextureDecodeError> {
+    let header = GnfHeader::read(bytes)?;
+    let (width, height) = (header.width, header.height);
+    let (elements_x, elements_y, element_size) = header.format.get_element_layout(width, height);
+    let pitch = match header.format.get_block_size() {
+        Some(_) => header.pitch.div_ceil(4),
+        None => header.pitch
+    }.max(elements_x);
+    let data = bytes.get(header.data_offset..).ok_or(TextureDecodeError::UnexpectedEof)?;
+    let packed = match header.tile_mode {
+        GNF_TILE_MODE_DISPLAY_LINEAR_ALIGNED | GNF_TILE_MODE_DISPLAY_LINEAR_GENERAL => {
+            let row = elements_x as usize * element_size;
+            let stride = pitch as usize * element_size;
+            let mut packed = Vec::with_capacity(row * elements_y as usize);
+            for y in 0..elements_y as usize {
+                packed.extend_from_slice(data.get(y * stride..y * stride + row).ok_or(TextureDecodeError::UnexpectedEof)?);
+            }
+            packed
+        },
+        GNF_TILE_MODE_DISPLAY_1D_THIN | GNF_TILE_MODE_THIN_1D_THIN => untile_ps4(data, elements_x, elements_y, pitch, element_size)?,
+        v => return Err(TextureDecodeError::UnsupportedTiling(v))
+    };
+    let pixels = header.format.decode_surface(&packed, width, height)?;
+    let pixels = pixels.chunks_exact(4).flat_map(|p| header.channels.map(|c| c.get(p))).collect();
+    Ok(DecodedTexture::new(width, height, 1, 1, false, vec![Surface::new(0, 0, width, height, pixels)]))
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::error::Error;
+    use super::{ decode_gnf, GNF_HEADER_SIZE, TextureDecodeError };
+
+    type TestReturn = Result<(), Box<dyn Error>>;
+
+    #[test]
+    fn decode_linear_gnf() -> TestReturn {
+        // 2x1 B8G8R8A8 stored as 8_8_8_8 with a ZYXW destination select
+        let mut bytes = vec![0u8; GNF_HEADER_SIZE + 0x20];
+        bytes[0..4].copy_from_slice(b"GNF ");
+        bytes[4..8].copy_from_slice(&0x28u32.to_le_bytes());
+        bytes[9] = 1;
+        let t = GNF_HEADER_SIZE;
+        bytes[t + 4..t + 8].copy_from_slice(&(10u32 << 20).to_le_bytes());
+        bytes[t + 8..t + 0xc].copy_from_slice(&1u32.to_le_bytes());
+        bytes[t + 0xc..t + 0x10].copy_from_slice(&(6u32 | 5 << 3 | 4 << 6 | 7 << 9 | 8 << 20).to_le_bytes());
+        bytes[t + 0x10..t + 0x14].copy_from_slice(&(1u32 << 13).to_le_bytes());
+        bytes.extend([1, 2, 3, 4, 5, 6, 7, 8]);
+        let texture = decode_gnf(&bytes)?;
+        let surface = texture.get_main_surface().unwrap();
+        assert_eq!(surface.get_pixel(0, 0), [3, 2, 1, 4]);
+        assert_eq!(surface.get_pixel(1, 0), [7, 6, 5, 8]);
+        // Thin 2D macro tiling
+        bytes[t + 0xc..t + 0x10].copy_from_slice(&(6u32 | 5 << 3 | 4 << 6 | 7 << 9 | 14 << 20).to_le_bytes());
+        assert_eq!(decode_gnf(&bytes), Err(TextureDecodeError::UnsupportedTiling(14)));
+        Ok(())
+    }
+}
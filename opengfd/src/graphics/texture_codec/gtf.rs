@@ -0,0 +1,168 @@
+//! Decode GTF textures, which are used by PS3 games. GTF is a thin wrapper
+//! around the RSX's CellGcmTexture description, so all values are big endian.
+//!
+//! Uncompressed textures are Morton ordered unless they have the linear flag set. DXT textures are
+//! always stored in row order. Only the first texture in a file is decoded.
+
+use crate::graphics::texture_codec::{
+    bc,
+    surface::{ decode_blocks, get_mip_size, get_surface_count, DecodedTexture, PixelMask, Surface, TextureDecodeError, MAX_MIP_COUNT },
+    swizzle::unswizzle_morton
+};
+
+pub const GTF_HEADER_SIZE: usize = 0xc;
+pub const GTF_ATTRIBUTE_SIZE: usize = 0x24;
+
+const CELL_GCM_TEXTURE_LN: u8 = 0x20;
+const CELL_GCM_TEXTURE_UN: u8 = 0x40;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum GtfPixelFormat {
+    B8 = 0x81,
+    A1R5G5B5 = 0x82,
+    A4R4G4B4 = 0x83,
+    R5G6B5 = 0x84,
+    A8R8G8B8 = 0x85,
+    DXT1 = 0x86,
+    DXT23 = 0x87,
+    DXT45 = 0x88
+}
+
+impl TryFrom<u8> for GtfPixelFormat {
+    type Error = TextureDecodeError;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value & !(CELL_GCM_TEXTURE_LN | CELL_GCM_TEXTURE_UN) {
+            0x81 => Ok(Self::B8),
+            0x82 => Ok(Self::A1R5G5B5),
+            0x83 => Ok(Self::A4R4G4B4),
+            0x84 => Ok(Self::R5G6B5),
+            0x85 => Ok(Self::A8R8G8B8),
+            0x86 => Ok(Self::DXT1),
+            0x87 => Ok(Self::DXT23),
+            0x88 => Ok(Self::DXT45),
+            v => Err(TextureDecodeError::UnsupportedPixelFormat(format!("GCM format 0x{:x}", v)))
+        }
+    }
+}
+
+impl GtfPixelFormat {
+    fn get_mask(&self) -> Option<PixelMask> {
+        // 16-bit formats are byte swapped before decoding, A8R8G8B8 is read as-is
+        match self {
+            Self::B8 => Some(PixelMask::luminance(8, 0xff, 0)),
+            Self::A1R5G5B5 => Some(PixelMask::new(16, 0x7c00, 0x3e0, 0x1f, 0x8000)),
+            Self::A4R4G4B4 => Some(PixelMask::new(16, 0xf00, 0xf0, 0xf, 0xf000)),
+            Self::R5G6B5 => Some(PixelMask::new(16, 0xf800, 0x7e0, 0x1f, 0)),
+            Self::A8R8G8B8 => Some(PixelMask::new(32, 0xff00, 0xff0000, 0xff000000, 0xff)),
+            _ => None
+        }
+    }
+
+    pub fn get_surface_size(&self, width: u32, height: u32) -> usize {
+        let blocks = width.div_ceil(4) as usize * height.div_ceil(4) as usize;
+        match (self, self.get_mask()) {
+            (Self::DXT1, _) => blocks * bc::BC1_BLOCK_SIZE,
+            (Self::DXT23 | Self::DXT45, _) => blocks * bc::BC2_BLOCK_SIZE,
+            (_, Some(mask)) => width as usize * height as usize * mask.bits as usize / 8,
+            _ => unreachable!()
+        }
+    }
+
+    pub fn decode_surface(&self, data: &[u8], width: u32, height: u32, linear: bool) -> Result<Vec<u8>, TextureDecodeError> {
+        match (self, self.get_mask()) {
+            (Self::DXT1, _) => decode_blocks(data, width, height, bc::BC1_BLOCK_SIZE, bc::decode_bc1),
+            (Self::DXT23, _) => decode_blocks(data, width, height, bc::BC2_BLOCK_SIZE, bc::decode_bc2),
+            (Self::DXT45, _) => decode_blocks(data, width, height, bc::BC3_BLOCK_SIZE, bc::decode_bc3),
+            (_, Some(mask)) => {
+                let element_size = mask.bits as usize / 8;
+                let mut data = match linear {
+                    true => data.to_vec(),
+                    false => unswizzle_morton(data, width, height, element_size)?
+                };
+                if element_size == 2 {
+                    data.chunks_exact_mut(2).for_each(|v| v.swap(0, 1));
+                }
+                mask.decode(&data, width, height)
+            },
+            _ => unreachable!()
+        }
+    }
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Result<u16, TextureDecodeError> {
+    bytes.get(offset..offset + 2)
+        .map(|v| u16::from_be_bytes(v.try_into().unwrap()))
+        .ok_or(TextureDecodeError::UnexpectedEof)
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, TextureDecodeError> {
+    bytes.get(offset..offset + 4)
+        .map(|v| u32::from_be_bytes(v.try_into().unwrap()))
+        .ok_or(TextureDecodeError::UnexpectedEof)
+}
+
+/// The first texture attribute in a GTF file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GtfHeader {
+    pub version: u32,
+    pub texture_count: u32,
+    pub data_offset: usize,
+    pub data_size: usize,
+    pub format: GtfPixelFormat,
+    pub linear: bool,
+    pub mip_count: u32,
+    pub cubemap: bool,
+    pub width: u32,
+    pub height: u32,
+    pub pitch: u32
+}
+
+impl GtfHeader {
+    pub fn read(bytes: &[u8]) -> Result<Self, TextureDecodeError> {
+        let version = read_u32(bytes, 0)?;
+        let texture_count = read_u32(bytes, 8)?;
+        if texture_count == 0 {
+            return Err(TextureDecodeError::UnexpectedEof);
+        }
+        let attr = GTF_HEADER_SIZE;
+        let format_raw = *bytes.get(attr + 0xc).ok_or(TextureDecodeError::UnexpectedEof)?;
+        if bytes[attr + 0xe] == 3 {
+            return Err(TextureDecodeError::UnsupportedVolumeTexture);
+        }
+        Ok(Self {
+            version,
+            texture_count,
+            data_offset: read_u32(bytes, attr + 4)? as usize,
+            data_size: read_u32(bytes, attr + 8)? as usize,
+            format: format_raw.try_into()?,
+            linear: format_raw & CELL_GCM_TEXTURE_LN != 0,
+            mip_count: (bytes[attr + 0xd] as u32).clamp(1, MAX_MIP_COUNT),
+            cubemap: bytes[attr + 0xf] != 0,
+            width: read_u16(bytes, attr + 0x14)? as u32,
+            height: read_u16(bytes, attr + 0x16)? as u32,
+            pitch: read_u32(bytes, attr + 0x1c)?
+        })
+    }
+}
+
+/// Decode every face and mip level of the first texture in a GTF file
+pub fn decode_gtf(bytes: &[u8]) -> Result<DecodedTexture, TextureDecodeError> {
+    let header = GtfHeader::read(bytes)?;
+    let layer_count = if header.cubemap { 6 } else { 1 };
+    let count = get_surface_count(layer_count, header.mip_count, bytes.len().saturating_sub(header.data_offset))?;
+    let mut surfaces = Vec::with_capacity(count);
+    let mut offset = header.data_offset;
+    for layer in 0..layer_count {
+        for mip in 0..header.mip_count {
+            let (width, height) = get_mip_size(header.width, header.height, mip);
+            let size = header.format.get_surface_size(width, height);
+            let data = offset.checked_add(size)
+                .and_then(|end| bytes.get(offset..end))
+                .ok_or(TextureDecodeError::UnexpectedEof)?;
+            surfaces.push(Surface::new(layer, mip, width, height, header.format.decode_surface(data, width, height, header.linear)?));
+            offset += size;
+        }
+    }
+    Ok(DecodedTexture::new(header.width, header.height, header.mip_count, layer_count, header.cubemap, surfaces))
+}
@@ -0,0 +1,235 @@
+//! Decode GXT textures, which are used by Vita games.
+//!
+//! Only version 3 of the container is supported. Swizzled and cube textures are stored in Morton
+//! order, while tiled textures aren't supported. Only the base level of the first texture and
+//! face is decoded.
+
+use crate::graphics::texture_codec::{
+    dds::DdsPixelFormat,
+    pvr::decode_pvrtc,
+    surface::{ DecodedTexture, PixelMask, Surface, TextureDecodeError },
+    swizzle::unswizzle_morton
+};
+
+pub const GXT_MAGIC: u32 = 0x00545847;
+pub const GXT_VERSION: u32 = 0x10000003;
+pub const GXT_HEADER_SIZE: usize = 0x20;
+pub const GXT_TEXTURE_INFO_SIZE: usize = 0x20;
+
+const P4_PALETTE_SIZE: usize = 16 * 4;
+const P8_PALETTE_SIZE: usize = 256 * 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum GxtTextureType {
+    Swizzled = 0x00000000,
+    Cube = 0x40000000,
+    Linear = 0x60000000,
+    SwizzledArbitrary = 0xA0000000,
+    LinearStrided = 0x0C000000
+}
+
+impl TryFrom<u32> for GxtTextureType {
+    type Error = TextureDecodeError;
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            0x00000000 => Ok(Self::Swizzled),
+            0x40000000 => Ok(Self::Cube),
+            0x60000000 => Ok(Self::Linear),
+            0xA0000000 => Ok(Self::SwizzledArbitrary),
+            0x0C000000 => Ok(Self::LinearStrided),
+            v => Err(TextureDecodeError::UnsupportedTiling(v))
+        }
+    }
+}
+
+impl GxtTextureType {
+    pub fn is_swizzled(&self) -> bool {
+        matches!(self, Self::Swizzled | Self::Cube | Self::SwizzledArbitrary)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GxtPixelFormat {
+    Pvrtc2Bpp,
+    Pvrtc4Bpp,
+    Dds(DdsPixelFormat),
+    /// 4-bit palette index, with the palette's channel mask
+    P4(PixelMask),
+    /// 8-bit palette index, with the palette's channel mask
+    P8(PixelMask)
+}
+
+impl GxtPixelFormat {
+    /// Build a mask from the channel order, starting from the least significant bits. `x` is
+    /// padding that reads as opaque.
+    fn get_mask(order: &[u8], width: fn(u8) -> u32) -> PixelMask {
+        let mut mask = PixelMask::new(0, 0, 0, 0, 0);
+        for channel in order {
+            let bits = width(*channel);
+            let value = (((1u64 << bits) - 1) as u32) << mask.bits;
+            match channel {
+                b'r' => mask.r = value,
+                b'g' => mask.g = value,
+                b'b' => mask.b = value,
+                b'a' => mask.a = value,
+                _ => ()
+            };
+            mask.bits += bits;
+        }
+        mask
+    }
+
+    fn get_color_order(swizzle: u32) -> Result<&'static [u8], TextureDecodeError> {
+        match swizzle {
+            0x0000 => Ok(b"rgba"),
+            0x1000 => Ok(b"bgra"),
+            0x2000 => Ok(b"abgr"),
+            0x3000 => Ok(b"argb"),
+            0x4000 => Ok(b"rgbx"),
+            0x5000 => Ok(b"bgrx"),
+            v => Err(TextureDecodeError::UnsupportedPixelFormat(format!("GXM swizzle 0x{:x}", v)))
+        }
+    }
+
+    pub fn from_gxm(format: u32) -> Result<Self, TextureDecodeError> {
+        let (base, swizzle) = (format & 0xff000000, format & 0xf000);
+        let rgba8 = |c| Ok::<_, TextureDecodeError>(Self::get_mask(Self::get_color_order(c)?, |_| 8));
+        match base {
+            // U8
+            0x00000000 => Ok(Self::Dds(DdsPixelFormat::Uncompressed(match swizzle {
+                0x2000 => PixelMask::luminance(8, 0xff, 0xff),
+                0x3000 => PixelMask::new(8, 0, 0, 0, 0xff),
+                0x1000 | 0x5000 => PixelMask::luminance(8, 0xff, 0),
+                _ => PixelMask::new(8, 0xff, 0, 0, 0)
+            }))),
+            // U4U4U4U4
+            0x02000000 => Ok(Self::Dds(DdsPixelFormat::Uncompressed(Self::get_mask(Self::get_color_order(swizzle)?, |_| 4)))),
+            // U1U5U5U5
+            0x04000000 => Ok(Self::Dds(DdsPixelFormat::Uncompressed(Self::get_mask(Self::get_color_order(swizzle)?,
+                |c| if c == b'a' || c == b'x' { 1 } else { 5 })))),
+            // U5U6U5
+            0x05000000 => {
+                let order: &[u8] = if swizzle == 0x1000 { b"bgr" } else { b"rgb" };
+                Ok(Self::Dds(DdsPixelFormat::Uncompressed(Self::get_mask(order, |c| if c == b'g' { 6 } else { 5 }))))
+            },
+            // U8U8U8U8
+            0x0C000000 => Ok(Self::Dds(DdsPixelFormat::Uncompressed(rgba8(swizzle)?))),
+            0x80000000 => Ok(Self::Pvrtc2Bpp),
+            0x81000000 => Ok(Self::Pvrtc4Bpp),
+            0x85000000 => Ok(Self::Dds(DdsPixelFormat::Bc1)),
+            0x86000000 => Ok(Self::Dds(DdsPixelFormat::Bc2)),
+            0x87000000 => Ok(Self::Dds(DdsPixelFormat::Bc3)),
+            0x88000000 => Ok(Self::Dds(DdsPixelFormat::Bc4)),
+            0x89000000 => Ok(Self::Dds(DdsPixelFormat::Bc4Signed)),
+            0x8A000000 => Ok(Self::Dds(DdsPixelFormat::Bc5)),
+            0x8B000000 => Ok(Self::Dds(DdsPixelFormat::Bc5Signed)),
+            0x94000000 => Ok(Self::P4(rgba8(swizzle)?)),
+            0x95000000 => Ok(Self::P8(rgba8(swizzle)?)),
+            v => Err(TextureDecodeError::UnsupportedPixelFormat(format!("GXM format 0x{:x}", v)))
+        }
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, TextureDecodeError> {
+    bytes.get(offset..offset + 4)
+        .map(|v| u32::from_le_bytes(v.try_into().unwrap()))
+        .ok_or(TextureDecodeError::UnexpectedEof)
+}
+
+/// The container header and the first texture's info
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GxtHeader {
+    pub texture_count: u32,
+    pub data_offset: usize,
+    pub data_size: usize,
+    pub p4_palette_count: u32,
+    pub p8_palette_count: u32,
+    pub texture_offset: usize,
+    pub texture_size: usize,
+    pub palette_index: u32,
+    pub texture_type: GxtTextureType,
+    pub format: GxtPixelFormat,
+    pub width: u32,
+    pub height: u32,
+    pub mip_count: u32
+}
+
+impl GxtHeader {
+    pub fn read(bytes: &[u8]) -> Result<Self, TextureDecodeError> {
+        let magic = read_u32(bytes, 0)?;
+        if magic != GXT_MAGIC {
+            return Err(TextureDecodeError::InvalidMagic(magic));
+        }
+        let version = read_u32(bytes, 4)?;
+        if version != GXT_VERSION {
+            return Err(TextureDecodeError::UnsupportedVersion(version));
+        }
+        let info = GXT_HEADER_SIZE;
+        let size = read_u32(bytes, info + 0x18)?;
+        Ok(Self {
+            texture_count: read_u32(bytes, 8)?,
+            data_offset: read_u32(bytes, 0xc)? as usize,
+            data_size: read_u32(bytes, 0x10)? as usize,
+            p4_palette_count: read_u32(bytes, 0x14)?,
+            p8_palette_count: read_u32(bytes, 0x18)?,
+            texture_offset: read_u32(bytes, info)? as usize,
+            texture_size: read_u32(bytes, info + 4)? as usize,
+            palette_index: read_u32(bytes, info + 8)?,
+            texture_type: read_u32(bytes, info + 0x10)?.try_into()?,
+            format: GxtPixelFormat::from_gxm(read_u32(bytes, info + 0x14)?)?,
+            width: size & 0xffff,
+            height: size >> 16,
+            mip_count: (*bytes.get(info + 0x1c).ok_or(TextureDecodeError::UnexpectedEof)? as u32).max(1)
+        })
+    }
+
+    /// Palettes are stored at the end of the texture data, with every 4-bit palette before every
+    /// 8-bit palette
+    fn get_palette(&self, bytes: &[u8], mask: &PixelMask, p8: bool) -> Result<Vec<u8>, TextureDecodeError> {
+        let palettes = (self.data_offset + self.data_size)
+            .checked_sub(self.p4_palette_count as usize * P4_PALETTE_SIZE + self.p8_palette_count as usize * P8_PALETTE_SIZE)
+            .ok_or(TextureDecodeError::UnexpectedEof)?;
+        let (offset, length) = match p8 {
+            true => (palettes + self.p4_palette_count as usize * P4_PALETTE_SIZE + self.palette_index as usize * P8_PALETTE_SIZE, 256),
+            false => (palettes + self.palette_index as usize * P4_PALETTE_SIZE, 16)
+        };
+        let data = bytes.get(offset..offset + length * 4).ok_or(TextureDecodeError::UnexpectedEof)?;
+        mask.decode(data, length as u32, 1)
+    }
+}
+
+/// Decode the base level of the first texture in a GXT file
+pub fn decode_gxt(bytes: &[u8]) -> Result<DecodedTexture, TextureDecodeError> {
+    let header = GxtHeader::read(bytes)?;
+    let (width, height) = (header.width, header.height);
+    let data = bytes.get(header.texture_offset..header.texture_offset + header.texture_size)
+        .ok_or(TextureDecodeError::UnexpectedEof)?;
+    let swizzled = header.texture_type.is_swizzled();
+    let pixels = match header.format {
+        GxtPixelFormat::Pvrtc2Bpp => decode_pvrtc(data, width, height, true)?,
+        GxtPixelFormat::Pvrtc4Bpp => decode_pvrtc(data, width, height, false)?,
+        GxtPixelFormat::Dds(format) => {
+            let (elements_x, elements_y, element_size) = format.get_element_layout(width, height);
+            match swizzled {
+                true => format.decode_surface(&unswizzle_morton(data, elements_x, elements_y, element_size)?, width, height)?,
+                false => format.decode_surface(data, width, height)?
+            }
+        },
+        GxtPixelFormat::P4(mask) | GxtPixelFormat::P8(mask) => {
+            let p8 = matches!(header.format, GxtPixelFormat::P8(_));
+            let palette = header.get_palette(bytes, &mask, p8)?;
+            let count = width as usize * height as usize;
+            let mut indices: Vec<u8> = match p8 {
+                true => data.get(..count).ok_or(TextureDecodeError::UnexpectedEof)?.to_vec(),
+                false => data.get(..count.div_ceil(2)).ok_or(TextureDecodeError::UnexpectedEof)?
+                    .iter().flat_map(|v| [v & 0xf, v >> 4]).take(count).collect()
+            };
+            if swizzled {
+                indices = unswizzle_morton(&indices, width, height, 1)?;
+            }
+            indices.iter().flat_map(|i| palette[*i as usize * 4..*i as usize * 4 + 4].to_vec()).collect()
+        }
+    };
+    Ok(DecodedTexture::new(width, height, 1, 1, false, vec![Surface::new(0, 0, width, height, pixels)]))
+}
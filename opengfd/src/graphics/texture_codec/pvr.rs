@@ -0,0 +1,336 @@
+//! Decode PVR textures, which are used by mobile and Vita builds. PVRTC data
+//! is decoded here, other compressed and uncompressed formats are shared with DDS.
+//!
+//! Only version 3 of the PVR container is supported. PVRTC-II is not supported.
+
+use crate::graphics::texture_codec::{
+    dds::DdsPixelFormat,
+    surface::{ get_mip_size, get_surface_count, DecodedTexture, PixelMask, Surface, TextureDecodeError, MAX_MIP_COUNT },
+    swizzle::morton_index
+};
+
+pub const PVR_MAGIC: u32 = 0x03525650;
+pub const PVR_HEADER_SIZE: usize = 0x34;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PvrPixelFormat {
+    Pvrtc2Bpp,
+    Pvrtc4Bpp,
+    Dds(DdsPixelFormat)
+}
+
+impl PvrPixelFormat {
+    fn from_compressed(id: u32) -> Result<Self, TextureDecodeError> {
+        match id {
+            0 | 1 => Ok(Self::Pvrtc2Bpp),
+            2 | 3 => Ok(Self::Pvrtc4Bpp),
+            7 => Ok(Self::Dds(DdsPixelFormat::Bc1)),
+            8 | 9 => Ok(Self::Dds(DdsPixelFormat::Bc2)),
+            10 | 11 => Ok(Self::Dds(DdsPixelFormat::Bc3)),
+            12 => Ok(Self::Dds(DdsPixelFormat::Bc4)),
+            13 => Ok(Self::Dds(DdsPixelFormat::Bc5)),
+            15 => Ok(Self::Dds(DdsPixelFormat::Bc7)),
+            v => Err(TextureDecodeError::UnsupportedPixelFormat(format!("PVR compressed format {}", v)))
+        }
+    }
+
+    /// Uncompressed formats name up to four channels and the number of bits in each. Packed
+    /// formats store the first channel in the most significant bits, while formats made of whole
+    /// bytes store the first channel in the first byte.
+    fn from_channels(names: [u8; 4], bits: [u8; 4]) -> Result<Self, TextureDecodeError> {
+        let total: u32 = bits.iter().map(|b| *b as u32).sum();
+        let bytes = bits.iter().all(|b| *b == 0 || *b == 8);
+        if total == 0 || total > 32 || total & 7 != 0 {
+            return Err(TextureDecodeError::UnsupportedPixelFormat(format!("PVR channels {:?} {:?}", names, bits)));
+        }
+        let mut mask = PixelMask::new(total, 0, 0, 0, 0);
+        let mut shift = match bytes { true => 0, false => total };
+        for (name, bits) in names.iter().zip(bits.iter()).filter(|(_, b)| **b != 0) {
+            let bits = *bits as u32;
+            if !bytes { shift -= bits; }
+            let channel = (((1u64 << bits) - 1) as u32) << shift;
+            match name {
+                b'r' => mask.r = channel,
+                b'g' => mask.g = channel,
+                b'b' => mask.b = channel,
+                b'a' => mask.a = channel,
+                b'l' | b'i' => { mask.r = channel; mask.luminance = true },
+                _ => ()
+            };
+            if bytes { shift += bits; }
+        }
+        Ok(Self::Dds(DdsPixelFormat::Uncompressed(mask)))
+    }
+
+    pub fn get_surface_size(&self, width: u32, height: u32) -> usize {
+        match self {
+            Self::Pvrtc2Bpp => (width.max(16) as usize).saturating_mul(height.max(8) as usize) / 4,
+            Self::Pvrtc4Bpp => (width.max(8) as usize).saturating_mul(height.max(8) as usize) / 2,
+            Self::Dds(f) => f.get_surface_size(width, height)
+        }
+    }
+
+    pub fn decode_surface(&self, data: &[u8], width: u32, height: u32) -> Result<Vec<u8>, TextureDecodeError> {
+        match self {
+            Self::Pvrtc2Bpp => decode_pvrtc(data, width, height, true),
+            Self::Pvrtc4Bpp => decode_pvrtc(data, width, height, false),
+            Self::Dds(f) => f.decode_surface(data, width, height)
+        }
+    }
+}
+
+/// Expand one of the two 16-bit PVRTC endpoint colors to RGBA8. Opaque colors (top bit set) are
+/// RGB555, or RGB554 for color A. Translucent colors are ARGB3444, or ARGB3443 for color A. Color
+/// A also gives up the lowest bit of blue to the modulation mode.
+fn get_pvrtc_color(value: u32, color_a: bool) -> [i32; 4] {
+    let expand = |v: u32, bits: u32| (v * 255 / ((1 << bits) - 1)) as i32;
+    let shift = color_a as u32;
+    match value & 0x8000 != 0 {
+        true => [
+            expand(value >> 10 & 0x1f, 5),
+            expand(value >> 5 & 0x1f, 5),
+            expand(value >> shift & (0x1f >> shift), 5 - shift),
+            255
+        ],
+        false => [
+            expand(value >> 8 & 0xf, 4),
+            expand(value >> 4 & 0xf, 4),
+            expand(value >> shift & (0xf >> shift), 4 - shift),
+            expand(value >> 12 & 0x7, 3)
+        ]
+    }
+}
+
+/// Decode a PVRTC1 surface. Blocks are twiddled with the y coordinate in the lowest bit, and
+/// colors are bilinearly interpolated between the four nearest blocks.
+pub fn decode_pvrtc(data: &[u8], width: u32, height: u32, bpp2: bool) -> Result<Vec<u8>, TextureDecodeError> {
+    const WEIGHTS: [i32; 4] = [0, 3, 5, 8];
+    let block_width = if bpp2 { 8 } else { 4 };
+    let blocks_x = (width.max(if bpp2 { 16 } else { 8 }) / block_width) as usize;
+    let blocks_y = (height.max(8) / 4) as usize;
+    if data.len() < blocks_x * blocks_y * 8 {
+        return Err(TextureDecodeError::UnexpectedEof);
+    }
+    let (full_w, full_h) = (blocks_x * block_width as usize, blocks_y * 4);
+    let mut colors = vec![([0; 4], [0; 4]); blocks_x * blocks_y];
+    // Modulation weight out of 8, whether the value is interpolated (and how) and punch-through
+    let mut weights = vec![0i32; full_w * full_h];
+    let mut modes = vec![0u8; full_w * full_h];
+    let mut punch = vec![false; full_w * full_h];
+    for by in 0..blocks_y {
+        for bx in 0..blocks_x {
+            let word = morton_index(by as u32, bx as u32, blocks_y as u32, blocks_x as u32) * 8;
+            let mut modulation = u32::from_le_bytes(data[word..word + 4].try_into().unwrap());
+            let color = u32::from_le_bytes(data[word + 4..word + 8].try_into().unwrap());
+            colors[by * blocks_x + bx] = (
+                get_pvrtc_color(color & 0xffff, true),
+                get_pvrtc_color(color >> 16, false)
+            );
+            let mut mode = (color & 1) as u8;
+            if bpp2 && mode != 0 {
+                if modulation & 1 != 0 {
+                    // H only or V only, using the low bit of the centre texel
+                    mode = if modulation & (1 << 20) != 0 { 3 } else { 2 };
+                    modulation = match modulation & (1 << 21) != 0 {
+                        true => modulation | (1 << 20),
+                        false => modulation & !(1 << 20)
+                    };
+                }
+                modulation = match modulation & 2 != 0 {
+                    true => modulation | 1,
+                    false => modulation & !1
+                };
+            }
+            for y in 0..4 {
+                for x in 0..block_width as usize {
+                    let i = (by * 4 + y) * full_w + bx * block_width as usize + x;
+                    modes[i] = mode;
+                    match (bpp2, mode) {
+                        (true, 0) => {
+                            weights[i] = if modulation & 1 != 0 { 8 } else { 0 };
+                            modulation >>= 1;
+                        },
+                        (true, _) => if (x ^ y) & 1 == 0 {
+                            weights[i] = WEIGHTS[(modulation & 3) as usize];
+                            modulation >>= 2;
+                        },
+                        (false, 0) => {
+                            weights[i] = WEIGHTS[(modulation & 3) as usize];
+                            modulation >>= 2;
+                        },
+                        (false, _) => {
+                            (weights[i], punch[i]) = match modulation & 3 {
+                                0 => (0, false),
+                                1 => (4, false),
+                                2 => (4, true),
+                                _ => (8, false)
+                            };
+                            modulation >>= 2;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    let mut out = Vec::with_capacity(width as usize * height as usize * 4);
+    for y in 0..height as usize {
+        for x in 0..width as usize {
+            let i = y * full_w + x;
+            let weight = match bpp2 && modes[i] != 0 && (x ^ y) & 1 != 0 {
+                false => weights[i],
+                true => {
+                    let (l, r) = ((x + full_w - 1) % full_w, (x + 1) % full_w);
+                    let (u, d) = ((y + full_h - 1) % full_h, (y + 1) % full_h);
+                    let h = weights[y * full_w + l] + weights[y * full_w + r];
+                    let v = weights[u * full_w + x] + weights[d * full_w + x];
+                    match modes[i] {
+                        1 => (h + v + 2) / 4,
+                        2 => (h + 1) / 2,
+                        _ => (v + 1) / 2
+                    }
+                }
+            };
+            // Bilinear interpolation between the centres of the four nearest blocks
+            let fx = x + full_w - block_width as usize / 2;
+            let fy = y + full_h - 2;
+            let (bx0, wx) = ((fx / block_width as usize) % blocks_x, (fx % block_width as usize) as i32);
+            let (by0, wy) = ((fy / 4) % blocks_y, (fy % 4) as i32);
+            let (bx1, by1) = ((bx0 + 1) % blocks_x, (by0 + 1) % blocks_y);
+            let bw = block_width as i32;
+            let corners = [
+                (by0 * blocks_x + bx0, (bw - wx) * (4 - wy)),
+                (by0 * blocks_x + bx1, wx * (4 - wy)),
+                (by1 * blocks_x + bx0, (bw - wx) * wy),
+                (by1 * blocks_x + bx1, wx * wy)
+            ];
+            let mut pixel = [0u8; 4];
+            for (c, channel) in pixel.iter_mut().enumerate() {
+                let (a, b) = corners.iter().fold((0, 0), |(a, b), (block, w)| (a + colors[*block].0[c] * w, b + colors[*block].1[c] * w));
+                *channel = ((a * (8 - weight) + b * weight) / (8 * bw * 4)) as u8;
+            }
+            if punch[i] {
+                pixel[3] = 0;
+            }
+            out.extend_from_slice(&pixel);
+        }
+    }
+    Ok(out)
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, TextureDecodeError> {
+    bytes.get(offset..offset + 4)
+        .map(|v| u32::from_le_bytes(v.try_into().unwrap()))
+        .ok_or(TextureDecodeError::UnexpectedEof)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PvrHeader {
+    pub format: PvrPixelFormat,
+    pub width: u32,
+    pub height: u32,
+    pub surface_count: u32,
+    pub face_count: u32,
+    pub mip_count: u32,
+    pub data_offset: usize
+}
+
+impl PvrHeader {
+    pub fn read(bytes: &[u8]) -> Result<Self, TextureDecodeError> {
+        let version = read_u32(bytes, 0)?;
+        if version != PVR_MAGIC {
+            return match bytes.get(0x2c..0x30) {
+                Some(b"PVR!") => Err(TextureDecodeError::UnsupportedVersion(2)),
+                _ => Err(TextureDecodeError::InvalidMagic(version))
+            };
+        }
+        let format_high = read_u32(bytes, 0xc)?;
+        let format = match format_high {
+            0 => PvrPixelFormat::from_compressed(read_u32(bytes, 8)?)?,
+            _ => PvrPixelFormat::from_channels(bytes[8..12].try_into().unwrap(), format_high.to_le_bytes())?
+        };
+        if read_u32(bytes, 0x20)? > 1 {
+            return Err(TextureDecodeError::UnsupportedVolumeTexture);
+        }
+        Ok(Self {
+            format,
+            height: read_u32(bytes, 0x18)?,
+            width: read_u32(bytes, 0x1c)?,
+            surface_count: read_u32(bytes, 0x24)?.max(1),
+            face_count: read_u32(bytes, 0x28)?.max(1),
+            mip_count: read_u32(bytes, 0x2c)?.clamp(1, MAX_MIP_COUNT),
+            data_offset: PVR_HEADER_SIZE + read_u32(bytes, 0x30)? as usize
+        })
+    }
+}
+
+/// Decode every surface in a PVR file. Data is ordered by mip level, then by array surface,
+/// then by cubemap face.
+pub fn decode_pvr(bytes: &[u8]) -> Result<DecodedTexture, TextureDecodeError> {
+    let header = PvrHeader::read(bytes)?;
+    let layer_count = header.surface_count.checked_mul(header.face_count)
+        .ok_or(TextureDecodeError::InvalidSurfaceCount(header.surface_count, header.mip_count))?;
+    let count = get_surface_count(layer_count, header.mip_count, bytes.len().saturating_sub(header.data_offset))?;
+    let mut surfaces = Vec::with_capacity(count);
+    let mut offset = header.data_offset;
+    for mip in 0..header.mip_count {
+        let (width, height) = get_mip_size(header.width, header.height, mip);
+        let size = header.format.get_surface_size(width, height);
+        for layer in 0..layer_count {
+            let data = offset.checked_add(size)
+                .and_then(|end| bytes.get(offset..end))
+                .ok_or(TextureDecodeError::UnexpectedEof)?;
+            surfaces.push(Surface::new(layer, mip, width, height, header.format.decode_surface(data, width, height)?));
+            offset += size;
+        }
+    }
+    surfaces.sort_by_key(|s| (s.get_layer(), s.get_mip()));
+    Ok(DecodedTexture::new(header.width, header.height, header.mip_count, layer_count, header.face_count == 6, surfaces))
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::error::Error;
+    use super::{ decode_pvr, PVR_HEADER_SIZE };
+
+    type TestReturn = Result<(), Box<dyn Error>>;
+
+    fn make_header(format: u64, width: u32, height: u32) -> Vec<u8> {
+        let mut bytes = vec![0u8; PVR_HEADER_SIZE];
+        bytes[0..4].copy_from_slice(b"PVR\x03");
+        bytes[8..16].copy_from_slice(&format.to_le_bytes());
+        bytes[0x18..0x1c].copy_from_slice(&height.to_le_bytes());
+        bytes[0x1c..0x20].copy_from_slice(&width.to_le_bytes());
+        for o in [0x20, 0x24, 0x28, 0x2c] {
+            bytes[o..o + 4].copy_from_slice(&1u32.to_le_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn decode_uncompressed_pvr() -> TestReturn {
+        // RGBA8888, first channel in the first byte
+        let mut bytes = make_header(u64::from_le_bytes(*b"rgba\x08\x08\x08\x08"), 1, 1);
+        bytes.extend([1, 2, 3, 4]);
+        assert_eq!(decode_pvr(&bytes)?.get_main_surface().unwrap().get_pixel(0, 0), [1, 2, 3, 4]);
+        // RGB565, first channel in the most significant bits
+        let mut bytes = make_header(u64::from_le_bytes(*b"rgb\0\x05\x06\x05\0"), 1, 1);
+        bytes.extend(0xf800u16.to_le_bytes());
+        assert_eq!(decode_pvr(&bytes)?.get_main_surface().unwrap().get_pixel(0, 0), [255, 0, 0, 255]);
+        Ok(())
+    }
+
+    #[test]
+    fn decode_pvrtc_solid() -> TestReturn {
+        // Every block has opaque white as color A and opaque black as color B with full
+        // modulation, so the whole texture should be black
+        let mut bytes = make_header(2, 8, 8);
+        for _ in 0..4 {
+            bytes.extend(0xffffffffu32.to_le_bytes());
+            bytes.extend(0x8000fffeu32.to_le_bytes());
+        }
+        let texture = decode_pvr(&bytes)?;
+        let surface = texture.get_main_surface().unwrap();
+        assert!(surface.get_data().chunks_exact(4).all(|p| p == [0, 0, 0, 255]));
+        Ok(())
+    }
+}
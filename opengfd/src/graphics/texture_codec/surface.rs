@@ -0,0 +1,244 @@
+//! Decoded texture data shared by each texture format's decoder.
+
+use std::{
+    error::Error,
+    fmt::{ Display, Formatter }
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextureDecodeError {
+    /// The data doesn't start with the format's magic number
+    InvalidMagic(u32),
+    /// The data ended before all surfaces could be read
+    UnexpectedEof,
+    /// The pixel format isn't one that can be decoded
+    UnsupportedPixelFormat(String),
+    /// Volume textures can't be decoded
+    UnsupportedVolumeTexture,
+    /// The file was written with a version of the format that can't be read
+    UnsupportedVersion(u32),
+    /// The texture uses a tiling mode that can't be removed
+    UnsupportedTiling(u32),
+    /// The header describes more surfaces than can be stored
+    InvalidSurfaceCount(u32, u32)
+}
+
+impl Error for TextureDecodeError {}
+impl Display for TextureDecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TextureDecodeError: {:?}", self)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextureEncodeError {
+    /// The pixel format can't be encoded
+    UnsupportedPixelFormat(String),
+    /// The image has no pixels, or fewer bytes than its size needs
+    InvalidImageSize(u32, u32)
+}
+
+impl Error for TextureEncodeError {}
+impl Display for TextureEncodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TextureEncodeError: {:?}", self)
+    }
+}
+
+/// A single mip level of one array layer or cubemap face, stored as RGBA8 in row order
+#[derive(Debug, Clone, PartialEq)]
+pub struct Surface {
+    layer: u32,
+    mip: u32,
+    width: u32,
+    height: u32,
+    data: Vec<u8>
+}
+
+impl Surface {
+    pub fn new(layer: u32, mip: u32, width: u32, height: u32, data: Vec<u8>) -> Self {
+        Self { layer, mip, width, height, data }
+    }
+
+    pub fn get_layer(&self) -> u32 { self.layer }
+    pub fn get_mip(&self) -> u32 { self.mip }
+    pub fn get_width(&self) -> u32 { self.width }
+    pub fn get_height(&self) -> u32 { self.height }
+    pub fn get_data(&self) -> &[u8] { self.data.as_slice() }
+
+    pub fn get_pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = (y * self.width + x) as usize * 4;
+        self.data[i..i + 4].try_into().unwrap()
+    }
+
+    /// Create the next mip level by averaging each 2x2 group of pixels. Odd edges reuse the last
+    /// row or column.
+    pub fn downsample(&self) -> Self {
+        let (width, height) = get_mip_size(self.width, self.height, 1);
+        let mut data = Vec::with_capacity(width as usize * height as usize * 4);
+        for y in 0..height {
+            for x in 0..width {
+                let (x0, y0) = ((x * 2).min(self.width - 1), (y * 2).min(self.height - 1));
+                let (x1, y1) = ((x * 2 + 1).min(self.width - 1), (y * 2 + 1).min(self.height - 1));
+                let samples = [self.get_pixel(x0, y0), self.get_pixel(x1, y0), self.get_pixel(x0, y1), self.get_pixel(x1, y1)];
+                for c in 0..4 {
+                    data.push(((samples.iter().map(|p| p[c] as u32).sum::<u32>() + 2) / 4) as u8);
+                }
+            }
+        }
+        Self::new(self.layer, self.mip + 1, width, height, data)
+    }
+
+    #[cfg(feature = "image_loader")]
+    pub fn to_image(&self) -> image::RgbaImage {
+        image::RgbaImage::from_raw(self.width, self.height, self.data.clone()).unwrap()
+    }
+}
+
+/// Every surface in a texture. Cubemap faces are stored as layers, six per cubemap, in the order
+/// +X, -X, +Y, -Y, +Z, -Z.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedTexture {
+    width: u32,
+    height: u32,
+    mip_count: u32,
+    layer_count: u32,
+    cubemap: bool,
+    surfaces: Vec<Surface>
+}
+
+impl DecodedTexture {
+    pub fn new(width: u32, height: u32, mip_count: u32, layer_count: u32, cubemap: bool, surfaces: Vec<Surface>) -> Self {
+        Self { width, height, mip_count, layer_count, cubemap, surfaces }
+    }
+
+    pub fn get_width(&self) -> u32 { self.width }
+    pub fn get_height(&self) -> u32 { self.height }
+    pub fn get_mip_count(&self) -> u32 { self.mip_count }
+    pub fn get_layer_count(&self) -> u32 { self.layer_count }
+    pub fn is_cubemap(&self) -> bool { self.cubemap }
+    pub fn get_surfaces(&self) -> &[Surface] { self.surfaces.as_slice() }
+
+    pub fn get_surface(&self, layer: u32, mip: u32) -> Option<&Surface> {
+        self.surfaces.iter().find(|s| s.layer == layer && s.mip == mip)
+    }
+
+    /// The full size image for the first layer
+    pub fn get_main_surface(&self) -> Option<&Surface> {
+        self.get_surface(0, 0)
+    }
+}
+
+/// Bit masks for each channel of an uncompressed pixel. Channels with an empty mask are set to
+/// zero, or fully opaque for alpha.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelMask {
+    pub bits: u32,
+    pub r: u32,
+    pub g: u32,
+    pub b: u32,
+    pub a: u32,
+    /// Copy the red channel into green and blue
+    pub luminance: bool
+}
+
+impl PixelMask {
+    pub const fn new(bits: u32, r: u32, g: u32, b: u32, a: u32) -> Self {
+        Self { bits, r, g, b, a, luminance: false }
+    }
+    pub const fn luminance(bits: u32, l: u32, a: u32) -> Self {
+        Self { bits, r: l, g: 0, b: 0, a, luminance: true }
+    }
+
+    fn get_channel(value: u32, mask: u32, default: u8) -> u8 {
+        if mask == 0 {
+            return default;
+        }
+        let shift = mask.trailing_zeros();
+        let max = (mask >> shift) as u64;
+        (((value & mask) >> shift) as u64 * 255 / max) as u8
+    }
+
+    /// Number of bytes in each pixel. Only whole byte sizes up to 32 bits are supported.
+    pub fn get_stride(&self) -> Result<usize, TextureDecodeError> {
+        match self.bits {
+            8 | 16 | 24 | 32 => Ok(self.bits as usize / 8),
+            v => Err(TextureDecodeError::UnsupportedPixelFormat(format!("{} bits per pixel", v)))
+        }
+    }
+
+    pub fn decode(&self, data: &[u8], width: u32, height: u32) -> Result<Vec<u8>, TextureDecodeError> {
+        let stride = self.get_stride()?;
+        let count = width as usize * height as usize;
+        if count.checked_mul(stride).is_none_or(|size| data.len() < size) {
+            return Err(TextureDecodeError::UnexpectedEof);
+        }
+        let mut out = Vec::with_capacity(count * 4);
+        for pixel in data.chunks_exact(stride).take(count) {
+            let value = pixel.iter().enumerate().fold(0u32, |v, (i, b)| v | (*b as u32) << (8 * i));
+            let r = Self::get_channel(value, self.r, 0);
+            let (g, b) = match self.luminance {
+                true => (r, r),
+                false => (Self::get_channel(value, self.g, 0), Self::get_channel(value, self.b, 0))
+            };
+            out.extend_from_slice(&[r, g, b, Self::get_channel(value, self.a, u8::MAX)]);
+        }
+        Ok(out)
+    }
+}
+
+/// A texture with 32 bit dimensions can't have more mip levels than this
+pub const MAX_MIP_COUNT: u32 = 32;
+
+/// Get the size of a mip level, which is never smaller than 1x1
+pub fn get_mip_size(width: u32, height: u32, mip: u32) -> (u32, u32) {
+    (width.checked_shr(mip).unwrap_or(0).max(1), height.checked_shr(mip).unwrap_or(0).max(1))
+}
+
+/// Get the number of surfaces in a texture, failing if the header's counts can't be right. Each
+/// surface takes at least one byte, so the result is also limited by the size of the data
+/// that's left.
+pub fn get_surface_count(layer_count: u32, mip_count: u32, remaining: usize) -> Result<usize, TextureDecodeError> {
+    match layer_count.checked_mul(mip_count) {
+        Some(count) if count as usize <= remaining => Ok(count as usize),
+        _ => Err(TextureDecodeError::InvalidSurfaceCount(layer_count, mip_count))
+    }
+}
+
+/// Encode a surface into blocks. Blocks that extend past the edge of the image repeat the last row
+/// and column.
+pub fn encode_blocks<F, const N: usize>(surface: &Surface, encode: F) -> Vec<u8>
+where F: Fn(&[[u8; 4]; 16]) -> [u8; N] {
+    let (blocks_x, blocks_y) = (surface.width.div_ceil(4), surface.height.div_ceil(4));
+    let mut out = Vec::with_capacity(blocks_x as usize * blocks_y as usize * N);
+    for by in 0..blocks_y {
+        for bx in 0..blocks_x {
+            let pixels = std::array::from_fn(|i| surface.get_pixel(
+                (bx * 4 + i as u32 % 4).min(surface.width - 1),
+                (by * 4 + i as u32 / 4).min(surface.height - 1)));
+            out.extend_from_slice(&encode(&pixels));
+        }
+    }
+    out
+}
+
+/// Decode a block compressed surface, cropping blocks that extend past the edge of the image
+pub fn decode_blocks<F>(data: &[u8], width: u32, height: u32, block_size: usize, decode: F) -> Result<Vec<u8>, TextureDecodeError>
+where F: Fn(&[u8]) -> [[u8; 4]; 16] {
+    let (blocks_x, blocks_y) = (width.div_ceil(4) as usize, height.div_ceil(4) as usize);
+    if data.len() < blocks_x * blocks_y * block_size {
+        return Err(TextureDecodeError::UnexpectedEof);
+    }
+    let mut out = vec![0u8; width as usize * height as usize * 4];
+    for (i, block) in data.chunks_exact(block_size).take(blocks_x * blocks_y).enumerate() {
+        let (bx, by) = (i % blocks_x * 4, i / blocks_x * 4);
+        for (p, pixel) in decode(block).iter().enumerate() {
+            let (x, y) = (bx + p % 4, by + p / 4);
+            if x < width as usize && y < height as usize {
+                let o = (y * width as usize + x) * 4;
+                out[o..o + 4].copy_from_slice(pixel);
+            }
+        }
+    }
+    Ok(out)
+}
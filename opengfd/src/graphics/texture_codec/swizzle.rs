@@ -0,0 +1,90 @@
+//! Remove the swizzling and tiling used by console GPUs. Elements are single pixels for
+//! uncompressed textures and 4x4 blocks for block compressed textures.
+
+use crate::graphics::texture_codec::surface::TextureDecodeError;
+
+/// Position of an element in a Morton ordered (Z order) texture. Bits from x and y are
+/// interleaved starting with x. Once the smaller dimension runs out of bits, the remaining bits
+/// come from the larger dimension. Dimensions are rounded up to a power of two.
+pub fn morton_index(x: u32, y: u32, width: u32, height: u32) -> usize {
+    let (mut w, mut h) = (width.next_power_of_two(), height.next_power_of_two());
+    let (mut index, mut out_bit, mut bit) = (0usize, 0, 0);
+    while w > 1 || h > 1 {
+        if w > 1 {
+            index |= ((x >> bit & 1) as usize) << out_bit;
+            out_bit += 1;
+            w >>= 1;
+        }
+        if h > 1 {
+            index |= ((y >> bit & 1) as usize) << out_bit;
+            out_bit += 1;
+            h >>= 1;
+        }
+        bit += 1;
+    }
+    index
+}
+
+/// Rearrange Morton ordered elements into row order. `width` and `height` are in elements.
+pub fn unswizzle_morton(data: &[u8], width: u32, height: u32, element_size: usize) -> Result<Vec<u8>, TextureDecodeError> {
+    let mut out = vec![0u8; width as usize * height as usize * element_size];
+    for y in 0..height {
+        for x in 0..width {
+            let src = morton_index(x, y, width, height) * element_size;
+            let dst = (y * width + x) as usize * element_size;
+            let element = data.get(src..src + element_size).ok_or(TextureDecodeError::UnexpectedEof)?;
+            out[dst..dst + element_size].copy_from_slice(element);
+        }
+    }
+    Ok(out)
+}
+
+/// Rearrange elements stored in 8x8 tiles, with Morton order inside of each tile, into row order.
+/// This is the thin micro tiling used by PS4 textures. `pitch` is the width of a row of tiles in
+/// elements.
+pub fn untile_ps4(data: &[u8], width: u32, height: u32, pitch: u32, element_size: usize) -> Result<Vec<u8>, TextureDecodeError> {
+    let mut out = vec![0u8; width as usize * height as usize * element_size];
+    let (tiles_x, tiles_y) = (pitch.max(width).div_ceil(8), height.div_ceil(8));
+    let mut src = 0;
+    for ty in 0..tiles_y {
+        for tx in 0..tiles_x {
+            for t in 0..64 {
+                let element = data.get(src..src + element_size).ok_or(TextureDecodeError::UnexpectedEof)?;
+                src += element_size;
+                // even bits of t are x, odd bits are y
+                let x = tx * 8 + (t & 1 | t >> 1 & 2 | t >> 2 & 4);
+                let y = ty * 8 + (t >> 1 & 1 | t >> 2 & 2 | t >> 3 & 4);
+                if x < width && y < height {
+                    let dst = (y * width + x) as usize * element_size;
+                    out[dst..dst + element_size].copy_from_slice(element);
+                }
+            }
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::error::Error;
+    use super::{ morton_index, untile_ps4, unswizzle_morton };
+
+    type TestReturn = Result<(), Box<dyn Error>>;
+
+    #[test]
+    fn morton_order() -> TestReturn {
+        let order: Vec<usize> = (0..4).flat_map(|y| (0..4).map(move |x| morton_index(x, y, 4, 4))).collect();
+        assert_eq!(order, [0, 1, 4, 5, 2, 3, 6, 7, 8, 9, 12, 13, 10, 11, 14, 15]);
+        // Non-square textures append the leftover bits from the larger dimension
+        assert_eq!(morton_index(3, 1, 4, 2), 7);
+        assert_eq!(morton_index(0, 3, 2, 4), 6);
+        let swizzled = [0u8, 1, 4, 5, 2, 3, 6, 7, 8, 9, 12, 13, 10, 11, 14, 15];
+        let linear = unswizzle_morton(&swizzled, 4, 4, 1)?;
+        assert_eq!(linear, (0..16).collect::<Vec<u8>>());
+        let tiled: Vec<u8> = (0..64).collect();
+        let untiled = untile_ps4(&tiled, 8, 8, 8, 1)?;
+        assert_eq!(&untiled[..8], &[0, 1, 4, 5, 16, 17, 20, 21]);
+        assert_eq!(untiled[8], 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,187 @@
+//! Decode TMX textures, which are used by Atlus' PS2 games and the PS2 era
+//! assets carried over into later titles.
+//!
+//! TMX stores Graphics Synthesizer pixel formats. Alpha uses the GS range where 0x80 is fully
+//! opaque, so it's doubled when decoding. Only the first palette and the base level are decoded.
+
+use crate::graphics::texture_codec::surface::{ DecodedTexture, PixelMask, Surface, TextureDecodeError };
+
+pub const TMX_MAGIC: u32 = 0x30584D54;
+pub const TMX_HEADER_SIZE: usize = 0x40;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum TmxPixelFormat {
+    PSMCT32 = 0x00,
+    PSMCT24 = 0x01,
+    PSMCT16 = 0x02,
+    PSMCT16S = 0x0A,
+    PSMT8 = 0x13,
+    PSMT4 = 0x14
+}
+
+impl TryFrom<u8> for TmxPixelFormat {
+    type Error = TextureDecodeError;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x00 => Ok(Self::PSMCT32),
+            0x01 => Ok(Self::PSMCT24),
+            0x02 => Ok(Self::PSMCT16),
+            0x0A => Ok(Self::PSMCT16S),
+            0x13 => Ok(Self::PSMT8),
+            0x14 => Ok(Self::PSMT4),
+            v => Err(TextureDecodeError::UnsupportedPixelFormat(format!("TMX format 0x{:x}", v)))
+        }
+    }
+}
+
+impl TmxPixelFormat {
+    /// Number of bits used by each pixel or palette entry
+    pub fn get_bits(&self) -> usize {
+        match self {
+            Self::PSMCT32 => 32,
+            Self::PSMCT24 => 24,
+            Self::PSMCT16 | Self::PSMCT16S => 16,
+            Self::PSMT8 => 8,
+            Self::PSMT4 => 4
+        }
+    }
+
+    /// Number of entries in the palette used by indexed formats
+    pub fn get_palette_length(&self) -> usize {
+        match self {
+            Self::PSMT8 => 256,
+            Self::PSMT4 => 16,
+            _ => 0
+        }
+    }
+
+    fn decode_colors(&self, data: &[u8], count: usize) -> Result<Vec<u8>, TextureDecodeError> {
+        let mut out = match self {
+            Self::PSMCT32 => PixelMask::new(32, 0xff, 0xff00, 0xff0000, 0xff000000).decode(data, count as u32, 1)?,
+            Self::PSMCT24 => return PixelMask::new(24, 0xff, 0xff00, 0xff0000, 0).decode(data, count as u32, 1),
+            Self::PSMCT16 | Self::PSMCT16S => PixelMask::new(16, 0x1f, 0x3e0, 0x7c00, 0x8000).decode(data, count as u32, 1)?,
+            _ => return Err(TextureDecodeError::UnsupportedPixelFormat(format!("TMX palette format {:?}", self)))
+        };
+        if *self == Self::PSMCT32 {
+            out.chunks_exact_mut(4).for_each(|p| p[3] = p[3].saturating_mul(2));
+        }
+        Ok(out)
+    }
+}
+
+/// Palette entries for 8-bit textures are stored with bits 3 and 4 of the index swapped
+fn get_clut_index(index: usize) -> usize {
+    (index & 0xe7) | ((index & 0x8) << 1) | ((index & 0x10) >> 1)
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Result<u16, TextureDecodeError> {
+    bytes.get(offset..offset + 2)
+        .map(|v| u16::from_le_bytes(v.try_into().unwrap()))
+        .ok_or(TextureDecodeError::UnexpectedEof)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TmxHeader {
+    pub width: u32,
+    pub height: u32,
+    pub palette_count: u32,
+    pub palette_format: TmxPixelFormat,
+    pub format: TmxPixelFormat,
+    pub mip_count: u32
+}
+
+impl TmxHeader {
+    pub fn read(bytes: &[u8]) -> Result<Self, TextureDecodeError> {
+        if bytes.len() < TMX_HEADER_SIZE {
+            return Err(TextureDecodeError::UnexpectedEof);
+        }
+        let magic = u32::from_le_bytes(bytes[8..12].try_into().unwrap());
+        if magic != TMX_MAGIC {
+            return Err(TextureDecodeError::InvalidMagic(magic));
+        }
+        Ok(Self {
+            palette_count: bytes[0x10] as u32,
+            palette_format: bytes[0x11].try_into()?,
+            width: read_u16(bytes, 0x12)? as u32,
+            height: read_u16(bytes, 0x14)? as u32,
+            format: bytes[0x16].try_into()?,
+            mip_count: bytes[0x17] as u32 + 1
+        })
+    }
+}
+
+/// Decode the base level of a TMX file
+pub fn decode_tmx(bytes: &[u8]) -> Result<DecodedTexture, TextureDecodeError> {
+    let header = TmxHeader::read(bytes)?;
+    let pixel_count = header.width as usize * header.height as usize;
+    let palette_length = header.format.get_palette_length();
+    let pixel_offset = TMX_HEADER_SIZE + header.palette_count as usize * palette_length * header.palette_format.get_bits() / 8;
+    let pixels = bytes.get(pixel_offset..pixel_offset + pixel_count * header.format.get_bits() / 8)
+        .ok_or(TextureDecodeError::UnexpectedEof)?;
+    let data = match header.format {
+        TmxPixelFormat::PSMT8 | TmxPixelFormat::PSMT4 => {
+            if header.palette_count == 0 {
+                return Err(TextureDecodeError::UnexpectedEof);
+            }
+            let palette = header.palette_format.decode_colors(&bytes[TMX_HEADER_SIZE..], palette_length)?;
+            let mut out = Vec::with_capacity(pixel_count * 4);
+            for i in 0..pixel_count {
+                let index = match header.format {
+                    TmxPixelFormat::PSMT8 => get_clut_index(pixels[i] as usize),
+                    _ => (pixels[i / 2] >> (4 * (i & 1)) & 0xf) as usize
+                };
+                out.extend_from_slice(&palette[index * 4..index * 4 + 4]);
+            }
+            out
+        },
+        f => f.decode_colors(pixels, pixel_count)?
+    };
+    let surface = Surface::new(0, 0, header.width, header.height, data);
+    Ok(DecodedTexture::new(header.width, header.height, 1, 1, false, vec![surface]))
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::error::Error;
+    use super::{ decode_tmx, TMX_HEADER_SIZE };
+
+    type TestReturn = Result<(), Box<dyn Error>>;
+
+    fn make_header(palette_count: u8, palette_format: u8, width: u16, height: u16, format: u8) -> Vec<u8> {
+        let mut bytes = vec![0u8; TMX_HEADER_SIZE];
+        bytes[8..12].copy_from_slice(b"TMX0");
+        bytes[0x10] = palette_count;
+        bytes[0x11] = palette_format;
+        bytes[0x12..0x14].copy_from_slice(&width.to_le_bytes());
+        bytes[0x14..0x16].copy_from_slice(&height.to_le_bytes());
+        bytes[0x16] = format;
+        bytes
+    }
+
+    #[test]
+    fn decode_indexed_tmx() -> TestReturn {
+        // 8-bit indexed with a 32-bit palette. Entry 8 is stored at position 16
+        let mut bytes = make_header(1, 0x00, 2, 1, 0x13);
+        let mut palette = vec![0u8; 256 * 4];
+        palette[16 * 4..17 * 4].copy_from_slice(&[10, 20, 30, 0x80]);
+        palette[4..8].copy_from_slice(&[40, 50, 60, 0x40]);
+        bytes.extend(palette);
+        bytes.extend([8, 1]);
+        let texture = decode_tmx(&bytes)?;
+        let surface = texture.get_main_surface().unwrap();
+        assert_eq!(surface.get_pixel(0, 0), [10, 20, 30, 255]);
+        assert_eq!(surface.get_pixel(1, 0), [40, 50, 60, 0x80]);
+        // 4-bit indexed with a 16-bit palette, low nibble first
+        let mut bytes = make_header(1, 0x02, 2, 1, 0x14);
+        let mut palette = vec![0u8; 16 * 2];
+        palette[2..4].copy_from_slice(&0x801fu16.to_le_bytes());
+        bytes.extend(palette);
+        bytes.push(0x01);
+        let texture = decode_tmx(&bytes)?;
+        let surface = texture.get_main_surface().unwrap();
+        assert_eq!(surface.get_pixel(0, 0), [255, 0, 0, 255]);
+        assert_eq!(surface.get_pixel(1, 0), [0, 0, 0, 0]);
+        Ok(())
+    }
+}
@@ -1,3 +1,5 @@
+use crate::utility::stream::DevicePlatform;
+
 #[allow(nonstandard_style)]
 pub enum GfdVersion {
     // GFD v1
@@ -5,6 +7,7 @@ pub enum GfdVersion {
     NameContainsHash = 0x1080001,
     MaterialBlendAddMultiplyMode = 0x1080004,
     MaterialHasHighlightMapMode = 0x108011c,
+    NodeRemoveExtraByte = 0x1090001,
     EnvFogMode = 0x1102000,
     GeometryAddGeomType = 0x1103021,
     MaterialBlendAsU8 = 0x1103041, // also adds mat constant field
@@ -24,6 +27,7 @@ pub enum GfdVersion {
     LightAddFlags = 0x1104191,
     MaterialExtensionEdgeLightAdd = 0x1104211,
     MaterialExtensionToonV2 = 0x1104221,
+    NodeAddVisibility = 0x1104231,
     EnvAddIndependenceLight = 0x1104241,
     MaterialExtensionToonV2LockYAxis = 0x1104261,
     EplPolygonFlashRingSpread = 0x1104281,
@@ -114,7 +118,66 @@ impl GfdVersion {
     pub const fn current() -> Self {
         #[cfg(feature = "v1-core")]
         { GfdVersion::PERSONA5ROYAL }
-        #[cfg(feature = "v2-core")]
+        #[cfg(not(feature = "v1-core"))]
         { GfdVersion::METAPHORREFANTAZIO }
     }
-}
\ No newline at end of file
+}
+/// Identifies which game a file was made for. OpenGFD is built against the data structures of one
+/// game (v1-core or v2-core), but the serializer uses this to decide how to read data that's
+/// stored differently between titles, so the same build can read files from any GFD game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameProfile {
+    Persona5Royal,
+    CatherineFullBody,
+    Persona4Dancing,
+    MetaphorRefantazio,
+    MetaphorRefantazioDemo
+}
+
+impl GameProfile {
+    /// The game that this build of OpenGFD hooks into
+    pub const fn current() -> Self {
+        #[cfg(feature = "v1-core")]
+        { GameProfile::Persona5Royal }
+        #[cfg(not(feature = "v1-core"))]
+        { GameProfile::MetaphorRefantazio }
+    }
+
+    /// Guess the game from a file's header. Catherine: Full Body and the Metaphor demo share a
+    /// platform with Persona 5 Royal and Metaphor, so they're told apart by version: files newer
+    /// than P5D but older than P5R are from CFB, and v2 files older than the retail release of
+    /// Metaphor are from the demo. Games that are grouped together read the same, so a file
+    /// that's detected as the wrong one still loads, and Stream::set_game_profile can override it.
+    pub fn detect(version: u32, platform: DevicePlatform) -> Self {
+        if version >= GfdVersion::METAPHORREFANTAZIO as u32 {
+            GameProfile::MetaphorRefantazio
+        } else if version >= GfdVersion::GFDV2 as u32 {
+            GameProfile::MetaphorRefantazioDemo
+        } else if platform == DevicePlatform::PDANCE_VITA
+            || version == GfdVersion::PERSONA4DANCING as u32 {
+            GameProfile::Persona4Dancing
+        } else if version > GfdVersion::P5DP3D as u32 && version < GfdVersion::PERSONA5ROYAL as u32 {
+            GameProfile::CatherineFullBody
+        } else {
+            GameProfile::Persona5Royal
+        }
+    }
+
+    /// The newest file version that the game can read
+    pub const fn get_version(&self) -> GfdVersion {
+        match self {
+            Self::Persona5Royal | Self::CatherineFullBody => GfdVersion::PERSONA5ROYAL,
+            Self::Persona4Dancing => GfdVersion::PERSONA4DANCING,
+            Self::MetaphorRefantazio | Self::MetaphorRefantazioDemo => GfdVersion::METAPHORREFANTAZIO
+        }
+    }
+
+    /// GFD v2 games store material parameters by type. v1 games store Lambert parameters
+    /// followed by a list of material extensions.
+    pub const fn is_gfd_v2(&self) -> bool {
+        match self {
+            Self::MetaphorRefantazio | Self::MetaphorRefantazioDemo => true,
+            _ => false
+        }
+    }
+}
@@ -7,6 +7,14 @@ compile_error!("v1-core and v2-core are mutually exclusive!");
 #[cfg(all(feature = "adapter-hedge", feature = "adapter-ngr"))]
 compile_error!("adapter-hedge and adapter-ngr are mutually exclusive!");
 
+#[cfg(all(feature = "headless", feature = "v1-core"))]
+compile_error!("headless is only supported with v2-core!");
+
+// Lets the globals list from opengfd-globals (which refers to this crate as opengfd) be included
+// in headless builds
+#[cfg(feature = "headless")]
+extern crate self as opengfd;
+
 // LIBRARY STRUCTURE
 
 pub mod ai {
@@ -62,7 +70,7 @@ pub mod device {
     pub mod ngr {
         pub mod allocator;
         pub mod hint;
-        #[cfg(target_os = "windows")]
+        #[cfg(any(target_os = "windows", feature = "headless"))]
         #[path = "win32"]
         pub mod platform {
             pub mod allocator;
@@ -71,7 +79,7 @@ pub mod device {
             pub mod blend;
             pub mod bytecode;
             pub mod cbuffer;
-            #[cfg(target_os = "windows")]
+            #[cfg(any(target_os = "windows", feature = "headless"))]
             #[path = "d3d"]
             pub mod platform {
                 pub mod d3d;
@@ -122,9 +130,19 @@ pub mod gfw {
     pub mod smartpointer;
 }
 // pub mod globals;
-#[cfg(feature = "v2-core")]
+#[cfg(all(feature = "v2-core", not(feature = "headless")))]
 #[path = "globals_xrd759.rs"]
 pub mod globals;
+#[cfg(all(feature = "v2-core", feature = "headless"))]
+#[path = "globals_headless.rs"]
+pub mod globals;
+/// Pointer to a program global, used by headless builds to store globals in OpenGFD itself
+#[cfg(feature = "headless")]
+pub struct UnsafePtr<T>(*mut T);
+#[cfg(feature = "headless")]
+unsafe impl<T> Send for UnsafePtr<T> {}
+#[cfg(feature = "headless")]
+unsafe impl<T> Sync for UnsafePtr<T> {}
 #[cfg(feature = "v1-core")]
 #[path = "globals_xrd744.rs"]
 pub mod globals;
@@ -145,6 +163,10 @@ pub mod graphics {
     pub mod infinite_ocean;
     pub mod lensflare;
     pub mod material;
+    #[cfg(feature = "v2-core")]
+    pub mod material_builder;
+    #[cfg(all(feature = "v2-core", feature = "io_serde"))]
+    pub mod material_serde;
     pub mod post_effect;
     pub mod render {
         pub mod cmd_buffer;
@@ -163,25 +185,43 @@ pub mod graphics {
             pub mod water_v1;
         }
         */
-        #[cfg(feature = "v2-core")]
+        // Lambert parameters and the v1 extensions are used by both cores
+        #[cfg(any(feature = "v1-core", feature = "v2-core"))]
         pub mod attribute {
+            #[cfg(feature = "v2-core")]
             pub mod distortion_v2;
+            #[cfg(feature = "v2-core")]
             pub mod field_v2;
             pub mod lambert_v2;
+            #[cfg(feature = "v2-core")]
             pub mod layered_v2;
+            #[cfg(feature = "v2-core")]
             pub mod metal_v2;
+            #[cfg(feature = "v2-core")]
             pub mod pbr_v2;
+            #[cfg(feature = "v2-core")]
             pub mod shadow_v2;
+            #[cfg(feature = "v2-core")]
             pub mod sky_v2;
+            #[cfg(feature = "v2-core")]
             pub mod toon_v2;
+            #[cfg(feature = "v2-core")]
             pub mod type3_v2;
+            #[cfg(feature = "v2-core")]
             pub mod type7_v2;
+            #[cfg(feature = "v2-core")]
             pub mod type8_v2;
+            #[cfg(feature = "v2-core")]
             pub mod type9_v2;
+            #[cfg(feature = "v2-core")]
             pub mod type11_v2;
+            #[cfg(feature = "v2-core")]
             pub mod type13_v2;
+            #[cfg(feature = "v2-core")]
             pub mod type14_v2;
+            #[cfg(feature = "v2-core")]
             pub mod type15_v2;
+            #[cfg(feature = "v2-core")]
             pub mod water_v2;
 
             pub mod toon_v1;
@@ -209,6 +249,18 @@ pub mod graphics {
     pub mod skin;
     pub mod terrain;
     pub mod texture;
+    pub mod texture_codec {
+        pub mod bc;
+        pub mod bc_encode;
+        pub mod dds;
+        pub mod gnf;
+        pub mod gtf;
+        pub mod gxt;
+        pub mod pvr;
+        pub mod surface;
+        pub mod swizzle;
+        pub mod tmx;
+    }
 }
 pub mod io {
     pub mod controller;
@@ -259,8 +311,18 @@ pub mod object {
     pub mod geometry;
     pub mod camera;
     pub mod light;
+    pub mod convert;
     pub mod epl;
+    pub mod extra_properties;
     pub mod morph;
+    pub mod physics;
+    #[cfg(feature = "io_serde")]
+    pub mod gltf {
+        pub mod document;
+        pub mod export;
+        #[cfg(feature = "serialize")]
+        pub mod import;
+    }
 }
 #[cfg(feature = "v1-core")]
 pub mod object {
@@ -334,6 +396,7 @@ pub mod utility {
     pub mod name;
     pub mod property;
     pub mod reference;
+    pub mod side_table;
     #[cfg(feature = "serialize")]
     pub mod stream;
 }
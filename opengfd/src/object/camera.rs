@@ -170,6 +170,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this)
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut SerializationSingleAllocator<AObject>) -> DynRes<()> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -184,12 +188,61 @@ where AObject: Allocator + Clone {
         self.fovy = stream.read_f32()?;
         self.aspect = stream.read_f32()?;
         self.roll = stream.has_feature(GfdVersion::CameraAddRoll).map_or(Ok(0.), |_| stream.read_f32())?;
-        #[cfg(feature = "v2-core")]
-        {
-            self.field11_0x198 = stream.has_feature(GfdVersion::CameraAddUnkMetaphor).map_or(Ok(0), |_| stream.read_u8())?;
-            self.field12_0x19c = stream.has_feature(GfdVersion::CameraAddUnkMetaphor).map_or(Ok(0.), |_| stream.read_f32())?;
-            self.field13_0x1a0 = stream.has_feature(GfdVersion::CameraAddUnkMetaphor).map_or(Ok(0.), |_| stream.read_f32())?;
+        if Self::has_metaphor_fields(stream) {
+            let field198 = stream.read_u8()?;
+            let field19c = stream.read_f32()?;
+            let field1a0 = stream.read_f32()?;
+            self.set_metaphor_fields(field198, field19c, field1a0);
+        } else {
+            self.set_metaphor_fields(0, 0., 0.);
         }
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> DynRes<()>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug {
+        self.view.stream_write(stream, &mut ())?;
+        stream.write_f32(self.near_clip)?;
+        stream.write_f32(self.far_clip)?;
+        stream.write_f32(self.fovy)?;
+        stream.write_f32(self.aspect)?;
+        if stream.has_feature(GfdVersion::CameraAddRoll).is_some() {
+            stream.write_f32(self.roll)?;
+        }
+        if Self::has_metaphor_fields(stream) {
+            let (field198, field19c, field1a0) = self.get_metaphor_fields();
+            stream.write_u8(field198)?;
+            stream.write_f32(field19c)?;
+            stream.write_f32(field1a0)?;
+        }
+        Ok(())
+    }
+
+    fn has_metaphor_fields<AStream, T>(stream: &Stream<AStream, T>) -> bool
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug {
+        stream.get_game_profile().is_gfd_v2() && stream.has_feature(GfdVersion::CameraAddUnkMetaphor).is_some()
+    }
+
+    // Only the v2 camera has space for these, other builds read them and write back defaults
+    #[cfg(feature = "v2-core")]
+    fn set_metaphor_fields(&mut self, field198: u8, field19c: f32, field1a0: f32) {
+        self.field11_0x198 = field198;
+        self.field12_0x19c = field19c;
+        self.field13_0x1a0 = field1a0;
+    }
+
+    #[cfg(not(feature = "v2-core"))]
+    fn set_metaphor_fields(&mut self, _: u8, _: f32, _: f32) {}
+
+    #[cfg(feature = "v2-core")]
+    fn get_metaphor_fields(&self) -> (u8, f32, f32) {
+        (self.field11_0x198, self.field12_0x19c, self.field13_0x1a0)
+    }
+
+    #[cfg(not(feature = "v2-core"))]
+    fn get_metaphor_fields(&self) -> (u8, f32, f32) {
+        (0, 0., 0.)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,391 @@
+//! Convert models between GFD versions, such as moving assets between Persona 5 Royal (v1) and
+//! Metaphor: Refantazio (v2).
+//!
+//! Most version differences are handled by the version checks in each object's stream_write, so
+//! converting a file is a matter of reading it and writing it back out with a different header.
+//! This module handles the data that those checks can't represent at the target version,
+//! adjusting it where possible and reporting what was changed.
+
+use allocator_api2::alloc::Allocator;
+#[cfg(feature = "serialize")]
+use allocator_api2::vec::Vec as AVec;
+use crate::{
+    graphics::material::MaterialFlags,
+    kernel::version::GfdVersion,
+    object::{
+        geometry::{ GeometryFlags, VertexWeight },
+        mesh::Mesh
+    }
+};
+#[cfg(feature = "v2-core")]
+use crate::{
+    graphics::{
+        material::params::MaterialId,
+        shader::attribute::lambert_v2::Lambert
+    },
+    utility::misc::RGBAFloat
+};
+#[cfg(feature = "serialize")]
+use crate::object::mesh::MeshFile;
+#[cfg(feature = "serialize")]
+use crate::utility::stream::{ GfdSerialize, SerializationSingleAllocator, StreamFactory, StreamHeader };
+use std::{
+    error::Error,
+    fmt::{ Display, Formatter }
+};
+#[cfg(feature = "serialize")]
+use std::fmt::Debug;
+
+/// Number of vertex weights that can be stored per vertex before GeometryUseNewVertexWeightFormat
+pub const LEGACY_VERTEX_WEIGHT_COUNT: usize = 4;
+
+// Versions that add effect fields which OpenGFD doesn't read yet. Effects are written using the
+// target version, so any of these between the target and source version will drop data. Fields
+// that are read are converted by EPL::convert_version instead.
+const EFFECT_FEATURES: [GfdVersion; 17] = [
+    GfdVersion::EplBehaviorUseCurve4ForLife,
+    GfdVersion::EplBehaviorAdjustmentParams,
+    GfdVersion::EplPolygonCircleTrackRGBCurve,
+    GfdVersion::EplPolygonWindMoveTexV,
+    GfdVersion::EplBehaviorSmokeGravity,
+    GfdVersion::EplBehaviorBlurParams,
+    GfdVersion::EplPolygonFlashRingSpread,
+    GfdVersion::EplPolygonBoardPivot,
+    GfdVersion::EplBehaviorRandomColor,
+    GfdVersion::EplLightSceneHasRotate,
+    GfdVersion::EplNoiseBlurSSAOMask,
+    GfdVersion::EplRadialBlurSSAOMask,
+    GfdVersion::EplAddP5RField80,
+    GfdVersion::EplBehaviorGetField4C,
+    GfdVersion::EplModelExtraFields,
+    GfdVersion::EplCurve4Field62,
+    GfdVersion::EplAddMetaphorField60,
+];
+
+/// Data that couldn't be represented at the target version. Each warning describes what was
+/// changed or lost so that it can be shown to the user.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConversionWarning {
+    /// Vertices with more than four weights had their smallest weights removed, with the
+    /// remaining weights renormalized.
+    VertexWeightsTruncated { geometry: usize, vertices: usize },
+    /// The geometry's skin mask was removed, since it's only stored in Metaphor files.
+    SkinMaskDropped { geometry: usize },
+    /// A v2 material type with no v1 equivalent was replaced with Lambert parameters
+    #[cfg(feature = "v2-core")]
+    MaterialTypeReplaced { material: String, material_type: MaterialId },
+    /// v1 material extensions aren't stored in v2 files
+    MaterialExtensionsDropped { material: String },
+    /// Effect leaves had their range, fade or flags reset to what the target version supports
+    EffectLeavesChanged { effect: usize, leaves: usize },
+    /// Effect fields introduced after the target version will be dropped
+    EffectFieldsDropped { effects: usize },
+    /// An imported primitive was left out, since it uses line loops or doesn't have positions
+    PrimitiveSkipped { mesh: String, primitive: usize },
+    /// An imported texture was left out, since its image couldn't be loaded
+    TextureSkipped { texture: String },
+    /// The material type doesn't have a slot for an imported texture (e.g "Normal Texture")
+    TextureSlotUnavailable { material: String, slot: String }
+}
+
+impl Display for ConversionWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::VertexWeightsTruncated { geometry, vertices } => write!(f, "Geometry {}: {} vertices had more than {} weights and were truncated", geometry, vertices, LEGACY_VERTEX_WEIGHT_COUNT),
+            Self::SkinMaskDropped { geometry } => write!(f, "Geometry {}: skin mask was removed", geometry),
+            #[cfg(feature = "v2-core")]
+            Self::MaterialTypeReplaced { material, material_type } => write!(f, "Material {}: {:?} parameters were replaced with Lambert parameters", material, material_type),
+            Self::MaterialExtensionsDropped { material } => write!(f, "Material {}: material extensions were removed", material),
+            Self::EffectLeavesChanged { effect, leaves } => write!(f, "Effect {}: {} leaves have data that can't be stored at the target version", effect, leaves),
+            Self::EffectFieldsDropped { effects } => write!(f, "{} effects have fields that can't be stored at the target version", effects),
+            Self::PrimitiveSkipped { mesh, primitive } => write!(f, "Mesh {}: primitive {} was skipped", mesh, primitive),
+            Self::TextureSkipped { texture } => write!(f, "Texture {}: image couldn't be loaded", texture),
+            Self::TextureSlotUnavailable { material, slot } => write!(f, "Material {}: material type has no {}", material, slot)
+        }
+    }
+}
+
+/// Data that prevents a model from being written at the target version
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConversionError {
+    /// A vertex references a bone index above 255 after its weights were reduced, which can't be
+    /// stored in the legacy vertex weight format
+    BoneIndexOutOfRange { geometry: usize, index: u16 }
+}
+impl Error for ConversionError {}
+impl Display for ConversionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BoneIndexOutOfRange { geometry, index } => write!(f, "Geometry {}: bone index {} can't be stored at the target version", geometry, index)
+        }
+    }
+}
+
+/// Reduce a vertex weight to the four largest influences, renormalizing them so that they still
+/// add up to the same total. Returns true if any influences were removed.
+pub fn reduce_vertex_weight(weight: &mut VertexWeight) -> bool {
+    if weight.weights[LEGACY_VERTEX_WEIGHT_COUNT..].iter().all(|w| *w == 0.) {
+        return false;
+    }
+    let total: f32 = weight.weights.iter().sum();
+    let mut order = [0, 1, 2, 3, 4, 5, 6, 7];
+    order.sort_by(|a, b| weight.weights[*b].total_cmp(&weight.weights[*a]));
+    let mut reduced = VertexWeight::default();
+    for (i, src) in order.into_iter().take(LEGACY_VERTEX_WEIGHT_COUNT).enumerate() {
+        reduced.weights[i] = weight.weights[src];
+        reduced.indices[i] = weight.indices[src];
+    }
+    let kept: f32 = reduced.weights.iter().sum();
+    if kept > 0. {
+        for w in &mut reduced.weights {
+            *w *= total / kept;
+        }
+    }
+    *weight = reduced;
+    true
+}
+
+fn convert_geometry<A>(mesh: &mut Mesh<A>, source: u32, target: u32, warnings: &mut Vec<ConversionWarning>) -> Result<(), ConversionError>
+where A: Allocator + Clone {
+    let legacy_weights = target < GfdVersion::GeometryUseNewVertexWeightFormat as u32;
+    let drop_skin_mask = source >= GfdVersion::GeometryAddMetaphorSkinMask as u32
+        && target < GfdVersion::GeometryAddMetaphorSkinMask as u32;
+    for (i, geometry) in mesh.get_geometry_list_mut().iter_mut().enumerate() {
+        let geometry = unsafe { geometry.as_mut() };
+        if drop_skin_mask && geometry.get_weight_mask() != u8::MAX {
+            geometry.set_weight_mask(u8::MAX);
+            warnings.push(ConversionWarning::SkinMaskDropped { geometry: i });
+        }
+        if !legacy_weights || !geometry.get_flags().contains(GeometryFlags::Skin) {
+            continue;
+        }
+        let mut truncated = 0;
+        if let Some(vertices) = geometry.get_vertex_data_mut() {
+            for weight in vertices.get_weights_mut().iter_mut() {
+                if reduce_vertex_weight(weight) {
+                    truncated += 1;
+                }
+                if let Some((_, index)) = weight.weights.iter().zip(weight.indices.iter())
+                    .take(LEGACY_VERTEX_WEIGHT_COUNT)
+                    .find(|(w, b)| **w != 0. && **b > u8::MAX as u16) {
+                    return Err(ConversionError::BoneIndexOutOfRange { geometry: i, index: *index });
+                }
+            }
+        }
+        if truncated > 0 {
+            warnings.push(ConversionWarning::VertexWeightsTruncated { geometry: i, vertices: truncated });
+        }
+    }
+    Ok(())
+}
+
+fn convert_materials<A>(mesh: &mut Mesh<A>, target: u32, warnings: &mut Vec<ConversionWarning>)
+where A: Allocator + Clone {
+    let v2 = target >= GfdVersion::GFDV2 as u32;
+    for material in mesh.get_material_list_mut().iter_mut() {
+        let material = unsafe { material.as_mut() };
+        let name = material.get_name().unwrap_or_default().to_owned();
+        if v2 {
+            // Extensions aren't read from v2 files, so the flag has to go along with them
+            if material.get_extension().is_some() || material.has_flags(MaterialFlags::Extension) {
+                material.set_flag(MaterialFlags::Extension, false);
+                warnings.push(ConversionWarning::MaterialExtensionsDropped { material: name });
+            }
+            continue;
+        }
+        #[cfg(feature = "v2-core")]
+        {
+            let material_type = material.get_data_type();
+            if material_type == MaterialId::Lambert {
+                continue;
+            }
+            // Field materials have a base color which is the closest thing to a diffuse color
+            let color = material.try_get_field()
+                .map_or(RGBAFloat::from_single_f32(1.), |f| f.get_base_color());
+            material.set_lambert(Lambert::new(color));
+            warnings.push(ConversionWarning::MaterialTypeReplaced { material: name, material_type });
+        }
+    }
+}
+
+fn convert_effects<A>(mesh: &mut Mesh<A>, source: u32, target: u32, warnings: &mut Vec<ConversionWarning>)
+where A: Allocator + Clone {
+    for (i, effect) in mesh.get_effect_list_mut().iter_mut().enumerate() {
+        let leaves = unsafe { effect.as_mut() }.convert_version(source, target);
+        if leaves > 0 {
+            warnings.push(ConversionWarning::EffectLeavesChanged { effect: i, leaves });
+        }
+    }
+    let effects = mesh.get_effect_list().len();
+    if effects > 0 && EFFECT_FEATURES.into_iter().any(|v| target < v as u32 && source >= v as u32) {
+        warnings.push(ConversionWarning::EffectFieldsDropped { effects });
+    }
+}
+
+/// Adjust a mesh that was loaded from a file at version `source` so that it can be written at
+/// version `target`. Anything that had to be changed or can't be stored at the target version
+/// is returned as a list of warnings, while data that can't be written at all is returned as an
+/// error.
+pub fn convert_mesh<A>(mesh: &mut Mesh<A>, source: u32, target: u32) -> Result<Vec<ConversionWarning>, ConversionError>
+where A: Allocator + Clone {
+    let mut warnings = vec![];
+    convert_geometry(mesh, source, target, &mut warnings)?;
+    convert_materials(mesh, target, &mut warnings);
+    convert_effects(mesh, source, target, &mut warnings);
+    Ok(warnings)
+}
+
+/// A model file that was written at a different version
+#[cfg(feature = "serialize")]
+pub struct ConvertedFile<A>
+where A: Allocator + Clone {
+    data: AVec<u8, A>,
+    warnings: Vec<ConversionWarning>
+}
+
+#[cfg(feature = "serialize")]
+impl<A> Debug for ConvertedFile<A>
+where A: Allocator + Clone {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ConvertedFile {{ data: 0x{:x} bytes, warnings: {:?} }}", self.data.len(), self.warnings)
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl<A> ConvertedFile<A>
+where A: Allocator + Clone {
+    pub fn get_data(&self) -> &[u8] { self.data.as_slice() }
+    pub fn get_warnings(&self) -> &[ConversionWarning] { self.warnings.as_slice() }
+}
+
+/// Read a model file and write it back out using the version, platform and byte order in
+/// `header`.
+#[cfg(feature = "serialize")]
+pub fn convert_file<A>(buf: &[u8], header: StreamHeader, allocator: A) -> Result<ConvertedFile<A>, Box<dyn Error>>
+where A: Allocator + Clone + Debug {
+    let factory = StreamFactory::new(allocator.clone());
+    let mut stream = factory.read_from_memory(buf)?;
+    let source = stream.get_header_version();
+    let mut param = SerializationSingleAllocator::new(allocator.clone());
+    let mut mesh = MeshFile::<A>::stream_read(&mut stream, &mut param)?.into_raw();
+    let warnings = convert_mesh(&mut mesh, source, header.get_version())?;
+    write_mesh(&mesh, header, warnings, allocator)
+}
+
+/// Write a mesh using the version, platform and byte order in `header`
+#[cfg(feature = "serialize")]
+pub(crate) fn write_mesh<A>(mesh: &MeshFile<A>, header: StreamHeader, warnings: Vec<ConversionWarning>, allocator: A) -> Result<ConvertedFile<A>, Box<dyn Error>>
+where A: Allocator + Clone + Debug {
+    let factory = StreamFactory::new(allocator.clone());
+    let mut param = SerializationSingleAllocator::new(allocator.clone());
+    let mut out = factory.write_to_memory(header)?;
+    mesh.stream_write(&mut out, &mut param)?;
+    let mut data = AVec::with_capacity_in(out.as_slice().len(), allocator);
+    data.extend_from_slice(out.as_slice());
+    Ok(ConvertedFile { data, warnings })
+}
+
+#[cfg(test)]
+pub mod tests {
+    use allocator_api2::alloc::Global;
+    use crate::object::{
+        geometry::{ Geometry, GeometryVertexData, TriangleIndexFormat, VertexWeight },
+        mesh::MeshFile,
+        node::Node
+    };
+    use glam::{ Vec3, Vec4 };
+    use std::error::Error;
+    use super::{ convert_mesh, reduce_vertex_weight, ConversionError, ConversionWarning };
+
+    type TestReturn = Result<(), Box<dyn Error>>;
+
+    fn create_mesh(weight: Option<VertexWeight>) -> Result<MeshFile<Global>, Box<dyn Error>> {
+        let mut vertices = GeometryVertexData::new(Global);
+        vertices.get_positions_mut().extend([Vec4::W, Vec4::new(1., 0., 0., 1.), Vec4::new(0., 1., 0., 1.)]);
+        vertices.get_normals_mut().extend([Vec3::Z; 3]);
+        if let Some(weight) = weight {
+            vertices.get_weights_mut().extend([weight; 3]);
+        }
+        let geometry = Geometry::new_in(vertices, None, TriangleIndexFormat::ThreeIndicesPerTriangle, Global)?;
+        let mut root = Node::new_in("root", Global);
+        unsafe { root.as_mut().attach_object(geometry.cast()) };
+        let mut mesh = MeshFile::new_in(Global);
+        mesh.set_root_node(root)?;
+        Ok(mesh)
+    }
+
+    #[test]
+    fn reduce_eight_weights_to_four() -> TestReturn {
+        let mut weight = VertexWeight {
+            weights: [0.05, 0.3, 0.05, 0.2, 0.1, 0.1, 0.15, 0.05],
+            indices: [0, 1, 2, 3, 4, 5, 6, 7]
+        };
+        assert!(reduce_vertex_weight(&mut weight));
+        assert_eq!(&weight.indices[..4], &[1, 3, 6, 4]);
+        assert_eq!(&weight.weights[4..], &[0.; 4]);
+        assert!((weight.weights.iter().sum::<f32>() - 1.).abs() < 1e-5);
+        assert!(weight.weights[0] > weight.weights[1]);
+        let mut legacy = VertexWeight {
+            weights: [0.5, 0.5, 0., 0., 0., 0., 0., 0.],
+            indices: [2, 3, 0, 0, 0, 0, 0, 0]
+        };
+        let copy = legacy;
+        assert!(!reduce_vertex_weight(&mut legacy));
+        assert_eq!(legacy, copy);
+        Ok(())
+    }
+
+    #[test]
+    fn convert_mesh_vertex_weights() -> TestReturn {
+        let weight = VertexWeight {
+            weights: [0.3, 0.2, 0.1, 0.1, 0.1, 0.1, 0.05, 0.05],
+            indices: [0, 1, 2, 3, 4, 5, 6, 7]
+        };
+        // v2 files can store every weight
+        let mut mesh = create_mesh(Some(weight))?;
+        assert!(convert_mesh(&mut mesh, 0x2110000, 0x2110000)?.is_empty());
+        let warnings = convert_mesh(&mut mesh, 0x2110000, 0x1105100)?;
+        assert_eq!(warnings, vec![ConversionWarning::VertexWeightsTruncated { geometry: 0, vertices: 3 }]);
+        let geometry = unsafe { mesh.get_geometry_list()[0].as_ref() };
+        assert!(geometry.get_vertex_data().unwrap().get_weights().iter().all(|w| w.weights[4..] == [0.; 4]));
+        // Bone indices above 255 can't be written to v1 files
+        let mut weight = weight;
+        weight.indices[1] = 300;
+        let mut mesh = create_mesh(Some(weight))?;
+        assert_eq!(convert_mesh(&mut mesh, 0x2110000, 0x1105100), Err(ConversionError::BoneIndexOutOfRange { geometry: 0, index: 300 }));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(all(feature = "serialize", feature = "v2-core"))]
+    fn convert_file_between_versions() -> TestReturn {
+        use crate::{
+            graphics::{
+                material::{ params::MaterialId, MaterialFlags },
+                material_builder::MaterialBuilder
+            },
+            utility::stream::{ DevicePlatform, GfdSerialize, SerializationSingleAllocator, StreamFactory, StreamHeader }
+        };
+        use super::{ convert_file, write_mesh };
+
+        let mut mesh = create_mesh(None)?;
+        let mut material = MaterialBuilder::with_type("field", MaterialId::Field, Global).build()?;
+        unsafe { material.as_mut() }.set_flag(MaterialFlags::Extension, true);
+        mesh.add_material(material)?;
+        // Metaphor to P5R
+        let warnings = convert_mesh(&mut mesh, 0x2110000, 0x1105100)?;
+        assert_eq!(warnings, vec![ConversionWarning::MaterialTypeReplaced { material: "field".to_owned(), material_type: MaterialId::Field }]);
+        let v1 = write_mesh(&mesh, StreamHeader::new(0x1105100, DevicePlatform::P5R_DX11), warnings, Global)?;
+        // and back again
+        let v2 = convert_file(v1.get_data(), StreamHeader::new(0x2110000, DevicePlatform::METAPHOR), Global)?;
+        assert_eq!(v2.get_warnings(), &[ConversionWarning::MaterialExtensionsDropped { material: "field".to_owned() }]);
+        let mut stream = StreamFactory::new(Global).read_from_memory(v2.get_data())?;
+        assert_eq!(stream.get_header_version(), 0x2110000);
+        let mesh = MeshFile::<Global>::stream_read(&mut stream, &mut SerializationSingleAllocator::new(Global))?.into_raw();
+        assert_eq!(mesh.get_geometry_list().len(), 1);
+        let material = unsafe { mesh.get_material_list()[0].as_ref() };
+        assert_eq!(material.get_data_type(), MaterialId::Lambert);
+        assert!(!material.has_flags(MaterialFlags::Extension));
+        Ok(())
+    }
+}
@@ -25,7 +25,7 @@ use crate::graphics::cull::CullObject;
 use crate::graphics::curve::CurveType;
 use crate::kernel::version::GfdVersion;
 use crate::object::object::{CastFromObject, ObjectId};
-use crate::utility::name::{NameSerializationContext, NameSerializationHash};
+use crate::utility::name::{NameSerializationContext, NameSerializationHash, NameSerializationTechnique};
 use crate::utility::stream::{DeserializationHeap, DeserializationStrategy, GfdSerializationUserData, GfdSerialize, SerializationSingleAllocator, Stream, StreamIODevice};
 
 #[derive(Debug)]
@@ -119,6 +119,27 @@ where A: Allocator + Clone
     const OBJECT_ID: ObjectId = ObjectId::EPL;
 }
 
+impl<A> EPL<A>
+where A: Allocator + Clone
+{
+    pub fn get_leaves(&self) -> &[NonNull<EPLLeaf<A>>] {
+        self.leaves.map_or(&[], |v| unsafe { v.as_ref().as_slice() })
+    }
+    pub fn get_leaves_mut(&mut self) -> &mut [NonNull<EPLLeaf<A>>] {
+        self.leaves.map_or(&mut [], |mut v| unsafe { v.as_mut().as_slice_mut() })
+    }
+
+    /// Adjust each leaf so that the effect can be written at version `target` after being read
+    /// at version `source`. Returns the number of leaves that had data which can't be stored at
+    /// the target version.
+    pub(crate) fn convert_version(&mut self, source: u32, target: u32) -> usize {
+        self.get_leaves_mut().iter_mut()
+            .map(|v| unsafe { v.as_mut() }.convert_version(source, target))
+            .filter(|dropped| *dropped)
+            .count()
+    }
+}
+
 #[cfg(feature = "serialize")]
 impl<AStream, AObject, T> GfdSerialize<AStream, T, AObject, DeserializationHeap<Self, AObject>, SerializationSingleAllocator<AObject>> for EPL<AObject>
 where T: Debug + Read + Write + Seek + StreamIODevice,
@@ -134,6 +155,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream, param)?;
         Ok(this)
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -149,6 +174,16 @@ where AObject: Allocator + Clone
         // read hierarchy
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug
+    {
+        stream.write_u32(self.flag.bits())?;
+        // write hierarchy
+        Ok(())
+    }
 }
 
 bitflags! {
@@ -213,6 +248,31 @@ where A: Allocator + Clone
     const OBJECT_ID: ObjectId = ObjectId::EPLLeaf;
 }
 
+impl<A> EPLLeaf<A>
+where A: Allocator + Clone
+{
+    /// Adjust the leaf so that it can be written at version `target` after being read at version
+    /// `source`. Fields added between the two versions are reset to the values that the game
+    /// uses when loading an older leaf. Returns true if any data was lost.
+    pub(crate) fn convert_version(&mut self, source: u32, target: u32) -> bool {
+        let mut dropped = false;
+        let range_fade = GfdVersion::EplLeafHasRangeFade as u32;
+        if source >= range_fade && target < range_fade {
+            dropped |= self.range != Range::default() || self.fade != Fade::default();
+            self.range = Range::default();
+            self.fade = Fade::default();
+        }
+        // Leaves older than EplLeafFlag3004 get these flags set when they're loaded
+        let legacy_flags = EplLeafFlags::Flag13 | EplLeafFlags::Flag12 | EplLeafFlags::Flag2;
+        let flag_3004 = GfdVersion::EplLeafFlag3004 as u32;
+        if source >= flag_3004 && target < flag_3004 {
+            dropped |= !self.flags.contains(legacy_flags);
+            self.flags |= legacy_flags;
+        }
+        dropped
+    }
+}
+
 #[repr(C)]
 #[derive(Debug)]
 pub struct EPLParts {
@@ -231,6 +291,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream, param)?;
         Ok(this)
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -251,4 +315,18 @@ where AObject: Allocator + Clone
         self.name = Name::<AObject>::stream_read(stream, &mut NameSerializationContext::new(param.get_heap_allocator().unwrap(), NameSerializationHash))?.into_raw();
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug
+    {
+        if stream.has_feature(GfdVersion::EplLeafHasRangeFade).is_some() {
+            self.range.stream_write(stream, &mut ())?;
+            self.fade.stream_write(stream, &mut ())?;
+        }
+        stream.write_u32(self.flags.bits())?;
+        NameSerializationHash.stream_write_inner(stream, &self.name)?;
+        Ok(())
+    }
 }
\ No newline at end of file
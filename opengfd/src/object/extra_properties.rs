@@ -0,0 +1,198 @@
+use allocator_api2::alloc::Allocator;
+use allocator_api2::vec::Vec as AVec;
+use crate::{
+    kernel::allocator::GfdAllocator,
+    utility::{
+        name::Name,
+        property::{ InvalidValueType, ValueType }
+    }
+};
+#[cfg(feature = "serialize")]
+use crate::utility::{
+    name::{ NameSerializationContext, NameSerializationHash, NameSerializationTechnique },
+    stream::{
+        ChunkError, ChunkSerializationContext, ChunkType,
+        DeserializationHeap, GfdSerializationUserData, GfdSerialize,
+        Stream, StreamIODevice
+    }
+};
+use std::{
+    error::Error,
+    fmt::Debug,
+    io::{ Read, Seek, Write }
+};
+#[cfg(feature = "serialize")]
+use std::io::SeekFrom;
+
+/// A single named value in an ExtraProperties chunk. These use the same layout as property
+/// chunks, but the value is kept as it's stored in the file so that unknown value types are
+/// written back out unchanged.
+#[derive(Debug)]
+pub struct ExtraProperty<A = GfdAllocator>
+where A: Allocator + Clone
+{
+    value_type: u32,
+    name: Name<A>,
+    data: AVec<u8, A>
+}
+
+impl<A> ExtraProperty<A>
+where A: Allocator + Clone
+{
+    pub fn new(value_type: ValueType, name: Name<A>, data: AVec<u8, A>) -> Self {
+        Self { value_type: value_type as u32, name, data }
+    }
+    pub fn get_value_type(&self) -> Result<ValueType, InvalidValueType> {
+        self.value_type.try_into()
+    }
+    pub fn get_name(&self) -> Option<&str> { self.name.get_string() }
+    pub fn get_data(&self) -> &[u8] { self.data.as_slice() }
+    pub fn get_data_mut(&mut self) -> &mut AVec<u8, A> { &mut self.data }
+}
+
+/// Extra property blocks stored in a model's ExtraProperties chunk. Anything in the chunk after
+/// the entries is kept as it is in the file so that it's written back out unchanged.
+pub struct ExtraProperties<A = GfdAllocator>
+where A: Allocator + Clone
+{
+    entries: AVec<ExtraProperty<A>, A>,
+    trailing_data: AVec<u8, A>
+}
+
+impl<A> Debug for ExtraProperties<A>
+where A: Allocator + Clone
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ExtraProperties {{ entries: {:?}, trailing_data: 0x{:x} bytes }}", self.entries, self.trailing_data.len())
+    }
+}
+
+impl<A> ExtraProperties<A>
+where A: Allocator + Clone
+{
+    pub fn get_entries(&self) -> &[ExtraProperty<A>] { self.entries.as_slice() }
+    pub fn get_entries_mut(&mut self) -> &mut AVec<ExtraProperty<A>, A> { &mut self.entries }
+    /// Get the first entry with a matching name
+    pub fn find(&self, name: &str) -> Option<&ExtraProperty<A>> {
+        self.entries.iter().find(|e| e.get_name() == Some(name))
+    }
+    /// Data stored after the entries, which is written back out unchanged
+    pub fn get_trailing_data(&self) -> &[u8] { self.trailing_data.as_slice() }
+}
+
+#[cfg(feature = "serialize")]
+impl<AStream, AObject, T> GfdSerialize<AStream, T, AObject, DeserializationHeap<Self, AObject>, ChunkSerializationContext<AObject>> for ExtraProperties<AObject>
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      AStream: Allocator + Clone + Debug,
+      AObject: Allocator + Clone
+{
+    fn stream_read(stream: &mut Stream<AStream, T>, param: &mut ChunkSerializationContext<AObject>) -> Result<DeserializationHeap<Self, AObject>, Box<dyn Error>> {
+        let allocator = param.get_heap_allocator().unwrap();
+        let length = param.get_length();
+        let invalid_length = || Box::new(ChunkError::InvalidLength(ChunkType::ExtraProperties, length as u32));
+        if length < size_of::<u32>() {
+            return Err(invalid_length());
+        }
+        let start = stream.seek(SeekFrom::Current(0))?;
+        let num_entries = stream.read_u32()?;
+        let mut entries = AVec::new_in(allocator.clone());
+        for _ in 0..num_entries {
+            entries.push(ExtraProperty::stream_read_inner(stream, allocator.clone(), start + length as u64)?);
+        }
+        let read = (stream.seek(SeekFrom::Current(0))? - start) as usize;
+        let trailing = length.checked_sub(read).ok_or_else(invalid_length)?;
+        let mut trailing_data = AVec::with_capacity_in(trailing, allocator.clone());
+        trailing_data.resize(trailing, 0);
+        stream.read_u8_slice(trailing_data.as_mut_slice())?;
+        Ok(DeserializationHeap::new(Self { entries, trailing_data }, allocator))
+    }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ChunkSerializationContext<AObject>) -> Result<(), Box<dyn Error>> {
+        stream.write_u32(self.entries.len() as u32)?;
+        for entry in &self.entries {
+            entry.stream_write_inner(stream)?;
+        }
+        stream.write_u8_slice(self.trailing_data.as_slice())?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl<AObject> ExtraProperty<AObject>
+where AObject: Allocator + Clone
+{
+    fn stream_read_inner<AStream, T>(stream: &mut Stream<AStream, T>, allocator: AObject, end: u64) -> Result<Self, Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        let value_type = stream.read_u32()?;
+        let name = Name::<AObject>::stream_read(stream, &mut NameSerializationContext::new(allocator.clone(), NameSerializationHash))?.into_raw();
+        let size = stream.read_u32()? as u64;
+        // Don't trust the size to allocate the value before checking that it fits in the chunk
+        if stream.seek(SeekFrom::Current(0))? + size > end {
+            return Err(Box::new(ChunkError::InvalidLength(ChunkType::ExtraProperties, size as u32)));
+        }
+        let mut data = AVec::with_capacity_in(size as usize, allocator);
+        data.resize(size as usize, 0);
+        stream.read_u8_slice(data.as_mut_slice())?;
+        Ok(Self { value_type, name, data })
+    }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        stream.write_u32(self.value_type)?;
+        NameSerializationHash.stream_write_inner(stream, &self.name)?;
+        stream.write_u32(self.data.len() as u32)?;
+        stream.write_u8_slice(self.data.as_slice())?;
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "serialize"))]
+pub mod tests {
+    use allocator_api2::alloc::Global;
+    use std::error::Error;
+    use crate::utility::{
+        name::{ Name, NameSerializationHash, NameSerializationTechnique },
+        property::ValueType,
+        stream::{ ChunkSerializationContext, DevicePlatform, GfdSerialize, StreamFactory, StreamHeader }
+    };
+    use super::ExtraProperties;
+
+    type TestReturn = Result<(), Box<dyn Error>>;
+
+    #[test]
+    fn extra_properties_round_trip() -> TestReturn {
+        let factory = StreamFactory::new(Global);
+        let mut stream = factory.write_to_memory(StreamHeader::new(0x2110000, DevicePlatform::METAPHOR))?;
+        let header_size = stream.as_slice().len();
+        stream.write_u32(2)?;
+        stream.write_u32(ValueType::Float as u32)?;
+        NameSerializationHash.stream_write_inner(&mut stream, &Name::new("scale"))?;
+        stream.write_u32(4)?;
+        stream.write_f32(1.5)?;
+        // unknown value type
+        stream.write_u32(0x20)?;
+        NameSerializationHash.stream_write_inner(&mut stream, &Name::new("unknown"))?;
+        stream.write_u32(3)?;
+        stream.write_u8_slice(&[1, 2, 3])?;
+        stream.write_u8_slice(&[0xde, 0xad])?;
+        let written = stream.as_slice().to_vec();
+        let mut stream = factory.read_from_memory(&written)?;
+        let mut param = ChunkSerializationContext::new(Global, written.len() - header_size);
+        let extra = ExtraProperties::<Global>::stream_read(&mut stream, &mut param)?.into_raw();
+        let extra = unsafe { extra.as_ref() };
+        assert_eq!(extra.get_entries().len(), 2);
+        let scale = extra.find("scale").unwrap();
+        assert_eq!(scale.get_value_type()?, ValueType::Float);
+        assert_eq!(scale.get_data().len(), size_of::<f32>());
+        let unknown = extra.find("unknown").unwrap();
+        assert!(unknown.get_value_type().is_err());
+        assert_eq!(unknown.get_data(), &[1, 2, 3]);
+        assert_eq!(extra.get_trailing_data(), &[0xde, 0xad]);
+        let mut out = factory.write_to_memory(StreamHeader::new(0x2110000, DevicePlatform::METAPHOR))?;
+        extra.stream_write(&mut out, &mut param)?;
+        assert_eq!(out.as_slice(), written.as_slice());
+        Ok(())
+    }
+}
@@ -1,7 +1,10 @@
+use std::collections::HashMap;
 use std::error::Error;
 use std::fmt::{Debug, Display, Formatter};
-use std::io::{Read, Seek, SeekFrom, Write};
+use std::io::{self, Read, Seek, Write};
 use allocator_api2::alloc::Allocator;
+use allocator_api2::boxed::Box as ABox;
+use allocator_api2::vec::Vec as AVec;
 use bitflags::bitflags;
 use crate::{
     device::ngr::renderer::state::{
@@ -20,24 +23,50 @@ use crate::{
     },
     utility::{
         misc::{ BoundingBox, BoundingSphere },
-        reference::{ GfdRcType, Reference }
+        reference::{ GfdRcType, Reference },
+        side_table::SideTable
     }
 };
-use glam::{Vec2, Vec3, Vec3A, Vec4};
+use glam::{Mat4, Vec2, Vec3, Vec3A, Vec4};
 use opengfd_proc::GfdRcAuto;
 use std::ptr::NonNull;
 use half::f16;
 use crate::kernel::version::GfdVersion;
-use crate::object::morph::MorphTarget;
+use crate::graphics::skin::{SkinError, SkinPalette, SkinnedVertices};
+use crate::object::morph::{MorphError, MorphTarget};
 use crate::object::object::{CastFromObject, ObjectId};
 use crate::utility::misc::RGBA;
-use crate::utility::name::{Name, NameSerializationContext, NameSerializationHash};
+use crate::utility::name::{Name, NameSerializationContext, NameSerializationHash, NameSerializationTechnique};
 use crate::utility::stream::{DeserializationHeap, DeserializationStrategy, GfdSerializationUserData, GfdSerialize, SerializationSingleAllocator, Stream, StreamIODevice};
 
 #[derive(Debug)]
 pub enum GeometryError {
     InvalidTriangleIndexType(u16),
     InvalidTriangleIndexFormat(u8),
+    /// The geometry has no vertex data to calculate tangents from
+    MissingVertexData,
+    /// Every vertex needs a normal to calculate tangents
+    MissingNormals,
+    /// Every vertex needs a texture coordinate in the channel to calculate tangents
+    MissingTexCoords(usize),
+    /// A triangle refers to a vertex past the end of the vertex data
+    InvalidVertexIndex(u32),
+    /// A vertex attribute in the geometry's flags doesn't have a value for every vertex
+    MissingVertexAttribute(VertexAttributeFlags),
+    /// Skinned geometry doesn't have a weight for every vertex
+    MissingVertexWeights,
+    /// There aren't enough indices to make a single triangle with the primitive type
+    NotEnoughIndices(u32),
+    /// The geometry has triangles but doesn't have an index for each triangle corner
+    MissingIndexData,
+    /// A bone index is too large for the vertex weight format being written
+    BoneIndexOutOfRange(u16),
+    /// A vertex index is too large for the geometry's TriangleIndexType
+    IndexOutOfRange(u32),
+    /// MikkTSpace couldn't calculate tangents for the triangles
+    TangentGenerationFailed,
+    /// The number of indices needed for this many triangles doesn't fit in the geometry
+    TooManyTriangles(u32)
 }
 impl Error for GeometryError {}
 impl Display for GeometryError {
@@ -131,26 +160,30 @@ impl TryFrom<u8> for TriangleIndexFormat {
 }
 
 impl TriangleIndexFormat {
-    pub fn from_vertex_count(&self, vertices: u32) -> u32 {
+    /// Get the number of triangles made from the given number of indices, or None if there
+    /// aren't enough indices for a single triangle
+    pub fn from_vertex_count(&self, vertices: u32) -> Option<u32> {
         match self {
-            Self::OneIndexPerTriangle => vertices,
-            Self::TwoIndicesPerTriangle => vertices / 2,
-            Self::OneIndexMinusOnePerTriangle => vertices - 1,
-            Self::ThreeIndicesPerTriangle => vertices / 3,
-            Self::OneIndexMinusTwoPerTriangle4 => vertices - 2,
-            Self::OneIndexMinusTwoPerTriangle5 => vertices - 2,
-        }
+            Self::OneIndexPerTriangle => Some(vertices),
+            Self::TwoIndicesPerTriangle => Some(vertices / 2),
+            Self::OneIndexMinusOnePerTriangle => vertices.checked_sub(1),
+            Self::ThreeIndicesPerTriangle => Some(vertices / 3),
+            Self::OneIndexMinusTwoPerTriangle4 => vertices.checked_sub(2),
+            Self::OneIndexMinusTwoPerTriangle5 => vertices.checked_sub(2),
+        }.filter(|v| *v > 0)
     }
 
-    pub fn to_vertex_count(&self, vertices: u32) -> u32 {
+    /// Get the number of indices needed for the given number of triangles. This is the inverse of
+    /// from_vertex_count.
+    pub fn to_vertex_count(&self, triangles: u32) -> Result<u32, GeometryError> {
         match self {
-            Self::OneIndexPerTriangle => vertices,
-            Self::TwoIndicesPerTriangle => vertices * 2,
-            Self::OneIndexMinusOnePerTriangle => vertices - 1,
-            Self::ThreeIndicesPerTriangle => vertices * 3,
-            Self::OneIndexMinusTwoPerTriangle4 => vertices + 2,
-            Self::OneIndexMinusTwoPerTriangle5 => vertices + 2,
-        }
+            Self::OneIndexPerTriangle => Some(triangles),
+            Self::TwoIndicesPerTriangle => triangles.checked_mul(2),
+            Self::OneIndexMinusOnePerTriangle => triangles.checked_add(1),
+            Self::ThreeIndicesPerTriangle => triangles.checked_mul(3),
+            Self::OneIndexMinusTwoPerTriangle4 => triangles.checked_add(2),
+            Self::OneIndexMinusTwoPerTriangle5 => triangles.checked_add(2),
+        }.filter(|v| *v <= i32::MAX as u32).ok_or(GeometryError::TooManyTriangles(triangles))
     }
 }
 
@@ -257,9 +290,93 @@ where A: Allocator + Clone
     _allocator: A
 }
 
+// Data read from a GFS file that the game doesn't keep once the geometry's buffers are created.
+// This is stored in GEOMETRY_DATA so that the geometry can be written back out. It's only created
+// through &mut Geometry and removed when the geometry is dropped, so get_data can borrow it for as
+// long as the geometry is borrowed.
+struct GeometryData<A>
+where A: Allocator + Clone
+{
+    weight_mask: u8,
+    material_name: Option<Name<A>>,
+    vertices: Option<GeometryVertexData<A>>,
+    indices: Option<GeometryIndexData<A>>
+}
+
+impl<A> GeometryData<A>
+where A: Allocator + Clone
+{
+    fn new() -> Self {
+        Self { weight_mask: u8::MAX, material_name: None, vertices: None, indices: None }
+    }
+}
+
+static GEOMETRY_DATA: SideTable = SideTable::new();
+
 impl<A> Geometry<A>
 where A: Allocator + Clone
 {
+    // Set the fields that aren't zero on a new geometry
+    fn initialize(&mut self) {
+        unsafe { self.super_.set_id(ObjectId::Geometry) };
+        self.ref_ = Reference::new();
+        for i in 0..3 {
+            self.cull[i] = CullObject::new(1, 1, 0, 0.);
+        }
+        self.field42_0x18e = 1;
+        self.blend_dst_color = 1;
+        self.color_mask = 0xf;
+        self.stencil_func = ComparisonFunc::Always;
+        self.stencil_mask = 0xff;
+        self.field50_0x19c = 7;
+        self.field52_0x1a0 = 1;
+        self.field53_0x1a1 = 1;
+        self.field1a2 = 3;
+    }
+
+    fn free_morph_target(&mut self) {
+        if let Some(target) = NonNull::new(self.morph_targets as *mut MorphTarget<A>) {
+            drop(unsafe { ABox::from_raw_in(target.as_ptr(), self._allocator.clone()) });
+            self.morph_targets = std::ptr::null_mut();
+        }
+    }
+
+    fn get_data(&self) -> Option<&GeometryData<A>> {
+        unsafe { GEOMETRY_DATA.get::<_, GeometryData<A>>(self as *const Self).map(|v| v.as_ref()) }
+    }
+    fn get_data_mut(&mut self) -> &mut GeometryData<A> {
+        let mut data = unsafe { GEOMETRY_DATA.get_or_insert_with(self as *const Self, GeometryData::<A>::new) };
+        unsafe { data.as_mut() }
+    }
+
+    /// Create a geometry from vertex data and (optionally) indices using the given primitive
+    /// type. The vertex attributes are taken from the vertex arrays that aren't empty, and the
+    /// geometry is skinned if it has vertex weights.
+    pub fn new_in(vertices: GeometryVertexData<A>, indices: Option<GeometryIndexData<A>>, prim: TriangleIndexFormat, allocator: A) -> Result<NonNull<Self>, GeometryError> {
+        let num_triangles = match indices.as_ref().filter(|v| !v.is_empty()) {
+            Some(v) => Some(prim.from_vertex_count(v.len() as u32).ok_or(GeometryError::NotEnoughIndices(v.len() as u32))?),
+            None => None
+        };
+        let mut this: ABox<Self, A> = unsafe { ABox::new_zeroed_in(allocator.clone()).assume_init() };
+        this.initialize();
+        unsafe { std::ptr::write(&raw mut this._allocator, allocator) };
+        this.fvf = vertices.get_attribute_flags();
+        this.num_vertices = vertices.len() as i32;
+        if !vertices.get_weights().is_empty() {
+            this.flags |= GeometryFlags::Skin;
+        }
+        this.prim = prim;
+        if let (Some(indices), Some(num_triangles)) = (indices, num_triangles) {
+            this.flags |= GeometryFlags::Triangles;
+            this.index = indices.get_index_type();
+            this.num_indices = indices.len() as i32;
+            this.num_triangles = num_triangles as i32;
+            this.get_data_mut().indices = Some(indices);
+        }
+        this.get_data_mut().vertices = Some(vertices);
+        Ok(unsafe { NonNull::new_unchecked(ABox::into_raw(this)) })
+    }
+
     pub fn vertex_sizeof_metaphor(&self) -> usize {
         let mut sizeof = if self.fvf.contains(VertexAttributeFlags::PositionXYZW) {
             size_of::<Vec4>()
@@ -308,23 +425,14 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
 {
     fn stream_read(stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<DeserializationHeap<Self, AObject>, Box<dyn Error>> {
         let mut this = DeserializationHeap::<Self, AObject>::zeroed(param);
-        unsafe { this.super_.set_id(ObjectId::Geometry) };
-        this.ref_ = Reference::new();
-        for i in 0..3 {
-            this.cull[i] = CullObject::new(1, 1, 0, 0.);
-        }
-        this.field42_0x18e = 1;
-        this.blend_dst_color = 1;
-        this.color_mask = 0xf;
-        this.stencil_func = ComparisonFunc::Always;
-        this.stencil_mask = 0xff;
-        this.field50_0x19c = 7;
-        this.field52_0x1a0 = 1;
-        this.field53_0x1a1 = 1;
-        this.field1a2 = 3;
+        this.initialize();
         this.stream_read_inner(stream, param)?;
         Ok(this)
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream, param)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -362,45 +470,36 @@ where AObject: Allocator + Clone
             .map_or::<Result<i32, Box<dyn Error>>, _>(
                 Ok(0), |_| Ok(stream.read_u32()? as i32)
             )?;
-        // Add vertex attributes
-        let vertex_sizeof = stream.
-            has_feature(GfdVersion::GFDV2).
-            map_or_else(
-                || self.vertex_sizeof_p5r(),
-                |_| self.vertex_sizeof_metaphor()
-            );
-        // Add vertex weighting
-        let vertex_sizeof = vertex_sizeof + match self.flags.contains(GeometryFlags::Skin) {
-            true => stream
-                .has_feature(GfdVersion::GeometryUseNewVertexWeightFormat)
-                .map_or_else(
-                    || size_of::<[u32; 0x4]>() + size_of::<[u8; 0x4]>(),
-                    |_| size_of::<[f16; 0x8]>() + size_of::<[u16; 0x8]>()
-                ),
+        self.index = index_format;
+        self.num_indices = match self.flags.contains(GeometryFlags::Triangles) {
+            true => self.prim.to_vertex_count(num_triangles)? as i32,
             false => 0
         };
+        // Read vertices
+        let allocator = param.get_heap_allocator().unwrap();
+        let mut context = GeometryBufferContext::new(allocator.clone(), self.fvf, self.flags, self.index, self.num_vertices as usize);
+        let mut vertices = GeometryVertexData::new(allocator.clone());
+        vertices.stream_read_inner(stream, &mut context)?;
+        self.get_data_mut().vertices = Some(vertices);
         // Get skin mask
-        stream.seek(SeekFrom::Current(self.num_vertices as i64 * vertex_sizeof as i64))?;
-        let weigh_mask = stream
+        self.get_data_mut().weight_mask = stream
             .has_feature(GfdVersion::GeometryAddMetaphorSkinMask)
             .map_or::<Result<u8, Box<dyn Error>>, _>(
                 Ok(u8::MAX), |_| Ok(stream.read_u8()?)
             )?;
         // Read morph targets
         if self.flags.contains(GeometryFlags::MorphTargets) {
-            let _ = MorphTarget::<AObject>::stream_read(stream, param);
+            self.morph_targets = MorphTarget::<AObject>::stream_read(stream, param)?.into_raw().as_ptr() as *mut std::os::raw::c_void;
         }
         // Read triangles
         if self.flags.contains(GeometryFlags::Triangles) {
-            match self.index {
-                TriangleIndexType::None => (),
-                TriangleIndexType::UInt16 => { let _ = stream.seek(SeekFrom::Current(size_of::<u16>() as i64 * self.num_triangles as i64))?; },
-                TriangleIndexType::UInt32 => { let _ = stream.seek(SeekFrom::Current(size_of::<u32>() as i64 * self.num_triangles as i64))?; },
-            };
+            let mut context = GeometryBufferContext::new(allocator.clone(), self.fvf, self.flags, self.index, self.num_indices as usize);
+            self.get_data_mut().indices = Some(GeometryIndexData::stream_read_inner(stream, &mut context)?);
         }
         // Read material info
         if self.flags.contains(GeometryFlags::Material) {
-            let material_name = Name::<AObject>::stream_read(stream, &mut NameSerializationContext::new(param.get_heap_allocator().unwrap(), NameSerializationHash))?.into_raw();
+            let name = Name::<AObject>::stream_read(stream, &mut NameSerializationContext::new(param.get_heap_allocator().unwrap(), NameSerializationHash))?.into_raw();
+            self.get_data_mut().material_name = Some(name);
         }
         // Read bounding box
         if self.flags.contains(GeometryFlags::BoundingBox) {
@@ -429,6 +528,62 @@ where AObject: Allocator + Clone
         }
         Ok(())
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        stream.write_u32(self.flags.bits())?;
+        stream.write_u32(self.fvf.bits())?;
+        if self.flags.contains(GeometryFlags::Triangles) {
+            stream.write_u32(self.num_triangles as u32)?;
+            stream.write_u16(self.index as u16)?;
+        }
+        stream.write_u32(self.num_vertices as u32)?;
+        if stream.has_feature(GfdVersion::EnvAddInfiniteOcean_LUTRecolorParams).is_some() {
+            stream.write_u8(self.prim as u8)?;
+        }
+        if stream.has_feature(GfdVersion::GeometryAddGeomType).is_some() {
+            stream.write_u32(self.type_ as u32)?;
+        }
+        // Write vertices
+        let allocator = param.get_heap_allocator().unwrap();
+        let mut context = GeometryBufferContext::new(allocator.clone(), self.fvf, self.flags, self.index, self.num_vertices as usize);
+        match self.get_vertex_data() {
+            Some(v) => v.stream_write(stream, &mut context)?,
+            None if self.num_vertices == 0 => (),
+            None => return Err(Box::new(GeometryError::MissingVertexData))
+        };
+        if stream.has_feature(GfdVersion::GeometryAddMetaphorSkinMask).is_some() {
+            stream.write_u8(self.get_weight_mask())?;
+        }
+        // Write morph targets
+        if self.flags.contains(GeometryFlags::MorphTargets) {
+            unsafe { &*(self.morph_targets as *const MorphTarget<AObject>) }.stream_write(stream, param)?;
+        }
+        // Write triangles
+        if self.flags.contains(GeometryFlags::Triangles) {
+            let mut context = GeometryBufferContext::new(allocator.clone(), self.fvf, self.flags, self.index, self.num_indices as usize);
+            self.get_index_data().ok_or(GeometryError::MissingIndexData)?.stream_write(stream, &mut context)?;
+        }
+        // Write material info
+        if self.flags.contains(GeometryFlags::Material) {
+            match self.get_data().and_then(|v| v.material_name.as_ref()) {
+                Some(name) => NameSerializationHash.stream_write_inner(stream, name)?,
+                None => NameSerializationHash.stream_write_inner(stream, &Name::new_in("", allocator))?
+            };
+        }
+        if self.flags.contains(GeometryFlags::BoundingBox) {
+            self.bounding_box.stream_write(stream, &mut ())?;
+        }
+        if self.flags.contains(GeometryFlags::BoundingSphere) {
+            self.bounding_sphere.stream_write(stream, &mut ())?;
+        }
+        if self.flags.contains(GeometryFlags::Lod) {
+            stream.write_f32(self.lod_start)?;
+            stream.write_f32(self.lod_end)?;
+        }
+        Ok(())
+    }
 }
 
 #[repr(C)]
@@ -490,7 +645,210 @@ where A: Allocator + Clone
         self.color_mask
     }
 
-    // pub fn get_indices(&self) -> &[u8]
+    pub fn get_flags(&self) -> GeometryFlags {
+        self.flags
+    }
+    pub fn get_vertex_attributes(&self) -> VertexAttributeFlags {
+        self.fvf
+    }
+    pub fn get_triangle_format(&self) -> TriangleIndexFormat {
+        self.prim
+    }
+
+    /// Metaphor's skin mask. Files before GeometryAddMetaphorSkinMask don't store this, so it's
+    /// set to u8::MAX when reading them.
+    pub fn get_weight_mask(&self) -> u8 {
+        self.get_data().map_or(u8::MAX, |v| v.weight_mask)
+    }
+    pub fn set_weight_mask(&mut self, mask: u8) {
+        self.get_data_mut().weight_mask = mask;
+    }
+
+    /// Get the vertex attributes decoded from a GFS file
+    pub fn get_vertex_data(&self) -> Option<&GeometryVertexData<A>> {
+        self.get_data().and_then(|v| v.vertices.as_ref())
+    }
+    pub fn get_vertex_data_mut(&mut self) -> Option<&mut GeometryVertexData<A>> {
+        self.get_data_mut().vertices.as_mut()
+    }
+    /// Get the triangle indices decoded from a GFS file
+    pub fn get_index_data(&self) -> Option<&GeometryIndexData<A>> {
+        self.get_data().and_then(|v| v.indices.as_ref())
+    }
+    pub fn get_index_data_mut(&mut self) -> Option<&mut GeometryIndexData<A>> {
+        self.get_data_mut().indices.as_mut()
+    }
+    /// Get the name of the material that this geometry was bound to in a GFS file
+    pub fn get_material_name(&self) -> Option<&str> {
+        self.get_data().and_then(|v| v.material_name.as_ref()).and_then(|v| v.get_string())
+    }
+
+    pub(crate) fn set_material_raw(&mut self, material: Option<NonNull<Material<A>>>) {
+        self.material = material;
+    }
+    /// Draw the geometry using a material. The material is stored by name in the file, so it
+    /// should also be in the mesh's material list.
+    pub fn set_material(&mut self, material: NonNull<Material<A>>) {
+        let name = Name::new_in(unsafe { material.as_ref() }.get_name().unwrap_or_default(), self._allocator.clone());
+        self.get_data_mut().material_name = Some(name);
+        self.material = Some(material);
+        self.flags |= GeometryFlags::Material;
+    }
+
+    pub fn get_bounding_box(&self) -> Option<&BoundingBox> {
+        self.flags.contains(GeometryFlags::BoundingBox).then_some(&self.bounding_box)
+    }
+    pub fn get_bounding_sphere(&self) -> Option<&BoundingSphere> {
+        self.flags.contains(GeometryFlags::BoundingSphere).then_some(&self.bounding_sphere)
+    }
+    pub fn set_bounding_box(&mut self, value: BoundingBox) {
+        self.bounding_box = value;
+        self.flags |= GeometryFlags::BoundingBox;
+    }
+    pub fn set_bounding_sphere(&mut self, value: BoundingSphere) {
+        self.bounding_sphere = value;
+        self.flags |= GeometryFlags::BoundingSphere;
+    }
+
+    /// Recalculate the bounding box and sphere from the vertex positions. These are in the space
+    /// of the node that the geometry is attached to, or the mesh's space for skinned geometry.
+    /// Returns false if there are no vertices.
+    pub fn recompute_bounds(&mut self) -> bool {
+        let positions: Vec<Vec3> = match self.get_vertex_data() {
+            Some(v) => v.get_positions().iter().map(|p| p.truncate()).collect(),
+            None => return false
+        };
+        match (BoundingBox::from_points(&positions), BoundingSphere::from_points(&positions)) {
+            (Some(bounding_box), Some(bounding_sphere)) => {
+                self.set_bounding_box(bounding_box);
+                self.set_bounding_sphere(bounding_sphere);
+                true
+            },
+            _ => false
+        }
+    }
+
+    /// Get the vertex indices of each triangle. Strips and fans are converted into a list, and
+    /// geometry without indices uses each vertex in order. Points and lines don't have any
+    /// triangles.
+    pub fn get_triangles(&self) -> Vec<[u32; 3]> {
+        let indices: Vec<u32> = match self.get_index_data() {
+            Some(v) => v.iter().collect(),
+            None => (0..self.get_vertex_data().map_or(0, |v| v.len() as u32)).collect()
+        };
+        match self.prim {
+            TriangleIndexFormat::ThreeIndicesPerTriangle => indices.chunks_exact(3)
+                .map(|v| [v[0], v[1], v[2]]).collect(),
+            // Every other triangle in a strip is flipped to keep the same winding order
+            TriangleIndexFormat::OneIndexMinusTwoPerTriangle4 => indices.windows(3).enumerate()
+                .map(|(i, v)| match i % 2 == 1 {
+                    true => [v[0], v[2], v[1]],
+                    false => [v[0], v[1], v[2]]
+                }).collect(),
+            TriangleIndexFormat::OneIndexMinusTwoPerTriangle5 => match indices.split_first() {
+                Some((first, rest)) => rest.windows(2).map(|v| [*first, v[0], v[1]]).collect(),
+                None => vec![]
+            },
+            _ => vec![]
+        }
+    }
+
+    /// Replace the tangents and binormals with ones calculated from the normals and the texture
+    /// coordinates in `channel`. See GeometryVertexData::generate_tangents. If any vertices are
+    /// split, morph targets get a copy of their offsets and the indices are replaced with a
+    /// triangle list.
+    pub fn generate_tangents(&mut self, channel: usize) -> Result<(), Box<dyn Error>> {
+        let mut triangles = self.get_triangles();
+        let vertices = self.get_vertex_data_mut().ok_or(GeometryError::MissingVertexData)?;
+        let copies = vertices.generate_tangents(&mut triangles, channel)?;
+        let count = vertices.len();
+        self.fvf |= VertexAttributeFlags::Tangent | VertexAttributeFlags::Binormal;
+        if copies.is_empty() {
+            return Ok(());
+        }
+        if let Some(target) = self.get_morph_target_mut() {
+            target.copy_vertices(&copies)?;
+        }
+        let indices = match count <= u16::MAX as usize + 1 {
+            true => {
+                let mut out = AVec::with_capacity_in(triangles.len() * 3, self._allocator.clone());
+                out.extend(triangles.as_flattened().iter().map(|i| *i as u16));
+                GeometryIndexData::UInt16(out)
+            },
+            false => {
+                let mut out = AVec::with_capacity_in(triangles.len() * 3, self._allocator.clone());
+                out.extend_from_slice(triangles.as_flattened());
+                GeometryIndexData::UInt32(out)
+            }
+        };
+        self.num_vertices = count as i32;
+        self.prim = TriangleIndexFormat::ThreeIndicesPerTriangle;
+        self.flags |= GeometryFlags::Triangles;
+        self.index = indices.get_index_type();
+        self.num_indices = indices.len() as i32;
+        self.num_triangles = triangles.len() as i32;
+        self.get_data_mut().indices = Some(indices);
+        Ok(())
+    }
+
+    /// Get the world transform of the node that the geometry is attached to. Geometry that isn't
+    /// attached to a node uses the identity matrix.
+    pub fn get_world_transform(&self) -> Mat4 {
+        self.super_.get_parent().map_or(Mat4::IDENTITY, |n| n.get_world_transform())
+    }
+
+    pub fn get_morph_target(&self) -> Option<&MorphTarget<A>> {
+        match self.flags.contains(GeometryFlags::MorphTargets) {
+            true => unsafe { (self.morph_targets as *const MorphTarget<A>).as_ref() },
+            false => None
+        }
+    }
+    fn get_morph_target_mut(&mut self) -> Option<&mut MorphTarget<A>> {
+        match self.flags.contains(GeometryFlags::MorphTargets) {
+            true => unsafe { (self.morph_targets as *mut MorphTarget<A>).as_mut() },
+            false => None
+        }
+    }
+    /// Set the geometry's morph target. The geometry takes ownership of the target, and frees
+    /// the target that it replaces.
+    pub fn set_morph_target(&mut self, value: NonNull<MorphTarget<A>>) {
+        self.free_morph_target();
+        self.morph_targets = value.as_ptr() as *mut std::os::raw::c_void;
+        self.flags |= GeometryFlags::MorphTargets;
+    }
+
+    /// Get the vertex positions after applying a weight to each morph target, such as the
+    /// weights from MorphController::get_target_weights. Geometry without morph targets returns
+    /// it's positions unchanged.
+    pub fn get_morphed_positions(&self, weights: &[f32]) -> Result<Vec<Vec3>, MorphError> {
+        let vertices = self.get_vertex_data().ok_or(MorphError::MissingVertexData)?;
+        let positions: Vec<Vec3> = vertices.get_positions().iter().map(|v| v.truncate()).collect();
+        match self.get_morph_target() {
+            Some(target) => target.apply_weights(&positions, weights),
+            None => Ok(positions)
+        }
+    }
+
+    /// Get the vertex positions and normals in the pose given by the skin palette. Skinned
+    /// geometry is moved by it's weighted bones, while other geometry is moved by the world
+    /// transform of the node that it's attached to.
+    pub fn get_skinned_vertices(&self, palette: &SkinPalette) -> Result<SkinnedVertices, SkinError> {
+        let vertices = self.get_vertex_data().ok_or(SkinError::MissingVertexData)?;
+        let (normals, weights) = (vertices.get_normals(), vertices.get_weights());
+        let skinned = self.flags.contains(GeometryFlags::Skin) && !weights.is_empty();
+        let world = self.get_world_transform();
+        let mut out = SkinnedVertices::default();
+        for (i, position) in vertices.get_positions().iter().enumerate() {
+            let matrix = match skinned {
+                true => palette.get_blend_matrix(&weights.get(i).copied().unwrap_or_default())
+                    .map_err(|bone| SkinError::InvalidBoneIndex { vertex: i, bone })?,
+                false => world
+            };
+            out.push(&matrix, position.truncate(), normals.get(i).copied());
+        }
+        Ok(out)
+    }
+
     /// Original function: gfdGeoemtryGetMaterial
     pub fn get_material(&self) -> Option<&Material<A>> {
         if self.flags.contains(GeometryFlags::Material) {
@@ -592,6 +950,15 @@ where A: Allocator + Clone
     const OBJECT_ID: ObjectId = ObjectId::Geometry;
 }
 
+impl<A> Drop for Geometry<A>
+where A: Allocator + Clone
+{
+    fn drop(&mut self) {
+        GEOMETRY_DATA.remove(self as *const Self);
+        self.free_morph_target();
+    }
+}
+
 #[repr(C)]
 pub struct GeometryCommand {
     prepare: *mut ResBuffer,
@@ -602,4 +969,764 @@ pub struct GeometryCommand {
 
 #[repr(C)]
 #[derive(Debug)]
-pub struct JobData;
\ No newline at end of file
+pub struct JobData;
+
+/// Skin weights for a single vertex. Files before GeometryUseNewVertexWeightFormat only store
+/// four weights per vertex, so the remaining weights will be zero.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct VertexWeight {
+    pub weights: [f32; 8],
+    pub indices: [u16; 8]
+}
+
+/// Vertex attributes for a geometry, stored as one array per attribute. Each array is either
+/// empty (if the geometry doesn't have that attribute) or contains one entry per vertex.
+pub struct GeometryVertexData<A = GfdAllocator>
+where A: Allocator + Clone
+{
+    positions: AVec<Vec4, A>,
+    normals: AVec<Vec3, A>,
+    binormals: AVec<Vec3, A>,
+    tangents: AVec<Vec3, A>,
+    diffuse_colors: AVec<RGBA, A>,
+    color2: AVec<RGBA, A>,
+    tex_coords: [AVec<Vec2, A>; 3],
+    color3: AVec<RGBA, A>,
+    // P5 beta terrain data, kept as is
+    terrain: AVec<[u8; 0x14], A>,
+    weights: AVec<VertexWeight, A>
+}
+
+impl<A> Debug for GeometryVertexData<A>
+where A: Allocator + Clone
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "GeometryVertexData {{ positions: {}, normals: {}, binormals: {}, tangents: {}, weights: {} }}",
+        self.positions.len(), self.normals.len(), self.binormals.len(), self.tangents.len(), self.weights.len())
+    }
+}
+
+impl<A> GeometryVertexData<A>
+where A: Allocator + Clone
+{
+    pub fn new(allocator: A) -> Self {
+        Self {
+            positions: AVec::new_in(allocator.clone()),
+            normals: AVec::new_in(allocator.clone()),
+            binormals: AVec::new_in(allocator.clone()),
+            tangents: AVec::new_in(allocator.clone()),
+            diffuse_colors: AVec::new_in(allocator.clone()),
+            color2: AVec::new_in(allocator.clone()),
+            tex_coords: [AVec::new_in(allocator.clone()), AVec::new_in(allocator.clone()), AVec::new_in(allocator.clone())],
+            color3: AVec::new_in(allocator.clone()),
+            terrain: AVec::new_in(allocator.clone()),
+            weights: AVec::new_in(allocator)
+        }
+    }
+
+    /// Get the vertex attributes for the arrays that contain data. Positions are always stored
+    /// as PositionXYZ.
+    pub fn get_attribute_flags(&self) -> VertexAttributeFlags {
+        let attributes = [
+            (!self.positions.is_empty(), VertexAttributeFlags::PositionXYZ),
+            (!self.normals.is_empty(), VertexAttributeFlags::Normal),
+            (!self.binormals.is_empty(), VertexAttributeFlags::Binormal),
+            (!self.tangents.is_empty(), VertexAttributeFlags::Tangent),
+            (!self.diffuse_colors.is_empty(), VertexAttributeFlags::DiffuseColor),
+            (!self.color2.is_empty(), VertexAttributeFlags::Color2),
+            (!self.tex_coords[0].is_empty(), VertexAttributeFlags::TexCoord0),
+            (!self.tex_coords[1].is_empty(), VertexAttributeFlags::TexCoord1),
+            (!self.tex_coords[2].is_empty(), VertexAttributeFlags::TexCoord2),
+            (!self.color3.is_empty(), VertexAttributeFlags::Color3),
+            (!self.terrain.is_empty(), VertexAttributeFlags::Flag31)
+        ];
+        attributes.into_iter().filter(|v| v.0).fold(VertexAttributeFlags::empty(), |a, v| a | v.1)
+    }
+
+    /// Get the number of vertices stored. Every geometry has positions, so this is used as the
+    /// vertex count.
+    pub fn len(&self) -> usize { self.positions.len() }
+    pub fn is_empty(&self) -> bool { self.positions.is_empty() }
+
+    /// Vertex positions. The W component is 1 unless the geometry uses PositionXYZW.
+    pub fn get_positions(&self) -> &[Vec4] { &self.positions }
+    pub fn get_normals(&self) -> &[Vec3] { &self.normals }
+    pub fn get_binormals(&self) -> &[Vec3] { &self.binormals }
+    pub fn get_tangents(&self) -> &[Vec3] { &self.tangents }
+    pub fn get_diffuse_colors(&self) -> &[RGBA] { &self.diffuse_colors }
+    pub fn get_color2(&self) -> &[RGBA] { &self.color2 }
+    pub fn get_color3(&self) -> &[RGBA] { &self.color3 }
+    pub fn get_tex_coords(&self, channel: usize) -> &[Vec2] { &self.tex_coords[channel] }
+    pub fn get_weights(&self) -> &[VertexWeight] { &self.weights }
+
+    pub fn get_positions_mut(&mut self) -> &mut AVec<Vec4, A> { &mut self.positions }
+    pub fn get_normals_mut(&mut self) -> &mut AVec<Vec3, A> { &mut self.normals }
+    pub fn get_binormals_mut(&mut self) -> &mut AVec<Vec3, A> { &mut self.binormals }
+    pub fn get_tangents_mut(&mut self) -> &mut AVec<Vec3, A> { &mut self.tangents }
+    pub fn get_diffuse_colors_mut(&mut self) -> &mut AVec<RGBA, A> { &mut self.diffuse_colors }
+    pub fn get_color2_mut(&mut self) -> &mut AVec<RGBA, A> { &mut self.color2 }
+    pub fn get_color3_mut(&mut self) -> &mut AVec<RGBA, A> { &mut self.color3 }
+    pub fn get_tex_coords_mut(&mut self, channel: usize) -> &mut AVec<Vec2, A> { &mut self.tex_coords[channel] }
+    pub fn get_weights_mut(&mut self) -> &mut AVec<VertexWeight, A> { &mut self.weights }
+
+    /// Calculate a MikkTSpace tangent and binormal for each vertex from the normals and the
+    /// texture coordinates in `channel`. The binormal is cross(normal, tangent), flipped where
+    /// the texture is mirrored. Vertices shared by triangles with different tangent frames, such
+    /// as vertices on a UV seam, are copied so that each frame gets it's own vertex, and
+    /// `triangles` is updated to use the copies. Returns the vertex that each copy was made from,
+    /// in the order that they were added.
+    pub fn generate_tangents(&mut self, triangles: &mut [[u32; 3]], channel: usize) -> Result<Vec<usize>, GeometryError> {
+        let count = self.len();
+        if self.normals.len() != count {
+            return Err(GeometryError::MissingNormals);
+        }
+        let tex_coords = self.tex_coords.get(channel)
+            .filter(|v| v.len() == count)
+            .ok_or(GeometryError::MissingTexCoords(channel))?;
+        if let Some(index) = triangles.iter().flatten().find(|i| **i as usize >= count) {
+            return Err(GeometryError::InvalidVertexIndex(*index));
+        }
+        let mut input = TangentSpaceInput {
+            positions: &self.positions,
+            normals: &self.normals,
+            tex_coords,
+            triangles,
+            tangents: vec![Vec4::ZERO; triangles.len() * 3]
+        };
+        if !triangles.is_empty() && !bevy_mikktspace::generate_tangents(&mut input) {
+            return Err(GeometryError::TangentGenerationFailed);
+        }
+        let corners = input.tangents;
+        // Give each vertex the frame of the first corner that uses it, copying the vertex for
+        // every other frame that it's used with
+        let mut frames: Vec<Option<Vec4>> = vec![None; count];
+        let mut copies = vec![];
+        let mut copy_indices: HashMap<(u32, [u32; 4]), u32> = HashMap::new();
+        for (corner, index) in triangles.iter_mut().flatten().enumerate() {
+            let (vertex, frame) = (*index, corners[corner]);
+            match frames[vertex as usize] {
+                None => frames[vertex as usize] = Some(frame),
+                Some(v) if v == frame => (),
+                Some(_) => {
+                    *index = *copy_indices.entry((vertex, frame.to_array().map(f32::to_bits))).or_insert_with(|| {
+                        copies.push(vertex as usize);
+                        frames.push(Some(frame));
+                        (frames.len() - 1) as u32
+                    });
+                }
+            }
+        }
+        append_copies(&mut self.positions, &copies);
+        append_copies(&mut self.normals, &copies);
+        append_copies(&mut self.diffuse_colors, &copies);
+        append_copies(&mut self.color2, &copies);
+        for channel in &mut self.tex_coords {
+            append_copies(channel, &copies);
+        }
+        append_copies(&mut self.color3, &copies);
+        append_copies(&mut self.terrain, &copies);
+        append_copies(&mut self.weights, &copies);
+        self.tangents.clear();
+        self.binormals.clear();
+        for (normal, frame) in self.normals.iter().zip(frames) {
+            let normal = normal.normalize_or_zero();
+            // Vertices that aren't used by a triangle don't have a frame
+            let frame = frame.unwrap_or(Vec4::W);
+            let tangent = match (frame.truncate() - normal * normal.dot(frame.truncate())).try_normalize() {
+                Some(v) => v,
+                None if normal != Vec3::ZERO => normal.any_orthonormal_vector(),
+                None => Vec3::X
+            };
+            self.tangents.push(tangent);
+            self.binormals.push(normal.cross(tangent) * frame.w);
+        }
+        Ok(copies)
+    }
+}
+
+// Add a copy of each vertex in `sources` to the end of an attribute that the geometry uses
+fn append_copies<T, A>(values: &mut AVec<T, A>, sources: &[usize])
+where T: Copy, A: Allocator {
+    if values.is_empty() {
+        return;
+    }
+    for source in sources {
+        let value = values[*source];
+        values.push(value);
+    }
+}
+
+// Triangle corners given to bevy_mikktspace, along with the tangent that it calculates for each
+// one. The W component is -1 where the binormal is flipped.
+struct TangentSpaceInput<'a> {
+    positions: &'a [Vec4],
+    normals: &'a [Vec3],
+    tex_coords: &'a [Vec2],
+    triangles: &'a [[u32; 3]],
+    tangents: Vec<Vec4>
+}
+
+impl TangentSpaceInput<'_> {
+    fn get_vertex(&self, face: usize, vert: usize) -> usize {
+        self.triangles[face][vert] as usize
+    }
+}
+
+impl bevy_mikktspace::Geometry for TangentSpaceInput<'_> {
+    fn num_faces(&self) -> usize { self.triangles.len() }
+    fn num_vertices_of_face(&self, _face: usize) -> usize { 3 }
+    fn position(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.positions[self.get_vertex(face, vert)].truncate().to_array()
+    }
+    fn normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.normals[self.get_vertex(face, vert)].to_array()
+    }
+    fn tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        self.tex_coords[self.get_vertex(face, vert)].to_array()
+    }
+    fn set_tangent_encoded(&mut self, tangent: [f32; 4], face: usize, vert: usize) {
+        self.tangents[face * 3 + vert] = Vec4::from_array(tangent);
+    }
+}
+
+/// Triangle indices for a geometry, stored using the geometry's TriangleIndexType.
+pub enum GeometryIndexData<A = GfdAllocator>
+where A: Allocator + Clone
+{
+    UInt16(AVec<u16, A>),
+    UInt32(AVec<u32, A>)
+}
+
+impl<A> Debug for GeometryIndexData<A>
+where A: Allocator + Clone
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UInt16(v) => write!(f, "GeometryIndexData::UInt16 {{ len: {} }}", v.len()),
+            Self::UInt32(v) => write!(f, "GeometryIndexData::UInt32 {{ len: {} }}", v.len())
+        }
+    }
+}
+
+impl<A> GeometryIndexData<A>
+where A: Allocator + Clone
+{
+    pub fn len(&self) -> usize {
+        match self {
+            Self::UInt16(v) => v.len(),
+            Self::UInt32(v) => v.len()
+        }
+    }
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+    pub fn get(&self, index: usize) -> Option<u32> {
+        match self {
+            Self::UInt16(v) => v.get(index).map(|i| *i as u32),
+            Self::UInt32(v) => v.get(index).copied()
+        }
+    }
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..self.len()).map(|i| self.get(i).unwrap())
+    }
+    pub fn get_index_type(&self) -> TriangleIndexType {
+        match self {
+            Self::UInt16(_) => TriangleIndexType::UInt16,
+            Self::UInt32(_) => TriangleIndexType::UInt32
+        }
+    }
+}
+
+pub struct GeometryBufferContext<A>
+where A: Allocator + Clone {
+    fvf: VertexAttributeFlags,
+    flags: GeometryFlags,
+    index: TriangleIndexType,
+    count: usize,
+    _allocator: A
+}
+
+impl<A> GfdSerializationUserData<A> for GeometryBufferContext<A>
+where A: Allocator + Clone {
+    fn get_heap_allocator(&self) -> Option<A> {
+        Some(self._allocator.clone())
+    }
+}
+
+impl<A> GeometryBufferContext<A>
+where A: Allocator + Clone {
+    pub(crate) fn new(_allocator: A, fvf: VertexAttributeFlags, flags: GeometryFlags, index: TriangleIndexType, count: usize) -> Self {
+        Self { fvf, flags, index, count, _allocator }
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl<AStream, AObject, T> GfdSerialize<AStream, T, AObject, DeserializationHeap<Self, AObject>, GeometryBufferContext<AObject>> for GeometryVertexData<AObject>
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      AStream: Allocator + Clone + Debug,
+      AObject: Allocator + Clone
+{
+    fn stream_read(stream: &mut Stream<AStream, T>, param: &mut GeometryBufferContext<AObject>) -> Result<DeserializationHeap<Self, AObject>, Box<dyn Error>> {
+        let allocator = param.get_heap_allocator().unwrap();
+        let mut this = Self::new(allocator.clone());
+        this.stream_read_inner(stream, param)?;
+        Ok(DeserializationHeap::new(this, allocator))
+    }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, param: &mut GeometryBufferContext<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream, param)
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl<AObject> GeometryVertexData<AObject>
+where AObject: Allocator + Clone
+{
+    fn read_tex_coord<AStream, T>(stream: &mut Stream<AStream, T>) -> Result<Vec2, Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        Ok(match stream.has_feature(GfdVersion::GFDV2) {
+            Some(_) => Vec2::new(f16::from_bits(stream.read_u16()?).to_f32(), f16::from_bits(stream.read_u16()?).to_f32()),
+            None => Vec2::stream_read(stream, &mut ())?.into_raw()
+        })
+    }
+
+    fn write_tex_coord<AStream, T>(stream: &mut Stream<AStream, T>, value: Vec2) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        match stream.has_feature(GfdVersion::GFDV2) {
+            Some(_) => {
+                stream.write_u16(f16::from_f32(value.x).to_bits())?;
+                stream.write_u16(f16::from_f32(value.y).to_bits())?;
+            },
+            None => value.stream_write(stream, &mut ())?
+        };
+        Ok(())
+    }
+
+    // Get the number of bytes that each vertex takes up in the file
+    fn get_vertex_size<AStream, T>(stream: &Stream<AStream, T>, param: &GeometryBufferContext<AObject>) -> usize
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        let fvf = param.fvf;
+        let tex_coord_size = match stream.has_feature(GfdVersion::GFDV2) {
+            Some(_) => 4,
+            None => 8
+        };
+        let attributes = [
+            (fvf.contains(VertexAttributeFlags::PositionXYZW), 16),
+            (!fvf.contains(VertexAttributeFlags::PositionXYZW) && fvf.contains(VertexAttributeFlags::PositionXYZ), 12),
+            (fvf.contains(VertexAttributeFlags::Normal), 12),
+            (fvf.contains(VertexAttributeFlags::Binormal), 12),
+            (fvf.contains(VertexAttributeFlags::Tangent), 12),
+            (fvf.contains(VertexAttributeFlags::Color2), 4),
+            (fvf.contains(VertexAttributeFlags::DiffuseColor), 4),
+            (fvf.contains(VertexAttributeFlags::TexCoord0), tex_coord_size),
+            (fvf.contains(VertexAttributeFlags::TexCoord1), tex_coord_size),
+            (fvf.contains(VertexAttributeFlags::TexCoord2), tex_coord_size),
+            (fvf.contains(VertexAttributeFlags::Color3), 4),
+            (fvf.contains(VertexAttributeFlags::Flag31), 0x14),
+            (param.flags.contains(GeometryFlags::Skin), match stream.has_feature(GfdVersion::GeometryUseNewVertexWeightFormat) {
+                Some(_) => 32,
+                None => 20
+            })
+        ];
+        attributes.into_iter().filter(|(used, _)| *used).map(|(_, size)| size).sum()
+    }
+
+    fn stream_read_inner<AStream, T>(&mut self, stream: &mut Stream<AStream, T>, param: &mut GeometryBufferContext<AObject>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        // The vertex count comes from the file, so check that there's enough data for it before
+        // reading anything
+        let remaining = stream.get_remaining()?;
+        if param.count.checked_mul(Self::get_vertex_size(stream, param)).is_none_or(|v| v as u64 > remaining) {
+            return Err(Box::new(io::Error::from(io::ErrorKind::UnexpectedEof)));
+        }
+        let fvf = param.fvf;
+        for _ in 0..param.count {
+            if fvf.contains(VertexAttributeFlags::PositionXYZW) {
+                self.positions.push(Vec4::stream_read(stream, &mut ())?.into_raw());
+            } else if fvf.contains(VertexAttributeFlags::PositionXYZ) {
+                self.positions.push(Vec3::stream_read(stream, &mut ())?.into_raw().extend(1.));
+            }
+            if fvf.contains(VertexAttributeFlags::Normal) {
+                self.normals.push(Vec3::stream_read(stream, &mut ())?.into_raw());
+            }
+            if fvf.contains(VertexAttributeFlags::Binormal) {
+                self.binormals.push(Vec3::stream_read(stream, &mut ())?.into_raw());
+            }
+            if fvf.contains(VertexAttributeFlags::Tangent) {
+                self.tangents.push(Vec3::stream_read(stream, &mut ())?.into_raw());
+            }
+            if fvf.contains(VertexAttributeFlags::Color2) {
+                self.color2.push(RGBA::stream_read(stream, &mut ())?.into_raw());
+            }
+            if fvf.contains(VertexAttributeFlags::DiffuseColor) {
+                self.diffuse_colors.push(RGBA::stream_read(stream, &mut ())?.into_raw());
+            }
+            let tex_coord_flags = [VertexAttributeFlags::TexCoord0, VertexAttributeFlags::TexCoord1, VertexAttributeFlags::TexCoord2];
+            for (i, flag) in tex_coord_flags.into_iter().enumerate() {
+                if fvf.contains(flag) {
+                    let tex_coord = Self::read_tex_coord(stream)?;
+                    self.tex_coords[i].push(tex_coord);
+                }
+            }
+            if fvf.contains(VertexAttributeFlags::Color3) {
+                self.color3.push(RGBA::stream_read(stream, &mut ())?.into_raw());
+            }
+            if fvf.contains(VertexAttributeFlags::Flag31) {
+                let mut terrain = [0u8; 0x14];
+                stream.read_u8_slice(terrain.as_mut_slice())?;
+                self.terrain.push(terrain);
+            }
+            if param.flags.contains(GeometryFlags::Skin) {
+                let mut weight = VertexWeight::default();
+                match stream.has_feature(GfdVersion::GeometryUseNewVertexWeightFormat) {
+                    Some(_) => {
+                        let mut half = [0u16; 8];
+                        stream.read_u16_slice(half.as_mut_slice())?;
+                        weight.weights = half.map(|v| f16::from_bits(v).to_f32());
+                        stream.read_u16_slice(weight.indices.as_mut_slice())?;
+                    },
+                    None => {
+                        stream.read_f32_slice(&mut weight.weights[..4])?;
+                        for index in weight.indices.iter_mut().take(4) {
+                            *index = stream.read_u8()? as u16;
+                        }
+                    }
+                };
+                self.weights.push(weight);
+            }
+        }
+        Ok(())
+    }
+
+    // Every attribute in the vertex format needs a value for each vertex, since there's no value
+    // that could stand in for a missing one
+    fn check_attributes(&self, param: &GeometryBufferContext<AObject>) -> Result<(), GeometryError> {
+        let attributes = [
+            (VertexAttributeFlags::PositionXYZ | VertexAttributeFlags::PositionXYZW, self.positions.len()),
+            (VertexAttributeFlags::Normal, self.normals.len()),
+            (VertexAttributeFlags::Binormal, self.binormals.len()),
+            (VertexAttributeFlags::Tangent, self.tangents.len()),
+            (VertexAttributeFlags::Color2, self.color2.len()),
+            (VertexAttributeFlags::DiffuseColor, self.diffuse_colors.len()),
+            (VertexAttributeFlags::TexCoord0, self.tex_coords[0].len()),
+            (VertexAttributeFlags::TexCoord1, self.tex_coords[1].len()),
+            (VertexAttributeFlags::TexCoord2, self.tex_coords[2].len()),
+            (VertexAttributeFlags::Color3, self.color3.len()),
+            (VertexAttributeFlags::Flag31, self.terrain.len())
+        ];
+        for (flag, len) in attributes {
+            if param.fvf.intersects(flag) && len != param.count {
+                return Err(GeometryError::MissingVertexAttribute(param.fvf & flag));
+            }
+        }
+        if param.flags.contains(GeometryFlags::Skin) && self.weights.len() != param.count {
+            return Err(GeometryError::MissingVertexWeights);
+        }
+        Ok(())
+    }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>, param: &mut GeometryBufferContext<AObject>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        self.check_attributes(param)?;
+        let fvf = param.fvf;
+        for i in 0..param.count {
+            if fvf.contains(VertexAttributeFlags::PositionXYZW) {
+                self.positions[i].stream_write(stream, &mut ())?;
+            } else if fvf.contains(VertexAttributeFlags::PositionXYZ) {
+                self.positions[i].truncate().stream_write(stream, &mut ())?;
+            }
+            if fvf.contains(VertexAttributeFlags::Normal) {
+                self.normals[i].stream_write(stream, &mut ())?;
+            }
+            if fvf.contains(VertexAttributeFlags::Binormal) {
+                self.binormals[i].stream_write(stream, &mut ())?;
+            }
+            if fvf.contains(VertexAttributeFlags::Tangent) {
+                self.tangents[i].stream_write(stream, &mut ())?;
+            }
+            if fvf.contains(VertexAttributeFlags::Color2) {
+                self.color2[i].stream_write(stream, &mut ())?;
+            }
+            if fvf.contains(VertexAttributeFlags::DiffuseColor) {
+                self.diffuse_colors[i].stream_write(stream, &mut ())?;
+            }
+            let tex_coord_flags = [VertexAttributeFlags::TexCoord0, VertexAttributeFlags::TexCoord1, VertexAttributeFlags::TexCoord2];
+            for (j, flag) in tex_coord_flags.into_iter().enumerate() {
+                if fvf.contains(flag) {
+                    Self::write_tex_coord(stream, self.tex_coords[j][i])?;
+                }
+            }
+            if fvf.contains(VertexAttributeFlags::Color3) {
+                self.color3[i].stream_write(stream, &mut ())?;
+            }
+            if fvf.contains(VertexAttributeFlags::Flag31) {
+                stream.write_u8_slice(self.terrain[i].as_slice())?;
+            }
+            if param.flags.contains(GeometryFlags::Skin) {
+                let weight = &self.weights[i];
+                match stream.has_feature(GfdVersion::GeometryUseNewVertexWeightFormat) {
+                    Some(_) => {
+                        stream.write_u16_slice(weight.weights.map(|v| f16::from_f32(v).to_bits()).as_slice())?;
+                        stream.write_u16_slice(weight.indices.as_slice())?;
+                    },
+                    None => {
+                        stream.write_f32_slice(&weight.weights[..4])?;
+                        for index in weight.indices.iter().take(4) {
+                            let index = u8::try_from(*index).map_err(|_| GeometryError::BoneIndexOutOfRange(*index))?;
+                            stream.write_u8(index)?;
+                        }
+                    }
+                };
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl<AStream, AObject, T> GfdSerialize<AStream, T, AObject, DeserializationHeap<Self, AObject>, GeometryBufferContext<AObject>> for GeometryIndexData<AObject>
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      AStream: Allocator + Clone + Debug,
+      AObject: Allocator + Clone
+{
+    fn stream_read(stream: &mut Stream<AStream, T>, param: &mut GeometryBufferContext<AObject>) -> Result<DeserializationHeap<Self, AObject>, Box<dyn Error>> {
+        let allocator = param.get_heap_allocator().unwrap();
+        Ok(DeserializationHeap::new(Self::stream_read_inner(stream, param)?, allocator))
+    }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, param: &mut GeometryBufferContext<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream, param)
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl<AObject> GeometryIndexData<AObject>
+where AObject: Allocator + Clone
+{
+    fn stream_read_inner<AStream, T>(stream: &mut Stream<AStream, T>, param: &mut GeometryBufferContext<AObject>) -> Result<Self, Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        let allocator = param.get_heap_allocator().unwrap();
+        // Geometry without an index type doesn't store any indices, but it's buffer is bounded
+        // like 16-bit indices so that it can't be larger than the file
+        let index_size = match param.index {
+            TriangleIndexType::UInt32 => 4,
+            _ => 2
+        };
+        let remaining = stream.get_remaining()?;
+        if param.count.checked_mul(index_size).is_none_or(|v| v as u64 > remaining) {
+            return Err(Box::new(io::Error::from(io::ErrorKind::UnexpectedEof)));
+        }
+        Ok(match param.index {
+            TriangleIndexType::UInt32 => {
+                let mut indices = AVec::with_capacity_in(param.count, allocator.clone());
+                indices.resize(param.count, 0);
+                stream.read_u32_slice(indices.as_mut_slice())?;
+                Self::UInt32(indices)
+            },
+            _ => {
+                let mut indices = AVec::with_capacity_in(param.count, allocator.clone());
+                indices.resize(param.count, 0);
+                if param.index == TriangleIndexType::UInt16 {
+                    stream.read_u16_slice(indices.as_mut_slice())?;
+                }
+                Self::UInt16(indices)
+            }
+        })
+    }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>, param: &mut GeometryBufferContext<AObject>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        if self.len() != param.count {
+            return Err(Box::new(GeometryError::MissingIndexData));
+        }
+        // Write using the geometry's index type, since the indices may have been replaced
+        for index in self.iter() {
+            match param.index {
+                TriangleIndexType::None => (),
+                TriangleIndexType::UInt16 => stream.write_u16(u16::try_from(index).map_err(|_| GeometryError::IndexOutOfRange(index))?)?,
+                TriangleIndexType::UInt32 => stream.write_u32(index)?
+            };
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use allocator_api2::alloc::Global;
+    use allocator_api2::vec::Vec as AVec;
+    use glam::{ Vec2, Vec3, Vec4 };
+    use std::error::Error;
+    use super::{ Geometry, GeometryIndexData, GeometryVertexData, TriangleIndexFormat, VertexAttributeFlags };
+
+    type TestReturn = Result<(), Box<dyn Error>>;
+
+    fn create_quad(mirrored: bool) -> GeometryVertexData<Global> {
+        let mut vertices = GeometryVertexData::new(Global);
+        let positions = [Vec3::ZERO, Vec3::X, Vec3::new(0., 2., 0.), Vec3::new(1., 2., 0.)];
+        vertices.get_positions_mut().extend(positions.map(|v| v.extend(1.)));
+        vertices.get_normals_mut().extend([Vec3::Z; 4]);
+        vertices.get_tex_coords_mut(0).extend(positions.map(|v| match mirrored {
+            true => Vec2::new(1. - v.x, v.y / 2.),
+            false => Vec2::new(v.x, v.y / 2.)
+        }));
+        vertices
+    }
+
+    #[test]
+    fn recompute_bounds_and_tangents() -> TestReturn {
+        let mut indices = AVec::new_in(Global);
+        indices.extend([0u16, 1, 2, 3]);
+        let mut ptr = Geometry::new_in(create_quad(false), Some(GeometryIndexData::UInt16(indices)), TriangleIndexFormat::OneIndexMinusTwoPerTriangle4, Global)?;
+        let geometry = unsafe { ptr.as_mut() };
+        assert_eq!(geometry.get_triangle_count(), 2);
+        assert_eq!(geometry.get_triangles(), vec![[0, 1, 2], [1, 3, 2]]);
+        assert!(geometry.recompute_bounds());
+        let bounding_box = geometry.get_bounding_box().unwrap();
+        assert_eq!((bounding_box.get_min(), bounding_box.get_max()), (Vec3::ZERO, Vec3::new(1., 2., 0.)));
+        let bounding_sphere = geometry.get_bounding_sphere().unwrap();
+        assert!(bounding_sphere.get_center().abs_diff_eq(Vec3::new(0.5, 1., 0.), 1e-5));
+        assert!((bounding_sphere.get_radius() - 1.25f32.sqrt()).abs() < 1e-5);
+
+        geometry.generate_tangents(0)?;
+        assert!(geometry.get_vertex_attributes().contains(VertexAttributeFlags::Tangent | VertexAttributeFlags::Binormal));
+        let vertices = geometry.get_vertex_data().unwrap();
+        assert!(vertices.get_tangents().iter().all(|v| v.abs_diff_eq(Vec3::X, 1e-5)));
+        assert!(vertices.get_binormals().iter().all(|v| v.abs_diff_eq(Vec3::Y, 1e-5)));
+        assert!(geometry.generate_tangents(1).is_err());
+
+        // Mirroring the texture flips the tangent but keeps the binormal pointing up the texture
+        let mut vertices = create_quad(true);
+        assert!(vertices.generate_tangents(&mut [[0, 1, 2], [2, 1, 3]], 0)?.is_empty());
+        assert!(vertices.get_tangents().iter().all(|v| v.abs_diff_eq(Vec3::NEG_X, 1e-5)));
+        assert!(vertices.get_binormals().iter().all(|v| v.abs_diff_eq(Vec3::Y, 1e-5)));
+        assert!(vertices.generate_tangents(&mut [[0, 1, 4]], 0).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn split_vertices_on_uv_seam() -> TestReturn {
+        use crate::object::morph::MorphTarget;
+        // The texture is mirrored across the edge from vertex 0 to 2, so the triangles on each
+        // side have opposite tangents
+        let mut vertices = GeometryVertexData::new(Global);
+        let positions = [Vec3::ZERO, Vec3::X, Vec3::Y, Vec3::NEG_X];
+        vertices.get_positions_mut().extend(positions.map(|v| v.extend(1.)));
+        vertices.get_normals_mut().extend([Vec3::Z; 4]);
+        vertices.get_tex_coords_mut(0).extend([Vec2::ZERO, Vec2::X, Vec2::Y, Vec2::X]);
+        let mut indices = AVec::new_in(Global);
+        indices.extend([0u16, 1, 2, 0, 2, 3]);
+        let mut ptr = Geometry::new_in(vertices, Some(GeometryIndexData::UInt16(indices)), TriangleIndexFormat::ThreeIndicesPerTriangle, Global)?;
+        let geometry = unsafe { ptr.as_mut() };
+        let offsets: Vec<Vec3> = (0..4).map(|i| Vec3::splat(i as f32)).collect();
+        geometry.set_morph_target(MorphTarget::new_in(&[offsets], Global)?);
+
+        geometry.generate_tangents(0)?;
+        let triangles = geometry.get_triangles();
+        assert_eq!(triangles, vec![[0, 1, 2], [4, 5, 3]]);
+        assert_eq!(geometry.get_index_data().map(|v| v.len()), Some(6));
+        let vertices = geometry.get_vertex_data().unwrap();
+        assert_eq!(vertices.len(), 6);
+        assert_eq!(&vertices.get_positions()[4..], &[Vec4::W, Vec4::new(0., 1., 0., 1.)]);
+        assert_eq!(&vertices.get_tex_coords(0)[4..], &[Vec2::ZERO, Vec2::Y]);
+        for (triangle, tangent) in triangles.iter().zip([Vec3::X, Vec3::NEG_X]) {
+            for index in triangle.map(|i| i as usize) {
+                assert!(vertices.get_tangents()[index].abs_diff_eq(tangent, 1e-5));
+                assert!(vertices.get_binormals()[index].abs_diff_eq(Vec3::Y, 1e-5));
+            }
+        }
+        let target = geometry.get_morph_target().unwrap().get_target(0).unwrap();
+        assert_eq!(&target.get_vertices()[4..], &[Vec3::ZERO, Vec3::splat(2.)]);
+        Ok(())
+    }
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn write_rejects_incomplete_vertices() -> TestReturn {
+        use crate::utility::stream::{ DevicePlatform, GfdSerialize, StreamFactory, StreamHeader };
+        use super::{ GeometryBufferContext, GeometryError, GeometryFlags, TriangleIndexType, VertexWeight };
+        let factory = StreamFactory::new(Global);
+        let mut vertices = create_quad(false);
+        vertices.get_weights_mut().extend([VertexWeight { weights: [1., 0., 0., 0., 0., 0., 0., 0.], indices: [300, 0, 0, 0, 0, 0, 0, 0] }; 4]);
+        let fvf = VertexAttributeFlags::PositionXYZ | VertexAttributeFlags::Normal | VertexAttributeFlags::TexCoord0;
+        let mut param = GeometryBufferContext::new(Global, fvf, GeometryFlags::Skin, TriangleIndexType::None, 4);
+        // Bone indices past 255 don't fit in the old weight format
+        let mut stream = factory.write_to_memory(StreamHeader::new(0x1105100, DevicePlatform::P5R_DX11))?;
+        let error = vertices.stream_write(&mut stream, &mut param).unwrap_err();
+        assert!(matches!(error.downcast_ref::<GeometryError>(), Some(GeometryError::BoneIndexOutOfRange(300))));
+        let mut stream = factory.write_to_memory(StreamHeader::new(0x2110000, DevicePlatform::METAPHOR))?;
+        vertices.stream_write(&mut stream, &mut param)?;
+        // Every attribute in the format needs a value for each vertex
+        let mut param = GeometryBufferContext::new(Global, fvf | VertexAttributeFlags::Tangent, GeometryFlags::Skin, TriangleIndexType::None, 4);
+        let error = vertices.stream_write(&mut stream, &mut param).unwrap_err();
+        assert!(matches!(error.downcast_ref::<GeometryError>(), Some(GeometryError::MissingVertexAttribute(f)) if *f == VertexAttributeFlags::Tangent));
+        vertices.get_weights_mut().clear();
+        let mut param = GeometryBufferContext::new(Global, fvf, GeometryFlags::Skin, TriangleIndexType::None, 4);
+        let error = vertices.stream_write(&mut stream, &mut param).unwrap_err();
+        assert!(matches!(error.downcast_ref::<GeometryError>(), Some(GeometryError::MissingVertexWeights)));
+        Ok(())
+    }
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn read_rejects_truncated_buffers() -> TestReturn {
+        use std::io;
+        use crate::utility::stream::{ DevicePlatform, GfdSerialize, StreamFactory, StreamHeader };
+        use super::{ GeometryBufferContext, GeometryError, GeometryFlags, TriangleIndexType, VertexWeight };
+        let factory = StreamFactory::new(Global);
+        let is_eof = |e: Box<dyn Error>| e.downcast_ref::<io::Error>().is_some_and(|e| e.kind() == io::ErrorKind::UnexpectedEof);
+        let mut vertices = create_quad(false);
+        vertices.get_weights_mut().extend([VertexWeight::default(); 4]);
+        let fvf = VertexAttributeFlags::PositionXYZ | VertexAttributeFlags::Normal | VertexAttributeFlags::TexCoord0;
+        let mut stream = factory.write_to_memory(StreamHeader::new(0x2110000, DevicePlatform::METAPHOR))?;
+        vertices.stream_write(&mut stream, &mut GeometryBufferContext::new(Global, fvf, GeometryFlags::Skin, TriangleIndexType::None, 4))?;
+        let written = stream.as_slice().to_vec();
+        for (count, valid) in [(4, true), (5, false), (usize::MAX, false)] {
+            let mut stream = factory.read_from_memory(&written)?;
+            let mut param = GeometryBufferContext::new(Global, fvf, GeometryFlags::Skin, TriangleIndexType::None, count);
+            match GeometryVertexData::<Global>::stream_read(&mut stream, &mut param) {
+                Ok(v) => assert!(valid && v.len() == 4),
+                Err(e) => assert!(!valid && is_eof(e))
+            };
+        }
+        let mut indices = AVec::new_in(Global);
+        indices.extend([0u16, 1, 2, 0, 2, 3]);
+        let mut stream = factory.write_to_memory(StreamHeader::new(0x2110000, DevicePlatform::METAPHOR))?;
+        GeometryIndexData::UInt16(indices).stream_write(&mut stream, &mut GeometryBufferContext::new(Global, fvf, GeometryFlags::Triangles, TriangleIndexType::UInt16, 6))?;
+        let written = stream.as_slice().to_vec();
+        for (index, count, valid) in [(TriangleIndexType::UInt16, 6, true), (TriangleIndexType::UInt32, 6, false), (TriangleIndexType::UInt16, 1 << 40, false)] {
+            let mut stream = factory.read_from_memory(&written)?;
+            let mut param = GeometryBufferContext::new(Global, fvf, GeometryFlags::Triangles, index, count);
+            match GeometryIndexData::<Global>::stream_read(&mut stream, &mut param) {
+                Ok(v) => assert!(valid && v.len() == 6),
+                Err(e) => assert!(!valid && is_eof(e))
+            };
+        }
+        // Index counts for each primitive type, which can't overflow
+        assert_eq!(TriangleIndexFormat::OneIndexMinusOnePerTriangle.to_vertex_count(3)?, 4);
+        assert_eq!(TriangleIndexFormat::OneIndexMinusTwoPerTriangle4.to_vertex_count(3)?, 5);
+        assert_eq!(TriangleIndexFormat::ThreeIndicesPerTriangle.to_vertex_count(3)?, 9);
+        assert!(matches!(TriangleIndexFormat::ThreeIndicesPerTriangle.to_vertex_count(u32::MAX), Err(GeometryError::TooManyTriangles(u32::MAX))));
+        assert!(matches!(TriangleIndexFormat::OneIndexPerTriangle.to_vertex_count(u32::MAX), Err(GeometryError::TooManyTriangles(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn reject_too_few_indices() -> TestReturn {
+        use super::GeometryError;
+        assert_eq!(TriangleIndexFormat::OneIndexMinusOnePerTriangle.from_vertex_count(4), Some(3));
+        assert_eq!(TriangleIndexFormat::OneIndexMinusOnePerTriangle.from_vertex_count(1), None);
+        assert_eq!(TriangleIndexFormat::OneIndexMinusTwoPerTriangle5.from_vertex_count(1), None);
+        assert_eq!(TriangleIndexFormat::ThreeIndicesPerTriangle.from_vertex_count(2), None);
+        let mut indices = AVec::new_in(Global);
+        indices.extend([0u16, 1]);
+        let result = Geometry::new_in(create_quad(false), Some(GeometryIndexData::UInt16(indices)), TriangleIndexFormat::OneIndexMinusTwoPerTriangle4, Global);
+        assert!(matches!(result, Err(GeometryError::NotEnoughIndices(2))));
+        Ok(())
+    }
+}
@@ -0,0 +1,868 @@
+//! The parts of the glTF 2.0 document format used to move models between OpenGFD and other
+//! tools, along with the binary (.glb) container.
+//!
+//! Only the properties that OpenGFD reads or writes are included. Everything is stored with the
+//! same names as the glTF specification, so a document can be serialized with serde_json as is.
+//! Sparse accessors, and buffers and images stored in external files are read, but never written.
+
+use crate::{
+    device::ngr::renderer::state::{ FilterMode, TextureAddressMode },
+    graphics::texture::TextureSampler
+};
+use serde::{ Deserialize, Serialize };
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt::Display,
+    path::Path
+};
+
+pub const GLB_MAGIC: u32 = 0x46546C67;
+pub const GLB_VERSION: u32 = 2;
+pub const GLB_HEADER_SIZE: usize = 12;
+pub const GLB_CHUNK_JSON: u32 = 0x4E4F534A;
+pub const GLB_CHUNK_BIN: u32 = 0x004E4942;
+
+pub const COMPONENT_BYTE: u32 = 5120;
+pub const COMPONENT_UNSIGNED_BYTE: u32 = 5121;
+pub const COMPONENT_SHORT: u32 = 5122;
+pub const COMPONENT_UNSIGNED_SHORT: u32 = 5123;
+pub const COMPONENT_UNSIGNED_INT: u32 = 5125;
+pub const COMPONENT_FLOAT: u32 = 5126;
+
+pub const TARGET_ARRAY_BUFFER: u32 = 34962;
+pub const TARGET_ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+pub const MODE_POINTS: u32 = 0;
+pub const MODE_LINES: u32 = 1;
+pub const MODE_LINE_LOOP: u32 = 2;
+pub const MODE_LINE_STRIP: u32 = 3;
+pub const MODE_TRIANGLES: u32 = 4;
+pub const MODE_TRIANGLE_STRIP: u32 = 5;
+pub const MODE_TRIANGLE_FAN: u32 = 6;
+
+pub const FILTER_NEAREST: u32 = 9728;
+pub const FILTER_LINEAR: u32 = 9729;
+pub const FILTER_NEAREST_MIPMAP_NEAREST: u32 = 9984;
+pub const FILTER_LINEAR_MIPMAP_NEAREST: u32 = 9985;
+pub const FILTER_NEAREST_MIPMAP_LINEAR: u32 = 9986;
+pub const FILTER_LINEAR_MIPMAP_LINEAR: u32 = 9987;
+
+pub const WRAP_CLAMP_TO_EDGE: u32 = 33071;
+pub const WRAP_MIRRORED_REPEAT: u32 = 33648;
+pub const WRAP_REPEAT: u32 = 10497;
+
+/// Extension for textures that use a DDS image, for textures that can't be converted to PNG
+pub const EXTENSION_TEXTURE_DDS: &str = "MSFT_texture_dds";
+
+fn is_false(value: &bool) -> bool { !*value }
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Gltf {
+    pub asset: Asset,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scene: Option<usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scenes: Vec<Scene>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub nodes: Vec<Node>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub meshes: Vec<Mesh>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skins: Vec<Skin>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub materials: Vec<Material>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub textures: Vec<Texture>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<Image>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub samplers: Vec<Sampler>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub accessors: Vec<Accessor>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub buffer_views: Vec<BufferView>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub buffers: Vec<Buffer>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extensions_used: Vec<String>
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Asset {
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generator: Option<String>
+}
+
+impl Default for Asset {
+    fn default() -> Self {
+        Self { version: "2.0".to_owned(), generator: Some("OpenGFD".to_owned()) }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Scene {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub nodes: Vec<usize>
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Node {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mesh: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skin: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub translation: Option<[f32; 3]>,
+    /// Rotation quaternion, stored as XYZW
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotation: Option<[f32; 4]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<[f32; 3]>,
+    /// Column major transform, used instead of translation, rotation and scale
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matrix: Option<[f32; 16]>
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Mesh {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub primitives: Vec<Primitive>,
+    /// Default morph target weights
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub weights: Vec<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extras: Option<MeshExtras>
+}
+
+/// Application specific mesh data. Blender and other tools store morph target names here.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MeshExtras {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub target_names: Vec<String>
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Primitive {
+    /// Accessor index for each vertex attribute, such as POSITION or TEXCOORD_0
+    pub attributes: BTreeMap<String, usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub indices: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub material: Option<usize>,
+    /// Defaults to triangles when not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<BTreeMap<String, usize>>
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Skin {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inverse_bind_matrices: Option<usize>,
+    pub joints: Vec<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skeleton: Option<usize>
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Material {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pbr_metallic_roughness: Option<PbrMetallicRoughness>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub normal_texture: Option<TextureInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub emissive_texture: Option<TextureInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub emissive_factor: Option<[f32; 3]>,
+    /// OPAQUE, MASK or BLEND. Defaults to OPAQUE when not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alpha_mode: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alpha_cutoff: Option<f32>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub double_sided: bool
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PbrMetallicRoughness {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_color_factor: Option<[f32; 4]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_color_texture: Option<TextureInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metallic_factor: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub roughness_factor: Option<f32>
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextureInfo {
+    pub index: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tex_coord: Option<usize>
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Texture {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sampler: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<TextureExtensions>
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TextureExtensions {
+    #[serde(rename = "MSFT_texture_dds", default, skip_serializing_if = "Option::is_none")]
+    pub texture_dds: Option<TextureSource>
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TextureSource {
+    pub source: usize
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Image {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub buffer_view: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sampler {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mag_filter: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_filter: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wrap_s: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wrap_t: Option<u32>
+}
+
+fn get_wrap(mode: Option<TextureAddressMode>) -> u32 {
+    match mode {
+        Some(TextureAddressMode::Wrap) | None => WRAP_REPEAT,
+        Some(TextureAddressMode::Mirror) => WRAP_MIRRORED_REPEAT,
+        Some(_) => WRAP_CLAMP_TO_EDGE
+    }
+}
+
+impl From<TextureSampler> for Sampler {
+    fn from(value: TextureSampler) -> Self {
+        let (mag, min) = match value.get_filter() {
+            FilterMode::MinMagMipPoint => (FILTER_NEAREST, FILTER_NEAREST_MIPMAP_NEAREST),
+            FilterMode::MinLinearMagMipPoint => (FILTER_NEAREST, FILTER_LINEAR_MIPMAP_NEAREST),
+            FilterMode::MinPointMagMipLinear => (FILTER_LINEAR, FILTER_NEAREST_MIPMAP_LINEAR),
+            _ => (FILTER_LINEAR, FILTER_LINEAR_MIPMAP_LINEAR)
+        };
+        Self {
+            mag_filter: Some(mag),
+            min_filter: Some(min),
+            wrap_s: Some(get_wrap(value.get_address_u())),
+            wrap_t: Some(get_wrap(value.get_address_v()))
+        }
+    }
+}
+
+fn get_address_mode(wrap: Option<u32>) -> TextureAddressMode {
+    match wrap {
+        Some(WRAP_MIRRORED_REPEAT) => TextureAddressMode::Mirror,
+        Some(WRAP_CLAMP_TO_EDGE) => TextureAddressMode::Clamp,
+        _ => TextureAddressMode::Wrap
+    }
+}
+
+impl From<&Sampler> for TextureSampler {
+    fn from(value: &Sampler) -> Self {
+        let min_linear = matches!(value.min_filter, None | Some(FILTER_LINEAR | FILTER_LINEAR_MIPMAP_NEAREST | FILTER_LINEAR_MIPMAP_LINEAR));
+        let mag_linear = value.mag_filter != Some(FILTER_NEAREST);
+        let mut sampler = Self::default();
+        sampler.set_filter(match (min_linear, mag_linear) {
+            (true, true) => FilterMode::MinMagMipLinear,
+            (true, false) => FilterMode::MinLinearMagMipPoint,
+            (false, false) => FilterMode::MinMagMipPoint,
+            (false, true) => FilterMode::MinPointMagMipLinear
+        });
+        sampler.set_address(get_address_mode(value.wrap_s), get_address_mode(value.wrap_t));
+        sampler
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Accessor {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub buffer_view: Option<usize>,
+    #[serde(default)]
+    pub byte_offset: usize,
+    pub component_type: u32,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub normalized: bool,
+    pub count: usize,
+    /// SCALAR, VEC2, VEC3, VEC4 or MAT4
+    #[serde(rename = "type")]
+    pub ty: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<Vec<f32>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<Vec<f32>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sparse: Option<AccessorSparse>
+}
+
+/// Elements of an accessor that replace the values in it's buffer view (or zero, if there isn't
+/// one). Exporters commonly use these for morph targets.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessorSparse {
+    pub count: usize,
+    pub indices: AccessorSparseIndices,
+    pub values: AccessorSparseValues
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessorSparseIndices {
+    pub buffer_view: usize,
+    #[serde(default)]
+    pub byte_offset: usize,
+    pub component_type: u32
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessorSparseValues {
+    pub buffer_view: usize,
+    #[serde(default)]
+    pub byte_offset: usize
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BufferView {
+    pub buffer: usize,
+    #[serde(default)]
+    pub byte_offset: usize,
+    pub byte_length: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub byte_stride: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<u32>
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Buffer {
+    pub byte_length: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>
+}
+
+/// The number of components in an accessor type
+pub fn get_component_count(ty: &str) -> Option<usize> {
+    match ty {
+        "SCALAR" => Some(1),
+        "VEC2" => Some(2),
+        "VEC3" => Some(3),
+        "VEC4" => Some(4),
+        "MAT4" => Some(16),
+        _ => None
+    }
+}
+
+/// Collects the binary data for a document into a single buffer, adding a buffer view for each
+/// block of data. Blocks are aligned to 4 bytes as required by the specification.
+#[derive(Debug, Default)]
+pub struct BufferBuilder {
+    data: Vec<u8>
+}
+
+impl BufferBuilder {
+    pub fn new() -> Self { Self::default() }
+    pub fn get_data(&self) -> &[u8] { self.data.as_slice() }
+    pub fn into_data(self) -> Vec<u8> { self.data }
+
+    /// Add a block of data, returning the index of it's buffer view
+    pub fn push_view(&mut self, document: &mut Gltf, bytes: &[u8], target: Option<u32>) -> usize {
+        self.data.resize(self.data.len().next_multiple_of(4), 0);
+        document.buffer_views.push(BufferView {
+            buffer: 0,
+            byte_offset: self.data.len(),
+            byte_length: bytes.len(),
+            byte_stride: None,
+            target
+        });
+        self.data.extend_from_slice(bytes);
+        document.buffer_views.len() - 1
+    }
+
+    /// Add an accessor for a list of float vectors, returning it's index. Set `bounds` to store
+    /// the minimum and maximum of each component, which is required for positions.
+    pub fn push_floats<const N: usize>(&mut self, document: &mut Gltf, values: &[[f32; N]], bounds: bool) -> usize {
+        let bytes: Vec<u8> = values.iter().flatten().flat_map(|v| v.to_le_bytes()).collect();
+        let view = self.push_view(document, &bytes, Some(TARGET_ARRAY_BUFFER));
+        let (min, max) = match bounds && !values.is_empty() {
+            true => (
+                Some((0..N).map(|c| values.iter().map(|v| v[c]).fold(f32::MAX, f32::min)).collect()),
+                Some((0..N).map(|c| values.iter().map(|v| v[c]).fold(f32::MIN, f32::max)).collect())
+            ),
+            false => (None, None)
+        };
+        self.push_accessor(document, Accessor {
+            buffer_view: Some(view),
+            component_type: COMPONENT_FLOAT,
+            count: values.len(),
+            ty: get_vector_type(N).to_owned(),
+            min,
+            max,
+            ..Default::default()
+        })
+    }
+
+    /// Add an accessor for a list of normalized RGBA8 colors
+    pub fn push_colors(&mut self, document: &mut Gltf, values: &[[u8; 4]]) -> usize {
+        let view = self.push_view(document, values.as_flattened(), Some(TARGET_ARRAY_BUFFER));
+        self.push_accessor(document, Accessor {
+            buffer_view: Some(view),
+            component_type: COMPONENT_UNSIGNED_BYTE,
+            normalized: true,
+            count: values.len(),
+            ty: "VEC4".to_owned(),
+            ..Default::default()
+        })
+    }
+
+    /// Add an accessor for a list of joint indices
+    pub fn push_joints(&mut self, document: &mut Gltf, values: &[[u16; 4]]) -> usize {
+        let bytes: Vec<u8> = values.iter().flatten().flat_map(|v| v.to_le_bytes()).collect();
+        let view = self.push_view(document, &bytes, Some(TARGET_ARRAY_BUFFER));
+        self.push_accessor(document, Accessor {
+            buffer_view: Some(view),
+            component_type: COMPONENT_UNSIGNED_SHORT,
+            count: values.len(),
+            ty: "VEC4".to_owned(),
+            ..Default::default()
+        })
+    }
+
+    /// Add an accessor for a list of indices, using 16-bit indices when they fit
+    pub fn push_indices(&mut self, document: &mut Gltf, values: &[u32]) -> usize {
+        let (bytes, component_type): (Vec<u8>, _) = match values.iter().all(|v| *v <= u16::MAX as u32) {
+            true => (values.iter().flat_map(|v| (*v as u16).to_le_bytes()).collect(), COMPONENT_UNSIGNED_SHORT),
+            false => (values.iter().flat_map(|v| v.to_le_bytes()).collect(), COMPONENT_UNSIGNED_INT)
+        };
+        let view = self.push_view(document, &bytes, Some(TARGET_ELEMENT_ARRAY_BUFFER));
+        self.push_accessor(document, Accessor {
+            buffer_view: Some(view),
+            component_type,
+            count: values.len(),
+            ty: "SCALAR".to_owned(),
+            ..Default::default()
+        })
+    }
+
+    /// Add an accessor for a list of column major matrices
+    pub fn push_matrices(&mut self, document: &mut Gltf, values: &[[f32; 16]]) -> usize {
+        let bytes: Vec<u8> = values.iter().flatten().flat_map(|v| v.to_le_bytes()).collect();
+        let view = self.push_view(document, &bytes, None);
+        self.push_accessor(document, Accessor {
+            buffer_view: Some(view),
+            component_type: COMPONENT_FLOAT,
+            count: values.len(),
+            ty: "MAT4".to_owned(),
+            ..Default::default()
+        })
+    }
+
+    fn push_accessor(&mut self, document: &mut Gltf, accessor: Accessor) -> usize {
+        document.accessors.push(accessor);
+        document.accessors.len() - 1
+    }
+}
+
+fn get_vector_type(components: usize) -> &'static str {
+    match components {
+        1 => "SCALAR",
+        2 => "VEC2",
+        3 => "VEC3",
+        _ => "VEC4"
+    }
+}
+
+fn push_chunk(out: &mut Vec<u8>, ty: u32, data: &[u8], padding: u8) {
+    let length = data.len().next_multiple_of(4);
+    out.extend_from_slice(&(length as u32).to_le_bytes());
+    out.extend_from_slice(&ty.to_le_bytes());
+    out.extend_from_slice(data);
+    out.resize(out.len() + length - data.len(), padding);
+}
+
+/// Write a document and it's binary buffer into a .glb file. The document's first buffer is set
+/// to refer to the binary chunk.
+pub fn write_glb(document: &mut Gltf, bin: &[u8]) -> Result<Vec<u8>, serde_json::Error> {
+    match bin.is_empty() {
+        true => document.buffers.clear(),
+        false => document.buffers = vec![Buffer { byte_length: bin.len(), uri: None }]
+    };
+    let json = serde_json::to_vec(document)?;
+    let mut out = Vec::with_capacity(GLB_HEADER_SIZE + json.len() + bin.len() + 0x20);
+    out.extend_from_slice(&GLB_MAGIC.to_le_bytes());
+    out.extend_from_slice(&GLB_VERSION.to_le_bytes());
+    out.extend_from_slice(&0u32.to_le_bytes());
+    push_chunk(&mut out, GLB_CHUNK_JSON, &json, b' ');
+    if !bin.is_empty() {
+        push_chunk(&mut out, GLB_CHUNK_BIN, bin, 0);
+    }
+    let length = out.len() as u32;
+    out[8..12].copy_from_slice(&length.to_le_bytes());
+    Ok(out)
+}
+
+#[derive(Debug)]
+pub enum GltfError {
+    InvalidMagic(u32),
+    UnsupportedVersion(u32),
+    InvalidChunk(usize),
+    MissingJsonChunk,
+    InvalidBuffer(usize),
+    InvalidBufferView(usize),
+    InvalidAccessor(usize),
+    InvalidAttributeCount(String),
+    InvalidJoint(usize),
+    UnsupportedComponentType(u32),
+    UnsupportedUri(String)
+}
+impl Error for GltfError {}
+impl Display for GltfError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "GltfError: {:?}", self)
+    }
+}
+
+/// Split a .glb file into it's document and binary chunk
+pub fn read_glb(bytes: &[u8]) -> Result<(Gltf, Option<&[u8]>), Box<dyn Error>> {
+    let read_u32 = |offset: usize| bytes.get(offset..offset + 4)
+        .map_or(0, |v| u32::from_le_bytes(v.try_into().unwrap()));
+    if read_u32(0) != GLB_MAGIC {
+        return Err(Box::new(GltfError::InvalidMagic(read_u32(0))));
+    }
+    if read_u32(4) != GLB_VERSION {
+        return Err(Box::new(GltfError::UnsupportedVersion(read_u32(4))));
+    }
+    let length = (read_u32(8) as usize).min(bytes.len());
+    let (mut document, mut bin) = (None, None);
+    let mut offset = GLB_HEADER_SIZE;
+    while offset + 8 <= length {
+        let start = offset + 8;
+        let end = start + read_u32(offset) as usize;
+        let data = bytes.get(start..end).ok_or(GltfError::InvalidChunk(offset))?;
+        // Unknown chunk types are skipped, as required by the specification
+        match read_u32(offset + 4) {
+            GLB_CHUNK_JSON => document = Some(serde_json::from_slice(data)?),
+            GLB_CHUNK_BIN => bin = Some(data),
+            _ => ()
+        };
+        offset = end;
+    }
+    match document {
+        Some(v) => Ok((v, bin)),
+        None => Err(Box::new(GltfError::MissingJsonChunk))
+    }
+}
+
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len() * 3 / 4);
+    let (mut bits, mut count) = (0u32, 0);
+    for c in text.bytes().filter(|c| *c != b'=' && !c.is_ascii_whitespace()) {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => return None
+        };
+        bits = bits << 6 | value as u32;
+        count += 6;
+        if count >= 8 {
+            count -= 8;
+            out.push((bits >> count) as u8);
+        }
+    }
+    Some(out)
+}
+
+fn decode_percent(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = match bytes[i] {
+            b'%' => text.get(i + 1..i + 3).and_then(|v| u8::from_str_radix(v, 16).ok()),
+            _ => None
+        };
+        match escaped {
+            Some(v) => {
+                out.push(v);
+                i += 3;
+            },
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        };
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Get the data for a buffer or image URI. This is either embedded in the URI as base64, or a
+/// path relative to the document's folder, which is only allowed if `base` is provided.
+pub fn load_uri(uri: &str, base: Option<&Path>) -> Result<Vec<u8>, Box<dyn Error>> {
+    if let Some(data) = uri.strip_prefix("data:") {
+        return data.split_once(";base64,")
+            .and_then(|(_, v)| decode_base64(v))
+            .ok_or_else(|| GltfError::UnsupportedUri(uri.to_owned()).into());
+    }
+    let base = base.ok_or_else(|| GltfError::UnsupportedUri(uri.to_owned()))?;
+    Ok(std::fs::read(base.join(decode_percent(uri)))?)
+}
+
+/// Get the data for each of the document's buffers. `bin` is the binary chunk of a .glb file,
+/// which is used for the first buffer when it doesn't have a URI.
+pub fn load_buffers(document: &Gltf, bin: Option<&[u8]>, base: Option<&Path>) -> Result<Vec<Vec<u8>>, Box<dyn Error>> {
+    document.buffers.iter().enumerate().map(|(i, buffer)| match (&buffer.uri, bin) {
+        (Some(uri), _) => load_uri(uri, base),
+        (None, Some(bin)) if i == 0 => Ok(bin.to_vec()),
+        (None, _) => Err(GltfError::InvalidBuffer(i).into())
+    }).collect()
+}
+
+/// Get the bytes covered by a buffer view
+pub fn get_view_data<'a>(document: &Gltf, buffers: &'a [Vec<u8>], index: usize) -> Result<&'a [u8], GltfError> {
+    let view = document.buffer_views.get(index).ok_or(GltfError::InvalidBufferView(index))?;
+    buffers.get(view.buffer)
+        .and_then(|v| v.get(view.byte_offset..view.byte_offset + view.byte_length))
+        .ok_or(GltfError::InvalidBufferView(index))
+}
+
+fn get_component_size(component_type: u32) -> Result<usize, GltfError> {
+    match component_type {
+        COMPONENT_BYTE | COMPONENT_UNSIGNED_BYTE => Ok(1),
+        COMPONENT_SHORT | COMPONENT_UNSIGNED_SHORT => Ok(2),
+        COMPONENT_UNSIGNED_INT | COMPONENT_FLOAT => Ok(4),
+        v => Err(GltfError::UnsupportedComponentType(v))
+    }
+}
+
+// Normalized integers are mapped to 0.0..1.0, or -1.0..1.0 for signed types
+fn read_component(bytes: &[u8], component_type: u32, normalized: bool) -> f64 {
+    let (value, max) = match component_type {
+        COMPONENT_BYTE => (bytes[0] as i8 as f64, i8::MAX as f64),
+        COMPONENT_UNSIGNED_BYTE => (bytes[0] as f64, u8::MAX as f64),
+        COMPONENT_SHORT => (i16::from_le_bytes([bytes[0], bytes[1]]) as f64, i16::MAX as f64),
+        COMPONENT_UNSIGNED_SHORT => (u16::from_le_bytes([bytes[0], bytes[1]]) as f64, u16::MAX as f64),
+        COMPONENT_UNSIGNED_INT => (u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64, u32::MAX as f64),
+        _ => return f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64
+    };
+    match normalized {
+        true => (value / max).max(-1.),
+        false => value
+    }
+}
+
+fn read_elements(out: &mut [f64], data: &[u8], offset: usize, stride: usize, components: usize, component_type: u32, normalized: bool) -> Option<()> {
+    let size = get_component_size(component_type).ok()?;
+    for (i, element) in out.chunks_mut(components).enumerate() {
+        for (c, value) in element.iter_mut().enumerate() {
+            let start = offset + i * stride + c * size;
+            *value = read_component(data.get(start..start + size)?, component_type, normalized);
+        }
+    }
+    Some(())
+}
+
+/// Read every component of an accessor in element order, along with the number of components
+/// in each element. Sparse values are applied on top of the accessor's buffer view.
+pub fn read_accessor(document: &Gltf, buffers: &[Vec<u8>], index: usize) -> Result<(Vec<f64>, usize), GltfError> {
+    let accessor = document.accessors.get(index).ok_or(GltfError::InvalidAccessor(index))?;
+    let components = get_component_count(&accessor.ty).ok_or(GltfError::InvalidAccessor(index))?;
+    let size = get_component_size(accessor.component_type)?;
+    let element_size = size * components;
+    // Check the element count against the data before allocating anything for it
+    let view = match accessor.buffer_view {
+        Some(view) => {
+            let data = get_view_data(document, buffers, view)?;
+            let stride = document.buffer_views[view].byte_stride.unwrap_or(element_size);
+            let end = match accessor.count {
+                0 => Some(0),
+                n => (n - 1).checked_mul(stride)
+                    .and_then(|v| v.checked_add(element_size))
+                    .and_then(|v| v.checked_add(accessor.byte_offset))
+            };
+            match end {
+                Some(end) if end <= data.len() => Some((data, stride)),
+                _ => return Err(GltfError::InvalidAccessor(index))
+            }
+        },
+        None => {
+            // Accessors without a buffer view start as zeros. They can't be larger than the
+            // buffers could have held if they had been stored
+            let total: usize = buffers.iter().map(|v| v.len()).sum();
+            match accessor.count.checked_mul(element_size) {
+                Some(v) if v <= total => None,
+                _ => return Err(GltfError::InvalidAccessor(index))
+            }
+        }
+    };
+    if accessor.sparse.as_ref().is_some_and(|v| v.count > accessor.count) {
+        return Err(GltfError::InvalidAccessor(index));
+    }
+    let mut values = vec![0.; accessor.count * components];
+    if let Some((data, stride)) = view {
+        read_elements(&mut values, data, accessor.byte_offset, stride, components, accessor.component_type, accessor.normalized)
+            .ok_or(GltfError::InvalidAccessor(index))?;
+    }
+    if let Some(sparse) = &accessor.sparse {
+        let mut targets = vec![0.; sparse.count];
+        let data = get_view_data(document, buffers, sparse.indices.buffer_view)?;
+        let index_size = get_component_size(sparse.indices.component_type)?;
+        read_elements(&mut targets, data, sparse.indices.byte_offset, index_size, 1, sparse.indices.component_type, false)
+            .ok_or(GltfError::InvalidAccessor(index))?;
+        let mut replaced = vec![0.; sparse.count * components];
+        let data = get_view_data(document, buffers, sparse.values.buffer_view)?;
+        read_elements(&mut replaced, data, sparse.values.byte_offset, element_size, components, accessor.component_type, accessor.normalized)
+            .ok_or(GltfError::InvalidAccessor(index))?;
+        for (target, value) in targets.into_iter().zip(replaced.chunks(components)) {
+            let start = target as usize * components;
+            values.get_mut(start..start + components).ok_or(GltfError::InvalidAccessor(index))?.copy_from_slice(value);
+        }
+    }
+    Ok((values, components))
+}
+
+/// Read an accessor as a list of float vectors. Missing components are set to 1, so RGB colors
+/// are read as opaque RGBA colors.
+pub fn read_floats<const N: usize>(document: &Gltf, buffers: &[Vec<u8>], index: usize) -> Result<Vec<[f32; N]>, GltfError> {
+    let (values, components) = read_accessor(document, buffers, index)?;
+    Ok(values.chunks(components)
+        .map(|v| std::array::from_fn(|c| v.get(c).map_or(1., |v| *v as f32)))
+        .collect())
+}
+
+/// Read an accessor of integers, such as indices or joints, with every component in one list
+pub fn read_integers(document: &Gltf, buffers: &[Vec<u8>], index: usize) -> Result<Vec<u32>, GltfError> {
+    Ok(read_accessor(document, buffers, index)?.0.into_iter().map(|v| v as u32).collect())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::error::Error;
+    use super::{
+        load_buffers,
+        read_floats,
+        read_glb,
+        read_integers,
+        write_glb,
+        AccessorSparse,
+        AccessorSparseIndices,
+        AccessorSparseValues,
+        Buffer,
+        BufferBuilder,
+        Gltf,
+        COMPONENT_UNSIGNED_SHORT,
+        GLB_CHUNK_BIN,
+        GLB_MAGIC
+    };
+
+    type TestReturn = Result<(), Box<dyn Error>>;
+
+    #[test]
+    fn write_glb_container() -> TestReturn {
+        let mut document = Gltf::default();
+        let mut buffer = BufferBuilder::new();
+        let positions = buffer.push_floats(&mut document, &[[0., 1., -2.], [3., -1., 0.], [1., 0., 5.]], true);
+        let indices = buffer.push_indices(&mut document, &[0, 1, 2]);
+        assert_eq!(document.accessors[positions].min, Some(vec![0., -1., -2.]));
+        assert_eq!(document.accessors[positions].max, Some(vec![3., 1., 5.]));
+        assert_eq!(document.accessors[indices].component_type, COMPONENT_UNSIGNED_SHORT);
+        // Index data (6 bytes) is padded so that the next view is aligned
+        buffer.push_floats(&mut document, &[[1f32]], false);
+        assert_eq!(document.buffer_views[2].byte_offset, 0x2c);
+        let bin = buffer.into_data();
+        let glb = write_glb(&mut document, &bin)?;
+        assert_eq!(u32::from_le_bytes(glb[0..4].try_into()?), GLB_MAGIC);
+        assert_eq!(u32::from_le_bytes(glb[8..12].try_into()?) as usize, glb.len());
+        let json_length = u32::from_le_bytes(glb[12..16].try_into()?) as usize;
+        assert_eq!(json_length % 4, 0);
+        let parsed: Gltf = serde_json::from_slice(&glb[20..20 + json_length])?;
+        assert_eq!(parsed, document);
+        let bin_chunk = 20 + json_length;
+        assert_eq!(u32::from_le_bytes(glb[bin_chunk + 4..bin_chunk + 8].try_into()?), GLB_CHUNK_BIN);
+        assert_eq!(&glb[bin_chunk + 8..bin_chunk + 8 + bin.len()], bin.as_slice());
+        Ok(())
+    }
+
+    #[test]
+    fn read_glb_container() -> TestReturn {
+        let mut document = Gltf::default();
+        let mut buffer = BufferBuilder::new();
+        let colors = buffer.push_colors(&mut document, &[[255, 0, 51, 255], [0, 255, 0, 0]]);
+        let indices = buffer.push_indices(&mut document, &[2, 0, 1]);
+        let offsets = buffer.push_floats(&mut document, &[[0f32; 3]; 4], false);
+        // Replace the third element using a sparse accessor
+        let sparse_indices = buffer.push_view(&mut document, &2u16.to_le_bytes(), None);
+        let sparse_values: Vec<u8> = [1f32, 2., 3.].iter().flat_map(|v| v.to_le_bytes()).collect();
+        let sparse_values = buffer.push_view(&mut document, &sparse_values, None);
+        document.accessors[offsets].sparse = Some(AccessorSparse {
+            count: 1,
+            indices: AccessorSparseIndices { buffer_view: sparse_indices, byte_offset: 0, component_type: COMPONENT_UNSIGNED_SHORT },
+            values: AccessorSparseValues { buffer_view: sparse_values, byte_offset: 0 }
+        });
+        let glb = write_glb(&mut document, &buffer.into_data())?;
+        let (parsed, bin) = read_glb(&glb)?;
+        let buffers = load_buffers(&parsed, bin, None)?;
+        assert_eq!(read_floats::<4>(&parsed, &buffers, colors)?, vec![[1., 0., 0.2, 1.], [0., 1., 0., 0.]]);
+        assert_eq!(read_integers(&parsed, &buffers, indices)?, vec![2, 0, 1]);
+        assert_eq!(read_floats::<3>(&parsed, &buffers, offsets)?[1..3], [[0.; 3], [1., 2., 3.]]);
+        // Buffers can also be embedded in the document as base64
+        let embedded = Gltf {
+            buffers: vec![Buffer { byte_length: 4, uri: Some("data:application/octet-stream;base64,AACAPw==".to_owned()) }],
+            ..Default::default()
+        };
+        assert_eq!(load_buffers(&embedded, None, None)?, vec![1f32.to_le_bytes().to_vec()]);
+        Ok(())
+    }
+}
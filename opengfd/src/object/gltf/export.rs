@@ -0,0 +1,630 @@
+//! Export a mesh to glTF 2.0 so that it can be opened in Blender and other modelling tools.
+//!
+//! The node hierarchy is written in the same depth first order as the mesh's node list, so glTF
+//! node indices match the bone to node indices in the model chunk. Each geometry becomes a
+//! primitive of the mesh attached to it's node. The mesh's skin becomes a single glTF skin, with
+//! one joint per bone. Morph targets are written with their names, and the weights of the morph
+//! controller for their geometry become the mesh's default weights.
+//!
+//! GFD materials don't map onto glTF's PBR model, so materials are approximated: the base, normal
+//! and emissive texture slots are connected to their glTF equivalents, and transparency, alpha
+//! testing and culling are kept. Textures are converted to PNG when the image crate is available
+//! and the format can be decoded. Otherwise, DDS textures are embedded as is using
+//! MSFT_texture_dds, and other textures are left out.
+
+use allocator_api2::alloc::Allocator;
+use glam::Vec3;
+use crate::{
+    graphics::{
+        material::{ AlphaTestFunc, Culling, Material, MaterialFlags },
+        texture::{ TextureEntry, TextureFormat }
+    },
+    object::{
+        geometry::{ Geometry, GeometryFlags, TriangleIndexFormat },
+        mesh::MeshFile,
+        morph::MorphController,
+        node::{ Node, NodeIterator, StandardNodeIterator },
+        object::ObjectId
+    }
+};
+use std::{
+    collections::{ BTreeMap, HashMap },
+    error::Error
+};
+use super::document::{
+    self,
+    write_glb,
+    BufferBuilder,
+    Gltf,
+    GltfError,
+    MeshExtras,
+    PbrMetallicRoughness,
+    TextureExtensions,
+    TextureInfo,
+    TextureSource,
+    EXTENSION_TEXTURE_DDS
+};
+
+/// Get the glTF primitive mode for a GFD index format. These follow Direct3D 9's primitive
+/// types, minus one.
+pub fn get_primitive_mode(format: TriangleIndexFormat) -> u32 {
+    match format {
+        TriangleIndexFormat::OneIndexPerTriangle => document::MODE_POINTS,
+        TriangleIndexFormat::TwoIndicesPerTriangle => document::MODE_LINES,
+        TriangleIndexFormat::OneIndexMinusOnePerTriangle => document::MODE_LINE_STRIP,
+        TriangleIndexFormat::ThreeIndicesPerTriangle => document::MODE_TRIANGLES,
+        TriangleIndexFormat::OneIndexMinusTwoPerTriangle4 => document::MODE_TRIANGLE_STRIP,
+        TriangleIndexFormat::OneIndexMinusTwoPerTriangle5 => document::MODE_TRIANGLE_FAN
+    }
+}
+
+/// Split up to eight weights per vertex into glTF's sets of four joints and weights. Weights
+/// are normalized since glTF requires them to add up to one.
+pub fn get_joint_sets(weights: &[f32; 8], indices: &[u16; 8]) -> [([u16; 4], [f32; 4]); 2] {
+    let total: f32 = weights.iter().filter(|w| **w > 0.).sum();
+    std::array::from_fn(|set| {
+        let mut joints = [0u16; 4];
+        let mut values = [0f32; 4];
+        for i in 0..4 {
+            let w = weights[set * 4 + i];
+            if w > 0. && total > 0. {
+                joints[i] = indices[set * 4 + i];
+                values[i] = w / total;
+            }
+        }
+        (joints, values)
+    })
+}
+
+/// Exports a mesh into a glTF document and it's binary buffer
+pub struct GltfExporter<'a, A>
+where A: Allocator + Clone {
+    mesh: &'a MeshFile<A>,
+    document: Gltf,
+    buffer: BufferBuilder,
+    nodes: HashMap<*const Node<A>, usize>,
+    materials: HashMap<*const Material<A>, usize>,
+    textures: HashMap<String, usize>
+}
+
+impl<'a, A> GltfExporter<'a, A>
+where A: Allocator + Clone {
+    pub fn new(mesh: &'a MeshFile<A>) -> Self {
+        Self {
+            mesh,
+            document: Gltf::default(),
+            buffer: BufferBuilder::new(),
+            nodes: HashMap::new(),
+            materials: HashMap::new(),
+            textures: HashMap::new()
+        }
+    }
+
+    /// Build the glTF document, returning it along with the contents of it's binary buffer
+    pub fn export(mut self) -> Result<(Gltf, Vec<u8>), Box<dyn Error>> {
+        self.export_textures()?;
+        self.export_materials();
+        self.export_nodes()?;
+        Ok((self.document, self.buffer.into_data()))
+    }
+
+    fn export_textures(&mut self) -> Result<(), Box<dyn Error>> {
+        let mesh = self.mesh;
+        let dictionary = match mesh.get_texture_dictionary() {
+            Some(v) => v,
+            None => return Ok(())
+        };
+        for entry in dictionary.iter() {
+            let name = entry.get_name().get_string().unwrap_or_default().to_owned();
+            let (image, dds) = match self.export_image(entry)? {
+                Some(v) => v,
+                None => continue
+            };
+            self.document.samplers.push(entry.get_sampler().into());
+            let sampler = Some(self.document.samplers.len() - 1);
+            self.document.textures.push(match dds {
+                true => document::Texture {
+                    name: Some(name.clone()),
+                    sampler,
+                    source: None,
+                    extensions: Some(TextureExtensions { texture_dds: Some(TextureSource { source: image }) })
+                },
+                false => document::Texture { name: Some(name.clone()), sampler, source: Some(image), extensions: None }
+            });
+            self.textures.insert(name, self.document.textures.len() - 1);
+        }
+        Ok(())
+    }
+
+    /// Add the texture's image, returning it's index and whether it was stored as a DDS
+    fn export_image(&mut self, entry: &TextureEntry<A>) -> Result<Option<(usize, bool)>, Box<dyn Error>> {
+        let name = entry.get_name().get_string().map(|v| v.to_owned());
+        #[cfg(feature = "image_loader")]
+        if let Some(png) = Self::encode_png(entry) {
+            let view = self.buffer.push_view(&mut self.document, &png, None);
+            self.document.images.push(document::Image {
+                name, mime_type: Some("image/png".to_owned()), buffer_view: Some(view), uri: None
+            });
+            return Ok(Some((self.document.images.len() - 1, false)));
+        }
+        if entry.get_format() != TextureFormat::DDS {
+            return Ok(None);
+        }
+        let view = self.buffer.push_view(&mut self.document, entry.get_data(), None);
+        self.document.images.push(document::Image {
+            name, mime_type: Some("image/vnd-ms.dds".to_owned()), buffer_view: Some(view), uri: None
+        });
+        if !self.document.extensions_used.iter().any(|v| v == EXTENSION_TEXTURE_DDS) {
+            self.document.extensions_used.push(EXTENSION_TEXTURE_DDS.to_owned());
+        }
+        Ok(Some((self.document.images.len() - 1, true)))
+    }
+
+    #[cfg(feature = "image_loader")]
+    fn encode_png(entry: &TextureEntry<A>) -> Option<Vec<u8>> {
+        let decoded = entry.decode().ok()?;
+        let mut png = std::io::Cursor::new(vec![]);
+        decoded.get_main_surface()?.to_image().write_to(&mut png, image::ImageFormat::Png).ok()?;
+        Some(png.into_inner())
+    }
+
+    fn get_texture_info(&self, material: &Material<A>, roles: &[&str]) -> Option<TextureInfo> {
+        material.iter_textures()
+            .find(|(slot, _)| roles.contains(&material.get_texture_slot_name(*slot)))
+            .and_then(|(_, texture)| texture.get_name())
+            .and_then(|name| self.textures.get(name))
+            .map(|index| TextureInfo { index: *index, tex_coord: None })
+    }
+
+    fn export_materials(&mut self) {
+        let mesh = self.mesh;
+        for material in mesh.get_material_list() {
+            let material = unsafe { material.as_ref() };
+            let (alpha_mode, alpha_cutoff) = match material.check_translucency() {
+                true => (Some("BLEND".to_owned()), None),
+                false if material.has_flags(MaterialFlags::AlphaTest) => {
+                    let test = material.get_alpha_test();
+                    let cutoff = match test.get_func() {
+                        AlphaTestFunc::Never => 1.,
+                        _ => (test.get_value() as f32 / 255.).clamp(0., 1.)
+                    };
+                    (Some("MASK".to_owned()), Some(cutoff))
+                },
+                false => (None, None)
+            };
+            let gltf = document::Material {
+                name: material.get_name().map(|v| v.to_owned()),
+                pbr_metallic_roughness: Some(PbrMetallicRoughness {
+                    base_color_factor: None,
+                    base_color_texture: self.get_texture_info(material, &["Base Texture", "Layer 0 Base Texture"]),
+                    metallic_factor: Some(0.),
+                    roughness_factor: Some(1.)
+                }),
+                normal_texture: self.get_texture_info(material, &["Normal Texture", "Layer 0 Normal Texture"]),
+                emissive_texture: match material.has_flags(MaterialFlags::Emissive) {
+                    true => self.get_texture_info(material, &["Emissive Texture"]),
+                    false => None
+                },
+                emissive_factor: None,
+                alpha_mode,
+                alpha_cutoff,
+                double_sided: material.get_culling() == Culling::None
+            };
+            let emissive = gltf.emissive_texture.is_some();
+            self.document.materials.push(document::Material {
+                emissive_factor: emissive.then_some([1.; 3]),
+                ..gltf
+            });
+            self.materials.insert(&raw const *material, self.document.materials.len() - 1);
+        }
+    }
+
+    fn export_skin(&mut self) -> Option<usize> {
+        let bones = self.mesh.get_bone_to_node_indices();
+        if bones.is_empty() {
+            return None;
+        }
+        let matrices: Vec<[f32; 16]> = self.mesh.get_inverse_bind_matrices().iter().map(|m| m.to_cols_array()).collect();
+        let inverse_bind_matrices = self.buffer.push_matrices(&mut self.document, &matrices);
+        self.document.skins.push(document::Skin {
+            name: None,
+            inverse_bind_matrices: Some(inverse_bind_matrices),
+            joints: bones.iter().map(|v| *v as usize).collect(),
+            skeleton: Some(0)
+        });
+        Some(self.document.skins.len() - 1)
+    }
+
+    fn export_nodes(&mut self) -> Result<(), GltfError> {
+        let mesh = self.mesh;
+        let root = match mesh.get_root_node() {
+            Some(v) => v,
+            None => return Ok(())
+        };
+        for node in NodeIterator::<A, StandardNodeIterator>::from_node(root) {
+            self.nodes.insert(&raw const *node, self.nodes.len());
+            let (t, r, s) = (node.get_translate(), node.get_rotate(), node.get_scale());
+            self.document.nodes.push(document::Node {
+                name: node.get_name().map(|v| v.to_owned()),
+                translation: Some(t.to_array()),
+                rotation: Some(r.to_array()),
+                scale: Some(s.to_array()),
+                ..Default::default()
+            });
+        }
+        let skin = self.export_skin();
+        for node in NodeIterator::<A, StandardNodeIterator>::from_node(root) {
+            let index = self.nodes[&(&raw const *node)];
+            let children: Vec<usize> = node.get_direct_children().into_iter()
+                .map(|v| self.nodes[&(&raw const *v)]).collect();
+            self.document.nodes[index].children = children;
+            let geometries: Vec<&Geometry<A>> = node.iter_object()
+                .filter(|v| v.get_id() == ObjectId::Geometry)
+                .filter_map(|v| v.as_object::<Geometry<A>>())
+                .collect();
+            // glTF ignores a skinned mesh's node transform, so skinned geometry can't share a
+            // mesh with geometry that uses it
+            let (skinned, rigid): (Vec<_>, Vec<_>) = geometries.into_iter()
+                .partition(|v| skin.is_some() && v.get_flags().contains(GeometryFlags::Skin));
+            if !rigid.is_empty() {
+                let mesh = self.export_mesh(node, &rigid, false)?;
+                self.document.nodes[index].mesh = mesh;
+            }
+            if !skinned.is_empty() {
+                let mesh = self.export_mesh(node, &skinned, true)?;
+                let target = match self.document.nodes[index].mesh {
+                    Some(_) => {
+                        self.document.nodes.push(document::Node {
+                            name: node.get_name().map(|v| format!("{}_skin", v)),
+                            ..Default::default()
+                        });
+                        let target = self.document.nodes.len() - 1;
+                        self.document.nodes[index].children.push(target);
+                        target
+                    },
+                    None => index
+                };
+                self.document.nodes[target].mesh = mesh;
+                self.document.nodes[target].skin = skin;
+            }
+        }
+        self.document.scenes.push(document::Scene { name: None, nodes: vec![0] });
+        self.document.scene = Some(0);
+        Ok(())
+    }
+
+    fn export_mesh(&mut self, node: &Node<A>, geometries: &[&Geometry<A>], skinned: bool) -> Result<Option<usize>, GltfError> {
+        let mut primitives = vec![];
+        let mut exported = vec![];
+        for geometry in geometries {
+            if let Some(primitive) = self.export_primitive(geometry, skinned)? {
+                primitives.push(primitive);
+                exported.push(*geometry);
+            }
+        }
+        if primitives.is_empty() {
+            return Ok(None);
+        }
+        // Every primitive in a glTF mesh has the same number of morph targets, so primitives
+        // with fewer targets are given ones that don't move any vertices
+        let target_count = primitives.iter().map(|v| v.targets.len()).max().unwrap_or_default();
+        for primitive in &mut primitives {
+            let count = self.document.accessors[primitive.attributes["POSITION"]].count;
+            while primitive.targets.len() < target_count {
+                let offsets = self.buffer.push_floats(&mut self.document, &vec![[0.; 3]; count], true);
+                primitive.targets.push(BTreeMap::from([("POSITION".to_owned(), offsets)]));
+            }
+        }
+        // Target names and weights come from the first geometry with every target
+        let morph = exported.iter()
+            .find(|v| v.get_morph_target().is_some_and(|t| t.get_targets().len() == target_count && target_count > 0));
+        let (weights, extras) = match morph {
+            Some(geometry) => {
+                let target = geometry.get_morph_target().unwrap();
+                let extras = (0..target_count).any(|i| target.get_target_name(i).is_some()).then(|| MeshExtras {
+                    target_names: (0..target_count)
+                        .map(|i| target.get_target_name(i).map_or_else(|| format!("Target{}", i), |v| v.to_owned()))
+                        .collect()
+                });
+                // Morph controllers find their geometry using it's material name
+                let weights = node.iter_object()
+                    .filter_map(|v| v.as_object::<MorphController<A>>())
+                    .find(|v| v.get_material_name().get_string() == geometry.get_material_name())
+                    .map_or(vec![], |v| v.get_target_weights(target_count));
+                (weights, extras)
+            },
+            None => (vec![], None)
+        };
+        self.document.meshes.push(document::Mesh {
+            name: node.get_name().map(|v| v.to_owned()),
+            primitives,
+            weights,
+            extras
+        });
+        Ok(Some(self.document.meshes.len() - 1))
+    }
+
+    fn export_primitive(&mut self, geometry: &Geometry<A>, skinned: bool) -> Result<Option<document::Primitive>, GltfError> {
+        let vertices = match geometry.get_vertex_data().filter(|v| !v.is_empty()) {
+            Some(v) => v,
+            None => return Ok(None)
+        };
+        // glTF requires every attribute to have a value for each vertex
+        let count = vertices.get_positions().len();
+        let streams = [
+            ("NORMAL", vertices.get_normals().len()),
+            ("TANGENT", vertices.get_tangents().len()),
+            ("BINORMAL", vertices.get_binormals().len()),
+            ("TEXCOORD_0", vertices.get_tex_coords(0).len()),
+            ("TEXCOORD_1", vertices.get_tex_coords(1).len()),
+            ("TEXCOORD_2", vertices.get_tex_coords(2).len()),
+            ("COLOR", vertices.get_diffuse_colors().len()),
+            ("COLOR2", vertices.get_color2().len()),
+            ("COLOR3", vertices.get_color3().len()),
+            ("WEIGHTS", vertices.get_weights().len())
+        ];
+        if let Some((name, _)) = streams.iter().find(|(_, len)| *len != 0 && *len != count) {
+            return Err(GltfError::InvalidAttributeCount((*name).to_owned()));
+        }
+        let (doc, buffer) = (&mut self.document, &mut self.buffer);
+        let mut attributes = BTreeMap::new();
+        let positions: Vec<[f32; 3]> = vertices.get_positions().iter().map(|v| v.truncate().to_array()).collect();
+        attributes.insert("POSITION".to_owned(), buffer.push_floats(doc, &positions, true));
+        let normals = vertices.get_normals();
+        if !normals.is_empty() {
+            let values: Vec<[f32; 3]> = normals.iter().map(|v| v.normalize_or(Vec3::Z).to_array()).collect();
+            attributes.insert("NORMAL".to_owned(), buffer.push_floats(doc, &values, false));
+            let (tangents, binormals) = (vertices.get_tangents(), vertices.get_binormals());
+            if !tangents.is_empty() {
+                // glTF stores the binormal's direction as the sign of the tangent's W
+                let values: Vec<[f32; 4]> = tangents.iter().enumerate().map(|(i, t)| {
+                    let sign = match binormals.get(i) {
+                        Some(b) if normals[i].cross(*t).dot(*b) < 0. => -1.,
+                        _ => 1.
+                    };
+                    t.normalize_or(Vec3::X).extend(sign).to_array()
+                }).collect();
+                attributes.insert("TANGENT".to_owned(), buffer.push_floats(doc, &values, false));
+            }
+        }
+        for (i, channel) in (0..3).map(|c| vertices.get_tex_coords(c)).enumerate() {
+            if !channel.is_empty() {
+                let values: Vec<[f32; 2]> = channel.iter().map(|v| v.to_array()).collect();
+                attributes.insert(format!("TEXCOORD_{}", i), buffer.push_floats(doc, &values, false));
+            }
+        }
+        let colors = [vertices.get_diffuse_colors(), vertices.get_color2(), vertices.get_color3()];
+        for (i, channel) in colors.into_iter().filter(|v| !v.is_empty()).enumerate() {
+            let values: Vec<[u8; 4]> = channel.iter().map(|v| v.get_values()).collect();
+            attributes.insert(format!("COLOR_{}", i), buffer.push_colors(doc, &values));
+        }
+        let weights = vertices.get_weights();
+        if skinned && !weights.is_empty() {
+            let sets: Vec<[([u16; 4], [f32; 4]); 2]> = weights.iter().map(|w| get_joint_sets(&w.weights, &w.indices)).collect();
+            let set_count = match sets.iter().any(|v| v[1].1.iter().any(|w| *w > 0.)) {
+                true => 2,
+                false => 1
+            };
+            for set in 0..set_count {
+                let joints: Vec<[u16; 4]> = sets.iter().map(|v| v[set].0).collect();
+                let values: Vec<[f32; 4]> = sets.iter().map(|v| v[set].1).collect();
+                attributes.insert(format!("JOINTS_{}", set), buffer.push_joints(doc, &joints));
+                attributes.insert(format!("WEIGHTS_{}", set), buffer.push_floats(doc, &values, false));
+            }
+        }
+        let indices = geometry.get_index_data()
+            .filter(|v| !v.is_empty())
+            .map(|v| buffer.push_indices(doc, &v.iter().collect::<Vec<_>>()));
+        let material = geometry.get_material()
+            .and_then(|v| self.materials.get(&(&raw const *v)).copied());
+        let mode = get_primitive_mode(geometry.get_triangle_format());
+        let mut targets = vec![];
+        for (i, target) in geometry.get_morph_target().map_or(&[][..], |v| v.get_targets()).iter().enumerate() {
+            let offsets: Vec<[f32; 3]> = target.get_vertices().iter().map(|v| v.to_array()).collect();
+            if offsets.len() != count {
+                return Err(GltfError::InvalidAttributeCount(format!("targets[{}].POSITION", i)));
+            }
+            // Bounds are required for morph target positions
+            targets.push(BTreeMap::from([("POSITION".to_owned(), self.buffer.push_floats(&mut self.document, &offsets, true))]));
+        }
+        Ok(Some(document::Primitive {
+            attributes,
+            indices,
+            material,
+            mode: (mode != document::MODE_TRIANGLES).then_some(mode),
+            targets
+        }))
+    }
+}
+
+/// Export a mesh as a binary glTF (.glb) file
+pub fn export_glb<A>(mesh: &MeshFile<A>) -> Result<Vec<u8>, Box<dyn Error>>
+where A: Allocator + Clone {
+    let (mut document, bin) = GltfExporter::new(mesh).export()?;
+    Ok(write_glb(&mut document, &bin)?)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use allocator_api2::alloc::Global;
+    use glam::{ Vec3, Vec4 };
+    use crate::object::{
+        geometry::{ Geometry, GeometryVertexData, TriangleIndexFormat },
+        gltf::document::GltfError,
+        mesh::MeshFile,
+        node::Node
+    };
+    use std::error::Error;
+    use super::{ get_joint_sets, GltfExporter };
+
+    type TestReturn = Result<(), Box<dyn Error>>;
+
+    #[test]
+    fn split_vertex_weights() -> TestReturn {
+        let sets = get_joint_sets(&[0.25, 0.25, 0., 0., 0., 0., 0., 0.], &[3, 7, 9, 0, 0, 0, 0, 0]);
+        assert_eq!(sets[0], ([3, 7, 0, 0], [0.5, 0.5, 0., 0.]));
+        assert_eq!(sets[1], ([0; 4], [0.; 4]));
+        let sets = get_joint_sets(&[0.2, 0.2, 0.1, 0.1, 0.1, 0.1, 0.1, 0.1], &[0, 1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(sets[1].0, [4, 5, 6, 7]);
+        let total: f32 = sets.iter().flat_map(|v| v.1).sum();
+        assert!((total - 1.).abs() < 1e-5);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "serialize")]
+    fn export_skinned_mesh() -> TestReturn {
+        use crate::object::gltf::{
+            document::{ read_floats, BufferBuilder, Gltf, Material, Mesh, Node, Primitive, Scene, Skin },
+            import::{ import_document, GltfImportSettings }
+        };
+        use std::collections::BTreeMap;
+        let mut doc = Gltf::default();
+        let mut buffer = BufferBuilder::new();
+        let position = buffer.push_floats(&mut doc, &[[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]], true);
+        let joints = buffer.push_joints(&mut doc, &[[0; 4]; 3]);
+        let weights = buffer.push_floats(&mut doc, &[[1., 0., 0., 0.]; 3], false);
+        let bind = glam::Mat4::from_translation(Vec3::new(0., -1., 0.));
+        let matrices = buffer.push_matrices(&mut doc, &[bind.to_cols_array()]);
+        doc.meshes.push(Mesh {
+            name: Some("Body".to_owned()),
+            primitives: vec![Primitive {
+                attributes: BTreeMap::from([
+                    ("POSITION".to_owned(), position),
+                    ("JOINTS_0".to_owned(), joints),
+                    ("WEIGHTS_0".to_owned(), weights)
+                ]),
+                indices: None,
+                material: Some(0),
+                mode: None,
+                targets: vec![]
+            }],
+            weights: vec![],
+            extras: None
+        });
+        doc.materials.push(Material {
+            name: Some("Skin".to_owned()),
+            alpha_mode: Some("MASK".to_owned()),
+            alpha_cutoff: Some(0.5),
+            double_sided: true,
+            ..Default::default()
+        });
+        doc.skins.push(Skin { name: None, inverse_bind_matrices: Some(matrices), joints: vec![2], skeleton: None });
+        doc.nodes.push(Node { name: Some("RootNode".to_owned()), children: vec![1, 2], ..Default::default() });
+        doc.nodes.push(Node { name: Some("Body".to_owned()), mesh: Some(0), skin: Some(0), ..Default::default() });
+        doc.nodes.push(Node { name: Some("Bone".to_owned()), translation: Some([0., 1., 0.]), ..Default::default() });
+        doc.scenes.push(Scene { name: None, nodes: vec![0] });
+        let (mesh, _) = import_document(&doc, &[buffer.into_data()], None, &GltfImportSettings::default(), Global)?;
+
+        let (exported, bin) = GltfExporter::new(&mesh).export()?;
+        let names: Vec<_> = exported.nodes.iter().map(|n| n.name.as_deref()).collect();
+        assert_eq!(names, vec![Some("RootNode"), Some("Body"), Some("Bone")]);
+        assert_eq!(exported.nodes[0].children, vec![1, 2]);
+        assert_eq!(exported.nodes[2].translation, Some([0., 1., 0.]));
+        assert_eq!((exported.nodes[1].mesh, exported.nodes[1].skin), (Some(0), Some(0)));
+        assert_eq!(exported.scenes[0].nodes, vec![0]);
+        // Skin
+        assert_eq!(exported.skins.len(), 1);
+        assert_eq!(exported.skins[0].joints, vec![2]);
+        let buffers = vec![bin];
+        let matrices = read_floats::<16>(&exported, &buffers, exported.skins[0].inverse_bind_matrices.unwrap())?;
+        assert_eq!(matrices, vec![bind.to_cols_array()]);
+        let primitive = &exported.meshes[0].primitives[0];
+        assert!(["POSITION", "JOINTS_0", "WEIGHTS_0"].iter().all(|a| primitive.attributes.contains_key(*a)));
+        assert!(!primitive.attributes.contains_key("JOINTS_1"));
+        assert_eq!(read_floats::<4>(&exported, &buffers, primitive.attributes["WEIGHTS_0"])?, vec![[1., 0., 0., 0.]; 3]);
+        // Material
+        assert_eq!(exported.materials.len(), 1);
+        assert_eq!(primitive.material, Some(0));
+        let material = &exported.materials[0];
+        assert_eq!(material.name.as_deref(), Some("Skin"));
+        assert_eq!(material.alpha_mode.as_deref(), Some("MASK"));
+        assert!((material.alpha_cutoff.unwrap() - 0.5).abs() < 0.01);
+        assert!(material.double_sided);
+        Ok(())
+    }
+
+    #[test]
+    fn reject_mismatched_attributes() -> TestReturn {
+        let mut vertices = GeometryVertexData::new(Global);
+        vertices.get_positions_mut().extend([Vec4::W; 3]);
+        vertices.get_normals_mut().extend([Vec3::Z; 2]);
+        let geometry = Geometry::new_in(vertices, None, TriangleIndexFormat::ThreeIndicesPerTriangle, Global)?;
+        let mut root = Node::new_in("RootNode", Global);
+        unsafe { root.as_mut().attach_object(geometry.cast()) };
+        let mut mesh = MeshFile::new_in(Global);
+        mesh.set_root_node(root)?;
+        let error = GltfExporter::new(&mesh).export().unwrap_err();
+        assert!(matches!(error.downcast_ref::<GltfError>(), Some(GltfError::InvalidAttributeCount(v)) if v == "NORMAL"));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "serialize")]
+    fn export_morph_targets() -> TestReturn {
+        use crate::object::gltf::{
+            document::{ read_floats, BufferBuilder, Gltf, Mesh, MeshExtras, Node, Primitive },
+            import::{ import_document, GltfImportSettings }
+        };
+        use std::collections::BTreeMap;
+
+        let mut doc = Gltf::default();
+        let mut buffer = BufferBuilder::new();
+        let position = buffer.push_floats(&mut doc, &[[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]], true);
+        let smile = [[0., 0.5, 0.], [0., 0.25, 0.], [0., 0., 0.]];
+        let blink = [[1., 0., 0.], [0., 0., 1.], [0., 1., 0.]];
+        let targets = vec![
+            BTreeMap::from([("POSITION".to_owned(), buffer.push_floats(&mut doc, &smile, true))]),
+            BTreeMap::from([("POSITION".to_owned(), buffer.push_floats(&mut doc, &blink, true))])
+        ];
+        doc.meshes.push(Mesh {
+            name: Some("Face".to_owned()),
+            primitives: vec![
+                Primitive {
+                    attributes: BTreeMap::from([("POSITION".to_owned(), position)]),
+                    indices: None,
+                    material: None,
+                    mode: None,
+                    targets
+                },
+                // A primitive without morph targets has to be given some in the same mesh
+                Primitive {
+                    attributes: BTreeMap::from([("POSITION".to_owned(), position)]),
+                    indices: None,
+                    material: Some(0),
+                    mode: None,
+                    targets: vec![]
+                }
+            ],
+            weights: vec![0.25, 0.5],
+            extras: Some(MeshExtras { target_names: vec!["smile".to_owned(), "blink".to_owned()] })
+        });
+        doc.materials.push(Default::default());
+        doc.nodes.push(Node { name: Some("Face".to_owned()), mesh: Some(0), ..Default::default() });
+        let (mesh, _) = import_document(&doc, &[buffer.into_data()], None, &GltfImportSettings::default(), Global)?;
+
+        let (exported, bin) = GltfExporter::new(&mesh).export()?;
+        let buffers = vec![bin];
+        let face = exported.nodes.iter().find(|n| n.name.as_deref() == Some("Face")).and_then(|n| n.mesh).ok_or("missing mesh")?;
+        let face = &exported.meshes[face];
+        assert_eq!(face.weights, vec![0.25, 0.5]);
+        assert_eq!(face.extras.as_ref().map(|v| v.target_names.as_slice()), Some(&["smile".to_owned(), "blink".to_owned()][..]));
+        assert_eq!(face.primitives.len(), 2);
+        let offsets = face.primitives[0].targets.iter()
+            .map(|t| read_floats::<3>(&exported, &buffers, t["POSITION"]))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(offsets, vec![smile.to_vec(), blink.to_vec()]);
+        assert_eq!(face.primitives[1].targets.len(), 2);
+        for target in &face.primitives[1].targets {
+            assert_eq!(read_floats::<3>(&exported, &buffers, target["POSITION"])?, vec![[0.; 3]; 3]);
+            assert!(exported.accessors[target["POSITION"]].min.is_some());
+        }
+        // and back again
+        let (mesh, _) = import_document(&exported, &buffers, None, &GltfImportSettings::default(), Global)?;
+        let target = mesh.get_geometry_list().iter()
+            .find_map(|g| unsafe { g.as_ref() }.get_morph_target())
+            .ok_or("missing morph target")?;
+        assert_eq!(target.find_target("blink"), Some(1));
+        Ok(())
+    }
+}
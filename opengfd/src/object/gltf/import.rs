@@ -0,0 +1,827 @@
+//! Import glTF 2.0 models into a mesh, which can then be written as a model file for any
+//! supported version.
+//!
+//! Each glTF node becomes a node, and each primitive becomes a geometry attached to it's node.
+//! Triangle strips and fans are converted to triangle lists, since the primitive type is only
+//! stored in newer files. Every skin is merged into the mesh's single skin, with vertex weights
+//! kept as eight weights per vertex. These are reduced to four when writing a version before
+//! GeometryUseNewVertexWeightFormat.
+//!
+//! Every material is created using the same material type, with glTF's base color, normal and
+//! emissive textures placed in the slots that the material type uses for them. DDS images are
+//! stored as is, and other images are block compressed when the image crate is available.
+
+use allocator_api2::{
+    alloc::Allocator,
+    vec::Vec as AVec
+};
+use glam::{ Mat4, Quat, Vec2, Vec3, Vec4 };
+use crate::{
+    graphics::{
+        material::{ params::MaterialId, AlphaTest, AlphaTestFunc, BlendType, Blending, Culling, Material, MaterialFlags, MaterialTexture },
+        material_builder::MaterialBuilder,
+        texture::{ TextureEntry, TextureFormat, TextureSampler },
+        texture_codec::dds::{ DdsPixelFormat, DDS_MAGIC }
+    },
+    object::{
+        convert::{ convert_mesh, write_mesh, ConversionWarning, ConvertedFile },
+        geometry::{ Geometry, GeometryIndexData, GeometryVertexData, TriangleIndexFormat, VertexWeight },
+        mesh::MeshFile,
+        morph::{ MorphController, MorphTarget },
+        node::Node
+    },
+    utility::{
+        misc::RGBA,
+        name::Name,
+        stream::StreamHeader
+    }
+};
+use std::{
+    collections::{ BTreeMap, HashMap, HashSet },
+    error::Error,
+    fmt::Debug,
+    path::Path,
+    ptr::NonNull
+};
+use super::document::{
+    self,
+    get_view_data,
+    load_buffers,
+    load_uri,
+    read_floats,
+    read_glb,
+    read_integers,
+    Gltf,
+    GltfError,
+    Primitive,
+    GLB_MAGIC
+};
+
+/// Name of the node at the top of a model's hierarchy
+pub const ROOT_NODE_NAME: &str = "RootNode";
+
+// Number of texture slots in a material
+const MATERIAL_TEXTURE_SLOTS: usize = 10;
+
+/// Settings used when creating GFD data from a glTF model
+#[derive(Debug, Clone)]
+pub struct GltfImportSettings {
+    /// Material type used for every material
+    pub material_type: MaterialId,
+    /// Block compression used for images that aren't already DDS files
+    pub texture_format: DdsPixelFormat
+}
+
+impl Default for GltfImportSettings {
+    fn default() -> Self {
+        Self {
+            material_type: MaterialId::Lambert,
+            texture_format: DdsPixelFormat::Bc3
+        }
+    }
+}
+
+/// Get the GFD index format for a glTF primitive mode. Strips and fans are converted to triangle
+/// lists, and line loops have no equivalent.
+pub fn get_index_format(mode: u32) -> Option<TriangleIndexFormat> {
+    match mode {
+        document::MODE_POINTS => Some(TriangleIndexFormat::OneIndexPerTriangle),
+        document::MODE_LINES => Some(TriangleIndexFormat::TwoIndicesPerTriangle),
+        document::MODE_LINE_STRIP => Some(TriangleIndexFormat::OneIndexMinusOnePerTriangle),
+        document::MODE_TRIANGLES
+        | document::MODE_TRIANGLE_STRIP
+        | document::MODE_TRIANGLE_FAN => Some(TriangleIndexFormat::ThreeIndicesPerTriangle),
+        _ => None
+    }
+}
+
+/// Convert triangle strip or fan indices into a triangle list. Every other triangle in a strip
+/// is flipped so that they all keep the same winding order.
+pub fn triangulate(indices: &[u32], mode: u32) -> Vec<u32> {
+    let count = indices.len().saturating_sub(2);
+    let mut out = Vec::with_capacity(count * 3);
+    for i in 0..count {
+        let triangle = match mode {
+            document::MODE_TRIANGLE_FAN => [indices[0], indices[i + 1], indices[i + 2]],
+            _ if i % 2 == 1 => [indices[i], indices[i + 2], indices[i + 1]],
+            _ => [indices[i], indices[i + 1], indices[i + 2]]
+        };
+        out.extend_from_slice(&triangle);
+    }
+    out
+}
+
+fn get_color(value: &[f32; 4]) -> RGBA {
+    let [r, g, b, a] = value.map(|v| (v.clamp(0., 1.) * 255.).round() as u8);
+    RGBA::from_rgba_u8(r, g, b, a)
+}
+
+/// Add `extension` to `name`, adding a number before it if the result is already in `names`
+fn get_unique_name(names: &mut HashSet<String>, name: &str, extension: &str) -> String {
+    let mut out = format!("{}{}", name, extension);
+    let mut i = 1;
+    while names.contains(&out) {
+        out = format!("{}_{}{}", name, i, extension);
+        i += 1;
+    }
+    names.insert(out.clone());
+    out
+}
+
+/// Builds a mesh from a glTF document and it's buffers
+struct GltfImporter<'a, A>
+where A: Allocator + Clone {
+    document: &'a Gltf,
+    buffers: &'a [Vec<u8>],
+    base: Option<&'a Path>,
+    settings: &'a GltfImportSettings,
+    allocator: A,
+    mesh: MeshFile<A>,
+    // one for each glTF node
+    nodes: Vec<NonNull<Node<A>>>,
+    materials: BTreeMap<Option<usize>, NonNull<Material<A>>>,
+    material_names: HashSet<String>,
+    textures: BTreeMap<usize, Option<(String, TextureSampler)>>,
+    texture_names: HashSet<String>,
+    // glTF node and inverse bind matrix for each bone in the merged skin
+    bones: Vec<(usize, Mat4)>,
+    // bone index for each joint, for every glTF skin
+    joints: Vec<Vec<u16>>,
+    warnings: Vec<ConversionWarning>
+}
+
+impl<'a, A> GltfImporter<'a, A>
+where A: Allocator + Clone {
+    fn new(document: &'a Gltf, buffers: &'a [Vec<u8>], base: Option<&'a Path>, settings: &'a GltfImportSettings, allocator: A) -> Self {
+        Self {
+            document,
+            buffers,
+            base,
+            settings,
+            mesh: MeshFile::new_in(allocator.clone()),
+            allocator,
+            nodes: vec![],
+            materials: BTreeMap::new(),
+            material_names: HashSet::new(),
+            textures: BTreeMap::new(),
+            texture_names: HashSet::new(),
+            bones: vec![],
+            joints: vec![],
+            warnings: vec![]
+        }
+    }
+
+    fn import(mut self) -> Result<(MeshFile<A>, Vec<ConversionWarning>), Box<dyn Error>> {
+        self.import_skins()?;
+        self.import_nodes();
+        let root = self.import_scene();
+        self.import_meshes()?;
+        for material in self.materials.values() {
+            self.mesh.add_material(*material)?;
+        }
+        self.mesh.set_root_node(root)?;
+        self.set_skin()?;
+        self.mesh.recompute_bounds();
+        Ok((self.mesh, self.warnings))
+    }
+
+    fn import_skins(&mut self) -> Result<(), GltfError> {
+        let document = self.document;
+        for skin in &document.skins {
+            let matrices: Vec<Mat4> = match skin.inverse_bind_matrices {
+                Some(v) => read_floats::<16>(document, self.buffers, v)?.iter().map(Mat4::from_cols_array).collect(),
+                None => vec![]
+            };
+            let mut joints = Vec::with_capacity(skin.joints.len());
+            for (i, node) in skin.joints.iter().enumerate() {
+                let matrix = matrices.get(i).copied().unwrap_or(Mat4::IDENTITY);
+                // Skins that share a joint with the same bind pose can share a bone
+                let bone = match self.bones.iter().position(|b| b.0 == *node && b.1 == matrix) {
+                    Some(v) => v,
+                    None => {
+                        self.bones.push((*node, matrix));
+                        self.bones.len() - 1
+                    }
+                };
+                joints.push(bone as u16);
+            }
+            self.joints.push(joints);
+        }
+        Ok(())
+    }
+
+    fn import_nodes(&mut self) {
+        for (i, node) in self.document.nodes.iter().enumerate() {
+            let name = node.name.clone().unwrap_or_else(|| format!("Node{}", i));
+            let mut ptr = Node::new_in(&name, self.allocator.clone());
+            let (scale, rotate, translate) = match node.matrix {
+                Some(m) => Mat4::from_cols_array(&m).to_scale_rotation_translation(),
+                None => (
+                    node.scale.map_or(Vec3::ONE, Vec3::from_array),
+                    node.rotation.map_or(Quat::IDENTITY, Quat::from_array),
+                    node.translation.map_or(Vec3::ZERO, Vec3::from_array)
+                )
+            };
+            let target = unsafe { ptr.as_mut() };
+            target.set_translate(translate.into());
+            target.set_rotate(rotate);
+            target.set_scale(scale.into());
+            self.nodes.push(ptr);
+        }
+        for (i, node) in self.document.nodes.iter().enumerate() {
+            let mut parent = self.nodes[i];
+            // Children are prepended, so add them in reverse to keep their order
+            for child in node.children.iter().rev().filter_map(|c| self.nodes.get(*c)) {
+                unsafe { parent.as_mut().prepend_child(*child) };
+            }
+        }
+    }
+
+    /// Get the root node for the scene, creating one if the scene doesn't have a single root
+    /// node named RootNode
+    fn import_scene(&self) -> NonNull<Node<A>> {
+        let document = self.document;
+        let roots: Vec<usize> = match document.scenes.get(document.scene.unwrap_or(0)) {
+            Some(scene) => scene.nodes.clone(),
+            // Without a scene, every node that isn't a child of another node is a root
+            None => (0..document.nodes.len())
+                .filter(|i| !document.nodes.iter().any(|n| n.children.contains(i)))
+                .collect()
+        };
+        if let [root] = roots.as_slice() {
+            if document.nodes.get(*root).and_then(|n| n.name.as_deref()) == Some(ROOT_NODE_NAME) {
+                return self.nodes[*root];
+            }
+        }
+        let mut root = Node::new_in(ROOT_NODE_NAME, self.allocator.clone());
+        for node in roots.iter().rev().filter_map(|i| self.nodes.get(*i)) {
+            unsafe { root.as_mut().prepend_child(*node) };
+        }
+        root
+    }
+
+    fn import_meshes(&mut self) -> Result<(), Box<dyn Error>> {
+        let document = self.document;
+        for (i, node) in document.nodes.iter().enumerate() {
+            let (index, gltf_mesh) = match node.mesh.and_then(|m| document.meshes.get(m).map(|v| (m, v))) {
+                Some(v) => v,
+                None => continue
+            };
+            let mut parent = self.nodes[i];
+            let target_names = gltf_mesh.extras.as_ref().map_or(&[][..], |v| v.target_names.as_slice());
+            for (p, primitive) in gltf_mesh.primitives.iter().enumerate() {
+                let geometry = match self.import_primitive(primitive, node.skin, target_names)? {
+                    Some(v) => v,
+                    None => {
+                        let mesh = gltf_mesh.name.clone().unwrap_or_else(|| format!("Mesh{}", index));
+                        self.warnings.push(ConversionWarning::PrimitiveSkipped { mesh, primitive: p });
+                        continue;
+                    }
+                };
+                unsafe { parent.as_mut().attach_object(geometry.cast()) };
+                // Morph controllers find their geometry using it's material name
+                let geometry_ref = unsafe { geometry.as_ref() };
+                if let Some(targets) = geometry_ref.get_morph_target() {
+                    let name = geometry_ref.get_material_name().unwrap_or_default();
+                    let mut controller = MorphController::new_in(name, targets.get_targets().len(), self.allocator.clone())?;
+                    for (weight, value) in unsafe { controller.as_mut() }.get_weights_mut().iter_mut().zip(gltf_mesh.weights.iter()) {
+                        *weight = *value;
+                    }
+                    unsafe { parent.as_mut().attach_object(controller.cast()) };
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Read a vertex attribute, checking that it has a value for every vertex
+    fn read_attribute<const N: usize>(&self, primitive: &Primitive, name: &str, count: usize) -> Result<Option<Vec<[f32; N]>>, GltfError> {
+        let index = match primitive.attributes.get(name) {
+            Some(v) => *v,
+            None => return Ok(None)
+        };
+        let values = read_floats::<N>(self.document, self.buffers, index)?;
+        match values.len() == count {
+            true => Ok(Some(values)),
+            false => Err(GltfError::InvalidAccessor(index))
+        }
+    }
+
+    fn import_primitive(&mut self, primitive: &Primitive, skin: Option<usize>, target_names: &[String]) -> Result<Option<NonNull<Geometry<A>>>, Box<dyn Error>> {
+        let document = self.document;
+        let mode = primitive.mode.unwrap_or(document::MODE_TRIANGLES);
+        let format = match get_index_format(mode) {
+            Some(v) => v,
+            None => return Ok(None)
+        };
+        let positions: Vec<Vec3> = match primitive.attributes.get("POSITION") {
+            Some(v) => read_floats::<3>(document, self.buffers, *v)?.into_iter().map(Vec3::from_array).collect(),
+            None => return Ok(None)
+        };
+        if positions.is_empty() {
+            return Ok(None);
+        }
+        let count = positions.len();
+        let mut vertices = GeometryVertexData::new(self.allocator.clone());
+        vertices.get_positions_mut().extend(positions.iter().map(|v| v.extend(1.)));
+        let normals = self.read_attribute::<3>(primitive, "NORMAL", count)?;
+        if let Some(normals) = &normals {
+            vertices.get_normals_mut().extend(normals.iter().map(|v| Vec3::from_array(*v)));
+            if let Some(tangents) = self.read_attribute::<4>(primitive, "TANGENT", count)? {
+                // glTF stores the binormal's direction as the sign of the tangent's W
+                for (normal, tangent) in normals.iter().zip(tangents.iter()) {
+                    let t = Vec4::from_array(*tangent);
+                    vertices.get_tangents_mut().push(t.truncate());
+                    vertices.get_binormals_mut().push(Vec3::from_array(*normal).cross(t.truncate()) * t.w.signum());
+                }
+            }
+        }
+        for channel in 0..3 {
+            if let Some(values) = self.read_attribute::<2>(primitive, &format!("TEXCOORD_{}", channel), count)? {
+                vertices.get_tex_coords_mut(channel).extend(values.iter().map(|v| Vec2::from_array(*v)));
+            }
+        }
+        for channel in 0..3 {
+            let values = match self.read_attribute::<4>(primitive, &format!("COLOR_{}", channel), count)? {
+                Some(v) => v,
+                None => continue
+            };
+            let target = match channel {
+                0 => vertices.get_diffuse_colors_mut(),
+                1 => vertices.get_color2_mut(),
+                _ => vertices.get_color3_mut()
+            };
+            target.extend(values.iter().map(get_color));
+        }
+        if let Some(weights) = self.read_weights(primitive, skin, count)? {
+            vertices.get_weights_mut().extend(weights);
+        }
+        let mut indices = match primitive.indices {
+            Some(v) => read_integers(document, self.buffers, v)?,
+            None => (0..count as u32).collect()
+        };
+        if mode == document::MODE_TRIANGLE_STRIP || mode == document::MODE_TRIANGLE_FAN {
+            indices = triangulate(&indices, mode);
+        }
+        // Skip primitives that don't have enough indices for a single triangle or line
+        if format.from_vertex_count(indices.len() as u32).is_none() {
+            return Ok(None);
+        }
+        if indices.iter().any(|i| *i as usize >= count) {
+            return Err(Box::new(GltfError::InvalidAccessor(primitive.indices.unwrap_or_default())));
+        }
+        let indices = match count <= u16::MAX as usize + 1 {
+            true => {
+                let mut out = AVec::with_capacity_in(indices.len(), self.allocator.clone());
+                out.extend(indices.iter().map(|i| *i as u16));
+                GeometryIndexData::UInt16(out)
+            },
+            false => {
+                let mut out = AVec::with_capacity_in(indices.len(), self.allocator.clone());
+                out.extend_from_slice(&indices);
+                GeometryIndexData::UInt32(out)
+            }
+        };
+        let mut targets = Vec::with_capacity(primitive.targets.len());
+        for target in &primitive.targets {
+            let offsets: Vec<Vec3> = match target.get("POSITION") {
+                Some(v) => read_floats::<3>(document, self.buffers, *v)?.into_iter().map(Vec3::from_array).collect(),
+                None => vec![Vec3::ZERO; count]
+            };
+            if offsets.len() != count {
+                return Err(Box::new(GltfError::InvalidAccessor(target["POSITION"])));
+            }
+            targets.push(offsets);
+        }
+        let material = self.import_material(primitive.material)?;
+        let mut ptr = Geometry::new_in(vertices, Some(indices), format, self.allocator.clone())?;
+        let geometry = unsafe { ptr.as_mut() };
+        geometry.set_material(material);
+        if !targets.is_empty() {
+            let mut morph = MorphTarget::new_in(&targets, self.allocator.clone())?;
+            for (i, name) in target_names.iter().take(targets.len()).enumerate() {
+                unsafe { morph.as_mut() }.set_target_name(i, name)?;
+            }
+            geometry.set_morph_target(morph);
+        }
+        geometry.recompute_bounds();
+        Ok(Some(ptr))
+    }
+
+    /// Read up to two sets of joints and weights, converting joints to bone indices in the
+    /// merged skin. Weights are normalized so that they add up to one.
+    fn read_weights(&self, primitive: &Primitive, skin: Option<usize>, count: usize) -> Result<Option<Vec<VertexWeight>>, GltfError> {
+        let joints = match skin.and_then(|s| self.joints.get(s)) {
+            Some(v) if primitive.attributes.contains_key("JOINTS_0") => v,
+            _ => return Ok(None)
+        };
+        let mut weights = vec![VertexWeight::default(); count];
+        for set in 0..2 {
+            let indices = self.read_attribute::<4>(primitive, &format!("JOINTS_{}", set), count)?;
+            let values = self.read_attribute::<4>(primitive, &format!("WEIGHTS_{}", set), count)?;
+            let (indices, values) = match (indices, values) {
+                (Some(i), Some(v)) => (i, v),
+                _ => continue
+            };
+            for (weight, (indices, values)) in weights.iter_mut().zip(indices.iter().zip(values.iter())) {
+                for (i, (joint, value)) in indices.iter().zip(values.iter()).enumerate() {
+                    weight.weights[set * 4 + i] = *value;
+                    weight.indices[set * 4 + i] = *joints.get(*joint as usize).ok_or(GltfError::InvalidJoint(*joint as usize))?;
+                }
+            }
+        }
+        for weight in &mut weights {
+            let total: f32 = weight.weights.iter().sum();
+            if total > 0. {
+                weight.weights.iter_mut().for_each(|w| *w /= total);
+            }
+        }
+        Ok(Some(weights))
+    }
+
+    fn import_material(&mut self, index: Option<usize>) -> Result<NonNull<Material<A>>, Box<dyn Error>> {
+        if let Some(material) = self.materials.get(&index) {
+            return Ok(*material);
+        }
+        let document = self.document;
+        let gltf = index.and_then(|i| document.materials.get(i));
+        let name = match (gltf.and_then(|m| m.name.as_deref()), index) {
+            (Some(name), _) => name.to_owned(),
+            (None, Some(i)) => format!("Material{}", i),
+            (None, None) => "DefaultMaterial".to_owned()
+        };
+        let name = get_unique_name(&mut self.material_names, &name, "");
+        let mut builder = MaterialBuilder::with_type(&name, self.settings.material_type, self.allocator.clone());
+        if let Some(material) = gltf {
+            match material.alpha_mode.as_deref() {
+                Some("BLEND") => builder = builder.blend(Blending::new(BlendType::Semitrans)),
+                Some("MASK") => {
+                    let cutoff = (material.alpha_cutoff.unwrap_or(0.5).clamp(0., 1.) * 255.).round() as i16;
+                    builder = builder.alpha_test(AlphaTest::new(AlphaTestFunc::GreaterOrEqual0, cutoff));
+                },
+                _ => ()
+            };
+            if material.double_sided {
+                builder = builder.culling(Culling::None);
+            }
+            let textures = [
+                (material.pbr_metallic_roughness.as_ref().and_then(|p| p.base_color_texture.as_ref()), &["Base Texture", "Layer 0 Base Texture"][..]),
+                (material.normal_texture.as_ref(), &["Normal Texture", "Layer 0 Normal Texture"][..]),
+                (material.emissive_texture.as_ref(), &["Emissive Texture"][..])
+            ];
+            for (info, roles) in textures {
+                let (texture, sampler) = match info.and_then(|v| self.import_texture(v.index).transpose()).transpose()? {
+                    Some(v) => v,
+                    None => continue
+                };
+                let slot = (0..MATERIAL_TEXTURE_SLOTS).find(|s| roles.contains(&builder.get_material_mut().get_texture_slot_name(*s)));
+                let slot = match slot {
+                    Some(v) => v,
+                    None => {
+                        self.warnings.push(ConversionWarning::TextureSlotUnavailable { material: name.clone(), slot: roles[0].to_owned() });
+                        continue;
+                    }
+                };
+                let mut texture = MaterialTexture::new_in(&texture, self.allocator.clone());
+                texture.set_sampler(sampler);
+                builder = builder.texture(slot, texture);
+                if roles[0] == "Emissive Texture" {
+                    builder = builder.flag(MaterialFlags::Emissive, true);
+                }
+            }
+        }
+        let material = builder.build()?;
+        self.materials.insert(index, material);
+        Ok(material)
+    }
+
+    /// Add a glTF texture to the mesh's texture dictionary, returning it's name and sampler.
+    /// Textures that can't be loaded are skipped with a warning.
+    fn import_texture(&mut self, index: usize) -> Result<Option<(String, TextureSampler)>, Box<dyn Error>> {
+        if let Some(texture) = self.textures.get(&index) {
+            return Ok(texture.clone());
+        }
+        let document = self.document;
+        let texture = document.textures.get(index);
+        let image = texture
+            .and_then(|t| t.extensions.as_ref().and_then(|e| e.texture_dds.as_ref()).map(|s| s.source).or(t.source))
+            .and_then(|i| document.images.get(i));
+        let uri = image.and_then(|i| i.uri.as_deref()).filter(|u| !u.starts_with("data:"));
+        let stem = image.and_then(|i| i.name.as_deref())
+            .or(uri)
+            .or(texture.and_then(|t| t.name.as_deref()))
+            .and_then(|v| Path::new(v).file_stem())
+            .map_or_else(|| format!("Texture{}", index), |v| v.to_string_lossy().into_owned());
+        let name = get_unique_name(&mut self.texture_names, &stem, ".dds");
+        let bytes = match image {
+            Some(document::Image { buffer_view: Some(view), .. }) => Some(get_view_data(document, self.buffers, *view)?.to_vec()),
+            Some(document::Image { uri: Some(uri), .. }) => load_uri(uri, self.base).ok(),
+            _ => None
+        };
+        let sampler = texture.and_then(|t| t.sampler)
+            .and_then(|s| document.samplers.get(s))
+            .map_or(TextureSampler::default(), TextureSampler::from);
+        let result = match bytes.and_then(|b| self.create_texture(&name, &b)) {
+            Some(mut entry) => {
+                entry.set_sampler(sampler);
+                self.mesh.insert_texture(entry)?;
+                Some((name, sampler))
+            },
+            None => {
+                self.warnings.push(ConversionWarning::TextureSkipped { texture: name });
+                None
+            }
+        };
+        self.textures.insert(index, result.clone());
+        Ok(result)
+    }
+
+    fn create_texture(&self, name: &str, bytes: &[u8]) -> Option<TextureEntry<A>> {
+        if bytes.starts_with(&DDS_MAGIC.to_le_bytes()) {
+            let mut data = AVec::with_capacity_in(bytes.len(), self.allocator.clone());
+            data.extend_from_slice(bytes);
+            return Some(TextureEntry::new(Name::new_in(name, self.allocator.clone()), TextureFormat::DDS, data));
+        }
+        #[cfg(feature = "image_loader")]
+        if let Ok(entry) = TextureEntry::from_image_stream(name, bytes, self.settings.texture_format, self.allocator.clone()) {
+            return Some(entry);
+        }
+        None
+    }
+
+    /// Set the mesh's skin from the merged skin. Bones refer to nodes by their index in the
+    /// mesh's node list, so this is done after the hierarchy is set.
+    fn set_skin(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.bones.is_empty() {
+            return Ok(());
+        }
+        let order: HashMap<*mut Node<A>, usize> = self.mesh.get_node_list().iter()
+            .enumerate()
+            .map(|(i, n)| (n.as_ptr(), i))
+            .collect();
+        let matrices: Vec<Mat4> = self.bones.iter().map(|b| b.1).collect();
+        let bone_to_node: Vec<u16> = self.bones.iter()
+            .map(|b| self.nodes.get(b.0).and_then(|n| order.get(&n.as_ptr())).map_or(0, |i| *i as u16))
+            .collect();
+        self.mesh.set_skin(&matrices, &bone_to_node)
+    }
+}
+
+/// Build a mesh from a glTF document and it's buffers. Relative URIs are loaded from `base`.
+pub fn import_document<A>(document: &Gltf, buffers: &[Vec<u8>], base: Option<&Path>, settings: &GltfImportSettings, allocator: A) -> Result<(MeshFile<A>, Vec<ConversionWarning>), Box<dyn Error>>
+where A: Allocator + Clone {
+    GltfImporter::new(document, buffers, base, settings, allocator).import()
+}
+
+fn write_document<A>(document: &Gltf, buffers: &[Vec<u8>], base: Option<&Path>, header: StreamHeader, settings: &GltfImportSettings, allocator: A) -> Result<ConvertedFile<A>, Box<dyn Error>>
+where A: Allocator + Clone + Debug {
+    let (mut mesh, mut warnings) = import_document(document, buffers, base, settings, allocator.clone())?;
+    // Reduce vertex weights and replace materials that the target version can't store
+    let version = header.get_version();
+    warnings.extend(convert_mesh(&mut mesh, version, version)?);
+    write_mesh(&mesh, header, warnings, allocator)
+}
+
+/// Convert a binary glTF (.glb) file into a model file, using the version, platform and byte
+/// order in `header`
+pub fn import_glb<A>(bytes: &[u8], header: StreamHeader, settings: &GltfImportSettings, allocator: A) -> Result<ConvertedFile<A>, Box<dyn Error>>
+where A: Allocator + Clone + Debug {
+    let (document, bin) = read_glb(bytes)?;
+    let buffers = load_buffers(&document, bin, None)?;
+    write_document(&document, &buffers, None, header, settings, allocator)
+}
+
+/// Convert a .gltf or .glb file into a model file. Buffers and images stored in other files are
+/// loaded from the same directory.
+pub fn import_file<A>(path: &Path, header: StreamHeader, settings: &GltfImportSettings, allocator: A) -> Result<ConvertedFile<A>, Box<dyn Error>>
+where A: Allocator + Clone + Debug {
+    let bytes = std::fs::read(path)?;
+    let (document, bin) = match bytes.starts_with(&GLB_MAGIC.to_le_bytes()) {
+        true => read_glb(&bytes)?,
+        false => (serde_json::from_slice(&bytes)?, None)
+    };
+    let buffers = load_buffers(&document, bin, path.parent())?;
+    write_document(&document, &buffers, path.parent(), header, settings, allocator)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use allocator_api2::alloc::Global;
+    use glam::Mat4;
+    use crate::{
+        graphics::material::{ AlphaTestFunc, Culling, MaterialFlags },
+        object::{
+            convert::ConversionWarning,
+            geometry::{ Geometry, GeometryFlags, TriangleIndexType },
+            gltf::document::{ self, write_glb, BufferBuilder, Gltf, GltfError, Material, Mesh, Node, Primitive, Scene, Skin },
+            mesh::MeshFile
+        },
+        utility::stream::{ DevicePlatform, GfdSerialize, SerializationSingleAllocator, StreamFactory, StreamHeader }
+    };
+    use std::{
+        collections::BTreeMap,
+        error::Error
+    };
+    use super::{ import_document, import_glb, triangulate, GltfImportSettings, ROOT_NODE_NAME };
+
+    type TestReturn = Result<(), Box<dyn Error>>;
+
+    // Add a mesh containing a triangle for each material, with every vertex bound to `joint`
+    fn push_triangles(doc: &mut Gltf, buffer: &mut BufferBuilder, materials: &[Option<usize>], joint: u16) -> usize {
+        let position = buffer.push_floats(doc, &[[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]], true);
+        let joints = buffer.push_joints(doc, &[[joint, 0, 0, 0]; 3]);
+        let weights = buffer.push_floats(doc, &[[1., 0., 0., 0.]; 3], false);
+        doc.meshes.push(Mesh {
+            name: None,
+            primitives: materials.iter().map(|material| Primitive {
+                attributes: BTreeMap::from([
+                    ("POSITION".to_owned(), position),
+                    ("JOINTS_0".to_owned(), joints),
+                    ("WEIGHTS_0".to_owned(), weights)
+                ]),
+                indices: None,
+                material: *material,
+                mode: None,
+                targets: vec![]
+            }).collect(),
+            weights: vec![],
+            extras: None
+        });
+        doc.meshes.len() - 1
+    }
+
+    fn get_geometry(mesh: &MeshFile<Global>, node: usize) -> Option<&Geometry<Global>> {
+        let node = unsafe { mesh.get_node_list()[node].as_ref() };
+        node.iter_object().find_map(|v| v.as_object::<Geometry<Global>>())
+    }
+
+    #[test]
+    fn triangulate_strips_and_fans() -> TestReturn {
+        let indices = [0, 1, 2, 3, 4];
+        assert_eq!(triangulate(&indices, document::MODE_TRIANGLE_STRIP), vec![0, 1, 2, 1, 3, 2, 2, 3, 4]);
+        assert_eq!(triangulate(&indices, document::MODE_TRIANGLE_FAN), vec![0, 1, 2, 0, 2, 3, 0, 3, 4]);
+        assert!(triangulate(&indices[..2], document::MODE_TRIANGLE_STRIP).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn import_quad() -> TestReturn {
+        let mut doc = Gltf::default();
+        let mut buffer = BufferBuilder::new();
+        let positions = [[0., 0., 0.], [1., 0., 0.], [0., 2., 0.], [1., 2., 0.]];
+        let position = buffer.push_floats(&mut doc, &positions, true);
+        let line = buffer.push_floats(&mut doc, &positions[..2], true);
+        doc.meshes.push(Mesh {
+            name: Some("quad".to_owned()),
+            primitives: vec![
+                Primitive {
+                    attributes: BTreeMap::from([("POSITION".to_owned(), position)]),
+                    indices: None,
+                    material: None,
+                    mode: Some(document::MODE_TRIANGLE_STRIP),
+                    targets: vec![]
+                },
+                Primitive {
+                    attributes: BTreeMap::from([("POSITION".to_owned(), position)]),
+                    indices: None,
+                    material: None,
+                    mode: Some(document::MODE_LINE_LOOP),
+                    targets: vec![]
+                },
+                // Two vertices aren't enough for a triangle
+                Primitive {
+                    attributes: BTreeMap::from([("POSITION".to_owned(), line)]),
+                    indices: None,
+                    material: None,
+                    mode: Some(document::MODE_TRIANGLE_FAN),
+                    targets: vec![]
+                }
+            ],
+            weights: vec![],
+            extras: None
+        });
+        doc.nodes.push(Node { name: Some("quad".to_owned()), mesh: Some(0), translation: Some([0., 0., 5.]), ..Default::default() });
+        doc.scenes.push(Scene { name: None, nodes: vec![0] });
+        let buffers = vec![buffer.into_data()];
+        let (mesh, warnings) = import_document(&doc, &buffers, None, &GltfImportSettings::default(), Global)?;
+        assert_eq!(warnings.len(), 2);
+        assert!(matches!(&warnings[1], ConversionWarning::PrimitiveSkipped { primitive: 2, .. }));
+        assert_eq!(mesh.get_root_node().and_then(|n| n.get_name()), Some(ROOT_NODE_NAME));
+        assert_eq!(mesh.get_node_list().len(), 2);
+        assert_eq!(mesh.get_material_list().len(), 1);
+        let geometry = unsafe { mesh.get_geometry_list()[0].as_ref() };
+        assert!(geometry.get_flags().contains(GeometryFlags::Triangles | GeometryFlags::Material | GeometryFlags::BoundingBox));
+        assert_eq!(geometry.get_index_data().map(|v| v.get_index_type()), Some(TriangleIndexType::UInt16));
+        assert_eq!(geometry.get_triangle_count(), 2);
+        assert_eq!(mesh.get_bounding_box().get_min().z, 5.);
+        Ok(())
+    }
+
+    #[test]
+    fn import_merged_skins() -> TestReturn {
+        let mut doc = Gltf::default();
+        let mut buffer = BufferBuilder::new();
+        let mesh = push_triangles(&mut doc, &mut buffer, &[None], 0);
+        let bind = Mat4::from_translation(glam::Vec3::new(0., -1., 0.));
+        let first = buffer.push_matrices(&mut doc, &[bind.to_cols_array()]);
+        let second = buffer.push_matrices(&mut doc, &[Mat4::IDENTITY.to_cols_array(), bind.to_cols_array()]);
+        // Both skins use the same bind pose for Bone, so it becomes a single bone
+        doc.skins.push(Skin { name: None, inverse_bind_matrices: Some(first), joints: vec![2], skeleton: None });
+        doc.skins.push(Skin { name: None, inverse_bind_matrices: Some(second), joints: vec![1, 2], skeleton: None });
+        doc.nodes.push(Node { name: Some(ROOT_NODE_NAME.to_owned()), children: vec![1, 2, 3], ..Default::default() });
+        doc.nodes.push(Node { name: Some("Body".to_owned()), mesh: Some(mesh), skin: Some(0), ..Default::default() });
+        doc.nodes.push(Node { name: Some("Bone".to_owned()), ..Default::default() });
+        doc.nodes.push(Node { name: Some("Body2".to_owned()), mesh: Some(mesh), skin: Some(1), ..Default::default() });
+        doc.scenes.push(Scene { name: None, nodes: vec![0] });
+        let buffers = vec![buffer.into_data()];
+        let (file, warnings) = import_document(&doc, &buffers, None, &GltfImportSettings::default(), Global)?;
+        assert!(warnings.is_empty());
+        assert_eq!(file.get_bone_to_node_indices(), &[2, 1]);
+        assert_eq!(file.get_inverse_bind_matrices(), &[bind, Mat4::IDENTITY]);
+        // Joint 0 is Bone in the first skin and Body in the second
+        for (node, bone) in [(1, 0), (3, 1)] {
+            let geometry = get_geometry(&file, node).ok_or("missing geometry")?;
+            assert!(geometry.get_flags().contains(GeometryFlags::Skin));
+            let weights = geometry.get_vertex_data().unwrap().get_weights();
+            assert!(weights.iter().all(|w| w.indices[0] == bone && w.weights[0] == 1.));
+        }
+        // Joints must exist in the node's skin
+        let mut doc = Gltf::default();
+        let mut buffer = BufferBuilder::new();
+        let mesh = push_triangles(&mut doc, &mut buffer, &[None], 1);
+        doc.skins.push(Skin { name: None, inverse_bind_matrices: None, joints: vec![0], skeleton: None });
+        doc.nodes.push(Node { name: Some("Body".to_owned()), mesh: Some(mesh), skin: Some(0), ..Default::default() });
+        let buffers = vec![buffer.into_data()];
+        let error = import_document(&doc, &buffers, None, &GltfImportSettings::default(), Global).unwrap_err();
+        assert!(matches!(error.downcast_ref::<GltfError>(), Some(GltfError::InvalidJoint(1))));
+        Ok(())
+    }
+
+    #[test]
+    fn import_materials() -> TestReturn {
+        let mut doc = Gltf::default();
+        let mut buffer = BufferBuilder::new();
+        let mesh = push_triangles(&mut doc, &mut buffer, &[Some(0), Some(1), None, Some(0)], 0);
+        doc.materials.push(Material {
+            name: Some("Metal".to_owned()),
+            alpha_mode: Some("MASK".to_owned()),
+            alpha_cutoff: Some(0.5),
+            ..Default::default()
+        });
+        doc.materials.push(Material {
+            name: Some("Metal".to_owned()),
+            alpha_mode: Some("BLEND".to_owned()),
+            double_sided: true,
+            ..Default::default()
+        });
+        doc.nodes.push(Node { name: Some("Body".to_owned()), mesh: Some(mesh), ..Default::default() });
+        let buffers = vec![buffer.into_data()];
+        let (file, _) = import_document(&doc, &buffers, None, &GltfImportSettings::default(), Global)?;
+        // Primitives that share a material share it's GFD material
+        assert_eq!(file.get_material_list().len(), 3);
+        let materials: Vec<_> = file.get_geometry_list().iter()
+            .map(|g| unsafe { g.as_ref() }.get_material().map(|m| m as *const _))
+            .collect();
+        assert_eq!(materials[0], materials[3]);
+        let get_material = |name: &str| file.get_material_list().iter()
+            .map(|m| unsafe { m.as_ref() })
+            .find(|m| m.get_name() == Some(name));
+        // Duplicate names are made unique
+        let mask = get_material("Metal").ok_or("missing Metal")?;
+        assert!(mask.has_flags(MaterialFlags::AlphaTest));
+        assert!(matches!(mask.get_alpha_test().get_func(), AlphaTestFunc::GreaterOrEqual0));
+        assert_eq!(mask.get_alpha_test().get_value(), 128);
+        assert!(!mask.check_translucency());
+        let blend = get_material("Metal_1").ok_or("missing Metal_1")?;
+        assert!(blend.check_translucency());
+        assert!(blend.get_culling() == Culling::None);
+        assert!(get_material("DefaultMaterial").is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn write_imported_glb() -> TestReturn {
+        let mut doc = Gltf::default();
+        let mut buffer = BufferBuilder::new();
+        let mesh = push_triangles(&mut doc, &mut buffer, &[None], 0);
+        let bones = buffer.push_matrices(&mut doc, &[Mat4::IDENTITY.to_cols_array()]);
+        doc.skins.push(Skin { name: None, inverse_bind_matrices: Some(bones), joints: vec![0], skeleton: None });
+        doc.nodes.push(Node { name: Some("Body".to_owned()), mesh: Some(mesh), skin: Some(0), ..Default::default() });
+        let glb = write_glb(&mut doc, &buffer.into_data())?;
+        let header = StreamHeader::new(0x2110000, DevicePlatform::METAPHOR);
+        let converted = import_glb(&glb, header, &GltfImportSettings::default(), Global)?;
+        assert!(converted.get_warnings().is_empty());
+        let mut stream = StreamFactory::new(Global).read_from_memory(converted.get_data())?;
+        assert_eq!(stream.get_header_version(), 0x2110000);
+        let file = MeshFile::<Global>::stream_read(&mut stream, &mut SerializationSingleAllocator::new(Global))?.into_raw();
+        let names: Vec<_> = file.get_node_list().iter().map(|n| unsafe { n.as_ref() }.get_name()).collect();
+        assert_eq!(names, vec![Some(ROOT_NODE_NAME), Some("Body")]);
+        assert_eq!(file.get_geometry_list().len(), 1);
+        assert_eq!(file.get_material_list().len(), 1);
+        assert_eq!(file.get_bone_to_node_indices(), &[1]);
+        let geometry = get_geometry(&file, 1).ok_or("missing geometry")?;
+        assert_eq!(geometry.get_vertex_data().map(|v| v.len()), Some(3));
+        assert!(geometry.get_vertex_data().unwrap().get_weights().iter().all(|w| w.weights[0] == 1.));
+        Ok(())
+    }
+}
@@ -152,6 +152,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.stream_read_inner(stream)?;
         Ok(this)
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut SerializationSingleAllocator<AObject>) -> DynRes<()> {
+        self.stream_write_inner(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -196,12 +200,81 @@ where AObject: Allocator + Clone
             }
         }
         self.alpha = stream.has_feature(GfdVersion::LightAddAlpha).map_or(Ok(0.), |_| stream.read_f32())?;
-        #[cfg(feature = "v2-core")]
-        {
-            self.toon = stream.has_feature(GfdVersion::LightAddToonInfluence).map_or(Ok(1.), |_| stream.read_f32())?;
+        let toon = match Self::has_toon_influence(stream) {
+            true => stream.read_f32()?,
+            false => 1.
+        };
+        self.set_toon_influence(toon);
+        Ok(())
+    }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> DynRes<()>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        if stream.has_feature(GfdVersion::LightAddFlags).is_some() {
+            stream.write_u32(self.flags.bits())?;
+        }
+        stream.write_u32(self.type_ as u32)?;
+        self.ambient.stream_write(stream, &mut ())?;
+        self.diffuse.stream_write(stream, &mut ())?;
+        self.specular.stream_write(stream, &mut ())?;
+        match self.type_ {
+            LightType::Directional => {
+                self.direction.stream_write(stream, &mut ())?;
+            },
+            LightType::Point => {
+                self.direction.stream_write(stream, &mut ())?;
+                stream.write_f32(self.attenuation.theta)?;
+                stream.write_f32(self.attenuation.phi)?;
+            },
+            LightType::Spot => {
+                self.position.stream_write(stream, &mut ())?;
+                match self.flags.contains(LightFlags::Flag1) {
+                    true => {
+                        stream.write_f32(self.attenuation.ds)?;
+                        stream.write_f32(self.attenuation.de)?;
+                    },
+                    false => {
+                        stream.write_f32(self.attenuation.kc)?;
+                        stream.write_f32(self.attenuation.kl)?;
+                        stream.write_f32(self.attenuation.kq)?;
+                    }
+                }
+            }
+        }
+        if stream.has_feature(GfdVersion::LightAddAlpha).is_some() {
+            stream.write_f32(self.alpha)?;
+        }
+        if Self::has_toon_influence(stream) {
+            stream.write_f32(self.get_toon_influence())?;
         }
         Ok(())
     }
+
+    fn has_toon_influence<AStream, T>(stream: &Stream<AStream, T>) -> bool
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        stream.get_game_profile().is_gfd_v2() && stream.has_feature(GfdVersion::LightAddToonInfluence).is_some()
+    }
+
+    // Only the v2 light has space for this, other builds read it and write back the default
+    #[cfg(feature = "v2-core")]
+    fn set_toon_influence(&mut self, value: f32) {
+        self.toon = value;
+    }
+
+    #[cfg(not(feature = "v2-core"))]
+    fn set_toon_influence(&mut self, _: f32) {}
+
+    #[cfg(feature = "v2-core")]
+    fn get_toon_influence(&self) -> f32 {
+        self.toon
+    }
+
+    #[cfg(not(feature = "v2-core"))]
+    fn get_toon_influence(&self) -> f32 {
+        1.
+    }
 }
 
 impl<A> Light<A>
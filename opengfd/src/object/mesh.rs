@@ -5,7 +5,7 @@ use crate::{
     anim::{
         anim_controller::AnimController,
         anim_effector::AnimEffector,
-        animation::AnimInterpolator,
+        animation::{ AnimInterpolator, AnimationPack },
     },
     graphics::{ 
         cull::CullObject,
@@ -20,42 +20,47 @@ use crate::{
         reference::{ GfdRcType, Reference },
     }
 };
-use glam::Vec3A;
+use glam::{ Mat4, Vec3A };
 use super::{
     camera::Camera,
     epl::EPL,
-    geometry::Geometry,
+    extra_properties::ExtraProperties,
+    geometry::{ Geometry, GeometryFlags },
     light::{ Light, LightContainer },
     morph::MorphController,
-    node::Node, 
-    object::Object
+    node::{ Node, NodeIterator, StandardNodeIterator },
+    object::Object,
+    physics::PhysicsDictionary
 };
 #[cfg(feature = "serialize")]
 use std::{
     fmt::Debug,
-    io::{ Read, Seek, Write },
+    io::{ Read, Seek, SeekFrom, Write },
 };
 use std::{
     error::Error,
+    fmt::Formatter,
+    ops::{ Deref, DerefMut },
     ptr::NonNull
 };
-use std::fmt::Formatter;
 // use std::io::SeekFrom;
-use std::time::Instant;
 use opengfd_proc::GfdRcAuto;
 // use crate::device::ngr::allocator::AllocatorHook;
-use crate::graphics::texture::{Texture, /*TextureFormat,*/ TextureSerializationContext};
+use crate::graphics::texture::{ TextureDictionary, TextureEntry };
 // use crate::kernel::version::GfdVersion;
 use crate::object::object::{CastFromObject, ObjectId};
 use crate::utility::misc::RGB;
+use crate::utility::stream::RawChunk;
 // use crate::utility::name::{Name, NameSerializationContext, NameSerializationNoHash};
 
+#[cfg(feature = "serialize")]
+use crate::anim::animation::AnimationError;
 #[cfg(feature = "serialize")]
 use crate::utility::stream::{
     ChunkHeader,
+    ChunkSerializationContext,
     ChunkType,
-    DeserializationHeap,
-    DeserializationStrategy,
+    DeserializationStack,
     GfdSerialize,
     GfdSerializationUserData,
     SerializationSingleAllocator,
@@ -66,10 +71,10 @@ use crate::utility::stream::{
 bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
     pub struct MeshFlags: u32 {
-        const Flag0  = 1 << 0;
-        const Flag1  = 1 << 1;
-        const Flag2  = 1 << 2;
-        const Flag3  = 1 << 3;
+        const BoundingBox    = 1 << 0;
+        const BoundingSphere = 1 << 1;
+        const Skin           = 1 << 2;
+        const Morph          = 1 << 3;
         const Flag4  = 1 << 4;
         const Flag5  = 1 << 5;
         const Flag6  = 1 << 6;
@@ -114,13 +119,13 @@ where A: Allocator + Clone
     super_: Object,
     flags: MeshFlags,
     hierarchy: Option<NonNull<Node<A>>>,
-    node_array: Option<NonNull<ItemArray<NonNull<Node<A>>>>>,
-    geometry_array: Option<NonNull<ItemArray<NonNull<Geometry<A>>>>>,
-    material_array: Option<NonNull<ItemArray<NonNull<Material<A>>>>>,
-    morph_array: Option<NonNull<ItemArray<NonNull<MorphController>>>>,
-    camera_array: Option<NonNull<ItemArray<NonNull<Camera<A>>>>>,
-    light_array: Option<NonNull<ItemArray<NonNull<Light<A>>>>>,
-    effect_array: Option<NonNull<ItemArray<NonNull<EPL<A>>>>>,
+    node_array: Option<NonNull<ItemArray<NonNull<Node<A>>, A>>>,
+    geometry_array: Option<NonNull<ItemArray<NonNull<Geometry<A>>, A>>>,
+    material_array: Option<NonNull<ItemArray<NonNull<Material<A>>, A>>>,
+    morph_array: Option<NonNull<ItemArray<NonNull<MorphController<A>>, A>>>,
+    camera_array: Option<NonNull<ItemArray<NonNull<Camera<A>>, A>>>,
+    light_array: Option<NonNull<ItemArray<NonNull<Light<A>>, A>>>,
+    effect_array: Option<NonNull<ItemArray<NonNull<EPL<A>>, A>>>,
     anim_interpolator: Option<NonNull<AnimInterpolator>>,
     anim_controller: Option<NonNull<AnimController<A>>>,
     anim_effector: Option<NonNull<AnimEffector>>,
@@ -180,7 +185,7 @@ where A: Allocator + Clone
     pub fn get_material_list(&self) -> &[NonNull<Material<A>>] {
         self.material_array.map_or(&[], |v| unsafe { v.as_ref().as_slice() })
     }
-    pub fn get_morph_list(&self) -> &[NonNull<MorphController>] {
+    pub fn get_morph_list(&self) -> &[NonNull<MorphController<A>>] {
         self.morph_array.map_or(&[], |v| unsafe { v.as_ref().as_slice() })
     }
     pub fn get_camera_list(&self) -> &[NonNull<Camera<A>>] {
@@ -202,7 +207,7 @@ where A: Allocator + Clone
     pub fn get_material_list_mut(&mut self) -> &mut [NonNull<Material<A>>] {
         self.material_array.map_or(&mut [], |mut v| unsafe { v.as_mut().as_slice_mut() })
     }
-    pub fn get_morph_list_mut(&mut self) -> &mut [NonNull<MorphController>] {
+    pub fn get_morph_list_mut(&mut self) -> &mut [NonNull<MorphController<A>>] {
         self.morph_array.map_or(&mut [], |mut v| unsafe { v.as_mut().as_slice_mut() })
     }
     pub fn get_camera_list_mut(&mut self) -> &mut [NonNull<Camera<A>>] {
@@ -245,77 +250,557 @@ where A: Allocator + Clone
     pub fn get_anim_effector_mut(&mut self) -> Option<&mut AnimEffector> {
         self.anim_effector.map(|mut v| unsafe { v.as_mut() })
     }
+
+    /// Create a mesh without a node hierarchy, materials or textures
+    pub fn new_in(allocator: A) -> NonNull<Self> {
+        let mut this: ABox<Self, A> = unsafe { ABox::new_zeroed_in(allocator.clone()).assume_init() };
+        unsafe { this.super_.set_id(ObjectId::Mesh) };
+        this.reference = Reference::new();
+        unsafe { std::ptr::write(&raw mut this._allocator, allocator) };
+        unsafe { NonNull::new_unchecked(ABox::into_raw(this)) }
+    }
+
+    /// Use a node as the root of the mesh's hierarchy. This updates the node transforms and the
+    /// node and attachment lists, so it should be called after the hierarchy is complete.
+    pub fn set_root_node(&mut self, mut root: NonNull<Node<A>>) -> Result<(), Box<dyn Error>> {
+        unsafe { root.as_mut().set_hierarchy_root(root) };
+        self.hierarchy = Some(root);
+        self.collect_hierarchy(self._allocator.clone())?;
+        self.bind_geometry_materials();
+        Ok(())
+    }
+
+    /// Recalculate the world transform of every node in the hierarchy. This should be called
+    /// after changing node transforms and before building a skin palette.
+    pub fn evaluate_world_transforms(&mut self) {
+        if let Some(root) = self.get_root_node_mut() {
+            root.evaluate_hierarchy_transform();
+        }
+    }
+
+    /// Recalculate the bounds of every geometry, then the mesh's bounds from the vertices of every
+    /// geometry moved by the world transform of the node that it's attached to. Skinned geometry
+    /// is already in the mesh's space, so it's used as is. Use evaluate_world_transforms first if
+    /// any node transforms were changed.
+    pub fn recompute_bounds(&mut self) {
+        let mut points = vec![];
+        for geometry in self.get_geometry_list_mut() {
+            let geometry = unsafe { geometry.as_mut() };
+            geometry.recompute_bounds();
+            let world = match geometry.get_flags().contains(GeometryFlags::Skin) {
+                true => Mat4::IDENTITY,
+                false => geometry.get_world_transform()
+            };
+            if let Some(vertices) = geometry.get_vertex_data() {
+                points.extend(vertices.get_positions().iter().map(|p| world.transform_point3(p.truncate())));
+            }
+        }
+        if let (Some(bounding_box), Some(bounding_sphere)) = (BoundingBox::from_points(&points), BoundingSphere::from_points(&points)) {
+            self.set_bounding_box(bounding_box);
+            self.set_bounding_sphere(bounding_sphere);
+        }
+    }
+
+    /// Add a material to the end of the mesh's material list
+    pub fn add_material(&mut self, material: NonNull<Material<A>>) -> Result<(), Box<dyn Error>> {
+        let allocator = self._allocator.clone();
+        let materials = unsafe { self.material_array.get_or_insert_with(|| NonNull::new_unchecked(
+            ABox::into_raw(ABox::new_in(ItemArray::new(allocator.clone()), allocator.clone())))).as_mut() };
+        materials.push(material)?;
+        Ok(())
+    }
+
+    pub fn set_bounding_box(&mut self, value: BoundingBox) {
+        self.bounding_box = value;
+        self.flags |= MeshFlags::BoundingBox;
+    }
+    pub fn set_bounding_sphere(&mut self, value: BoundingSphere) {
+        self.bounding_sphere = value;
+        self.flags |= MeshFlags::BoundingSphere;
+    }
+
+    /// Fill the node and attachment arrays from the node hierarchy. Nodes are stored in depth
+    /// first order, which is the order used by bone to node indices in the model chunk.
+    fn collect_hierarchy(&mut self, allocator: A) -> Result<(), Box<dyn Error>> {
+        let mut nodes = ItemArray::<NonNull<Node<A>>, A>::new(allocator.clone());
+        let mut geometries = ItemArray::<NonNull<Geometry<A>>, A>::new(allocator.clone());
+        let mut morphs = ItemArray::<NonNull<MorphController<A>>, A>::new(allocator.clone());
+        let mut cameras = ItemArray::<NonNull<Camera<A>>, A>::new(allocator.clone());
+        let mut lights = ItemArray::<NonNull<Light<A>>, A>::new(allocator.clone());
+        let mut effects = ItemArray::<NonNull<EPL<A>>, A>::new(allocator.clone());
+        if let Some(root) = self.get_root_node() {
+            for node in NodeIterator::<A, StandardNodeIterator>::from_node(root) {
+                nodes.push(NonNull::from(node))?;
+                for object in node.iter_object() {
+                    let ptr = NonNull::from(object);
+                    match object.get_id() {
+                        ObjectId::Geometry => geometries.push(ptr.cast())?,
+                        ObjectId::Morph => morphs.push(ptr.cast())?,
+                        ObjectId::Camera => cameras.push(ptr.cast())?,
+                        ObjectId::Light => lights.push(ptr.cast())?,
+                        ObjectId::EPL => effects.push(ptr.cast())?,
+                        _ => ()
+                    };
+                }
+            }
+        }
+        self.node_array = Some(unsafe { NonNull::new_unchecked(ABox::into_raw(ABox::new_in(nodes, allocator.clone()))) });
+        self.geometry_array = Some(unsafe { NonNull::new_unchecked(ABox::into_raw(ABox::new_in(geometries, allocator.clone()))) });
+        self.morph_array = Some(unsafe { NonNull::new_unchecked(ABox::into_raw(ABox::new_in(morphs, allocator.clone()))) });
+        self.camera_array = Some(unsafe { NonNull::new_unchecked(ABox::into_raw(ABox::new_in(cameras, allocator.clone()))) });
+        self.light_array = Some(unsafe { NonNull::new_unchecked(ABox::into_raw(ABox::new_in(lights, allocator.clone()))) });
+        self.effect_array = Some(unsafe { NonNull::new_unchecked(ABox::into_raw(ABox::new_in(effects, allocator.clone()))) });
+        Ok(())
+    }
+
+    // Geometry refers to it's material by name, so this is done once every chunk has been read
+    fn bind_geometry_materials(&mut self) {
+        let materials = self.get_material_list();
+        for geometry in self.get_geometry_list() {
+            let geometry = unsafe { &mut *geometry.as_ptr() };
+            let name = match geometry.get_material_name() {
+                Some(v) => v,
+                None => continue
+            };
+            let material = materials.iter().find(|m| unsafe { m.as_ref() }.get_name() == Some(name));
+            if let Some(m) = material {
+                geometry.set_material_raw(Some(*m));
+            }
+        }
+    }
+}
+
+// Free a node along with everything attached to it and all of it's children
+unsafe fn free_node<A>(node: NonNull<Node<A>>, allocator: A)
+where A: Allocator + Clone {
+    let objects: Vec<NonNull<Object<A>>> = node.as_ref().iter_object().map(NonNull::from).collect();
+    for object in objects {
+        match object.as_ref().get_id() {
+            ObjectId::Node => free_node(object.cast::<Node<A>>(), allocator.clone()),
+            ObjectId::Geometry => drop(ABox::from_raw_in(object.cast::<Geometry<A>>().as_ptr(), allocator.clone())),
+            ObjectId::Morph => drop(ABox::from_raw_in(object.cast::<MorphController<A>>().as_ptr(), allocator.clone())),
+            ObjectId::Camera => drop(ABox::from_raw_in(object.cast::<Camera<A>>().as_ptr(), allocator.clone())),
+            ObjectId::Light => drop(ABox::from_raw_in(object.cast::<Light<A>>().as_ptr(), allocator.clone())),
+            ObjectId::EPL => drop(ABox::from_raw_in(object.cast::<EPL<A>>().as_ptr(), allocator.clone())),
+            _ => ()
+        };
+    }
+    let children: Vec<NonNull<Node<A>>> = node.as_ref().get_direct_children().into_iter().map(NonNull::from).collect();
+    for child in children {
+        free_node(child, allocator.clone());
+    }
+    if let Some(property) = node.as_ref().get_property() {
+        drop(ABox::from_raw_in(property as *const Property<A> as *mut Property<A>, allocator.clone()));
+    }
+    drop(ABox::from_raw_in(node.as_ptr(), allocator));
+}
+
+unsafe fn free_list<T, A>(list: Option<NonNull<ItemArray<NonNull<T>, A>>>, allocator: A)
+where A: Allocator + Clone {
+    if let Some(list) = list {
+        drop(ABox::from_raw_in(list.as_ptr(), allocator));
+    }
+}
+
+impl<A> Mesh<A>
+where A: Allocator + Clone
+{
+    // Free the node hierarchy, everything attached to it, the materials and the object lists.
+    // Only meshes that were created by OpenGFD can be freed this way.
+    unsafe fn free_objects(&mut self) {
+        let allocator = self._allocator.clone();
+        if let Some(root) = self.hierarchy.take() {
+            free_node(root, allocator.clone());
+        }
+        for material in self.get_material_list() {
+            drop(ABox::from_raw_in(material.as_ptr(), allocator.clone()));
+        }
+        free_list(self.node_array.take(), allocator.clone());
+        free_list(self.geometry_array.take(), allocator.clone());
+        free_list(self.material_array.take(), allocator.clone());
+        free_list(self.morph_array.take(), allocator.clone());
+        free_list(self.camera_array.take(), allocator.clone());
+        free_list(self.light_array.take(), allocator.clone());
+        free_list(self.effect_array.take(), allocator);
+    }
+}
+
+/// A mesh along with the chunks stored next to it in a GFS file. When the game loads a file, the
+/// skin, textures, animations and physics are moved into structures outside of the mesh, so
+/// they're kept here instead. Dropping a MeshFile frees the mesh, it's node hierarchy and
+/// everything attached to it.
+pub struct MeshFile<A = GfdAllocator>
+where A: Allocator + Clone
+{
+    mesh: NonNull<Mesh<A>>,
+    inverse_bind_matrices: ItemArray<Mat4, A>,
+    bone_to_node: ItemArray<u16, A>,
+    texture_dictionary: Option<ABox<TextureDictionary<A>, A>>,
+    animation_pack: Option<ABox<AnimationPack<A>, A>>,
+    physics_dictionary: Option<ABox<PhysicsDictionary<A>, A>>,
+    extra_properties: Option<ABox<ExtraProperties<A>, A>>,
+    // Chunks that OpenGFD can't parse, written back out unchanged
+    raw_chunks: Vec<ABox<RawChunk<A>, A>>,
+    // Order that chunks were read in, so that they're written back out in the same place
+    #[cfg(feature = "serialize")]
+    chunk_order: Vec<MeshFileChunk>,
+    _allocator: A
+}
+
+#[cfg(feature = "serialize")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MeshFileChunk {
+    Known(ChunkType),
+    // Index into raw_chunks
+    Raw(usize)
+}
+
+// Order used for chunks that weren't in the file that was read
+#[cfg(feature = "serialize")]
+const MESH_FILE_CHUNK_ORDER: [ChunkType; 6] = [
+    ChunkType::TextureDictionary,
+    ChunkType::MaterialDictionary,
+    ChunkType::Model,
+    ChunkType::PhysicsDictionary,
+    ChunkType::ExtraProperties,
+    ChunkType::AnimationPack
+];
+
+impl<A> Debug for MeshFile<A>
+where A: Allocator + Clone {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MeshFile {{ bones: {}, raw chunks: {} }}", self.bone_to_node.len(), self.raw_chunks.len())
+    }
+}
+
+impl<A> MeshFile<A>
+where A: Allocator + Clone
+{
+    /// Create a file containing a mesh without a node hierarchy, materials or textures
+    pub fn new_in(allocator: A) -> Self {
+        unsafe { Self::from_raw(Mesh::new_in(allocator)) }
+    }
+
+    /// Take ownership of a mesh, which will be freed when the MeshFile is dropped.
+    /// # Safety
+    /// The mesh and all of the objects in it must have been allocated by OpenGFD using the
+    /// mesh's allocator, and nothing else can free them.
+    pub unsafe fn from_raw(mesh: NonNull<Mesh<A>>) -> Self {
+        let allocator = mesh.as_ref()._allocator.clone();
+        Self {
+            mesh,
+            inverse_bind_matrices: ItemArray::new(allocator.clone()),
+            bone_to_node: ItemArray::new(allocator.clone()),
+            texture_dictionary: None,
+            animation_pack: None,
+            physics_dictionary: None,
+            extra_properties: None,
+            raw_chunks: vec![],
+            #[cfg(feature = "serialize")]
+            chunk_order: vec![],
+            _allocator: allocator
+        }
+    }
+
+    pub fn get_mesh(&self) -> &Mesh<A> {
+        unsafe { self.mesh.as_ref() }
+    }
+    pub fn get_mesh_mut(&mut self) -> &mut Mesh<A> {
+        unsafe { self.mesh.as_mut() }
+    }
+
+    /// Get the inverse bind matrix for each bone in the mesh's skin
+    pub fn get_inverse_bind_matrices(&self) -> &[Mat4] {
+        self.inverse_bind_matrices.as_slice()
+    }
+    /// Get the index into the node list for each bone in the mesh's skin
+    pub fn get_bone_to_node_indices(&self) -> &[u16] {
+        self.bone_to_node.as_slice()
+    }
+    /// Set the mesh's skin, with the inverse bind matrix and node list index for each bone
+    pub fn set_skin(&mut self, inverse_bind_matrices: &[Mat4], bone_to_node: &[u16]) -> Result<(), Box<dyn Error>> {
+        let mut matrices = ItemArray::<Mat4, A>::with_capacity(inverse_bind_matrices.len(), self._allocator.clone())?;
+        for matrix in inverse_bind_matrices {
+            matrices.push(*matrix)?;
+        }
+        let mut bones = ItemArray::<u16, A>::with_capacity(bone_to_node.len(), self._allocator.clone())?;
+        for bone in bone_to_node {
+            bones.push(*bone)?;
+        }
+        self.inverse_bind_matrices = matrices;
+        self.bone_to_node = bones;
+        self.get_mesh_mut().flags.set(MeshFlags::Skin, !bone_to_node.is_empty());
+        Ok(())
+    }
+
+    /// Packs that contain effect animations can't be read yet, so they're kept as raw chunks
+    /// instead.
+    pub fn get_animation_pack(&self) -> Option<&AnimationPack<A>> {
+        self.animation_pack.as_deref()
+    }
+    pub fn get_animation_pack_mut(&mut self) -> Option<&mut AnimationPack<A>> {
+        self.animation_pack.as_deref_mut()
+    }
+    pub fn get_physics_dictionary(&self) -> Option<&PhysicsDictionary<A>> {
+        self.physics_dictionary.as_deref()
+    }
+    pub fn get_physics_dictionary_mut(&mut self) -> Option<&mut PhysicsDictionary<A>> {
+        self.physics_dictionary.as_deref_mut()
+    }
+    pub fn get_extra_properties(&self) -> Option<&ExtraProperties<A>> {
+        self.extra_properties.as_deref()
+    }
+    pub fn get_extra_properties_mut(&mut self) -> Option<&mut ExtraProperties<A>> {
+        self.extra_properties.as_deref_mut()
+    }
+    pub fn get_raw_chunks(&self) -> impl Iterator<Item = &RawChunk<A>> {
+        self.raw_chunks.iter().map(|v| v.as_ref())
+    }
+
+    /// Get the texture dictionary. This is only populated for files read from a stream, or after
+    /// a texture is inserted.
+    pub fn get_texture_dictionary(&self) -> Option<&TextureDictionary<A>> {
+        self.texture_dictionary.as_deref()
+    }
+    pub fn get_texture_dictionary_mut(&mut self) -> Option<&mut TextureDictionary<A>> {
+        self.texture_dictionary.as_deref_mut()
+    }
+    pub fn get_texture(&self, name: &str) -> Option<&TextureEntry<A>> {
+        self.get_texture_dictionary().and_then(|v| v.get_by_str(name))
+    }
+    /// Add a texture to the texture dictionary, creating the dictionary if needed. See
+    /// [`TextureDictionary::insert`].
+    pub fn insert_texture(&mut self, texture: TextureEntry<A>) -> Result<bool, Box<dyn Error>> {
+        let allocator = self._allocator.clone();
+        self.texture_dictionary
+            .get_or_insert_with(|| ABox::new_in(TextureDictionary::new(allocator.clone()), allocator))
+            .insert(texture)
+    }
+}
+
+impl<A> Deref for MeshFile<A>
+where A: Allocator + Clone
+{
+    type Target = Mesh<A>;
+    fn deref(&self) -> &Self::Target {
+        self.get_mesh()
+    }
+}
+
+impl<A> DerefMut for MeshFile<A>
+where A: Allocator + Clone
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.get_mesh_mut()
+    }
+}
+
+impl<A> Drop for MeshFile<A>
+where A: Allocator + Clone
+{
+    fn drop(&mut self) {
+        // SAFETY: from_raw requires that the mesh is only freed by this MeshFile
+        let mut mesh = unsafe { ABox::from_raw_in(self.mesh.as_ptr(), self._allocator.clone()) };
+        unsafe { mesh.free_objects() };
+    }
 }
 
 #[cfg(feature = "serialize")]
-impl<AStream, AObject, T> GfdSerialize<AStream, T, AObject, DeserializationHeap<Self, AObject>, SerializationSingleAllocator<AObject>> for Mesh<AObject>
+impl<AStream, AObject, T> GfdSerialize<AStream, T, AObject, DeserializationStack<Self>, SerializationSingleAllocator<AObject>> for MeshFile<AObject>
 where T: Debug + Read + Write + Seek + StreamIODevice,
       AStream: Allocator + Clone + Debug,
       AObject: Allocator + Clone
 {
-    fn stream_read(stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<DeserializationHeap<Self, AObject>, Box<dyn Error>> {
-        let mut this = DeserializationHeap::<Self, AObject>::zeroed(param);
-        unsafe { this.super_.set_id(ObjectId::Mesh) };
-        this.reference = Reference::new();
+    fn stream_read(stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<DeserializationStack<Self>, Box<dyn Error>> {
+        let mut this = Self::new_in(param.get_heap_allocator().unwrap());
         this.stream_read_inner(stream, param)?;
-        Ok(this)
+        Ok(this.into())
+    }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream, param)
     }
 }
 
 #[cfg(feature = "serialize")]
-impl<AObject> Mesh<AObject>
+impl<AObject> MeshFile<AObject>
 where AObject: Allocator + Clone {
     fn stream_read_inner<AStream, T>(&mut self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>>
     where T: Debug + Read + Write + Seek + StreamIODevice,
           AStream: Allocator + Clone + Debug {
-        let allocator = param.get_heap_allocator().unwrap();
-        let start = Instant::now();
-        loop {
-            let chunk = ChunkHeader::stream_read(stream, &mut ());
-            if chunk.is_err() {
-                break;
-            }
-            let chunk = chunk?.into_raw();
-            // let chunk = ChunkHeader::stream_read(stream, &mut ())?.into_raw();
-            println!("{:?}", chunk);
+        let allocator = self._allocator.clone();
+        let start = stream.seek(SeekFrom::Current(0))?;
+        let end = stream.seek(SeekFrom::End(0))?;
+        stream.seek(SeekFrom::Start(start))?;
+        // Files without an EOF chunk end once there's no data left
+        while stream.seek(SeekFrom::Current(0))? < end {
+            let chunk = ChunkHeader::stream_read(stream, &mut ())?.into_raw();
+            let raw_count = self.raw_chunks.len();
             match chunk.get_chunk_id() {
-                /*
-                ChunkType::Model => {
-
-                },
+                ChunkType::Model => self.stream_read_model(stream, param)?,
                 ChunkType::ExtraProperties => {
-
+                    let mut context = ChunkSerializationContext::new(allocator.clone(), chunk.get_body_length());
+                    self.extra_properties = Some(ExtraProperties::<AObject>::stream_read(stream, &mut context)?.into_box());
                 },
                 ChunkType::PhysicsDictionary => {
-
+                    let mut context = ChunkSerializationContext::new(allocator.clone(), chunk.get_body_length());
+                    self.physics_dictionary = Some(PhysicsDictionary::<AObject>::stream_read(stream, &mut context)?.into_box());
                 },
-                */
                 ChunkType::MaterialDictionary => {
-                    let mat_count = stream.read_u32()? as usize;
-                    println!("{} materials", mat_count);
-                    for i in 0..mat_count {
-                        let _ = Material::<AObject>::stream_read(stream, param)?.into_raw();
+                    let count = stream.read_u32()?;
+                    for _ in 0..count {
+                        let material = Material::<AObject>::stream_read(stream, param)?.into_raw();
+                        self.get_mesh_mut().add_material(material)?;
                     }
                 },
                 ChunkType::TextureDictionary => {
-                    let mut textures = ItemArray::<NonNull<Texture<AObject>>, AObject>::with_capacity(stream.read_u32()? as usize, self._allocator.clone())?;
-                    println!("texture count: {}", textures.capacity());
-                    for i in 0..textures.capacity() {
-                        textures.push(Texture::<AObject>::stream_read(stream, &mut TextureSerializationContext::new(allocator.clone(), allocator.clone()))?.into_raw())?;
-                    }
+                    self.texture_dictionary = Some(TextureDictionary::<AObject>::stream_read(stream, param)?.into_box());
                 },
-                /*
                 ChunkType::AnimationPack => {
-
+                    let start = stream.seek(SeekFrom::Current(0))?;
+                    match AnimationPack::<AObject>::stream_read(stream, param) {
+                        Ok(v) => self.animation_pack = Some(v.into_box()),
+                        // Effect animations can't be read yet, so keep the pack as is
+                        Err(e) if matches!(e.downcast_ref::<AnimationError>(), Some(AnimationError::UnsupportedEffect)) => {
+                            stream.seek(SeekFrom::Start(start - size_of::<ChunkHeader>() as u64))?;
+                            self.raw_chunks.push(RawChunk::<AObject>::stream_read(stream, param)?.into_box());
+                        },
+                        Err(e) => return Err(e)
+                    };
                 },
-                ChunkType::ChunkType000100FE => {
+                ChunkType::EOF => break,
+                // Keep anything else (including ChunkType000100FE) so it can be written back out
+                _ => {
+                    stream.seek(SeekFrom::Current(-(size_of::<ChunkHeader>() as i64)))?;
+                    self.raw_chunks.push(RawChunk::<AObject>::stream_read(stream, param)?.into_box());
+                }
+            };
+            self.chunk_order.push(match self.raw_chunks.len() > raw_count {
+                true => MeshFileChunk::Raw(raw_count),
+                false => MeshFileChunk::Known(chunk.get_chunk_id())
+            });
+        }
+        self.get_mesh_mut().bind_geometry_materials();
+        Ok(())
+    }
+
+    fn stream_read_model<AStream, T>(&mut self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        let allocator = self._allocator.clone();
+        let mesh = unsafe { self.mesh.as_mut() };
+        mesh.flags = MeshFlags::from_bits_retain(stream.read_u32()?);
+        if mesh.flags.contains(MeshFlags::Skin) {
+            let count = stream.read_u32()? as usize;
+            let mut matrices = ItemArray::<Mat4, AObject>::new(allocator.clone());
+            for _ in 0..count {
+                matrices.push(Mat4::stream_read(stream, &mut ())?.into_raw())?;
+            }
+            let mut bones = ItemArray::<u16, AObject>::new(allocator.clone());
+            for _ in 0..count {
+                bones.push(stream.read_u16()?)?;
+            }
+            self.inverse_bind_matrices = matrices;
+            self.bone_to_node = bones;
+        }
+        if mesh.flags.contains(MeshFlags::BoundingBox) {
+            mesh.bounding_box = BoundingBox::stream_read(stream, &mut ())?.into_raw();
+        }
+        if mesh.flags.contains(MeshFlags::BoundingSphere) {
+            mesh.bounding_sphere = BoundingSphere::stream_read(stream, &mut ())?.into_raw();
+        }
+        let mut root = Node::<AObject>::stream_read(stream, param)?.into_raw();
+        unsafe { root.as_mut().set_hierarchy_root(root) };
+        mesh.hierarchy = Some(root);
+        mesh.collect_hierarchy(allocator)
+    }
 
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        let version = stream.get_header_version();
+        let mut written_known = vec![];
+        let mut written_raw = vec![false; self.raw_chunks.len()];
+        for slot in &self.chunk_order {
+            match *slot {
+                MeshFileChunk::Known(ty) => if !written_known.contains(&ty) {
+                    self.stream_write_chunk(stream, param, ty)?;
+                    written_known.push(ty);
                 },
-                */
-                _ => break
+                MeshFileChunk::Raw(i) => if let Some(false) = written_raw.get(i) {
+                    self.raw_chunks[i].stream_write(stream, param)?;
+                    written_raw[i] = true;
+                }
             };
         }
-        println!("Time: {} ms", start.elapsed().as_micros() as f64 / 1000.);
+        // Chunks added after the file was read
+        for ty in MESH_FILE_CHUNK_ORDER {
+            if !written_known.contains(&ty) {
+                self.stream_write_chunk(stream, param, ty)?;
+            }
+        }
+        for (chunk, _) in self.raw_chunks.iter().zip(written_raw).filter(|(_, w)| !*w) {
+            chunk.stream_write(stream, param)?;
+        }
+        stream.write_chunk(ChunkType::EOF, version, |_| Ok(()))
+    }
+
+    /// Write a chunk that OpenGFD knows how to serialize, if the file contains it
+    fn stream_write_chunk<AStream, T>(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>, ty: ChunkType) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        let version = stream.get_header_version();
+        let mesh = self.get_mesh();
+        match ty {
+            ChunkType::TextureDictionary => if let Some(textures) = self.get_texture_dictionary() {
+                stream.write_chunk(ChunkType::TextureDictionary, version, |stream| textures.stream_write(stream, param))?;
+            },
+            ChunkType::MaterialDictionary => if mesh.material_array.is_some() {
+                stream.write_chunk(ChunkType::MaterialDictionary, version, |stream| {
+                    let materials = mesh.get_material_list();
+                    stream.write_u32(materials.len() as u32)?;
+                    for material in materials {
+                        unsafe { material.as_ref() }.stream_write(stream, param)?;
+                    }
+                    Ok(())
+                })?;
+            },
+            ChunkType::Model => if let Some(root) = mesh.get_root_node() {
+                stream.write_chunk(ChunkType::Model, version, |stream| {
+                    stream.write_u32(mesh.flags.bits())?;
+                    if mesh.flags.contains(MeshFlags::Skin) {
+                        let matrices = self.get_inverse_bind_matrices();
+                        stream.write_u32(matrices.len() as u32)?;
+                        for matrix in matrices {
+                            matrix.stream_write(stream, &mut ())?;
+                        }
+                        stream.write_u16_slice(self.get_bone_to_node_indices())?;
+                    }
+                    if mesh.flags.contains(MeshFlags::BoundingBox) {
+                        mesh.bounding_box.stream_write(stream, &mut ())?;
+                    }
+                    if mesh.flags.contains(MeshFlags::BoundingSphere) {
+                        mesh.bounding_sphere.stream_write(stream, &mut ())?;
+                    }
+                    root.stream_write(stream, param)
+                })?;
+            },
+            ChunkType::PhysicsDictionary => if let Some(physics) = self.get_physics_dictionary() {
+                stream.write_chunk(ChunkType::PhysicsDictionary, version, |stream| {
+                    physics.stream_write(stream, &mut ChunkSerializationContext::new(self._allocator.clone(), 0))
+                })?;
+            },
+            ChunkType::ExtraProperties => if let Some(extra) = self.get_extra_properties() {
+                stream.write_chunk(ChunkType::ExtraProperties, version, |stream| {
+                    extra.stream_write(stream, &mut ChunkSerializationContext::new(self._allocator.clone(), 0))
+                })?;
+            },
+            ChunkType::AnimationPack => if let Some(animations) = self.get_animation_pack() {
+                stream.write_chunk(ChunkType::AnimationPack, version, |stream| animations.stream_write(stream, param))?;
+            },
+            // EOF is written once every other chunk is done, and anything else is kept as a raw chunk
+            _ => ()
+        };
         Ok(())
     }
 }
@@ -415,3 +900,48 @@ pub mod ffi {
 pub mod test {
 
 }
+
+#[cfg(all(test, feature = "serialize"))]
+pub mod tests {
+    use allocator_api2::alloc::Global;
+    use std::error::Error;
+    use crate::utility::stream::{ DevicePlatform, GfdSerialize, SerializationSingleAllocator, StreamFactory, StreamHeader };
+    use super::MeshFile;
+
+    type TestReturn = Result<(), Box<dyn Error>>;
+
+    #[test]
+    fn raw_chunks_keep_their_position() -> TestReturn {
+        let factory = StreamFactory::new(Global);
+        let mut stream = factory.write_to_memory(StreamHeader::new(0x2110000, DevicePlatform::METAPHOR))?;
+        // unknown chunk from an older version
+        stream.write_u32_slice(&[0x1105000, 0x000100FA, 0x14, 0, 0x12345678])?;
+        // empty ExtraProperties
+        stream.write_u32_slice(&[0x2110000, 0x000100F8, 0x14, 0, 0])?;
+        // ChunkType000100FE
+        stream.write_u32_slice(&[0x2110000, 0x000100FE, 0x18, 0, 1, 2])?;
+        // EOF
+        stream.write_u32_slice(&[0x2110000, 0, 0x10, 0])?;
+        let written = stream.as_slice().to_vec();
+        let mut stream = factory.read_from_memory(&written)?;
+        let mut param = SerializationSingleAllocator::new(Global);
+        let mesh = MeshFile::<Global>::stream_read(&mut stream, &mut param)?.into_raw();
+        assert_eq!(mesh.get_raw_chunks().count(), 2);
+        assert!(mesh.get_extra_properties().is_some());
+        let mut out = factory.write_to_memory(StreamHeader::new(0x2110000, DevicePlatform::METAPHOR))?;
+        mesh.stream_write(&mut out, &mut param)?;
+        assert_eq!(out.as_slice(), written.as_slice());
+        Ok(())
+    }
+
+    #[test]
+    fn truncated_chunk_is_an_error() -> TestReturn {
+        let factory = StreamFactory::new(Global);
+        let mut stream = factory.write_to_memory(StreamHeader::new(0x2110000, DevicePlatform::METAPHOR))?;
+        stream.write_u32_slice(&[0x2110000, 0x000100FA])?;
+        let written = stream.as_slice().to_vec();
+        let mut stream = factory.read_from_memory(&written)?;
+        assert!(MeshFile::<Global>::stream_read(&mut stream, &mut SerializationSingleAllocator::new(Global)).is_err());
+        Ok(())
+    }
+}
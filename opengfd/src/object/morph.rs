@@ -1,29 +1,174 @@
 use std::error::Error;
-use std::fmt::Debug;
-use std::io::{Read, Seek, SeekFrom, Write};
+use std::fmt::{Debug, Display};
+use std::io::{Read, Seek, Write};
 use crate::utility::reference::GfdRcType;
 use crate::utility::{
-    name::Name,
-    reference::Reference
+    name::{ Name, NameSerializationContext, NameSerializationHash, NameSerializationTechnique },
+    reference::Reference,
+    side_table::SideTable
 };
 use glam::Vec3;
-use super::object::Object;
+use super::object::{ CastFromObject, Object, ObjectId };
 use std::ptr::NonNull;
-use allocator_api2::alloc::Allocator;
+use allocator_api2::alloc::{Allocator, Layout};
+use allocator_api2::boxed::Box as ABox;
 use bitflags::{bitflags, Flags};
 use half::f16;
 use opengfd_proc::GfdRcAuto;
 use crate::kernel::allocator::GfdAllocator;
+use crate::object::geometry::VertexAttributeFlags;
 use crate::utility::stream::{DeserializationHeap, DeserializationStrategy, GfdSerializationUserData, GfdSerialize, SerializationSingleAllocator, Stream, StreamIODevice};
 
+#[derive(Debug)]
+pub enum MorphError {
+    /// A morph target has a different number of vertices to the geometry
+    VertexCountMismatch { target: usize, expected: usize, found: usize },
+    InvalidTargetIndex(usize),
+    /// A vertex index is past the end of a target's offsets
+    InvalidVertexIndex(usize),
+    /// The geometry has no vertex data to apply the morph targets to
+    MissingVertexData
+}
+impl Error for MorphError {}
+impl Display for MorphError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MorphError: {:?}", self)
+    }
+}
+
 #[repr(C)]
 #[derive(Debug)]
-pub struct MorphController {
-    _super: Object,
+pub struct MorphController<A = GfdAllocator>
+where A: Allocator + Clone {
+    _super: Object<A>,
     num_targets: u32,
     weights: Option<NonNull<f32>>,
     active_morphs: *mut u8,
-    name: Name
+    name: Name<A>
+}
+
+// The morph target index for each weight, stored alongside the controller in a GFS file. The
+// game doesn't keep these after loading the file.
+static MORPH_TARGET_IDS: SideTable = SideTable::new();
+
+impl<A> CastFromObject for MorphController<A>
+where A: Allocator + Clone
+{
+    const OBJECT_ID: ObjectId = ObjectId::Morph;
+}
+
+impl<A> MorphController<A>
+where A: Allocator + Clone {
+    pub fn get_target_count(&self) -> usize { self.num_targets as usize }
+    pub fn get_weights(&self) -> &[f32] {
+        match self.weights {
+            Some(v) => unsafe { std::slice::from_raw_parts(v.as_ptr(), self.num_targets as usize) },
+            None => &[]
+        }
+    }
+    pub fn get_weights_mut(&mut self) -> &mut [f32] {
+        match self.weights {
+            Some(v) => unsafe { std::slice::from_raw_parts_mut(v.as_ptr(), self.num_targets as usize) },
+            None => &mut []
+        }
+    }
+    /// Get the morph target index that each weight applies to
+    pub fn get_target_ids(&self) -> &[u32] {
+        match unsafe { MORPH_TARGET_IDS.get::<_, Vec<u32>>(self as *const Self) } {
+            Some(v) => unsafe { v.as_ref() }.as_slice(),
+            None => &[]
+        }
+    }
+    /// Get the weight for each of the geometry's `count` morph targets, ordered by target index.
+    /// Targets that the controller doesn't have a weight for are set to zero.
+    pub fn get_target_weights(&self, count: usize) -> Vec<f32> {
+        let mut out = vec![0.; count];
+        for (weight, target) in self.get_weights().iter().zip(self.get_target_ids()) {
+            if let Some(v) = out.get_mut(*target as usize) {
+                *v = *weight;
+            }
+        }
+        out
+    }
+    /// Get the name of the material that this morph controller is bound to.
+    pub fn get_material_name(&self) -> &Name<A> { &self.name }
+
+    /// Create a controller for the morph targets of the geometry that uses `material_name`.
+    /// Every weight starts at zero.
+    pub fn new_in(material_name: &str, count: usize, allocator: A) -> Result<NonNull<Self>, Box<dyn Error>> {
+        let mut this: ABox<Self, A> = unsafe { ABox::new_zeroed_in(allocator.clone()).assume_init() };
+        unsafe { std::ptr::write(&raw mut this._super, Object::new(ObjectId::Morph, allocator.clone())) };
+        this.num_targets = count as u32;
+        if count > 0 {
+            this.weights = Some(allocator.allocate_zeroed(Layout::array::<f32>(count)?)?.cast::<f32>());
+        }
+        unsafe { MORPH_TARGET_IDS.insert(&raw const *this, (0..count as u32).collect::<Vec<_>>()) };
+        unsafe { std::ptr::write(&raw mut this.name, Name::new_in(material_name, allocator)) };
+        Ok(unsafe { NonNull::new_unchecked(ABox::into_raw(this)) })
+    }
+}
+
+impl<A> Drop for MorphController<A>
+where A: Allocator + Clone {
+    fn drop(&mut self) {
+        MORPH_TARGET_IDS.remove(self as *const Self);
+        if let Some(weights) = self.weights.take() {
+            let layout = Layout::array::<f32>(self.num_targets as usize).unwrap();
+            unsafe { self._super.get_allocator().deallocate(weights.cast(), layout) };
+        }
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl<AStream, AObject, T> GfdSerialize<AStream, T, AObject, DeserializationHeap<Self, AObject>, SerializationSingleAllocator<AObject>> for MorphController<AObject>
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      AStream: Allocator + Clone + Debug,
+      AObject: Allocator + Clone
+{
+    fn stream_read(stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<DeserializationHeap<Self, AObject>, Box<dyn Error>> {
+        let mut this = DeserializationHeap::<Self, AObject>::zeroed(param);
+        unsafe { this._super.set_id(ObjectId::Morph) };
+        this.stream_read_inner(stream, param)?;
+        Ok(this)
+    }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream)
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl<AObject> MorphController<AObject>
+where AObject: Allocator + Clone
+{
+    fn stream_read_inner<AStream, T>(&mut self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        let allocator = param.get_heap_allocator().unwrap();
+        let count = stream.read_u32()? as usize;
+        let target_ids = (0..count).map(|_| stream.read_u32()).collect::<Result<Vec<_>, _>>()?;
+        self.num_targets = count as u32;
+        if count > 0 {
+            self.weights = Some(allocator.allocate_zeroed(Layout::array::<f32>(count)?)?.cast::<f32>());
+        }
+        unsafe { MORPH_TARGET_IDS.insert(self as *const Self, target_ids) };
+        self.name = Name::<AObject>::stream_read(stream, &mut NameSerializationContext::new(allocator, NameSerializationHash))?.into_raw();
+        Ok(())
+    }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        stream.write_u32(self.num_targets)?;
+        match self.get_target_ids() {
+            v if v.len() == self.num_targets as usize => stream.write_u32_slice(v)?,
+            // Controllers created by the game don't keep their target indices, so use the order
+            // that new_in gives them
+            _ => stream.write_u32_slice((0..self.num_targets).collect::<Vec<_>>().as_slice())?
+        };
+        NameSerializationHash.stream_write_inner(stream, &self.name)?;
+        Ok(())
+    }
 }
 
 #[repr(C)]
@@ -38,6 +183,10 @@ where A: Allocator + Clone {
     _allocator: A
 }
 
+// A name for each target, which isn't stored in GFS files. These are kept when importing models
+// so that targets can be found by name.
+static MORPH_TARGET_NAMES: SideTable = SideTable::new();
+
 #[repr(C)]
 #[derive(Debug)]
 pub struct Shape<A = GfdAllocator>
@@ -49,6 +198,159 @@ where A: Allocator + Clone {
     _allocator: A
 }
 
+impl<A> MorphTarget<A>
+where A: Allocator + Clone {
+    /// Create morph targets from the position offsets for each target, stored at single
+    /// precision
+    pub fn new_in(targets: &[Vec<Vec3>], allocator: A) -> Result<NonNull<Self>, Box<dyn Error>> {
+        let mut this: ABox<Self, A> = unsafe { ABox::new_zeroed_in(allocator.clone()).assume_init() };
+        this.ref_ = Reference::new();
+        unsafe { std::ptr::write(&raw mut this._allocator, allocator.clone()) };
+        this.num_targets = targets.len() as i32;
+        this.fvf = MorphTargetAttribute::UseSinglePrecision;
+        let shapes = allocator.allocate(Layout::array::<Shape<A>>(targets.len())?)?.cast::<Shape<A>>();
+        for (i, vertices) in targets.iter().enumerate() {
+            let shape = Shape::from_vertices(vertices, allocator.clone())?;
+            unsafe { shapes.as_ptr().add(i).write(shape) };
+        }
+        this.targets = Some(shapes);
+        Ok(unsafe { NonNull::new_unchecked(ABox::into_raw(this)) })
+    }
+
+    pub fn get_targets(&self) -> &[Shape<A>] {
+        match self.targets {
+            Some(v) => unsafe { std::slice::from_raw_parts(v.as_ptr(), self.num_targets as usize) },
+            None => &[]
+        }
+    }
+    pub fn get_target(&self, index: usize) -> Option<&Shape<A>> { self.get_targets().get(index) }
+    pub fn get_attributes(&self) -> MorphTargetAttribute { self.fvf }
+
+    /// Check if position offsets are written as f32 instead of f16. Offsets are always decoded
+    /// to f32 when they're read.
+    pub fn is_single_precision(&self) -> bool {
+        self.fvf.contains(MorphTargetAttribute::UseSinglePrecision)
+    }
+    pub fn set_single_precision(&mut self, value: bool) {
+        self.fvf.set(MorphTargetAttribute::UseSinglePrecision, value);
+    }
+
+    fn get_names(&self) -> &[Option<String>] {
+        match unsafe { MORPH_TARGET_NAMES.get::<_, Vec<Option<String>>>(self as *const Self) } {
+            Some(v) => unsafe { v.as_ref() }.as_slice(),
+            None => &[]
+        }
+    }
+    pub fn get_target_name(&self, index: usize) -> Option<&str> {
+        self.get_names().get(index).and_then(|v| v.as_deref())
+    }
+    /// Find a target's index using the name given to it with set_target_name
+    pub fn find_target(&self, name: &str) -> Option<usize> {
+        self.get_names().iter().position(|v| v.as_deref() == Some(name))
+    }
+    pub fn set_target_name(&mut self, index: usize, name: &str) -> Result<(), MorphError> {
+        let count = self.num_targets as usize;
+        if index >= count {
+            return Err(MorphError::InvalidTargetIndex(index));
+        }
+        let mut names = unsafe { MORPH_TARGET_NAMES.get_or_insert_with(self as *const Self, || vec![None; count]) };
+        unsafe { names.as_mut() }[index] = Some(name.to_owned());
+        Ok(())
+    }
+
+    /// Add a copy of the offsets for each vertex in `sources` to the end of every target, for
+    /// vertices that were copied in the geometry
+    pub(crate) fn copy_vertices(&mut self, sources: &[usize]) -> Result<(), Box<dyn Error>> {
+        let targets = match self.targets {
+            Some(v) => unsafe { std::slice::from_raw_parts_mut(v.as_ptr(), self.num_targets as usize) },
+            None => return Ok(())
+        };
+        for target in targets {
+            let mut vertices = target.get_vertices().to_vec();
+            for source in sources {
+                vertices.push(vertices.get(*source).copied().ok_or(MorphError::InvalidVertexIndex(*source))?);
+            }
+            let fvf = target.fvf;
+            *target = Shape::from_vertices(&vertices, self._allocator.clone())?;
+            target.fvf = fvf;
+        }
+        Ok(())
+    }
+
+    /// Add each target's position offsets to `positions`, scaled by the target's weight.
+    /// Targets without a weight are left out.
+    pub fn apply_weights(&self, positions: &[Vec3], weights: &[f32]) -> Result<Vec<Vec3>, MorphError> {
+        let mut out = positions.to_vec();
+        for (i, (target, weight)) in self.get_targets().iter().zip(weights.iter()).enumerate() {
+            let offsets = target.get_vertices();
+            if offsets.len() != positions.len() {
+                return Err(MorphError::VertexCountMismatch { target: i, expected: positions.len(), found: offsets.len() });
+            }
+            if *weight == 0. {
+                continue;
+            }
+            for (position, offset) in out.iter_mut().zip(offsets.iter()) {
+                *position += *offset * *weight;
+            }
+        }
+        Ok(out)
+    }
+}
+
+impl<A> Drop for MorphTarget<A>
+where A: Allocator + Clone {
+    fn drop(&mut self) {
+        MORPH_TARGET_NAMES.remove(self as *const Self);
+        if let Some(targets) = self.targets.take() {
+            let count = self.num_targets as usize;
+            unsafe { std::ptr::drop_in_place(std::ptr::slice_from_raw_parts_mut(targets.as_ptr(), count)) };
+            let layout = Layout::array::<Shape<A>>(count).unwrap();
+            unsafe { self._allocator.deallocate(targets.cast(), layout) };
+        }
+    }
+}
+
+impl<A> Shape<A>
+where A: Allocator + Clone {
+    pub(crate) fn new(allocator: A) -> Self {
+        Self {
+            fvf: 0,
+            num_vertices: 0,
+            vertices: None,
+            _allocator: allocator
+        }
+    }
+
+    pub(crate) fn from_vertices(vertices: &[Vec3], allocator: A) -> Result<Self, Box<dyn Error>> {
+        let data = allocator.allocate(Layout::array::<Vec3>(vertices.len())?)?.cast::<Vec3>();
+        unsafe { std::ptr::copy_nonoverlapping(vertices.as_ptr(), data.as_ptr(), vertices.len()) };
+        Ok(Self {
+            fvf: VertexAttributeFlags::PositionXYZ.bits(),
+            num_vertices: vertices.len() as i32,
+            vertices: Some(data),
+            _allocator: allocator
+        })
+    }
+
+    /// Get the position offset for each vertex, relative to the geometry's vertex positions
+    pub fn get_vertices(&self) -> &[Vec3] {
+        match self.vertices {
+            Some(v) => unsafe { std::slice::from_raw_parts(v.as_ptr(), self.num_vertices as usize) },
+            None => &[]
+        }
+    }
+}
+
+impl<A> Drop for Shape<A>
+where A: Allocator + Clone {
+    fn drop(&mut self) {
+        if let Some(vertices) = self.vertices.take() {
+            let layout = Layout::array::<Vec3>(self.num_vertices as usize).unwrap();
+            unsafe { self._allocator.deallocate(vertices.cast(), layout) };
+        }
+    }
+}
+
 bitflags! {
     #[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq)]
     pub struct MorphTargetAttribute : u32 {
@@ -65,9 +367,14 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
     fn stream_read(stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<DeserializationHeap<Self, AObject>, Box<dyn Error>> {
         let mut this = DeserializationHeap::<Self, AObject>::zeroed(param);
         this.ref_ = Reference::new();
+        unsafe { std::ptr::write(&raw mut this._allocator, param.get_heap_allocator().unwrap()) };
         this.stream_read_inner(stream, param)?;
         Ok(this)
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream, param)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -77,10 +384,28 @@ where AObject: Allocator + Clone
     fn stream_read_inner<AStream, T>(&mut self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>>
     where T: Debug + Read + Write + Seek + StreamIODevice,
         AStream: Allocator + Clone + Debug {
+        let allocator = param.get_heap_allocator().unwrap();
         self.num_targets = stream.read_u32()? as i32;
         self.fvf = MorphTargetAttribute::from_bits_truncate(stream.read_u32()?);
-        for _ in 0..self.num_targets {
-            let _ = Shape::<AObject>::stream_read(stream, &mut ShapeSerializationContext::new(param.get_heap_allocator().unwrap(), self.fvf));
+        let targets = allocator.allocate(Layout::array::<Shape<AObject>>(self.num_targets as usize)?)?.cast::<Shape<AObject>>();
+        let mut context = ShapeSerializationContext::new(allocator.clone(), self.fvf);
+        for i in 0..self.num_targets as usize {
+            let mut shape = Shape::new(allocator.clone());
+            shape.stream_read_inner(stream, &mut context)?;
+            unsafe { targets.as_ptr().add(i).write(shape) };
+        }
+        self.targets = Some(targets);
+        Ok(())
+    }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug {
+        stream.write_u32(self.num_targets as u32)?;
+        stream.write_u32(self.fvf.bits())?;
+        let mut context = ShapeSerializationContext::new(param.get_heap_allocator().unwrap(), self.fvf);
+        for target in self.get_targets() {
+            target.stream_write_inner(stream, &mut context)?;
         }
         Ok(())
     }
@@ -115,9 +440,14 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
 {
     fn stream_read(stream: &mut Stream<AStream, T>, param: &mut ShapeSerializationContext<AObject>) -> Result<DeserializationHeap<Self, AObject>, Box<dyn Error>> {
         let mut this = DeserializationHeap::<Self, AObject>::zeroed(param);
+        unsafe { std::ptr::write(&raw mut this._allocator, param.get_heap_allocator().unwrap()) };
         this.stream_read_inner(stream, param)?;
         Ok(this)
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, param: &mut ShapeSerializationContext<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream, param)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -129,12 +459,73 @@ where AObject: Allocator + Clone
           AStream: Allocator + Clone + Debug {
         self.fvf = stream.read_u32()?;
         self.num_vertices = stream.read_u32()? as i32;
-        for i in 0..self.num_vertices {
+        let vertices = param.get_heap_allocator().unwrap().allocate(Layout::array::<Vec3>(self.num_vertices as usize)?)?.cast::<Vec3>();
+        for i in 0..self.num_vertices as usize {
+            let vertex = match param.fvf.contains(MorphTargetAttribute::UseSinglePrecision) {
+                true => Vec3::stream_read(stream, &mut ())?.into_raw(),
+                false => {
+                    let mut half = [0u16; 3];
+                    stream.read_u16_slice(half.as_mut_slice())?;
+                    Vec3::new(f16::from_bits(half[0]).to_f32(), f16::from_bits(half[1]).to_f32(), f16::from_bits(half[2]).to_f32())
+                }
+            };
+            unsafe { vertices.as_ptr().add(i).write(vertex) };
+        }
+        self.vertices = Some(vertices);
+        Ok(())
+    }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>, param: &mut ShapeSerializationContext<AObject>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        stream.write_u32(self.fvf)?;
+        stream.write_u32(self.num_vertices as u32)?;
+        for vertex in self.get_vertices() {
             match param.fvf.contains(MorphTargetAttribute::UseSinglePrecision) {
-                true => stream.seek(SeekFrom::Current(self.num_vertices as i64 * size_of::<Vec3>() as i64))?,
-                false => stream.seek(SeekFrom::Current(self.num_vertices as i64 * size_of::<[f16; 3]>() as i64))?
+                true => vertex.stream_write(stream, &mut ())?,
+                false => stream.write_u16_slice(vertex.to_array().map(|v| f16::from_f32(v).to_bits()).as_slice())?
             };
         }
         Ok(())
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+pub mod tests {
+    use allocator_api2::{
+        alloc::Global,
+        boxed::Box as ABox
+    };
+    use glam::Vec3;
+    use std::error::Error;
+    use super::{ MorphController, MorphError, MorphTarget, MORPH_TARGET_NAMES };
+
+    type TestReturn = Result<(), Box<dyn Error>>;
+
+    #[test]
+    fn apply_morph_weights() -> TestReturn {
+        let ptr = MorphTarget::new_in(&[vec![Vec3::Y; 2], vec![Vec3::X; 2]], Global)?;
+        let mut target = unsafe { ABox::from_raw_in(ptr.as_ptr(), Global) };
+        assert!(target.is_single_precision());
+        let positions = [Vec3::ZERO, Vec3::Z];
+        let morphed = target.apply_weights(&positions, &[0.5, 2.])?;
+        assert_eq!(morphed, vec![Vec3::new(2., 0.5, 0.), Vec3::new(2., 0.5, 1.)]);
+        assert!(matches!(target.apply_weights(&[Vec3::ZERO], &[1.]),
+            Err(MorphError::VertexCountMismatch { target: 0, expected: 1, found: 2 })));
+
+        assert_eq!(target.get_target_name(1), None);
+        target.set_target_name(1, "smile")?;
+        assert_eq!(target.get_target_name(1), Some("smile"));
+        assert_eq!(target.get_target_name(0), None);
+        assert_eq!(target.find_target("smile"), Some(1));
+        assert!(matches!(target.set_target_name(2, "blink"), Err(MorphError::InvalidTargetIndex(2))));
+        // Names are removed along with the target
+        drop(target);
+        assert!(unsafe { MORPH_TARGET_NAMES.get::<_, Vec<Option<String>>>(ptr.as_ptr()) }.is_none());
+
+        let ptr = MorphController::new_in("mat", 2, Global)?;
+        let mut controller = unsafe { ABox::from_raw_in(ptr.as_ptr(), Global) };
+        controller.get_weights_mut().copy_from_slice(&[0.25, 0.75]);
+        assert_eq!(controller.get_target_weights(3), vec![0.25, 0.75, 0.]);
+        Ok(())
+    }
+}
@@ -1,5 +1,8 @@
 #![allow(dead_code)]
-use allocator_api2::alloc::Allocator;
+use allocator_api2::{
+    alloc::Allocator,
+    boxed::Box as ABox
+};
 use glam::{ Vec3A, Quat, Mat4 };
 use crate::{
     kernel::allocator::GfdAllocator,
@@ -10,11 +13,37 @@ use crate::{
     }
 };
 use std::{
+    error::Error,
     fmt::{ Display, Debug },
     ops::{ Deref, DerefMut },
     ptr::NonNull
 };
 use crate::object::object::CastFromObject;
+#[cfg(feature = "serialize")]
+use std::io::{ Read, Seek, Write };
+#[cfg(feature = "serialize")]
+use crate::{
+    kernel::version::GfdVersion,
+    object::{
+        camera::Camera,
+        epl::EPL,
+        geometry::Geometry,
+        light::Light,
+        morph::MorphController
+    },
+    utility::{
+        name::{ NameSerializationContext, NameSerializationHash, NameSerializationTechnique },
+        stream::{
+            DeserializationHeap,
+            DeserializationStrategy,
+            GfdSerializationUserData,
+            GfdSerialize,
+            SerializationSingleAllocator,
+            Stream,
+            StreamIODevice
+        }
+    }
+};
 // use riri_mod_tools_rt::logln;
 
 #[repr(C)]
@@ -27,7 +56,7 @@ where A: Allocator + Clone
     local_tm: Mat4,
     transform: NodeTransform,
     link: NodeLink<A>,
-    name: Name,
+    name: Name<A>,
     visibility: f32,
     object_head: Option<NonNull<Object<A>>>,
     object_tail: Option<NonNull<Object<A>>>,
@@ -48,6 +77,199 @@ where A: Allocator + Clone
     const OBJECT_ID: ObjectId = ObjectId::Node;
 }
 
+#[derive(Debug)]
+pub enum NodeError {
+    UnsupportedAttachment(ObjectId)
+}
+impl Error for NodeError {}
+impl Display for NodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl<A> Node<A>
+where A: Allocator + Clone
+{
+    /// Create a node with an identity transform and no attachments or children
+    pub fn new_in(name: &str, allocator: A) -> NonNull<Self> {
+        let mut this: ABox<Self, A> = unsafe { ABox::new_zeroed_in(allocator.clone()).assume_init() };
+        unsafe { this._super.set_id(ObjectId::Node) };
+        unsafe { std::ptr::write(&raw mut this._allocator, allocator.clone()) };
+        this.world_tm = Mat4::IDENTITY;
+        this.local_tm = Mat4::IDENTITY;
+        this.transform.rotate = Quat::IDENTITY;
+        this.transform.scale = Vec3A::ONE;
+        this.visibility = 1.;
+        this.name = Name::new_in(name, allocator);
+        unsafe { NonNull::new_unchecked(ABox::into_raw(this)) }
+    }
+
+    /// Add an object to the end of this node's attachment list.
+    pub(crate) fn attach_object(&mut self, mut object: NonNull<Object<A>>) {
+        let obj = unsafe { object.as_mut() };
+        obj.set_parent(Some(self));
+        obj.set_prev(self.object_tail);
+        obj.set_next(None);
+        match self.object_tail {
+            Some(mut v) => unsafe { v.as_mut().set_next(Some(object)) },
+            None => self.object_head = Some(object)
+        };
+        self.object_tail = Some(object);
+    }
+
+    /// Add a node to the start of this node's child list.
+    pub(crate) fn prepend_child(&mut self, mut child: NonNull<Node<A>>) {
+        let node = unsafe { child.as_mut() };
+        node._super.set_parent(Some(self));
+        node.link.next = self.link.child;
+        self.link.child = Some(child);
+    }
+
+    /// Set the root node for this node and all of its descendants, then recalculate their
+    /// transforms. Parent nodes are always visited before their children.
+    pub(crate) fn set_hierarchy_root(&mut self, root: NonNull<Node<A>>) {
+        self.for_each_in_hierarchy(|node| {
+            node.link.root = Some(root);
+            node.evaluate_local_transform();
+        });
+    }
+
+    /// Recalculate the local and world transform for this node and all of its descendants.
+    /// Parent nodes are always visited before their children, so each world transform includes
+    /// the parent's updated transform.
+    pub fn evaluate_hierarchy_transform(&mut self) {
+        self.for_each_in_hierarchy(Self::evaluate_local_transform);
+    }
+
+    // Visit this node and all of its descendants, parents first
+    fn for_each_in_hierarchy<F>(&mut self, f: F)
+    where F: FnMut(&mut Node<A>) {
+        NodeIteratorMut::<A, StandardNodeIterator>::from_node(self).for_each(f);
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl<AStream, AObject, T> GfdSerialize<AStream, T, AObject, DeserializationHeap<Self, AObject>, SerializationSingleAllocator<AObject>> for Node<AObject>
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      AStream: Allocator + Clone + Debug,
+      AObject: Allocator + Clone
+{
+    fn stream_read(stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<DeserializationHeap<Self, AObject>, Box<dyn Error>> {
+        let mut this = DeserializationHeap::<Self, AObject>::zeroed(param);
+        unsafe { this._super.set_id(ObjectId::Node) };
+        this.world_tm = Mat4::IDENTITY;
+        this.local_tm = Mat4::IDENTITY;
+        this.visibility = 1.;
+        this.stream_read_inner(stream, param)?;
+        Ok(this)
+    }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        self.stream_write_inner(stream, param)
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl<AObject> Node<AObject>
+where AObject: Allocator + Clone
+{
+    // Reads the node along with all of it's attachments and children
+    fn stream_read_inner<AStream, T>(&mut self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        self.name = Name::<AObject>::stream_read(stream, &mut NameSerializationContext::new(param.get_heap_allocator().unwrap(), NameSerializationHash))?.into_raw();
+        self.transform.translate = Vec3A::stream_read(stream, &mut ())?.into_raw();
+        self.transform.rotate = Quat::stream_read(stream, &mut ())?.into_raw();
+        self.transform.scale = Vec3A::stream_read(stream, &mut ())?.into_raw();
+        if stream.has_feature(GfdVersion::NodeRemoveExtraByte).is_none() {
+            stream.read_u8()?;
+        }
+        let attachment_count = stream.read_u32()?;
+        for _ in 0..attachment_count {
+            let object = Self::stream_read_attachment(stream, param)?;
+            self.attach_object(object);
+        }
+        if stream.has_feature(GfdVersion::NodeContainsProperties).is_some() && stream.read_u8()? != 0 {
+            self.property = Some(Property::<AObject>::stream_read(stream, param)?.into_raw());
+        }
+        if stream.has_feature(GfdVersion::NodeAddVisibility).is_some() {
+            self.visibility = stream.read_f32()?;
+        }
+        // Children are stored in reverse order, so prepending them restores the original order
+        let child_count = stream.read_u32()?;
+        for _ in 0..child_count {
+            let child = Self::stream_read(stream, param)?.into_raw();
+            self.prepend_child(child);
+        }
+        Ok(())
+    }
+
+    fn stream_read_attachment<AStream, T>(stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<NonNull<Object<AObject>>, Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        let id: ObjectId = stream.read_u32()?.try_into()?;
+        Ok(match id {
+            ObjectId::Node => Self::stream_read(stream, param)?.into_raw().cast(),
+            ObjectId::Geometry => Geometry::<AObject>::stream_read(stream, param)?.into_raw().cast(),
+            ObjectId::Camera => Camera::<AObject>::stream_read(stream, param)?.into_raw().cast(),
+            ObjectId::Light => Light::<AObject>::stream_read(stream, param)?.into_raw().cast(),
+            ObjectId::EPL => EPL::<AObject>::stream_read(stream, param)?.into_raw().cast(),
+            ObjectId::Morph => MorphController::<AObject>::stream_read(stream, param)?.into_raw().cast(),
+            v => return Err(Box::new(NodeError::UnsupportedAttachment(v)))
+        })
+    }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        NameSerializationHash.stream_write_inner(stream, &self.name)?;
+        self.transform.translate.stream_write(stream, &mut ())?;
+        self.transform.rotate.stream_write(stream, &mut ())?;
+        self.transform.scale.stream_write(stream, &mut ())?;
+        if stream.has_feature(GfdVersion::NodeRemoveExtraByte).is_none() {
+            stream.write_u8(0)?;
+        }
+        stream.write_u32(self.iter_object().count() as u32)?;
+        for object in self.iter_object() {
+            Self::stream_write_attachment(object, stream, param)?;
+        }
+        if stream.has_feature(GfdVersion::NodeContainsProperties).is_some() {
+            match self.get_property() {
+                Some(p) => {
+                    stream.write_u8(1)?;
+                    p.stream_write(stream, param)?;
+                },
+                None => stream.write_u8(0)?
+            };
+        }
+        if stream.has_feature(GfdVersion::NodeAddVisibility).is_some() {
+            stream.write_f32(self.visibility)?;
+        }
+        let children = self.get_direct_children();
+        stream.write_u32(children.len() as u32)?;
+        for child in children.into_iter().rev() {
+            child.stream_write_inner(stream, param)?;
+        }
+        Ok(())
+    }
+
+    fn stream_write_attachment<AStream, T>(object: &Object<AObject>, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        stream.write_u32(object.get_id() as u32)?;
+        match object.get_id() {
+            ObjectId::Node => object.as_object::<Self>().unwrap().stream_write_inner(stream, param),
+            ObjectId::Geometry => object.as_object::<Geometry<AObject>>().unwrap().stream_write(stream, param),
+            ObjectId::Camera => object.as_object::<Camera<AObject>>().unwrap().stream_write(stream, param),
+            ObjectId::Light => object.as_object::<Light<AObject>>().unwrap().stream_write(stream, param),
+            ObjectId::EPL => object.as_object::<EPL<AObject>>().unwrap().stream_write(stream, param),
+            ObjectId::Morph => object.as_object::<MorphController<AObject>>().unwrap().stream_write(stream, param),
+            v => Err(Box::new(NodeError::UnsupportedAttachment(v)))
+        }
+    }
+}
+
 // ==========================
 // Standard Node Iterator
 // ==========================
@@ -758,7 +980,7 @@ where A: Allocator + Clone
     pub fn get_name(&self) -> Option<&str> {
         self.name.get_string()
     }
-    pub fn get_name_platform(&self) -> &Name {
+    pub fn get_name_platform(&self) -> &Name<A> {
         &self.name
     }
 
@@ -5,7 +5,8 @@ use crate::{
     object::node::Node
 };
 use std::{
-    fmt::Debug,
+    error::Error,
+    fmt::{ Debug, Display },
     hash::{ Hash, Hasher },
     marker::PhantomPinned,
     ptr::NonNull,
@@ -28,6 +29,34 @@ pub enum ObjectId {
     Morph
 }
 
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq)]
+pub struct InvalidObjectId(u32);
+
+impl Error for InvalidObjectId {}
+impl Display for InvalidObjectId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid Object ID {}", self.0)
+    }
+}
+
+impl TryFrom<u32> for ObjectId {
+    type Error = InvalidObjectId;
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            2 => Ok(Self::Mesh),
+            3 => Ok(Self::Node),
+            4 => Ok(Self::Geometry),
+            5 => Ok(Self::Camera),
+            6 => Ok(Self::Light),
+            7 => Ok(Self::EPL),
+            8 => Ok(Self::EPLLeaf),
+            9 => Ok(Self::Morph),
+            v => Err(InvalidObjectId(v))
+        }
+    }
+}
+
 pub trait ObjectFunctionTable {
 }
 
@@ -147,6 +176,16 @@ where A: Allocator + Clone
     pub unsafe fn set_id(&mut self, id: ObjectId) {
         self.id = id;
     }
+
+    pub(crate) fn set_prev(&mut self, prev: Option<NonNull<Self>>) {
+        self.prev = prev;
+    }
+    pub(crate) fn set_next(&mut self, next: Option<NonNull<Self>>) {
+        self.next = next;
+    }
+    pub(crate) fn get_allocator(&self) -> &A {
+        &self._allocator
+    }
 }
 
 pub trait CastFromObject {
@@ -0,0 +1,345 @@
+use allocator_api2::alloc::Allocator;
+use allocator_api2::vec::Vec as AVec;
+use crate::{
+    kernel::allocator::GfdAllocator,
+    utility::name::Name
+};
+#[cfg(feature = "serialize")]
+use crate::utility::{
+    name::{ NameSerializationContext, NameSerializationHash, NameSerializationTechnique },
+    stream::{
+        ChunkError, ChunkSerializationContext, ChunkType,
+        DeserializationHeap, GfdSerializationUserData, GfdSerialize,
+        Stream, StreamIODevice
+    }
+};
+use glam::Mat4;
+use std::{
+    error::Error,
+    fmt::Debug,
+    io::{ Read, Seek, Write }
+};
+#[cfg(feature = "serialize")]
+use std::io::SeekFrom;
+
+// flags + 4 parameters + 3 entry counts
+const PHYSICS_HEADER_SIZE: usize = size_of::<u32>() * 8;
+
+/// Simulation parameters for a bone that's driven by cloth or hair physics
+#[derive(Debug)]
+pub struct PhysicsBody<A = GfdAllocator>
+where A: Allocator + Clone
+{
+    params: [f32; 5],
+    name: Name<A>
+}
+
+impl<A> PhysicsBody<A>
+where A: Allocator + Clone
+{
+    pub fn new(params: [f32; 5], name: Name<A>) -> Self {
+        Self { params, name }
+    }
+    pub fn get_params(&self) -> &[f32; 5] { &self.params }
+    pub fn get_params_mut(&mut self) -> &mut [f32; 5] { &mut self.params }
+    pub fn get_name(&self) -> Option<&str> { self.name.get_string() }
+}
+
+/// A collision shape attached to a bone, which physics bodies are pushed out of
+#[derive(Debug)]
+pub struct PhysicsCollision<A = GfdAllocator>
+where A: Allocator + Clone
+{
+    collision_type: u32,
+    radius: f32,
+    height: f32,
+    transform: Mat4,
+    bone: Name<A>
+}
+
+impl<A> PhysicsCollision<A>
+where A: Allocator + Clone
+{
+    pub fn new(collision_type: u32, radius: f32, height: f32, transform: Mat4, bone: Name<A>) -> Self {
+        Self { collision_type, radius, height, transform, bone }
+    }
+    pub fn get_collision_type(&self) -> u32 { self.collision_type }
+    pub fn get_radius(&self) -> f32 { self.radius }
+    pub fn set_radius(&mut self, radius: f32) { self.radius = radius; }
+    pub fn get_height(&self) -> f32 { self.height }
+    pub fn set_height(&mut self, height: f32) { self.height = height; }
+    pub fn get_transform(&self) -> &Mat4 { &self.transform }
+    pub fn set_transform(&mut self, transform: Mat4) { self.transform = transform; }
+    pub fn get_bone_name(&self) -> Option<&str> { self.bone.get_string() }
+}
+
+/// A constraint that keeps two bones at a set distance from each other
+#[derive(Debug)]
+pub struct PhysicsLink<A = GfdAllocator>
+where A: Allocator + Clone
+{
+    params: [f32; 4],
+    parent: Name<A>,
+    child: Name<A>
+}
+
+impl<A> PhysicsLink<A>
+where A: Allocator + Clone
+{
+    pub fn new(params: [f32; 4], parent: Name<A>, child: Name<A>) -> Self {
+        Self { params, parent, child }
+    }
+    pub fn get_params(&self) -> &[f32; 4] { &self.params }
+    pub fn get_params_mut(&mut self) -> &mut [f32; 4] { &mut self.params }
+    pub fn get_parent_name(&self) -> Option<&str> { self.parent.get_string() }
+    pub fn get_child_name(&self) -> Option<&str> { self.child.get_string() }
+}
+
+/// Cloth and hair physics data stored in a model's PhysicsDictionary chunk. Anything in the chunk
+/// after the links is kept as it is in the file, so reading and writing a model never loses
+/// physics data.
+pub struct PhysicsDictionary<A = GfdAllocator>
+where A: Allocator + Clone
+{
+    flags: u32,
+    params: [f32; 4],
+    bodies: AVec<PhysicsBody<A>, A>,
+    collisions: AVec<PhysicsCollision<A>, A>,
+    links: AVec<PhysicsLink<A>, A>,
+    trailing_data: AVec<u8, A>
+}
+
+impl<A> Debug for PhysicsDictionary<A>
+where A: Allocator + Clone
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PhysicsDictionary {{ flags: 0x{:x}, params: {:?}, bodies: {:?}, collisions: {:?}, links: {:?}, trailing_data: 0x{:x} bytes }}",
+            self.flags, self.params, self.bodies, self.collisions, self.links, self.trailing_data.len())
+    }
+}
+
+impl<A> PhysicsDictionary<A>
+where A: Allocator + Clone
+{
+    pub fn get_flags(&self) -> u32 { self.flags }
+    pub fn set_flags(&mut self, flags: u32) { self.flags = flags; }
+    pub fn get_params(&self) -> &[f32; 4] { &self.params }
+    pub fn get_params_mut(&mut self) -> &mut [f32; 4] { &mut self.params }
+    pub fn get_bodies(&self) -> &[PhysicsBody<A>] { self.bodies.as_slice() }
+    pub fn get_bodies_mut(&mut self) -> &mut AVec<PhysicsBody<A>, A> { &mut self.bodies }
+    pub fn get_collisions(&self) -> &[PhysicsCollision<A>] { self.collisions.as_slice() }
+    pub fn get_collisions_mut(&mut self) -> &mut AVec<PhysicsCollision<A>, A> { &mut self.collisions }
+    pub fn get_links(&self) -> &[PhysicsLink<A>] { self.links.as_slice() }
+    pub fn get_links_mut(&mut self) -> &mut AVec<PhysicsLink<A>, A> { &mut self.links }
+    /// Data stored after the links, which is written back out unchanged
+    pub fn get_trailing_data(&self) -> &[u8] { self.trailing_data.as_slice() }
+}
+
+#[cfg(feature = "serialize")]
+impl<AStream, AObject, T> GfdSerialize<AStream, T, AObject, DeserializationHeap<Self, AObject>, ChunkSerializationContext<AObject>> for PhysicsDictionary<AObject>
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      AStream: Allocator + Clone + Debug,
+      AObject: Allocator + Clone
+{
+    fn stream_read(stream: &mut Stream<AStream, T>, param: &mut ChunkSerializationContext<AObject>) -> Result<DeserializationHeap<Self, AObject>, Box<dyn Error>> {
+        let allocator = param.get_heap_allocator().unwrap();
+        let length = param.get_length();
+        let invalid_length = || Box::new(ChunkError::InvalidLength(ChunkType::PhysicsDictionary, length as u32));
+        if length < PHYSICS_HEADER_SIZE {
+            return Err(invalid_length());
+        }
+        let start = stream.seek(SeekFrom::Current(0))?;
+        let flags = stream.read_u32()?;
+        let mut params = [0f32; 4];
+        stream.read_f32_slice(params.as_mut_slice())?;
+        let num_bodies = stream.read_u32()?;
+        let num_collisions = stream.read_u32()?;
+        let num_links = stream.read_u32()?;
+        let mut bodies = AVec::new_in(allocator.clone());
+        for _ in 0..num_bodies {
+            bodies.push(PhysicsBody::stream_read_inner(stream, allocator.clone())?);
+        }
+        let mut collisions = AVec::new_in(allocator.clone());
+        for _ in 0..num_collisions {
+            collisions.push(PhysicsCollision::stream_read_inner(stream, allocator.clone())?);
+        }
+        let mut links = AVec::new_in(allocator.clone());
+        for _ in 0..num_links {
+            links.push(PhysicsLink::stream_read_inner(stream, allocator.clone())?);
+        }
+        let read = (stream.seek(SeekFrom::Current(0))? - start) as usize;
+        let trailing = length.checked_sub(read).ok_or_else(invalid_length)?;
+        let mut trailing_data = AVec::with_capacity_in(trailing, allocator.clone());
+        trailing_data.resize(trailing, 0);
+        stream.read_u8_slice(trailing_data.as_mut_slice())?;
+        Ok(DeserializationHeap::new(Self { flags, params, bodies, collisions, links, trailing_data }, allocator))
+    }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut ChunkSerializationContext<AObject>) -> Result<(), Box<dyn Error>> {
+        stream.write_u32(self.flags)?;
+        stream.write_f32_slice(self.params.as_slice())?;
+        stream.write_u32(self.bodies.len() as u32)?;
+        stream.write_u32(self.collisions.len() as u32)?;
+        stream.write_u32(self.links.len() as u32)?;
+        for body in &self.bodies {
+            body.stream_write_inner(stream)?;
+        }
+        for collision in &self.collisions {
+            collision.stream_write_inner(stream)?;
+        }
+        for link in &self.links {
+            link.stream_write_inner(stream)?;
+        }
+        stream.write_u8_slice(self.trailing_data.as_slice())?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl<AObject> PhysicsBody<AObject>
+where AObject: Allocator + Clone
+{
+    fn stream_read_inner<AStream, T>(stream: &mut Stream<AStream, T>, allocator: AObject) -> Result<Self, Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        let mut params = [0f32; 5];
+        stream.read_f32_slice(params.as_mut_slice())?;
+        let name = Name::<AObject>::stream_read(stream, &mut NameSerializationContext::new(allocator, NameSerializationHash))?.into_raw();
+        Ok(Self { params, name })
+    }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        stream.write_f32_slice(self.params.as_slice())?;
+        NameSerializationHash.stream_write_inner(stream, &self.name)?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl<AObject> PhysicsCollision<AObject>
+where AObject: Allocator + Clone
+{
+    fn stream_read_inner<AStream, T>(stream: &mut Stream<AStream, T>, allocator: AObject) -> Result<Self, Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        let collision_type = stream.read_u32()?;
+        let radius = stream.read_f32()?;
+        let height = stream.read_f32()?;
+        let mut transform = [0f32; 16];
+        stream.read_f32_slice(transform.as_mut_slice())?;
+        let bone = Name::<AObject>::stream_read(stream, &mut NameSerializationContext::new(allocator, NameSerializationHash))?.into_raw();
+        Ok(Self { collision_type, radius, height, transform: Mat4::from_cols_array(&transform), bone })
+    }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        stream.write_u32(self.collision_type)?;
+        stream.write_f32(self.radius)?;
+        stream.write_f32(self.height)?;
+        stream.write_f32_slice(self.transform.to_cols_array().as_slice())?;
+        NameSerializationHash.stream_write_inner(stream, &self.bone)?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl<AObject> PhysicsLink<AObject>
+where AObject: Allocator + Clone
+{
+    fn stream_read_inner<AStream, T>(stream: &mut Stream<AStream, T>, allocator: AObject) -> Result<Self, Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        let mut params = [0f32; 4];
+        stream.read_f32_slice(params.as_mut_slice())?;
+        let parent = Name::<AObject>::stream_read(stream, &mut NameSerializationContext::new(allocator.clone(), NameSerializationHash))?.into_raw();
+        let child = Name::<AObject>::stream_read(stream, &mut NameSerializationContext::new(allocator, NameSerializationHash))?.into_raw();
+        Ok(Self { params, parent, child })
+    }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        stream.write_f32_slice(self.params.as_slice())?;
+        NameSerializationHash.stream_write_inner(stream, &self.parent)?;
+        NameSerializationHash.stream_write_inner(stream, &self.child)?;
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "serialize"))]
+pub mod tests {
+    use allocator_api2::alloc::Global;
+    use glam::Mat4;
+    use std::error::Error;
+    use crate::utility::{
+        name::{ Name, NameSerializationHash, NameSerializationTechnique },
+        stream::{ ChunkSerializationContext, DevicePlatform, GfdSerialize, StreamFactory, StreamHeader }
+    };
+    use super::PhysicsDictionary;
+
+    type TestReturn = Result<(), Box<dyn Error>>;
+
+    #[test]
+    fn physics_dictionary_round_trip() -> TestReturn {
+        let factory = StreamFactory::new(Global);
+        let mut stream = factory.write_to_memory(StreamHeader::new(0x2110000, DevicePlatform::METAPHOR))?;
+        let header_size = stream.as_slice().len();
+        stream.write_u32(1)?;
+        stream.write_f32_slice(&[0.5, 1., 2., 4.])?;
+        stream.write_u32_slice(&[1, 1, 1])?;
+        // body
+        stream.write_f32_slice(&[1., 2., 3., 4., 5.])?;
+        NameSerializationHash.stream_write_inner(&mut stream, &Name::new("hair_01"))?;
+        // collision
+        stream.write_u32(2)?;
+        stream.write_f32_slice(&[0.25, 1.5])?;
+        stream.write_f32_slice(Mat4::from_translation([0., 1., 0.].into()).to_cols_array().as_slice())?;
+        NameSerializationHash.stream_write_inner(&mut stream, &Name::new("head"))?;
+        // link
+        stream.write_f32_slice(&[0.1, 0.2, 0.3, 0.4])?;
+        NameSerializationHash.stream_write_inner(&mut stream, &Name::new("hair_01"))?;
+        NameSerializationHash.stream_write_inner(&mut stream, &Name::new("hair_02"))?;
+        // unknown trailing data
+        stream.write_u8_slice(&[0xde, 0xad, 0xbe, 0xef, 0x12])?;
+        let written = stream.as_slice().to_vec();
+        let mut stream = factory.read_from_memory(&written)?;
+        let mut param = ChunkSerializationContext::new(Global, written.len() - header_size);
+        let physics = PhysicsDictionary::<Global>::stream_read(&mut stream, &mut param)?.into_raw();
+        let physics = unsafe { physics.as_ref() };
+        assert_eq!(physics.get_params()[3], 4.);
+        assert_eq!(physics.get_bodies().len(), 1);
+        assert_eq!(physics.get_bodies()[0].get_params()[4], 5.);
+        assert_eq!(physics.get_bodies()[0].get_name(), Some("hair_01"));
+        let collision = &physics.get_collisions()[0];
+        assert_eq!(collision.get_collision_type(), 2);
+        assert_eq!(collision.get_height(), 1.5);
+        assert_eq!(collision.get_transform().w_axis.y, 1.);
+        assert_eq!(collision.get_bone_name(), Some("head"));
+        assert_eq!(physics.get_links()[0].get_child_name(), Some("hair_02"));
+        assert_eq!(physics.get_trailing_data(), &[0xde, 0xad, 0xbe, 0xef, 0x12]);
+        let mut out = factory.write_to_memory(StreamHeader::new(0x2110000, DevicePlatform::METAPHOR))?;
+        physics.stream_write(&mut out, &mut param)?;
+        assert_eq!(out.as_slice(), written.as_slice());
+        Ok(())
+    }
+
+    #[test]
+    fn physics_dictionary_rejects_short_chunk() -> TestReturn {
+        let factory = StreamFactory::new(Global);
+        let mut stream = factory.write_to_memory(StreamHeader::new(0x2110000, DevicePlatform::METAPHOR))?;
+        stream.write_u32(0)?;
+        stream.write_f32_slice(&[0.; 4])?;
+        stream.write_u32_slice(&[1, 0, 0])?;
+        stream.write_f32_slice(&[0.; 5])?;
+        NameSerializationHash.stream_write_inner(&mut stream, &Name::new("hair_01"))?;
+        let written = stream.as_slice().to_vec();
+        let mut stream = factory.read_from_memory(&written)?;
+        // Chunk length only covers the header, but the body goes past it
+        let mut param = ChunkSerializationContext::new(Global, size_of::<u32>() * 8);
+        assert!(PhysicsDictionary::<Global>::stream_read(&mut stream, &mut param).is_err());
+        Ok(())
+    }
+}
@@ -4,7 +4,7 @@ use std::error::Error;
 use std::fmt::Debug;
 use std::io::{Read, Seek, Write};
 use allocator_api2::alloc::Allocator;
-use glam::{swizzles::Vec4Swizzles, Mat4, Quat, Vec2, Vec3, Vec3A, Vec4};
+use glam::{swizzles::Vec4Swizzles, BVec3, Mat4, Quat, Vec2, Vec3, Vec3A, Vec4};
 use crate::utility::stream::{DeserializationStack, GfdSerialize, Stream, StreamIODevice};
 
 #[repr(C)]
@@ -14,8 +14,24 @@ pub struct BoundingBox {
     min: Vec3
 }
 impl BoundingBox {
+    pub fn new(min: Vec3, max: Vec3) -> Self { Self { max, min } }
     pub fn get_max(&self) -> Vec3 { self.max }
     pub fn get_min(&self) -> Vec3 { self.min }
+    pub fn get_center(&self) -> Vec3 { (self.min + self.max) / 2. }
+    /// Get the smallest box that contains every point, or None if there are no points
+    pub fn from_points(points: &[Vec3]) -> Option<Self> {
+        let first = *points.first()?;
+        let (min, max) = points.iter().fold((first, first), |(min, max), p| (min.min(*p), max.max(*p)));
+        Some(Self::new(min, max))
+    }
+    pub fn get_corners(&self) -> [Vec3; 8] {
+        std::array::from_fn(|i| Vec3::select(BVec3::new(i & 1 != 0, i & 2 != 0, i & 4 != 0), self.max, self.min))
+    }
+    /// Get the box that contains this box after it's moved by a transform
+    pub fn transform(&self, matrix: &Mat4) -> Self {
+        let corners = self.get_corners().map(|v| matrix.transform_point3(v));
+        Self::from_points(&corners).unwrap()
+    }
     // for imgui
     pub fn get_min_mut_f32(&mut self) -> &mut [f32; 3] { 
         unsafe { std::mem::transmute::<_, &mut [f32; 3]>(&mut self.min) } 
@@ -36,6 +52,12 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.min = Vec3::stream_read(stream, &mut ())?.into_raw();
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.max.stream_write(stream, &mut ())?;
+        self.min.stream_write(stream, &mut ())?;
+        Ok(())
+    }
 }
 
 #[repr(C)]
@@ -45,6 +67,35 @@ pub struct BoundingSphere {
     radius: f32
 }
 impl BoundingSphere {
+    pub fn new(center: Vec3, radius: f32) -> Self { Self { center, radius } }
+    pub fn get_center(&self) -> Vec3 { self.center }
+    pub fn get_radius(&self) -> f32 { self.radius }
+    /// Get a sphere that contains every point using Ritter's algorithm, which is usually a few
+    /// percent larger than the smallest sphere. The sphere around the center of the points'
+    /// bounding box is used instead if it's smaller. Returns None if there are no points.
+    pub fn from_points(points: &[Vec3]) -> Option<Self> {
+        let first = *points.first()?;
+        let farthest = |from: Vec3| points.iter().copied()
+            .fold(from, |a, p| if p.distance_squared(from) > a.distance_squared(from) { p } else { a });
+        let a = farthest(first);
+        let b = farthest(a);
+        let mut center = (a + b) / 2.;
+        let mut radius = a.distance(center);
+        for point in points {
+            let distance = point.distance(center);
+            if distance > radius {
+                // Grow the sphere just enough to reach the point from the opposite side
+                radius = (radius + distance) / 2.;
+                center = *point + (center - *point) * (radius / distance);
+            }
+        }
+        let box_center = BoundingBox::from_points(points)?.get_center();
+        let box_radius = points.iter().map(|p| p.distance(box_center)).fold(0., f32::max);
+        Some(match box_radius < radius {
+            true => Self::new(box_center, box_radius),
+            false => Self::new(center, radius)
+        })
+    }
     // for imgui
     pub fn get_center_mut_f32(&mut self) -> &mut [f32; 3] { 
         unsafe { std::mem::transmute::<_, &mut [f32; 3]>(&mut self.center) }
@@ -63,6 +114,12 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.radius = stream.read_f32()?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        self.center.stream_write(stream, &mut ())?;
+        stream.write_f32(self.radius)?;
+        Ok(())
+    }
 }
 
 
@@ -144,6 +201,11 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         stream.read_u8_slice(new.0.as_mut().as_mut_slice())?;
         Ok(new.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        stream.write_u8_slice(self.0.as_ref().as_slice())?;
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -250,9 +312,15 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         stream.read_u8_slice(new.0.as_mut().as_mut_slice())?;
         Ok(new.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        stream.write_u8_slice(self.0.as_ref().as_slice())?;
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RGBFloat(glam::Vec3);
 
 impl RGBFloat {
@@ -315,10 +383,16 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         stream.read_f32_slice(new.get_raw_mut().as_mut_slice())?;
         Ok(new.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        stream.write_f32_slice(self.0.as_ref().as_slice())?;
+        Ok(())
+    }
 }
 
 // Using [f32; 4] instead of glam::Vec4 due to alignment requirements
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "io_serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RGBAFloat([f32; 4]);
 impl RGBAFloat {
     pub const fn from_rgba_array_f32(val: [f32; 4]) -> Self { RGBAFloat(val) }
@@ -411,6 +485,11 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         stream.read_f32_slice(new.get_raw_mut().as_mut_slice())?;
         Ok(new.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        stream.write_f32_slice(self.0.as_slice())?;
+        Ok(())
+    }
 }
 
 /// See https://www.w3.org/TR/css-color-4/#named-colors
@@ -560,7 +639,7 @@ pub mod web_colors {
 }
 
 #[repr(C)]
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct Fade {
     in_: f32,
     out_: f32
@@ -585,10 +664,16 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.out_ = stream.read_f32()?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        stream.write_f32(self.in_)?;
+        stream.write_f32(self.out_)?;
+        Ok(())
+    }
 }
 
 #[repr(C)]
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct Range {
     datums: f32,
     range: f32
@@ -613,6 +698,12 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         this.range = stream.read_f32()?;
         Ok(this.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        stream.write_f32(self.datums)?;
+        stream.write_f32(self.range)?;
+        Ok(())
+    }
 }
 
 #[repr(C)]
@@ -652,6 +743,11 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         stream.read_f32_slice(new.as_mut().as_mut_slice())?;
         Ok(new.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        stream.write_f32_slice(self.as_ref().as_slice())?;
+        Ok(())
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -664,6 +760,11 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         stream.read_f32_slice(new.as_mut().as_mut_slice())?;
         Ok(new.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        stream.write_f32_slice(self.as_ref().as_slice())?;
+        Ok(())
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -676,6 +777,11 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         stream.read_f32_slice(new.as_mut().as_mut_slice())?;
         Ok(new.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        stream.write_f32_slice(self.as_ref().as_slice())?;
+        Ok(())
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -688,6 +794,11 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         stream.read_f32_slice(new.as_mut().as_mut_slice())?;
         Ok(new.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        stream.write_f32_slice(self.as_ref().as_slice())?;
+        Ok(())
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -700,6 +811,11 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         stream.read_f32_slice(new.as_mut_slice())?;
         Ok(Quat::from_array(new).into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        stream.write_f32_slice(self.to_array().as_slice())?;
+        Ok(())
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -712,4 +828,9 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         stream.read_f32_slice(new.as_mut().as_mut_slice())?;
         Ok(new.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        stream.write_f32_slice(self.as_ref().as_slice())?;
+        Ok(())
+    }
 }
\ No newline at end of file
@@ -15,7 +15,7 @@ use std::error::Error;
 use std::fmt::Formatter;
 use std::io::{Read, Seek, Write};
 use glam::Mat4;
-use crate::kernel::version::GfdVersion;
+use crate::kernel::version::{ GameProfile, GfdVersion };
 #[cfg(feature = "serialize")]
 use crate::utility::stream::{DeserializationStack, GfdSerializationUserData, GfdSerialize, SerializationSingleAllocator, Stream, StreamIODevice};
 
@@ -239,6 +239,9 @@ where AObject: Allocator + Clone {
     fn stream_read_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>, alloc: AObject) -> Result<Name<AObject>, Box<dyn Error>>
     where T: Debug + Read + Write + Seek + StreamIODevice,
           AStream: Allocator + Clone + Debug;
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>, name: &Name<AObject>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug;
 }
 
 #[cfg(feature = "serialize")]
@@ -280,6 +283,14 @@ where AObject: Allocator + Clone {
     {
         Name::stream_read_string(stream, alloc)
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>, name: &Name<AObject>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        name.stream_write_string(stream)
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -312,6 +323,18 @@ where AObject: Allocator + Clone {
         Ok(name)
         */
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>, name: &Name<AObject>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        name.stream_write_string(stream)?;
+        if stream.has_feature(GfdVersion::NameContainsHash).is_some() {
+            stream.write_u32(name.get_hash())?;
+        }
+        Ok(())
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -325,7 +348,8 @@ where AObject: Allocator + Clone {
     {
         let name = Name::stream_read_string(stream, alloc)?;
         if stream.has_feature(GfdVersion::NameContainsHash).is_some() {
-            if cfg!(feature = "cfb_gap") {
+            // Catherine: Full Body has an extra byte between the name and it's hash
+            if stream.get_game_profile() == GameProfile::CatherineFullBody {
                 let _ = stream.read_u8()?;
             }
             let serial_hash = stream.read_u32()?;
@@ -336,6 +360,21 @@ where AObject: Allocator + Clone {
         }
         Ok(name)
     }
+
+    fn stream_write_inner<AStream, T>(&self, stream: &mut Stream<AStream, T>, name: &Name<AObject>) -> Result<(), Box<dyn Error>>
+    where
+        T: Debug + Read + Write + Seek + StreamIODevice,
+        AStream: Allocator + Clone + Debug,
+    {
+        name.stream_write_string(stream)?;
+        if stream.has_feature(GfdVersion::NameContainsHash).is_some() {
+            if stream.get_game_profile() == GameProfile::CatherineFullBody {
+                stream.write_u8(0)?;
+            }
+            stream.write_u32(name.get_hash())?;
+        }
+        Ok(())
+    }
 }
 
 // Called from stream_read
@@ -351,6 +390,15 @@ where AObject: Allocator + Clone
         stream.read_u8_slice(slice)?;
         Ok(Self::new_in(unsafe { std::str::from_utf8_unchecked(slice) }, alloc))
     }
+
+    fn stream_write_string<T, AStream>(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        let text = self.get_string().unwrap_or("");
+        stream.write_u16(text.len() as u16)?;
+        stream.write_u8_slice(text.as_bytes())?;
+        Ok(())
+    }
 }
 
 #[cfg(feature = "serialize")]
@@ -363,6 +411,10 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
     fn stream_read(stream: &mut Stream<AStream, T>, param: &mut NameSerializationContext<AObject, D>) -> Result<DeserializationStack<Self>, Box<dyn Error>> {
         Ok(param.technique.stream_read_inner(stream, param.get_heap_allocator().unwrap())?.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, param: &mut NameSerializationContext<AObject, D>) -> Result<(), Box<dyn Error>> {
+        param.technique.stream_write_inner(stream, self)
+    }
 }
 
 #[allow(non_snake_case)]
@@ -448,4 +500,30 @@ pub mod tests {
         assert_eq!(Name::new("COMMON/init/gfdDefaultEnv.dds").get_hash(), 0xBB4AC992);
         Ok(())
     }
+
+    #[test]
+    #[cfg(feature = "serialize")]
+    fn gap_name_padding_follows_profile() -> TestReturn {
+        use allocator_api2::alloc::Global;
+        use crate::{
+            kernel::version::GameProfile,
+            utility::stream::{ DevicePlatform, StreamFactory, StreamHeader }
+        };
+        use super::{ NameSerializationHashGAP, NameSerializationTechnique };
+        let factory = StreamFactory::new(Global);
+        let name = Name::new("RootNode");
+        for (profile, size) in [(GameProfile::Persona5Royal, 14), (GameProfile::CatherineFullBody, 15)] {
+            let mut stream = factory.write_to_memory(StreamHeader::new(0x1105100, DevicePlatform::P5R_DX11))?;
+            stream.set_game_profile(profile);
+            let start = stream.as_slice().len();
+            NameSerializationHashGAP.stream_write_inner(&mut stream, &name)?;
+            assert_eq!(stream.as_slice().len() - start, size);
+            let written = stream.as_slice().to_vec();
+            let mut stream = factory.read_from_memory(&written)?;
+            stream.set_game_profile(profile);
+            let read = NameSerializationHashGAP.stream_read_inner(&mut stream, Global)?;
+            assert_eq!(read.get_string(), Some("RootNode"));
+        }
+        Ok(())
+    }
 }
@@ -1,4 +1,4 @@
-use allocator_api2::alloc::Allocator;
+use allocator_api2::alloc::{ Allocator, Layout };
 use bitflags::bitflags;
 use crate::{
     kernel::allocator::GfdAllocator,
@@ -14,6 +14,24 @@ use std::{
     fmt::Display,
     ptr::NonNull
 };
+#[cfg(feature = "serialize")]
+use std::{
+    fmt::Debug,
+    io::{ Read, Seek, Write }
+};
+#[cfg(feature = "serialize")]
+use crate::utility::{
+    name::{ NameSerializationContext, NameSerializationHash, NameSerializationTechnique },
+    stream::{
+        DeserializationHeap,
+        DeserializationStrategy,
+        GfdSerializationUserData,
+        GfdSerialize,
+        SerializationSingleAllocator,
+        Stream,
+        StreamIODevice
+    }
+};
 
 #[repr(u32)]
 #[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
@@ -31,6 +49,36 @@ pub enum ValueType {
     ByteArray = 9,
 }
 
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq)]
+pub struct InvalidValueType(u32);
+
+impl Error for InvalidValueType {}
+impl Display for InvalidValueType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid Property Value Type {}", self.0)
+    }
+}
+
+impl TryFrom<u32> for ValueType {
+    type Error = InvalidValueType;
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Invalid),
+            1 => Ok(Self::Int),
+            2 => Ok(Self::Float),
+            3 => Ok(Self::Bool),
+            4 => Ok(Self::String),
+            5 => Ok(Self::ByteVector3),
+            6 => Ok(Self::ByteVector4),
+            7 => Ok(Self::Vector3),
+            8 => Ok(Self::Vector4),
+            9 => Ok(Self::ByteArray),
+            v => Err(InvalidValueType(v))
+        }
+    }
+}
+
 #[repr(u32)]
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum PropertyAccess {
@@ -258,7 +306,7 @@ where A: Allocator + Clone
 {
     ty: ValueType,
     field04: u32,
-    name: Name,
+    name: Name<A>,
     data: [u8; PROPERTY_MAXIMUM_SIZE],
     size: i32,
     prev: Option<NonNull<Self>>,
@@ -342,11 +390,28 @@ where A: Allocator + Clone
     }
     pub fn get_byte_array_value(&self) -> Result<&[u8], PropertyChunkTypeError> {
         match self.ty {
-            ValueType::ByteArray => Ok(unsafe { std::slice::from_raw_parts(self.data.as_ptr() as *const u8, self.size as usize)}),
+            ValueType::ByteArray => Ok(unsafe { std::slice::from_raw_parts(self.get_data_ptr(), self.size as usize)}),
             _ => Err(PropertyChunkTypeError::new(ValueType::ByteArray, self.ty))
         }
     }
 
+    /// Strings and byte arrays that don't fit inside of the chunk are stored on the heap, with the
+    /// chunk's data holding a pointer to them instead.
+    fn is_data_on_heap(&self) -> bool {
+        match self.ty {
+            ValueType::String => true,
+            ValueType::ByteArray => self.size as usize > PROPERTY_MAXIMUM_SIZE,
+            _ => false
+        }
+    }
+
+    fn get_data_ptr(&self) -> *const u8 {
+        match self.is_data_on_heap() {
+            true => unsafe { *(self.data.as_ptr() as *const *const u8) },
+            false => self.data.as_ptr()
+        }
+    }
+
     pub fn format_data(&self) -> String {
         match self.ty {
             ValueType::Int => format!("{}", self.get_integer_value().unwrap()),
@@ -369,4 +434,92 @@ where A: Allocator + Clone
         let fmt_data = self.format_data();
         write!(f, "<{}: {}>", self.name, fmt_data)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(feature = "serialize")]
+impl<AStream, AObject, T> GfdSerialize<AStream, T, AObject, DeserializationHeap<Self, AObject>, SerializationSingleAllocator<AObject>> for Property<AObject>
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      AStream: Allocator + Clone + Debug,
+      AObject: Allocator + Clone
+{
+    fn stream_read(stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<DeserializationHeap<Self, AObject>, Box<dyn Error>> {
+        let mut this = DeserializationHeap::<Self, AObject>::zeroed(param);
+        this.ref_ = Reference::new();
+        this.stream_read_inner(stream, param)?;
+        Ok(this)
+    }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        stream.write_u32(self.len() as u32)?;
+        for chunk in self {
+            chunk.stream_write(stream, param)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl<AObject> Property<AObject>
+where AObject: Allocator + Clone
+{
+    fn stream_read_inner<AStream, T>(&mut self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        let count = stream.read_u32()?;
+        for _ in 0..count {
+            let mut chunk = PropertyChunk::<AObject>::stream_read(stream, param)?.into_raw();
+            // append to end of chunk list
+            unsafe { chunk.as_mut().prev = self.tail };
+            match self.tail {
+                Some(mut v) => unsafe { v.as_mut().next = Some(chunk) },
+                None => self.head = Some(chunk)
+            };
+            self.tail = Some(chunk);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl<AStream, AObject, T> GfdSerialize<AStream, T, AObject, DeserializationHeap<Self, AObject>, SerializationSingleAllocator<AObject>> for PropertyChunk<AObject>
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      AStream: Allocator + Clone + Debug,
+      AObject: Allocator + Clone
+{
+    fn stream_read(stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<DeserializationHeap<Self, AObject>, Box<dyn Error>> {
+        let mut this = DeserializationHeap::<Self, AObject>::zeroed(param);
+        this.stream_read_inner(stream, param)?;
+        Ok(this)
+    }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        stream.write_u32(self.ty as u32)?;
+        NameSerializationHash.stream_write_inner(stream, &self.name)?;
+        stream.write_u32(self.size as u32)?;
+        stream.write_u8_slice(unsafe { std::slice::from_raw_parts(self.get_data_ptr(), self.size as usize) })?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl<AObject> PropertyChunk<AObject>
+where AObject: Allocator + Clone
+{
+    fn stream_read_inner<AStream, T>(&mut self, stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>>
+    where T: Debug + Read + Write + Seek + StreamIODevice,
+          AStream: Allocator + Clone + Debug {
+        let allocator = param.get_heap_allocator().unwrap();
+        self.ty = stream.read_u32()?.try_into()?;
+        self.name = Name::<AObject>::stream_read(stream, &mut NameSerializationContext::new(allocator.clone(), NameSerializationHash))?.into_raw();
+        self.size = stream.read_u32()? as i32;
+        match self.is_data_on_heap() {
+            true => {
+                let buffer: NonNull<u8> = allocator.allocate(Layout::array::<u8>(self.size as usize)?)?.cast();
+                stream.read_u8_slice(unsafe { std::slice::from_raw_parts_mut(buffer.as_ptr(), self.size as usize) })?;
+                unsafe { *(self.data.as_mut_ptr() as *mut *mut u8) = buffer.as_ptr() };
+            },
+            false => stream.read_u8_slice(&mut self.data[..self.size as usize])?
+        };
+        Ok(())
+    }
+}
@@ -0,0 +1,111 @@
+use std::{
+    collections::BTreeMap,
+    ptr::NonNull,
+    sync::Mutex
+};
+
+// A value owned by a side table, along with the function to drop it with. The value has its own
+// box, so it doesn't move when the map's nodes are rebalanced.
+struct SideEntry {
+    data: NonNull<u8>,
+    drop: unsafe fn(NonNull<u8>)
+}
+
+// SAFETY: Entries are only accessed through the table's mutex, or through references tied to the
+// lifetime of the object that they're stored for
+unsafe impl Send for SideEntry {}
+
+impl Drop for SideEntry {
+    fn drop(&mut self) {
+        unsafe { (self.drop)(self.data) }
+    }
+}
+
+impl SideEntry {
+    fn new<T>(value: T) -> Self {
+        let data = unsafe { NonNull::new_unchecked(Box::into_raw(Box::new(value))) }.cast::<u8>();
+        Self { data, drop: drop_entry::<T> }
+    }
+}
+
+unsafe fn drop_entry<T>(data: NonNull<u8>) {
+    drop(Box::from_raw(data.cast::<T>().as_ptr()))
+}
+
+/// Data that OpenGFD stores for a game structure without changing the structure's layout, keyed
+/// by the structure's address. Since game code will never remove these entries, only objects
+/// allocated by OpenGFD should have them, and they should be removed when the object is dropped.
+///
+/// Pointers returned by the table point into each value's own allocation rather than into the map,
+/// so inserting other keys never invalidates them. A pointer is valid until the value for its key
+/// is replaced or removed, which callers should only do while they have exclusive access to the
+/// object that the key belongs to.
+pub(crate) struct SideTable(Mutex<BTreeMap<usize, SideEntry>>);
+
+impl SideTable {
+    pub(crate) const fn new() -> Self {
+        Self(Mutex::new(BTreeMap::new()))
+    }
+
+    /// Store a value for `key`, replacing the previous value.
+    /// # Safety
+    /// Every value stored in this table for `key` must be of type T
+    pub(crate) unsafe fn insert<K, T>(&self, key: *const K, value: T) {
+        let entry = SideEntry::new(value);
+        // Drop the previous value after the lock is released, in case it owns objects with
+        // entries in the same table
+        let old = self.0.lock().unwrap().insert(key as usize, entry);
+        drop(old);
+    }
+
+    /// Get the value stored for `key`
+    /// # Safety
+    /// Every value stored in this table for `key` must be of type T
+    pub(crate) unsafe fn get<K, T>(&self, key: *const K) -> Option<NonNull<T>> {
+        self.0.lock().unwrap().get(&(key as usize)).map(|v| v.data.cast::<T>())
+    }
+
+    /// Get the value stored for `key`, storing the result of `f` if there isn't one. This is done
+    /// under a single lock, so a value stored for the same key by another thread is never replaced.
+    /// # Safety
+    /// Every value stored in this table for `key` must be of type T
+    pub(crate) unsafe fn get_or_insert_with<K, T, F>(&self, key: *const K, f: F) -> NonNull<T>
+    where F: FnOnce() -> T {
+        self.0.lock().unwrap().entry(key as usize)
+            .or_insert_with(|| SideEntry::new(f()))
+            .data.cast::<T>()
+    }
+
+    /// Drop the value stored for `key` if there is one
+    pub(crate) fn remove<K>(&self, key: *const K) {
+        let old = self.0.lock().unwrap().remove(&(key as usize));
+        drop(old);
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::error::Error;
+    use super::SideTable;
+
+    type TestReturn = Result<(), Box<dyn Error>>;
+
+    #[test]
+    fn insert_and_remove_side_values() -> TestReturn {
+        static TABLE: SideTable = SideTable::new();
+        let (a, b) = (0u32, 0u32);
+        unsafe { TABLE.insert(&raw const a, String::from("first")) };
+        assert_eq!(unsafe { TABLE.get::<_, String>(&raw const a).map(|v| v.as_ref().clone()) }, Some("first".to_owned()));
+        assert!(unsafe { TABLE.get::<_, String>(&raw const b) }.is_none());
+        let value = unsafe { TABLE.get_or_insert_with(&raw const b, || String::from("second")) };
+        assert_eq!(unsafe { value.as_ref() }, "second");
+        // An existing value is returned instead of being replaced
+        let existing = unsafe { TABLE.get_or_insert_with(&raw const b, || String::from("third")) };
+        assert_eq!(existing, value);
+        assert_eq!(unsafe { existing.as_ref() }, "second");
+        TABLE.remove(&raw const a);
+        assert!(unsafe { TABLE.get::<_, String>(&raw const a) }.is_none());
+        TABLE.remove(&raw const b);
+        Ok(())
+    }
+}
@@ -7,7 +7,7 @@ use allocator_api2::{
     vec::Vec as AVec
 };
 use bitflags::bitflags;
-use byteorder::{ByteOrder, LittleEndian, BigEndian, ReadBytesExt, NativeEndian};
+use byteorder::{ByteOrder, LittleEndian, BigEndian, ReadBytesExt, WriteBytesExt, NativeEndian};
 use half::f16;
 use std::{
     alloc::Layout,
@@ -26,7 +26,7 @@ use std::ops::{Deref, DerefMut};
 use rkyv::rancor::OptionExt;
 use crate::kernel::{
     allocator::GfdAllocator,
-    version::GfdVersion
+    version::{ GameProfile, GfdVersion }
 };
 use crate::kernel::chip::ChipAllocator;
 
@@ -45,6 +45,29 @@ pub type ReverseEndian = BigEndian;
 #[cfg(target_endian = "big")]
 pub type ReverseEndian = LittleEndian;
 
+/// Byte order of a GFS file, decided by how the magic is stored in the file header. Files are
+/// usually big endian, but every primitive read or written through a stream follows the header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+    Little,
+    Big
+}
+
+impl Endianness {
+    pub const fn native() -> Self {
+        match cfg!(target_endian = "little") {
+            true => Self::Little,
+            false => Self::Big
+        }
+    }
+    pub const fn reverse(self) -> Self {
+        match self {
+            Self::Little => Self::Big,
+            Self::Big => Self::Little
+        }
+    }
+}
+
 type SizeType = u32;
 
 const GFS0_MAGIC: u32 = 0x47465330; // GFS0
@@ -140,9 +163,20 @@ impl<T, A> DeserializationHeap<T, A>
 where T: Sized,
       A: Allocator + Clone
 {
+    pub fn new(value: T, allocator: A) -> Self {
+        Self {
+            instance: ABox::new_in(value, allocator.clone()),
+            allocator
+        }
+    }
+
     pub fn into_raw(self) -> NonNull<T> {
         unsafe { NonNull::new_unchecked(ABox::into_raw(self.instance)) }
     }
+
+    pub fn into_box(self) -> ABox<T, A> {
+        self.instance
+    }
 }
 
 pub trait GfdSerializationUserData<A>
@@ -180,9 +214,7 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
       Self: Sized
 {
     fn stream_read(stream: &mut Stream<AStream, T>, param: &mut P) -> Result<D, Box<dyn Error>>;
-    fn stream_write(&self, stream: &mut Stream<AStream, T>) -> Result<(), Box<dyn Error>> {
-        todo!()
-    }
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, param: &mut P) -> Result<(), Box<dyn Error>>;
 }
 
 #[repr(u32)]
@@ -259,6 +291,17 @@ impl StreamHeader {
     pub(crate) fn is_endian_reversed_static(magic: u32) -> bool {
         magic == GFS0_MAGIC_REVERSE
     }
+    /// Get the byte order that the rest of the file is stored in.
+    pub fn get_endianness(&self) -> Endianness {
+        match self.is_endian_reversed() {
+            false => Endianness::native(),
+            true => Endianness::native().reverse()
+        }
+    }
+    /// Guess which game the file was made for from its version and platform.
+    pub fn get_game_profile(&self) -> GameProfile {
+        GameProfile::detect(self.version, self.platform)
+    }
 }
 
 impl Default for StreamHeader {
@@ -279,6 +322,31 @@ impl Debug for StreamHeader {
 }
 
 impl StreamHeader {
+    /// Create a header for a new file. GFS files are big endian, so the magic is stored in the
+    /// byte order that reads back as "GFS0" on disk.
+    pub fn new(version: u32, platform: DevicePlatform) -> Self {
+        Self::new_with_endianness(version, platform, Endianness::Big)
+    }
+    /// Create a header for a new file that's stored in the given byte order.
+    pub fn new_with_endianness(version: u32, platform: DevicePlatform, endian: Endianness) -> Self {
+        let magic = match endian {
+            Endianness::Little => GFS0_MAGIC.to_le_bytes(),
+            Endianness::Big => GFS0_MAGIC.to_be_bytes()
+        };
+        Self {
+            magic: u32::from_ne_bytes(magic),
+            version,
+            platform,
+            _reserve: 0
+        }
+    }
+    pub fn get_version(&self) -> u32 {
+        self.version
+    }
+    pub fn get_platform(&self) -> DevicePlatform {
+        self.platform
+    }
+
     pub fn read<R>(reader: &mut R) -> Result<Self, Box<dyn Error>>
     where R: Read + Seek {
         let magic = reader.read_u32::<NativeEndian>()?;
@@ -303,6 +371,24 @@ impl StreamHeader {
             _reserve: 0
         })
     }
+
+    pub fn write<W>(&self, writer: &mut W) -> Result<(), Box<dyn Error>>
+    where W: Write + Seek {
+        writer.write_u32::<NativeEndian>(self.magic)?;
+        match self.is_endian_reversed() {
+            false => {
+                writer.write_u32::<NativeEndian>(self.version)?;
+                writer.write_u32::<NativeEndian>(self.platform as u32)?;
+                writer.write_u32::<NativeEndian>(0)?;
+            },
+            true => {
+                writer.write_u32::<ReverseEndian>(self.version)?;
+                writer.write_u32::<ReverseEndian>(self.platform as u32)?;
+                writer.write_u32::<ReverseEndian>(0)?;
+            }
+        };
+        Ok(())
+    }
 }
 
 #[repr(u32)]
@@ -315,18 +401,22 @@ pub enum ChunkType {
     MaterialDictionary = 0x000100FB,
     TextureDictionary  = 0x000100FC,
     AnimationPack      = 0x000100FD,
-    ChunkType000100FE  = 0x000100FE
+    ChunkType000100FE  = 0x000100FE,
+    /// Chunk ID that OpenGFD doesn't recognize. The original ID is kept in the ChunkHeader.
+    Unknown            = 0xFFFFFFFF
 }
 
 #[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq)]
 pub enum ChunkError {
-    UnknownChunkType(u32)
+    UnknownChunkType(u32),
+    InvalidLength(ChunkType, u32)
 }
 impl Error for ChunkError {}
 impl Display for ChunkError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::UnknownChunkType(c) => write!(f, "Unknown chunk type 0x{:x}", *c),
+            Self::InvalidLength(c, l) => write!(f, "Chunk {:?} has an invalid length 0x{:x}", *c, *l),
             _ => write!(f, "ChunkError: {:?}", self)
         }
     }
@@ -354,7 +444,8 @@ impl TryFrom<u32> for ChunkType {
 #[derive(Clone)]
 pub struct ChunkHeader {
     version: u32,
-    chunk_id: ChunkType,
+    // Stored as the raw ID so that chunks from newer versions can be preserved
+    chunk_id: u32,
     length: u32,
     #[allow(dead_code)]
     _reserve: u32
@@ -364,7 +455,7 @@ impl Default for ChunkHeader {
     fn default() -> Self {
         Self {
             version: GfdVersion::current() as u32,
-            chunk_id: ChunkType::EOF,
+            chunk_id: ChunkType::EOF as u32,
             length: 0,
             _reserve: 0
         }
@@ -372,15 +463,104 @@ impl Default for ChunkHeader {
 }
 
 impl ChunkHeader {
+    pub fn new(version: u32, chunk_id: ChunkType, length: u32) -> Self {
+        Self { version, chunk_id: chunk_id as u32, length, _reserve: 0 }
+    }
     pub fn get_version(&self) -> u32 {
         self.version
     }
+    /// Get the chunk type, or ChunkType::Unknown if the ID isn't recognized
     pub fn get_chunk_id(&self) -> ChunkType {
+        self.chunk_id.try_into().unwrap_or(ChunkType::Unknown)
+    }
+    pub fn get_raw_chunk_id(&self) -> u32 {
         self.chunk_id
     }
     pub fn get_length(&self) -> u32 {
         self.length
     }
+    /// Get the length of the chunk's contents, excluding the chunk header
+    pub fn get_body_length(&self) -> usize {
+        (self.length as usize).saturating_sub(size_of::<Self>())
+    }
+}
+
+/// Serialization parameters for chunks which need to know how long they are (e.g to keep data
+/// that isn't parsed yet)
+pub struct ChunkSerializationContext<A>
+where A: Allocator + Clone {
+    length: usize,
+    _allocator: A
+}
+
+impl<A> ChunkSerializationContext<A>
+where A: Allocator + Clone {
+    pub fn new(_allocator: A, length: usize) -> Self {
+        Self { length, _allocator }
+    }
+    pub fn get_length(&self) -> usize {
+        self.length
+    }
+}
+
+impl<A> GfdSerializationUserData<A> for ChunkSerializationContext<A>
+where A: Allocator + Clone {
+    fn get_heap_allocator(&self) -> Option<A> {
+        Some(self._allocator.clone())
+    }
+}
+
+/// A chunk that's kept as-is, either because OpenGFD doesn't know how to parse it or because it
+/// comes from a newer version of GFD. The chunk is written back out with it's original header.
+pub struct RawChunk<A = GfdAllocator>
+where A: Allocator + Clone {
+    header: ChunkHeader,
+    data: AVec<u8, A>
+}
+
+impl<A> Debug for RawChunk<A>
+where A: Allocator + Clone {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RawChunk {{ header: {:?}, data: 0x{:x} bytes }}", self.header, self.data.len())
+    }
+}
+
+impl<A> RawChunk<A>
+where A: Allocator + Clone {
+    pub fn new(header: ChunkHeader, data: AVec<u8, A>) -> Self {
+        Self { header, data }
+    }
+    pub fn get_header(&self) -> &ChunkHeader {
+        &self.header
+    }
+    pub fn get_data(&self) -> &[u8] {
+        self.data.as_slice()
+    }
+}
+
+impl<AStream, AObject, T> GfdSerialize<AStream, T, AObject, DeserializationHeap<Self, AObject>, SerializationSingleAllocator<AObject>> for RawChunk<AObject>
+where T: Debug + Read + Write + Seek + StreamIODevice,
+      AStream: Allocator + Clone + Debug,
+      AObject: Allocator + Clone
+{
+    fn stream_read(stream: &mut Stream<AStream, T>, param: &mut SerializationSingleAllocator<AObject>) -> Result<DeserializationHeap<Self, AObject>, Box<dyn Error>> {
+        let allocator = param.get_heap_allocator().unwrap();
+        let header = ChunkHeader::stream_read(stream, &mut ())?.into_raw();
+        let mut data = AVec::with_capacity_in(header.get_body_length(), allocator.clone());
+        data.resize(header.get_body_length(), 0);
+        stream.read_u8_slice(data.as_mut_slice())?;
+        Ok(DeserializationHeap::new(Self { header, data }, allocator))
+    }
+
+    fn stream_write(&self, stream: &mut Stream<AStream, T>, _: &mut SerializationSingleAllocator<AObject>) -> Result<(), Box<dyn Error>> {
+        let header = ChunkHeader {
+            length: (size_of::<ChunkHeader>() + self.data.len()) as u32,
+            ..self.header.clone()
+        };
+        header.stream_write(stream, &mut ())?;
+        stream.write_u8_slice(self.data.as_slice())?;
+        Ok(())
+    }
 }
 
 impl<A, T> GfdSerialize<A, T> for ChunkHeader
@@ -389,16 +569,24 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
 {
     fn stream_read(stream: &mut Stream<A, T>, _: &mut ()) -> Result<DeserializationStack<Self>, Box<dyn Error>> {
         let version = stream.read_u32()?;
-        let chunk_id = stream.read_u32()?.try_into()?;
+        let chunk_id = stream.read_u32()?;
         let length = stream.read_u32()?;
         stream.seek(SeekFrom::Current(size_of::<u32>() as i64))?;
         Ok(Self { version, chunk_id, length, _reserve: 0 }.into())
     }
+
+    fn stream_write(&self, stream: &mut Stream<A, T>, _: &mut ()) -> Result<(), Box<dyn Error>> {
+        stream.write_u32(self.version)?;
+        stream.write_u32(self.chunk_id)?;
+        stream.write_u32(self.length)?;
+        stream.write_u32(0)?;
+        Ok(())
+    }
 }
 
 impl Debug for ChunkHeader {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "ChunkHeader {{ version: 0x{:x}, chunk_id: {:?}, length: 0x{:x} }}", self.version, self.chunk_id, self.length)
+        write!(f, "ChunkHeader {{ version: 0x{:x}, chunk_id: {:?} (0x{:x}), length: 0x{:x} }}", self.version, self.get_chunk_id(), self.chunk_id, self.length)
     }
 }
 
@@ -413,6 +601,7 @@ where T: Debug + Read + Write + Seek,
     stream_type: StreamType,
     access_type: AccessType,
     device: T,
+    profile: GameProfile,
     _allocator: A,
     // _endian: PhantomData<E>
 }
@@ -430,6 +619,22 @@ where A: Allocator + Clone + Debug {
         let mut device = StreamMemory::new_reallocate(buf, self.0.clone());
         let header = StreamHeader::read(&mut device)?;
         Ok(Stream {
+            profile: header.get_game_profile(),
+            header,
+            stream_type: StreamType::MemoryStream,
+            access_type: AccessType::Write,
+            device,
+            _allocator: self.0.clone()
+        })
+    }
+
+    /// Create an empty memory stream with the file header already written out. The header's
+    /// version determines which version-gated fields get written by stream_write.
+    pub fn write_to_memory(&self, header: StreamHeader) -> Result<Stream<A, StreamMemory<A>>, Box<dyn Error>> {
+        let mut device = StreamMemory::new_blank(self.0.clone());
+        header.write(&mut device)?;
+        Ok(Stream {
+            profile: header.get_game_profile(),
             header,
             stream_type: StreamType::MemoryStream,
             access_type: AccessType::Write,
@@ -457,6 +662,34 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         self.header.version
     }
 
+    pub fn get_header_platform(&self) -> DevicePlatform {
+        self.header.platform
+    }
+
+    pub fn get_endianness(&self) -> Endianness {
+        self.header.get_endianness()
+    }
+
+    /// Get the game that the file is being read from or written for. This is detected from the
+    /// header when the stream is created.
+    pub fn get_game_profile(&self) -> GameProfile {
+        self.profile
+    }
+
+    /// Override the detected game for files that can't be told apart by their header.
+    pub fn set_game_profile(&mut self, profile: GameProfile) {
+        self.profile = profile;
+    }
+
+    /// Get the number of bytes between the current position and the end of the stream. Used to
+    /// check lengths read from the file before allocating space for them.
+    pub fn get_remaining(&mut self) -> io::Result<u64> {
+        let position = self.device.seek(SeekFrom::Current(0))?;
+        let end = self.device.seek(SeekFrom::End(0))?;
+        self.device.seek(SeekFrom::Start(position))?;
+        Ok(end.saturating_sub(position))
+    }
+
     pub fn has_feature(&self, feature: GfdVersion) -> Option<()> {
         match self.get_header_version() >= feature as u32 {
             true => Some(()),
@@ -486,6 +719,38 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         Ok(out)
     }
 
+    pub fn read_i8(&mut self) -> io::Result<i8> {
+        self.device.read_i8()
+    }
+
+    pub fn read_i16(&mut self) -> io::Result<i16> {
+        Ok(match self.header.is_endian_reversed() {
+            false => self.device.read_i16::<NativeEndian>()?,
+            true => self.device.read_i16::<ReverseEndian>()?,
+        })
+    }
+
+    pub fn read_i16_slice(&mut self, buf: &mut [i16]) -> io::Result<()> {
+        Ok(match self.header.is_endian_reversed() {
+            false => self.device.read_i16_into::<NativeEndian>(buf)?,
+            true => self.device.read_i16_into::<ReverseEndian>(buf)?,
+        })
+    }
+
+    pub fn read_i32(&mut self) -> io::Result<i32> {
+        Ok(match self.header.is_endian_reversed() {
+            false => self.device.read_i32::<NativeEndian>()?,
+            true => self.device.read_i32::<ReverseEndian>()?,
+        })
+    }
+
+    pub fn read_i32_slice(&mut self, buf: &mut [i32]) -> io::Result<()> {
+        Ok(match self.header.is_endian_reversed() {
+            false => self.device.read_i32_into::<NativeEndian>(buf)?,
+            true => self.device.read_i32_into::<ReverseEndian>(buf)?,
+        })
+    }
+
     pub fn read_u16(&mut self) -> io::Result<u16> {
         Ok(match self.header.is_endian_reversed() {
             false => self.device.read_u16::<NativeEndian>()?,
@@ -528,6 +793,17 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
         })
     }
 
+    pub fn read_f16(&mut self) -> io::Result<f16> {
+        Ok(f16::from_bits(self.read_u16()?))
+    }
+
+    pub fn read_f16_slice(&mut self, buf: &mut [f16]) -> io::Result<()> {
+        for v in buf {
+            *v = self.read_f16()?;
+        }
+        Ok(())
+    }
+
     pub fn read_f32(&mut self) -> io::Result<f32> {
         Ok(match self.header.is_endian_reversed() {
             false => self.device.read_f32::<NativeEndian>()?,
@@ -555,6 +831,150 @@ where T: Debug + Read + Write + Seek + StreamIODevice,
             true => self.device.read_f64_into::<ReverseEndian>(buf)?,
         })
     }
+
+    pub fn write_u8(&mut self, value: u8) -> io::Result<()> {
+        self.device.write_u8(value)
+    }
+
+    pub fn write_u8_slice(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.device.write_all(buf)
+    }
+
+    pub fn write_i8(&mut self, value: i8) -> io::Result<()> {
+        self.device.write_i8(value)
+    }
+
+    pub fn write_i16(&mut self, value: i16) -> io::Result<()> {
+        match self.header.is_endian_reversed() {
+            false => self.device.write_i16::<NativeEndian>(value),
+            true => self.device.write_i16::<ReverseEndian>(value),
+        }
+    }
+
+    pub fn write_i16_slice(&mut self, buf: &[i16]) -> io::Result<()> {
+        for v in buf {
+            self.write_i16(*v)?;
+        }
+        Ok(())
+    }
+
+    pub fn write_i32(&mut self, value: i32) -> io::Result<()> {
+        match self.header.is_endian_reversed() {
+            false => self.device.write_i32::<NativeEndian>(value),
+            true => self.device.write_i32::<ReverseEndian>(value),
+        }
+    }
+
+    pub fn write_i32_slice(&mut self, buf: &[i32]) -> io::Result<()> {
+        for v in buf {
+            self.write_i32(*v)?;
+        }
+        Ok(())
+    }
+
+    pub fn write_u16(&mut self, value: u16) -> io::Result<()> {
+        match self.header.is_endian_reversed() {
+            false => self.device.write_u16::<NativeEndian>(value),
+            true => self.device.write_u16::<ReverseEndian>(value),
+        }
+    }
+
+    pub fn write_u16_slice(&mut self, buf: &[u16]) -> io::Result<()> {
+        for v in buf {
+            self.write_u16(*v)?;
+        }
+        Ok(())
+    }
+
+    pub fn write_u32(&mut self, value: u32) -> io::Result<()> {
+        match self.header.is_endian_reversed() {
+            false => self.device.write_u32::<NativeEndian>(value),
+            true => self.device.write_u32::<ReverseEndian>(value),
+        }
+    }
+
+    pub fn write_u32_slice(&mut self, buf: &[u32]) -> io::Result<()> {
+        for v in buf {
+            self.write_u32(*v)?;
+        }
+        Ok(())
+    }
+
+    pub fn write_u64(&mut self, value: u64) -> io::Result<()> {
+        match self.header.is_endian_reversed() {
+            false => self.device.write_u64::<NativeEndian>(value),
+            true => self.device.write_u64::<ReverseEndian>(value),
+        }
+    }
+
+    pub fn write_u64_slice(&mut self, buf: &[u64]) -> io::Result<()> {
+        for v in buf {
+            self.write_u64(*v)?;
+        }
+        Ok(())
+    }
+
+    pub fn write_f16(&mut self, value: f16) -> io::Result<()> {
+        self.write_u16(value.to_bits())
+    }
+
+    pub fn write_f16_slice(&mut self, buf: &[f16]) -> io::Result<()> {
+        for v in buf {
+            self.write_f16(*v)?;
+        }
+        Ok(())
+    }
+
+    pub fn write_f32(&mut self, value: f32) -> io::Result<()> {
+        match self.header.is_endian_reversed() {
+            false => self.device.write_f32::<NativeEndian>(value),
+            true => self.device.write_f32::<ReverseEndian>(value),
+        }
+    }
+
+    pub fn write_f32_slice(&mut self, buf: &[f32]) -> io::Result<()> {
+        for v in buf {
+            self.write_f32(*v)?;
+        }
+        Ok(())
+    }
+
+    pub fn write_f64(&mut self, value: f64) -> io::Result<()> {
+        match self.header.is_endian_reversed() {
+            false => self.device.write_f64::<NativeEndian>(value),
+            true => self.device.write_f64::<ReverseEndian>(value),
+        }
+    }
+
+    pub fn write_f64_slice(&mut self, buf: &[f64]) -> io::Result<()> {
+        for v in buf {
+            self.write_f64(*v)?;
+        }
+        Ok(())
+    }
+
+    /// Write a chunk header followed by the chunk's contents, then go back and fill in the
+    /// chunk's length once it's known. The length includes the size of the chunk header.
+    pub fn write_chunk<F>(&mut self, chunk_id: ChunkType, version: u32, f: F) -> Result<(), Box<dyn Error>>
+    where F: FnOnce(&mut Self) -> Result<(), Box<dyn Error>> {
+        let start = self.device.stream_position()?;
+        ChunkHeader::new(version, chunk_id, 0).stream_write(self, &mut ())?;
+        f(self)?;
+        let end = self.device.stream_position()?;
+        self.device.seek(SeekFrom::Start(start + size_of::<u32>() as u64 * 2))?;
+        self.write_u32((end - start) as u32)?;
+        self.device.seek(SeekFrom::Start(end))?;
+        Ok(())
+    }
+}
+
+impl<A> Stream<A, StreamMemory<A>>
+where A: Allocator + Clone + Debug
+{
+    /// Get the contents written to the stream so far, including the file header.
+    pub fn as_slice(&self) -> &[u8] {
+        self.device.as_slice()
+    }
 }
 
 impl<A, T> Seek for Stream<A, T>
@@ -632,6 +1052,7 @@ pub struct StreamMemory<A = GfdAllocator>
 where A: Allocator + Clone {
     position: SizeType,
     size: SizeType,
+    capacity: SizeType,
     buffer: Option<NonNull<u8>>,
     _allocator: A
 }
@@ -652,6 +1073,7 @@ where A: Allocator + Clone {
                 Self {
                     position: 0,
                     size: i as SizeType,
+                    capacity: i as SizeType,
                     buffer: Some(buffer),
                     _allocator: allocator
                 }
@@ -663,11 +1085,12 @@ where A: Allocator + Clone {
         match buf.len() {
             0 => Self::new_blank(buf.allocator().clone()),
             _ => {
-                let (buffer, _, size, _allocator) = buf.into_raw_parts_with_alloc();
+                let (buffer, size, capacity, _allocator) = buf.into_raw_parts_with_alloc();
                 let buffer = NonNull::new(buffer);
                 Self {
                     position: 0,
                     size: size as SizeType,
+                    capacity: capacity as SizeType,
                     buffer,
                     _allocator
                 }
@@ -679,10 +1102,18 @@ where A: Allocator + Clone {
         Self {
             position: 0,
             size: 0,
+            capacity: 0,
             buffer: None,
             _allocator: allocator
         }
     }
+
+    pub fn as_slice(&self) -> &[u8] {
+        match self.buffer {
+            Some(b) => unsafe { std::slice::from_raw_parts(b.as_ptr(), self.size as usize) },
+            None => &[]
+        }
+    }
 }
 
 impl<A> StreamIODevice for StreamMemory<A>
@@ -704,7 +1135,7 @@ impl<A> Drop for StreamMemory<A>
 where A: Allocator + Clone {
     fn drop(&mut self) {
         if let Some(buf) = self.buffer {
-            let layout = Self::get_layout(self.size as usize);
+            let layout = Self::get_layout(self.capacity as usize);
             unsafe { self._allocator.deallocate(buf, layout) };
         }
     }
@@ -768,14 +1199,19 @@ where A: Allocator + Clone
         if let Some(b) = self.buffer {
             unsafe {
                 std::ptr::copy_nonoverlapping(b.as_ptr(), new.as_ptr(), self.size as usize);
-                self._allocator.deallocate(b, Self::get_layout(self.size as usize));
+                self._allocator.deallocate(b, Self::get_layout(self.capacity as usize));
             }
         }
         self.buffer = Some(new);
+        self.capacity = new_size as SizeType;
         Ok(())
     }
-    fn resize_auto(&mut self) -> io::Result<()> {
-        self.resize_to(self.buffer.map_or(0x100, |_| self.size as usize * 2))
+    fn resize_auto(&mut self, min_size: usize) -> io::Result<()> {
+        let mut new_size = self.buffer.map_or(0x100, |_| self.capacity as usize * 2);
+        while new_size < min_size {
+            new_size *= 2;
+        }
+        self.resize_to(new_size)
     }
 }
 
@@ -784,11 +1220,13 @@ where A: Allocator + Clone
 {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         // Reallocate
-        if self.position as usize + buf.len() > self.size as usize {
-            self.resize_auto()?;
+        let end = self.position as usize + buf.len();
+        if end > self.capacity as usize {
+            self.resize_auto(end)?;
         }
         unsafe { std::ptr::copy(buf.as_ptr(), self.buffer.unwrap().as_ptr().add(self.position as usize), buf.len()) };
         self.position += buf.len() as SizeType;
+        self.size = self.size.max(self.position);
         Ok(buf.len())
     }
     fn flush(&mut self) -> io::Result<()> {
@@ -801,7 +1239,7 @@ where A: Allocator + Clone
 pub struct StreamFile<A = GfdAllocator>
 where A: Allocator + Clone {
     fd: i32, // Unix file descriptor (see https://doc.rust-lang.org/std/os/fd/struct.OwnedFd.html)
-    _padding: [u8; 0xc], // make Stream<StreamFile> the same size as Stream<StreamMemory>
+    _padding: [u8; 0x14], // make Stream<StreamFile> the same size as Stream<StreamMemory>
     _allocator: PhantomData<A>
 }
 
@@ -810,5 +1248,110 @@ pub mod ffi {
 }
 #[cfg(test)]
 pub mod tests {
+    use allocator_api2::alloc::Global;
+    use std::error::Error;
+    use half::f16;
+    use super::{ ChunkHeader, ChunkType, DevicePlatform, Endianness, GameProfile, GfdSerialize, RawChunk, SerializationSingleAllocator, StreamFactory, StreamHeader };
+
+    type TestReturn = Result<(), Box<dyn Error>>;
+
+    #[test]
+    fn write_chunk_round_trip() -> TestReturn {
+        let factory = StreamFactory::new(Global);
+        let mut stream = factory.write_to_memory(StreamHeader::new(0x2110000, DevicePlatform::METAPHOR))?;
+        stream.write_chunk(ChunkType::MaterialDictionary, 0x2110000, |s| {
+            s.write_u32(0xdeadbeef)?;
+            Ok(())
+        })?;
+        let mut stream = factory.read_from_memory(stream.as_slice())?;
+        assert_eq!(stream.get_header_version(), 0x2110000);
+        let chunk = ChunkHeader::stream_read(&mut stream, &mut ())?.into_raw();
+        assert_eq!(chunk.get_chunk_id(), ChunkType::MaterialDictionary);
+        assert_eq!(chunk.get_length(), 0x14);
+        assert_eq!(stream.read_u32()?, 0xdeadbeef);
+        Ok(())
+    }
+    #[test]
+    fn raw_chunk_round_trip() -> TestReturn {
+        let factory = StreamFactory::new(Global);
+        let mut stream = factory.write_to_memory(StreamHeader::new(0x2110000, DevicePlatform::METAPHOR))?;
+        stream.write_u32_slice(&[0x2110000, 0x000100FA, 0x18, 0])?;
+        stream.write_u32_slice(&[0x12345678, 0x9abcdef0])?;
+        let written = stream.as_slice().to_vec();
+        let mut stream = factory.read_from_memory(&written)?;
+        let mut param = SerializationSingleAllocator::new(Global);
+        let chunk = RawChunk::<Global>::stream_read(&mut stream, &mut param)?.into_raw();
+        let chunk = unsafe { chunk.as_ref() };
+        assert_eq!(chunk.get_header().get_chunk_id(), ChunkType::Unknown);
+        assert_eq!(chunk.get_header().get_raw_chunk_id(), 0x000100FA);
+        let mut out = factory.write_to_memory(StreamHeader::new(0x2110000, DevicePlatform::METAPHOR))?;
+        chunk.stream_write(&mut out, &mut param)?;
+        assert_eq!(out.as_slice(), written.as_slice());
+        Ok(())
+    }
 
+    #[test]
+    fn read_endianness_from_magic() -> TestReturn {
+        let factory = StreamFactory::new(Global);
+        // PS3 files store everything big endian
+        let big = [
+            b'G', b'F', b'S', b'0', 0x01, 0x10, 0x51, 0x00, 0, 0, 0, 2, 0, 0, 0, 0,
+            0x12, 0x34, 0xde, 0xad, 0xbe, 0xef, 0x3f, 0x80, 0x00, 0x00, 0x3c, 0x00
+        ];
+        let mut stream = factory.read_from_memory(&big)?;
+        assert_eq!(stream.get_endianness(), Endianness::Big);
+        assert_eq!(stream.get_header_version(), 0x1105100);
+        assert_eq!(stream.get_header_platform(), DevicePlatform::P5_PS3);
+        assert_eq!(stream.read_u16()?, 0x1234);
+        assert_eq!(stream.read_u32()?, 0xdeadbeef);
+        assert_eq!(stream.read_f32()?, 1.0);
+        assert_eq!(stream.read_f16()?, f16::ONE);
+        let little = [
+            b'0', b'S', b'F', b'G', 0x00, 0x51, 0x10, 0x01, 4, 0, 0, 0, 0, 0, 0, 0,
+            0x34, 0x12, 0xef, 0xbe, 0xad, 0xde, 0x00, 0x00, 0x80, 0x3f, 0x00, 0x3c
+        ];
+        let mut stream = factory.read_from_memory(&little)?;
+        assert_eq!(stream.get_endianness(), Endianness::Little);
+        assert_eq!(stream.get_header_version(), 0x1105100);
+        assert_eq!(stream.get_header_platform(), DevicePlatform::PDANCE_VITA);
+        assert_eq!(stream.read_u16()?, 0x1234);
+        assert_eq!(stream.read_u32()?, 0xdeadbeef);
+        assert_eq!(stream.read_f32()?, 1.0);
+        assert_eq!(stream.read_f16()?, f16::ONE);
+        Ok(())
+    }
+    #[test]
+    fn write_little_endian_round_trip() -> TestReturn {
+        let factory = StreamFactory::new(Global);
+        let header = StreamHeader::new_with_endianness(0x1105100, DevicePlatform::PDANCE_VITA, Endianness::Little);
+        let mut stream = factory.write_to_memory(header)?;
+        stream.write_i16(-2)?;
+        stream.write_u32_slice(&[1, 2])?;
+        assert_eq!(&stream.as_slice()[..4], b"0SFG");
+        assert_eq!(&stream.as_slice()[16..], &[0xfe, 0xff, 1, 0, 0, 0, 2, 0, 0, 0]);
+        let mut stream = factory.read_from_memory(stream.as_slice())?;
+        assert_eq!(stream.get_endianness(), Endianness::Little);
+        assert_eq!(stream.read_i16()?, -2);
+        let mut values = [0u32; 2];
+        stream.read_u32_slice(&mut values)?;
+        assert_eq!(values, [1, 2]);
+        Ok(())
+    }
+    #[test]
+    fn detect_game_profile() -> TestReturn {
+        let factory = StreamFactory::new(Global);
+        let p5r = factory.write_to_memory(StreamHeader::new(0x1105100, DevicePlatform::P5R_DX11))?;
+        assert_eq!(p5r.get_game_profile(), GameProfile::Persona5Royal);
+        let p4d = factory.write_to_memory(StreamHeader::new(0x1105030, DevicePlatform::PDANCE_VITA))?;
+        assert_eq!(p4d.get_game_profile(), GameProfile::Persona4Dancing);
+        let cfb = factory.write_to_memory(StreamHeader::new(0x1105092, DevicePlatform::P5R_DX11))?;
+        assert_eq!(cfb.get_game_profile(), GameProfile::CatherineFullBody);
+        let demo = factory.write_to_memory(StreamHeader::new(0x2110213, DevicePlatform::METAPHOR))?;
+        assert_eq!(demo.get_game_profile(), GameProfile::MetaphorRefantazioDemo);
+        let mut metaphor = factory.write_to_memory(StreamHeader::new(0x2110221, DevicePlatform::METAPHOR))?;
+        assert_eq!(metaphor.get_game_profile(), GameProfile::MetaphorRefantazio);
+        metaphor.set_game_profile(GameProfile::MetaphorRefantazioDemo);
+        assert!(metaphor.get_game_profile().is_gfd_v2());
+        Ok(())
+    }
 }
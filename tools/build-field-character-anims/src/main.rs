@@ -1,3 +1,310 @@
-fn main() {
-    println!("Hello, world!");
+//! Builds field animation packs by collecting animations from existing GAP files.
+//!
+//! Usage: build-field-character-anims [manifest] [output GAP]
+//!
+//! Each line in the manifest adds one animation to the output pack, in order:
+//! `base [path to GAP] [animation index]` or `blend [path to GAP] [animation index]`. The pack's
+//! neck animations can be taken from one GAP with `neck [path to GAP]`.
+//! Empty lines and lines starting with `#` are ignored. The output file uses the same version and
+//! platform as the first GAP listed in the manifest, and every other GAP must match it.
+
+use allocator_api2::alloc::Global;
+use opengfd::{
+    anim::animation::{ Animation, AnimationNeck, AnimationPack },
+    object::mesh::MeshFile,
+    utility::stream::{
+        DevicePlatform,
+        GfdSerialize,
+        SerializationSingleAllocator,
+        StreamFactory,
+        StreamHeader
+    }
+};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::{ Display, Formatter },
+    path::{ Path, PathBuf },
+    ptr::NonNull
+};
+
+#[derive(Debug)]
+enum BuildError {
+    Usage,
+    InvalidManifestLine(usize, String),
+    MissingAnimationPack(PathBuf),
+    MissingAnimation(PathBuf, usize),
+    MissingNeck(PathBuf),
+    DuplicateNeck(PathBuf),
+    MismatchedHeader(PathBuf, u32, DevicePlatform, u32, DevicePlatform),
+    EmptyManifest
+}
+impl Error for BuildError {}
+impl Display for BuildError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Usage => write!(f, "Usage: build-field-character-anims [manifest] [output GAP]"),
+            Self::InvalidManifestLine(l, s) => write!(f, "Invalid manifest entry on line {}: \"{}\"", l, s),
+            Self::MissingAnimationPack(p) => write!(f, "{} doesn't contain an animation pack", p.display()),
+            Self::MissingAnimation(p, i) => write!(f, "{} doesn't contain an animation at index {}", p.display(), i),
+            Self::MissingNeck(p) => write!(f, "{} doesn't contain neck animations", p.display()),
+            Self::DuplicateNeck(p) => write!(f, "Neck animations from {} were given, but the output already has neck animations", p.display()),
+            Self::MismatchedHeader(p, v, pl, ev, epl) => write!(f,
+                "{} has version {:x} ({:?}), but the output uses version {:x} ({:?}). Animations from different versions can't be mixed",
+                p.display(), v, pl, ev, epl),
+            Self::EmptyManifest => write!(f, "Manifest doesn't contain any animations")
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AnimSlot {
+    Base,
+    Blend,
+    Neck
+}
+
+struct ManifestEntry {
+    slot: AnimSlot,
+    path: PathBuf,
+    index: usize
+}
+
+fn parse_manifest(manifest: &Path) -> Result<Vec<ManifestEntry>, Box<dyn Error>> {
+    let base_dir = manifest.parent().unwrap_or(Path::new("."));
+    let mut entries = vec![];
+    for (i, line) in std::fs::read_to_string(manifest)?.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let invalid = || BuildError::InvalidManifestLine(i + 1, line.to_owned());
+        let mut parts = line.split_whitespace();
+        let slot = match parts.next() {
+            Some("base") => AnimSlot::Base,
+            Some("blend") => AnimSlot::Blend,
+            Some("neck") => AnimSlot::Neck,
+            _ => return Err(Box::new(invalid()))
+        };
+        let path = base_dir.join(parts.next().ok_or_else(invalid)?);
+        // A pack only has one set of neck animations, so neck entries don't take an index
+        let index = match slot {
+            AnimSlot::Neck => 0,
+            _ => parts.next().ok_or_else(invalid)?.parse::<usize>().map_err(|_| invalid())?
+        };
+        entries.push(ManifestEntry { slot, path, index });
+    }
+    Ok(entries)
+}
+
+struct SourcePack {
+    version: u32,
+    platform: DevicePlatform,
+    mesh: MeshFile<Global>
+}
+
+impl SourcePack {
+    fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let file = std::fs::read(path)?;
+        let factory = StreamFactory::new(Global);
+        let mut stream = factory.read_from_memory(&file)?;
+        let mesh = MeshFile::<Global>::stream_read(&mut stream, &mut SerializationSingleAllocator::new(Global))?.into_raw();
+        Ok(Self {
+            version: stream.get_header_version(),
+            platform: stream.get_header_platform(),
+            mesh
+        })
+    }
+
+    fn get_animation_pack(&self, entry: &ManifestEntry) -> Result<&AnimationPack<Global>, BuildError> {
+        self.mesh.get_animation_pack().ok_or_else(|| BuildError::MissingAnimationPack(entry.path.clone()))
+    }
+
+    fn get_animation(&self, entry: &ManifestEntry) -> Result<NonNull<Animation<Global>>, Box<dyn Error>> {
+        let pack = self.get_animation_pack(entry)?;
+        let animations = match entry.slot {
+            AnimSlot::Blend => pack.get_blend_animations(),
+            _ => pack.get_animations()
+        };
+        Ok(*animations.get(entry.index)
+            .ok_or_else(|| BuildError::MissingAnimation(entry.path.clone(), entry.index))?)
+    }
+
+    fn get_neck(&self, entry: &ManifestEntry) -> Result<NonNull<AnimationNeck<Global>>, Box<dyn Error>> {
+        Ok(self.get_animation_pack(entry)?.get_neck().map(NonNull::from)
+            .ok_or_else(|| BuildError::MissingNeck(entry.path.clone()))?)
+    }
+
+    fn has_neck(&self) -> bool {
+        self.mesh.get_animation_pack().is_some_and(|v| v.get_neck().is_some())
+    }
+}
+
+/// What was written to the output pack
+struct BuildSummary {
+    base_count: usize,
+    blend_count: usize,
+    has_neck: bool,
+    /// Source packs with neck animations that weren't copied to the output
+    unused_necks: Vec<PathBuf>
+}
+
+fn build(manifest: &Path, output: &Path) -> Result<BuildSummary, Box<dyn Error>> {
+    let entries = parse_manifest(manifest)?;
+    let mut sources: HashMap<PathBuf, SourcePack> = HashMap::new();
+    // The animations are still owned by their source packs, and the same animation can appear
+    // more than once in the manifest, so the output pack must not free them.
+    let mut pack = std::mem::ManuallyDrop::new(AnimationPack::new(Global));
+    let mut header = None;
+    for entry in &entries {
+        if !sources.contains_key(&entry.path) {
+            sources.insert(entry.path.clone(), SourcePack::load(&entry.path)?);
+        }
+        let source = sources.get(&entry.path).unwrap();
+        match header {
+            None => header = Some((source.version, source.platform)),
+            Some((version, platform)) => if version != source.version || platform != source.platform {
+                return Err(Box::new(BuildError::MismatchedHeader(
+                    entry.path.clone(), source.version, source.platform, version, platform)));
+            }
+        };
+        match entry.slot {
+            AnimSlot::Base => pack.push_animation(source.get_animation(entry)?)?,
+            AnimSlot::Blend => pack.push_blend_animation(source.get_animation(entry)?)?,
+            // Replacing the neck would free the previous one, which belongs to its source pack
+            AnimSlot::Neck => match pack.get_neck() {
+                Some(_) => return Err(Box::new(BuildError::DuplicateNeck(entry.path.clone()))),
+                None => pack.set_neck(Some(source.get_neck(entry)?))
+            }
+        };
+    }
+    let (version, platform) = header.ok_or(BuildError::EmptyManifest)?;
+    let mut stream = StreamFactory::new(Global).write_to_memory(StreamHeader::new(version, platform))?;
+    pack.stream_write_gap(&mut stream, &mut SerializationSingleAllocator::new(Global))?;
+    std::fs::write(output, stream.as_slice())?;
+    let neck_source = entries.iter().find(|v| v.slot == AnimSlot::Neck).map(|v| &v.path);
+    let mut unused_necks: Vec<PathBuf> = sources.iter()
+        .filter(|(path, source)| source.has_neck() && Some(*path) != neck_source)
+        .map(|(path, _)| path.clone())
+        .collect();
+    unused_necks.sort();
+    Ok(BuildSummary {
+        base_count: pack.get_animations().len(),
+        blend_count: pack.get_blend_animations().len(),
+        has_neck: pack.get_neck().is_some(),
+        unused_necks
+    })
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 3 {
+        return Err(Box::new(BuildError::Usage));
+    }
+    let output = Path::new(&args[2]);
+    let summary = build(Path::new(&args[1]), output)?;
+    for path in &summary.unused_necks {
+        eprintln!("Warning: {} has neck animations that weren't copied. Add a \"neck\" entry to the manifest to keep them", path.display());
+    }
+    println!("Wrote {} base and {} blend animations{} to {}", summary.base_count, summary.blend_count,
+        if summary.has_neck { " with neck animations" } else { "" }, output.display());
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use allocator_api2::vec::Vec as AVec;
+    use super::*;
+
+    type TestReturn = Result<(), Box<dyn Error>>;
+
+    fn test_dir(name: &str) -> Result<PathBuf, Box<dyn Error>> {
+        let dir = std::env::temp_dir().join(format!("build-field-character-anims-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        Ok(dir)
+    }
+
+    fn write_gap(path: &Path, version: u32, durations: &[f32]) -> TestReturn {
+        let mut pack = AnimationPack::new(Global);
+        for duration in durations {
+            pack.push_animation(Animation::new_in(*duration, AVec::new_in(Global), Global))?;
+        }
+        let mut stream = StreamFactory::new(Global).write_to_memory(StreamHeader::new(version, DevicePlatform::METAPHOR))?;
+        pack.stream_write_gap(&mut stream, &mut SerializationSingleAllocator::new(Global))?;
+        std::fs::write(path, stream.as_slice())?;
+        Ok(())
+    }
+
+    #[test]
+    pub fn parse_manifest_entries() -> TestReturn {
+        let dir = test_dir("parse")?;
+        let manifest = dir.join("manifest.txt");
+        std::fs::write(&manifest, "# comment\n\nbase a.gap 0\nblend  b.gap 2\n")?;
+        let entries = parse_manifest(&manifest)?;
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].slot, AnimSlot::Base);
+        assert_eq!(entries[0].path, dir.join("a.gap"));
+        assert_eq!(entries[0].index, 0);
+        assert_eq!(entries[1].slot, AnimSlot::Blend);
+        assert_eq!(entries[1].path, dir.join("b.gap"));
+        assert_eq!(entries[1].index, 2);
+        std::fs::write(&manifest, "neck c.gap\n")?;
+        let entries = parse_manifest(&manifest)?;
+        assert_eq!(entries[0].slot, AnimSlot::Neck);
+        assert_eq!(entries[0].path, dir.join("c.gap"));
+        std::fs::write(&manifest, "base a.gap 0\nbase a.gap\n")?;
+        match parse_manifest(&manifest) {
+            Err(e) => assert!(matches!(e.downcast_ref::<BuildError>(), Some(BuildError::InvalidManifestLine(2, _)))),
+            Ok(_) => panic!("Manifest with a missing index should be rejected")
+        };
+        Ok(())
+    }
+
+    #[test]
+    pub fn build_collects_animations() -> TestReturn {
+        let dir = test_dir("collect")?;
+        write_gap(&dir.join("a.gap"), 0x2110000, &[1., 2.])?;
+        write_gap(&dir.join("b.gap"), 0x2110000, &[3.])?;
+        let manifest = dir.join("manifest.txt");
+        std::fs::write(&manifest, "base a.gap 1\nbase b.gap 0\nblend a.gap 0\nbase a.gap 1\n")?;
+        let output = dir.join("out.gap");
+        let summary = build(&manifest, &output)?;
+        assert_eq!((summary.base_count, summary.blend_count, summary.has_neck), (3, 1, false));
+        assert!(summary.unused_necks.is_empty());
+        let out = SourcePack::load(&output)?;
+        assert_eq!(out.version, 0x2110000);
+        let pack = out.mesh.get_animation_pack().unwrap();
+        let durations: Vec<f32> = pack.get_animations().iter().map(|v| unsafe { v.as_ref() }.get_duration()).collect();
+        assert_eq!(durations, [2., 3., 2.]);
+        assert_eq!(pack.get_blend_animations().len(), 1);
+        assert_eq!(unsafe { pack.get_blend_animations()[0].as_ref() }.get_duration(), 1.);
+        Ok(())
+    }
+
+    #[test]
+    pub fn build_rejects_mixed_versions() -> TestReturn {
+        let dir = test_dir("mixed")?;
+        write_gap(&dir.join("a.gap"), 0x2110000, &[1.])?;
+        write_gap(&dir.join("b.gap"), 0x2000000, &[1.])?;
+        let manifest = dir.join("manifest.txt");
+        std::fs::write(&manifest, "base a.gap 0\nbase b.gap 0\n")?;
+        match build(&manifest, &dir.join("out.gap")) {
+            Err(e) => assert!(matches!(e.downcast_ref::<BuildError>(), Some(BuildError::MismatchedHeader(_, 0x2000000, _, 0x2110000, _)))),
+            Ok(_) => panic!("GAP files with different versions should be rejected")
+        };
+        Ok(())
+    }
+
+    #[test]
+    pub fn build_rejects_missing_neck() -> TestReturn {
+        let dir = test_dir("neck")?;
+        write_gap(&dir.join("a.gap"), 0x2110000, &[1.])?;
+        let manifest = dir.join("manifest.txt");
+        std::fs::write(&manifest, "base a.gap 0\nneck a.gap\n")?;
+        match build(&manifest, &dir.join("out.gap")) {
+            Err(e) => assert!(matches!(e.downcast_ref::<BuildError>(), Some(BuildError::MissingNeck(_)))),
+            Ok(_) => panic!("Neck entry for a GAP without neck animations should be rejected")
+        };
+        Ok(())
+    }
 }
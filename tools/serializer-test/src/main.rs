@@ -7,21 +7,22 @@ use std::{
 };
 use std::fmt::Debug;
 use opengfd::{
-    kernel::allocator::GfdAllocator,
     utility::{
         item_array::ItemArray,
         stream::{ChunkHeader, StreamFactory, GfdSerialize}
     }
 };
-use opengfd::object::mesh::Mesh;
+use opengfd::object::mesh::MeshFile;
 use opengfd::utility::misc::RGBAFloat;
 use opengfd::utility::stream::SerializationSingleAllocator;
 
 fn main() {
-    let file = std::fs::read("E:/Metaphor/base_cpk/COMMON/model/character/0001/c_0001_001_B.GFS").unwrap();
+    let path = std::env::args().nth(1)
+        .unwrap_or("E:/Metaphor/base_cpk/COMMON/model/character/0001/c_0001_001_B.GFS".to_owned());
+    let file = std::fs::read(path).unwrap();
     let factory = StreamFactory::new(Global);
     let mut stream = factory.read_from_memory(&file).unwrap();
-    let mesh = unsafe { Mesh::<GfdAllocator>::stream_read(&mut stream, &mut SerializationSingleAllocator::new(GfdAllocator)).unwrap().into_raw().as_ref() };
+    let mesh = MeshFile::<Global>::stream_read(&mut stream, &mut SerializationSingleAllocator::new(Global)).unwrap().into_raw();
     println!("{:?}", mesh);
     /*
     let mut array = ItemArray::<u32, Global>::new(Global);